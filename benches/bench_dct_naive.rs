@@ -79,6 +79,15 @@ fn dct2_naive_02(b: &mut Bencher) {
 fn dct2_naive_01(b: &mut Bencher) {
     bench_dct2_naive(b, 1);
 }
+// medium sizes, where the O(n^2) products dominate
+#[bench]
+fn dct2_naive_0101(b: &mut Bencher) {
+    bench_dct2_naive(b, 101);
+}
+#[bench]
+fn dct2_naive_0499(b: &mut Bencher) {
+    bench_dct2_naive(b, 499);
+}
 
 /// Times just the DCT3 execution (not allocation and pre-calculation)
 /// for a given length
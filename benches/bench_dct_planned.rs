@@ -95,3 +95,84 @@ fn dct4_planned_0999999(b: &mut Bencher) {
 fn dct4_planned_1000000(b: &mut Bencher) {
     bench_dct4_planned(b, 1000000);
 }
+
+/// Times just the 2D DCT2 execution (not allocation and pre-calculation)
+/// for a given side length
+fn bench_dct2_2d_square_planned(b: &mut Bencher, len: usize) {
+    use rustdct::RequiredScratch;
+
+    let mut planner = rustdct::DctPlanner::new();
+    let dct = rustdct::dct2d::Dct2dSquare::new(planner.plan_dct2(len));
+
+    let mut image = vec![0_f32; len * len];
+    let mut scratch = vec![0_f32; dct.get_scratch_len()];
+    b.iter(|| {
+        dct.process_dct2_2d_with_scratch(&mut image, &mut scratch);
+    });
+}
+
+#[bench]
+fn dct2_2d_square_planned_0256(b: &mut Bencher) {
+    bench_dct2_2d_square_planned(b, 256);
+}
+#[bench]
+fn dct2_2d_square_planned_2048(b: &mut Bencher) {
+    bench_dct2_2d_square_planned(b, 2048);
+}
+
+/// Times the fused JPEG encoder kernel on 1024 blocks, which is a 256x256 image
+#[bench]
+fn jpeg8x8_forward_fused(b: &mut Bencher) {
+    let jpeg = rustdct::dct2d::Jpeg8x8Forward::new(&[16; 64]);
+
+    let blocks: Vec<f32> = (0..1024 * 64).map(|i| (i % 255) as f32 - 128.0).collect();
+    let mut coefficients = vec![0_i16; blocks.len()];
+    b.iter(|| {
+        jpeg.process_blocks(&blocks, &mut coefficients);
+    });
+}
+
+/// Times the same work as `jpeg8x8_forward_fused`, done with a planned 8-point DCT2 on each row and column of each
+/// block, followed by a separate quantization and zigzag pass
+#[bench]
+fn jpeg8x8_forward_row_column(b: &mut Bencher) {
+    use rustdct::dct2d::JPEG_ZIGZAG;
+
+    let mut planner = rustdct::DctPlanner::new();
+    let dct = planner.plan_dct2(8);
+    let multipliers: Vec<f32> = (0..64)
+        .map(|natural| {
+            let c = |index: usize| if index == 0 { 0.5f32.sqrt() } else { 1.0 };
+            c(natural / 8) * c(natural % 8) / 4.0 / 16.0
+        })
+        .collect();
+
+    let blocks: Vec<f32> = (0..1024 * 64).map(|i| (i % 255) as f32 - 128.0).collect();
+    let mut buffer = blocks.clone();
+    let mut coefficients = vec![0_i16; blocks.len()];
+    let mut scratch = vec![0_f32; dct.get_scratch_len()];
+    b.iter(|| {
+        buffer.copy_from_slice(&blocks);
+        for (block, output) in buffer
+            .chunks_exact_mut(64)
+            .zip(coefficients.chunks_exact_mut(64))
+        {
+            for row in block.chunks_exact_mut(8) {
+                dct.process_dct2_with_scratch(row, &mut scratch);
+            }
+            let mut column = [0_f32; 8];
+            for x in 0..8 {
+                for (y, element) in column.iter_mut().enumerate() {
+                    *element = block[y * 8 + x];
+                }
+                dct.process_dct2_with_scratch(&mut column, &mut scratch);
+                for (y, element) in column.iter().enumerate() {
+                    block[y * 8 + x] = *element;
+                }
+            }
+            for (position, natural) in JPEG_ZIGZAG.iter().enumerate() {
+                output[position] = (block[*natural] * multipliers[*natural]).round() as i16;
+            }
+        }
+    });
+}
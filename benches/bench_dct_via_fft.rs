@@ -8,8 +8,12 @@ use rustdct::algorithm::*;
 use rustdct::mdct::{window_fn, Mdct, MdctViaDct4};
 use rustdct::rustfft::FftPlanner;
 use rustdct::DctPlanner;
-use rustdct::{algorithm::type2and3_butterflies::*, RequiredScratch};
-use rustdct::{Dct1, Dct2, Dct3, Dct4, Dst6, Dst7, TransformType2And3};
+use rustdct::{
+    algorithm::type2and3_butterflies::*, algorithm::type4_butterflies::*, RequiredScratch,
+};
+use rustdct::{
+    Dct1, Dct2, Dct3, Dct4, Dct8, Dst4, Dst6, Dst7, Dst8, TransformType2And3, TransformType4,
+};
 
 use test::Bencher;
 
@@ -47,6 +51,51 @@ fn dct1_fft_010(b: &mut Bencher) {
     bench_dct1_fft(b, 10);
 }
 
+/// Times the DCT1 via a real FFT of size `2 * (len - 1)`, computed with a complex FFT of size `len - 1`, for a given length
+fn bench_dct1_real_fft(b: &mut Bencher, len: usize) {
+    let mut planner = FftPlanner::new();
+    let dct = Dct1ConvertToRealFft::new(planner.plan_fft_forward(len - 1));
+
+    let mut buffer = vec![0_f32; len];
+    let mut scratch = vec![0_f32; dct.get_scratch_len()];
+    b.iter(|| {
+        dct.process_dct1_with_scratch(&mut buffer, &mut scratch);
+    });
+}
+
+#[bench]
+fn dct1_real_fft_0129(b: &mut Bencher) {
+    bench_dct1_real_fft(b, 129);
+}
+#[bench]
+fn dct1_real_fft_0130(b: &mut Bencher) {
+    bench_dct1_real_fft(b, 130);
+}
+#[bench]
+fn dct1_real_fft_1025(b: &mut Bencher) {
+    bench_dct1_real_fft(b, 1025);
+}
+#[bench]
+fn dct1_real_fft_1026(b: &mut Bencher) {
+    bench_dct1_real_fft(b, 1026);
+}
+#[bench]
+fn dct1_fft_0129(b: &mut Bencher) {
+    bench_dct1_fft(b, 129);
+}
+#[bench]
+fn dct1_fft_0130(b: &mut Bencher) {
+    bench_dct1_fft(b, 130);
+}
+#[bench]
+fn dct1_fft_1025(b: &mut Bencher) {
+    bench_dct1_fft(b, 1025);
+}
+#[bench]
+fn dct1_fft_1026(b: &mut Bencher) {
+    bench_dct1_fft(b, 1026);
+}
+
 /// Times just the DCT2 execution (not allocation and pre-calculation)
 /// for a given length
 fn bench_dct2_fft(b: &mut Bencher, len: usize) {
@@ -85,6 +134,93 @@ fn dct2_fft_01(b: &mut Bencher) {
     bench_dct2_fft(b, 1);
 }
 
+/// Times the DCT2 and DCT3 via a half-size complex FFT, for a given even length
+fn bench_dct2_real_fft(b: &mut Bencher, len: usize) {
+    let mut planner = FftPlanner::new();
+    let dct = Type2And3ConvertToRealFft::new(planner.plan_fft_forward(len / 2));
+
+    let mut buffer = vec![0_f32; len];
+    let mut scratch = vec![0_f32; dct.get_scratch_len()];
+    b.iter(|| {
+        dct.process_dct2_with_scratch(&mut buffer, &mut scratch);
+    });
+}
+fn bench_dct3_real_fft(b: &mut Bencher, len: usize) {
+    let mut planner = FftPlanner::new();
+    let dct = Type2And3ConvertToRealFft::new(planner.plan_fft_forward(len / 2));
+
+    let mut buffer = vec![0_f32; len];
+    let mut scratch = vec![0_f32; dct.get_scratch_len()];
+    b.iter(|| {
+        dct.process_dct3_with_scratch(&mut buffer, &mut scratch);
+    });
+}
+
+#[bench]
+fn dct2_fft_0006(b: &mut Bencher) {
+    bench_dct2_fft(b, 6);
+}
+#[bench]
+fn dct2_fft_0010(b: &mut Bencher) {
+    bench_dct2_fft(b, 10);
+}
+#[bench]
+fn dct2_fft_0012(b: &mut Bencher) {
+    bench_dct2_fft(b, 12);
+}
+#[bench]
+fn dct2_fft_0024(b: &mut Bencher) {
+    bench_dct2_fft(b, 24);
+}
+#[bench]
+fn dct2_fft_0100(b: &mut Bencher) {
+    bench_dct2_fft(b, 100);
+}
+#[bench]
+fn dct2_fft_1000(b: &mut Bencher) {
+    bench_dct2_fft(b, 1000);
+}
+#[bench]
+fn dct2_fft_6000(b: &mut Bencher) {
+    bench_dct2_fft(b, 6000);
+}
+#[bench]
+fn dct2_real_fft_0006(b: &mut Bencher) {
+    bench_dct2_real_fft(b, 6);
+}
+#[bench]
+fn dct2_real_fft_0010(b: &mut Bencher) {
+    bench_dct2_real_fft(b, 10);
+}
+#[bench]
+fn dct2_real_fft_0012(b: &mut Bencher) {
+    bench_dct2_real_fft(b, 12);
+}
+#[bench]
+fn dct2_real_fft_0024(b: &mut Bencher) {
+    bench_dct2_real_fft(b, 24);
+}
+#[bench]
+fn dct2_real_fft_0100(b: &mut Bencher) {
+    bench_dct2_real_fft(b, 100);
+}
+#[bench]
+fn dct2_real_fft_1000(b: &mut Bencher) {
+    bench_dct2_real_fft(b, 1000);
+}
+#[bench]
+fn dct2_real_fft_6000(b: &mut Bencher) {
+    bench_dct2_real_fft(b, 6000);
+}
+#[bench]
+fn dct3_fft_1000(b: &mut Bencher) {
+    bench_dct3_fft(b, 1000);
+}
+#[bench]
+fn dct3_real_fft_1000(b: &mut Bencher) {
+    bench_dct3_real_fft(b, 1000);
+}
+
 /// Times just the DCT2 execution (not allocation and pre-calculation)
 /// for a given length
 fn bench_dct2_split(b: &mut Bencher, len: usize) {
@@ -187,6 +323,118 @@ fn dct2_power2_fft_065536(b: &mut Bencher) {
     bench_dct2_fft(b, 65536);
 }
 
+/// Times just the DCT2 execution (not allocation and pre-calculation)
+/// for a given odd length, using planned DCT6 and DCT8 instances for the halves
+fn bench_dct2_type6and8(b: &mut Bencher, len: usize) {
+    let mut planner = DctPlanner::new();
+    let dct = Type2And3ConvertToType6And8::new(
+        planner.plan_dct6(len / 2 + 1),
+        planner.plan_dct8(len / 2),
+    );
+
+    let mut buffer = vec![0_f32; len];
+    let mut scratch = vec![0_f32; dct.get_scratch_len()];
+    b.iter(|| {
+        dct.process_dct2_with_scratch(&mut buffer, &mut scratch);
+    });
+}
+#[bench]
+fn dct2_odd_type6and8_0005(b: &mut Bencher) {
+    bench_dct2_type6and8(b, 5);
+}
+#[bench]
+fn dct2_odd_type6and8_0007(b: &mut Bencher) {
+    bench_dct2_type6and8(b, 7);
+}
+#[bench]
+fn dct2_odd_type6and8_0009(b: &mut Bencher) {
+    bench_dct2_type6and8(b, 9);
+}
+#[bench]
+fn dct2_odd_type6and8_0011(b: &mut Bencher) {
+    bench_dct2_type6and8(b, 11);
+}
+#[bench]
+fn dct2_odd_type6and8_0013(b: &mut Bencher) {
+    bench_dct2_type6and8(b, 13);
+}
+#[bench]
+fn dct2_odd_type6and8_0015(b: &mut Bencher) {
+    bench_dct2_type6and8(b, 15);
+}
+#[bench]
+fn dct2_odd_type6and8_0017(b: &mut Bencher) {
+    bench_dct2_type6and8(b, 17);
+}
+#[bench]
+fn dct2_odd_type6and8_0021(b: &mut Bencher) {
+    bench_dct2_type6and8(b, 21);
+}
+#[bench]
+fn dct2_odd_type6and8_0025(b: &mut Bencher) {
+    bench_dct2_type6and8(b, 25);
+}
+#[bench]
+fn dct2_odd_type6and8_0031(b: &mut Bencher) {
+    bench_dct2_type6and8(b, 31);
+}
+#[bench]
+fn dct2_odd_type6and8_0045(b: &mut Bencher) {
+    bench_dct2_type6and8(b, 45);
+}
+#[bench]
+fn dct2_odd_type6and8_0063(b: &mut Bencher) {
+    bench_dct2_type6and8(b, 63);
+}
+#[bench]
+fn dct2_odd_fft_0005(b: &mut Bencher) {
+    bench_dct2_fft(b, 5);
+}
+#[bench]
+fn dct2_odd_fft_0007(b: &mut Bencher) {
+    bench_dct2_fft(b, 7);
+}
+#[bench]
+fn dct2_odd_fft_0009(b: &mut Bencher) {
+    bench_dct2_fft(b, 9);
+}
+#[bench]
+fn dct2_odd_fft_0011(b: &mut Bencher) {
+    bench_dct2_fft(b, 11);
+}
+#[bench]
+fn dct2_odd_fft_0013(b: &mut Bencher) {
+    bench_dct2_fft(b, 13);
+}
+#[bench]
+fn dct2_odd_fft_0015(b: &mut Bencher) {
+    bench_dct2_fft(b, 15);
+}
+#[bench]
+fn dct2_odd_fft_0017(b: &mut Bencher) {
+    bench_dct2_fft(b, 17);
+}
+#[bench]
+fn dct2_odd_fft_0021(b: &mut Bencher) {
+    bench_dct2_fft(b, 21);
+}
+#[bench]
+fn dct2_odd_fft_0025(b: &mut Bencher) {
+    bench_dct2_fft(b, 25);
+}
+#[bench]
+fn dct2_odd_fft_0031(b: &mut Bencher) {
+    bench_dct2_fft(b, 31);
+}
+#[bench]
+fn dct2_odd_fft_0045(b: &mut Bencher) {
+    bench_dct2_fft(b, 45);
+}
+#[bench]
+fn dct2_odd_fft_0063(b: &mut Bencher) {
+    bench_dct2_fft(b, 63);
+}
+
 /// Times just the DCT3 execution (not allocation and pre-calculation)
 /// for a given length
 fn bench_dct3_fft(b: &mut Bencher, len: usize) {
@@ -342,11 +590,130 @@ fn dct4_even_via_dct3_10(b: &mut Bencher) {
     bench_dct4_via_dct3(b, 10);
 }
 
+#[bench]
+fn dct4_even_via_dct3_16(b: &mut Bencher) {
+    bench_dct4_via_dct3(b, 16);
+}
+#[bench]
+fn dct4_even_via_dct3_32(b: &mut Bencher) {
+    bench_dct4_via_dct3(b, 32);
+}
+
 #[bench]
 fn dct4_even_via_dct3_1000000(b: &mut Bencher) {
     bench_dct4_via_dct3(b, 1000000);
 }
 
+/// Times just the DST4 execution (not allocation and pre-calculation)
+/// for a given length. Compare with the DCT4 benches above: both transforms have the same cost
+fn bench_dst4_via_dct3(b: &mut Bencher, len: usize) {
+    let mut planner = DctPlanner::new();
+    let inner_dct3 = planner.plan_dct3(len / 2);
+    let dst = Type4ConvertToType3Even::new(inner_dct3);
+
+    let mut buffer = vec![0_f32; len];
+    let mut scratch = vec![0_f32; dst.get_scratch_len()];
+    b.iter(|| {
+        dst.process_dst4_with_scratch(&mut buffer, &mut scratch);
+    });
+}
+
+#[bench]
+fn dst4_even_via_dct3_02(b: &mut Bencher) {
+    bench_dst4_via_dct3(b, 2);
+}
+#[bench]
+fn dst4_even_via_dct3_04(b: &mut Bencher) {
+    bench_dst4_via_dct3(b, 4);
+}
+#[bench]
+fn dst4_even_via_dct3_06(b: &mut Bencher) {
+    bench_dst4_via_dct3(b, 6);
+}
+#[bench]
+fn dst4_even_via_dct3_08(b: &mut Bencher) {
+    bench_dst4_via_dct3(b, 8);
+}
+#[bench]
+fn dst4_even_via_dct3_10(b: &mut Bencher) {
+    bench_dst4_via_dct3(b, 10);
+}
+
+#[bench]
+fn dst4_even_via_dct3_16(b: &mut Bencher) {
+    bench_dst4_via_dct3(b, 16);
+}
+#[bench]
+fn dst4_even_via_dct3_32(b: &mut Bencher) {
+    bench_dst4_via_dct3(b, 32);
+}
+
+#[bench]
+fn dst4_even_via_dct3_1000000(b: &mut Bencher) {
+    bench_dst4_via_dct3(b, 1000000);
+}
+
+/// Times just the DCT4 execution (not allocation and pre-calculation)
+/// for a given length, splitting into half-size DCT4s all the way down to the butterflies
+fn bench_dct4_split_radix(b: &mut Bencher, len: usize) {
+    fn plan_split_radix(planner: &mut DctPlanner<f32>, len: usize) -> Arc<dyn TransformType4<f32>> {
+        if len <= 32 {
+            planner.plan_dct4(len)
+        } else {
+            Arc::new(Type4SplitRadix::new(plan_split_radix(planner, len / 2)))
+        }
+    }
+
+    let mut planner = DctPlanner::new();
+    let dct = plan_split_radix(&mut planner, len);
+
+    let mut buffer = vec![0_f32; len];
+    let mut scratch = vec![0_f32; dct.get_scratch_len()];
+    b.iter(|| {
+        dct.process_dct4_with_scratch(&mut buffer, &mut scratch);
+    });
+}
+
+#[bench]
+fn dct4_power2_split_0064(b: &mut Bencher) {
+    bench_dct4_split_radix(b, 64);
+}
+#[bench]
+fn dct4_power2_split_0256(b: &mut Bencher) {
+    bench_dct4_split_radix(b, 256);
+}
+#[bench]
+fn dct4_power2_split_065536(b: &mut Bencher) {
+    bench_dct4_split_radix(b, 65536);
+}
+
+/// Times just the DCT4 execution (not allocation and pre-calculation)
+/// for a given butterfly
+fn bench_dct4_butterfly<D: Dct4<f32> + RequiredScratch>(b: &mut Bencher, dct: D) {
+    let mut buffer = vec![0_f32; dct.len()];
+    let mut scratch = vec![0_f32; dct.get_scratch_len()];
+    b.iter(|| {
+        dct.process_dct4_with_scratch(&mut buffer, &mut scratch);
+    });
+}
+
+#[bench]
+fn dct4_butterfly_04(b: &mut Bencher) {
+    bench_dct4_butterfly(b, Type4Butterfly4::new());
+}
+#[bench]
+fn dct4_butterfly_08(b: &mut Bencher) {
+    bench_dct4_butterfly(b, Type4Butterfly8::new());
+}
+#[bench]
+fn dct4_butterfly_16(b: &mut Bencher) {
+    bench_dct4_butterfly(b, Type4Butterfly16::new());
+}
+#[bench]
+fn dct4_butterfly_32(b: &mut Bencher) {
+    bench_dct4_butterfly(b, Type4Butterfly32::new());
+}
+
 /// Times just the DCT4 execution (not allocation and pre-calculation)
 /// for a given length
 fn bench_dct4_via_fft_odd(b: &mut Bencher, len: usize) {
@@ -732,3 +1099,69 @@ fn dst7_fft_38(b: &mut Bencher) {
 fn dst7_fft_39(b: &mut Bencher) {
     bench_dst7_fft(b, 39);
 }
+
+/// Times just the DCT8 execution (not allocation and pre-calculation)
+/// for a given length
+fn bench_dct8_type2and3(b: &mut Bencher, len: usize) {
+    let mut planner = DctPlanner::new();
+    let dct = Dct8ConvertToType2And3::new(planner.plan_dct2(len * 2 + 1));
+
+    let mut buffer = vec![0_f32; len];
+    let mut scratch = vec![0_f32; dct.get_scratch_len()];
+    b.iter(|| {
+        dct.process_dct8_with_scratch(&mut buffer, &mut scratch);
+    });
+}
+#[bench]
+fn dct8_type2and3_8(b: &mut Bencher) {
+    bench_dct8_type2and3(b, 8);
+}
+#[bench]
+fn dct8_type2and3_16(b: &mut Bencher) {
+    bench_dct8_type2and3(b, 16);
+}
+#[bench]
+fn dct8_type2and3_32(b: &mut Bencher) {
+    bench_dct8_type2and3(b, 32);
+}
+#[bench]
+fn dct8_type2and3_64(b: &mut Bencher) {
+    bench_dct8_type2and3(b, 64);
+}
+#[bench]
+fn dct8_type2and3_100(b: &mut Bencher) {
+    bench_dct8_type2and3(b, 100);
+}
+
+/// Times just the DST8 execution (not allocation and pre-calculation)
+/// for a given length
+fn bench_dst8_type2and3(b: &mut Bencher, len: usize) {
+    let mut planner = DctPlanner::new();
+    let dst = Dst8ConvertToType2And3::new(planner.plan_dst2(len * 2 - 1));
+
+    let mut buffer = vec![0_f32; len];
+    let mut scratch = vec![0_f32; dst.get_scratch_len()];
+    b.iter(|| {
+        dst.process_dst8_with_scratch(&mut buffer, &mut scratch);
+    });
+}
+#[bench]
+fn dst8_type2and3_8(b: &mut Bencher) {
+    bench_dst8_type2and3(b, 8);
+}
+#[bench]
+fn dst8_type2and3_16(b: &mut Bencher) {
+    bench_dst8_type2and3(b, 16);
+}
+#[bench]
+fn dst8_type2and3_32(b: &mut Bencher) {
+    bench_dst8_type2and3(b, 32);
+}
+#[bench]
+fn dst8_type2and3_64(b: &mut Bencher) {
+    bench_dst8_type2and3(b, 64);
+}
+#[bench]
+fn dst8_type2and3_100(b: &mut Bencher) {
+    bench_dst8_type2and3(b, 100);
+}
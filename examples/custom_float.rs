@@ -0,0 +1,154 @@
+//! This example shows how to use RustDCT with a custom numeric type, like a software float.
+//!
+//! Any type that implements RustFFT's `FftNum` trait and num-traits' `FloatConst` trait automatically implements
+//! `DctNum`. `FftNum` is implemented for every type that implements `Copy + FromPrimitive + Signed + Sync + Send + Debug +
+//! 'static`, so together with the constants of `FloatConst`, that's all a custom type needs to provide.
+//!
+//! The one thing to be careful about is `FromPrimitive::from_f64`. Its default implementation truncates through an
+//! integer, which would turn every twiddle factor into 0 or 1. RustDCT computes all of its constants and twiddle
+//! factors with `from_f64`, so custom types must override it to round to the nearest representable value.
+//!
+//! To run:
+//!   `cargo run --example custom_float`
+
+use std::fmt;
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+use rustdct::num_traits::{FloatConst, FromPrimitive, Num, One, Signed, Zero};
+use rustdct::rustfft::Length;
+use rustdct::{DctPlanner, Normalization};
+
+/// A stand-in for a software float type. A real software float would implement its arithmetic with integer
+/// operations, but the trait implementations needed by RustDCT would look the same.
+#[derive(Copy, Clone, PartialEq, PartialOrd)]
+struct SoftFloat(f64);
+
+impl fmt::Debug for SoftFloat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.4}", self.0)
+    }
+}
+
+macro_rules! impl_binary_op {
+    ($trait_name:ident, $fn_name:ident, $op:tt) => {
+        impl $trait_name for SoftFloat {
+            type Output = Self;
+            fn $fn_name(self, other: Self) -> Self {
+                SoftFloat(self.0 $op other.0)
+            }
+        }
+    };
+}
+impl_binary_op!(Add, add, +);
+impl_binary_op!(Sub, sub, -);
+impl_binary_op!(Mul, mul, *);
+impl_binary_op!(Div, div, /);
+impl_binary_op!(Rem, rem, %);
+
+impl Neg for SoftFloat {
+    type Output = Self;
+    fn neg(self) -> Self {
+        SoftFloat(-self.0)
+    }
+}
+
+impl Zero for SoftFloat {
+    fn zero() -> Self {
+        SoftFloat(0.0)
+    }
+    fn is_zero(&self) -> bool {
+        self.0 == 0.0
+    }
+}
+impl One for SoftFloat {
+    fn one() -> Self {
+        SoftFloat(1.0)
+    }
+}
+impl Num for SoftFloat {
+    type FromStrRadixErr = <f64 as Num>::FromStrRadixErr;
+    fn from_str_radix(string: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
+        f64::from_str_radix(string, radix).map(SoftFloat)
+    }
+}
+impl Signed for SoftFloat {
+    fn abs(&self) -> Self {
+        SoftFloat(self.0.abs())
+    }
+    fn abs_sub(&self, other: &Self) -> Self {
+        if self.0 <= other.0 {
+            Self::zero()
+        } else {
+            *self - *other
+        }
+    }
+    fn signum(&self) -> Self {
+        SoftFloat(self.0.signum())
+    }
+    fn is_positive(&self) -> bool {
+        self.0 > 0.0
+    }
+    fn is_negative(&self) -> bool {
+        self.0 < 0.0
+    }
+}
+impl FromPrimitive for SoftFloat {
+    fn from_i64(n: i64) -> Option<Self> {
+        Some(SoftFloat(n as f64))
+    }
+    fn from_u64(n: u64) -> Option<Self> {
+        Some(SoftFloat(n as f64))
+    }
+    // Required: the default implementation would truncate the value to an integer
+    fn from_f64(n: f64) -> Option<Self> {
+        Some(SoftFloat(n))
+    }
+}
+
+macro_rules! impl_float_const {
+    ($($name:ident),*) => {
+        impl FloatConst for SoftFloat {
+            $(
+                fn $name() -> Self {
+                    SoftFloat(std::f64::consts::$name)
+                }
+            )*
+        }
+    };
+}
+impl_float_const!(
+    E,
+    FRAC_1_PI,
+    FRAC_1_SQRT_2,
+    FRAC_2_PI,
+    FRAC_2_SQRT_PI,
+    FRAC_PI_2,
+    FRAC_PI_3,
+    FRAC_PI_4,
+    FRAC_PI_6,
+    FRAC_PI_8,
+    LN_10,
+    LN_2,
+    LOG10_E,
+    LOG2_E,
+    PI,
+    SQRT_2
+);
+
+fn main() {
+    let mut planner = DctPlanner::new();
+    let dct = planner.plan_dct2_pair(12, Normalization::Inverse);
+
+    let input: Vec<SoftFloat> = (0..dct.len()).map(|i| SoftFloat(i as f64)).collect();
+
+    let mut buffer = input.clone();
+    dct.forward(&mut buffer);
+    println!("DCT2:      {:?}", buffer);
+
+    dct.inverse(&mut buffer);
+    println!("Roundtrip: {:?}", buffer);
+
+    for (expected, actual) in input.iter().zip(buffer.iter()) {
+        assert!((expected.0 - actual.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,19 @@
+//! Measures the time taken by a few planned transforms, using the stable measurement loop in `rustdct::bench`.
+//!
+//! To run:
+//!   `cargo run --release --features bench --example measure_plans`
+
+use rustdct::bench::measure_process_time;
+use rustdct::DctPlanner;
+
+fn main() {
+    let mut planner = DctPlanner::<f32>::new();
+    let iterations = 1000;
+
+    println!("{:>6} {:>14} {:>14}", "len", "dct2 median", "dct4 median");
+    for &len in &[16, 100, 128, 1000, 1024, 4096] {
+        let dct2 = measure_process_time(&planner.plan_dct2(len), iterations);
+        let dct4 = measure_process_time(&planner.plan_dct4(len), iterations);
+        println!("{:>6} {:>14?} {:>14?}", len, dct2.median, dct4.median);
+    }
+}
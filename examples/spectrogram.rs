@@ -0,0 +1,48 @@
+//! This example computes a DCT2 spectrogram of a chirp, and prints the strongest coefficient of each frame.
+//!
+//! `CosineSpectrogram` splits the signal into overlapping frames, windows each frame, and computes its DCT2 with a
+//! cached plan. As the chirp's frequency rises, the strongest coefficient moves to higher indexes.
+//!
+//! To run:
+//!   `cargo run --example spectrogram`
+
+use std::f64::consts::PI;
+
+use rustdct::mdct::window_fn;
+use rustdct::pipeline::CosineSpectrogram;
+
+fn main() {
+    let sample_rate = 8000.0;
+    let signal: Vec<f64> = (0..8000)
+        .map(|i| {
+            // a chirp from 100 Hz to 2100 Hz over one second
+            let t = i as f64 / sample_rate;
+            (2.0 * PI * (100.0 * t + 1000.0 * t * t)).sin()
+        })
+        .collect();
+
+    let frame_len = 256;
+    let spectrogram = CosineSpectrogram::new(frame_len, frame_len / 2, window_fn::mp3);
+    let frames = spectrogram.process(&signal);
+
+    for (frame_index, frame) in frames.iter().enumerate().step_by(8) {
+        let (peak, _) =
+            frame
+                .iter()
+                .enumerate()
+                .fold((0, 0.0), |(best, best_magnitude), (k, value)| {
+                    if value.abs() > best_magnitude {
+                        (k, value.abs())
+                    } else {
+                        (best, best_magnitude)
+                    }
+                });
+
+        // DCT2 coefficient k corresponds to a frequency of k / 2 cycles per frame
+        let frequency = peak as f64 * sample_rate / (2 * frame_len) as f64;
+        println!(
+            "frame {:3}: strongest coefficient {:3} (~{:.0} Hz)",
+            frame_index, peak, frequency
+        );
+    }
+}
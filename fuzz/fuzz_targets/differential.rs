@@ -0,0 +1,52 @@
+#![no_main]
+
+//! Picks a transform type, a size, and an input from the fuzzer's data, and checks the planned transform against the
+//! naive algorithm. Run with `cargo +nightly fuzz run differential` from the crate root.
+
+use libfuzzer_sys::fuzz_target;
+use rustdct::differential::{check_planned, min_len};
+use rustdct::{DctPlanner, DctType};
+
+const ALL_TYPES: [DctType; 16] = [
+    DctType::Dct1,
+    DctType::Dct2,
+    DctType::Dct3,
+    DctType::Dct4,
+    DctType::Dct5,
+    DctType::Dct6,
+    DctType::Dct7,
+    DctType::Dct8,
+    DctType::Dst1,
+    DctType::Dst2,
+    DctType::Dst3,
+    DctType::Dst4,
+    DctType::Dst5,
+    DctType::Dst6,
+    DctType::Dst7,
+    DctType::Dst8,
+];
+
+// Sizes are capped so that the naive O(n^2) reference stays fast enough to fuzz with
+const MAX_LEN: usize = 2048;
+
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 3 {
+        return;
+    }
+    let kind = ALL_TYPES[data[0] as usize % ALL_TYPES.len()];
+    let len = (u16::from_le_bytes([data[1], data[2]]) as usize % MAX_LEN).max(min_len(kind));
+
+    // fill the input from the remaining bytes, mapped to [-1, 1], and repeat them if there aren't enough
+    let bytes = &data[3..];
+    let input: Vec<f64> = (0..len)
+        .map(|i| match bytes.get(i % bytes.len().max(1)) {
+            Some(&byte) => byte as f64 / 127.5 - 1.0,
+            None => 0.0,
+        })
+        .collect();
+
+    let mut planner = DctPlanner::new();
+    if let Err(mismatch) = check_planned(&mut planner, kind, &input) {
+        panic!("{}", mismatch);
+    }
+});
@@ -0,0 +1,221 @@
+use std::sync::Arc;
+
+use rustfft::Length;
+
+use crate::common::Cancelled;
+use crate::ScratchLayout;
+use crate::{Dct2, Dct3, Dct4, DctNum, Dst2, Dst3, Dst4, RequiredScratch};
+use crate::{MemoryUsage, MemoryVisitor, PlanIdentity, PlanKey, PlanMemory};
+use crate::{TransformType2And3, TransformType4};
+
+/// Adapter that computes the DST2, DST3, and DST4 with an inner plan that only computes the DCT2, DCT3, and DCT4.
+///
+/// Each DST is a DCT of the same size, with some signs flipped and one of the buffers reversed:
+///
+/// - The DST2 negates the odd-indexed inputs, computes a DCT2, and reverses the output.
+/// - The DST3 reverses the input, computes a DCT3, and negates the odd-indexed outputs.
+/// - The DST4 reverses the input, computes a DCT4, and negates the odd-indexed outputs.
+///
+/// So an algorithm only needs to implement the DCT side: If the inner plan implements `Dct2` and `Dct3`, this
+/// implements `TransformType2And3`, and if the inner plan implements `Dct4`, this implements `TransformType4`. The DCTs
+/// are forwarded to the inner plan unchanged. The identities are exact, so the DSTs are as accurate as the inner DCTs,
+/// and only add a O(n) pass over the buffer.
+///
+/// The adapter reports the inner plan's `plan_key()`. Since the adapter computes the whole family of transforms, an
+/// inner plan that's only used through this adapter should report the key of the whole family, like
+/// `PlanKind::Type2And3` or `PlanKind::Type4`.
+///
+/// ~~~
+/// // Computes a DST4 of size 1234 with a plan that only has to provide the DCT4
+/// use std::sync::Arc;
+/// use rustdct::algorithm::{DstViaDct, Type4Naive};
+/// use rustdct::Dst4;
+///
+/// let len = 1234;
+/// let dst = DstViaDct::new(Arc::new(Type4Naive::new(len)));
+///
+/// let mut buffer = vec![0f32; len];
+/// dst.process_dst4(&mut buffer);
+/// ~~~
+pub struct DstViaDct<D: ?Sized> {
+    inner: Arc<D>,
+}
+
+impl<D: ?Sized> DstViaDct<D> {
+    /// Creates a new adapter that will compute the DSTs with `inner`, and process signals of length `inner.len()`
+    pub fn new(inner: Arc<D>) -> Self {
+        Self { inner }
+    }
+}
+
+// Negates every odd-indexed element of `buffer`
+fn negate_odd<T: DctNum>(buffer: &mut [T]) {
+    for element in buffer.iter_mut().skip(1).step_by(2) {
+        *element = -*element;
+    }
+}
+
+impl<T: DctNum, D: Dct2<T> + ?Sized> Dct2<T> for DstViaDct<D> {
+    // this adapter computes the same outputs as its inner plan, and doesn't have any data of its own, so every
+    // transform trait reports the inner plan's memory and key
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        self.inner.as_memory_usage()
+    }
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        self.inner.known_plan_key()
+    }
+    fn process_dct2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        self.inner.process_dct2_with_scratch(buffer, scratch);
+    }
+    fn process_dct2_from(&self, input: &[T], output: &mut [T], scratch: &mut [T]) {
+        self.inner.process_dct2_from(input, output, scratch);
+    }
+    fn process_dct2_two(&self, a: &mut [T], b: &mut [T], scratch: &mut [T]) {
+        self.inner.process_dct2_two(a, b, scratch);
+    }
+    fn process_dct2_cancellable(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        should_continue: &mut dyn FnMut() -> bool,
+    ) -> Result<(), Cancelled> {
+        self.inner
+            .process_dct2_cancellable(buffer, scratch, should_continue)
+    }
+}
+impl<T: DctNum, D: Dct2<T> + ?Sized> Dst2<T> for DstViaDct<D> {
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        self.inner.as_memory_usage()
+    }
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        self.inner.known_plan_key()
+    }
+    fn process_dst2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        negate_odd(buffer);
+        self.inner.process_dct2_with_scratch(buffer, scratch);
+        buffer.reverse();
+    }
+    fn process_dst2_cancellable(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        should_continue: &mut dyn FnMut() -> bool,
+    ) -> Result<(), Cancelled> {
+        negate_odd(buffer);
+        self.inner
+            .process_dct2_cancellable(buffer, scratch, should_continue)?;
+        buffer.reverse();
+        Ok(())
+    }
+}
+impl<T: DctNum, D: Dct3<T> + ?Sized> Dct3<T> for DstViaDct<D> {
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        self.inner.as_memory_usage()
+    }
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        self.inner.known_plan_key()
+    }
+    fn process_dct3_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        self.inner.process_dct3_with_scratch(buffer, scratch);
+    }
+    fn process_dct3_scaled(&self, buffer: &mut [T], scratch: &mut [T], scale: T) {
+        self.inner.process_dct3_scaled(buffer, scratch, scale);
+    }
+    fn process_dct3_from(&self, input: &[T], output: &mut [T], scratch: &mut [T]) {
+        self.inner.process_dct3_from(input, output, scratch);
+    }
+    fn process_dct3_cancellable(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        should_continue: &mut dyn FnMut() -> bool,
+    ) -> Result<(), Cancelled> {
+        self.inner
+            .process_dct3_cancellable(buffer, scratch, should_continue)
+    }
+}
+impl<T: DctNum, D: Dct3<T> + ?Sized> Dst3<T> for DstViaDct<D> {
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        self.inner.as_memory_usage()
+    }
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        self.inner.known_plan_key()
+    }
+    fn process_dst3_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        buffer.reverse();
+        self.inner.process_dct3_with_scratch(buffer, scratch);
+        negate_odd(buffer);
+    }
+    fn process_dst3_scaled(&self, buffer: &mut [T], scratch: &mut [T], scale: T) {
+        buffer.reverse();
+        self.inner.process_dct3_scaled(buffer, scratch, scale);
+        negate_odd(buffer);
+    }
+    fn process_dst3_cancellable(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        should_continue: &mut dyn FnMut() -> bool,
+    ) -> Result<(), Cancelled> {
+        buffer.reverse();
+        self.inner
+            .process_dct3_cancellable(buffer, scratch, should_continue)?;
+        negate_odd(buffer);
+        Ok(())
+    }
+}
+impl<T: DctNum, D: Dct2<T> + Dct3<T> + ?Sized> TransformType2And3<T> for DstViaDct<D> {}
+impl<T: DctNum, D: Dct4<T> + ?Sized> Dct4<T> for DstViaDct<D> {
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        self.inner.as_memory_usage()
+    }
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        self.inner.known_plan_key()
+    }
+    fn process_dct4_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        self.inner.process_dct4_with_scratch(buffer, scratch);
+    }
+}
+impl<T: DctNum, D: Dct4<T> + ?Sized> Dst4<T> for DstViaDct<D> {
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        self.inner.as_memory_usage()
+    }
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        self.inner.known_plan_key()
+    }
+    fn process_dst4_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        buffer.reverse();
+        self.inner.process_dct4_with_scratch(buffer, scratch);
+        negate_odd(buffer);
+    }
+}
+impl<T: DctNum, D: Dct4<T> + ?Sized> TransformType4<T> for DstViaDct<D> {}
+impl<T, D: PlanIdentity<T> + ?Sized> PlanIdentity<T> for DstViaDct<D> {
+    fn plan_key(&self) -> PlanKey<T> {
+        self.inner.plan_key()
+    }
+}
+impl<D: MemoryUsage + ?Sized> MemoryUsage for DstViaDct<D> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            inner_plans_bytes: visitor.inner_plan(&self.inner),
+            ..PlanMemory::default()
+        }
+    }
+}
+impl<D: RequiredScratch + ?Sized> RequiredScratch for DstViaDct<D> {
+    fn get_scratch_len(&self) -> usize {
+        self.inner.get_scratch_len()
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        self.inner.get_complex_scratch_len()
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        self.inner.scratch_layout()
+    }
+}
+impl<D: Length + ?Sized> Length for DstViaDct<D> {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
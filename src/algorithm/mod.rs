@@ -1,39 +1,70 @@
+mod dst_via_dct;
+mod naive_kernel;
+
+pub mod type1_butterflies;
 mod type1_convert_to_fft;
+mod type1_convert_to_real_fft;
 mod type1_naive;
 
+mod type2_output_order;
 pub mod type2and3_butterflies;
 mod type2and3_convert_to_fft;
+mod type2and3_convert_to_real_fft;
+mod type2and3_convert_to_type6and8;
+mod type2and3_dct3_convention;
+mod type2and3_dst_convention;
+mod type2and3_mixed_precision;
 mod type2and3_naive;
 mod type2and3_splitradix;
 
+pub mod type4_butterflies;
 mod type4_convert_to_fft;
 mod type4_convert_to_type3;
 mod type4_naive;
+mod type4_splitradix;
 
 mod type5_naive;
+pub mod type5to8_butterflies;
 mod type6and7_convert_to_fft;
+mod type6and7_convert_to_type2and3;
 mod type6and7_naive;
+mod type8_convert_to_type2and3;
 mod type8_naive;
 
+pub use self::dst_via_dct::DstViaDct;
+pub use self::naive_kernel::Accumulation;
+
 pub use self::type1_convert_to_fft::Dct1ConvertToFft;
 pub use self::type1_convert_to_fft::Dst1ConvertToFft;
+pub use self::type1_convert_to_real_fft::Dct1ConvertToRealFft;
 pub use self::type1_naive::Dct1Naive;
 pub use self::type1_naive::Dst1Naive;
 
+pub use self::type2_output_order::Dct2WithOutputOrder;
 pub use self::type2and3_convert_to_fft::Type2And3ConvertToFft;
+pub use self::type2and3_convert_to_real_fft::Type2And3ConvertToRealFft;
+pub use self::type2and3_convert_to_type6and8::Type2And3ConvertToType6And8;
+pub use self::type2and3_dct3_convention::Type2And3WithDct3Convention;
+pub use self::type2and3_dst_convention::Type2And3WithDstConvention;
+pub use self::type2and3_mixed_precision::Type2And3MixedPrecision;
 pub use self::type2and3_naive::Type2And3Naive;
 pub use self::type2and3_splitradix::Type2And3SplitRadix;
 
 pub use self::type4_convert_to_fft::Type4ConvertToFftOdd;
 pub use self::type4_convert_to_type3::Type4ConvertToType3Even;
 pub use self::type4_naive::Type4Naive;
+pub use self::type4_splitradix::Type4SplitRadix;
 
 pub use self::type5_naive::Dct5Naive;
 pub use self::type5_naive::Dst5Naive;
 
 pub use self::type6and7_convert_to_fft::Dst6And7ConvertToFft;
+pub use self::type6and7_convert_to_type2and3::Dct6And7ConvertToType2And3;
+pub use self::type6and7_convert_to_type2and3::Dst6And7ConvertToType2And3;
 pub use self::type6and7_naive::Dct6And7Naive;
 pub use self::type6and7_naive::Dst6And7Naive;
 
+pub use self::type8_convert_to_type2and3::Dct8ConvertToType2And3;
+pub use self::type8_convert_to_type2and3::Dst8ConvertToType2And3;
 pub use self::type8_naive::Dct8Naive;
 pub use self::type8_naive::Dst8Naive;
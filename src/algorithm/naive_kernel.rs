@@ -0,0 +1,204 @@
+use crate::DctNum;
+
+// The number of independent partial sums used by `twiddle_dot`
+const LANES: usize = 4;
+
+/// How the naive algorithms accumulate the `len` products that make up each output.
+///
+/// With `Simple` accumulation, the rounding error of each output grows in proportion to `len` in the worst case, which
+/// starts to matter for `f32` transforms of tens of thousands of elements. `Compensated` accumulation carries each
+/// addition's rounding error into the next addition, using Kahan summation, so the error of the sum stays close to
+/// the error of the individual products, independently of `len`. It's about 3x slower than `Simple` accumulation.
+///
+/// The fast algorithms don't have long sums to compensate: Each of their outputs is computed from a fixed number of
+/// terms at each level of recursion, so their error only grows in proportion to `log(len)`. For accuracy-critical
+/// uses, plan with [`DctPlanner::set_accumulation`](../struct.DctPlanner.html#method.set_accumulation), which makes
+/// every naive instance that the planner creates, including the ones below the cost model's crossover sizes, use the
+/// chosen accumulation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Accumulation {
+    /// Each output is summed in four interleaved partial sums, which are added together at the end. This is the
+    /// default.
+    Simple,
+    /// Each output is summed with Kahan summation.
+    Compensated,
+}
+
+// Computes the dot product of `input` with a row of a naive transform matrix, where the matrix row is described by
+// twiddle indexes that start at `twiddle_index` and advance by `twiddle_stride`, wrapping around at `twiddles.len()`.
+// `get_twiddle` extracts the real value to use from each twiddle, so that this works for both real and complex tables.
+//
+// The straightforward loop has two serial dependency chains: every multiply-add waits on the previous running sum,
+// and every twiddle index waits on the previous index. Instead, this splits the row into `LANES` interleaved lanes,
+// each with its own partial sum and its own twiddle index advancing by `LANES * twiddle_stride`. The lanes are
+// independent, so the CPU can overlap them.
+//
+// This is deliberately scalar code. Each product needs a twiddle loaded from its own wrapping index, so the loads
+// dominate, not the multiply-adds: An explicit SSE version of this loop, which gathered 4 twiddles into a register
+// and did the multiply-adds with vector instructions, was no faster on the `dct2_naive_0101` and `dct2_naive_0499`
+// benchmarks.
+//
+// With `Accumulation::Compensated`, the products are summed in order with Kahan summation instead.
+//
+// `twiddle_index` and `twiddle_stride` must both be less than `twiddles.len()`.
+#[inline(always)]
+pub fn twiddle_dot<T: DctNum, W: Copy, F: Fn(W) -> T>(
+    input: &[T],
+    twiddles: &[W],
+    twiddle_index: usize,
+    twiddle_stride: usize,
+    get_twiddle: F,
+    accumulation: Accumulation,
+) -> T {
+    let twiddle_len = twiddles.len();
+    let wrap = |index: usize| {
+        if index >= twiddle_len {
+            index - twiddle_len
+        } else {
+            index
+        }
+    };
+
+    if accumulation == Accumulation::Compensated {
+        let mut sum = CompensatedSum::new();
+        let mut index = twiddle_index;
+        for input_val in input {
+            sum.add(*input_val * get_twiddle(twiddles[index]));
+            index = wrap(index + twiddle_stride);
+        }
+        return sum.sum;
+    }
+
+    // short rows don't have enough work to make up for the extra setup, so just compute them directly
+    if input.len() < LANES * 2 {
+        let mut sum = T::zero();
+        let mut index = twiddle_index;
+        for input_val in input {
+            sum = sum + *input_val * get_twiddle(twiddles[index]);
+            index = wrap(index + twiddle_stride);
+        }
+        return sum;
+    }
+
+    // set up the starting index of each lane, and the distance each lane advances per block
+    let mut lane_indexes = [0; LANES];
+    let mut index = twiddle_index;
+    for lane_index in lane_indexes.iter_mut() {
+        *lane_index = index;
+        index = wrap(index + twiddle_stride);
+    }
+    let lane_stride = (twiddle_stride * LANES) % twiddle_len.max(1);
+
+    let mut sums = [T::zero(); LANES];
+    let mut chunks = input.chunks_exact(LANES);
+    for chunk in chunks.by_ref() {
+        for ((sum, lane_index), input_val) in
+            sums.iter_mut().zip(lane_indexes.iter_mut()).zip(chunk)
+        {
+            *sum = *sum + *input_val * get_twiddle(twiddles[*lane_index]);
+            *lane_index = wrap(*lane_index + lane_stride);
+        }
+    }
+
+    // the lanes' next indexes line up with the remainder elements, in order
+    let mut remainder_sum = T::zero();
+    for (input_val, lane_index) in chunks.remainder().iter().zip(lane_indexes.iter()) {
+        remainder_sum = remainder_sum + *input_val * get_twiddle(twiddles[*lane_index]);
+    }
+
+    (sums[0] + sums[2]) + (sums[1] + sums[3]) + remainder_sum
+}
+
+// Computes the sum of `values`, in order
+pub fn accumulate<T: DctNum>(values: &[T], accumulation: Accumulation) -> T {
+    match accumulation {
+        Accumulation::Simple => values.iter().fold(T::zero(), |acc, e| acc + *e),
+        Accumulation::Compensated => {
+            let mut sum = CompensatedSum::new();
+            for value in values {
+                sum.add(*value);
+            }
+            sum.sum
+        }
+    }
+}
+
+// A running sum with Kahan summation
+struct CompensatedSum<T> {
+    sum: T,
+    // the low-order bits that were lost by the previous addition, negated
+    compensation: T,
+}
+impl<T: DctNum> CompensatedSum<T> {
+    fn new() -> Self {
+        Self {
+            sum: T::zero(),
+            compensation: T::zero(),
+        }
+    }
+
+    #[inline(always)]
+    fn add(&mut self, value: T) {
+        let term = value - self.compensation;
+        let new_sum = self.sum + term;
+        self.compensation = (new_sum - self.sum) - term;
+        self.sum = new_sum;
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::test_utils::{fuzzy_cmp, random_signal};
+
+    #[test]
+    fn test_twiddle_dot() {
+        let twiddles = random_signal(37);
+        for len in 0..30 {
+            let input = random_signal(len);
+            for &(start, stride) in &[(0, 0), (3, 1), (36, 5), (10, 36)] {
+                let expected: f32 = input
+                    .iter()
+                    .enumerate()
+                    .map(|(i, x)| x * twiddles[(start + i * stride) % twiddles.len()])
+                    .sum();
+                for &accumulation in &[Accumulation::Simple, Accumulation::Compensated] {
+                    let actual = twiddle_dot(&input, &twiddles, start, stride, |t| t, accumulation);
+                    assert!(
+                        fuzzy_cmp(expected, actual, 0.01),
+                        "len = {}, start = {}, stride = {}, {:?}",
+                        len,
+                        start,
+                        stride,
+                        accumulation
+                    );
+                }
+            }
+        }
+    }
+
+    /// Verify that compensated accumulation keeps the error of a long sum close to the error of each product
+    #[test]
+    fn test_compensated_accuracy() {
+        let len = 100_000;
+        let input = vec![0.1f32; len];
+        let twiddles = [1.0f32];
+        let expected = len as f64 * 0.1f32 as f64;
+
+        let simple = twiddle_dot(&input, &twiddles, 0, 0, |t| t, Accumulation::Simple);
+        let compensated = twiddle_dot(&input, &twiddles, 0, 0, |t| t, Accumulation::Compensated);
+        let simple_error = (simple as f64 - expected).abs();
+        let compensated_error = (compensated as f64 - expected).abs();
+        assert!(
+            compensated_error <= expected * 1e-7,
+            "{}",
+            compensated_error
+        );
+        assert!(
+            compensated_error * 100.0 < simple_error,
+            "{} {}",
+            compensated_error,
+            simple_error
+        );
+    }
+}
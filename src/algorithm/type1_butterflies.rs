@@ -0,0 +1,283 @@
+use std::f64;
+
+use rustfft::Length;
+
+use crate::common::dct_error_inplace;
+use crate::RequiredScratch;
+use crate::{Dct1, DctNum, Dst1};
+use crate::{FromPlanKey, PlanIdentity, PlanKey, PlanKind};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+
+// Each butterfly stores its entire transform matrix inline and copies its input to a fixed-size array on the stack,
+// so that it doesn't need any scratch space. Because the sizes are known at compile time, the compiler is able to
+// fully unroll and vectorize the matrix multiplication.
+macro_rules! dct1_butterfly {
+    ($struct_name:ident, $size:expr) => {
+        /// DCT Type 1 implementation for a hardcoded size, which requires no scratch space.
+        ///
+        /// `get_scratch_len()` always returns 0, so `process_dct1_with_scratch` can be given an empty scratch slice.
+        pub struct $struct_name<T> {
+            matrix: [[T; $size]; $size],
+        }
+        impl<T: DctNum> $struct_name<T> {
+            /// Creates a new DCT1 butterfly
+            pub fn new() -> Self {
+                let constant_factor = f64::consts::PI / (($size - 1) as f64);
+
+                // Precompute the entire transform matrix, including the half weights of the first and last inputs
+                let mut matrix = [[T::zero(); $size]; $size];
+                for (k, row) in matrix.iter_mut().enumerate() {
+                    for (i, cell) in row.iter_mut().enumerate() {
+                        let twiddle_index = (i * k) % (2 * ($size - 1));
+                        let mut value = (constant_factor * twiddle_index as f64).cos();
+                        if i == 0 || i == $size - 1 {
+                            value *= 0.5;
+                        }
+                        *cell = T::from_f64(value).unwrap();
+                    }
+                }
+
+                Self { matrix }
+            }
+        }
+        impl<T: DctNum> Default for $struct_name<T> {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+        impl<T: DctNum> Dct1<T> for $struct_name<T> {
+            plan_hooks!(T);
+            fn process_dct1_with_scratch(&self, buffer: &mut [T], _scratch: &mut [T]) {
+                validate_buffer!(buffer, self.len());
+
+                let mut input = [T::zero(); $size];
+                input.copy_from_slice(buffer);
+
+                for (output_cell, row) in buffer.iter_mut().zip(self.matrix.iter()) {
+                    let mut sum = T::zero();
+                    for (input_cell, matrix_cell) in input.iter().zip(row.iter()) {
+                        sum = sum + *input_cell * *matrix_cell;
+                    }
+                    *output_cell = sum;
+                }
+            }
+        }
+        impl<T: DctNum> PlanIdentity<T> for $struct_name<T> {
+            fn plan_key(&self) -> PlanKey<T> {
+                PlanKey::new(PlanKind::Dct1, self.len())
+            }
+        }
+        impl<T: DctNum> FromPlanKey<T> for $struct_name<T> {
+            fn from_plan_key(key: &PlanKey<T>) -> Option<Self> {
+                if *key == PlanKey::new(PlanKind::Dct1, $size) {
+                    Some(Self::new())
+                } else {
+                    None
+                }
+            }
+        }
+        impl<T> MemoryUsage for $struct_name<T> {
+            fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+                // the butterfly's fields are all twiddle factors
+                PlanMemory {
+                    twiddles_bytes: std::mem::size_of::<Self>(),
+                    ..PlanMemory::default()
+                }
+            }
+        }
+        impl<T> RequiredScratch for $struct_name<T> {
+            fn get_scratch_len(&self) -> usize {
+                0
+            }
+        }
+        impl<T> Length for $struct_name<T> {
+            fn len(&self) -> usize {
+                $size
+            }
+        }
+    };
+}
+
+macro_rules! dst1_butterfly {
+    ($struct_name:ident, $size:expr) => {
+        /// DST Type 1 implementation for a hardcoded size, which requires no scratch space.
+        ///
+        /// `get_scratch_len()` always returns 0, so `process_dst1_with_scratch` can be given an empty scratch slice.
+        pub struct $struct_name<T> {
+            matrix: [[T; $size]; $size],
+        }
+        impl<T: DctNum> $struct_name<T> {
+            /// Creates a new DST1 butterfly
+            pub fn new() -> Self {
+                let constant_factor = f64::consts::PI / (($size + 1) as f64);
+
+                let mut matrix = [[T::zero(); $size]; $size];
+                for (k, row) in matrix.iter_mut().enumerate() {
+                    for (i, cell) in row.iter_mut().enumerate() {
+                        let twiddle_index = ((i + 1) * (k + 1)) % (2 * ($size + 1));
+                        *cell =
+                            T::from_f64((constant_factor * twiddle_index as f64).sin()).unwrap();
+                    }
+                }
+
+                Self { matrix }
+            }
+        }
+        impl<T: DctNum> Default for $struct_name<T> {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+        impl<T: DctNum> Dst1<T> for $struct_name<T> {
+            plan_hooks!(T);
+            fn process_dst1_with_scratch(&self, buffer: &mut [T], _scratch: &mut [T]) {
+                validate_buffer!(buffer, self.len());
+
+                let mut input = [T::zero(); $size];
+                input.copy_from_slice(buffer);
+
+                for (output_cell, row) in buffer.iter_mut().zip(self.matrix.iter()) {
+                    let mut sum = T::zero();
+                    for (input_cell, matrix_cell) in input.iter().zip(row.iter()) {
+                        sum = sum + *input_cell * *matrix_cell;
+                    }
+                    *output_cell = sum;
+                }
+            }
+        }
+        impl<T: DctNum> PlanIdentity<T> for $struct_name<T> {
+            fn plan_key(&self) -> PlanKey<T> {
+                PlanKey::new(PlanKind::Dst1, self.len())
+            }
+        }
+        impl<T: DctNum> FromPlanKey<T> for $struct_name<T> {
+            fn from_plan_key(key: &PlanKey<T>) -> Option<Self> {
+                if *key == PlanKey::new(PlanKind::Dst1, $size) {
+                    Some(Self::new())
+                } else {
+                    None
+                }
+            }
+        }
+        impl<T> MemoryUsage for $struct_name<T> {
+            fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+                // the butterfly's fields are all twiddle factors
+                PlanMemory {
+                    twiddles_bytes: std::mem::size_of::<Self>(),
+                    ..PlanMemory::default()
+                }
+            }
+        }
+        impl<T> RequiredScratch for $struct_name<T> {
+            fn get_scratch_len(&self) -> usize {
+                0
+            }
+        }
+        impl<T> Length for $struct_name<T> {
+            fn len(&self) -> usize {
+                $size
+            }
+        }
+    };
+}
+
+dct1_butterfly!(Dct1Butterfly2, 2);
+dct1_butterfly!(Dct1Butterfly3, 3);
+dct1_butterfly!(Dct1Butterfly4, 4);
+dct1_butterfly!(Dct1Butterfly5, 5);
+dct1_butterfly!(Dct1Butterfly6, 6);
+dct1_butterfly!(Dct1Butterfly7, 7);
+dct1_butterfly!(Dct1Butterfly8, 8);
+dct1_butterfly!(Dct1Butterfly9, 9);
+dct1_butterfly!(Dct1Butterfly10, 10);
+dct1_butterfly!(Dct1Butterfly11, 11);
+dct1_butterfly!(Dct1Butterfly12, 12);
+dct1_butterfly!(Dct1Butterfly13, 13);
+dct1_butterfly!(Dct1Butterfly14, 14);
+dct1_butterfly!(Dct1Butterfly15, 15);
+dct1_butterfly!(Dct1Butterfly16, 16);
+
+dst1_butterfly!(Dst1Butterfly2, 2);
+dst1_butterfly!(Dst1Butterfly3, 3);
+dst1_butterfly!(Dst1Butterfly4, 4);
+dst1_butterfly!(Dst1Butterfly5, 5);
+dst1_butterfly!(Dst1Butterfly6, 6);
+dst1_butterfly!(Dst1Butterfly7, 7);
+dst1_butterfly!(Dst1Butterfly8, 8);
+dst1_butterfly!(Dst1Butterfly9, 9);
+dst1_butterfly!(Dst1Butterfly10, 10);
+dst1_butterfly!(Dst1Butterfly11, 11);
+dst1_butterfly!(Dst1Butterfly12, 12);
+dst1_butterfly!(Dst1Butterfly13, 13);
+dst1_butterfly!(Dst1Butterfly14, 14);
+dst1_butterfly!(Dst1Butterfly15, 15);
+dst1_butterfly!(Dst1Butterfly16, 16);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algorithm::{Dct1Naive, Dst1Naive};
+    use crate::test_utils::{compare_float_vectors, random_signal};
+
+    //the tests for all butterflies will be identical except for the identifiers used and size
+    //so it's ideal for a macro
+    macro_rules! test_butterfly_func {
+        ($test_name:ident, $dct1_struct:ident, $dst1_struct:ident, $size:expr) => {
+            #[test]
+            fn $test_name() {
+                let size = $size;
+
+                let dct1_butterfly = $dct1_struct::new();
+                let dst1_butterfly = $dst1_struct::new();
+
+                assert_eq!(dct1_butterfly.get_scratch_len(), 0);
+                assert_eq!(dst1_butterfly.get_scratch_len(), 0);
+
+                // DCT1
+                let mut expected_buffer = random_signal(size);
+                let mut actual_buffer = expected_buffer.clone();
+
+                Dct1Naive::new(size).process_dct1(&mut expected_buffer);
+                dct1_butterfly.process_dct1_with_scratch(&mut actual_buffer, &mut []);
+
+                println!("expected output: {:?}", expected_buffer);
+                println!("actual output:   {:?}", actual_buffer);
+                assert!(
+                    compare_float_vectors(&expected_buffer, &actual_buffer),
+                    "process_dct1() failed, length = {}",
+                    size
+                );
+
+                // DST1
+                let mut expected_buffer = random_signal(size);
+                let mut actual_buffer = expected_buffer.clone();
+
+                Dst1Naive::new(size).process_dst1(&mut expected_buffer);
+                dst1_butterfly.process_dst1_with_scratch(&mut actual_buffer, &mut []);
+
+                println!("expected output: {:?}", expected_buffer);
+                println!("actual output:   {:?}", actual_buffer);
+                assert!(
+                    compare_float_vectors(&expected_buffer, &actual_buffer),
+                    "process_dst1() failed, length = {}",
+                    size
+                );
+            }
+        };
+    }
+    test_butterfly_func!(test_butterfly2_type1, Dct1Butterfly2, Dst1Butterfly2, 2);
+    test_butterfly_func!(test_butterfly3_type1, Dct1Butterfly3, Dst1Butterfly3, 3);
+    test_butterfly_func!(test_butterfly4_type1, Dct1Butterfly4, Dst1Butterfly4, 4);
+    test_butterfly_func!(test_butterfly5_type1, Dct1Butterfly5, Dst1Butterfly5, 5);
+    test_butterfly_func!(test_butterfly6_type1, Dct1Butterfly6, Dst1Butterfly6, 6);
+    test_butterfly_func!(test_butterfly7_type1, Dct1Butterfly7, Dst1Butterfly7, 7);
+    test_butterfly_func!(test_butterfly8_type1, Dct1Butterfly8, Dst1Butterfly8, 8);
+    test_butterfly_func!(test_butterfly9_type1, Dct1Butterfly9, Dst1Butterfly9, 9);
+    test_butterfly_func!(test_butterfly10_type1, Dct1Butterfly10, Dst1Butterfly10, 10);
+    test_butterfly_func!(test_butterfly11_type1, Dct1Butterfly11, Dst1Butterfly11, 11);
+    test_butterfly_func!(test_butterfly12_type1, Dct1Butterfly12, Dst1Butterfly12, 12);
+    test_butterfly_func!(test_butterfly13_type1, Dct1Butterfly13, Dst1Butterfly13, 13);
+    test_butterfly_func!(test_butterfly14_type1, Dct1Butterfly14, Dst1Butterfly14, 14);
+    test_butterfly_func!(test_butterfly15_type1, Dct1Butterfly15, Dst1Butterfly15, 15);
+    test_butterfly_func!(test_butterfly16_type1, Dct1Butterfly16, Dst1Butterfly16, 16);
+}
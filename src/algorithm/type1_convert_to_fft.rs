@@ -5,8 +5,12 @@ use rustfft::FftDirection;
 use rustfft::{Fft, Length};
 
 use crate::common::dct_error_inplace;
+use crate::scratch_layout::fft_layout;
+use crate::ScratchLayout;
 use crate::{array_utils::into_complex_mut, DctNum, RequiredScratch};
 use crate::{Dct1, Dst1};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
 
 /// DCT Type 1 implementation that converts the problem into a FFT of size 2 * (n - 1)
 ///
@@ -62,6 +66,7 @@ impl<T: DctNum> Dct1ConvertToFft<T> {
 }
 
 impl<T: DctNum> Dct1<T> for Dct1ConvertToFft<T> {
+    plan_hooks!(T);
     fn process_dct1_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
 
@@ -96,10 +101,27 @@ impl<T: DctNum> Dct1<T> for Dct1ConvertToFft<T> {
         }
     }
 }
+impl<T: DctNum> PlanIdentity<T> for Dct1ConvertToFft<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Dct1, self.len())
+    }
+}
+impl<T> MemoryUsage for Dct1ConvertToFft<T> {
+    fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+        // the only precomputed data is the inner FFT
+        PlanMemory::default()
+    }
+}
 impl<T: DctNum> RequiredScratch for Dct1ConvertToFft<T> {
     fn get_scratch_len(&self) -> usize {
         self.scratch_len
     }
+    fn get_complex_scratch_len(&self) -> usize {
+        self.scratch_len / 2
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        fft_layout(2 * self.inner_fft_len, self.scratch_len)
+    }
 }
 impl<T> Length for Dct1ConvertToFft<T> {
     fn len(&self) -> usize {
@@ -162,6 +184,7 @@ impl<T: DctNum> Dst1ConvertToFft<T> {
 }
 
 impl<T: DctNum> Dst1<T> for Dst1ConvertToFft<T> {
+    plan_hooks!(T);
     fn process_dst1_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
 
@@ -188,10 +211,27 @@ impl<T: DctNum> Dst1<T> for Dst1ConvertToFft<T> {
         }
     }
 }
+impl<T: DctNum> PlanIdentity<T> for Dst1ConvertToFft<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Dst1, self.len())
+    }
+}
+impl<T> MemoryUsage for Dst1ConvertToFft<T> {
+    fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+        // the only precomputed data is the inner FFT
+        PlanMemory::default()
+    }
+}
 impl<T: DctNum> RequiredScratch for Dst1ConvertToFft<T> {
     fn get_scratch_len(&self) -> usize {
         self.scratch_len
     }
+    fn get_complex_scratch_len(&self) -> usize {
+        self.scratch_len / 2
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        fft_layout(2 * self.inner_fft_len, self.scratch_len)
+    }
 }
 impl<T> Length for Dst1ConvertToFft<T> {
     fn len(&self) -> usize {
@@ -0,0 +1,199 @@
+use std::sync::Arc;
+
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftDirection, Length};
+
+use crate::common::{dct_error_inplace, derived_len};
+use crate::memory::table_bytes;
+use crate::scratch_layout::fft_layout;
+use crate::ScratchLayout;
+use crate::{array_utils::into_complex_mut, twiddles, RequiredScratch};
+use crate::{Dct1, DctNum};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
+
+/// DCT Type 1 implementation that converts the problem into a real-valued FFT of size `2 * (n - 1)`, which is computed
+/// with a complex FFT of size `n - 1`.
+///
+/// `Dct1ConvertToFft` puts the even-symmetric extension of the input into a complex FFT of size `2 * (n - 1)`, whose
+/// inputs all have zero imaginary parts. The even and odd outputs of that FFT are each a FFT of size `n - 1`, and both
+/// are real, so this algorithm computes them together as the real and imaginary parts of one complex FFT of half the
+/// size. Folding the input into that FFT's input takes one twiddle factor per pair of input elements, and the FFT's
+/// output is the DCT1's output, so the rounding error is the same as `Dct1ConvertToFft`'s. This halves both the FFT
+/// work and the scratch space.
+///
+/// The symmetric extension could be folded further, into a real FFT of size `n - 1`, but then the odd outputs can only
+/// be recovered from their differences, with a running sum whose rounding error grows linearly with the size.
+///
+/// ~~~
+/// // Computes a O(NlogN) DCT1 of size 1234 with a complex FFT of size 1233
+/// use rustdct::Dct1;
+/// use rustdct::algorithm::Dct1ConvertToRealFft;
+/// use rustdct::rustfft::FftPlanner;
+///
+/// let len = 1234;
+///
+/// let mut planner = FftPlanner::new();
+/// let fft = planner.plan_fft_forward(len - 1);
+///
+/// let dct = Dct1ConvertToRealFft::new(fft);
+///
+/// let mut buffer = vec![0f32; len];
+/// dct.process_dct1(&mut buffer);
+/// ~~~
+pub struct Dct1ConvertToRealFft<T> {
+    fft: Arc<dyn Fft<T>>,
+
+    // `len / 2` twiddles whose real part is `cos(pi * j / (len - 1))` and whose imaginary part is
+    // `sin(pi * j / (len - 1))`, used to fold the input into the FFT's input
+    twiddles: Box<[Complex<T>]>,
+
+    scratch_len: usize,
+}
+
+impl<T: DctNum> Dct1ConvertToRealFft<T> {
+    /// Creates a new DCT1 context that will process signals of length `inner_fft.len() + 1`.
+    pub fn new(inner_fft: Arc<dyn Fft<T>>) -> Self {
+        assert_eq!(
+            inner_fft.fft_direction(),
+            FftDirection::Forward,
+            "The 'DCT type 1 via real FFT' algorithm requires a forward FFT, but an inverse FFT was provided"
+        );
+
+        let fft_len = inner_fft.len();
+        assert!(
+            fft_len > 0,
+            "The 'DCT type 1 via real FFT' algorithm requires a non-empty inner FFT"
+        );
+        derived_len(fft_len, 1, 1, "DCT1");
+        let twiddle_len = derived_len(fft_len, 2, 0, "twiddle table");
+        let scratch_len = derived_len(
+            fft_len + inner_fft.get_inplace_scratch_len(),
+            2,
+            0,
+            "scratch",
+        );
+
+        let twiddles: Vec<Complex<T>> = (0..(fft_len + 1) / 2)
+            .map(|j| twiddles::single_twiddle::<T>(j, twiddle_len).conj())
+            .collect();
+
+        Self {
+            fft: inner_fft,
+            twiddles: twiddles.into_boxed_slice(),
+            scratch_len,
+        }
+    }
+}
+
+impl<T: DctNum> Dct1<T> for Dct1ConvertToRealFft<T> {
+    plan_hooks!(T);
+    fn process_dct1_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+
+        let fft_len = self.fft.len();
+        let half = T::half();
+
+        // the real parts of the FFT input are `x[j] + x[len - 1 - j]` plus a twiddled `x[j] - x[len - 1 - j]`, and the
+        // imaginary parts are that difference times another twiddle. elements `j` and `len - 1 - j` are computed
+        // together, and if `len - 1` is even, element `(len - 1) / 2` is its own mirror. the FFT's output is doubled, so
+        // the halving is applied here
+        let (fft_buffer, fft_scratch) = into_complex_mut(scratch).split_at_mut(fft_len);
+        fft_buffer[0] = Complex {
+            re: (buffer[0] + buffer[fft_len]) * half,
+            im: (buffer[0] - buffer[fft_len]) * half,
+        };
+        if fft_len % 2 == 0 {
+            fft_buffer[fft_len / 2] = Complex {
+                re: buffer[fft_len / 2],
+                im: T::zero(),
+            };
+        }
+        for j in 1..(fft_len + 1) / 2 {
+            let twiddle = self.twiddles[j];
+            let sum = (buffer[j] + buffer[fft_len - j]) * half;
+            let diff = (buffer[j] - buffer[fft_len - j]) * half;
+
+            let im = twiddle.re * diff;
+            fft_buffer[j] = Complex {
+                re: sum + twiddle.im * diff,
+                im,
+            };
+            fft_buffer[fft_len - j] = Complex {
+                re: sum - twiddle.im * diff,
+                im,
+            };
+        }
+
+        self.fft.process_with_scratch(fft_buffer, fft_scratch);
+
+        // the real part of FFT output `k` is output `2k`, and its imaginary part is output `2k + 1`
+        for (output_pair, fft_output) in buffer.chunks_mut(2).zip(fft_buffer.iter()) {
+            output_pair[0] = fft_output.re;
+            if let Some(odd_output) = output_pair.get_mut(1) {
+                *odd_output = fft_output.im;
+            }
+        }
+    }
+}
+impl<T: DctNum> PlanIdentity<T> for Dct1ConvertToRealFft<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Dct1, self.len())
+    }
+}
+impl<T> MemoryUsage for Dct1ConvertToRealFft<T> {
+    fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            twiddles_bytes: table_bytes(&self.twiddles),
+            ..PlanMemory::default()
+        }
+    }
+}
+impl<T: DctNum> RequiredScratch for Dct1ConvertToRealFft<T> {
+    fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        self.scratch_len / 2
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        fft_layout(2 * self.fft.len(), self.scratch_len)
+    }
+}
+impl<T> Length for Dct1ConvertToRealFft<T> {
+    fn len(&self) -> usize {
+        self.fft.len() + 1
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algorithm::Dct1Naive;
+
+    use crate::test_utils::{compare_float_vectors, random_signal};
+    use rustfft::FftPlanner;
+
+    /// Verify that our fast implementation of the DCT1 gives the same output as the slow version, for many different inputs
+    #[test]
+    fn test_dct1_via_real_fft() {
+        let mut fft_planner = FftPlanner::new();
+        for size in 2..60 {
+            let mut expected_buffer = random_signal(size);
+            let mut actual_buffer = expected_buffer.clone();
+
+            let naive_dct = Dct1Naive::new(size);
+            naive_dct.process_dct1(&mut expected_buffer);
+
+            let dct = Dct1ConvertToRealFft::new(fft_planner.plan_fft_forward(size - 1));
+            assert_eq!(dct.len(), size);
+            dct.process_dct1(&mut actual_buffer);
+
+            assert!(
+                compare_float_vectors(&actual_buffer, &expected_buffer),
+                "len = {}",
+                size
+            );
+        }
+    }
+}
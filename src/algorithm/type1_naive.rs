@@ -2,9 +2,13 @@ use std::f64;
 
 use rustfft::Length;
 
-use crate::common::dct_error_inplace;
+use crate::algorithm::naive_kernel::{twiddle_dot, Accumulation};
+use crate::common::{dct_error_inplace, derived_len};
+use crate::memory::twiddles_only;
 use crate::RequiredScratch;
 use crate::{Dct1, DctNum, Dst1};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
 
 /// Naive O(n^2 ) DCT Type 1 implementation
 ///
@@ -25,6 +29,7 @@ use crate::{Dct1, DctNum, Dst1};
 /// ~~~
 pub struct Dct1Naive<T> {
     twiddles: Box<[T]>,
+    accumulation: Accumulation,
 }
 
 impl<T: DctNum> Dct1Naive<T> {
@@ -33,18 +38,28 @@ impl<T: DctNum> Dct1Naive<T> {
 
         let constant_factor = f64::consts::PI / ((len - 1) as f64);
 
-        let twiddles: Vec<T> = (0..(len - 1) * 2)
+        let twiddle_len = derived_len(len, 2, -2, "twiddle table");
+        let twiddles: Vec<T> = (0..twiddle_len)
             .map(|i| (constant_factor * (i as f64)).cos())
             .map(|c| T::from_f64(c).unwrap())
             .collect();
 
         Self {
             twiddles: twiddles.into_boxed_slice(),
+            accumulation: Accumulation::Simple,
         }
     }
+
+    /// Sets how each output's sum of products is accumulated, which is `Accumulation::Simple` by default. See
+    /// [`Accumulation`](enum.Accumulation.html).
+    pub fn with_accumulation(mut self, accumulation: Accumulation) -> Self {
+        self.accumulation = accumulation;
+        self
+    }
 }
 
 impl<T: DctNum> Dct1<T> for Dct1Naive<T> {
+    plan_hooks!(T);
     fn process_dct1_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
         scratch.copy_from_slice(buffer);
@@ -55,21 +70,19 @@ impl<T: DctNum> Dct1<T> for Dct1Naive<T> {
 
         for k in 0..buffer.len() {
             let output_cell = buffer.get_mut(k).unwrap();
-            *output_cell = scratch[0];
 
             let twiddle_stride = k;
-            let mut twiddle_index = twiddle_stride;
-
-            for i in 1..scratch.len() {
-                let twiddle = self.twiddles[twiddle_index];
-
-                *output_cell = *output_cell + scratch[i] * twiddle;
-
-                twiddle_index += twiddle_stride;
-                if twiddle_index >= self.twiddles.len() {
-                    twiddle_index -= self.twiddles.len();
-                }
-            }
+            let twiddle_index = twiddle_stride;
+
+            *output_cell = scratch[0]
+                + twiddle_dot(
+                    &scratch[1..],
+                    &self.twiddles,
+                    twiddle_index,
+                    twiddle_stride,
+                    |twiddle| twiddle,
+                    self.accumulation,
+                );
         }
     }
 }
@@ -78,6 +91,16 @@ impl<T> Length for Dct1Naive<T> {
         self.twiddles.len() / 2 + 1
     }
 }
+impl<T: DctNum> PlanIdentity<T> for Dct1Naive<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Dct1, self.len())
+    }
+}
+impl<T> MemoryUsage for Dct1Naive<T> {
+    fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+        twiddles_only(&self.twiddles)
+    }
+}
 impl<T> RequiredScratch for Dct1Naive<T> {
     fn get_scratch_len(&self) -> usize {
         self.len()
@@ -102,6 +125,7 @@ impl<T> RequiredScratch for Dct1Naive<T> {
 /// ~~~
 pub struct Dst1Naive<T> {
     twiddles: Box<[T]>,
+    accumulation: Accumulation,
 }
 
 impl<T: DctNum> Dst1Naive<T> {
@@ -109,39 +133,46 @@ impl<T: DctNum> Dst1Naive<T> {
     pub fn new(len: usize) -> Self {
         let constant_factor = f64::consts::PI / ((len + 1) as f64);
 
-        let twiddles: Vec<T> = (0..(len + 1) * 2)
+        let twiddle_len = derived_len(len, 2, 2, "twiddle table");
+        let twiddles: Vec<T> = (0..twiddle_len)
             .map(|i| (constant_factor * (i as f64)).sin())
             .map(|c| T::from_f64(c).unwrap())
             .collect();
 
         Self {
             twiddles: twiddles.into_boxed_slice(),
+            accumulation: Accumulation::Simple,
         }
     }
+
+    /// Sets how each output's sum of products is accumulated, which is `Accumulation::Simple` by default. See
+    /// [`Accumulation`](enum.Accumulation.html).
+    pub fn with_accumulation(mut self, accumulation: Accumulation) -> Self {
+        self.accumulation = accumulation;
+        self
+    }
 }
 
 impl<T: DctNum> Dst1<T> for Dst1Naive<T> {
+    plan_hooks!(T);
     fn process_dst1_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
         scratch.copy_from_slice(buffer);
 
         for k in 0..buffer.len() {
             let output_cell = buffer.get_mut(k).unwrap();
-            *output_cell = T::zero();
 
             let twiddle_stride = k + 1;
-            let mut twiddle_index = twiddle_stride;
-
-            for i in 0..scratch.len() {
-                let twiddle = self.twiddles[twiddle_index];
-
-                *output_cell = *output_cell + scratch[i] * twiddle;
-
-                twiddle_index += twiddle_stride;
-                if twiddle_index >= self.twiddles.len() {
-                    twiddle_index -= self.twiddles.len();
-                }
-            }
+            let twiddle_index = twiddle_stride;
+
+            *output_cell = twiddle_dot(
+                scratch,
+                &self.twiddles,
+                twiddle_index,
+                twiddle_stride,
+                |twiddle| twiddle,
+                self.accumulation,
+            );
         }
     }
 }
@@ -150,6 +181,16 @@ impl<T> Length for Dst1Naive<T> {
         self.twiddles.len() / 2 - 1
     }
 }
+impl<T: DctNum> PlanIdentity<T> for Dst1Naive<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Dst1, self.len())
+    }
+}
+impl<T> MemoryUsage for Dst1Naive<T> {
+    fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+        twiddles_only(&self.twiddles)
+    }
+}
 impl<T> RequiredScratch for Dst1Naive<T> {
     fn get_scratch_len(&self) -> usize {
         self.len()
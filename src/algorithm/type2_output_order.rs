@@ -0,0 +1,266 @@
+use std::sync::Arc;
+
+use rustfft::num_complex::Complex;
+use rustfft::Length;
+
+use crate::algorithm::{Type2And3ConvertToFft, Type2And3SplitRadix};
+use crate::common::{dct_error_inplace, derived_len, offset_complex_scratch_len};
+use crate::memory::table_bytes;
+use crate::ScratchLayout;
+use crate::{Dct2, DctNum, OutputOrder, RequiredScratch, TransformType2And3};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
+
+enum InnerDct<T, S> {
+    SplitRadix(Type2And3SplitRadix<T, S>),
+    ConvertToFft(Type2And3ConvertToFft<T, S>),
+    Other(Arc<dyn TransformType2And3<T>>),
+}
+
+/// DCT2 implementation that writes its output coefficients in a custom [`OutputOrder`](../enum.OutputOrder.html).
+///
+/// When it's built from a `Type2And3SplitRadix` or a `Type2And3ConvertToFft`, the inner algorithm writes each
+/// coefficient directly to its final position during its last pass, so the reordering costs nothing extra. Any other
+/// `TransformType2And3` instance can be used with `new`, but then the transform is computed in the scratch buffer and
+/// permuted into place afterwards.
+///
+/// ~~~
+/// // Computes a DCT Type 2 of size 1234, with the even coefficients first
+/// use rustdct::algorithm::{Dct2WithOutputOrder, Type2And3ConvertToFft};
+/// use rustdct::rustfft::FftPlanner;
+/// use rustdct::{Dct2, OutputOrder};
+///
+/// let len = 1234;
+/// let mut planner = FftPlanner::new();
+/// let inner_dct = Type2And3ConvertToFft::new(planner.plan_fft_forward(len));
+///
+/// let dct = Dct2WithOutputOrder::from_convert_to_fft(inner_dct, &OutputOrder::EvensThenOdds);
+///
+/// let mut buffer = vec![0f32; len];
+/// dct.process_dct2(&mut buffer);
+/// ~~~
+pub struct Dct2WithOutputOrder<T, S = Box<[Complex<T>]>> {
+    inner: InnerDct<T, S>,
+    destinations: Option<Box<[usize]>>,
+    len: usize,
+    scratch_len: usize,
+}
+
+impl<T: DctNum> Dct2WithOutputOrder<T> {
+    /// Creates a new DCT2 context that computes its output with `inner_dct`, and writes it in the given `order`.
+    ///
+    /// Unless `order` is `OutputOrder::Natural`, this requires `inner_dct.len()` extra elements of scratch, and an
+    /// extra pass over the data to reorder the output.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is a `Custom` order that isn't a permutation of `0..inner_dct.len()`.
+    pub fn new(inner_dct: Arc<dyn TransformType2And3<T>>, order: &OutputOrder) -> Self {
+        let len = inner_dct.len();
+        let destinations = order.destinations(len);
+        let scratch_len = if destinations.is_some() {
+            derived_len(len, 1, inner_dct.get_scratch_len() as isize, "scratch")
+        } else {
+            inner_dct.get_scratch_len()
+        };
+
+        Self {
+            inner: InnerDct::Other(inner_dct),
+            destinations,
+            len,
+            scratch_len,
+        }
+    }
+}
+
+impl<T: DctNum, S: AsRef<[Complex<T>]>> Dct2WithOutputOrder<T, S> {
+    /// Creates a new DCT2 context that computes its output with `inner_dct`, and writes each coefficient directly to
+    /// its position in the given `order`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is a `Custom` order that isn't a permutation of `0..inner_dct.len()`.
+    pub fn from_split_radix(inner_dct: Type2And3SplitRadix<T, S>, order: &OutputOrder) -> Self {
+        Self {
+            len: inner_dct.len(),
+            scratch_len: inner_dct.get_scratch_len(),
+            destinations: order.destinations(inner_dct.len()),
+            inner: InnerDct::SplitRadix(inner_dct),
+        }
+    }
+
+    /// Creates a new DCT2 context that computes its output with `inner_dct`, and writes each coefficient directly to
+    /// its position in the given `order`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is a `Custom` order that isn't a permutation of `0..inner_dct.len()`.
+    pub fn from_convert_to_fft(
+        inner_dct: Type2And3ConvertToFft<T, S>,
+        order: &OutputOrder,
+    ) -> Self {
+        Self {
+            len: inner_dct.len(),
+            scratch_len: inner_dct.get_scratch_len(),
+            destinations: order.destinations(inner_dct.len()),
+            inner: InnerDct::ConvertToFft(inner_dct),
+        }
+    }
+}
+
+impl<T: DctNum, S: AsRef<[Complex<T>]> + Send + Sync> Dct2<T> for Dct2WithOutputOrder<T, S> {
+    plan_hooks!(T);
+    fn process_dct2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        let destinations = match &self.destinations {
+            Some(destinations) => destinations,
+            None => {
+                return match &self.inner {
+                    InnerDct::SplitRadix(dct) => dct.process_dct2_with_scratch(buffer, scratch),
+                    InnerDct::ConvertToFft(dct) => dct.process_dct2_with_scratch(buffer, scratch),
+                    InnerDct::Other(dct) => dct.process_dct2_with_scratch(buffer, scratch),
+                };
+            }
+        };
+
+        match &self.inner {
+            InnerDct::SplitRadix(dct) => {
+                dct.process_dct2_to_destinations(buffer, scratch, destinations)
+            }
+            InnerDct::ConvertToFft(dct) => {
+                dct.process_dct2_to_destinations(buffer, scratch, destinations)
+            }
+            InnerDct::Other(dct) => {
+                let scratch =
+                    validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+                let (dct_buffer, inner_scratch) = scratch.split_at_mut(self.len());
+
+                dct_buffer.copy_from_slice(buffer);
+                dct.process_dct2_with_scratch(dct_buffer, inner_scratch);
+
+                for (coefficient, destination) in dct_buffer.iter().zip(destinations.iter()) {
+                    buffer[*destination] = *coefficient;
+                }
+            }
+        }
+    }
+}
+impl<T: DctNum, S> PlanIdentity<T> for Dct2WithOutputOrder<T, S> {
+    fn plan_key(&self) -> PlanKey<T> {
+        let key = PlanKey::new(PlanKind::Dct2, self.len());
+        match &self.destinations {
+            Some(destinations) => key.with_output_order(destinations),
+            None => key,
+        }
+    }
+}
+impl<T: DctNum, S: AsRef<[Complex<T>]>> MemoryUsage for Dct2WithOutputOrder<T, S> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        let inner_plans_bytes = match &self.inner {
+            InnerDct::SplitRadix(dct) => dct.memory_usage_with_visitor(visitor).total_bytes(),
+            InnerDct::ConvertToFft(dct) => dct.memory_usage_with_visitor(visitor).total_bytes(),
+            InnerDct::Other(dct) => visitor.inner_plan(dct),
+        };
+        PlanMemory {
+            twiddles_bytes: self
+                .destinations
+                .as_ref()
+                .map_or(0, |destinations| table_bytes(destinations)),
+            window_bytes: 0,
+            inner_plans_bytes,
+        }
+    }
+}
+impl<T: DctNum, S: AsRef<[Complex<T>]>> RequiredScratch for Dct2WithOutputOrder<T, S> {
+    fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        match &self.inner {
+            InnerDct::SplitRadix(dct) => dct.get_complex_scratch_len(),
+            InnerDct::ConvertToFft(dct) => dct.get_complex_scratch_len(),
+            // with a custom order, the output is computed in the start of the scratch, before the inner scratch
+            InnerDct::Other(dct) if self.destinations.is_some() => {
+                offset_complex_scratch_len(self.len, dct.get_complex_scratch_len())
+            }
+            InnerDct::Other(dct) => dct.get_complex_scratch_len(),
+        }
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        match &self.inner {
+            InnerDct::SplitRadix(dct) => dct.scratch_layout(),
+            InnerDct::ConvertToFft(dct) => dct.scratch_layout(),
+            InnerDct::Other(dct) if self.destinations.is_some() => ScratchLayout::new()
+                .with_region("DCT buffer", self.len, false)
+                .with_inner(dct.scratch_layout()),
+            InnerDct::Other(dct) => dct.scratch_layout(),
+        }
+    }
+}
+impl<T, S> Length for Dct2WithOutputOrder<T, S> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algorithm::Type2And3Naive;
+    use crate::test_utils::{compare_float_vectors, random_signal};
+    use rustfft::FftPlanner;
+
+    fn orders(len: usize) -> Vec<OutputOrder> {
+        vec![
+            OutputOrder::Natural,
+            OutputOrder::EvensThenOdds,
+            OutputOrder::Custom((0..len).rev().collect::<Vec<_>>().into()),
+        ]
+    }
+
+    fn check_order(dct: &Dct2WithOutputOrder<f32>, order: &OutputOrder) {
+        let len = dct.len();
+        let input = random_signal(len);
+
+        let mut natural = input.clone();
+        Type2And3Naive::new(len).process_dct2(&mut natural);
+        let expected: Vec<f32> = order
+            .coefficient_order(len)
+            .into_iter()
+            .map(|coefficient| natural[coefficient])
+            .collect();
+
+        let mut actual = input.clone();
+        dct.process_dct2(&mut actual);
+        assert!(
+            compare_float_vectors(&expected, &actual),
+            "len = {}, order = {:?}",
+            len,
+            order
+        );
+    }
+
+    #[test]
+    fn test_dct2_with_output_order() {
+        let mut fft_planner = FftPlanner::new();
+        for &len in &[4, 8, 16, 32, 64] {
+            for order in orders(len).iter() {
+                let split_radix = Type2And3SplitRadix::new(
+                    Arc::new(Type2And3Naive::new(len / 2)),
+                    Arc::new(Type2And3Naive::new(len / 4)),
+                );
+                check_order(
+                    &Dct2WithOutputOrder::from_split_radix(split_radix, order),
+                    order,
+                );
+            }
+            for order in orders(len + 1).iter() {
+                let fft = Type2And3ConvertToFft::new(fft_planner.plan_fft_forward(len + 1));
+                check_order(&Dct2WithOutputOrder::from_convert_to_fft(fft, order), order);
+            }
+            for order in orders(len - 1).iter() {
+                let naive = Arc::new(Type2And3Naive::new(len - 1));
+                check_order(&Dct2WithOutputOrder::new(naive, order), order);
+            }
+        }
+    }
+}
@@ -6,10 +6,13 @@ use rustfft::Length;
 use crate::common::dct_error_inplace;
 use crate::{twiddles, DctNum, RequiredScratch};
 use crate::{Dct2, Dct3, Dst2, Dst3, TransformType2And3};
+use crate::{FromPlanKey, PlanIdentity, PlanKey, PlanKind};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
 
 macro_rules! butterfly_boilerplate {
     ($struct_name:ident, $size:expr) => {
         impl<T: DctNum> Dct2<T> for $struct_name<T> {
+            plan_hooks!(T);
             fn process_dct2_with_scratch(&self, buffer: &mut [T], _scratch: &mut [T]) {
                 validate_buffer!(buffer, self.len());
 
@@ -19,6 +22,7 @@ macro_rules! butterfly_boilerplate {
             }
         }
         impl<T: DctNum> Dct3<T> for $struct_name<T> {
+            plan_hooks!(T);
             fn process_dct3_with_scratch(&self, buffer: &mut [T], _scratch: &mut [T]) {
                 validate_buffer!(buffer, self.len());
 
@@ -28,6 +32,7 @@ macro_rules! butterfly_boilerplate {
             }
         }
         impl<T: DctNum> Dst2<T> for $struct_name<T> {
+            plan_hooks!(T);
             fn process_dst2_with_scratch(&self, buffer: &mut [T], _scratch: &mut [T]) {
                 validate_buffer!(buffer, self.len());
 
@@ -37,6 +42,7 @@ macro_rules! butterfly_boilerplate {
             }
         }
         impl<T: DctNum> Dst3<T> for $struct_name<T> {
+            plan_hooks!(T);
             fn process_dst3_with_scratch(&self, buffer: &mut [T], _scratch: &mut [T]) {
                 validate_buffer!(buffer, self.len());
 
@@ -46,6 +52,29 @@ macro_rules! butterfly_boilerplate {
             }
         }
         impl<T: DctNum> TransformType2And3<T> for $struct_name<T> {}
+        impl<T: DctNum> PlanIdentity<T> for $struct_name<T> {
+            fn plan_key(&self) -> PlanKey<T> {
+                PlanKey::new(PlanKind::Type2And3, self.len())
+            }
+        }
+        impl<T: DctNum> FromPlanKey<T> for $struct_name<T> {
+            fn from_plan_key(key: &PlanKey<T>) -> Option<Self> {
+                if *key == PlanKey::new(PlanKind::Type2And3, $size) {
+                    Some(Self::new())
+                } else {
+                    None
+                }
+            }
+        }
+        impl<T> MemoryUsage for $struct_name<T> {
+            fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+                // the butterfly's fields are all twiddle factors
+                PlanMemory {
+                    twiddles_bytes: std::mem::size_of::<Self>(),
+                    ..PlanMemory::default()
+                }
+            }
+        }
         impl<T> RequiredScratch for $struct_name<T> {
             fn get_scratch_len(&self) -> usize {
                 0
@@ -59,6 +88,9 @@ macro_rules! butterfly_boilerplate {
     };
 }
 
+/// DCT2, DCT3, DST2, and DST3 implementation for size 2, which requires no scratch space.
+///
+/// `get_scratch_len()` always returns 0, so the `process_*_with_scratch` methods can be given an empty scratch slice.
 pub struct Type2And3Butterfly2<T> {
     _phantom: PhantomData<T>,
 }
@@ -71,26 +103,26 @@ impl<T: DctNum> Type2And3Butterfly2<T> {
     pub unsafe fn process_inplace_dct2(&self, buffer: &mut [T]) {
         let sum = *buffer.get_unchecked(0) + *buffer.get_unchecked(1);
         *buffer.get_unchecked_mut(1) =
-            (*buffer.get_unchecked(0) - *buffer.get_unchecked(1)) * T::FRAC_1_SQRT_2();
+            (*buffer.get_unchecked(0) - *buffer.get_unchecked(1)) * T::frac_1_sqrt_2();
         *buffer.get_unchecked_mut(0) = sum;
     }
     unsafe fn process_scattered_dct2(buffer: &mut [T], zero: usize, one: usize) {
         let sum = *buffer.get_unchecked(zero) + *buffer.get_unchecked(one);
         *buffer.get_unchecked_mut(one) =
-            (*buffer.get_unchecked(zero) - *buffer.get_unchecked(one)) * T::FRAC_1_SQRT_2();
+            (*buffer.get_unchecked(zero) - *buffer.get_unchecked(one)) * T::frac_1_sqrt_2();
         *buffer.get_unchecked_mut(zero) = sum;
     }
 
     pub unsafe fn process_inplace_dct3(&self, buffer: &mut [T]) {
         let half_0 = *buffer.get_unchecked(0) * T::half();
-        let frac_1 = *buffer.get_unchecked(1) * T::FRAC_1_SQRT_2();
+        let frac_1 = *buffer.get_unchecked(1) * T::frac_1_sqrt_2();
 
         *buffer.get_unchecked_mut(0) = half_0 + frac_1;
         *buffer.get_unchecked_mut(1) = half_0 - frac_1;
     }
     unsafe fn process_scattered_dct3(buffer: &mut [T], zero: usize, one: usize) {
         let half_0 = *buffer.get_unchecked(zero) * T::half();
-        let frac_1 = *buffer.get_unchecked(one) * T::FRAC_1_SQRT_2();
+        let frac_1 = *buffer.get_unchecked(one) * T::frac_1_sqrt_2();
 
         *buffer.get_unchecked_mut(zero) = half_0 + frac_1;
         *buffer.get_unchecked_mut(one) = half_0 - frac_1;
@@ -99,12 +131,12 @@ impl<T: DctNum> Type2And3Butterfly2<T> {
     pub unsafe fn process_inplace_dst2(&self, buffer: &mut [T]) {
         let sum = *buffer.get_unchecked(0) - *buffer.get_unchecked(1);
         *buffer.get_unchecked_mut(0) =
-            (*buffer.get_unchecked(0) + *buffer.get_unchecked(1)) * T::FRAC_1_SQRT_2();
+            (*buffer.get_unchecked(0) + *buffer.get_unchecked(1)) * T::frac_1_sqrt_2();
         *buffer.get_unchecked_mut(1) = sum;
     }
 
     pub unsafe fn process_inplace_dst3(&self, buffer: &mut [T]) {
-        let frac_0 = *buffer.get_unchecked(0) * T::FRAC_1_SQRT_2();
+        let frac_0 = *buffer.get_unchecked(0) * T::frac_1_sqrt_2();
         let half_1 = *buffer.get_unchecked(1) * T::half();
 
         *buffer.get_unchecked_mut(0) = frac_0 + half_1;
@@ -112,39 +144,43 @@ impl<T: DctNum> Type2And3Butterfly2<T> {
     }
 }
 impl<T: DctNum> Dct2<T> for Type2And3Butterfly2<T> {
+    plan_hooks!(T);
     fn process_dct2_with_scratch(&self, buffer: &mut [T], _scratch: &mut [T]) {
         validate_buffer!(buffer, self.len());
 
         let sum = buffer[0] + buffer[1];
-        buffer[1] = (buffer[0] - buffer[1]) * T::FRAC_1_SQRT_2();
+        buffer[1] = (buffer[0] - buffer[1]) * T::frac_1_sqrt_2();
         buffer[0] = sum;
     }
 }
 impl<T: DctNum> Dct3<T> for Type2And3Butterfly2<T> {
+    plan_hooks!(T);
     fn process_dct3_with_scratch(&self, buffer: &mut [T], _scratch: &mut [T]) {
         validate_buffer!(buffer, self.len());
 
         let half_0 = buffer[0] * T::half();
-        let frac_1 = buffer[1] * T::FRAC_1_SQRT_2();
+        let frac_1 = buffer[1] * T::frac_1_sqrt_2();
 
         buffer[0] = half_0 + frac_1;
         buffer[1] = half_0 - frac_1;
     }
 }
 impl<T: DctNum> Dst2<T> for Type2And3Butterfly2<T> {
+    plan_hooks!(T);
     fn process_dst2_with_scratch(&self, buffer: &mut [T], _scratch: &mut [T]) {
         validate_buffer!(buffer, self.len());
 
-        let sum = (buffer[0] + buffer[1]) * T::FRAC_1_SQRT_2();
+        let sum = (buffer[0] + buffer[1]) * T::frac_1_sqrt_2();
         buffer[1] = buffer[0] - buffer[1];
         buffer[0] = sum;
     }
 }
 impl<T: DctNum> Dst3<T> for Type2And3Butterfly2<T> {
+    plan_hooks!(T);
     fn process_dst3_with_scratch(&self, buffer: &mut [T], _scratch: &mut [T]) {
         validate_buffer!(buffer, self.len());
 
-        let frac_0 = buffer[0] * T::FRAC_1_SQRT_2();
+        let frac_0 = buffer[0] * T::frac_1_sqrt_2();
         let half_1 = buffer[1] * T::half();
 
         buffer[0] = frac_0 + half_1;
@@ -157,12 +193,34 @@ impl<T> Length for Type2And3Butterfly2<T> {
         2
     }
 }
+impl<T: DctNum> PlanIdentity<T> for Type2And3Butterfly2<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Type2And3, self.len())
+    }
+}
+impl<T: DctNum> FromPlanKey<T> for Type2And3Butterfly2<T> {
+    fn from_plan_key(key: &PlanKey<T>) -> Option<Self> {
+        if *key == PlanKey::new(PlanKind::Type2And3, 2) {
+            Some(Self::new())
+        } else {
+            None
+        }
+    }
+}
+impl<T> MemoryUsage for Type2And3Butterfly2<T> {
+    fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory::default()
+    }
+}
 impl<T> RequiredScratch for Type2And3Butterfly2<T> {
     fn get_scratch_len(&self) -> usize {
         0
     }
 }
 
+/// DCT2, DCT3, DST2, and DST3 implementation for size 3, which requires no scratch space.
+///
+/// `get_scratch_len()` always returns 0, so the `process_*_with_scratch` methods can be given an empty scratch slice.
 pub struct Type2And3Butterfly3<T> {
     twiddle: T,
 }
@@ -217,6 +275,9 @@ impl<T: DctNum> Type2And3Butterfly3<T> {
 }
 butterfly_boilerplate!(Type2And3Butterfly3, 3);
 
+/// DCT2, DCT3, DST2, and DST3 implementation for size 4, which requires no scratch space.
+///
+/// `get_scratch_len()` always returns 0, so the `process_*_with_scratch` methods can be given an empty scratch slice.
 pub struct Type2And3Butterfly4<T> {
     twiddle: Complex<T>,
 }
@@ -293,6 +354,27 @@ impl<T: DctNum> Type2And3Butterfly4<T> {
 }
 butterfly_boilerplate!(Type2And3Butterfly4, 4);
 
+// Computes `sums[i] = buffer[i] + buffer[len - 1 - i]` and `differences[i] = buffer[i] - buffer[len - 1 - i]` for the
+// first half of `buffer`, the first step of the DCT2 butterflies
+#[inline(always)]
+fn fold_mirrored<T: DctNum>(buffer: &[T], sums: &mut [T], differences: &mut [T]) {
+    #[cfg(feature = "portable-simd")]
+    {
+        if crate::simd::fold_mirrored(buffer, sums, differences) {
+            return;
+        }
+    }
+
+    let len = buffer.len();
+    for (i, (sum, difference)) in sums.iter_mut().zip(differences.iter_mut()).enumerate() {
+        *sum = buffer[i] + buffer[len - 1 - i];
+        *difference = buffer[i] - buffer[len - 1 - i];
+    }
+}
+
+/// DCT2, DCT3, DST2, and DST3 implementation for size 8, which requires no scratch space.
+///
+/// `get_scratch_len()` always returns 0, so the `process_*_with_scratch` methods can be given an empty scratch slice.
 pub struct Type2And3Butterfly8<T> {
     butterfly4: Type2And3Butterfly4<T>,
     butterfly2: Type2And3Butterfly2<T>,
@@ -313,20 +395,17 @@ impl<T: DctNum> Type2And3Butterfly8<T> {
         // perform a step of split radix -- derived from DCT2SplitRadix with n = 8
 
         //process the evens
-        let mut dct2_buffer = [
-            *buffer.get_unchecked(0) + *buffer.get_unchecked(7),
-            *buffer.get_unchecked(1) + *buffer.get_unchecked(6),
-            *buffer.get_unchecked(2) + *buffer.get_unchecked(5),
-            *buffer.get_unchecked(3) + *buffer.get_unchecked(4),
-        ];
+        let mut dct2_buffer = [T::zero(); 4];
+        let mut mirrored_differences = [T::zero(); 4];
+        fold_mirrored(buffer, &mut dct2_buffer, &mut mirrored_differences);
         self.butterfly4.process_inplace_dct2(&mut dct2_buffer);
 
         //process the odds
         let differences = [
-            *buffer.get_unchecked(0) - *buffer.get_unchecked(7),
-            *buffer.get_unchecked(3) - *buffer.get_unchecked(4),
-            *buffer.get_unchecked(1) - *buffer.get_unchecked(6),
-            *buffer.get_unchecked(2) - *buffer.get_unchecked(5),
+            mirrored_differences[0],
+            mirrored_differences[3],
+            mirrored_differences[1],
+            mirrored_differences[2],
         ];
 
         let mut dct4_even_buffer = [
@@ -494,6 +573,9 @@ impl<T: DctNum> Type2And3Butterfly8<T> {
 }
 butterfly_boilerplate!(Type2And3Butterfly8, 8);
 
+/// DCT2, DCT3, DST2, and DST3 implementation for size 16, which requires no scratch space.
+///
+/// `get_scratch_len()` always returns 0, so the `process_*_with_scratch` methods can be given an empty scratch slice.
 pub struct Type2And3Butterfly16<T> {
     butterfly8: Type2And3Butterfly8<T>,
     butterfly4: Type2And3Butterfly4<T>,
@@ -516,28 +598,21 @@ impl<T: DctNum> Type2And3Butterfly16<T> {
         // perform a step of split radix -- derived from DCT2SplitRadix with n = 16
 
         //process the evens
-        let mut dct2_buffer = [
-            *buffer.get_unchecked(0) + *buffer.get_unchecked(15),
-            *buffer.get_unchecked(1) + *buffer.get_unchecked(14),
-            *buffer.get_unchecked(2) + *buffer.get_unchecked(13),
-            *buffer.get_unchecked(3) + *buffer.get_unchecked(12),
-            *buffer.get_unchecked(4) + *buffer.get_unchecked(11),
-            *buffer.get_unchecked(5) + *buffer.get_unchecked(10),
-            *buffer.get_unchecked(6) + *buffer.get_unchecked(9),
-            *buffer.get_unchecked(7) + *buffer.get_unchecked(8),
-        ];
+        let mut dct2_buffer = [T::zero(); 8];
+        let mut mirrored_differences = [T::zero(); 8];
+        fold_mirrored(buffer, &mut dct2_buffer, &mut mirrored_differences);
         self.butterfly8.process_inplace_dct2(&mut dct2_buffer);
 
         //process the odds
         let differences = [
-            *buffer.get_unchecked(0) - *buffer.get_unchecked(15),
-            *buffer.get_unchecked(7) - *buffer.get_unchecked(8),
-            *buffer.get_unchecked(1) - *buffer.get_unchecked(14),
-            *buffer.get_unchecked(6) - *buffer.get_unchecked(9),
-            *buffer.get_unchecked(2) - *buffer.get_unchecked(13),
-            *buffer.get_unchecked(5) - *buffer.get_unchecked(10),
-            *buffer.get_unchecked(3) - *buffer.get_unchecked(12),
-            *buffer.get_unchecked(4) - *buffer.get_unchecked(11),
+            mirrored_differences[0],
+            mirrored_differences[7],
+            mirrored_differences[1],
+            mirrored_differences[6],
+            mirrored_differences[2],
+            mirrored_differences[5],
+            mirrored_differences[3],
+            mirrored_differences[4],
         ];
 
         let mut dct4_even_buffer = [
@@ -788,12 +863,33 @@ mod test {
                     let naive = Type2And3Naive::new(size);
                     let butterfly = $struct_name::new();
 
+                    check_scratch_free(&butterfly, &naive);
+
                     check_dct2(&butterfly, &naive);
                     check_dct3(&butterfly, &naive);
                     check_dst2(&butterfly, &naive);
                     check_dst3(&butterfly, &naive);
                 }
 
+                // the butterflies promise not to need any scratch, so verify that they work with an empty scratch slice
+                fn check_scratch_free(
+                    butterfly_instance: &$struct_name<f32>,
+                    naive_instance: &dyn TransformType2And3<f32>,
+                ) {
+                    assert_eq!(butterfly_instance.get_scratch_len(), 0);
+
+                    let mut expected_buffer = random_signal($size);
+                    let mut actual_buffer = expected_buffer.clone();
+
+                    naive_instance.process_dct2(&mut expected_buffer);
+                    butterfly_instance.process_dct2_with_scratch(&mut actual_buffer, &mut []);
+                    assert!(compare_float_vectors(&expected_buffer, &actual_buffer));
+
+                    naive_instance.process_dst3(&mut expected_buffer);
+                    butterfly_instance.process_dst3_with_scratch(&mut actual_buffer, &mut []);
+                    assert!(compare_float_vectors(&expected_buffer, &actual_buffer));
+                }
+
                 fn check_dct2(
                     butterfly_instance: &$struct_name<f32>,
                     naive_instance: &dyn Dct2<f32>,
@@ -1,11 +1,23 @@
+use std::mem::MaybeUninit;
 use std::sync::Arc;
 
 use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
 use rustfft::{Fft, FftDirection, Length};
 
-use crate::common::dct_error_inplace;
+use crate::backend::BatchedType2And3;
+use crate::common::{dct_error_inplace, dct_error_outofplace, dct_error_two, derived_len};
+use crate::convert::sample_to_float;
+use crate::scratch_layout::fft_layout;
+use crate::ScratchLayout;
 use crate::{array_utils::into_complex_mut, twiddles, RequiredScratch};
 use crate::{Dct2, Dct3, DctNum, Dst2, Dst3, TransformType2And3};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
+
+// When processing batches, the FFTs of several chunks are computed in one call, with a buffer of about this many
+// complex elements
+const BATCH_FFT_BUFFER_LEN: usize = 4096;
 
 /// DCT2, DST2, DCT3, and DST3 implementation that converts the problem into a FFT of the same size
 ///
@@ -33,9 +45,9 @@ use crate::{Dct2, Dct3, DctNum, Dst2, Dst3, TransformType2And3};
 /// let mut dst3_buffer = vec![0f32; len];
 /// dct.process_dst3(&mut dst3_buffer);
 /// ~~~
-pub struct Type2And3ConvertToFft<T> {
+pub struct Type2And3ConvertToFft<T, S = Box<[Complex<T>]>> {
     fft: Arc<dyn Fft<T>>,
-    twiddles: Box<[Complex<T>]>,
+    twiddles: S,
 
     scratch_len: usize,
 }
@@ -43,148 +55,328 @@ pub struct Type2And3ConvertToFft<T> {
 impl<T: DctNum> Type2And3ConvertToFft<T> {
     /// Creates a new DCT2, DST2, DCT3, and DST3 context that will process signals of length `inner_fft.len()`.
     pub fn new(inner_fft: Arc<dyn Fft<T>>) -> Self {
+        let len = validate_inner_fft(inner_fft.as_ref());
+        let twiddles = Self::compute_twiddles(len);
+
+        Self::from_twiddles(inner_fft, twiddles.into_boxed_slice())
+    }
+
+    // Computes the twiddle table for an instance of size `len`
+    pub(crate) fn compute_twiddles(len: usize) -> Vec<Complex<T>> {
+        (0..Self::twiddle_len_for(len))
+            .map(|i| twiddles::single_twiddle(i, len * 4))
+            .collect()
+    }
+
+    /// Returns the number of twiddle factors that an instance of size `len` stores. This is the exact length of the
+    /// storage passed to `with_twiddle_storage`.
+    pub fn twiddle_len_for(len: usize) -> usize {
+        len
+    }
+}
+
+impl<'a, T: DctNum> Type2And3ConvertToFft<T, &'a [Complex<T>]> {
+    /// Creates a new DCT2, DST2, DCT3, and DST3 context that will process signals of length `inner_fft.len()`, and
+    /// stores its twiddle factors in `storage` instead of allocating them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `storage.len()` isn't exactly `Type2And3ConvertToFft::<T>::twiddle_len_for(inner_fft.len())`.
+    pub fn with_twiddle_storage(
+        inner_fft: Arc<dyn Fft<T>>,
+        storage: &'a mut [MaybeUninit<Complex<T>>],
+    ) -> Self {
+        let len = validate_inner_fft(inner_fft.as_ref());
+
+        let twiddle_len = Type2And3ConvertToFft::<T>::twiddle_len_for(len);
+        let twiddles = twiddles::fill_storage(storage, twiddle_len, |i| {
+            twiddles::single_twiddle(i, len * 4)
+        });
+
+        Self::from_twiddles(inner_fft, twiddles)
+    }
+}
+
+impl<T: DctNum> Type2And3ConvertToFft<T, Arc<[Complex<T>]>> {
+    // Creates an instance that uses a twiddle table shared with other instances of the same size. `twiddles` must
+    // have been computed by `compute_twiddles`
+    pub(crate) fn with_shared_twiddles(
+        inner_fft: Arc<dyn Fft<T>>,
+        twiddles: Arc<[Complex<T>]>,
+    ) -> Self {
+        let len = validate_inner_fft(inner_fft.as_ref());
         assert_eq!(
-            inner_fft.fft_direction(),
-            FftDirection::Forward,
-            "The 'DCT type 2 via FFT' algorithm requires a forward FFT, but an inverse FFT was provided"
+            twiddles.len(),
+            Type2And3ConvertToFft::<T>::twiddle_len_for(len)
         );
 
-        let len = inner_fft.len();
+        Self::from_twiddles(inner_fft, twiddles)
+    }
+}
 
-        let twiddles: Vec<Complex<T>> = (0..len)
-            .map(|i| twiddles::single_twiddle(i, len * 4))
-            .collect();
+// Checks that the FFT can be used to compute DCTs, and returns its length
+fn validate_inner_fft<T: DctNum>(inner_fft: &dyn Fft<T>) -> usize {
+    assert_eq!(
+        inner_fft.fft_direction(),
+        FftDirection::Forward,
+        "The 'DCT type 2 via FFT' algorithm requires a forward FFT, but an inverse FFT was provided"
+    );
+
+    let len = inner_fft.len();
+    derived_len(len, 4, 0, "twiddle table");
+    len
+}
 
-        let scratch_len = 2 * (len + inner_fft.get_inplace_scratch_len());
+impl<T: DctNum, S> Type2And3ConvertToFft<T, S> {
+    fn from_twiddles(inner_fft: Arc<dyn Fft<T>>, twiddles: S) -> Self {
+        let scratch_len = 2 * (inner_fft.len() + inner_fft.get_inplace_scratch_len());
 
         Self {
             fft: inner_fft,
-            twiddles: twiddles.into_boxed_slice(),
+            twiddles,
             scratch_len,
         }
     }
 }
 
-impl<T: DctNum> Dct2<T> for Type2And3ConvertToFft<T> {
-    fn process_dct2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
-        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+impl<T: DctNum, S: AsRef<[Complex<T>]> + Send + Sync> Type2And3ConvertToFft<T, S> {
+    // Reorders `input` into the FFT buffer at the start of `scratch`, runs the FFT, and returns the FFT output.
+    // The DCT2 coefficient `k` is the real part of the FFT output `k`, times twiddle `k`. Buffers must already be validated
+    fn dct2_fft<'s>(&self, input: &[T], scratch: &'s mut [T]) -> &'s [Complex<T>] {
+        self.dct2_fft_with(input, scratch, |sample| sample)
+    }
 
+    // Like `dct2_fft`, but converts each input element with `convert` while reordering it
+    fn dct2_fft_with<'s, I: Copy>(
+        &self,
+        input: &[I],
+        scratch: &'s mut [T],
+        convert: impl Fn(I) -> T,
+    ) -> &'s [Complex<T>] {
         let len = self.len();
 
         let complex_scratch = into_complex_mut(scratch);
         let (fft_buffer, fft_scratch) = complex_scratch.split_at_mut(len);
 
+        self.fill_dct2_fft_input_with(input, fft_buffer, convert);
+
+        // run the fft
+        self.fft.process_with_scratch(fft_buffer, fft_scratch);
+        fft_buffer
+    }
+
+    // Reorders `input` into the FFT input for the DCT2
+    fn fill_dct2_fft_input(&self, input: &[T], fft_buffer: &mut [Complex<T>]) {
+        self.fill_dct2_fft_input_with(input, fft_buffer, |sample| sample);
+    }
+
+    // Like `fill_dct2_fft_input`, but converts each input element with `convert`
+    fn fill_dct2_fft_input_with<I: Copy>(
+        &self,
+        input: &[I],
+        fft_buffer: &mut [Complex<T>],
+        convert: impl Fn(I) -> T,
+    ) {
+        self.reorder_dct2_input(input, fft_buffer, |element, sample| {
+            *element = Complex::from(convert(sample))
+        });
+    }
+
+    // Reorders `input` into `fft_buffer` for the DCT2, storing each input element with `store`
+    fn reorder_dct2_input<I: Copy>(
+        &self,
+        input: &[I],
+        fft_buffer: &mut [Complex<T>],
+        store: impl Fn(&mut Complex<T>, I),
+    ) {
+        let len = self.len();
+
         // the first half of the array will be the even elements, in order
-        let even_end = (buffer.len() + 1) / 2;
+        let even_end = (len + 1) / 2;
         for i in 0..even_end {
-            fft_buffer[i] = Complex::from(buffer[i * 2]);
+            store(&mut fft_buffer[i], input[i * 2]);
         }
 
         // the second half is the odd elements, in reverse order
-        if self.len() > 1 {
-            let odd_end = self.len() - 1 - self.len() % 2;
-            for i in 0..self.len() / 2 {
-                fft_buffer[even_end + i] = Complex::from(buffer[odd_end - 2 * i]);
+        if len > 1 {
+            let odd_end = len - 1 - len % 2;
+            for i in 0..len / 2 {
+                store(&mut fft_buffer[even_end + i], input[odd_end - 2 * i]);
             }
         }
+    }
 
-        // run the fft
-        self.fft.process_with_scratch(fft_buffer, fft_scratch);
+    // Applies the DCT2 correction factors to the FFT output, and stores the result in `output`
+    fn finish_dct2(&self, fft_output: &[Complex<T>], output: &mut [T]) {
+        #[cfg(feature = "portable-simd")]
+        {
+            if crate::simd::rotate_real(fft_output, self.twiddles.as_ref(), output) {
+                return;
+            }
+        }
 
-        // apply a correction factor to the result
-        for ((fft_entry, correction_entry), spectrum_entry) in fft_buffer
+        for ((fft_entry, correction_entry), output_entry) in fft_output
             .iter()
-            .zip(self.twiddles.iter())
-            .zip(buffer.iter_mut())
+            .zip(self.twiddles.as_ref().iter())
+            .zip(output.iter_mut())
         {
-            *spectrum_entry = (fft_entry * correction_entry).re;
+            *output_entry = (fft_entry * correction_entry).re;
         }
     }
-}
-impl<T: DctNum> Dst2<T> for Type2And3ConvertToFft<T> {
-    fn process_dst2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+
+    // Computes the DCT2 of `buffer`, putting the output for coefficient `k` at index `destination(k)`.
+    // `destination` must map `0..len` to a permutation of `0..len`
+    fn process_dct2_with_destination<D: Fn(usize) -> usize>(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        destination: D,
+    ) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
 
+        let fft_output = self.dct2_fft(buffer, scratch);
+
+        // apply a correction factor to the result
+        for (k, (fft_entry, correction_entry)) in fft_output
+            .iter()
+            .zip(self.twiddles.as_ref().iter())
+            .enumerate()
+        {
+            buffer[destination(k)] = (fft_entry * correction_entry).re;
+        }
+    }
+
+    // Applies the DCT3 correction factors to `input`, puts the result in the FFT buffer at the start of `scratch`, runs
+    // the FFT, and returns the FFT output. Buffers must already be validated
+    fn dct3_fft<'s>(&self, input: &[T], scratch: &'s mut [T]) -> &'s [Complex<T>] {
         let len = self.len();
 
         let complex_scratch = into_complex_mut(scratch);
         let (fft_buffer, fft_scratch) = complex_scratch.split_at_mut(len);
 
-        // the first half of the array will be the even elements, in order
-        let even_end = (buffer.len() + 1) / 2;
-        for i in 0..even_end {
-            fft_buffer[i] = Complex::from(buffer[i * 2]);
-        }
-
-        // the second half is the odd elements, in reverse order and negated
-        if self.len() > 1 {
-            let odd_end = self.len() - 1 - self.len() % 2;
-            for i in 0..self.len() / 2 {
-                fft_buffer[even_end + i] = Complex::from(-buffer[odd_end - 2 * i]);
-            }
-        }
+        self.fill_dct3_fft_input(input, fft_buffer);
 
         // run the fft
         self.fft.process_with_scratch(fft_buffer, fft_scratch);
-
-        // apply a correction factor to the result, and put it in reversed order in the output buffer
-        for ((fft_entry, correction_entry), spectrum_entry) in fft_buffer
-            .iter()
-            .zip(self.twiddles.iter())
-            .zip(buffer.iter_mut().rev())
-        {
-            *spectrum_entry = (fft_entry * correction_entry).re;
-        }
+        fft_buffer
     }
-}
-impl<T: DctNum> Dct3<T> for Type2And3ConvertToFft<T> {
-    fn process_dct3_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
-        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
 
+    // Computes the FFT input for the DCT3 of `input`, based on the correction factors
+    fn fill_dct3_fft_input(&self, input: &[T], fft_buffer: &mut [Complex<T>]) {
         let len = self.len();
+        if len == 0 {
+            return;
+        }
 
-        let complex_scratch = into_complex_mut(scratch);
-        let (fft_buffer, fft_scratch) = complex_scratch.split_at_mut(len);
+        fft_buffer[0] = Complex::from(input[0] * T::half());
 
-        // compute the FFT buffer based on the correction factors
-        fft_buffer[0] = Complex::from(buffer[0] * T::half());
+        #[cfg(feature = "portable-simd")]
+        {
+            if crate::simd::twiddle_mirrored_pairs(input, self.twiddles.as_ref(), fft_buffer) {
+                return;
+            }
+        }
 
         for (i, (fft_input_element, twiddle)) in fft_buffer
             .iter_mut()
-            .zip(self.twiddles.iter())
+            .zip(self.twiddles.as_ref().iter())
             .enumerate()
             .skip(1)
         {
             let c = Complex {
-                re: buffer[i],
-                im: buffer[buffer.len() - i],
+                re: input[i],
+                im: input[len - i],
             };
             *fft_input_element = c * twiddle * T::half();
         }
+    }
 
-        // run the fft
-        self.fft.process_with_scratch(fft_buffer, fft_scratch);
+    // Computes the DCT2 or DCT3 of every chunk of `data`. The chunks are reordered into the FFT buffer with `fill`,
+    // several at a time, so that each call to the inner FFT processes several chunks, and then they're written back
+    // with `finish`
+    fn process_batch<F, G>(&self, data: &mut [T], fill: F, finish: G)
+    where
+        F: Fn(&Self, &[T], &mut [Complex<T>]),
+        G: Fn(&Self, &[Complex<T>], &mut [T]),
+    {
+        let len = self.len();
+        assert!(
+            len > 0 && data.chunks_exact(len).remainder().is_empty(),
+            "Provided data must be a multiple of the transform size. Transform len = {}, got data len = {}",
+            len,
+            data.len()
+        );
+        if data.is_empty() {
+            return;
+        }
+
+        let chunks_per_fft = (BATCH_FFT_BUFFER_LEN / len).max(1).min(data.len() / len);
+        let mut fft_buffer = vec![Complex::zero(); chunks_per_fft * len];
+        let mut fft_scratch = vec![Complex::zero(); self.fft.get_inplace_scratch_len()];
+
+        for chunks in data.chunks_mut(chunks_per_fft * len) {
+            let fft_buffer = &mut fft_buffer[..chunks.len()];
+            for (chunk, fft_chunk) in chunks
+                .chunks_exact(len)
+                .zip(fft_buffer.chunks_exact_mut(len))
+            {
+                fill(self, chunk, fft_chunk);
+            }
+
+            self.fft.process_with_scratch(fft_buffer, &mut fft_scratch);
+
+            for (chunk, fft_chunk) in chunks
+                .chunks_exact_mut(len)
+                .zip(fft_buffer.chunks_exact(len))
+            {
+                finish(self, fft_chunk, chunk);
+            }
+        }
+    }
+
+    // Copies the FFT output of `dct3_fft` into the DCT3 output
+    fn finish_dct3(&self, fft_output: &[Complex<T>], output: &mut [T]) {
+        self.finish_dct3_with(fft_output, output, |element| element);
+    }
+
+    // Copies the FFT output of `dct3_fft` into the DCT3 output, passing each element through `scale_output`, which lets
+    // the compiler remove the scaling entirely for the unscaled DCT3
+    fn finish_dct3_with<F: Fn(T) -> T>(
+        &self,
+        fft_output: &[Complex<T>],
+        output: &mut [T],
+        scale_output: F,
+    ) {
+        let len = self.len();
 
         // copy the first half of the fft output into the even elements of the buffer
-        let even_end = (buffer.len() + 1) / 2;
+        let even_end = (len + 1) / 2;
         for i in 0..even_end {
-            buffer[i * 2] = fft_buffer[i].re;
+            output[i * 2] = scale_output(fft_output[i].re);
         }
 
         // copy the second half of the fft buffer into the odd elements, reversed
-        if self.len() > 1 {
-            let odd_end = self.len() - 1 - self.len() % 2;
-            for i in 0..self.len() / 2 {
-                buffer[odd_end - 2 * i] = fft_buffer[i + even_end].re;
+        if len > 1 {
+            let odd_end = len - 1 - len % 2;
+            for i in 0..len / 2 {
+                output[odd_end - 2 * i] = scale_output(fft_output[i + even_end].re);
             }
         }
     }
-}
-impl<T: DctNum> Dst3<T> for Type2And3ConvertToFft<T> {
-    fn process_dst3_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+
+    // Computes the DST3 of `buffer`, passing each output through `scale_output`
+    fn process_dst3_with<F: Fn(T) -> T>(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        scale_output: F,
+    ) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
 
         let len = self.len();
+        if len == 0 {
+            return;
+        }
 
         let complex_scratch = into_complex_mut(scratch);
         let (fft_buffer, fft_scratch) = complex_scratch.split_at_mut(len);
@@ -194,7 +386,7 @@ impl<T: DctNum> Dst3<T> for Type2And3ConvertToFft<T> {
 
         for (i, (fft_input_element, twiddle)) in fft_buffer
             .iter_mut()
-            .zip(self.twiddles.iter())
+            .zip(self.twiddles.as_ref().iter())
             .enumerate()
             .skip(1)
         {
@@ -211,28 +403,300 @@ impl<T: DctNum> Dst3<T> for Type2And3ConvertToFft<T> {
         // copy the first half of the fft output into the even elements of the output
         let even_end = (self.len() + 1) / 2;
         for i in 0..even_end {
-            buffer[i * 2] = fft_buffer[i].re;
+            buffer[i * 2] = scale_output(fft_buffer[i].re);
         }
 
         // copy the second half of the fft output into the odd elements, reversed
         if self.len() > 1 {
             let odd_end = self.len() - 1 - self.len() % 2;
             for i in 0..self.len() / 2 {
-                buffer[odd_end - 2 * i] = -fft_buffer[i + even_end].re;
+                buffer[odd_end - 2 * i] = scale_output(-fft_buffer[i + even_end].re);
+            }
+        }
+    }
+
+    // Computes the DCT2 of `buffer`, putting the output for coefficient `k` at index `destinations[k]`.
+    // `destinations` must be a permutation of `0..len`
+    pub(crate) fn process_dct2_to_destinations(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        destinations: &[usize],
+    ) {
+        assert_eq!(destinations.len(), self.len());
+        self.process_dct2_with_destination(buffer, scratch, |k| destinations[k]);
+    }
+}
+
+impl<T: DctNum, S: AsRef<[Complex<T>]> + Send + Sync> Dct2<T> for Type2And3ConvertToFft<T, S> {
+    plan_hooks!(T);
+    fn process_dct2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+
+        let fft_output = self.dct2_fft(buffer, scratch);
+        self.finish_dct2(fft_output, buffer);
+    }
+    fn process_dct2_from(&self, input: &[T], output: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers_outofplace!(
+            input,
+            output,
+            scratch,
+            self.len(),
+            self.get_scratch_len()
+        );
+
+        let fft_output = self.dct2_fft(input, scratch);
+        self.finish_dct2(fft_output, output);
+    }
+    fn process_dct2_two(&self, a: &mut [T], b: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers_two!(a, b, scratch, self.len(), self.get_scratch_len());
+
+        let len = self.len();
+
+        let complex_scratch = into_complex_mut(scratch);
+        let (fft_buffer, fft_scratch) = complex_scratch.split_at_mut(len);
+
+        // the FFT is linear, so with `a` in the real parts and `b` in the imaginary parts, its output is A + iB, where A
+        // and B are the FFTs of the reordered `a` and `b`
+        self.reorder_dct2_input(a, fft_buffer, |element, sample| {
+            *element = Complex::from(sample)
+        });
+        self.reorder_dct2_input(b, fft_buffer, |element, sample| element.im = sample);
+
+        // run the fft
+        self.fft.process_with_scratch(fft_buffer, fft_scratch);
+
+        // A and B are the FFTs of real inputs, so they're conjugate symmetric, which separates them:
+        // A[k] = (Z[k] + conj(Z[len - k])) / 2, and B[k] = (Z[k] - conj(Z[len - k])) / 2i
+        let half = T::half();
+        for (k, twiddle) in self.twiddles.as_ref().iter().enumerate() {
+            let z = fft_buffer[k];
+            let mirrored = fft_buffer[(len - k) % len].conj();
+
+            let sum = z + mirrored;
+            let difference = z - mirrored;
+            let spectrum_a = Complex::new(sum.re * half, sum.im * half);
+            let spectrum_b = Complex::new(difference.im * half, -difference.re * half);
+
+            a[k] = (spectrum_a * twiddle).re;
+            b[k] = (spectrum_b * twiddle).re;
+        }
+    }
+}
+impl<T: DctNum, S: AsRef<[Complex<T>]> + Send + Sync> Dst2<T> for Type2And3ConvertToFft<T, S> {
+    plan_hooks!(T);
+    fn process_dst2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+
+        let len = self.len();
+
+        let complex_scratch = into_complex_mut(scratch);
+        let (fft_buffer, fft_scratch) = complex_scratch.split_at_mut(len);
+
+        // the first half of the array will be the even elements, in order
+        let even_end = (buffer.len() + 1) / 2;
+        for i in 0..even_end {
+            fft_buffer[i] = Complex::from(buffer[i * 2]);
+        }
+
+        // the second half is the odd elements, in reverse order and negated
+        if self.len() > 1 {
+            let odd_end = self.len() - 1 - self.len() % 2;
+            for i in 0..self.len() / 2 {
+                fft_buffer[even_end + i] = Complex::from(-buffer[odd_end - 2 * i]);
+            }
+        }
+
+        // run the fft
+        self.fft.process_with_scratch(fft_buffer, fft_scratch);
+
+        // apply a correction factor to the result, and put it in reversed order in the output buffer
+        for ((fft_entry, correction_entry), spectrum_entry) in fft_buffer
+            .iter()
+            .zip(self.twiddles.as_ref().iter())
+            .zip(buffer.iter_mut().rev())
+        {
+            *spectrum_entry = (fft_entry * correction_entry).re;
+        }
+    }
+}
+impl<T: DctNum, S: AsRef<[Complex<T>]> + Send + Sync> Dct3<T> for Type2And3ConvertToFft<T, S> {
+    plan_hooks!(T);
+    fn process_dct3_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+
+        let fft_output = self.dct3_fft(buffer, scratch);
+        self.finish_dct3(fft_output, buffer);
+    }
+    fn process_dct3_scaled(&self, buffer: &mut [T], scratch: &mut [T], scale: T) {
+        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+
+        let fft_output = self.dct3_fft(buffer, scratch);
+        self.finish_dct3_with(fft_output, buffer, |element| element * scale);
+    }
+    fn process_dct3_from(&self, input: &[T], output: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers_outofplace!(
+            input,
+            output,
+            scratch,
+            self.len(),
+            self.get_scratch_len()
+        );
+
+        let fft_output = self.dct3_fft(input, scratch);
+        self.finish_dct3(fft_output, output);
+    }
+}
+impl<T: DctNum, S: AsRef<[Complex<T>]> + Send + Sync> Dst3<T> for Type2And3ConvertToFft<T, S> {
+    plan_hooks!(T);
+    fn process_dst3_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        self.process_dst3_with(buffer, scratch, |element| element);
+    }
+    fn process_dst3_scaled(&self, buffer: &mut [T], scratch: &mut [T], scale: T) {
+        self.process_dst3_with(buffer, scratch, |element| element * scale);
+    }
+}
+impl<T: DctNum, S: AsRef<[Complex<T>]> + Send + Sync> TransformType2And3<T>
+    for Type2And3ConvertToFft<T, S>
+{
+    fn process_dct2_from_pcm(&self, input: &[i16], output: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers_outofplace!(
+            input,
+            output,
+            scratch,
+            self.len(),
+            self.get_scratch_len()
+        );
+
+        let fft_output = self.dct2_fft_with(input, scratch, |sample| sample_to_float(sample, 0.0));
+        self.finish_dct2(fft_output, output);
+    }
+
+    fn process_dct2_dst2(
+        &self,
+        input: &[T],
+        dct_output: &mut [T],
+        dst_output: &mut [T],
+        scratch: &mut [T],
+    ) {
+        let scratch = validate_buffers_outofplace!(
+            input,
+            dct_output,
+            scratch,
+            self.len(),
+            self.get_scratch_len()
+        );
+        if dst_output.len() != self.len() {
+            dct_error_outofplace(
+                input.len(),
+                dst_output.len(),
+                scratch.len(),
+                self.len(),
+                self.get_scratch_len(),
+            );
+            return;
+        }
+
+        let len = self.len();
+        if len == 0 {
+            return;
+        }
+
+        let complex_scratch = into_complex_mut(scratch);
+        let (fft_buffer, fft_scratch) = complex_scratch.split_at_mut(len);
+
+        // The DCT2 and DST2 FFT inputs are the same reordering of the input, except that the DST2 negates the odd
+        // elements. Both are real, so put the DCT2's in the real part and the DST2's in the imaginary part
+        let even_end = (len + 1) / 2;
+        for i in 0..even_end {
+            let element = input[i * 2];
+            fft_buffer[i] = Complex::new(element, element);
+        }
+        if len > 1 {
+            let odd_end = len - 1 - len % 2;
+            for i in 0..len / 2 {
+                let element = input[odd_end - 2 * i];
+                fft_buffer[even_end + i] = Complex::new(element, -element);
             }
         }
+
+        self.fft.process_with_scratch(fft_buffer, fft_scratch);
+
+        // The FFT of a real signal is conjugate-symmetric, so the two FFTs can be separated using each entry and its
+        // mirror: dct_fft[k] = (fft[k] + conj(fft[-k])) / 2, and dst_fft[k] = (fft[k] - conj(fft[-k])) / 2i. The
+        // mirrored entries of the separated FFTs are the conjugates of these, so process each pair together
+        let twiddles = self.twiddles.as_ref();
+        let half = T::half();
+
+        dct_output[0] = fft_buffer[0].re * twiddles[0].re;
+        dst_output[len - 1] = fft_buffer[0].im * twiddles[0].re;
+
+        for k in 1..(len + 1) / 2 {
+            let mirror = len - k;
+            let fft_entry = fft_buffer[k];
+            let mirror_entry = fft_buffer[mirror];
+
+            let dct_fft_entry = Complex::new(
+                (fft_entry.re + mirror_entry.re) * half,
+                (fft_entry.im - mirror_entry.im) * half,
+            );
+            let dst_fft_entry = Complex::new(
+                (fft_entry.im + mirror_entry.im) * half,
+                (mirror_entry.re - fft_entry.re) * half,
+            );
+
+            dct_output[k] = (dct_fft_entry * twiddles[k]).re;
+            dct_output[mirror] = (dct_fft_entry.conj() * twiddles[mirror]).re;
+            dst_output[len - 1 - k] = (dst_fft_entry * twiddles[k]).re;
+            dst_output[k - 1] = (dst_fft_entry.conj() * twiddles[mirror]).re;
+        }
+
+        // for even sizes, the middle entry is its own mirror
+        if len % 2 == 0 {
+            let middle = len / 2;
+            dct_output[middle] = fft_buffer[middle].re * twiddles[middle].re;
+            dst_output[middle - 1] = fft_buffer[middle].im * twiddles[middle].re;
+        }
+    }
+}
+impl<T: DctNum, S: AsRef<[Complex<T>]> + Send + Sync> BatchedType2And3<T>
+    for Type2And3ConvertToFft<T, S>
+{
+    fn process_dct2_batch(&self, data: &mut [T]) {
+        self.process_batch(data, Self::fill_dct2_fft_input, Self::finish_dct2);
+    }
+    fn process_dct3_batch(&self, data: &mut [T]) {
+        self.process_batch(data, Self::fill_dct3_fft_input, Self::finish_dct3);
     }
 }
-impl<T: DctNum> TransformType2And3<T> for Type2And3ConvertToFft<T> {}
-impl<T> Length for Type2And3ConvertToFft<T> {
+impl<T, S: AsRef<[Complex<T>]>> Length for Type2And3ConvertToFft<T, S> {
     fn len(&self) -> usize {
-        self.twiddles.len()
+        self.twiddles.as_ref().len()
+    }
+}
+impl<T, S: AsRef<[Complex<T>]>> MemoryUsage for Type2And3ConvertToFft<T, S> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            twiddles_bytes: visitor.shared_table(self.twiddles.as_ref()),
+            ..PlanMemory::default()
+        }
+    }
+}
+impl<T: DctNum, S: AsRef<[Complex<T>]>> PlanIdentity<T> for Type2And3ConvertToFft<T, S> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Type2And3, self.len())
     }
 }
-impl<T: DctNum> RequiredScratch for Type2And3ConvertToFft<T> {
+impl<T: DctNum, S> RequiredScratch for Type2And3ConvertToFft<T, S> {
     fn get_scratch_len(&self) -> usize {
         self.scratch_len
     }
+    fn get_complex_scratch_len(&self) -> usize {
+        self.scratch_len / 2
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        fft_layout(2 * self.fft.len(), self.scratch_len)
+    }
 }
 
 #[cfg(test)]
@@ -246,7 +710,7 @@ mod test {
     /// Verify that our fast implementation of the DCT2 gives the same output as the naive version, for many different inputs
     #[test]
     fn test_dct2_via_fft() {
-        for size in 2..20 {
+        for size in 0..20 {
             let mut expected_buffer = random_signal(size);
             let mut actual_buffer = expected_buffer.clone();
 
@@ -269,10 +733,138 @@ mod test {
         }
     }
 
+    /// Verify that computing two DCT2s with one FFT gives the same output as the naive version
+    #[test]
+    fn test_dct2_two_via_fft() {
+        for size in 0..20 {
+            // `random_signal` always starts with the same values, so take the two signals from one longer signal
+            let signals = random_signal(size * 2);
+            let (mut expected_a, mut expected_b) =
+                (signals[..size].to_vec(), signals[size..].to_vec());
+            let (mut actual_a, mut actual_b) = (expected_a.clone(), expected_b.clone());
+
+            let naive_dct = Type2And3Naive::new(size);
+            naive_dct.process_dct2(&mut expected_a);
+            naive_dct.process_dct2(&mut expected_b);
+
+            let mut fft_planner = FftPlanner::new();
+            let dct = Type2And3ConvertToFft::new(fft_planner.plan_fft_forward(size));
+            let mut scratch = vec![0f32; dct.get_scratch_len()];
+            dct.process_dct2_two(&mut actual_a, &mut actual_b, &mut scratch);
+
+            assert!(
+                compare_float_vectors(&actual_a, &expected_a),
+                "a, len = {}",
+                size
+            );
+            assert!(
+                compare_float_vectors(&actual_b, &expected_b),
+                "b, len = {}",
+                size
+            );
+        }
+    }
+
+    /// Verify that the batched DCT2 and DCT3 give the same output as processing each chunk separately, including when
+    /// the chunks don't fit in a single FFT call
+    #[test]
+    fn test_batch_via_fft() {
+        for &(size, count) in &[(1, 3), (7, 1), (7, 1000), (12, 400), (5000, 2)] {
+            let mut fft_planner = FftPlanner::new();
+            let dct = Type2And3ConvertToFft::new(fft_planner.plan_fft_forward(size));
+
+            let mut expected = random_signal(size * count);
+            let mut actual = expected.clone();
+            for chunk in expected.chunks_exact_mut(size) {
+                dct.process_dct2(chunk);
+            }
+            dct.process_dct2_batch(&mut actual);
+            assert!(
+                compare_float_vectors(&expected, &actual),
+                "dct2, len = {}, count = {}",
+                size,
+                count
+            );
+
+            for chunk in expected.chunks_exact_mut(size) {
+                dct.process_dct3(chunk);
+            }
+            dct.process_dct3_batch(&mut actual);
+            assert!(
+                compare_float_vectors(&expected, &actual),
+                "dct3, len = {}, count = {}",
+                size,
+                count
+            );
+        }
+    }
+
+    /// Verify that computing the DCT2 and DST2 together gives the same output as the naive version, for many different
+    /// inputs
+    #[test]
+    fn test_dct2_dst2_via_fft() {
+        for size in 0..20 {
+            let input = random_signal(size);
+
+            let naive = Type2And3Naive::new(size);
+            let mut expected_dct = vec![0f32; size];
+            let mut expected_dst = vec![0f32; size];
+            let mut naive_scratch = vec![0f32; naive.get_scratch_len()];
+            naive.process_dct2_dst2(
+                &input,
+                &mut expected_dct,
+                &mut expected_dst,
+                &mut naive_scratch,
+            );
+
+            let mut fft_planner = FftPlanner::new();
+            let dct = Type2And3ConvertToFft::new(fft_planner.plan_fft_forward(size));
+            let mut actual_dct = vec![0f32; size];
+            let mut actual_dst = vec![0f32; size];
+            let mut scratch = vec![0f32; dct.get_scratch_len()];
+            dct.process_dct2_dst2(&input, &mut actual_dct, &mut actual_dst, &mut scratch);
+
+            assert!(
+                compare_float_vectors(&actual_dct, &expected_dct),
+                "dct2, len = {}",
+                size
+            );
+            assert!(
+                compare_float_vectors(&actual_dst, &expected_dst),
+                "dst2, len = {}",
+                size
+            );
+        }
+    }
+
+    /// Verify that converting PCM samples during the DCT2 gives the same output as the default implementation, which
+    /// converts them first
+    #[test]
+    fn test_dct2_from_pcm_via_fft() {
+        for size in 0..20 {
+            let input: Vec<i16> = (0..size)
+                .map(|i| (i as i16).wrapping_mul(4099) ^ 0x5555)
+                .collect();
+
+            let naive = Type2And3Naive::new(size);
+            let mut expected = vec![0f32; size];
+            let mut naive_scratch = vec![0f32; naive.get_scratch_len()];
+            naive.process_dct2_from_pcm(&input, &mut expected, &mut naive_scratch);
+
+            let mut fft_planner = FftPlanner::new();
+            let dct = Type2And3ConvertToFft::new(fft_planner.plan_fft_forward(size));
+            let mut actual = vec![0f32; size];
+            let mut scratch = vec![0f32; dct.get_scratch_len()];
+            dct.process_dct2_from_pcm(&input, &mut actual, &mut scratch);
+
+            assert!(compare_float_vectors(&actual, &expected), "len = {}", size);
+        }
+    }
+
     /// Verify that our fast implementation of the DST2 gives the same output as the naive version, for many different inputs
     #[test]
     fn test_dst2_via_fft() {
-        for size in 2..20 {
+        for size in 0..20 {
             let mut expected_buffer = random_signal(size);
             let mut actual_buffer = expected_buffer.clone();
 
@@ -298,7 +890,7 @@ mod test {
     /// Verify that our fast implementation of the DCT3 gives the same output as the naive version, for many different inputs
     #[test]
     fn test_dct3_via_fft() {
-        for size in 2..20 {
+        for size in 0..20 {
             let mut expected_buffer = random_signal(size);
             let mut actual_buffer = expected_buffer.clone();
 
@@ -324,7 +916,7 @@ mod test {
     /// Verify that our fast implementation of the DST3 gives the same output as the naive version, for many different inputs
     #[test]
     fn test_dst3_via_fft() {
-        for size in 2..20 {
+        for size in 0..20 {
             let mut expected_buffer = random_signal(size);
             let mut actual_buffer = expected_buffer.clone();
 
@@ -346,4 +938,29 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn test_convert_to_fft_twiddle_storage() {
+        let mut fft_planner = FftPlanner::new();
+        for len in 1..20 {
+            let fft = fft_planner.plan_fft_forward(len);
+
+            let mut storage =
+                vec![MaybeUninit::uninit(); Type2And3ConvertToFft::<f32>::twiddle_len_for(len)];
+            let stored =
+                Type2And3ConvertToFft::with_twiddle_storage(Arc::clone(&fft), &mut storage);
+            let boxed = Type2And3ConvertToFft::new(fft);
+            assert_eq!(stored.len(), len);
+
+            let mut expected_buffer = random_signal(len);
+            let mut actual_buffer = expected_buffer.clone();
+            boxed.process_dct2(&mut expected_buffer);
+            stored.process_dct2(&mut actual_buffer);
+            assert!(compare_float_vectors(&expected_buffer, &actual_buffer));
+
+            boxed.process_dst3(&mut expected_buffer);
+            stored.process_dst3(&mut actual_buffer);
+            assert!(compare_float_vectors(&expected_buffer, &actual_buffer));
+        }
+    }
 }
@@ -0,0 +1,328 @@
+use std::sync::Arc;
+
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftDirection, Length};
+
+use crate::common::{dct_error_inplace, derived_len};
+use crate::memory::table_bytes;
+use crate::scratch_layout::fft_layout;
+use crate::ScratchLayout;
+use crate::{array_utils::into_complex_mut, twiddles, RequiredScratch};
+use crate::{Dct2, Dct3, DctNum, Dst2, Dst3, TransformType2And3};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
+
+/// DCT2, DST2, DCT3, and DST3 implementation that converts the problem into a real-valued FFT of the same size, which
+/// is computed with a complex FFT of half the size.
+///
+/// `Type2And3ConvertToFft` puts the real input into a complex FFT of the full size, so half of the FFT's work is spent
+/// on an imaginary part that's always zero. This algorithm packs pairs of real elements into each complex element
+/// instead, which halves the FFT work and the scratch space, at the cost of more pre- and post-processing. This makes the
+/// DCT3 and DST3 faster, but the DCT2 and DST2 post-processing costs more than the FFT work it saves, unless the
+/// full-size FFT is unusually slow. The problem size must be even.
+///
+/// ~~~
+/// // Computes a O(NlogN) DCT2, DST2, DCT3, and DST3 of size 1234 with a complex FFT of size 617
+/// use rustdct::{Dct2, Dst2, Dct3, Dst3};
+/// use rustdct::algorithm::Type2And3ConvertToRealFft;
+/// use rustdct::rustfft::FftPlanner;
+///
+/// let len = 1234;
+/// let mut planner = FftPlanner::new();
+/// let fft = planner.plan_fft_forward(len / 2);
+///
+/// let dct = Type2And3ConvertToRealFft::new(fft);
+///
+/// let mut dct2_buffer = vec![0f32; len];
+/// dct.process_dct2(&mut dct2_buffer);
+///
+/// let mut dst2_buffer = vec![0f32; len];
+/// dct.process_dst2(&mut dst2_buffer);
+///
+/// let mut dct3_buffer = vec![0f32; len];
+/// dct.process_dct3(&mut dct3_buffer);
+///
+/// let mut dst3_buffer = vec![0f32; len];
+/// dct.process_dst3(&mut dst3_buffer);
+/// ~~~
+pub struct Type2And3ConvertToRealFft<T> {
+    fft: Arc<dyn Fft<T>>,
+
+    // `len / 2 + 1` twiddles that rotate the real FFT's output into the DCT2's output, pre-multiplied by 0.5
+    dct_twiddles: Box<[Complex<T>]>,
+    // `len / 4 + 1` twiddles that split the half-size complex FFT's output into the real FFT's output, pre-multiplied
+    // by -i
+    fft_twiddles: Box<[Complex<T>]>,
+
+    scratch_len: usize,
+}
+
+impl<T: DctNum> Type2And3ConvertToRealFft<T> {
+    /// Creates a new DCT2, DST2, DCT3, and DST3 context that will process signals of length `inner_fft.len() * 2`.
+    pub fn new(inner_fft: Arc<dyn Fft<T>>) -> Self {
+        assert_eq!(
+            inner_fft.fft_direction(),
+            FftDirection::Forward,
+            "The 'DCT type 2 via real FFT' algorithm requires a forward FFT, but an inverse FFT was provided"
+        );
+
+        let half_len = inner_fft.len();
+        assert!(
+            half_len > 0,
+            "The 'DCT type 2 via real FFT' algorithm requires a non-empty inner FFT"
+        );
+        let len = derived_len(half_len, 2, 0, "DCT2");
+        let twiddle_len = derived_len(len, 4, 0, "twiddle table");
+
+        let dct_twiddles: Vec<Complex<T>> = (0..=half_len)
+            .map(|k| twiddles::single_twiddle(k, twiddle_len) * T::half())
+            .collect();
+        let fft_twiddles: Vec<Complex<T>> = (0..=half_len / 2)
+            .map(|k| {
+                let twiddle = twiddles::single_twiddle::<T>(k, len);
+                Complex {
+                    re: twiddle.im,
+                    im: -twiddle.re,
+                }
+            })
+            .collect();
+
+        let scratch_len = 2 * (half_len + inner_fft.get_inplace_scratch_len());
+
+        Self {
+            fft: inner_fft,
+            dct_twiddles: dct_twiddles.into_boxed_slice(),
+            fft_twiddles: fft_twiddles.into_boxed_slice(),
+            scratch_len,
+        }
+    }
+
+    // Computes the DCT2 of `buffer`, in-place. If `dst` is true, computes the DST2 instead, by negating the odd inputs
+    // and reversing the output
+    #[inline(always)]
+    fn process_type2(&self, buffer: &mut [T], scratch: &mut [T], dst: bool) {
+        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+
+        let len = self.len();
+        let half_len = len / 2;
+
+        // the real FFT input is the even elements in order, followed by the odd elements in reverse order. the complex
+        // FFT buffer holds each pair of consecutive real FFT inputs in one complex element, so it's the same memory
+        // as the real FFT input
+        let (real_fft_input, fft_scratch) = scratch.split_at_mut(len);
+        let (evens, odds) = real_fft_input.split_at_mut(half_len);
+        for (even, pair) in evens.iter_mut().zip(buffer.chunks_exact(2)) {
+            *even = pair[0];
+        }
+        for (odd, pair) in odds.iter_mut().zip(buffer.chunks_exact(2).rev()) {
+            *odd = if dst { -pair[1] } else { pair[1] };
+        }
+
+        let fft_buffer = into_complex_mut(real_fft_input);
+        self.fft
+            .process_with_scratch(fft_buffer, into_complex_mut(fft_scratch));
+
+        // the even half of the input contributes the real part of the first complex FFT output, and the odd half
+        // contributes the imaginary part. output `half_len` is its own mirror, so it has no imaginary part to write
+        let first = fft_buffer[0];
+        let middle = Complex::from((first.re - first.im) * T::two()) * self.dct_twiddles[half_len];
+        buffer[if dst { len - 1 } else { 0 }] = first.re + first.im;
+        buffer[if dst { half_len - 1 } else { half_len }] = middle.re;
+
+        // rotates element `k` of the real FFT output, times two, into DCT2 outputs `k` and `len - k`. `k` must be in
+        // `1..half_len`
+        let mut write_output = |k: usize, doubled_output: Complex<T>| {
+            let rotated = doubled_output * self.dct_twiddles[k];
+            if dst {
+                buffer[len - 1 - k] = rotated.re;
+                buffer[k - 1] = -rotated.im;
+            } else {
+                buffer[k] = rotated.re;
+                buffer[len - k] = -rotated.im;
+            }
+        };
+
+        // for the rest, split the half-size FFT output into the real FFT output. outputs `k` and `half_len - k` are
+        // conjugates of the sum and difference of the same two terms, so we compute them together. if `k == half_len - k`, both
+        // writes compute the same value
+        for k in 1..=half_len / 2 {
+            let z_k = fft_buffer[k];
+            let z_mirror = fft_buffer[half_len - k].conj();
+
+            let even = z_k + z_mirror;
+            let odd = (z_k - z_mirror) * self.fft_twiddles[k];
+            write_output(k, even + odd);
+            write_output(half_len - k, (even - odd).conj());
+        }
+    }
+
+    // Computes the DCT3 of `buffer`, in-place. If `dst` is true, computes the DST3 instead, by reversing the input and
+    // negating the odd outputs. Each output is passed through `scale_output` as it's written
+    #[inline(always)]
+    fn process_type3<F: Fn(T) -> T>(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        dst: bool,
+        scale_output: F,
+    ) {
+        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+
+        let len = self.len();
+        let half_len = len / 2;
+
+        let (real_fft_output, fft_scratch) = scratch.split_at_mut(len);
+        let fft_buffer = into_complex_mut(real_fft_output);
+
+        // reconstruct element `k` of the real FFT spectrum that a DCT2 of the output would have produced, times 0.5.
+        // `k` must be in `1..=half_len`
+        let input = |k: usize| if dst { buffer[len - 1 - k] } else { buffer[k] };
+        let spectrum = |k: usize| {
+            Complex {
+                re: input(k),
+                im: -input(len - k),
+            } * self.dct_twiddles[k].conj()
+        };
+
+        // combine the spectrum into the spectrum of a half-size complex signal, conjugated so that the forward FFT
+        // computes an inverse FFT. as with the DCT2, elements `k` and `half_len - k` are computed together. the first
+        // element of the spectrum is real, and only depends on input 0
+        let v_first = Complex::from(input(0) * T::half());
+        let v_mirror = spectrum(half_len).conj();
+        let even = v_first + v_mirror;
+        let odd = v_first - v_mirror;
+        fft_buffer[0] = Complex {
+            re: even.re - odd.im,
+            im: -(even.im + odd.re),
+        };
+        for k in 1..=half_len / 2 {
+            let v_k = spectrum(k);
+            let v_mirror = spectrum(half_len - k).conj();
+
+            let even = v_k + v_mirror;
+            let odd = (v_k - v_mirror) * self.fft_twiddles[k].conj();
+            fft_buffer[k] = (even + odd).conj();
+            fft_buffer[half_len - k] = even - odd;
+        }
+
+        self.fft
+            .process_with_scratch(fft_buffer, into_complex_mut(fft_scratch));
+
+        // undo the conjugation, which leaves the real outputs as the even elements followed by the odd elements in
+        // reverse order
+        for element in fft_buffer.iter_mut() {
+            element.im = -element.im;
+        }
+        let (evens, odds) = real_fft_output.split_at(half_len);
+        for (even, pair) in evens.iter().zip(buffer.chunks_exact_mut(2)) {
+            pair[0] = scale_output(*even);
+        }
+        for (odd, pair) in odds.iter().zip(buffer.chunks_exact_mut(2).rev()) {
+            pair[1] = scale_output(if dst { -*odd } else { *odd });
+        }
+    }
+}
+
+impl<T: DctNum> Dct2<T> for Type2And3ConvertToRealFft<T> {
+    plan_hooks!(T);
+    fn process_dct2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        self.process_type2(buffer, scratch, false);
+    }
+}
+impl<T: DctNum> Dst2<T> for Type2And3ConvertToRealFft<T> {
+    plan_hooks!(T);
+    fn process_dst2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        self.process_type2(buffer, scratch, true);
+    }
+}
+impl<T: DctNum> Dct3<T> for Type2And3ConvertToRealFft<T> {
+    plan_hooks!(T);
+    fn process_dct3_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        self.process_type3(buffer, scratch, false, |element| element);
+    }
+    fn process_dct3_scaled(&self, buffer: &mut [T], scratch: &mut [T], scale: T) {
+        self.process_type3(buffer, scratch, false, |element| element * scale);
+    }
+}
+impl<T: DctNum> Dst3<T> for Type2And3ConvertToRealFft<T> {
+    plan_hooks!(T);
+    fn process_dst3_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        self.process_type3(buffer, scratch, true, |element| element);
+    }
+    fn process_dst3_scaled(&self, buffer: &mut [T], scratch: &mut [T], scale: T) {
+        self.process_type3(buffer, scratch, true, |element| element * scale);
+    }
+}
+impl<T: DctNum> TransformType2And3<T> for Type2And3ConvertToRealFft<T> {}
+impl<T> Length for Type2And3ConvertToRealFft<T> {
+    fn len(&self) -> usize {
+        (self.dct_twiddles.len() - 1) * 2
+    }
+}
+impl<T: DctNum> PlanIdentity<T> for Type2And3ConvertToRealFft<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Type2And3, self.len())
+    }
+}
+impl<T> MemoryUsage for Type2And3ConvertToRealFft<T> {
+    fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            twiddles_bytes: table_bytes(&self.dct_twiddles) + table_bytes(&self.fft_twiddles),
+            ..PlanMemory::default()
+        }
+    }
+}
+impl<T: DctNum> RequiredScratch for Type2And3ConvertToRealFft<T> {
+    fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        self.scratch_len / 2
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        fft_layout(self.len(), self.scratch_len)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algorithm::Type2And3Naive;
+
+    use crate::test_utils::{compare_float_vectors, random_signal};
+    use rustfft::FftPlanner;
+
+    type Transform = fn(&dyn TransformType2And3<f32>, &mut [f32]);
+
+    /// Verify that our fast implementation of all four transforms gives the same output as the naive version
+    #[test]
+    fn test_type2and3_via_real_fft() {
+        let mut fft_planner = FftPlanner::new();
+        for size in (2..40).step_by(2) {
+            let naive = Type2And3Naive::new(size);
+            let dct = Type2And3ConvertToRealFft::new(fft_planner.plan_fft_forward(size / 2));
+            assert_eq!(dct.len(), size);
+
+            let transforms: [(&str, Transform); 4] = [
+                ("dct2", |dct, buffer| dct.process_dct2(buffer)),
+                ("dst2", |dct, buffer| dct.process_dst2(buffer)),
+                ("dct3", |dct, buffer| dct.process_dct3(buffer)),
+                ("dst3", |dct, buffer| dct.process_dst3(buffer)),
+            ];
+            for (name, transform) in transforms.iter() {
+                let mut expected_buffer = random_signal(size);
+                let mut actual_buffer = expected_buffer.clone();
+
+                transform(&naive, &mut expected_buffer);
+                transform(&dct, &mut actual_buffer);
+
+                assert!(
+                    compare_float_vectors(&actual_buffer, &expected_buffer),
+                    "{}, len = {}",
+                    name,
+                    size
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,289 @@
+use std::sync::Arc;
+
+use rustfft::Length;
+
+use crate::common::{dct_error_inplace, derived_len, offset_complex_scratch_len};
+use crate::ScratchLayout;
+use crate::{Dct2, Dct3, Dct6And7, Dct8, Dst2, Dst3, TransformType2And3};
+use crate::{DctNum, RequiredScratch};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
+
+/// DCT2, DCT3, DST2, and DST3 implementation for odd sizes that splits the problem into a DCT6/DCT7 and a DCT8 of
+/// roughly half size, using only real arithmetic.
+///
+/// For a DCT2 of size `N = 2M + 1`, the even outputs are a DCT6 of size `M + 1` of the sums `x[n] + x[N - 1 - n]`,
+/// and the odd outputs are a DCT8 of size `M` of the differences `x[n] - x[N - 1 - n]`. The DCT3 uses the transpose of
+/// the same structure, with a DCT7 in place of the DCT6.
+///
+/// The problem size must be odd.
+///
+/// The speed of this algorithm depends entirely on the inner DCT6 and DCT8. Benchmarking shows that it's faster than
+/// `Type2And3ConvertToFft` when both are butterflies, so the planner uses it for sizes 5 and 7.
+///
+/// ~~~
+/// // Computes a DCT Type 2 of size 1235
+/// use std::sync::Arc;
+/// use rustdct::algorithm::{Dct6And7Naive, Dct8Naive, Type2And3ConvertToType6And8};
+/// use rustdct::Dct2;
+///
+/// let len = 1235;
+/// let dct6 = Arc::new(Dct6And7Naive::new(len / 2 + 1));
+/// let dct8 = Arc::new(Dct8Naive::new(len / 2));
+///
+/// let dct = Type2And3ConvertToType6And8::new(dct6, dct8);
+///
+/// let mut buffer = vec![0f32; len];
+/// dct.process_dct2(&mut buffer);
+/// ~~~
+pub struct Type2And3ConvertToType6And8<T> {
+    dct6: Arc<dyn Dct6And7<T>>,
+    dct8: Arc<dyn Dct8<T>>,
+    len: usize,
+    scratch_len: usize,
+}
+
+impl<T: DctNum> Type2And3ConvertToType6And8<T> {
+    /// Creates a new DCT2, DCT3, DST2, and DST3 context that will process signals of length `dct6.len() + dct8.len()`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dct6.len()` isn't equal to `dct8.len() + 1`.
+    pub fn new(dct6: Arc<dyn Dct6And7<T>>, dct8: Arc<dyn Dct8<T>>) -> Self {
+        assert_eq!(
+            dct6.len(),
+            dct8.len() + 1,
+            "dct6.len() must be dct8.len() + 1. Got dct6.len()={}, dct8.len()={}",
+            dct6.len(),
+            dct8.len()
+        );
+        let len = derived_len(dct8.len(), 2, 1, "DCT2");
+
+        // we use the caller's buffer as scratch for the inner DCTs. if they need more than that, reserve extra scratch space for them
+        let inner_scratch = dct6.get_scratch_len().max(dct8.get_scratch_len());
+        let scratch_len = if inner_scratch <= len {
+            len
+        } else {
+            len.checked_add(inner_scratch).expect(
+                "The scratch space required by Type2And3ConvertToType6And8's inner DCTs overflows usize",
+            )
+        };
+
+        Self {
+            dct6,
+            dct8,
+            len,
+            scratch_len,
+        }
+    }
+}
+
+impl<T: DctNum> Dct2<T> for Type2And3ConvertToType6And8<T> {
+    plan_hooks!(T);
+    fn process_dct2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+        let (self_scratch, extra_scratch) = scratch.split_at_mut(self.len());
+
+        let half_len = self.dct8.len();
+        let (sums, differences) = self_scratch.split_at_mut(half_len + 1);
+
+        for i in 0..half_len {
+            let lower = buffer[i];
+            let upper = buffer[buffer.len() - 1 - i];
+            sums[i] = lower + upper;
+            differences[i] = lower - upper;
+        }
+        // the middle element pairs with itself, and the DCT6 halves its last input, so double it
+        sums[half_len] = buffer[half_len] * T::two();
+
+        {
+            let inner_scratch = if !extra_scratch.is_empty() {
+                extra_scratch
+            } else {
+                &mut buffer[..]
+            };
+            self.dct6.process_dct6_with_scratch(sums, inner_scratch);
+            self.dct8
+                .process_dct8_with_scratch(differences, inner_scratch);
+        }
+
+        // the DCT6 computed the even outputs, and the DCT8 computed the odd outputs
+        for (i, element) in buffer.iter_mut().enumerate() {
+            *element = if i % 2 == 0 {
+                sums[i / 2]
+            } else {
+                differences[i / 2]
+            };
+        }
+    }
+}
+impl<T: DctNum> Dst2<T> for Type2And3ConvertToType6And8<T> {
+    plan_hooks!(T);
+    fn process_dst2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        for i in 0..(self.len() / 2) {
+            buffer[2 * i + 1] = buffer[2 * i + 1].neg();
+        }
+
+        self.process_dct2_with_scratch(buffer, scratch);
+
+        buffer.reverse();
+    }
+}
+impl<T: DctNum> Dct3<T> for Type2And3ConvertToType6And8<T> {
+    plan_hooks!(T);
+    fn process_dct3_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+        let (self_scratch, extra_scratch) = scratch.split_at_mut(self.len());
+
+        let half_len = self.dct8.len();
+        let (evens, odds) = self_scratch.split_at_mut(half_len + 1);
+
+        // the DCT7 halves its first input just like the DCT3 does, so the even inputs can be passed through as-is
+        for (i, element) in buffer.iter().enumerate() {
+            if i % 2 == 0 {
+                evens[i / 2] = *element;
+            } else {
+                odds[i / 2] = *element;
+            }
+        }
+
+        {
+            let inner_scratch = if !extra_scratch.is_empty() {
+                extra_scratch
+            } else {
+                &mut buffer[..]
+            };
+            self.dct6.process_dct7_with_scratch(evens, inner_scratch);
+            self.dct8.process_dct8_with_scratch(odds, inner_scratch);
+        }
+
+        for i in 0..half_len {
+            buffer[i] = evens[i] + odds[i];
+            buffer[self.len - 1 - i] = evens[i] - odds[i];
+        }
+        buffer[half_len] = evens[half_len];
+    }
+}
+impl<T: DctNum> Dst3<T> for Type2And3ConvertToType6And8<T> {
+    plan_hooks!(T);
+    fn process_dst3_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        buffer.reverse();
+
+        self.process_dct3_with_scratch(buffer, scratch);
+
+        for i in 0..(self.len() / 2) {
+            buffer[2 * i + 1] = buffer[2 * i + 1].neg();
+        }
+    }
+}
+impl<T: DctNum> TransformType2And3<T> for Type2And3ConvertToType6And8<T> {}
+impl<T: DctNum> PlanIdentity<T> for Type2And3ConvertToType6And8<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Type2And3, self.len())
+    }
+}
+impl<T: DctNum> MemoryUsage for Type2And3ConvertToType6And8<T> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            inner_plans_bytes: visitor.inner_plan(&self.dct6) + visitor.inner_plan(&self.dct8),
+            ..PlanMemory::default()
+        }
+    }
+}
+impl<T: DctNum> RequiredScratch for Type2And3ConvertToType6And8<T> {
+    fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        // the inner DCTs only get the scratch after this plan's own `len` elements when they need more than `len`
+        let inner_complex_len = self
+            .dct6
+            .get_complex_scratch_len()
+            .max(self.dct8.get_complex_scratch_len());
+        if self.scratch_len > self.len {
+            offset_complex_scratch_len(self.len, inner_complex_len)
+        } else {
+            0
+        }
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        let layout = ScratchLayout::new().with_region("sub-problems", self.len, false);
+        if self.scratch_len == self.len {
+            layout
+        } else if self.dct6.get_scratch_len() >= self.dct8.get_scratch_len() {
+            layout.with_inner(self.dct6.scratch_layout())
+        } else {
+            layout.with_inner(self.dct8.scratch_layout())
+        }
+    }
+}
+impl<T> Length for Type2And3ConvertToType6And8<T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algorithm::{Dct6And7Naive, Dct8Naive, Type2And3Naive};
+    use crate::test_utils::{compare_float_vectors, random_signal};
+
+    /// Verify that our fast implementation of the DCT2, DCT3, DST2, and DST3 gives the same output as the naive version,
+    /// for many different inputs
+    #[test]
+    fn test_type6and8_odd() {
+        for half_len in 0..15 {
+            let len = half_len * 2 + 1;
+            let naive = Type2And3Naive::new(len);
+            let dct = Type2And3ConvertToType6And8::new(
+                Arc::new(Dct6And7Naive::new(half_len + 1)),
+                Arc::new(Dct8Naive::new(half_len)),
+            );
+            assert_eq!(dct.len(), len);
+
+            let input = random_signal(len);
+            let mut scratch = vec![0f32; dct.get_scratch_len()];
+
+            let mut expected = input.clone();
+            let mut actual = input.clone();
+            naive.process_dct2(&mut expected);
+            dct.process_dct2_with_scratch(&mut actual, &mut scratch);
+            assert!(
+                compare_float_vectors(&expected, &actual),
+                "dct2, len = {}",
+                len
+            );
+
+            let mut expected = input.clone();
+            let mut actual = input.clone();
+            naive.process_dst2(&mut expected);
+            dct.process_dst2_with_scratch(&mut actual, &mut scratch);
+            assert!(
+                compare_float_vectors(&expected, &actual),
+                "dst2, len = {}",
+                len
+            );
+
+            let mut expected = input.clone();
+            let mut actual = input.clone();
+            naive.process_dct3(&mut expected);
+            dct.process_dct3_with_scratch(&mut actual, &mut scratch);
+            assert!(
+                compare_float_vectors(&expected, &actual),
+                "dct3, len = {}",
+                len
+            );
+
+            let mut expected = input.clone();
+            let mut actual = input.clone();
+            naive.process_dst3(&mut expected);
+            dct.process_dst3_with_scratch(&mut actual, &mut scratch);
+            assert!(
+                compare_float_vectors(&expected, &actual),
+                "dst3, len = {}",
+                len
+            );
+        }
+    }
+}
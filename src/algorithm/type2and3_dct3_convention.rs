@@ -0,0 +1,332 @@
+use std::sync::Arc;
+
+use rustfft::Length;
+
+use crate::common::{dct_error_inplace, Cancelled};
+use crate::ScratchLayout;
+use crate::{Dct2, Dct3, Dct3Convention, DctNum, Dst2, Dst3, RequiredScratch, TransformType2And3};
+use crate::{MemoryUsage, MemoryVisitor, PlanIdentity, PlanKey, PlanMemory};
+
+/// Adapter that computes the DCT3 of an inner plan with the definition of another library, described by a
+/// [`Dct3Convention`](../enum.Dct3Convention.html).
+///
+/// The DCT3's inputs are scaled in a O(n) pass over the buffer. The DCT2, DST2, and DST3 are forwarded to the inner
+/// plan unchanged.
+///
+/// ~~~
+/// // Computes a DCT3 of size 1234 that matches MATLAB's idct
+/// use rustdct::algorithm::Type2And3WithDct3Convention;
+/// use rustdct::{DctPlanner, Dct3Convention, Dct3};
+///
+/// let len = 1234;
+/// let mut planner = DctPlanner::new();
+/// let dct = Type2And3WithDct3Convention::new(planner.plan_dct3(len), Dct3Convention::Matlab);
+///
+/// let mut buffer = vec![0f32; len];
+/// dct.process_dct3(&mut buffer);
+/// ~~~
+pub struct Type2And3WithDct3Convention<T> {
+    inner: Arc<dyn TransformType2And3<T>>,
+    convention: Dct3Convention,
+
+    // the scale of the first element, and the scale of every other element. None if nothing needs to be scaled
+    dct3_input_scales: Option<(T, T)>,
+}
+
+impl<T: DctNum> Type2And3WithDct3Convention<T> {
+    /// Creates a new adapter that computes the DCT3 with `inner`, and scales it to match `convention`.
+    pub fn new(inner: Arc<dyn TransformType2And3<T>>, convention: Dct3Convention) -> Self {
+        let len = inner.len();
+        Self {
+            dct3_input_scales: convention
+                .dct3_input_scales(len)
+                .map(|(first_scale, scale)| {
+                    (
+                        T::from_f64(first_scale).unwrap(),
+                        T::from_f64(scale).unwrap(),
+                    )
+                }),
+            inner,
+            convention,
+        }
+    }
+
+    /// Returns the convention that this adapter's DCT3 follows
+    pub fn convention(&self) -> Dct3Convention {
+        self.convention
+    }
+
+    // Checks the buffer length before it's scaled, since the inner plan only checks it afterwards
+    fn validate(&self, buffer: &[T], scratch: &[T]) -> bool {
+        if crate::common::CHECK_BUFFER_LENS && buffer.len() != self.len() {
+            dct_error_inplace(
+                buffer.len(),
+                scratch.len(),
+                self.len(),
+                self.get_scratch_len(),
+            );
+            return false;
+        }
+        true
+    }
+
+    // Multiplies the first element of `buffer` by the first scale, and every other element by the second scale
+    fn scale_input(&self, buffer: &mut [T]) {
+        if let (Some((first_scale, scale)), Some((first, rest))) =
+            (self.dct3_input_scales, buffer.split_first_mut())
+        {
+            *first = *first * first_scale;
+            for element in rest.iter_mut() {
+                *element = *element * scale;
+            }
+        }
+    }
+}
+
+impl<T: DctNum> Dct2<T> for Type2And3WithDct3Convention<T> {
+    plan_hooks!(T);
+    fn process_dct2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        self.inner.process_dct2_with_scratch(buffer, scratch);
+    }
+    fn process_dct2_from(&self, input: &[T], output: &mut [T], scratch: &mut [T]) {
+        self.inner.process_dct2_from(input, output, scratch);
+    }
+    fn process_dct2_two(&self, a: &mut [T], b: &mut [T], scratch: &mut [T]) {
+        self.inner.process_dct2_two(a, b, scratch);
+    }
+    fn process_dct2_cancellable(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        should_continue: &mut dyn FnMut() -> bool,
+    ) -> Result<(), Cancelled> {
+        self.inner
+            .process_dct2_cancellable(buffer, scratch, should_continue)
+    }
+}
+impl<T: DctNum> Dst2<T> for Type2And3WithDct3Convention<T> {
+    plan_hooks!(T);
+    fn process_dst2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        self.inner.process_dst2_with_scratch(buffer, scratch);
+    }
+    fn process_dst2_cancellable(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        should_continue: &mut dyn FnMut() -> bool,
+    ) -> Result<(), Cancelled> {
+        self.inner
+            .process_dst2_cancellable(buffer, scratch, should_continue)
+    }
+}
+impl<T: DctNum> Dct3<T> for Type2And3WithDct3Convention<T> {
+    plan_hooks!(T);
+    fn process_dct3_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        if self.validate(buffer, scratch) {
+            self.scale_input(buffer);
+            self.inner.process_dct3_with_scratch(buffer, scratch);
+        }
+    }
+    fn process_dct3_scaled(&self, buffer: &mut [T], scratch: &mut [T], output_scale: T) {
+        if self.validate(buffer, scratch) {
+            self.scale_input(buffer);
+            self.inner
+                .process_dct3_scaled(buffer, scratch, output_scale);
+        }
+    }
+    fn process_dct3_cancellable(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        should_continue: &mut dyn FnMut() -> bool,
+    ) -> Result<(), Cancelled> {
+        if !self.validate(buffer, scratch) {
+            return Ok(());
+        }
+        self.scale_input(buffer);
+        self.inner
+            .process_dct3_cancellable(buffer, scratch, should_continue)
+    }
+}
+impl<T: DctNum> Dst3<T> for Type2And3WithDct3Convention<T> {
+    plan_hooks!(T);
+    fn process_dst3_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        self.inner.process_dst3_with_scratch(buffer, scratch);
+    }
+    fn process_dst3_scaled(&self, buffer: &mut [T], scratch: &mut [T], scale: T) {
+        self.inner.process_dst3_scaled(buffer, scratch, scale);
+    }
+    fn process_dst3_cancellable(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        should_continue: &mut dyn FnMut() -> bool,
+    ) -> Result<(), Cancelled> {
+        self.inner
+            .process_dst3_cancellable(buffer, scratch, should_continue)
+    }
+}
+impl<T: DctNum> TransformType2And3<T> for Type2And3WithDct3Convention<T> {
+    fn process_dct2_dst2(
+        &self,
+        input: &[T],
+        dct_output: &mut [T],
+        dst_output: &mut [T],
+        scratch: &mut [T],
+    ) {
+        self.inner
+            .process_dct2_dst2(input, dct_output, dst_output, scratch);
+    }
+    fn process_dct2_from_pcm(&self, input: &[i16], output: &mut [T], scratch: &mut [T]) {
+        self.inner.process_dct2_from_pcm(input, output, scratch);
+    }
+}
+impl<T: DctNum> PlanIdentity<T> for Type2And3WithDct3Convention<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        self.inner.plan_key().with_dct3_convention(self.convention)
+    }
+}
+impl<T: DctNum> MemoryUsage for Type2And3WithDct3Convention<T> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            inner_plans_bytes: visitor.inner_plan(&self.inner),
+            ..PlanMemory::default()
+        }
+    }
+}
+impl<T: DctNum> RequiredScratch for Type2And3WithDct3Convention<T> {
+    fn get_scratch_len(&self) -> usize {
+        self.inner.get_scratch_len()
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        self.inner.get_complex_scratch_len()
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        self.inner.scratch_layout()
+    }
+}
+impl<T: DctNum> Length for Type2And3WithDct3Convention<T> {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algorithm::Type2And3Naive;
+    use crate::test_utils::{compare_float_vectors, random_signal};
+    use std::f64::consts::PI;
+
+    // sum(weight(n) * x[n] * cos(pi * n * (k + 0.5) / len)), where weight(0) is `first_weight`, and every other weight
+    // is `weight`
+    fn weighted_dct3(input: &[f64], first_weight: f64, weight: f64) -> Vec<f64> {
+        let len = input.len() as f64;
+        (0..input.len())
+            .map(|k| {
+                input.iter().enumerate().fold(0.0, |sum, (n, x)| {
+                    let w = if n == 0 { first_weight } else { weight };
+                    sum + w * x * (PI * n as f64 * (k as f64 + 0.5) / len).cos()
+                })
+            })
+            .collect()
+    }
+
+    // MATLAB's dct, which is the orthonormal DCT2, and SciPy's DCT2 with norm="ortho"
+    fn orthonormal_dct2(input: &[f64]) -> Vec<f64> {
+        let len = input.len() as f64;
+        (0..input.len())
+            .map(|k| {
+                let weight = if k == 0 {
+                    (1.0 / len).sqrt()
+                } else {
+                    (2.0 / len).sqrt()
+                };
+                weight
+                    * input.iter().enumerate().fold(0.0, |sum, (n, x)| {
+                        sum + x * (PI * (n as f64 + 0.5) * k as f64 / len).cos()
+                    })
+            })
+            .collect()
+    }
+
+    fn to_f32(values: &[f64]) -> Vec<f32> {
+        values.iter().map(|value| *value as f32).collect()
+    }
+
+    /// Verify each convention against its library's definition
+    #[test]
+    fn test_dct3_conventions() {
+        for len in 1..20 {
+            let input = random_signal(len);
+            let input_f64: Vec<f64> = input.iter().map(|x| *x as f64).collect();
+            let inner: Arc<dyn TransformType2And3<f32>> = Arc::new(Type2And3Naive::new(len));
+
+            // FFTW's REDFT01, and SciPy's DCT3 with norm="backward": x[0] + 2 * sum(x[n] * cos(...)) over n > 0
+            for &convention in &[Dct3Convention::Fftw, Dct3Convention::Scipy] {
+                let dct = Type2And3WithDct3Convention::new(Arc::clone(&inner), convention);
+                let mut actual = input.clone();
+                dct.process_dct3(&mut actual);
+                let expected = weighted_dct3(&input_f64, 1.0, 2.0);
+                assert!(
+                    compare_float_vectors(&to_f32(&expected), &actual),
+                    "{:?}, len = {}",
+                    convention,
+                    len
+                );
+            }
+
+            // the textbook definition, without any weighting
+            let dct =
+                Type2And3WithDct3Convention::new(Arc::clone(&inner), Dct3Convention::Unweighted);
+            let mut actual = input.clone();
+            dct.process_dct3(&mut actual);
+            let expected = weighted_dct3(&input_f64, 1.0, 1.0);
+            assert!(
+                compare_float_vectors(&to_f32(&expected), &actual),
+                "unweighted, len = {}",
+                len
+            );
+
+            // MATLAB's idct weights the first input by sqrt(1/len) and the others by sqrt(2/len), and inverts its dct
+            let dct = Type2And3WithDct3Convention::new(Arc::clone(&inner), Dct3Convention::Matlab);
+            let mut actual = input.clone();
+            dct.process_dct3(&mut actual);
+            let len_f64 = len as f64;
+            let expected =
+                weighted_dct3(&input_f64, (1.0 / len_f64).sqrt(), (2.0 / len_f64).sqrt());
+            assert!(
+                compare_float_vectors(&to_f32(&expected), &actual),
+                "matlab, len = {}",
+                len
+            );
+            let mut roundtrip = to_f32(&orthonormal_dct2(&input_f64));
+            dct.process_dct3(&mut roundtrip);
+            assert!(
+                compare_float_vectors(&input, &roundtrip),
+                "matlab roundtrip, len = {}",
+                len
+            );
+
+            // this crate's convention, and the other transforms, are unchanged
+            let dct = Type2And3WithDct3Convention::new(Arc::clone(&inner), Dct3Convention::Wang);
+            let mut expected = input.clone();
+            let mut actual = input.clone();
+            inner.process_dct3(&mut expected);
+            dct.process_dct3(&mut actual);
+            assert_eq!(expected, actual);
+            assert!(
+                compare_float_vectors(&to_f32(&weighted_dct3(&input_f64, 0.5, 1.0)), &actual),
+                "wang, len = {}",
+                len
+            );
+
+            let dct = Type2And3WithDct3Convention::new(Arc::clone(&inner), Dct3Convention::Fftw);
+            let mut expected = input.clone();
+            let mut actual = input.clone();
+            inner.process_dst3(&mut expected);
+            dct.process_dst3(&mut actual);
+            assert_eq!(expected, actual);
+        }
+    }
+}
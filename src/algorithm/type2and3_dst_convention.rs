@@ -0,0 +1,330 @@
+use std::sync::Arc;
+
+use rustfft::Length;
+
+use crate::common::{dct_error_inplace, Cancelled};
+use crate::ScratchLayout;
+use crate::{Dct2, Dct3, DctNum, Dst2, Dst3, DstConvention, RequiredScratch, TransformType2And3};
+use crate::{MemoryUsage, MemoryVisitor, PlanIdentity, PlanKey, PlanMemory};
+
+/// Adapter that computes the DST2 and DST3 of an inner plan with the definitions of another library, described by a
+/// [`DstConvention`](../enum.DstConvention.html).
+///
+/// The DST2's outputs and the DST3's inputs are scaled in a O(n) pass over the buffer. The DCT2 and DCT3 are forwarded
+/// to the inner plan unchanged.
+///
+/// ~~~
+/// // Computes a DST2 of size 1234 that matches FFTW's FFTW_RODFT10
+/// use rustdct::algorithm::Type2And3WithDstConvention;
+/// use rustdct::{DctPlanner, DstConvention, Dst2};
+///
+/// let len = 1234;
+/// let mut planner = DctPlanner::new();
+/// let dst = Type2And3WithDstConvention::new(planner.plan_dst2(len), DstConvention::Fftw);
+///
+/// let mut buffer = vec![0f32; len];
+/// dst.process_dst2(&mut buffer);
+/// ~~~
+pub struct Type2And3WithDstConvention<T> {
+    inner: Arc<dyn TransformType2And3<T>>,
+    convention: DstConvention,
+
+    // the scale of every element but the last, and the scale of the last element. None if nothing needs to be scaled
+    dst2_output_scales: Option<(T, T)>,
+    dst3_input_scales: Option<(T, T)>,
+}
+
+impl<T: DctNum> Type2And3WithDstConvention<T> {
+    /// Creates a new adapter that computes the DST2 and DST3 with `inner`, and scales them to match `convention`.
+    pub fn new(inner: Arc<dyn TransformType2And3<T>>, convention: DstConvention) -> Self {
+        let len = inner.len();
+        let to_t = |(scale, last_scale): (f64, f64)| {
+            (
+                T::from_f64(scale).unwrap(),
+                T::from_f64(last_scale).unwrap(),
+            )
+        };
+
+        Self {
+            dst2_output_scales: convention.dst2_output_scales(len).map(to_t),
+            dst3_input_scales: convention.dst3_input_scales(len).map(to_t),
+            inner,
+            convention,
+        }
+    }
+
+    /// Returns the convention that this adapter's DST2 and DST3 follow
+    pub fn convention(&self) -> DstConvention {
+        self.convention
+    }
+
+    // Checks the buffer length before it's scaled, since the inner plan only checks it afterwards
+    fn validate(&self, buffer: &[T], scratch: &[T]) -> bool {
+        if crate::common::CHECK_BUFFER_LENS && buffer.len() != self.len() {
+            dct_error_inplace(
+                buffer.len(),
+                scratch.len(),
+                self.len(),
+                self.get_scratch_len(),
+            );
+            return false;
+        }
+        true
+    }
+}
+
+// Multiplies every element of `buffer` but the last by `scale`, and the last element by `last_scale`
+fn scale<T: DctNum>(buffer: &mut [T], scales: Option<(T, T)>) {
+    if let (Some((scale, last_scale)), Some((last, rest))) = (scales, buffer.split_last_mut()) {
+        for element in rest.iter_mut() {
+            *element = *element * scale;
+        }
+        *last = *last * last_scale;
+    }
+}
+
+impl<T: DctNum> Dct2<T> for Type2And3WithDstConvention<T> {
+    plan_hooks!(T);
+    fn process_dct2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        self.inner.process_dct2_with_scratch(buffer, scratch);
+    }
+    fn process_dct2_from(&self, input: &[T], output: &mut [T], scratch: &mut [T]) {
+        self.inner.process_dct2_from(input, output, scratch);
+    }
+    fn process_dct2_two(&self, a: &mut [T], b: &mut [T], scratch: &mut [T]) {
+        self.inner.process_dct2_two(a, b, scratch);
+    }
+    fn process_dct2_cancellable(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        should_continue: &mut dyn FnMut() -> bool,
+    ) -> Result<(), Cancelled> {
+        self.inner
+            .process_dct2_cancellable(buffer, scratch, should_continue)
+    }
+}
+impl<T: DctNum> Dst2<T> for Type2And3WithDstConvention<T> {
+    plan_hooks!(T);
+    fn process_dst2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        self.inner.process_dst2_with_scratch(buffer, scratch);
+        scale(buffer, self.dst2_output_scales);
+    }
+    fn process_dst2_cancellable(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        should_continue: &mut dyn FnMut() -> bool,
+    ) -> Result<(), Cancelled> {
+        self.inner
+            .process_dst2_cancellable(buffer, scratch, should_continue)?;
+        scale(buffer, self.dst2_output_scales);
+        Ok(())
+    }
+}
+impl<T: DctNum> Dct3<T> for Type2And3WithDstConvention<T> {
+    plan_hooks!(T);
+    fn process_dct3_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        self.inner.process_dct3_with_scratch(buffer, scratch);
+    }
+    fn process_dct3_scaled(&self, buffer: &mut [T], scratch: &mut [T], scale: T) {
+        self.inner.process_dct3_scaled(buffer, scratch, scale);
+    }
+    fn process_dct3_from(&self, input: &[T], output: &mut [T], scratch: &mut [T]) {
+        self.inner.process_dct3_from(input, output, scratch);
+    }
+    fn process_dct3_cancellable(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        should_continue: &mut dyn FnMut() -> bool,
+    ) -> Result<(), Cancelled> {
+        self.inner
+            .process_dct3_cancellable(buffer, scratch, should_continue)
+    }
+}
+impl<T: DctNum> Dst3<T> for Type2And3WithDstConvention<T> {
+    plan_hooks!(T);
+    fn process_dst3_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        if self.validate(buffer, scratch) {
+            scale(buffer, self.dst3_input_scales);
+            self.inner.process_dst3_with_scratch(buffer, scratch);
+        }
+    }
+    fn process_dst3_scaled(&self, buffer: &mut [T], scratch: &mut [T], output_scale: T) {
+        if self.validate(buffer, scratch) {
+            scale(buffer, self.dst3_input_scales);
+            self.inner
+                .process_dst3_scaled(buffer, scratch, output_scale);
+        }
+    }
+    fn process_dst3_cancellable(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        should_continue: &mut dyn FnMut() -> bool,
+    ) -> Result<(), Cancelled> {
+        if !self.validate(buffer, scratch) {
+            return Ok(());
+        }
+        scale(buffer, self.dst3_input_scales);
+        self.inner
+            .process_dst3_cancellable(buffer, scratch, should_continue)
+    }
+}
+impl<T: DctNum> TransformType2And3<T> for Type2And3WithDstConvention<T> {
+    fn process_dct2_dst2(
+        &self,
+        input: &[T],
+        dct_output: &mut [T],
+        dst_output: &mut [T],
+        scratch: &mut [T],
+    ) {
+        self.inner
+            .process_dct2_dst2(input, dct_output, dst_output, scratch);
+        scale(dst_output, self.dst2_output_scales);
+    }
+    fn process_dct2_from_pcm(&self, input: &[i16], output: &mut [T], scratch: &mut [T]) {
+        self.inner.process_dct2_from_pcm(input, output, scratch);
+    }
+}
+impl<T: DctNum> PlanIdentity<T> for Type2And3WithDstConvention<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        self.inner.plan_key().with_dst_convention(self.convention)
+    }
+}
+impl<T: DctNum> MemoryUsage for Type2And3WithDstConvention<T> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            inner_plans_bytes: visitor.inner_plan(&self.inner),
+            ..PlanMemory::default()
+        }
+    }
+}
+impl<T: DctNum> RequiredScratch for Type2And3WithDstConvention<T> {
+    fn get_scratch_len(&self) -> usize {
+        self.inner.get_scratch_len()
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        self.inner.get_complex_scratch_len()
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        self.inner.scratch_layout()
+    }
+}
+impl<T: DctNum> Length for Type2And3WithDstConvention<T> {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algorithm::Type2And3Naive;
+    use crate::test_utils::{compare_float_vectors, random_signal};
+    use std::f64::consts::PI;
+
+    // FFTW's RODFT10, and SciPy's DST2 with norm="backward"
+    fn fftw_dst2(input: &[f64]) -> Vec<f64> {
+        let len = input.len() as f64;
+        (0..input.len())
+            .map(|k| {
+                input.iter().enumerate().fold(0.0, |sum, (n, x)| {
+                    sum + 2.0 * x * (PI * (n as f64 + 0.5) * (k as f64 + 1.0) / len).sin()
+                })
+            })
+            .collect()
+    }
+
+    // FFTW's RODFT01, and SciPy's DST3 with norm="backward"
+    fn fftw_dst3(input: &[f64]) -> Vec<f64> {
+        let len = input.len();
+        (0..len)
+            .map(|k| {
+                let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+                let sum = input[..len - 1]
+                    .iter()
+                    .enumerate()
+                    .fold(0.0, |sum, (n, x)| {
+                        sum + 2.0
+                            * x
+                            * (PI * (n as f64 + 1.0) * (k as f64 + 0.5) / len as f64).sin()
+                    });
+                sign * input[len - 1] + sum
+            })
+            .collect()
+    }
+
+    // The orthonormal DST2, which is SciPy's DST2 with norm="ortho"
+    fn orthonormal_dst2(input: &[f64]) -> Vec<f64> {
+        let len = input.len() as f64;
+        let mut output = fftw_dst2(input);
+        let (last, rest) = output.split_last_mut().unwrap();
+        for element in rest.iter_mut() {
+            *element *= (1.0 / (2.0 * len)).sqrt();
+        }
+        *last *= (1.0 / (4.0 * len)).sqrt();
+        output
+    }
+
+    fn to_f32(values: &[f64]) -> Vec<f32> {
+        values.iter().map(|value| *value as f32).collect()
+    }
+
+    /// Verify each convention against its library's definition
+    #[test]
+    fn test_dst_conventions() {
+        for len in 1..20 {
+            let input = random_signal(len);
+            let input_f64: Vec<f64> = input.iter().map(|x| *x as f64).collect();
+            let inner: Arc<dyn TransformType2And3<f32>> = Arc::new(Type2And3Naive::new(len));
+
+            for &convention in &[DstConvention::Fftw, DstConvention::Scipy] {
+                let dst = Type2And3WithDstConvention::new(Arc::clone(&inner), convention);
+
+                let mut dst2 = input.clone();
+                dst.process_dst2(&mut dst2);
+                assert!(
+                    compare_float_vectors(&to_f32(&fftw_dst2(&input_f64)), &dst2),
+                    "{:?} dst2, len = {}",
+                    convention,
+                    len
+                );
+
+                let mut dst3 = input.clone();
+                dst.process_dst3(&mut dst3);
+                assert!(
+                    compare_float_vectors(&to_f32(&fftw_dst3(&input_f64)), &dst3),
+                    "{:?} dst3, len = {}",
+                    convention,
+                    len
+                );
+            }
+
+            // the orthonormal DST3 inverts the orthonormal DST2
+            let dst = Type2And3WithDstConvention::new(Arc::clone(&inner), DstConvention::Matlab);
+            let mut buffer = input.clone();
+            dst.process_dst2(&mut buffer);
+            assert!(
+                compare_float_vectors(&to_f32(&orthonormal_dst2(&input_f64)), &buffer),
+                "matlab dst2, len = {}",
+                len
+            );
+            dst.process_dst3(&mut buffer);
+            assert!(
+                compare_float_vectors(&input, &buffer),
+                "matlab dst3, len = {}",
+                len
+            );
+
+            // the DCTs and this crate's convention are unchanged
+            let dst = Type2And3WithDstConvention::new(Arc::clone(&inner), DstConvention::Wang);
+            let mut expected = input.clone();
+            let mut actual = input.clone();
+            inner.process_dst2(&mut expected);
+            dst.process_dst2(&mut actual);
+            assert_eq!(expected, actual);
+        }
+    }
+}
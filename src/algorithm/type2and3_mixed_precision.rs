@@ -0,0 +1,258 @@
+use std::sync::Arc;
+
+use rustfft::Length;
+
+use crate::common::{dct_error_inplace, derived_len, with_f64_scratch};
+use crate::RequiredScratch;
+use crate::ScratchLayout;
+use crate::{Dct2, Dct3, Dst2, Dst3, TransformType2And3};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
+
+/// DCT2, DST2, DCT3, and DST3 implementation that takes `f32` inputs and outputs, but computes the transform in `f64`
+///
+/// This wraps an `f64` instance of any other `TransformType2And3` algorithm. The input is converted to `f64`,
+/// transformed with the inner algorithm, and rounded back to `f32`, so the only `f32` rounding error is in the final
+/// output. This is more accurate than running the same algorithm in `f32`, especially for large naive transforms,
+/// at the cost of some speed and twice as much scratch space.
+///
+/// ~~~
+/// // Computes a mixed-precision DCT2 of size 1234
+/// use std::sync::Arc;
+/// use rustdct::{Dct2, DctPlanner};
+/// use rustdct::algorithm::Type2And3MixedPrecision;
+///
+/// let mut f64_planner = DctPlanner::<f64>::new();
+/// let dct = Type2And3MixedPrecision::new(f64_planner.plan_dct2(1234));
+///
+/// let mut buffer = vec![0f32; 1234];
+/// dct.process_dct2(&mut buffer);
+/// ~~~
+pub struct Type2And3MixedPrecision {
+    inner: Arc<dyn TransformType2And3<f64>>,
+    f64_scratch_len: usize,
+    scratch_len: usize,
+}
+
+impl Type2And3MixedPrecision {
+    /// Creates a new mixed-precision DCT2, DCT3, DST2, and DST3 context that will process signals of length `inner.len()`,
+    /// using `inner` to compute the transform.
+    pub fn new(inner: Arc<dyn TransformType2And3<f64>>) -> Self {
+        let f64_scratch_len = inner
+            .len()
+            .checked_add(inner.get_scratch_len())
+            .expect("The scratch length of the inner DCT doesn't fit in a usize");
+
+        // Each f64 takes up the space of two f32s, and we might need to skip one f32 to align the f64s
+        let scratch_len = derived_len(f64_scratch_len, 2, 1, "scratch");
+
+        Self {
+            inner,
+            f64_scratch_len,
+            scratch_len,
+        }
+    }
+
+    // Converts `buffer` to f64, runs `process` on it with the rest of the f64 scratch, and converts the result back to f32
+    fn process_f64<F: Fn(&dyn TransformType2And3<f64>, &mut [f64], &mut [f64])>(
+        &self,
+        buffer: &mut [f32],
+        scratch: &mut [f32],
+        process: F,
+    ) {
+        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+
+        with_f64_scratch(scratch, self.f64_scratch_len, |f64_scratch| {
+            let (f64_buffer, inner_scratch) = f64_scratch.split_at_mut(self.len());
+            for (f64_element, element) in f64_buffer.iter_mut().zip(buffer.iter()) {
+                *f64_element = *element as f64;
+            }
+
+            process(self.inner.as_ref(), f64_buffer, inner_scratch);
+
+            for (element, f64_element) in buffer.iter_mut().zip(f64_buffer.iter()) {
+                *element = *f64_element as f32;
+            }
+        });
+    }
+}
+
+impl Dct2<f32> for Type2And3MixedPrecision {
+    plan_hooks!(f32);
+    fn process_dct2_with_scratch(&self, buffer: &mut [f32], scratch: &mut [f32]) {
+        self.process_f64(buffer, scratch, |inner, buffer, scratch| {
+            inner.process_dct2_with_scratch(buffer, scratch)
+        });
+    }
+}
+impl Dst2<f32> for Type2And3MixedPrecision {
+    plan_hooks!(f32);
+    fn process_dst2_with_scratch(&self, buffer: &mut [f32], scratch: &mut [f32]) {
+        self.process_f64(buffer, scratch, |inner, buffer, scratch| {
+            inner.process_dst2_with_scratch(buffer, scratch)
+        });
+    }
+}
+impl Dct3<f32> for Type2And3MixedPrecision {
+    plan_hooks!(f32);
+    fn process_dct3_with_scratch(&self, buffer: &mut [f32], scratch: &mut [f32]) {
+        self.process_f64(buffer, scratch, |inner, buffer, scratch| {
+            inner.process_dct3_with_scratch(buffer, scratch)
+        });
+    }
+    fn process_dct3_scaled(&self, buffer: &mut [f32], scratch: &mut [f32], scale: f32) {
+        self.process_f64(buffer, scratch, |inner, buffer, scratch| {
+            inner.process_dct3_scaled(buffer, scratch, scale as f64)
+        });
+    }
+}
+impl Dst3<f32> for Type2And3MixedPrecision {
+    plan_hooks!(f32);
+    fn process_dst3_with_scratch(&self, buffer: &mut [f32], scratch: &mut [f32]) {
+        self.process_f64(buffer, scratch, |inner, buffer, scratch| {
+            inner.process_dst3_with_scratch(buffer, scratch)
+        });
+    }
+    fn process_dst3_scaled(&self, buffer: &mut [f32], scratch: &mut [f32], scale: f32) {
+        self.process_f64(buffer, scratch, |inner, buffer, scratch| {
+            inner.process_dst3_scaled(buffer, scratch, scale as f64)
+        });
+    }
+}
+impl TransformType2And3<f32> for Type2And3MixedPrecision {}
+impl PlanIdentity<f32> for Type2And3MixedPrecision {
+    fn plan_key(&self) -> PlanKey<f32> {
+        PlanKey::new(PlanKind::Type2And3, self.len())
+    }
+}
+impl MemoryUsage for Type2And3MixedPrecision {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            inner_plans_bytes: visitor.inner_plan(&self.inner),
+            ..PlanMemory::default()
+        }
+    }
+}
+impl RequiredScratch for Type2And3MixedPrecision {
+    fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        // the inner plan's buffer and scratch are `f64`s packed into the `f32` scratch, so they can't be described as
+        // regions of it
+        ScratchLayout::new().with_region("f64 scratch", self.scratch_len, false)
+    }
+}
+impl Length for Type2And3MixedPrecision {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algorithm::{Type2And3Naive, Type2And3SplitRadix};
+    use crate::test_utils::{compare_float_vectors, random_signal};
+    use crate::DctPlanner;
+
+    fn max_error(expected: &[f64], actual: &[f32]) -> f64 {
+        expected
+            .iter()
+            .zip(actual.iter())
+            .map(|(e, a)| (e - *a as f64).abs())
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn test_mixed_precision_matches_f32() {
+        for len in 1..20 {
+            let dct = Type2And3MixedPrecision::new(Arc::new(Type2And3Naive::new(len)));
+            let naive = Type2And3Naive::new(len);
+
+            let input = random_signal(len);
+            let mut scratch = vec![0f32; dct.get_scratch_len()];
+
+            let mut expected = input.clone();
+            let mut actual = input.clone();
+            naive.process_dct2(&mut expected);
+            dct.process_dct2_with_scratch(&mut actual, &mut scratch);
+            assert!(compare_float_vectors(&expected, &actual), "len = {}", len);
+
+            let mut expected = input.clone();
+            let mut actual = input.clone();
+            naive.process_dst2(&mut expected);
+            dct.process_dst2_with_scratch(&mut actual, &mut scratch);
+            assert!(compare_float_vectors(&expected, &actual), "len = {}", len);
+
+            let mut expected = input.clone();
+            let mut actual = input.clone();
+            naive.process_dct3(&mut expected);
+            dct.process_dct3_with_scratch(&mut actual, &mut scratch);
+            assert!(compare_float_vectors(&expected, &actual), "len = {}", len);
+
+            let mut expected = input.clone();
+            let mut actual = input.clone();
+            naive.process_dst3(&mut expected);
+            dct.process_dst3_with_scratch(&mut actual, &mut scratch);
+            assert!(compare_float_vectors(&expected, &actual), "len = {}", len);
+        }
+    }
+
+    /// Compare the accuracy of f32 and mixed-precision transforms against an f64 reference
+    #[test]
+    fn test_mixed_precision_accuracy() {
+        let mut f32_planner = DctPlanner::<f32>::new();
+        let mut f64_planner = DctPlanner::<f64>::new();
+
+        let len = 2048;
+        let input = random_signal(len);
+        let f64_input: Vec<f64> = input.iter().map(|x| *x as f64).collect();
+
+        let mut reference = f64_input.clone();
+        Type2And3Naive::new(len).process_dct2(&mut reference);
+
+        let f32_algorithms: Vec<(&str, Arc<dyn TransformType2And3<f32>>)> = vec![
+            ("naive", Arc::new(Type2And3Naive::new(len))),
+            (
+                "split radix",
+                Arc::new(Type2And3SplitRadix::new(
+                    f32_planner.plan_dct2(len / 2),
+                    f32_planner.plan_dct2(len / 4),
+                )),
+            ),
+        ];
+        let f64_algorithms: Vec<Arc<dyn TransformType2And3<f64>>> = vec![
+            Arc::new(Type2And3Naive::new(len)),
+            Arc::new(Type2And3SplitRadix::new(
+                f64_planner.plan_dct2(len / 2),
+                f64_planner.plan_dct2(len / 4),
+            )),
+        ];
+
+        for ((name, f32_dct), f64_dct) in f32_algorithms.into_iter().zip(f64_algorithms) {
+            let mixed_dct = Type2And3MixedPrecision::new(f64_dct);
+
+            let mut f32_output = input.clone();
+            f32_dct.process_dct2(&mut f32_output);
+
+            let mut mixed_output = input.clone();
+            mixed_dct.process_dct2(&mut mixed_output);
+
+            let f32_error = max_error(&reference, &f32_output);
+            let mixed_error = max_error(&reference, &mixed_output);
+            println!(
+                "{}: f32 max error = {:e}, mixed precision max error = {:e}",
+                name, f32_error, mixed_error
+            );
+
+            assert!(
+                mixed_error * 4.0 < f32_error,
+                "{}: f32 max error = {:e}, mixed precision max error = {:e}",
+                name,
+                f32_error,
+                mixed_error
+            );
+        }
+    }
+}
@@ -1,10 +1,14 @@
 use rustfft::num_complex::Complex;
 use rustfft::Length;
 
-use crate::common::dct_error_inplace;
+use crate::algorithm::naive_kernel::{twiddle_dot, Accumulation};
+use crate::common::{dct_error_inplace, dct_error_outofplace, derived_len};
+use crate::memory::twiddles_only;
 use crate::RequiredScratch;
 use crate::{twiddles, DctNum};
 use crate::{Dct2, Dct3, Dst2, Dst3, TransformType2And3};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
 
 /// Naive O(n^2 ) DCT Type 2, DST Type 2, DCT Type 3, and DST Type 3 implementation
 ///
@@ -30,124 +34,168 @@ use crate::{Dct2, Dct3, Dst2, Dst3, TransformType2And3};
 /// ~~~
 pub struct Type2And3Naive<T> {
     twiddles: Box<[Complex<T>]>,
+    accumulation: Accumulation,
 }
 
 impl<T: DctNum> Type2And3Naive<T> {
     /// Creates a new DCT2, DCT3, DST2, and DST3 context that will process signals of length `len`
     pub fn new(len: usize) -> Self {
-        let twiddles: Vec<Complex<T>> = (0..len * 4)
-            .map(|i| twiddles::single_twiddle(i, len * 4))
+        let twiddle_len = derived_len(len, 4, 0, "twiddle table");
+        let twiddles: Vec<Complex<T>> = (0..twiddle_len)
+            .map(|i| twiddles::single_twiddle(i, twiddle_len))
             .collect();
 
         Self {
             twiddles: twiddles.into_boxed_slice(),
+            accumulation: Accumulation::Simple,
         }
     }
+
+    /// Sets how each output's sum of products is accumulated, which is `Accumulation::Simple` by default. See
+    /// [`Accumulation`](enum.Accumulation.html).
+    pub fn with_accumulation(mut self, accumulation: Accumulation) -> Self {
+        self.accumulation = accumulation;
+        self
+    }
 }
 
-impl<T: DctNum> Dct2<T> for Type2And3Naive<T> {
-    fn process_dct2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+impl<T: DctNum> Type2And3Naive<T> {
+    // Computes the DCT2 of `input` into `output`. Both must already be validated
+    fn dct2_into(&self, input: &[T], output: &mut [T]) {
+        for (k, output_cell) in output.iter_mut().enumerate() {
+            let twiddle_stride = k * 2;
+            let twiddle_index = k;
+
+            *output_cell = twiddle_dot(
+                input,
+                &self.twiddles,
+                twiddle_index,
+                twiddle_stride,
+                |twiddle| twiddle.re,
+                self.accumulation,
+            );
+        }
+    }
+
+    // Computes the DCT3 of `input` into `output`, passing each output through `scale_output`. Both must already be
+    // validated
+    fn dct3_into<F: Fn(T) -> T>(&self, input: &[T], output: &mut [T], scale_output: F) {
+        if input.is_empty() {
+            return;
+        }
+        let half_first = T::half() * input[0];
+
+        for (k, output_cell) in output.iter_mut().enumerate() {
+            let twiddle_stride = k * 2 + 1;
+            let twiddle_index = twiddle_stride;
+
+            *output_cell = scale_output(
+                half_first
+                    + twiddle_dot(
+                        &input[1..],
+                        &self.twiddles,
+                        twiddle_index,
+                        twiddle_stride,
+                        |twiddle| twiddle.re,
+                        self.accumulation,
+                    ),
+            );
+        }
+    }
+
+    // Computes the DST3 of `buffer`, in-place, passing each output through `scale_output`
+    fn process_dst3_with<F: Fn(T) -> T>(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        scale_output: F,
+    ) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+        if buffer.is_empty() {
+            return;
+        }
         scratch.copy_from_slice(buffer);
 
+        // scale the last scratch value by half before going into the loop
+        scratch[scratch.len() - 1] = scratch[scratch.len() - 1] * T::half();
+
         for k in 0..buffer.len() {
             let output_cell = buffer.get_mut(k).unwrap();
-            *output_cell = T::zero();
-
-            let twiddle_stride = k * 2;
-            let mut twiddle_index = k;
-
-            for i in 0..scratch.len() {
-                let twiddle = self.twiddles[twiddle_index];
 
-                *output_cell = *output_cell + scratch[i] * twiddle.re;
-
-                twiddle_index += twiddle_stride;
-                if twiddle_index >= self.twiddles.len() {
-                    twiddle_index -= self.twiddles.len();
-                }
-            }
+            let twiddle_stride = k * 2 + 1;
+            let twiddle_index = twiddle_stride;
+
+            *output_cell = scale_output(twiddle_dot(
+                scratch,
+                &self.twiddles,
+                twiddle_index,
+                twiddle_stride,
+                |twiddle| -twiddle.im,
+                self.accumulation,
+            ));
         }
     }
 }
+
+impl<T: DctNum> Dct2<T> for Type2And3Naive<T> {
+    plan_hooks!(T);
+    fn process_dct2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+        scratch.copy_from_slice(buffer);
+        self.dct2_into(scratch, buffer);
+    }
+    fn process_dct2_from(&self, input: &[T], output: &mut [T], scratch: &mut [T]) {
+        validate_buffers_outofplace!(input, output, scratch, self.len(), self.get_scratch_len());
+        self.dct2_into(input, output);
+    }
+}
 impl<T: DctNum> Dst2<T> for Type2And3Naive<T> {
+    plan_hooks!(T);
     fn process_dst2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
         scratch.copy_from_slice(buffer);
 
         for k in 0..buffer.len() {
             let output_cell = buffer.get_mut(k).unwrap();
-            *output_cell = T::zero();
 
             let twiddle_stride = (k + 1) * 2;
-            let mut twiddle_index = k + 1;
-
-            for i in 0..scratch.len() {
-                let twiddle = self.twiddles[twiddle_index];
-
-                *output_cell = *output_cell - scratch[i] * twiddle.im;
-
-                twiddle_index += twiddle_stride;
-                if twiddle_index >= self.twiddles.len() {
-                    twiddle_index -= self.twiddles.len();
-                }
-            }
+            let twiddle_index = k + 1;
+
+            *output_cell = twiddle_dot(
+                scratch,
+                &self.twiddles,
+                twiddle_index,
+                twiddle_stride,
+                |twiddle| -twiddle.im,
+                self.accumulation,
+            );
         }
     }
 }
 impl<T: DctNum> Dct3<T> for Type2And3Naive<T> {
+    plan_hooks!(T);
     fn process_dct3_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
         scratch.copy_from_slice(buffer);
-
-        let half_first = T::half() * scratch[0];
-
-        for k in 0..buffer.len() {
-            let output_cell = buffer.get_mut(k).unwrap();
-            *output_cell = half_first;
-
-            let twiddle_stride = k * 2 + 1;
-            let mut twiddle_index = twiddle_stride;
-
-            for i in 1..scratch.len() {
-                let twiddle = self.twiddles[twiddle_index];
-
-                *output_cell = *output_cell + scratch[i] * twiddle.re;
-
-                twiddle_index += twiddle_stride;
-                if twiddle_index >= self.twiddles.len() {
-                    twiddle_index -= self.twiddles.len();
-                }
-            }
-        }
+        self.dct3_into(scratch, buffer, |element| element);
+    }
+    fn process_dct3_scaled(&self, buffer: &mut [T], scratch: &mut [T], scale: T) {
+        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+        scratch.copy_from_slice(buffer);
+        self.dct3_into(scratch, buffer, |element| element * scale);
+    }
+    fn process_dct3_from(&self, input: &[T], output: &mut [T], scratch: &mut [T]) {
+        validate_buffers_outofplace!(input, output, scratch, self.len(), self.get_scratch_len());
+        self.dct3_into(input, output, |element| element);
     }
 }
 impl<T: DctNum> Dst3<T> for Type2And3Naive<T> {
+    plan_hooks!(T);
     fn process_dst3_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
-        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
-        scratch.copy_from_slice(buffer);
-
-        // scale the last scratch value by half before going into the loop
-        scratch[scratch.len() - 1] = scratch[scratch.len() - 1] * T::half();
-
-        for k in 0..buffer.len() {
-            let output_cell = buffer.get_mut(k).unwrap();
-            *output_cell = T::zero();
-
-            let twiddle_stride = k * 2 + 1;
-            let mut twiddle_index = twiddle_stride;
-
-            for i in 0..scratch.len() {
-                let twiddle = self.twiddles[twiddle_index];
-
-                *output_cell = *output_cell - scratch[i] * twiddle.im;
-
-                twiddle_index += twiddle_stride;
-                if twiddle_index >= self.twiddles.len() {
-                    twiddle_index -= self.twiddles.len();
-                }
-            }
-        }
+        self.process_dst3_with(buffer, scratch, |element| element);
+    }
+    fn process_dst3_scaled(&self, buffer: &mut [T], scratch: &mut [T], scale: T) {
+        self.process_dst3_with(buffer, scratch, |element| element * scale);
     }
 }
 impl<T: DctNum> TransformType2And3<T> for Type2And3Naive<T> {}
@@ -156,6 +204,16 @@ impl<T> Length for Type2And3Naive<T> {
         self.twiddles.len() / 4
     }
 }
+impl<T: DctNum> PlanIdentity<T> for Type2And3Naive<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Type2And3, self.len())
+    }
+}
+impl<T> MemoryUsage for Type2And3Naive<T> {
+    fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+        twiddles_only(&self.twiddles)
+    }
+}
 impl<T> RequiredScratch for Type2And3Naive<T> {
     fn get_scratch_len(&self) -> usize {
         self.len()
@@ -1,16 +1,26 @@
+use std::mem::MaybeUninit;
 use std::sync::Arc;
 
 use rustfft::num_complex::Complex;
 use rustfft::Length;
 
-use crate::common::dct_error_inplace;
-use crate::{twiddles, DctNum, RequiredScratch};
+use crate::common::{dct_error_inplace, derived_len};
+use crate::ScratchLayout;
+use crate::{twiddles, Cancelled, DctNum, RequiredScratch};
 use crate::{Dct2, Dct3, Dst2, Dst3, TransformType2And3};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
 
 /// DCT2, DCT3, DST2, and DST3 implemention that recursively divides the problem in half.
 ///
 /// The problem size must be 2^n, n > 1
 ///
+/// The three sub-problems are stored in the scratch buffer, and the caller's buffer is reused as scratch space for the
+/// inner DCTs, so nested split radix instances need no more than `len` elements of scratch in total. If either inner
+/// DCT needs more scratch than that, the sub-problems are moved into the caller's buffer instead, and the entire
+/// scratch buffer is given to the inner DCTs. Either way, `get_scratch_len()` is the true peak, `max(len, inner scratch)`,
+/// so any `TransformType2And3` instance can be used as an inner DCT.
+///
 /// ~~~
 /// // Computes a DCT Type 2 of size 1024
 /// use rustdct::algorithm::Type2And3SplitRadix;
@@ -28,10 +38,12 @@ use crate::{Dct2, Dct3, Dst2, Dst3, TransformType2And3};
 /// let mut buffer = vec![0f32; len];
 /// dct.process_dct2(&mut buffer);
 /// ~~~
-pub struct Type2And3SplitRadix<T> {
+pub struct Type2And3SplitRadix<T, S = Box<[Complex<T>]>> {
     half_dct: Arc<dyn TransformType2And3<T>>,
     quarter_dct: Arc<dyn TransformType2And3<T>>,
-    twiddles: Box<[Complex<T>]>,
+    twiddles: S,
+    sub_problems_in_buffer: bool,
+    scratch_len: usize,
 }
 
 impl<T: DctNum> Type2And3SplitRadix<T> {
@@ -40,81 +52,207 @@ impl<T: DctNum> Type2And3SplitRadix<T> {
         half_dct: Arc<dyn TransformType2And3<T>>,
         quarter_dct: Arc<dyn TransformType2And3<T>>,
     ) -> Self {
-        let half_len = half_dct.len();
-        let quarter_len = quarter_dct.len();
-        let len = half_len * 2;
+        let len = validate_inner_lens(half_dct.as_ref(), quarter_dct.as_ref());
+        let twiddles = Self::compute_twiddles(len);
 
-        assert!(
-            len.is_power_of_two() && len > 2,
-            "The DCT2SplitRadix algorithm requires a power-of-two input size greater than two. Got {}", len 
-        );
-        assert_eq!(half_len, quarter_len * 2,
-            "half_dct.len() must be 2 * quarter_dct.len(). Got half_dct.len()={}, quarter_dct.len()={}", half_len, quarter_len
+        Self::from_twiddles(half_dct, quarter_dct, twiddles.into_boxed_slice())
+    }
+
+    // Computes the twiddle table for an instance of size `len`
+    pub(crate) fn compute_twiddles(len: usize) -> Vec<Complex<T>> {
+        (0..Self::twiddle_len_for(len))
+            .map(|i| compute_twiddle(i, len))
+            .collect()
+    }
+
+    /// Returns the number of twiddle factors that a split radix instance of size `len` stores. This is the exact length
+    /// of the storage passed to `with_twiddle_storage`.
+    pub fn twiddle_len_for(len: usize) -> usize {
+        len / 4
+    }
+}
+
+impl<'a, T: DctNum> Type2And3SplitRadix<T, &'a [Complex<T>]> {
+    /// Creates a new DCT2, DCT3, DST2, and DST3 context that will process signals of length `half_dct.len() * 2`, and
+    /// stores its twiddle factors in `storage` instead of allocating them.
+    ///
+    /// This lets the memory for the twiddle factors come from a static buffer or an arena, so that the memory budget
+    /// of a set of transforms can be fixed ahead of time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `storage.len()` isn't exactly `Type2And3SplitRadix::<T>::twiddle_len_for(half_dct.len() * 2)`.
+    pub fn with_twiddle_storage(
+        half_dct: Arc<dyn TransformType2And3<T>>,
+        quarter_dct: Arc<dyn TransformType2And3<T>>,
+        storage: &'a mut [MaybeUninit<Complex<T>>],
+    ) -> Self {
+        let len = validate_inner_lens(half_dct.as_ref(), quarter_dct.as_ref());
+
+        let twiddle_len = Type2And3SplitRadix::<T>::twiddle_len_for(len);
+        let twiddles = twiddles::fill_storage(storage, twiddle_len, |i| compute_twiddle(i, len));
+
+        Self::from_twiddles(half_dct, quarter_dct, twiddles)
+    }
+}
+
+impl<T: DctNum> Type2And3SplitRadix<T, Arc<[Complex<T>]>> {
+    // Creates an instance that uses a twiddle table shared with other instances of the same size. `twiddles` must
+    // have been computed by `compute_twiddles`
+    pub(crate) fn with_shared_twiddles(
+        half_dct: Arc<dyn TransformType2And3<T>>,
+        quarter_dct: Arc<dyn TransformType2And3<T>>,
+        twiddles: Arc<[Complex<T>]>,
+    ) -> Self {
+        let len = validate_inner_lens(half_dct.as_ref(), quarter_dct.as_ref());
+        assert_eq!(
+            twiddles.len(),
+            Type2And3SplitRadix::<T>::twiddle_len_for(len)
         );
 
-        let twiddles: Vec<Complex<T>> = (0..(len / 4))
-            .map(|i| twiddles::single_twiddle(2 * i + 1, len * 4).conj())
-            .collect();
+        Self::from_twiddles(half_dct, quarter_dct, twiddles)
+    }
+}
+
+// Checks that the inner DCTs can be used by a split radix instance, and returns the length of that instance
+fn validate_inner_lens<T: DctNum>(
+    half_dct: &dyn TransformType2And3<T>,
+    quarter_dct: &dyn TransformType2And3<T>,
+) -> usize {
+    let half_len = half_dct.len();
+    let quarter_len = quarter_dct.len();
+    let len = derived_len(half_len, 2, 0, "DCT2");
+    derived_len(len, 4, 0, "twiddle table");
+
+    assert!(
+        len.is_power_of_two() && len > 2,
+        "The DCT2SplitRadix algorithm requires a power-of-two input size greater than two. Got {}",
+        len
+    );
+    assert_eq!(
+        half_len,
+        quarter_len * 2,
+        "half_dct.len() must be 2 * quarter_dct.len(). Got half_dct.len()={}, quarter_dct.len()={}",
+        half_len,
+        quarter_len
+    );
+
+    len
+}
+
+fn compute_twiddle<T: DctNum>(i: usize, len: usize) -> Complex<T> {
+    twiddles::single_twiddle(2 * i + 1, len * 4).conj()
+}
+
+impl<T: DctNum, S: AsRef<[Complex<T>]>> Type2And3SplitRadix<T, S> {
+    fn from_twiddles(
+        half_dct: Arc<dyn TransformType2And3<T>>,
+        quarter_dct: Arc<dyn TransformType2And3<T>>,
+        twiddles: S,
+    ) -> Self {
+        let len = half_dct.len() * 2;
+
+        // we use the caller's buffer as scratch for the inner DCTs. if they need more than that, swap the roles of the two buffers
+        let inner_scratch = half_dct
+            .get_scratch_len()
+            .max(quarter_dct.get_scratch_len());
+        let sub_problems_in_buffer = inner_scratch > len;
+        let scratch_len = len.max(inner_scratch);
 
         Self {
             half_dct: half_dct,
             quarter_dct: quarter_dct,
-            twiddles: twiddles.into_boxed_slice(),
+            twiddles,
+            sub_problems_in_buffer,
+            scratch_len,
         }
     }
 }
 
-impl<T: DctNum> Dct2<T> for Type2And3SplitRadix<T> {
-    fn process_dct2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
-        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+impl<T: DctNum, S: AsRef<[Complex<T>]>> Type2And3SplitRadix<T, S> {
+    // Splits the scratch buffer into the three sub-problems used by both the DCT2 and DCT3: One of size n/2, and two of size n/4
+    fn split_scratch(scratch: &mut [T]) -> (&mut [T], &mut [T], &mut [T]) {
+        let half_len = scratch.len() / 2;
+        let quarter_len = scratch.len() / 4;
+
+        let (half, quarters) = scratch.split_at_mut(half_len);
+        let (quarter_a, quarter_b) = quarters.split_at_mut(quarter_len);
+        (half, quarter_a, quarter_b)
+    }
+
+    // Runs `process_inner` on the sub-problems that were prepared in the first `len` elements of `scratch`, and leaves the results there.
+    // `process_inner` receives the sub-problems and the scratch space for the inner DCTs. Normally the sub-problems stay in the scratch,
+    // and the caller's buffer is used as the inner scratch. If the inner DCTs need more than that, the sub-problems are moved to
+    // the caller's buffer, and the inner DCTs get the whole scratch buffer
+    fn process_sub_problems<R>(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        process_inner: impl FnOnce(&mut [T], &mut [T]) -> R,
+    ) -> R {
+        let len = self.len();
+        if self.sub_problems_in_buffer {
+            buffer.copy_from_slice(&scratch[..len]);
+            let result = process_inner(buffer, scratch);
+            scratch[..len].copy_from_slice(buffer);
+            result
+        } else {
+            process_inner(&mut scratch[..len], buffer)
+        }
+    }
 
+    fn prepare_dct2(&self, buffer: &[T], scratch: &mut [T]) {
         let len = self.len();
         let half_len = len / 2;
         let quarter_len = len / 4;
 
         //preprocess the data by splitting it up into vectors of size n/2, n/4, and n/4
-        let (input_dct2, input_dct4) = scratch.split_at_mut(half_len);
-        let (input_dct4_even, input_dct4_odd) = input_dct4.split_at_mut(quarter_len);
+        let (input_dct2, input_dct4_even, input_dct4_odd) = Self::split_scratch(scratch);
 
         for i in 0..quarter_len {
-            
-                let input_bottom = unsafe { *buffer.get_unchecked(i) };
-                let input_top = unsafe { *buffer.get_unchecked(len - i - 1) };
-
-                let input_half_bottom = unsafe { *buffer.get_unchecked(half_len - i - 1) };
-                let input_half_top = unsafe { *buffer.get_unchecked(half_len + i) };
-
-                //prepare the inner DCT2
-                unsafe {*input_dct2.get_unchecked_mut(i)  = input_top + input_bottom };
-                unsafe {*input_dct2.get_unchecked_mut(half_len - i - 1)  =
-                    input_half_bottom + input_half_top };
-
-                //prepare the inner DCT4 - which consists of two DCT2s of half size
-                let lower_dct4 = input_bottom - input_top;
-                let upper_dct4 = input_half_bottom - input_half_top;
-                let twiddle = unsafe { self.twiddles.get_unchecked(i) };
-
-                let cos_input = lower_dct4 * twiddle.re + upper_dct4 * twiddle.im;
-                let sin_input = upper_dct4 * twiddle.re - lower_dct4 * twiddle.im;
-
-                unsafe {*input_dct4_even.get_unchecked_mut(i) = cos_input };
-                unsafe {*input_dct4_odd.get_unchecked_mut(quarter_len - i - 1) =
-                    if i % 2 == 0 { sin_input } else { -sin_input } };
-            
+            let input_bottom = unsafe { *buffer.get_unchecked(i) };
+            let input_top = unsafe { *buffer.get_unchecked(len - i - 1) };
+
+            let input_half_bottom = unsafe { *buffer.get_unchecked(half_len - i - 1) };
+            let input_half_top = unsafe { *buffer.get_unchecked(half_len + i) };
+
+            //prepare the inner DCT2
+            unsafe { *input_dct2.get_unchecked_mut(i) = input_top + input_bottom };
+            unsafe {
+                *input_dct2.get_unchecked_mut(half_len - i - 1) = input_half_bottom + input_half_top
+            };
+
+            //prepare the inner DCT4 - which consists of two DCT2s of half size
+            let lower_dct4 = input_bottom - input_top;
+            let upper_dct4 = input_half_bottom - input_half_top;
+            let twiddle = unsafe { self.twiddles.as_ref().get_unchecked(i) };
+
+            let cos_input = lower_dct4 * twiddle.re + upper_dct4 * twiddle.im;
+            let sin_input = upper_dct4 * twiddle.re - lower_dct4 * twiddle.im;
+
+            unsafe { *input_dct4_even.get_unchecked_mut(i) = cos_input };
+            unsafe {
+                *input_dct4_odd.get_unchecked_mut(quarter_len - i - 1) =
+                    if i % 2 == 0 { sin_input } else { -sin_input }
+            };
         }
+    }
 
-        // compute the recursive DCT2s, using the original buffer as scratch space
-        self.half_dct.process_dct2_with_scratch(input_dct2, buffer);
-        self.quarter_dct
-            .process_dct2_with_scratch(input_dct4_even, buffer);
-        self.quarter_dct
-            .process_dct2_with_scratch(input_dct4_odd, buffer);
+    // Writes the DCT2 output to `buffer`, putting the output for coefficient `k` at index `destination(k)`.
+    // `destination` must map `0..len` to a permutation of `0..len`
+    fn finish_dct2<D: Fn(usize) -> usize>(&self, scratch: &[T], buffer: &mut [T], destination: D) {
+        let len = self.len();
+        let half_len = len / 2;
+        let quarter_len = len / 4;
+
+        let (input_dct2, input_dct4) = scratch.split_at(half_len);
+        let (input_dct4_even, input_dct4_odd) = input_dct4.split_at(quarter_len);
 
         unsafe {
             //post process the 3 DCT2 outputs. the first few and the last will be done outside of the loop
-            *buffer.get_unchecked_mut(0) = *input_dct2.get_unchecked(0);
-            *buffer.get_unchecked_mut(1) = *input_dct4_even.get_unchecked(0);
-            *buffer.get_unchecked_mut(2) = *input_dct2.get_unchecked(1);
+            *buffer.get_unchecked_mut(destination(0)) = *input_dct2.get_unchecked(0);
+            *buffer.get_unchecked_mut(destination(1)) = *input_dct4_even.get_unchecked(0);
+            *buffer.get_unchecked_mut(destination(2)) = *input_dct2.get_unchecked(1);
 
             for i in 1..quarter_len {
                 let dct4_cos_output = *input_dct4_even.get_unchecked(i);
@@ -124,40 +262,56 @@ impl<T: DctNum> Dct2<T> for Type2And3SplitRadix<T> {
                     *input_dct4_odd.get_unchecked(quarter_len - i)
                 };
 
-                *buffer.get_unchecked_mut(i * 4 - 1) = dct4_cos_output + dct4_sin_output;
-                *buffer.get_unchecked_mut(i * 4) = *input_dct2.get_unchecked(i * 2);
+                *buffer.get_unchecked_mut(destination(i * 4 - 1)) =
+                    dct4_cos_output + dct4_sin_output;
+                *buffer.get_unchecked_mut(destination(i * 4)) = *input_dct2.get_unchecked(i * 2);
 
-                *buffer.get_unchecked_mut(i * 4 + 1) = dct4_cos_output - dct4_sin_output;
-                *buffer.get_unchecked_mut(i * 4 + 2) = *input_dct2.get_unchecked(i * 2 + 1);
+                *buffer.get_unchecked_mut(destination(i * 4 + 1)) =
+                    dct4_cos_output - dct4_sin_output;
+                *buffer.get_unchecked_mut(destination(i * 4 + 2)) =
+                    *input_dct2.get_unchecked(i * 2 + 1);
             }
 
-            *buffer.get_unchecked_mut(len - 1) = -*input_dct4_odd.get_unchecked(0);
+            *buffer.get_unchecked_mut(destination(len - 1)) = -*input_dct4_odd.get_unchecked(0);
         }
     }
-}
-impl<T: DctNum> Dst2<T> for Type2And3SplitRadix<T> {
-    fn process_dst2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
-        for i in 0..(self.len() / 2) {
-            buffer[2 * i + 1] = buffer[2 * i + 1].neg();
-        }
 
-        self.process_dct2_with_scratch(buffer, scratch);
+    // Computes the DCT2 of `buffer`, putting the output for coefficient `k` at index `destinations[k]`.
+    // `destinations` must be a permutation of `0..len`
+    pub(crate) fn process_dct2_to_destinations(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        destinations: &[usize],
+    ) {
+        let scratch =
+            validate_buffers!(@always buffer, scratch, self.len(), self.get_scratch_len());
+        assert_eq!(destinations.len(), self.len());
+
+        self.prepare_dct2(buffer, &mut scratch[..self.len()]);
+        self.process_sub_problems(buffer, scratch, |sub_problems, inner_scratch| {
+            self.process_dct2_sub_problems(sub_problems, inner_scratch)
+        });
+        self.finish_dct2(&scratch[..self.len()], buffer, |k| destinations[k]);
+    }
 
-        buffer.reverse();
+    fn process_dct2_sub_problems(&self, sub_problems: &mut [T], inner_scratch: &mut [T]) {
+        let (input_dct2, input_dct4_even, input_dct4_odd) = Self::split_scratch(sub_problems);
+        self.half_dct
+            .process_dct2_with_scratch(input_dct2, inner_scratch);
+        self.quarter_dct
+            .process_dct2_with_scratch(input_dct4_even, inner_scratch);
+        self.quarter_dct
+            .process_dct2_with_scratch(input_dct4_odd, inner_scratch);
     }
-}
-impl<T: DctNum> Dct3<T> for Type2And3SplitRadix<T> {
-    fn process_dct3_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
-        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
 
-        let len = buffer.len();
-        let half_len = len / 2;
+    fn prepare_dct3(&self, buffer: &[T], scratch: &mut [T]) {
+        let len = self.len();
         let quarter_len = len / 4;
 
         // divide the output into 3 sub-lists to use for our inner DCTs, one of size N/2 and two of size N/4
-        let (recursive_input_evens, recursive_input_odds) = scratch.split_at_mut(half_len);
-        let (recursive_input_n1, recursive_input_n3) =
-            recursive_input_odds.split_at_mut(quarter_len);
+        let (recursive_input_evens, recursive_input_n1, recursive_input_n3) =
+            Self::split_scratch(scratch);
 
         // do the same pre-loop setup as DCT4ViaDCT3, and since we're skipping the first iteration of the loop we
         // to also set up the corresponding evens cells
@@ -182,20 +336,22 @@ impl<T: DctNum> Dct3<T> for Type2And3SplitRadix<T> {
                     *buffer.get_unchecked(k - 1) - *buffer.get_unchecked(k + 1);
             }
         }
+    }
 
-        //perform our recursive DCTs, using the original buffer as scratch space
-        self.half_dct
-            .process_dct3_with_scratch(recursive_input_evens, buffer);
-        self.quarter_dct
-            .process_dct3_with_scratch(recursive_input_n1, buffer);
-        self.quarter_dct
-            .process_dct3_with_scratch(recursive_input_n3, buffer);
+    // Each output is passed through `scale_output` as it's written
+    fn finish_dct3<F: Fn(T) -> T>(&self, scratch: &[T], buffer: &mut [T], scale_output: F) {
+        let len = self.len();
+        let half_len = len / 2;
+        let quarter_len = len / 4;
+
+        let (recursive_input_evens, recursive_input_odds) = scratch.split_at(half_len);
+        let (recursive_input_n1, recursive_input_n3) = recursive_input_odds.split_at(quarter_len);
 
         //merge the results. we're going to combine 2 separate things:
         // - merging the two smaller DCT3 outputs into a DCT4 output
         // - marging the DCT4 outputand the larger DCT3 output into the final output
         for i in 0..quarter_len {
-            let twiddle = self.twiddles[i];
+            let twiddle = self.twiddles.as_ref()[i];
             let cosine_value = recursive_input_n1[i];
 
             // flip the sign of every other sine value to finish the job of using a DCT3 to compute a DST3
@@ -212,16 +368,178 @@ impl<T: DctNum> Dct3<T> for Type2And3SplitRadix<T> {
                 let lower_dct3 = *recursive_input_evens.get_unchecked(i);
                 let upper_dct3 = *recursive_input_evens.get_unchecked(half_len - i - 1);
 
-                *buffer.get_unchecked_mut(i) = lower_dct3 + lower_dct4;
-                *buffer.get_unchecked_mut(len - i - 1) = lower_dct3 - lower_dct4;
+                *buffer.get_unchecked_mut(i) = scale_output(lower_dct3 + lower_dct4);
+                *buffer.get_unchecked_mut(len - i - 1) = scale_output(lower_dct3 - lower_dct4);
 
-                *buffer.get_unchecked_mut(half_len - i - 1) = upper_dct3 + upper_dct4;
-                *buffer.get_unchecked_mut(half_len + i) = upper_dct3 - upper_dct4;
+                *buffer.get_unchecked_mut(half_len - i - 1) = scale_output(upper_dct3 + upper_dct4);
+                *buffer.get_unchecked_mut(half_len + i) = scale_output(upper_dct3 - upper_dct4);
             }
         }
     }
 }
-impl<T: DctNum> Dst3<T> for Type2And3SplitRadix<T> {
+
+impl<T: DctNum, S: AsRef<[Complex<T>]> + Send + Sync> Dct2<T> for Type2And3SplitRadix<T, S> {
+    plan_hooks!(T);
+    fn process_dct2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        let scratch =
+            validate_buffers!(@always buffer, scratch, self.len(), self.get_scratch_len());
+
+        self.prepare_dct2(buffer, &mut scratch[..self.len()]);
+
+        // compute the recursive DCT2s
+        self.process_sub_problems(buffer, scratch, |sub_problems, inner_scratch| {
+            self.process_dct2_sub_problems(sub_problems, inner_scratch)
+        });
+
+        self.finish_dct2(&scratch[..self.len()], buffer, |k| k);
+    }
+
+    fn process_dct2_cancellable(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        should_continue: &mut dyn FnMut() -> bool,
+    ) -> Result<(), Cancelled> {
+        let scratch =
+            validate_buffers!(@always buffer, scratch, self.len(), self.get_scratch_len(), Ok(()));
+
+        if !should_continue() {
+            return Err(Cancelled);
+        }
+        self.prepare_dct2(buffer, &mut scratch[..self.len()]);
+
+        self.process_sub_problems(buffer, scratch, |sub_problems, inner_scratch| {
+            let (input_dct2, input_dct4_even, input_dct4_odd) = Self::split_scratch(sub_problems);
+            self.half_dct
+                .process_dct2_cancellable(input_dct2, inner_scratch, should_continue)?;
+            self.quarter_dct.process_dct2_cancellable(
+                input_dct4_even,
+                inner_scratch,
+                should_continue,
+            )?;
+            self.quarter_dct.process_dct2_cancellable(
+                input_dct4_odd,
+                inner_scratch,
+                should_continue,
+            )
+        })?;
+
+        self.finish_dct2(&scratch[..self.len()], buffer, |k| k);
+        Ok(())
+    }
+}
+impl<T: DctNum, S: AsRef<[Complex<T>]> + Send + Sync> Dst2<T> for Type2And3SplitRadix<T, S> {
+    plan_hooks!(T);
+    fn process_dst2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        for i in 0..(self.len() / 2) {
+            buffer[2 * i + 1] = buffer[2 * i + 1].neg();
+        }
+
+        self.process_dct2_with_scratch(buffer, scratch);
+
+        buffer.reverse();
+    }
+
+    fn process_dst2_cancellable(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        should_continue: &mut dyn FnMut() -> bool,
+    ) -> Result<(), Cancelled> {
+        for i in 0..(self.len() / 2) {
+            buffer[2 * i + 1] = buffer[2 * i + 1].neg();
+        }
+
+        self.process_dct2_cancellable(buffer, scratch, should_continue)?;
+
+        buffer.reverse();
+        Ok(())
+    }
+}
+impl<T: DctNum, S: AsRef<[Complex<T>]> + Send + Sync> Dct3<T> for Type2And3SplitRadix<T, S> {
+    plan_hooks!(T);
+    fn process_dct3_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        let scratch =
+            validate_buffers!(@always buffer, scratch, self.len(), self.get_scratch_len());
+
+        self.prepare_dct3(buffer, &mut scratch[..self.len()]);
+
+        //perform our recursive DCTs
+        self.process_sub_problems(buffer, scratch, |sub_problems, inner_scratch| {
+            let (recursive_input_evens, recursive_input_n1, recursive_input_n3) =
+                Self::split_scratch(sub_problems);
+            self.half_dct
+                .process_dct3_with_scratch(recursive_input_evens, inner_scratch);
+            self.quarter_dct
+                .process_dct3_with_scratch(recursive_input_n1, inner_scratch);
+            self.quarter_dct
+                .process_dct3_with_scratch(recursive_input_n3, inner_scratch);
+        });
+
+        self.finish_dct3(&scratch[..self.len()], buffer, |element| element);
+    }
+
+    fn process_dct3_scaled(&self, buffer: &mut [T], scratch: &mut [T], scale: T) {
+        let scratch =
+            validate_buffers!(@always buffer, scratch, self.len(), self.get_scratch_len());
+
+        self.prepare_dct3(buffer, &mut scratch[..self.len()]);
+
+        // the recursive DCTs are unscaled, because the merge step applies the scale
+        self.process_sub_problems(buffer, scratch, |sub_problems, inner_scratch| {
+            let (recursive_input_evens, recursive_input_n1, recursive_input_n3) =
+                Self::split_scratch(sub_problems);
+            self.half_dct
+                .process_dct3_with_scratch(recursive_input_evens, inner_scratch);
+            self.quarter_dct
+                .process_dct3_with_scratch(recursive_input_n1, inner_scratch);
+            self.quarter_dct
+                .process_dct3_with_scratch(recursive_input_n3, inner_scratch);
+        });
+
+        self.finish_dct3(&scratch[..self.len()], buffer, |element| element * scale);
+    }
+
+    fn process_dct3_cancellable(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        should_continue: &mut dyn FnMut() -> bool,
+    ) -> Result<(), Cancelled> {
+        let scratch =
+            validate_buffers!(@always buffer, scratch, self.len(), self.get_scratch_len(), Ok(()));
+
+        if !should_continue() {
+            return Err(Cancelled);
+        }
+        self.prepare_dct3(buffer, &mut scratch[..self.len()]);
+
+        self.process_sub_problems(buffer, scratch, |sub_problems, inner_scratch| {
+            let (recursive_input_evens, recursive_input_n1, recursive_input_n3) =
+                Self::split_scratch(sub_problems);
+            self.half_dct.process_dct3_cancellable(
+                recursive_input_evens,
+                inner_scratch,
+                should_continue,
+            )?;
+            self.quarter_dct.process_dct3_cancellable(
+                recursive_input_n1,
+                inner_scratch,
+                should_continue,
+            )?;
+            self.quarter_dct.process_dct3_cancellable(
+                recursive_input_n3,
+                inner_scratch,
+                should_continue,
+            )
+        })?;
+
+        self.finish_dct3(&scratch[..self.len()], buffer, |element| element);
+        Ok(())
+    }
+}
+impl<T: DctNum, S: AsRef<[Complex<T>]> + Send + Sync> Dst3<T> for Type2And3SplitRadix<T, S> {
+    plan_hooks!(T);
     fn process_dst3_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         buffer.reverse();
 
@@ -231,23 +549,93 @@ impl<T: DctNum> Dst3<T> for Type2And3SplitRadix<T> {
             buffer[2 * i + 1] = buffer[2 * i + 1].neg();
         }
     }
+
+    fn process_dst3_scaled(&self, buffer: &mut [T], scratch: &mut [T], scale: T) {
+        buffer.reverse();
+
+        self.process_dct3_scaled(buffer, scratch, scale);
+
+        for i in 0..(self.len() / 2) {
+            buffer[2 * i + 1] = buffer[2 * i + 1].neg();
+        }
+    }
+
+    fn process_dst3_cancellable(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        should_continue: &mut dyn FnMut() -> bool,
+    ) -> Result<(), Cancelled> {
+        buffer.reverse();
+
+        self.process_dct3_cancellable(buffer, scratch, should_continue)?;
+
+        for i in 0..(self.len() / 2) {
+            buffer[2 * i + 1] = buffer[2 * i + 1].neg();
+        }
+        Ok(())
+    }
+}
+impl<T: DctNum, S: AsRef<[Complex<T>]> + Send + Sync> TransformType2And3<T>
+    for Type2And3SplitRadix<T, S>
+{
 }
-impl<T: DctNum> TransformType2And3<T> for Type2And3SplitRadix<T> {}
-impl<T> Length for Type2And3SplitRadix<T> {
+impl<T, S: AsRef<[Complex<T>]>> Length for Type2And3SplitRadix<T, S> {
     fn len(&self) -> usize {
-        self.twiddles.len() * 4
+        self.twiddles.as_ref().len() * 4
     }
 }
-impl<T> RequiredScratch for Type2And3SplitRadix<T> {
+impl<T: DctNum, S: AsRef<[Complex<T>]>> MemoryUsage for Type2And3SplitRadix<T, S> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            twiddles_bytes: visitor.shared_table(self.twiddles.as_ref()),
+            window_bytes: 0,
+            inner_plans_bytes: visitor.inner_plan(&self.half_dct)
+                + visitor.inner_plan(&self.quarter_dct),
+        }
+    }
+}
+impl<T: DctNum, S: AsRef<[Complex<T>]>> PlanIdentity<T> for Type2And3SplitRadix<T, S> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Type2And3, self.len())
+    }
+}
+impl<T: DctNum, S: AsRef<[Complex<T>]>> RequiredScratch for Type2And3SplitRadix<T, S> {
     fn get_scratch_len(&self) -> usize {
-        self.len()
+        self.scratch_len
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        // the inner DCTs only get the scratch buffer when the sub-problems are stored in the caller's buffer
+        if self.sub_problems_in_buffer {
+            self.half_dct
+                .get_complex_scratch_len()
+                .max(self.quarter_dct.get_complex_scratch_len())
+        } else {
+            0
+        }
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        // when the inner DCTs need more scratch than `len`, they get the whole scratch buffer, and the sub-problems are
+        // stored in the caller's buffer while it runs
+        if self.sub_problems_in_buffer {
+            let inner = if self.half_dct.get_scratch_len() >= self.quarter_dct.get_scratch_len() {
+                self.half_dct.scratch_layout()
+            } else {
+                self.quarter_dct.scratch_layout()
+            };
+            ScratchLayout::new().with_inner(inner)
+        } else {
+            ScratchLayout::new().with_region("sub-problems", self.len(), false)
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::algorithm::Type2And3Naive;
+    use crate::algorithm::{Type2And3ConvertToFft, Type2And3Naive};
+    use rustfft::algorithm::Dft;
+    use rustfft::FftDirection;
 
     use crate::test_utils::{compare_float_vectors, random_signal};
 
@@ -310,4 +698,115 @@ mod test {
             );
         }
     }
+
+    /// Verify that the cancellable methods give the same output as the regular ones when never cancelled,
+    /// and that they stop early when the callback asks them to
+    #[test]
+    fn test_splitradix_cancellable() {
+        let size = 64;
+        let dct = Type2And3SplitRadix::new(
+            Arc::new(Type2And3SplitRadix::new(
+                Arc::new(Type2And3Naive::new(16)),
+                Arc::new(Type2And3Naive::new(8)),
+            )),
+            Arc::new(Type2And3Naive::new(16)),
+        );
+        let mut scratch = vec![0f32; dct.get_scratch_len()];
+
+        let mut expected_buffer = random_signal(size);
+        let mut actual_buffer = expected_buffer.clone();
+        dct.process_dct3(&mut expected_buffer);
+
+        let mut num_calls = 0;
+        let result = dct.process_dct3_cancellable(&mut actual_buffer, &mut scratch, &mut || {
+            num_calls += 1;
+            true
+        });
+        assert_eq!(result, Ok(()));
+        assert!(compare_float_vectors(&actual_buffer, &expected_buffer));
+
+        // 1 call for the outer split radix, 1 for the inner split radix, and one for each of the 5 naive DCTs
+        assert_eq!(num_calls, 7);
+
+        let mut remaining_calls = 3;
+        let result = dct.process_dst2_cancellable(&mut actual_buffer, &mut scratch, &mut || {
+            remaining_calls -= 1;
+            remaining_calls > 0
+        });
+        assert_eq!(result, Err(Cancelled));
+        assert_eq!(remaining_calls, 0);
+    }
+
+    /// Verify that inner DCTs that need more scratch than the split radix's buffer still work correctly
+    #[test]
+    fn test_splitradix_large_inner_scratch() {
+        let size = 128;
+        // the DFT algorithm needs as much scratch space as its length, so the inner DCT will need more scratch than the split radix's buffer
+        let half_dct = Arc::new(Type2And3ConvertToFft::new(Arc::new(Dft::new(
+            size / 2,
+            FftDirection::Forward,
+        ))));
+        let quarter_dct = Arc::new(Type2And3Naive::new(size / 4));
+        let inner_scratch_len = half_dct.get_scratch_len();
+        assert!(inner_scratch_len > size);
+
+        // the split radix should give the entire scratch to the inner DCTs instead of adding to it
+        let dct = Type2And3SplitRadix::new(half_dct, quarter_dct);
+        assert_eq!(dct.get_scratch_len(), inner_scratch_len);
+
+        let naive_dct = Type2And3Naive::new(size);
+        let input = random_signal(size);
+
+        let mut expected_buffer = input.clone();
+        let mut actual_buffer = input.clone();
+        naive_dct.process_dct2(&mut expected_buffer);
+        dct.process_dct2(&mut actual_buffer);
+        assert!(compare_float_vectors(&actual_buffer, &expected_buffer));
+
+        let mut expected_buffer = input.clone();
+        let mut actual_buffer = input;
+        naive_dct.process_dct3(&mut expected_buffer);
+        dct.process_dct3(&mut actual_buffer);
+        assert!(compare_float_vectors(&actual_buffer, &expected_buffer));
+    }
+
+    #[test]
+    fn test_splitradix_twiddle_storage() {
+        for &len in &[4, 8, 16, 32] {
+            let half_dct: Arc<dyn TransformType2And3<f32>> = Arc::new(Type2And3Naive::new(len / 2));
+            let quarter_dct: Arc<dyn TransformType2And3<f32>> =
+                Arc::new(Type2And3Naive::new(len / 4));
+
+            let mut storage =
+                vec![MaybeUninit::uninit(); Type2And3SplitRadix::<f32>::twiddle_len_for(len)];
+            let stored = Type2And3SplitRadix::with_twiddle_storage(
+                Arc::clone(&half_dct),
+                Arc::clone(&quarter_dct),
+                &mut storage,
+            );
+            let boxed = Type2And3SplitRadix::new(half_dct, quarter_dct);
+            assert_eq!(stored.len(), len);
+
+            let mut expected_buffer = random_signal(len);
+            let mut actual_buffer = expected_buffer.clone();
+            boxed.process_dct2(&mut expected_buffer);
+            stored.process_dct2(&mut actual_buffer);
+            assert!(compare_float_vectors(&expected_buffer, &actual_buffer));
+
+            boxed.process_dct3(&mut expected_buffer);
+            stored.process_dct3(&mut actual_buffer);
+            assert!(compare_float_vectors(&expected_buffer, &actual_buffer));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_splitradix_twiddle_storage_wrong_len() {
+        let mut storage = vec![MaybeUninit::uninit(); 3];
+        Type2And3SplitRadix::<f32, _>::with_twiddle_storage(
+            Arc::new(Type2And3Naive::new(8)),
+            Arc::new(Type2And3Naive::new(4)),
+            &mut storage,
+        );
+    }
 }
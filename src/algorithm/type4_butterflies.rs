@@ -0,0 +1,221 @@
+use rustfft::num_complex::Complex;
+use rustfft::Length;
+
+use crate::algorithm::type2and3_butterflies::*;
+use crate::common::dct_error_inplace;
+use crate::{twiddles, Dct4, DctNum, Dst4, RequiredScratch, TransformType4};
+use crate::{FromPlanKey, PlanIdentity, PlanKey, PlanKind};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+
+// Each butterfly performs one step of the DCT4-via-DCT3 algorithm from `Type4ConvertToType3Even`, with its sub-problems
+// stored in fixed-size arrays on the stack, and a DCT3 butterfly of half size for the inner transforms. So unlike
+// `Type4ConvertToType3Even`, it doesn't need any scratch space, and the compiler is able to fully unroll it.
+macro_rules! dct4_butterfly {
+    ($struct_name:ident, $inner_name:ident, $size:expr) => {
+        /// DCT4 and DST4 implementation for a hardcoded size, which requires no scratch space.
+        ///
+        /// `get_scratch_len()` always returns 0, so the `process_*_with_scratch` methods can be given an empty scratch slice.
+        pub struct $struct_name<T> {
+            inner_butterfly: $inner_name<T>,
+            twiddles: [Complex<T>; $size / 2],
+        }
+        impl<T: DctNum> $struct_name<T> {
+            /// Creates a new DCT4 butterfly
+            pub fn new() -> Self {
+                let mut twiddles = [Complex::new(T::zero(), T::zero()); $size / 2];
+                for (i, twiddle) in twiddles.iter_mut().enumerate() {
+                    *twiddle = twiddles::single_twiddle(2 * i + 1, $size * 8).conj();
+                }
+
+                Self {
+                    inner_butterfly: $inner_name::new(),
+                    twiddles,
+                }
+            }
+
+            /// Computes the DCT4 of `buffer` in place, without checking its length.
+            ///
+            /// # Safety
+            ///
+            /// `buffer.len()` must be equal to the size of this butterfly.
+            pub unsafe fn process_inplace_dct4(&self, buffer: &mut [T]) {
+                const HALF: usize = $size / 2;
+
+                //pre-process the input by splitting into into two arrays, one for the inner DCT3, and the other for the DST3
+                let mut cos_buffer = [T::zero(); HALF];
+                let mut sin_buffer = [T::zero(); HALF];
+
+                cos_buffer[0] = *buffer.get_unchecked(0) * T::two();
+                for k in 1..HALF {
+                    let lower = *buffer.get_unchecked(2 * k - 1);
+                    let upper = *buffer.get_unchecked(2 * k);
+                    cos_buffer[k] = lower + upper;
+                    sin_buffer[k - 1] = lower - upper;
+                }
+                sin_buffer[HALF - 1] = *buffer.get_unchecked($size - 1) * T::two();
+
+                self.inner_butterfly.process_inplace_dct3(&mut cos_buffer);
+                self.inner_butterfly.process_inplace_dst3(&mut sin_buffer);
+
+                //post-process the data by combining it back into a single array
+                for k in 0..HALF {
+                    let twiddle = self.twiddles[k];
+                    let cos_value = cos_buffer[k];
+                    let sin_value = sin_buffer[k];
+
+                    *buffer.get_unchecked_mut(k) = cos_value * twiddle.re + sin_value * twiddle.im;
+                    *buffer.get_unchecked_mut($size - 1 - k) =
+                        cos_value * twiddle.im - sin_value * twiddle.re;
+                }
+            }
+
+            /// Computes the DST4 of `buffer` in place, without checking its length.
+            ///
+            /// # Safety
+            ///
+            /// `buffer.len()` must be equal to the size of this butterfly.
+            pub unsafe fn process_inplace_dst4(&self, buffer: &mut [T]) {
+                const HALF: usize = $size / 2;
+
+                //pre-process the input by splitting into into two arrays, one for the inner DCT3, and the other for the DST3
+                let mut cos_buffer = [T::zero(); HALF];
+                let mut sin_buffer = [T::zero(); HALF];
+
+                sin_buffer[0] = *buffer.get_unchecked(0) * T::two();
+                for k in 1..HALF {
+                    let lower = *buffer.get_unchecked(2 * k - 1);
+                    let upper = *buffer.get_unchecked(2 * k);
+                    cos_buffer[k - 1] = lower + upper;
+                    sin_buffer[k] = upper - lower;
+                }
+                cos_buffer[HALF - 1] = *buffer.get_unchecked($size - 1) * T::two();
+
+                self.inner_butterfly.process_inplace_dst3(&mut cos_buffer);
+                self.inner_butterfly.process_inplace_dct3(&mut sin_buffer);
+
+                //post-process the data by combining it back into a single array
+                for k in 0..HALF {
+                    let twiddle = self.twiddles[k];
+                    let cos_value = cos_buffer[k];
+                    let sin_value = sin_buffer[k];
+
+                    *buffer.get_unchecked_mut(k) = cos_value * twiddle.re + sin_value * twiddle.im;
+                    *buffer.get_unchecked_mut($size - 1 - k) =
+                        sin_value * twiddle.re - cos_value * twiddle.im;
+                }
+            }
+        }
+        impl<T: DctNum> Default for $struct_name<T> {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+        impl<T: DctNum> Dct4<T> for $struct_name<T> {
+            plan_hooks!(T);
+            fn process_dct4_with_scratch(&self, buffer: &mut [T], _scratch: &mut [T]) {
+                validate_buffer!(buffer, self.len());
+
+                unsafe {
+                    self.process_inplace_dct4(buffer);
+                }
+            }
+        }
+        impl<T: DctNum> Dst4<T> for $struct_name<T> {
+            plan_hooks!(T);
+            fn process_dst4_with_scratch(&self, buffer: &mut [T], _scratch: &mut [T]) {
+                validate_buffer!(buffer, self.len());
+
+                unsafe {
+                    self.process_inplace_dst4(buffer);
+                }
+            }
+        }
+        impl<T: DctNum> TransformType4<T> for $struct_name<T> {}
+        impl<T: DctNum> PlanIdentity<T> for $struct_name<T> {
+            fn plan_key(&self) -> PlanKey<T> {
+                PlanKey::new(PlanKind::Type4, self.len())
+            }
+        }
+        impl<T: DctNum> FromPlanKey<T> for $struct_name<T> {
+            fn from_plan_key(key: &PlanKey<T>) -> Option<Self> {
+                if *key == PlanKey::new(PlanKind::Type4, $size) {
+                    Some(Self::new())
+                } else {
+                    None
+                }
+            }
+        }
+        impl<T> MemoryUsage for $struct_name<T> {
+            fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+                // the butterfly's fields are all twiddle factors
+                PlanMemory {
+                    twiddles_bytes: std::mem::size_of::<Self>(),
+                    ..PlanMemory::default()
+                }
+            }
+        }
+        impl<T> RequiredScratch for $struct_name<T> {
+            fn get_scratch_len(&self) -> usize {
+                0
+            }
+        }
+        impl<T> Length for $struct_name<T> {
+            fn len(&self) -> usize {
+                $size
+            }
+        }
+    };
+}
+
+dct4_butterfly!(Type4Butterfly4, Type2And3Butterfly2, 4);
+dct4_butterfly!(Type4Butterfly8, Type2And3Butterfly4, 8);
+dct4_butterfly!(Type4Butterfly16, Type2And3Butterfly8, 16);
+dct4_butterfly!(Type4Butterfly32, Type2And3Butterfly16, 32);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algorithm::Type4Naive;
+    use crate::test_utils::{compare_float_vectors, random_signal};
+
+    //the tests for all butterflies will be identical except for the identifiers used and size
+    //so it's ideal for a macro
+    macro_rules! test_butterfly_func {
+        ($test_name:ident, $struct_name:ident, $size:expr) => {
+            #[test]
+            fn $test_name() {
+                let naive = Type4Naive::new($size);
+                let butterfly = $struct_name::new();
+                assert_eq!(butterfly.len(), $size);
+                assert_eq!(butterfly.get_scratch_len(), 0);
+
+                // the butterflies promise not to need any scratch, so verify that they work with an empty scratch slice
+                let input = random_signal($size);
+
+                let mut expected_buffer = input.clone();
+                let mut actual_buffer = input.clone();
+                naive.process_dct4(&mut expected_buffer);
+                butterfly.process_dct4_with_scratch(&mut actual_buffer, &mut []);
+                assert!(
+                    compare_float_vectors(&expected_buffer, &actual_buffer),
+                    "process_dct4() failed, length = {}",
+                    $size
+                );
+
+                let mut expected_buffer = input.clone();
+                let mut actual_buffer = input.clone();
+                naive.process_dst4(&mut expected_buffer);
+                butterfly.process_dst4_with_scratch(&mut actual_buffer, &mut []);
+                assert!(
+                    compare_float_vectors(&expected_buffer, &actual_buffer),
+                    "process_dst4() failed, length = {}",
+                    $size
+                );
+            }
+        };
+    }
+    test_butterfly_func!(test_butterfly4_type4, Type4Butterfly4, 4);
+    test_butterfly_func!(test_butterfly8_type4, Type4Butterfly8, 8);
+    test_butterfly_func!(test_butterfly16_type4, Type4Butterfly16, 16);
+    test_butterfly_func!(test_butterfly32_type4, Type4Butterfly32, 32);
+}
@@ -5,8 +5,12 @@ use rustfft::FftDirection;
 use rustfft::{Fft, Length};
 
 use crate::common::dct_error_inplace;
+use crate::scratch_layout::fft_layout;
+use crate::ScratchLayout;
 use crate::{array_utils::into_complex_mut, DctNum, RequiredScratch};
 use crate::{Dct4, Dst4, TransformType4};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
 
 /// DCT Type 4 and DST Type 4 implementation that converts the problem into a FFT of the same size.
 ///
@@ -63,6 +67,7 @@ impl<T: DctNum> Type4ConvertToFftOdd<T> {
 }
 
 impl<T: DctNum> Dct4<T> for Type4ConvertToFftOdd<T> {
+    plan_hooks!(T);
     fn process_dct4_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
 
@@ -134,7 +139,7 @@ impl<T: DctNum> Dct4<T> for Type4ConvertToFftOdd<T> {
         // run the fft
         self.fft.process_with_scratch(fft_buffer, fft_scratch);
 
-        let result_scale = T::SQRT_2() * T::half();
+        let result_scale = T::sqrt_2() * T::half();
         let second_half_sign = if len % 4 == 1 { T::one() } else { -T::one() };
 
         //post-process the results 4 at a time
@@ -165,6 +170,7 @@ impl<T: DctNum> Dct4<T> for Type4ConvertToFftOdd<T> {
     }
 }
 impl<T: DctNum> Dst4<T> for Type4ConvertToFftOdd<T> {
+    plan_hooks!(T);
     fn process_dst4_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
 
@@ -236,7 +242,7 @@ impl<T: DctNum> Dst4<T> for Type4ConvertToFftOdd<T> {
         // run the fft
         self.fft.process_with_scratch(fft_buffer, fft_scratch);
 
-        let result_scale = T::SQRT_2() * T::half();
+        let result_scale = T::sqrt_2() * T::half();
         let second_half_sign = if len % 4 == 1 { T::one() } else { -T::one() };
 
         //post-process the results 4 at a time
@@ -266,10 +272,27 @@ impl<T: DctNum> Dst4<T> for Type4ConvertToFftOdd<T> {
         }
     }
 }
+impl<T: DctNum> PlanIdentity<T> for Type4ConvertToFftOdd<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Type4, self.len())
+    }
+}
+impl<T> MemoryUsage for Type4ConvertToFftOdd<T> {
+    fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+        // the only precomputed data is the inner FFT
+        PlanMemory::default()
+    }
+}
 impl<T: DctNum> RequiredScratch for Type4ConvertToFftOdd<T> {
     fn get_scratch_len(&self) -> usize {
         self.scratch_len
     }
+    fn get_complex_scratch_len(&self) -> usize {
+        self.scratch_len / 2
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        fft_layout(2 * self.len, self.scratch_len)
+    }
 }
 impl<T: DctNum> TransformType4<T> for Type4ConvertToFftOdd<T> {}
 impl<T> Length for Type4ConvertToFftOdd<T> {
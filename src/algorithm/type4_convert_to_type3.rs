@@ -3,13 +3,25 @@ use std::sync::Arc;
 use rustfft::num_complex::Complex;
 use rustfft::Length;
 
-use crate::common::dct_error_inplace;
+use crate::common::{dct_error_inplace, derived_len};
+use crate::memory::table_bytes;
+use crate::ScratchLayout;
 use crate::{twiddles, Dct4, DctNum, Dst4, RequiredScratch, TransformType2And3, TransformType4};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
 
 /// DCT4 and DST4 implementation that converts the problem into two DCT3 of half size.
 ///
 /// If the inner DCT3 is O(nlogn), then so is this. This algorithm can only be used if the problem size is even.
 ///
+/// The DST4 isn't computed by reversing the input and output of a DCT4. Instead, its pre- and post-processing passes
+/// read and write the buffer with their own index mappings, and swap the roles of the inner DCT3 and DST3, so the
+/// DST4 performs exactly the same number of passes and operations as the DCT4.
+///
+/// Like `Type2And3SplitRadix`, the caller's buffer is reused as scratch space for the inner DCT3, unless the inner
+/// DCT3 needs more than that, in which case the roles of the two buffers are swapped. The required scratch is
+/// `max(len, inner scratch)`.
+///
 /// ~~~
 /// // Computes a DCT Type 4 of size 1234
 /// use std::sync::Arc;
@@ -29,6 +41,7 @@ use crate::{twiddles, Dct4, DctNum, Dst4, RequiredScratch, TransformType2And3, T
 pub struct Type4ConvertToType3Even<T> {
     inner_dct: Arc<dyn TransformType2And3<T>>,
     twiddles: Box<[Complex<T>]>,
+    sub_problems_in_buffer: bool,
     scratch_len: usize,
 }
 
@@ -36,37 +49,58 @@ impl<T: DctNum> Type4ConvertToType3Even<T> {
     /// Creates a new DCT4 context that will process signals of length `inner_dct.len() * 2`.
     pub fn new(inner_dct: Arc<dyn TransformType2And3<T>>) -> Self {
         let inner_len = inner_dct.len();
-        let len = inner_len * 2;
+        let len = derived_len(inner_len, 2, 0, "DCT4");
+        let twiddle_len = derived_len(len, 8, 0, "twiddle table");
 
         let twiddles: Vec<Complex<T>> = (0..inner_len)
-            .map(|i| twiddles::single_twiddle(2 * i + 1, len * 8).conj())
+            .map(|i| twiddles::single_twiddle(2 * i + 1, twiddle_len).conj())
             .collect();
 
+        // we use the caller's buffer as scratch for the inner DCT. if it needs more than that, swap the roles of the two buffers
         let inner_scratch = inner_dct.get_scratch_len();
-        let scratch_len = if inner_scratch <= len {
-            len
-        } else {
-            len + inner_scratch
-        };
+        let sub_problems_in_buffer = inner_scratch > len;
+        let scratch_len = len.max(inner_scratch);
 
         Self {
             inner_dct: inner_dct,
             twiddles: twiddles.into_boxed_slice(),
+            sub_problems_in_buffer,
             scratch_len,
         }
     }
+
+    // Runs the inner DCTs on the two halves of the sub-problems that were prepared in the first `len` elements of `scratch`,
+    // and leaves the results there. Normally the sub-problems stay in the scratch, and the caller's buffer is used as the
+    // inner scratch. If the inner DCT needs more than that, the sub-problems are moved to the caller's buffer, and the
+    // inner DCT gets the whole scratch buffer
+    fn process_sub_problems(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        process_inner: impl Fn(&dyn TransformType2And3<T>, &mut [T], &mut [T], &mut [T]),
+    ) {
+        let len = self.len();
+        if self.sub_problems_in_buffer {
+            buffer.copy_from_slice(&scratch[..len]);
+            let (left, right) = buffer.split_at_mut(len / 2);
+            process_inner(self.inner_dct.as_ref(), left, right, scratch);
+            scratch[..len].copy_from_slice(buffer);
+        } else {
+            let (left, right) = scratch[..len].split_at_mut(len / 2);
+            process_inner(self.inner_dct.as_ref(), left, right, buffer);
+        }
+    }
 }
 impl<T: DctNum> Dct4<T> for Type4ConvertToType3Even<T> {
+    plan_hooks!(T);
     fn process_dct4_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
 
-        let (self_scratch, extra_scratch) = scratch.split_at_mut(self.len());
-
         let len = self.len();
         let inner_len = len / 2;
 
         //pre-process the input by splitting into into two arrays, one for the inner DCT3, and the other for the DST3
-        let (mut output_left, mut output_right) = self_scratch.split_at_mut(inner_len);
+        let (output_left, output_right) = scratch[..len].split_at_mut(inner_len);
 
         output_left[0] = buffer[0] * T::two();
         for k in 1..inner_len {
@@ -76,18 +110,13 @@ impl<T: DctNum> Dct4<T> for Type4ConvertToType3Even<T> {
         output_right[inner_len - 1] = buffer[len - 1] * T::two();
 
         //run the two inner DCTs on our separated arrays
-        let inner_scratch = if extra_scratch.len() > 0 {
-            extra_scratch
-        } else {
-            &mut buffer[..]
-        };
-
-        self.inner_dct
-            .process_dct3_with_scratch(&mut output_left, inner_scratch);
-        self.inner_dct
-            .process_dst3_with_scratch(&mut output_right, inner_scratch);
+        self.process_sub_problems(buffer, scratch, |inner_dct, left, right, inner_scratch| {
+            inner_dct.process_dct3_with_scratch(left, inner_scratch);
+            inner_dct.process_dst3_with_scratch(right, inner_scratch);
+        });
 
         //post-process the data by combining it back into a single array
+        let (output_left, output_right) = scratch[..len].split_at(inner_len);
         for k in 0..inner_len {
             let twiddle = self.twiddles[k];
             let cos_value = output_left[k];
@@ -99,16 +128,15 @@ impl<T: DctNum> Dct4<T> for Type4ConvertToType3Even<T> {
     }
 }
 impl<T: DctNum> Dst4<T> for Type4ConvertToType3Even<T> {
+    plan_hooks!(T);
     fn process_dst4_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
 
-        let (self_scratch, extra_scratch) = scratch.split_at_mut(self.len());
-
         let len = self.len();
         let inner_len = len / 2;
 
         //pre-process the input by splitting into into two arrays, one for the inner DCT3, and the other for the DST3
-        let (mut output_left, mut output_right) = self_scratch.split_at_mut(inner_len);
+        let (output_left, output_right) = scratch[..len].split_at_mut(inner_len);
 
         output_right[0] = buffer[0] * T::two();
         for k in 1..inner_len {
@@ -118,18 +146,13 @@ impl<T: DctNum> Dst4<T> for Type4ConvertToType3Even<T> {
         output_left[inner_len - 1] = buffer[len - 1] * T::two();
 
         //run the two inner DCTs on our separated arrays
-        let inner_scratch = if extra_scratch.len() > 0 {
-            extra_scratch
-        } else {
-            &mut buffer[..]
-        };
-
-        self.inner_dct
-            .process_dst3_with_scratch(&mut output_left, inner_scratch);
-        self.inner_dct
-            .process_dct3_with_scratch(&mut output_right, inner_scratch);
+        self.process_sub_problems(buffer, scratch, |inner_dct, left, right, inner_scratch| {
+            inner_dct.process_dst3_with_scratch(left, inner_scratch);
+            inner_dct.process_dct3_with_scratch(right, inner_scratch);
+        });
 
         //post-process the data by combining it back into a single array
+        let (output_left, output_right) = scratch[..len].split_at(inner_len);
         for k in 0..inner_len {
             let twiddle = self.twiddles[k];
             let cos_value = output_left[k];
@@ -140,10 +163,41 @@ impl<T: DctNum> Dst4<T> for Type4ConvertToType3Even<T> {
         }
     }
 }
-impl<T> RequiredScratch for Type4ConvertToType3Even<T> {
+impl<T: DctNum> PlanIdentity<T> for Type4ConvertToType3Even<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Type4, self.len())
+    }
+}
+impl<T: DctNum> MemoryUsage for Type4ConvertToType3Even<T> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            twiddles_bytes: table_bytes(&self.twiddles),
+            window_bytes: 0,
+            inner_plans_bytes: visitor.inner_plan(&self.inner_dct),
+        }
+    }
+}
+impl<T: DctNum> RequiredScratch for Type4ConvertToType3Even<T> {
     fn get_scratch_len(&self) -> usize {
         self.scratch_len
     }
+    fn get_complex_scratch_len(&self) -> usize {
+        // the inner DCT only gets the scratch buffer when the sub-problems are stored in the caller's buffer
+        if self.sub_problems_in_buffer {
+            self.inner_dct.get_complex_scratch_len()
+        } else {
+            0
+        }
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        // when the inner DCT needs more scratch than `len`, it gets the whole scratch buffer, and the sub-problems are
+        // stored in the caller's buffer while it runs
+        if self.sub_problems_in_buffer {
+            ScratchLayout::new().with_inner(self.inner_dct.scratch_layout())
+        } else {
+            ScratchLayout::new().with_region("sub-problems", self.len(), false)
+        }
+    }
 }
 impl<T: DctNum> TransformType4<T> for Type4ConvertToType3Even<T> {}
 impl<T> Length for Type4ConvertToType3Even<T> {
@@ -155,8 +209,10 @@ impl<T> Length for Type4ConvertToType3Even<T> {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::algorithm::{Type2And3Naive, Type4Naive};
+    use crate::algorithm::{Type2And3ConvertToFft, Type2And3Naive, Type4Naive};
     use crate::test_utils::{compare_float_vectors, random_signal};
+    use rustfft::algorithm::Dft;
+    use rustfft::FftDirection;
 
     #[test]
     fn unittest_dct4_via_type3() {
@@ -211,4 +267,35 @@ mod test {
             );
         }
     }
+
+    /// Verify that an inner DCT3 that needs more scratch than the DCT4's buffer still works correctly
+    #[test]
+    fn unittest_type4_large_inner_scratch() {
+        let size = 64;
+        // the DFT algorithm needs as much scratch space as its length, so the inner DCT will need more scratch than the DCT4's buffer
+        let inner_dct3 = Arc::new(Type2And3ConvertToFft::new(Arc::new(Dft::new(
+            size / 2,
+            FftDirection::Forward,
+        ))));
+        let inner_scratch_len = inner_dct3.get_scratch_len();
+        assert!(inner_scratch_len > size);
+
+        let dct = Type4ConvertToType3Even::new(inner_dct3);
+        assert_eq!(dct.get_scratch_len(), inner_scratch_len);
+
+        let naive_dct = Type4Naive::new(size);
+        let input = random_signal(size);
+
+        let mut expected_buffer = input.clone();
+        let mut actual_buffer = input.clone();
+        naive_dct.process_dct4(&mut expected_buffer);
+        dct.process_dct4(&mut actual_buffer);
+        assert!(compare_float_vectors(&actual_buffer, &expected_buffer));
+
+        let mut expected_buffer = input.clone();
+        let mut actual_buffer = input;
+        naive_dct.process_dst4(&mut expected_buffer);
+        dct.process_dst4(&mut actual_buffer);
+        assert!(compare_float_vectors(&actual_buffer, &expected_buffer));
+    }
 }
@@ -1,10 +1,14 @@
 use rustfft::num_complex::Complex;
 use rustfft::Length;
 
-use crate::common::dct_error_inplace;
+use crate::algorithm::naive_kernel::{twiddle_dot, Accumulation};
+use crate::common::{dct_error_inplace, derived_len};
+use crate::memory::twiddles_only;
 use crate::RequiredScratch;
 use crate::{twiddles, DctNum};
 use crate::{Dct4, Dst4, TransformType4};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
 
 /// Naive O(n^2 ) DCT Type 4 and DST Type 4 implementation
 ///
@@ -24,71 +28,87 @@ use crate::{Dct4, Dst4, TransformType4};
 /// ~~~
 pub struct Type4Naive<T> {
     twiddles: Box<[Complex<T>]>,
+    accumulation: Accumulation,
 }
 
 impl<T: DctNum> Type4Naive<T> {
     /// Creates a new DCT4 and DTS4 context that will process signals of length `len`
     pub fn new(len: usize) -> Self {
-        let twiddles: Vec<Complex<T>> = (0..len * 4)
-            .map(|i| twiddles::single_twiddle_halfoffset(i, len * 4))
+        let twiddle_len = derived_len(len, 4, 0, "twiddle table");
+        let twiddles: Vec<Complex<T>> = (0..twiddle_len)
+            .map(|i| twiddles::single_twiddle_halfoffset(i, twiddle_len))
             .collect();
 
         Self {
             twiddles: twiddles.into_boxed_slice(),
+            accumulation: Accumulation::Simple,
         }
     }
+
+    /// Sets how each output's sum of products is accumulated, which is `Accumulation::Simple` by default. See
+    /// [`Accumulation`](enum.Accumulation.html).
+    pub fn with_accumulation(mut self, accumulation: Accumulation) -> Self {
+        self.accumulation = accumulation;
+        self
+    }
 }
 
 impl<T: DctNum> Dct4<T> for Type4Naive<T> {
+    plan_hooks!(T);
     fn process_dct4_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
         scratch.copy_from_slice(buffer);
 
         for k in 0..buffer.len() {
             let output_cell = buffer.get_mut(k).unwrap();
-            *output_cell = T::zero();
 
-            let mut twiddle_index = k;
+            let twiddle_index = k;
             let twiddle_stride = k * 2 + 1;
 
-            for i in 0..scratch.len() {
-                let twiddle = self.twiddles[twiddle_index];
-
-                *output_cell = *output_cell + scratch[i] * twiddle.re;
-
-                twiddle_index += twiddle_stride;
-                if twiddle_index >= self.twiddles.len() {
-                    twiddle_index -= self.twiddles.len();
-                }
-            }
+            *output_cell = twiddle_dot(
+                scratch,
+                &self.twiddles,
+                twiddle_index,
+                twiddle_stride,
+                |twiddle| twiddle.re,
+                self.accumulation,
+            );
         }
     }
 }
 impl<T: DctNum> Dst4<T> for Type4Naive<T> {
+    plan_hooks!(T);
     fn process_dst4_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
         scratch.copy_from_slice(buffer);
 
         for k in 0..buffer.len() {
             let output_cell = buffer.get_mut(k).unwrap();
-            *output_cell = T::zero();
 
-            let mut twiddle_index = k;
+            let twiddle_index = k;
             let twiddle_stride = k * 2 + 1;
 
-            for i in 0..scratch.len() {
-                let twiddle = self.twiddles[twiddle_index];
-
-                *output_cell = *output_cell - scratch[i] * twiddle.im;
-
-                twiddle_index += twiddle_stride;
-                if twiddle_index >= self.twiddles.len() {
-                    twiddle_index -= self.twiddles.len();
-                }
-            }
+            *output_cell = twiddle_dot(
+                scratch,
+                &self.twiddles,
+                twiddle_index,
+                twiddle_stride,
+                |twiddle| -twiddle.im,
+                self.accumulation,
+            );
         }
     }
 }
+impl<T: DctNum> PlanIdentity<T> for Type4Naive<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Type4, self.len())
+    }
+}
+impl<T> MemoryUsage for Type4Naive<T> {
+    fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+        twiddles_only(&self.twiddles)
+    }
+}
 impl<T> RequiredScratch for Type4Naive<T> {
     fn get_scratch_len(&self) -> usize {
         self.len()
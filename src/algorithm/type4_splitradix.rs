@@ -0,0 +1,266 @@
+use std::sync::Arc;
+
+use rustfft::num_complex::Complex;
+use rustfft::Length;
+
+use crate::common::{dct_error_inplace, derived_len};
+use crate::memory::table_bytes;
+use crate::ScratchLayout;
+use crate::{twiddles, Dct4, DctNum, Dst4, RequiredScratch, TransformType4};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
+
+/// DCT4 and DST4 implementation that recursively divides the problem into a DCT4 and a DST4 of half size.
+///
+/// The even and odd input elements are combined into sums and differences, which go into a half-size DCT4 and DST4,
+/// and then each pair of outputs `k` and `len - 1 - k` is rotated into place. Unlike `Type4ConvertToType3Even`, the
+/// inner transform is another DCT4, so for power-of-two sizes this recurses all the way down to the DCT4 butterflies.
+/// This algorithm can only be used if the problem size is even.
+///
+/// The caller's buffer is reused as scratch space for the inner transforms, unless they need more than that, in which
+/// case the roles of the two buffers are swapped. The required scratch is `max(len, inner scratch)`.
+///
+/// ~~~
+/// // Computes a DCT Type 4 of size 1024
+/// use rustdct::Dct4;
+/// use rustdct::algorithm::Type4SplitRadix;
+/// use rustdct::DctPlanner;
+///
+/// let len = 1024;
+/// let mut planner = DctPlanner::new();
+/// let inner_dct4 = planner.plan_dct4(len / 2);
+///
+/// let dct = Type4SplitRadix::new(inner_dct4);
+///
+/// let mut buffer = vec![0f32; len];
+/// dct.process_dct4(&mut buffer);
+/// ~~~
+pub struct Type4SplitRadix<T> {
+    inner_dct: Arc<dyn TransformType4<T>>,
+    twiddles: Box<[Complex<T>]>,
+    sub_problems_in_buffer: bool,
+    scratch_len: usize,
+}
+
+impl<T: DctNum> Type4SplitRadix<T> {
+    /// Creates a new DCT4 context that will process signals of length `inner_dct.len() * 2`.
+    pub fn new(inner_dct: Arc<dyn TransformType4<T>>) -> Self {
+        let inner_len = inner_dct.len();
+        let len = derived_len(inner_len, 2, 0, "DCT4");
+        let twiddle_len = derived_len(len, 8, 0, "twiddle table");
+
+        let twiddles: Vec<Complex<T>> = (0..inner_len)
+            .map(|i| twiddles::single_twiddle(2 * i + 1, twiddle_len).conj())
+            .collect();
+
+        // we use the caller's buffer as scratch for the inner DCT. if it needs more than that, swap the roles of the two buffers
+        let inner_scratch = inner_dct.get_scratch_len();
+        let sub_problems_in_buffer = inner_scratch > len;
+        let scratch_len = len.max(inner_scratch);
+
+        Self {
+            inner_dct,
+            twiddles: twiddles.into_boxed_slice(),
+            sub_problems_in_buffer,
+            scratch_len,
+        }
+    }
+
+    // Computes the DCT4 of `buffer`, whose even and odd elements have already been combined into sums in the first half
+    // of `scratch` and differences in the second half. If `dst` is true, negates the odd outputs, which turns the DCT4
+    // of the reversed input into the DST4 of the original input
+    fn process_sub_problems(&self, buffer: &mut [T], scratch: &mut [T], dst: bool) {
+        let len = self.len();
+        let inner_len = len / 2;
+
+        // the DCT4 of the sums and the DST4 of the differences
+        if self.sub_problems_in_buffer {
+            buffer.copy_from_slice(&scratch[..len]);
+            let (sums, differences) = buffer.split_at_mut(inner_len);
+            self.inner_dct.process_dct4_with_scratch(sums, scratch);
+            self.inner_dct
+                .process_dst4_with_scratch(differences, scratch);
+            scratch[..len].copy_from_slice(buffer);
+        } else {
+            let (sums, differences) = scratch[..len].split_at_mut(inner_len);
+            self.inner_dct.process_dct4_with_scratch(sums, buffer);
+            self.inner_dct
+                .process_dst4_with_scratch(differences, buffer);
+        }
+
+        // rotate each pair of outputs into place
+        let (cos_values, sin_values) = scratch[..len].split_at(inner_len);
+        for k in 0..inner_len {
+            let twiddle = self.twiddles[k];
+            let cos_value = cos_values[k];
+            let sin_value = sin_values[k];
+
+            let front = cos_value * twiddle.re + sin_value * twiddle.im;
+            let back = sin_value * twiddle.re - cos_value * twiddle.im;
+
+            // since len is even, outputs k and len - 1 - k always have opposite parity
+            if dst && k % 2 == 1 {
+                buffer[k] = -front;
+                buffer[len - 1 - k] = back;
+            } else if dst {
+                buffer[k] = front;
+                buffer[len - 1 - k] = -back;
+            } else {
+                buffer[k] = front;
+                buffer[len - 1 - k] = back;
+            }
+        }
+    }
+}
+impl<T: DctNum> Dct4<T> for Type4SplitRadix<T> {
+    plan_hooks!(T);
+    fn process_dct4_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+
+        let len = self.len();
+        let (sums, differences) = scratch[..len].split_at_mut(len / 2);
+        for ((pair, sum), difference) in buffer
+            .chunks_exact(2)
+            .zip(sums.iter_mut())
+            .zip(differences.iter_mut())
+        {
+            *sum = pair[0] + pair[1];
+            *difference = pair[0] - pair[1];
+        }
+
+        self.process_sub_problems(buffer, scratch, false);
+    }
+}
+impl<T: DctNum> Dst4<T> for Type4SplitRadix<T> {
+    plan_hooks!(T);
+    fn process_dst4_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+
+        // same as the DCT4, but on the reversed input
+        let len = self.len();
+        let (sums, differences) = scratch[..len].split_at_mut(len / 2);
+        for ((pair, sum), difference) in buffer
+            .rchunks_exact(2)
+            .zip(sums.iter_mut())
+            .zip(differences.iter_mut())
+        {
+            *sum = pair[1] + pair[0];
+            *difference = pair[1] - pair[0];
+        }
+
+        self.process_sub_problems(buffer, scratch, true);
+    }
+}
+impl<T: DctNum> PlanIdentity<T> for Type4SplitRadix<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Type4, self.len())
+    }
+}
+impl<T: DctNum> MemoryUsage for Type4SplitRadix<T> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            twiddles_bytes: table_bytes(&self.twiddles),
+            window_bytes: 0,
+            inner_plans_bytes: visitor.inner_plan(&self.inner_dct),
+        }
+    }
+}
+impl<T: DctNum> RequiredScratch for Type4SplitRadix<T> {
+    fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        // the inner DCT only gets the scratch buffer when the sub-problems are stored in the caller's buffer
+        if self.sub_problems_in_buffer {
+            self.inner_dct.get_complex_scratch_len()
+        } else {
+            0
+        }
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        // when the inner DCT needs more scratch than `len`, it gets the whole scratch buffer, and the sub-problems are
+        // stored in the caller's buffer while it runs
+        if self.sub_problems_in_buffer {
+            ScratchLayout::new().with_inner(self.inner_dct.scratch_layout())
+        } else {
+            ScratchLayout::new().with_region("sub-problems", self.len(), false)
+        }
+    }
+}
+impl<T: DctNum> TransformType4<T> for Type4SplitRadix<T> {}
+impl<T> Length for Type4SplitRadix<T> {
+    fn len(&self) -> usize {
+        self.twiddles.len() * 2
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algorithm::{Type4ConvertToFftOdd, Type4Naive};
+    use crate::test_utils::{compare_float_vectors, random_signal};
+    use rustfft::algorithm::Dft;
+    use rustfft::FftDirection;
+
+    #[test]
+    fn unittest_type4_splitradix() {
+        for inner_size in 1..20 {
+            let size = inner_size * 2;
+            let input = random_signal(size);
+
+            let naive = Type4Naive::new(size);
+            let dct = Type4SplitRadix::new(Arc::new(Type4Naive::new(inner_size)));
+
+            let mut expected_buffer = input.clone();
+            let mut actual_buffer = input.clone();
+            naive.process_dct4(&mut expected_buffer);
+            dct.process_dct4(&mut actual_buffer);
+            assert!(
+                compare_float_vectors(&expected_buffer, &actual_buffer),
+                "dct4, len = {}",
+                size
+            );
+
+            let mut expected_buffer = input.clone();
+            let mut actual_buffer = input;
+            naive.process_dst4(&mut expected_buffer);
+            dct.process_dst4(&mut actual_buffer);
+            assert!(
+                compare_float_vectors(&expected_buffer, &actual_buffer),
+                "dst4, len = {}",
+                size
+            );
+        }
+    }
+
+    /// Verify that an inner DCT4 that needs more scratch than the DCT4's buffer still works correctly
+    #[test]
+    fn unittest_type4_splitradix_large_inner_scratch() {
+        let size = 18;
+        // the DFT algorithm needs as much scratch space as its length, so the inner DCT will need more scratch than the DCT4's buffer
+        let inner_dct4 = Arc::new(Type4ConvertToFftOdd::new(Arc::new(Dft::new(
+            size / 2,
+            FftDirection::Forward,
+        ))));
+        let inner_scratch_len = inner_dct4.get_scratch_len();
+        assert!(inner_scratch_len > size);
+
+        let dct = Type4SplitRadix::new(inner_dct4);
+        assert_eq!(dct.get_scratch_len(), inner_scratch_len);
+
+        let naive_dct = Type4Naive::new(size);
+        let input = random_signal(size);
+
+        let mut expected_buffer = input.clone();
+        let mut actual_buffer = input.clone();
+        naive_dct.process_dct4(&mut expected_buffer);
+        dct.process_dct4(&mut actual_buffer);
+        assert!(compare_float_vectors(&actual_buffer, &expected_buffer));
+
+        let mut expected_buffer = input.clone();
+        let mut actual_buffer = input;
+        naive_dct.process_dst4(&mut expected_buffer);
+        dct.process_dst4(&mut actual_buffer);
+        assert!(compare_float_vectors(&actual_buffer, &expected_buffer));
+    }
+}
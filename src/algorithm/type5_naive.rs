@@ -2,9 +2,13 @@ use std::f64;
 
 use rustfft::Length;
 
-use crate::common::dct_error_inplace;
+use crate::algorithm::naive_kernel::{accumulate, twiddle_dot, Accumulation};
+use crate::common::{dct_error_inplace, derived_len};
+use crate::memory::twiddles_only;
 use crate::RequiredScratch;
 use crate::{Dct5, DctNum, Dst5};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
 
 /// Naive O(n^2 ) DCT Type 5 implementation
 ///
@@ -23,51 +27,77 @@ use crate::{Dct5, DctNum, Dst5};
 /// ~~~
 pub struct Dct5Naive<T> {
     twiddles: Box<[T]>,
+    accumulation: Accumulation,
 }
 
 impl<T: DctNum> Dct5Naive<T> {
     pub fn new(len: usize) -> Self {
         let constant_factor = f64::consts::PI / (len as f64 - 0.5);
 
-        let twiddles: Vec<T> = (0..len * 2 - 1)
+        let twiddle_len = if len == 0 {
+            0
+        } else {
+            derived_len(len, 2, -1, "twiddle table")
+        };
+        let twiddles: Vec<T> = (0..twiddle_len)
             .map(|i| (constant_factor * (i as f64)).cos())
             .map(|c| T::from_f64(c).unwrap())
             .collect();
 
         Self {
             twiddles: twiddles.into_boxed_slice(),
+            accumulation: Accumulation::Simple,
         }
     }
+
+    /// Sets how each output's sum of products is accumulated, which is `Accumulation::Simple` by default. See
+    /// [`Accumulation`](enum.Accumulation.html).
+    pub fn with_accumulation(mut self, accumulation: Accumulation) -> Self {
+        self.accumulation = accumulation;
+        self
+    }
 }
 
 impl<T: DctNum> Dct5<T> for Dct5Naive<T> {
+    plan_hooks!(T);
     fn process_dct5_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+        if buffer.is_empty() {
+            return;
+        }
         scratch.copy_from_slice(buffer);
 
         scratch[0] = scratch[0] * T::half();
-        buffer[0] = scratch.iter().fold(T::zero(), |acc, e| acc + *e);
+        buffer[0] = accumulate(scratch, self.accumulation);
 
         for k in 1..buffer.len() {
             let output_cell = buffer.get_mut(k).unwrap();
-            *output_cell = scratch[0];
 
             let twiddle_stride = k;
-            let mut twiddle_index = twiddle_stride;
-
-            for i in 1..scratch.len() {
-                let twiddle = self.twiddles[twiddle_index];
-
-                *output_cell = *output_cell + scratch[i] * twiddle;
-
-                twiddle_index += twiddle_stride;
-                if twiddle_index >= self.twiddles.len() {
-                    twiddle_index -= self.twiddles.len();
-                }
-            }
+            let twiddle_index = twiddle_stride;
+
+            *output_cell = scratch[0]
+                + twiddle_dot(
+                    &scratch[1..],
+                    &self.twiddles,
+                    twiddle_index,
+                    twiddle_stride,
+                    |twiddle| twiddle,
+                    self.accumulation,
+                );
         }
     }
 }
+impl<T: DctNum> PlanIdentity<T> for Dct5Naive<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Dct5, self.len())
+    }
+}
+impl<T> MemoryUsage for Dct5Naive<T> {
+    fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+        twiddles_only(&self.twiddles)
+    }
+}
 impl<T> RequiredScratch for Dct5Naive<T> {
     fn get_scratch_len(&self) -> usize {
         self.len()
@@ -96,6 +126,7 @@ impl<T> Length for Dct5Naive<T> {
 /// ~~~
 pub struct Dst5Naive<T> {
     twiddles: Box<[T]>,
+    accumulation: Accumulation,
 }
 
 impl<T: DctNum> Dst5Naive<T> {
@@ -103,42 +134,59 @@ impl<T: DctNum> Dst5Naive<T> {
     pub fn new(len: usize) -> Self {
         let constant_factor = f64::consts::PI / (len as f64 + 0.5);
 
-        let twiddles: Vec<T> = (0..len * 2 + 1)
+        let twiddle_len = derived_len(len, 2, 1, "twiddle table");
+        let twiddles: Vec<T> = (0..twiddle_len)
             .map(|i| (constant_factor * (i as f64)).sin())
             .map(|c| T::from_f64(c).unwrap())
             .collect();
 
         Self {
             twiddles: twiddles.into_boxed_slice(),
+            accumulation: Accumulation::Simple,
         }
     }
+
+    /// Sets how each output's sum of products is accumulated, which is `Accumulation::Simple` by default. See
+    /// [`Accumulation`](enum.Accumulation.html).
+    pub fn with_accumulation(mut self, accumulation: Accumulation) -> Self {
+        self.accumulation = accumulation;
+        self
+    }
 }
 
 impl<T: DctNum> Dst5<T> for Dst5Naive<T> {
+    plan_hooks!(T);
     fn process_dst5_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
         scratch.copy_from_slice(buffer);
 
         for k in 0..buffer.len() {
             let output_cell = buffer.get_mut(k).unwrap();
-            *output_cell = T::zero();
 
             let twiddle_stride = k + 1;
-            let mut twiddle_index = twiddle_stride;
-
-            for i in 0..scratch.len() {
-                let twiddle = self.twiddles[twiddle_index];
-
-                *output_cell = *output_cell + scratch[i] * twiddle;
-
-                twiddle_index += twiddle_stride;
-                if twiddle_index >= self.twiddles.len() {
-                    twiddle_index -= self.twiddles.len();
-                }
-            }
+            let twiddle_index = twiddle_stride;
+
+            *output_cell = twiddle_dot(
+                scratch,
+                &self.twiddles,
+                twiddle_index,
+                twiddle_stride,
+                |twiddle| twiddle,
+                self.accumulation,
+            );
         }
     }
 }
+impl<T: DctNum> PlanIdentity<T> for Dst5Naive<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Dst5, self.len())
+    }
+}
+impl<T> MemoryUsage for Dst5Naive<T> {
+    fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+        twiddles_only(&self.twiddles)
+    }
+}
 impl<T> RequiredScratch for Dst5Naive<T> {
     fn get_scratch_len(&self) -> usize {
         self.len()
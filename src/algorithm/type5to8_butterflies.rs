@@ -0,0 +1,389 @@
+use std::f64;
+
+use rustfft::Length;
+
+use crate::common::dct_error_inplace;
+use crate::RequiredScratch;
+use crate::{Dct5, Dct6, Dct6And7, Dct7, Dct8, DctNum, Dst5, Dst6, Dst6And7, Dst7, Dst8};
+use crate::{FromPlanKey, PlanIdentity, PlanKey, PlanKind};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+
+// The transforms of types 5 through 8 are defined by the matrix entries below, where `k` is the output index, `n` is
+// the input index, and `len` is the size. The planner uses these butterflies for every size from 0 to 4, regardless of
+// its cost model, so that tiny sizes don't depend on the edge cases of the naive twiddle tables or the conversions to
+// other transforms. Size 0 has an empty matrix, so its process methods don't do anything.
+fn dct5_entry(k: usize, n: usize, len: usize) -> f64 {
+    let weight = if n == 0 { 0.5 } else { 1.0 };
+    weight * (f64::consts::PI * (k * n) as f64 / (len as f64 - 0.5)).cos()
+}
+fn dct6_entry(k: usize, n: usize, len: usize) -> f64 {
+    let weight = if n == len - 1 { 0.5 } else { 1.0 };
+    weight * (f64::consts::PI * k as f64 * (n as f64 + 0.5) / (len as f64 - 0.5)).cos()
+}
+fn dct7_entry(k: usize, n: usize, len: usize) -> f64 {
+    let weight = if n == 0 { 0.5 } else { 1.0 };
+    weight * (f64::consts::PI * (k as f64 + 0.5) * n as f64 / (len as f64 - 0.5)).cos()
+}
+fn dct8_entry(k: usize, n: usize, len: usize) -> f64 {
+    (f64::consts::PI * (k as f64 + 0.5) * (n as f64 + 0.5) / (len as f64 + 0.5)).cos()
+}
+fn dst5_entry(k: usize, n: usize, len: usize) -> f64 {
+    (f64::consts::PI * ((k + 1) * (n + 1)) as f64 / (len as f64 + 0.5)).sin()
+}
+fn dst6_entry(k: usize, n: usize, len: usize) -> f64 {
+    (f64::consts::PI * (k + 1) as f64 * (n as f64 + 0.5) / (len as f64 + 0.5)).sin()
+}
+fn dst7_entry(k: usize, n: usize, len: usize) -> f64 {
+    (f64::consts::PI * (k as f64 + 0.5) * (n + 1) as f64 / (len as f64 + 0.5)).sin()
+}
+fn dst8_entry(k: usize, n: usize, len: usize) -> f64 {
+    let weight = if n == len - 1 { 0.5 } else { 1.0 };
+    weight * (f64::consts::PI * (k as f64 + 0.5) * (n as f64 + 0.5) / (len as f64 - 0.5)).sin()
+}
+
+// Multiplies `input` by `matrix`, and writes the products to `output`
+fn multiply<T: DctNum, R: AsRef<[T]>>(matrix: &[R], input: &[T], output: &mut [T]) {
+    for (output_cell, row) in output.iter_mut().zip(matrix.iter()) {
+        let mut sum = T::zero();
+        for (input_cell, matrix_cell) in input.iter().zip(row.as_ref().iter()) {
+            sum = sum + *input_cell * *matrix_cell;
+        }
+        *output_cell = sum;
+    }
+}
+
+// Like the type 1 butterflies, each butterfly stores its entire transform matrix inline and copies its input to a
+// fixed-size array on the stack, so that it doesn't need any scratch space. The DCT6 and DCT7 butterflies, and the DST6
+// and DST7 butterflies, store one matrix for each transform.
+//
+// The arrays are filled from a local zero, since clippy rejects function calls in the initializers of size 0 arrays
+macro_rules! matrix_butterfly {
+    (
+        $struct_name:ident, $size:expr, $kind:ident, $doc:literal,
+        [$(($trait_name:ident, $process_fn:ident, $matrix:ident, $entry_fn:ident)),+]
+        $(, $combined_trait:ident)?
+    ) => {
+        #[doc = $doc]
+        ///
+        /// `get_scratch_len()` always returns 0, so the process methods can be given an empty scratch slice.
+        pub struct $struct_name<T> {
+            $($matrix: [[T; $size]; $size],)+
+        }
+        impl<T: DctNum> $struct_name<T> {
+            /// Creates a new butterfly
+            pub fn new() -> Self {
+                Self {
+                    $($matrix: {
+                        let zero = T::zero();
+                        let mut matrix = [[zero; $size]; $size];
+                        for (k, row) in matrix.iter_mut().enumerate() {
+                            for (n, cell) in row.iter_mut().enumerate() {
+                                *cell = T::from_f64($entry_fn(k, n, $size)).unwrap();
+                            }
+                        }
+                        matrix
+                    },)+
+                }
+            }
+        }
+        impl<T: DctNum> Default for $struct_name<T> {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+        $(
+            impl<T: DctNum> $trait_name<T> for $struct_name<T> {
+                plan_hooks!(T);
+                fn $process_fn(&self, buffer: &mut [T], _scratch: &mut [T]) {
+                    validate_buffer!(buffer, self.len());
+
+                    let zero = T::zero();
+                    let mut input = [zero; $size];
+                    input.copy_from_slice(buffer);
+                    multiply(&self.$matrix, &input, buffer);
+                }
+            }
+        )+
+        $(impl<T: DctNum> $combined_trait<T> for $struct_name<T> {})?
+        impl<T: DctNum> PlanIdentity<T> for $struct_name<T> {
+            fn plan_key(&self) -> PlanKey<T> {
+                PlanKey::new(PlanKind::$kind, self.len())
+            }
+        }
+        impl<T: DctNum> FromPlanKey<T> for $struct_name<T> {
+            fn from_plan_key(key: &PlanKey<T>) -> Option<Self> {
+                if *key == PlanKey::new(PlanKind::$kind, $size) {
+                    Some(Self::new())
+                } else {
+                    None
+                }
+            }
+        }
+        impl<T> MemoryUsage for $struct_name<T> {
+            fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+                // the butterfly's fields are all twiddle factors
+                PlanMemory {
+                    twiddles_bytes: std::mem::size_of::<Self>(),
+                    ..PlanMemory::default()
+                }
+            }
+        }
+        impl<T> RequiredScratch for $struct_name<T> {
+            fn get_scratch_len(&self) -> usize {
+                0
+            }
+        }
+        impl<T> Length for $struct_name<T> {
+            fn len(&self) -> usize {
+                $size
+            }
+        }
+    };
+}
+
+macro_rules! type5to8_butterflies {
+    ($size:expr, $dct5:ident, $dct6and7:ident, $dct8:ident, $dst5:ident, $dst6and7:ident, $dst8:ident) => {
+        matrix_butterfly!(
+            $dct5,
+            $size,
+            Dct5,
+            "DCT Type 5 implementation for a hardcoded size, which requires no scratch space.",
+            [(Dct5, process_dct5_with_scratch, matrix, dct5_entry)]
+        );
+        matrix_butterfly!(
+            $dct6and7,
+            $size,
+            Dct6And7,
+            "DCT Type 6 and DCT Type 7 implementation for a hardcoded size, which requires no scratch space.",
+            [
+                (Dct6, process_dct6_with_scratch, dct6_matrix, dct6_entry),
+                (Dct7, process_dct7_with_scratch, dct7_matrix, dct7_entry)
+            ],
+            Dct6And7
+        );
+        matrix_butterfly!(
+            $dct8,
+            $size,
+            Dct8,
+            "DCT Type 8 implementation for a hardcoded size, which requires no scratch space.",
+            [(Dct8, process_dct8_with_scratch, matrix, dct8_entry)]
+        );
+        matrix_butterfly!(
+            $dst5,
+            $size,
+            Dst5,
+            "DST Type 5 implementation for a hardcoded size, which requires no scratch space.",
+            [(Dst5, process_dst5_with_scratch, matrix, dst5_entry)]
+        );
+        matrix_butterfly!(
+            $dst6and7,
+            $size,
+            Dst6And7,
+            "DST Type 6 and DST Type 7 implementation for a hardcoded size, which requires no scratch space.",
+            [
+                (Dst6, process_dst6_with_scratch, dst6_matrix, dst6_entry),
+                (Dst7, process_dst7_with_scratch, dst7_matrix, dst7_entry)
+            ],
+            Dst6And7
+        );
+        matrix_butterfly!(
+            $dst8,
+            $size,
+            Dst8,
+            "DST Type 8 implementation for a hardcoded size, which requires no scratch space.",
+            [(Dst8, process_dst8_with_scratch, matrix, dst8_entry)]
+        );
+    };
+}
+
+type5to8_butterflies!(
+    0,
+    Dct5Butterfly0,
+    Dct6And7Butterfly0,
+    Dct8Butterfly0,
+    Dst5Butterfly0,
+    Dst6And7Butterfly0,
+    Dst8Butterfly0
+);
+type5to8_butterflies!(
+    1,
+    Dct5Butterfly1,
+    Dct6And7Butterfly1,
+    Dct8Butterfly1,
+    Dst5Butterfly1,
+    Dst6And7Butterfly1,
+    Dst8Butterfly1
+);
+type5to8_butterflies!(
+    2,
+    Dct5Butterfly2,
+    Dct6And7Butterfly2,
+    Dct8Butterfly2,
+    Dst5Butterfly2,
+    Dst6And7Butterfly2,
+    Dst8Butterfly2
+);
+type5to8_butterflies!(
+    3,
+    Dct5Butterfly3,
+    Dct6And7Butterfly3,
+    Dct8Butterfly3,
+    Dst5Butterfly3,
+    Dst6And7Butterfly3,
+    Dst8Butterfly3
+);
+type5to8_butterflies!(
+    4,
+    Dct5Butterfly4,
+    Dct6And7Butterfly4,
+    Dct8Butterfly4,
+    Dst5Butterfly4,
+    Dst6And7Butterfly4,
+    Dst8Butterfly4
+);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algorithm::{
+        Dct5Naive, Dct6And7Naive, Dct8Naive, Dst5Naive, Dst6And7Naive, Dst8Naive,
+    };
+    use crate::test_utils::{compare_float_vectors, random_signal};
+
+    // Verifies that `butterfly` matches `naive`, for one process method
+    macro_rules! compare_with_naive {
+        ($butterfly:expr, $naive:expr, $process_fn:ident, $process_with_scratch_fn:ident) => {
+            let butterfly = $butterfly;
+            assert_eq!(butterfly.get_scratch_len(), 0);
+
+            let mut expected_buffer = random_signal(butterfly.len());
+            let mut actual_buffer = expected_buffer.clone();
+
+            $naive.$process_fn(&mut expected_buffer);
+            butterfly.$process_with_scratch_fn(&mut actual_buffer, &mut []);
+
+            println!("expected output: {:?}", expected_buffer);
+            println!("actual output:   {:?}", actual_buffer);
+            assert!(
+                compare_float_vectors(&expected_buffer, &actual_buffer),
+                "{}() failed, length = {}",
+                stringify!($process_fn),
+                butterfly.len()
+            );
+        };
+    }
+
+    //the tests for all butterflies will be identical except for the identifiers used and size
+    //so it's ideal for a macro
+    macro_rules! test_butterfly_func {
+        ($test_name:ident, $size:expr, $dct5:ident, $dct6and7:ident, $dct8:ident, $dst5:ident, $dst6and7:ident, $dst8:ident) => {
+            #[test]
+            fn $test_name() {
+                let size = $size;
+                compare_with_naive!(
+                    $dct5::new(),
+                    Dct5Naive::new(size),
+                    process_dct5,
+                    process_dct5_with_scratch
+                );
+                compare_with_naive!(
+                    $dct6and7::new(),
+                    Dct6And7Naive::new(size),
+                    process_dct6,
+                    process_dct6_with_scratch
+                );
+                compare_with_naive!(
+                    $dct6and7::new(),
+                    Dct6And7Naive::new(size),
+                    process_dct7,
+                    process_dct7_with_scratch
+                );
+                compare_with_naive!(
+                    $dct8::new(),
+                    Dct8Naive::new(size),
+                    process_dct8,
+                    process_dct8_with_scratch
+                );
+                compare_with_naive!(
+                    $dst5::new(),
+                    Dst5Naive::new(size),
+                    process_dst5,
+                    process_dst5_with_scratch
+                );
+                compare_with_naive!(
+                    $dst6and7::new(),
+                    Dst6And7Naive::new(size),
+                    process_dst6,
+                    process_dst6_with_scratch
+                );
+                compare_with_naive!(
+                    $dst6and7::new(),
+                    Dst6And7Naive::new(size),
+                    process_dst7,
+                    process_dst7_with_scratch
+                );
+                compare_with_naive!(
+                    $dst8::new(),
+                    Dst8Naive::new(size),
+                    process_dst8,
+                    process_dst8_with_scratch
+                );
+            }
+        };
+    }
+    test_butterfly_func!(
+        test_butterfly1_type5to8,
+        1,
+        Dct5Butterfly1,
+        Dct6And7Butterfly1,
+        Dct8Butterfly1,
+        Dst5Butterfly1,
+        Dst6And7Butterfly1,
+        Dst8Butterfly1
+    );
+    test_butterfly_func!(
+        test_butterfly2_type5to8,
+        2,
+        Dct5Butterfly2,
+        Dct6And7Butterfly2,
+        Dct8Butterfly2,
+        Dst5Butterfly2,
+        Dst6And7Butterfly2,
+        Dst8Butterfly2
+    );
+    test_butterfly_func!(
+        test_butterfly3_type5to8,
+        3,
+        Dct5Butterfly3,
+        Dct6And7Butterfly3,
+        Dct8Butterfly3,
+        Dst5Butterfly3,
+        Dst6And7Butterfly3,
+        Dst8Butterfly3
+    );
+    test_butterfly_func!(
+        test_butterfly4_type5to8,
+        4,
+        Dct5Butterfly4,
+        Dct6And7Butterfly4,
+        Dct8Butterfly4,
+        Dst5Butterfly4,
+        Dst6And7Butterfly4,
+        Dst8Butterfly4
+    );
+
+    /// The naive algorithms don't support size 0, so verify the empty butterflies directly
+    #[test]
+    fn test_butterfly0_type5to8() {
+        let mut buffer: Vec<f32> = Vec::new();
+        Dct5Butterfly0::new().process_dct5_with_scratch(&mut buffer, &mut []);
+        Dct6And7Butterfly0::new().process_dct6_with_scratch(&mut buffer, &mut []);
+        Dct6And7Butterfly0::new().process_dct7_with_scratch(&mut buffer, &mut []);
+        Dct8Butterfly0::new().process_dct8_with_scratch(&mut buffer, &mut []);
+        Dst5Butterfly0::new().process_dst5_with_scratch(&mut buffer, &mut []);
+        Dst6And7Butterfly0::new().process_dst6_with_scratch(&mut buffer, &mut []);
+        Dst6And7Butterfly0::new().process_dst7_with_scratch(&mut buffer, &mut []);
+        Dst8Butterfly0::new().process_dst8_with_scratch(&mut buffer, &mut []);
+        assert!(buffer.is_empty());
+    }
+}
@@ -1,11 +1,16 @@
 use std::sync::Arc;
 
 use rustfft::num_complex::Complex;
+use rustfft::num_traits::Zero;
 use rustfft::{Fft, FftDirection, Length};
 
 use crate::common::dct_error_inplace;
+use crate::scratch_layout::fft_layout;
+use crate::ScratchLayout;
 use crate::{array_utils::into_complex_mut, DctNum, RequiredScratch};
 use crate::{Dst6, Dst6And7, Dst7};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
 
 /// DST6 and DST7 implementation that converts the problem into a FFT of the same size
 ///
@@ -59,13 +64,18 @@ impl<T: DctNum> Dst6And7ConvertToFft<T> {
     }
 }
 impl<T: DctNum> Dst6<T> for Dst6And7ConvertToFft<T> {
+    plan_hooks!(T);
     fn process_dst6_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
 
         let complex_scratch = into_complex_mut(scratch);
         let (fft_buffer, fft_scratch) = complex_scratch.split_at_mut(self.inner_fft_len);
 
-        // Copy the buffer to the odd imaginary components of the FFT inputs
+        // Copy the buffer to the odd imaginary components of the FFT inputs. Every other component is zero, and the scratch
+        // may contain data from a previous call, so clear it first
+        for element in fft_buffer.iter_mut() {
+            *element = Complex::zero();
+        }
         for i in 0..buffer.len() {
             fft_buffer[i * 2 + 1].im = buffer[i];
         }
@@ -89,6 +99,7 @@ impl<T: DctNum> Dst6<T> for Dst6And7ConvertToFft<T> {
     }
 }
 impl<T: DctNum> Dst7<T> for Dst6And7ConvertToFft<T> {
+    plan_hooks!(T);
     fn process_dst7_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
 
@@ -115,7 +126,8 @@ impl<T: DctNum> Dst7<T> for Dst6And7ConvertToFft<T> {
                 im: T::zero(),
             };
         }
-        // Copy the back of the array to the front, negated and reversed
+        // Copy the back of the array to the front, negated and reversed, after a leading zero
+        fft_buffer[0] = Complex::zero();
         for i in 0..buffer.len() {
             fft_buffer[i + 1] = -fft_buffer[fft_buffer.len() - 1 - i];
         }
@@ -130,10 +142,27 @@ impl<T: DctNum> Dst7<T> for Dst6And7ConvertToFft<T> {
     }
 }
 impl<T: DctNum> Dst6And7<T> for Dst6And7ConvertToFft<T> {}
+impl<T: DctNum> PlanIdentity<T> for Dst6And7ConvertToFft<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Dst6And7, self.len())
+    }
+}
+impl<T> MemoryUsage for Dst6And7ConvertToFft<T> {
+    fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+        // the only precomputed data is the inner FFT
+        PlanMemory::default()
+    }
+}
 impl<T: DctNum> RequiredScratch for Dst6And7ConvertToFft<T> {
     fn get_scratch_len(&self) -> usize {
         self.scratch_len
     }
+    fn get_complex_scratch_len(&self) -> usize {
+        self.scratch_len / 2
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        fft_layout(2 * self.inner_fft_len, self.scratch_len)
+    }
 }
 impl<T> Length for Dst6And7ConvertToFft<T> {
     fn len(&self) -> usize {
@@ -204,4 +233,29 @@ mod test {
             );
         }
     }
+
+    /// Verify that leftover data in the scratch buffer doesn't affect the output
+    #[test]
+    fn test_dst6_and7_dirty_scratch() {
+        let size = 20;
+        let mut fft_planner = FftPlanner::new();
+        let dst = Dst6And7ConvertToFft::new(fft_planner.plan_fft_forward(size * 2 + 1));
+        let naive_dst = Dst6And7Naive::new(size);
+
+        let mut dirty_scratch = vec![100f32; dst.get_scratch_len()];
+
+        let mut expected_buffer = random_signal(size);
+        let mut actual_buffer = expected_buffer.clone();
+        naive_dst.process_dst6(&mut expected_buffer);
+        dst.process_dst6_with_scratch(&mut actual_buffer, &mut dirty_scratch);
+        assert!(compare_float_vectors(&actual_buffer, &expected_buffer));
+
+        let mut dirty_scratch = vec![100f32; dst.get_scratch_len()];
+
+        let mut expected_buffer = random_signal(size);
+        let mut actual_buffer = expected_buffer.clone();
+        naive_dst.process_dst7(&mut expected_buffer);
+        dst.process_dst7_with_scratch(&mut actual_buffer, &mut dirty_scratch);
+        assert!(compare_float_vectors(&actual_buffer, &expected_buffer));
+    }
 }
@@ -0,0 +1,322 @@
+use std::sync::Arc;
+
+use rustfft::Length;
+
+use crate::common::{dct_error_inplace, derived_len, offset_complex_scratch_len};
+use crate::ScratchLayout;
+use crate::TransformType2And3;
+use crate::{Dct6, Dct6And7, Dct7, DctNum, Dst6, Dst6And7, Dst7, RequiredScratch};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
+
+/// DCT6 and DCT7 implementation that converts the problem into a DCT2 or DCT3 of odd size `2 * len - 1`.
+///
+/// The DCT6 is the even half of the outputs of a DCT2 whose input is zero-padded to `2 * len - 1` elements, and the
+/// DCT7 is the first half of the outputs of a DCT3 whose input has zeroes between each element. Half of the inner
+/// transform's work is discarded, but if the inner DCT is O(nlogn), then so is this.
+///
+/// ~~~
+/// // Computes a O(NlogN) DCT6 and DCT7 of size 1234 by converting them to a DCT2 and DCT3 of size 2467
+/// use rustdct::{Dct6, Dct7, DctPlanner};
+/// use rustdct::algorithm::Dct6And7ConvertToType2And3;
+///
+/// let len = 1234;
+/// let mut planner = DctPlanner::new();
+/// let inner_dct = planner.plan_dct2(len * 2 - 1);
+///
+/// let dct = Dct6And7ConvertToType2And3::new(inner_dct);
+///
+/// let mut dct6_buffer = vec![0f32; len];
+/// dct.process_dct6(&mut dct6_buffer);
+///
+/// let mut dct7_buffer = vec![0f32; len];
+/// dct.process_dct7(&mut dct7_buffer);
+/// ~~~
+pub struct Dct6And7ConvertToType2And3<T> {
+    inner_dct: Arc<dyn TransformType2And3<T>>,
+    len: usize,
+    scratch_len: usize,
+}
+
+impl<T: DctNum> Dct6And7ConvertToType2And3<T> {
+    /// Creates a new DCT6 and DCT7 context that will process signals of length `(inner_dct.len() + 1) / 2`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `inner_dct.len()` is even.
+    pub fn new(inner_dct: Arc<dyn TransformType2And3<T>>) -> Self {
+        let inner_len = inner_dct.len();
+        assert!(
+            inner_len % 2 == 1,
+            "The 'Dct6And7ConvertToType2And3' algorithm requires an odd-len inner DCT. Provided len={}",
+            inner_len
+        );
+
+        Self {
+            len: inner_len / 2 + 1,
+            scratch_len: derived_len(
+                inner_len,
+                1,
+                inner_dct.get_scratch_len() as isize,
+                "scratch",
+            ),
+            inner_dct,
+        }
+    }
+}
+impl<T: DctNum> Dct6<T> for Dct6And7ConvertToType2And3<T> {
+    plan_hooks!(T);
+    fn process_dct6_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+        let (inner_buffer, inner_scratch) = scratch.split_at_mut(self.inner_dct.len());
+
+        // the DCT6 halves its last input. the rest of the DCT2's input is zero
+        let (head, tail) = inner_buffer.split_at_mut(self.len());
+        head.copy_from_slice(buffer);
+        head[self.len() - 1] = head[self.len() - 1] * T::half();
+        for element in tail.iter_mut() {
+            *element = T::zero();
+        }
+
+        self.inner_dct
+            .process_dct2_with_scratch(inner_buffer, inner_scratch);
+
+        for (element, inner_element) in buffer.iter_mut().zip(inner_buffer.iter().step_by(2)) {
+            *element = *inner_element;
+        }
+    }
+}
+impl<T: DctNum> Dct7<T> for Dct6And7ConvertToType2And3<T> {
+    plan_hooks!(T);
+    fn process_dct7_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+        let (inner_buffer, inner_scratch) = scratch.split_at_mut(self.inner_dct.len());
+
+        // put the input in the even elements of the DCT3's input, and zero out the odd elements
+        for (i, element) in inner_buffer.iter_mut().enumerate() {
+            *element = if i % 2 == 0 { buffer[i / 2] } else { T::zero() };
+        }
+
+        self.inner_dct
+            .process_dct3_with_scratch(inner_buffer, inner_scratch);
+
+        buffer.copy_from_slice(&inner_buffer[..self.len()]);
+    }
+}
+impl<T: DctNum> Dct6And7<T> for Dct6And7ConvertToType2And3<T> {}
+impl<T: DctNum> PlanIdentity<T> for Dct6And7ConvertToType2And3<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Dct6And7, self.len())
+    }
+}
+impl<T: DctNum> MemoryUsage for Dct6And7ConvertToType2And3<T> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            inner_plans_bytes: visitor.inner_plan(&self.inner_dct),
+            ..PlanMemory::default()
+        }
+    }
+}
+impl<T: DctNum> RequiredScratch for Dct6And7ConvertToType2And3<T> {
+    fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        offset_complex_scratch_len(
+            self.inner_dct.len(),
+            self.inner_dct.get_complex_scratch_len(),
+        )
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        ScratchLayout::new()
+            .with_region("inner buffer", self.inner_dct.len(), false)
+            .with_inner(self.inner_dct.scratch_layout())
+    }
+}
+impl<T> Length for Dct6And7ConvertToType2And3<T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// DST6 and DST7 implementation that converts the problem into a DST2 or DST3 of odd size `2 * len + 1`.
+///
+/// The DST6 is the odd half of the outputs of a DST2 whose input is zero-padded to `2 * len + 1` elements, and the
+/// DST7 is the first half of the outputs of a DST3 whose input has zeroes between each element. Half of the inner
+/// transform's work is discarded, but if the inner DST is O(nlogn), then so is this.
+///
+/// ~~~
+/// // Computes a O(NlogN) DST6 and DST7 of size 1234 by converting them to a DST2 and DST3 of size 2469
+/// use rustdct::{Dst6, Dst7, DctPlanner};
+/// use rustdct::algorithm::Dst6And7ConvertToType2And3;
+///
+/// let len = 1234;
+/// let mut planner = DctPlanner::new();
+/// let inner_dst = planner.plan_dst2(len * 2 + 1);
+///
+/// let dst = Dst6And7ConvertToType2And3::new(inner_dst);
+///
+/// let mut dst6_buffer = vec![0f32; len];
+/// dst.process_dst6(&mut dst6_buffer);
+///
+/// let mut dst7_buffer = vec![0f32; len];
+/// dst.process_dst7(&mut dst7_buffer);
+/// ~~~
+pub struct Dst6And7ConvertToType2And3<T> {
+    inner_dst: Arc<dyn TransformType2And3<T>>,
+    len: usize,
+    scratch_len: usize,
+}
+
+impl<T: DctNum> Dst6And7ConvertToType2And3<T> {
+    /// Creates a new DST6 and DST7 context that will process signals of length `(inner_dst.len() - 1) / 2`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `inner_dst.len()` is even.
+    pub fn new(inner_dst: Arc<dyn TransformType2And3<T>>) -> Self {
+        let inner_len = inner_dst.len();
+        assert!(
+            inner_len % 2 == 1,
+            "The 'Dst6And7ConvertToType2And3' algorithm requires an odd-len inner DST. Provided len={}",
+            inner_len
+        );
+
+        Self {
+            len: inner_len / 2,
+            scratch_len: derived_len(
+                inner_len,
+                1,
+                inner_dst.get_scratch_len() as isize,
+                "scratch",
+            ),
+            inner_dst,
+        }
+    }
+}
+impl<T: DctNum> Dst6<T> for Dst6And7ConvertToType2And3<T> {
+    plan_hooks!(T);
+    fn process_dst6_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+        let (inner_buffer, inner_scratch) = scratch.split_at_mut(self.inner_dst.len());
+
+        let (head, tail) = inner_buffer.split_at_mut(self.len());
+        head.copy_from_slice(buffer);
+        for element in tail.iter_mut() {
+            *element = T::zero();
+        }
+
+        self.inner_dst
+            .process_dst2_with_scratch(inner_buffer, inner_scratch);
+
+        for (element, inner_element) in buffer
+            .iter_mut()
+            .zip(inner_buffer.iter().skip(1).step_by(2))
+        {
+            *element = *inner_element;
+        }
+    }
+}
+impl<T: DctNum> Dst7<T> for Dst6And7ConvertToType2And3<T> {
+    plan_hooks!(T);
+    fn process_dst7_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+        let (inner_buffer, inner_scratch) = scratch.split_at_mut(self.inner_dst.len());
+
+        // put the input in the odd elements of the DST3's input, and zero out the even elements
+        for (i, element) in inner_buffer.iter_mut().enumerate() {
+            *element = if i % 2 == 1 { buffer[i / 2] } else { T::zero() };
+        }
+
+        self.inner_dst
+            .process_dst3_with_scratch(inner_buffer, inner_scratch);
+
+        buffer.copy_from_slice(&inner_buffer[..self.len()]);
+    }
+}
+impl<T: DctNum> Dst6And7<T> for Dst6And7ConvertToType2And3<T> {}
+impl<T: DctNum> PlanIdentity<T> for Dst6And7ConvertToType2And3<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Dst6And7, self.len())
+    }
+}
+impl<T: DctNum> MemoryUsage for Dst6And7ConvertToType2And3<T> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            inner_plans_bytes: visitor.inner_plan(&self.inner_dst),
+            ..PlanMemory::default()
+        }
+    }
+}
+impl<T: DctNum> RequiredScratch for Dst6And7ConvertToType2And3<T> {
+    fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        offset_complex_scratch_len(
+            self.inner_dst.len(),
+            self.inner_dst.get_complex_scratch_len(),
+        )
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        ScratchLayout::new()
+            .with_region("inner buffer", self.inner_dst.len(), false)
+            .with_inner(self.inner_dst.scratch_layout())
+    }
+}
+impl<T> Length for Dst6And7ConvertToType2And3<T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algorithm::{Dct6And7Naive, Dst6And7Naive, Type2And3Naive};
+
+    use crate::test_utils::{compare_float_vectors, random_signal};
+
+    /// Verify that our fast implementation of the DCT6, DCT7, DST6, and DST7 gives the same output as the naive version
+    #[test]
+    fn test_type6and7_convert_to_type2and3() {
+        for len in 1..20 {
+            let naive_dct = Dct6And7Naive::new(len);
+            let dct = Dct6And7ConvertToType2And3::new(Arc::new(Type2And3Naive::new(len * 2 - 1)));
+            let naive_dst = Dst6And7Naive::new(len);
+            let dst = Dst6And7ConvertToType2And3::new(Arc::new(Type2And3Naive::new(len * 2 + 1)));
+            assert_eq!(dct.len(), len);
+            assert_eq!(dst.len(), len);
+
+            let input = random_signal(len);
+            let check = |name: &str, expected: &[f32], actual: &[f32]| {
+                assert!(
+                    compare_float_vectors(expected, actual),
+                    "{}, len = {}",
+                    name,
+                    len
+                );
+            };
+
+            let (mut expected, mut actual) = (input.clone(), input.clone());
+            naive_dct.process_dct6(&mut expected);
+            dct.process_dct6(&mut actual);
+            check("dct6", &expected, &actual);
+
+            let (mut expected, mut actual) = (input.clone(), input.clone());
+            naive_dct.process_dct7(&mut expected);
+            dct.process_dct7(&mut actual);
+            check("dct7", &expected, &actual);
+
+            let (mut expected, mut actual) = (input.clone(), input.clone());
+            naive_dst.process_dst6(&mut expected);
+            dst.process_dst6(&mut actual);
+            check("dst6", &expected, &actual);
+
+            let (mut expected, mut actual) = (input.clone(), input.clone());
+            naive_dst.process_dst7(&mut expected);
+            dst.process_dst7(&mut actual);
+            check("dst7", &expected, &actual);
+        }
+    }
+}
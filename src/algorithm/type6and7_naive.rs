@@ -1,8 +1,12 @@
 use rustfft::Length;
 
-use crate::common::dct_error_inplace;
+use crate::algorithm::naive_kernel::{accumulate, twiddle_dot, Accumulation};
+use crate::common::{dct_error_inplace, derived_len};
+use crate::memory::twiddles_only;
 use crate::RequiredScratch;
 use crate::{Dct6, Dct6And7, Dct7, DctNum, Dst6, Dst6And7, Dst7};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
 
 /// Naive O(n^2 ) DCT Type 6 and DCT Type 7 implementation
 ///
@@ -22,79 +26,106 @@ use crate::{Dct6, Dct6And7, Dct7, DctNum, Dst6, Dst6And7, Dst7};
 /// ~~~
 pub struct Dct6And7Naive<T> {
     twiddles: Box<[T]>,
+    accumulation: Accumulation,
 }
 
 impl<T: DctNum> Dct6And7Naive<T> {
     /// Creates a new DCT6 and DCT7 context that will process signals of length `len`
     pub fn new(len: usize) -> Self {
-        let constant_factor = std::f64::consts::PI / (len * 2 - 1) as f64;
-
-        let twiddles: Vec<T> = (0..len * 4 - 2)
+        let twiddle_len = if len == 0 {
+            0
+        } else {
+            derived_len(len, 4, -2, "twiddle table")
+        };
+        let constant_factor = std::f64::consts::PI / (twiddle_len / 2) as f64;
+
+        let twiddles: Vec<T> = (0..twiddle_len)
             .map(|i| (constant_factor * (i as f64)).cos())
             .map(|c| T::from_f64(c).unwrap())
             .collect();
 
         Self {
             twiddles: twiddles.into_boxed_slice(),
+            accumulation: Accumulation::Simple,
         }
     }
+
+    /// Sets how each output's sum of products is accumulated, which is `Accumulation::Simple` by default. See
+    /// [`Accumulation`](enum.Accumulation.html).
+    pub fn with_accumulation(mut self, accumulation: Accumulation) -> Self {
+        self.accumulation = accumulation;
+        self
+    }
 }
 
 impl<T: DctNum> Dct6<T> for Dct6And7Naive<T> {
+    plan_hooks!(T);
     fn process_dct6_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+        if buffer.is_empty() {
+            return;
+        }
         scratch.copy_from_slice(buffer);
 
         scratch[scratch.len() - 1] = scratch[scratch.len() - 1] * T::half();
-        buffer[0] = scratch.iter().fold(T::zero(), |acc, e| acc + *e);
+        buffer[0] = accumulate(scratch, self.accumulation);
 
         for k in 1..buffer.len() {
             let output_cell = buffer.get_mut(k).unwrap();
-            *output_cell = T::zero();
 
             let twiddle_stride = k * 2;
-            let mut twiddle_index = k;
-
-            for i in 0..scratch.len() {
-                let twiddle = self.twiddles[twiddle_index];
-
-                *output_cell = *output_cell + scratch[i] * twiddle;
-
-                twiddle_index += twiddle_stride;
-                if twiddle_index >= self.twiddles.len() {
-                    twiddle_index -= self.twiddles.len();
-                }
-            }
+            let twiddle_index = k;
+
+            *output_cell = twiddle_dot(
+                scratch,
+                &self.twiddles,
+                twiddle_index,
+                twiddle_stride,
+                |twiddle| twiddle,
+                self.accumulation,
+            );
         }
     }
 }
 impl<T: DctNum> Dct7<T> for Dct6And7Naive<T> {
+    plan_hooks!(T);
     fn process_dct7_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+        if buffer.is_empty() {
+            return;
+        }
         scratch.copy_from_slice(buffer);
 
         scratch[0] = scratch[0] * T::half();
 
         for k in 0..buffer.len() {
             let output_cell = buffer.get_mut(k).unwrap();
-            *output_cell = scratch[0];
 
             let twiddle_stride = k * 2 + 1;
-            let mut twiddle_index = twiddle_stride;
-
-            for i in 1..scratch.len() {
-                let twiddle = self.twiddles[twiddle_index];
-
-                *output_cell = *output_cell + scratch[i] * twiddle;
-
-                twiddle_index += twiddle_stride;
-                if twiddle_index >= self.twiddles.len() {
-                    twiddle_index -= self.twiddles.len();
-                }
-            }
+            let twiddle_index = twiddle_stride;
+
+            *output_cell = scratch[0]
+                + twiddle_dot(
+                    &scratch[1..],
+                    &self.twiddles,
+                    twiddle_index,
+                    twiddle_stride,
+                    |twiddle| twiddle,
+                    self.accumulation,
+                );
         }
     }
 }
+impl<T: DctNum> PlanIdentity<T> for Dct6And7Naive<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Dct6And7, self.len())
+    }
+}
+impl<T> MemoryUsage for Dct6And7Naive<T> {
+    fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+        twiddles_only(&self.twiddles)
+    }
+}
 impl<T> RequiredScratch for Dct6And7Naive<T> {
     fn get_scratch_len(&self) -> usize {
         self.len()
@@ -125,74 +156,90 @@ impl<T> Length for Dct6And7Naive<T> {
 /// ~~~
 pub struct Dst6And7Naive<T> {
     twiddles: Box<[T]>,
+    accumulation: Accumulation,
 }
 
 impl<T: DctNum> Dst6And7Naive<T> {
     /// Creates a new DST6 and DST7 context that will process signals of length `len`
     pub fn new(len: usize) -> Self {
-        let constant_factor = std::f64::consts::PI / (len * 2 + 1) as f64;
+        let twiddle_len = derived_len(len, 4, 2, "twiddle table");
+        let constant_factor = std::f64::consts::PI / (twiddle_len / 2) as f64;
 
-        let twiddles: Vec<T> = (0..len * 4 + 2)
+        let twiddles: Vec<T> = (0..twiddle_len)
             .map(|i| (constant_factor * (i as f64)).sin())
             .map(|c| T::from_f64(c).unwrap())
             .collect();
 
         Self {
             twiddles: twiddles.into_boxed_slice(),
+            accumulation: Accumulation::Simple,
         }
     }
+
+    /// Sets how each output's sum of products is accumulated, which is `Accumulation::Simple` by default. See
+    /// [`Accumulation`](enum.Accumulation.html).
+    pub fn with_accumulation(mut self, accumulation: Accumulation) -> Self {
+        self.accumulation = accumulation;
+        self
+    }
 }
 
 impl<T: DctNum> Dst6<T> for Dst6And7Naive<T> {
+    plan_hooks!(T);
     fn process_dst6_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
         scratch.copy_from_slice(buffer);
 
         for k in 0..buffer.len() {
             let output_cell = buffer.get_mut(k).unwrap();
-            *output_cell = T::zero();
 
             let twiddle_stride = (k + 1) * 2;
-            let mut twiddle_index = k + 1;
-
-            for i in 0..scratch.len() {
-                let twiddle = self.twiddles[twiddle_index];
-
-                *output_cell = *output_cell + scratch[i] * twiddle;
-
-                twiddle_index += twiddle_stride;
-                if twiddle_index >= self.twiddles.len() {
-                    twiddle_index -= self.twiddles.len();
-                }
-            }
+            let twiddle_index = k + 1;
+
+            *output_cell = twiddle_dot(
+                scratch,
+                &self.twiddles,
+                twiddle_index,
+                twiddle_stride,
+                |twiddle| twiddle,
+                self.accumulation,
+            );
         }
     }
 }
 impl<T: DctNum> Dst7<T> for Dst6And7Naive<T> {
+    plan_hooks!(T);
     fn process_dst7_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
         scratch.copy_from_slice(buffer);
 
         for k in 0..buffer.len() {
             let output_cell = buffer.get_mut(k).unwrap();
-            *output_cell = T::zero();
 
             let twiddle_stride = k * 2 + 1;
-            let mut twiddle_index = twiddle_stride;
-
-            for i in 0..scratch.len() {
-                let twiddle = self.twiddles[twiddle_index];
-
-                *output_cell = *output_cell + scratch[i] * twiddle;
-
-                twiddle_index += twiddle_stride;
-                if twiddle_index >= self.twiddles.len() {
-                    twiddle_index -= self.twiddles.len();
-                }
-            }
+            let twiddle_index = twiddle_stride;
+
+            *output_cell = twiddle_dot(
+                scratch,
+                &self.twiddles,
+                twiddle_index,
+                twiddle_stride,
+                |twiddle| twiddle,
+                self.accumulation,
+            );
         }
     }
 }
+impl<T: DctNum> PlanIdentity<T> for Dst6And7Naive<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Dst6And7, self.len())
+    }
+}
+impl<T> MemoryUsage for Dst6And7Naive<T> {
+    fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+        twiddles_only(&self.twiddles)
+    }
+}
 impl<T> RequiredScratch for Dst6And7Naive<T> {
     fn get_scratch_len(&self) -> usize {
         self.len()
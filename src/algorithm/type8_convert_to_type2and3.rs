@@ -0,0 +1,267 @@
+use std::sync::Arc;
+
+use rustfft::Length;
+
+use crate::common::{dct_error_inplace, derived_len, offset_complex_scratch_len};
+use crate::ScratchLayout;
+use crate::TransformType2And3;
+use crate::{Dct8, DctNum, Dst8, RequiredScratch};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
+
+/// DCT8 implementation that converts the problem into a DCT2 of odd size `2 * len + 1`.
+///
+/// The DCT8 is the odd half of the outputs of a DCT2 whose input is zero-padded to `2 * len + 1` elements. Half of the
+/// inner transform's work is discarded, but if the inner DCT is O(nlogn), then so is this.
+///
+/// ~~~
+/// // Computes a O(NlogN) DCT8 of size 1234 by converting it to a DCT2 of size 2469
+/// use rustdct::{Dct8, DctPlanner};
+/// use rustdct::algorithm::Dct8ConvertToType2And3;
+///
+/// let len = 1234;
+/// let mut planner = DctPlanner::new();
+/// let inner_dct = planner.plan_dct2(len * 2 + 1);
+///
+/// let dct = Dct8ConvertToType2And3::new(inner_dct);
+///
+/// let mut buffer = vec![0f32; len];
+/// dct.process_dct8(&mut buffer);
+/// ~~~
+pub struct Dct8ConvertToType2And3<T> {
+    inner_dct: Arc<dyn TransformType2And3<T>>,
+    len: usize,
+    scratch_len: usize,
+}
+
+impl<T: DctNum> Dct8ConvertToType2And3<T> {
+    /// Creates a new DCT8 context that will process signals of length `(inner_dct.len() - 1) / 2`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `inner_dct.len()` is even.
+    pub fn new(inner_dct: Arc<dyn TransformType2And3<T>>) -> Self {
+        let inner_len = inner_dct.len();
+        assert!(
+            inner_len % 2 == 1,
+            "The 'Dct8ConvertToType2And3' algorithm requires an odd-len inner DCT. Provided len={}",
+            inner_len
+        );
+
+        Self {
+            len: inner_len / 2,
+            scratch_len: derived_len(
+                inner_len,
+                1,
+                inner_dct.get_scratch_len() as isize,
+                "scratch",
+            ),
+            inner_dct,
+        }
+    }
+}
+impl<T: DctNum> Dct8<T> for Dct8ConvertToType2And3<T> {
+    plan_hooks!(T);
+    fn process_dct8_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+        let (inner_buffer, inner_scratch) = scratch.split_at_mut(self.inner_dct.len());
+
+        let (head, tail) = inner_buffer.split_at_mut(self.len());
+        head.copy_from_slice(buffer);
+        for element in tail.iter_mut() {
+            *element = T::zero();
+        }
+
+        self.inner_dct
+            .process_dct2_with_scratch(inner_buffer, inner_scratch);
+
+        for (element, inner_element) in buffer
+            .iter_mut()
+            .zip(inner_buffer.iter().skip(1).step_by(2))
+        {
+            *element = *inner_element;
+        }
+    }
+}
+impl<T: DctNum> PlanIdentity<T> for Dct8ConvertToType2And3<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Dct8, self.len())
+    }
+}
+impl<T: DctNum> MemoryUsage for Dct8ConvertToType2And3<T> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            inner_plans_bytes: visitor.inner_plan(&self.inner_dct),
+            ..PlanMemory::default()
+        }
+    }
+}
+impl<T: DctNum> RequiredScratch for Dct8ConvertToType2And3<T> {
+    fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        offset_complex_scratch_len(
+            self.inner_dct.len(),
+            self.inner_dct.get_complex_scratch_len(),
+        )
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        ScratchLayout::new()
+            .with_region("inner buffer", self.inner_dct.len(), false)
+            .with_inner(self.inner_dct.scratch_layout())
+    }
+}
+impl<T> Length for Dct8ConvertToType2And3<T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// DST8 implementation that converts the problem into a DST2 of odd size `2 * len - 1`.
+///
+/// The DST8 is the even half of the outputs of a DST2 whose input is zero-padded to `2 * len - 1` elements. Half of the
+/// inner transform's work is discarded, but if the inner DST is O(nlogn), then so is this.
+///
+/// ~~~
+/// // Computes a O(NlogN) DST8 of size 1234 by converting it to a DST2 of size 2467
+/// use rustdct::{Dst8, DctPlanner};
+/// use rustdct::algorithm::Dst8ConvertToType2And3;
+///
+/// let len = 1234;
+/// let mut planner = DctPlanner::new();
+/// let inner_dst = planner.plan_dst2(len * 2 - 1);
+///
+/// let dst = Dst8ConvertToType2And3::new(inner_dst);
+///
+/// let mut buffer = vec![0f32; len];
+/// dst.process_dst8(&mut buffer);
+/// ~~~
+pub struct Dst8ConvertToType2And3<T> {
+    inner_dst: Arc<dyn TransformType2And3<T>>,
+    len: usize,
+    scratch_len: usize,
+}
+
+impl<T: DctNum> Dst8ConvertToType2And3<T> {
+    /// Creates a new DST8 context that will process signals of length `(inner_dst.len() + 1) / 2`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `inner_dst.len()` is even.
+    pub fn new(inner_dst: Arc<dyn TransformType2And3<T>>) -> Self {
+        let inner_len = inner_dst.len();
+        assert!(
+            inner_len % 2 == 1,
+            "The 'Dst8ConvertToType2And3' algorithm requires an odd-len inner DST. Provided len={}",
+            inner_len
+        );
+
+        Self {
+            len: inner_len / 2 + 1,
+            scratch_len: derived_len(
+                inner_len,
+                1,
+                inner_dst.get_scratch_len() as isize,
+                "scratch",
+            ),
+            inner_dst,
+        }
+    }
+}
+impl<T: DctNum> Dst8<T> for Dst8ConvertToType2And3<T> {
+    plan_hooks!(T);
+    fn process_dst8_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+        let (inner_buffer, inner_scratch) = scratch.split_at_mut(self.inner_dst.len());
+
+        // the DST8 halves its last input. the rest of the DST2's input is zero
+        let (head, tail) = inner_buffer.split_at_mut(self.len());
+        head.copy_from_slice(buffer);
+        head[self.len() - 1] = head[self.len() - 1] * T::half();
+        for element in tail.iter_mut() {
+            *element = T::zero();
+        }
+
+        self.inner_dst
+            .process_dst2_with_scratch(inner_buffer, inner_scratch);
+
+        for (element, inner_element) in buffer.iter_mut().zip(inner_buffer.iter().step_by(2)) {
+            *element = *inner_element;
+        }
+    }
+}
+impl<T: DctNum> PlanIdentity<T> for Dst8ConvertToType2And3<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Dst8, self.len())
+    }
+}
+impl<T: DctNum> MemoryUsage for Dst8ConvertToType2And3<T> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            inner_plans_bytes: visitor.inner_plan(&self.inner_dst),
+            ..PlanMemory::default()
+        }
+    }
+}
+impl<T: DctNum> RequiredScratch for Dst8ConvertToType2And3<T> {
+    fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        offset_complex_scratch_len(
+            self.inner_dst.len(),
+            self.inner_dst.get_complex_scratch_len(),
+        )
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        ScratchLayout::new()
+            .with_region("inner buffer", self.inner_dst.len(), false)
+            .with_inner(self.inner_dst.scratch_layout())
+    }
+}
+impl<T> Length for Dst8ConvertToType2And3<T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algorithm::{Dct8Naive, Dst8Naive, Type2And3Naive};
+    use crate::test_utils::{compare_float_vectors, random_signal};
+
+    /// Verify that our fast implementation of the DCT8 and DST8 gives the same output as the naive version
+    #[test]
+    fn test_type8_convert_to_type2and3() {
+        for len in 1..20 {
+            let naive_dct = Dct8Naive::new(len);
+            let dct = Dct8ConvertToType2And3::new(Arc::new(Type2And3Naive::new(len * 2 + 1)));
+            let naive_dst = Dst8Naive::new(len);
+            let dst = Dst8ConvertToType2And3::new(Arc::new(Type2And3Naive::new(len * 2 - 1)));
+            assert_eq!(dct.len(), len);
+            assert_eq!(dst.len(), len);
+
+            let input = random_signal(len);
+
+            let (mut expected, mut actual) = (input.clone(), input.clone());
+            naive_dct.process_dct8(&mut expected);
+            dct.process_dct8(&mut actual);
+            assert!(
+                compare_float_vectors(&expected, &actual),
+                "dct8, len = {}",
+                len
+            );
+
+            let (mut expected, mut actual) = (input.clone(), input);
+            naive_dst.process_dst8(&mut expected);
+            dst.process_dst8(&mut actual);
+            assert!(
+                compare_float_vectors(&expected, &actual),
+                "dst8, len = {}",
+                len
+            );
+        }
+    }
+}
@@ -1,8 +1,12 @@
 use rustfft::Length;
 
-use crate::common::dct_error_inplace;
+use crate::algorithm::naive_kernel::{twiddle_dot, Accumulation};
+use crate::common::{dct_error_inplace, derived_len};
+use crate::memory::twiddles_only;
 use crate::RequiredScratch;
 use crate::{Dct8, DctNum, Dst8};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
 
 /// Naive O(n^2 ) DCT Type 8 implementation
 ///
@@ -19,47 +23,65 @@ use crate::{Dct8, DctNum, Dst8};
 /// ~~~
 pub struct Dct8Naive<T> {
     twiddles: Box<[T]>,
+    accumulation: Accumulation,
 }
 impl<T: DctNum> Dct8Naive<T> {
     /// Creates a new DCT8 context that will process signals of length `len`
     pub fn new(len: usize) -> Self {
-        let constant_factor = std::f64::consts::PI / (len * 2 + 1) as f64;
+        let twiddle_len = derived_len(len, 4, 2, "twiddle table");
+        let constant_factor = std::f64::consts::PI / (twiddle_len / 2) as f64;
 
-        let twiddles: Vec<T> = (0..len * 4 + 2)
+        let twiddles: Vec<T> = (0..twiddle_len)
             .map(|i| (constant_factor * (i as f64 + 0.5)).cos())
             .map(|c| T::from_f64(c).unwrap())
             .collect();
 
         Self {
             twiddles: twiddles.into_boxed_slice(),
+            accumulation: Accumulation::Simple,
         }
     }
+
+    /// Sets how each output's sum of products is accumulated, which is `Accumulation::Simple` by default. See
+    /// [`Accumulation`](enum.Accumulation.html).
+    pub fn with_accumulation(mut self, accumulation: Accumulation) -> Self {
+        self.accumulation = accumulation;
+        self
+    }
 }
 impl<T: DctNum> Dct8<T> for Dct8Naive<T> {
+    plan_hooks!(T);
     fn process_dct8_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
         scratch.copy_from_slice(buffer);
 
         for k in 0..buffer.len() {
             let output_cell = buffer.get_mut(k).unwrap();
-            *output_cell = T::zero();
 
-            let mut twiddle_index = k;
+            let twiddle_index = k;
             let twiddle_stride = k * 2 + 1;
 
-            for i in 0..scratch.len() {
-                let twiddle = self.twiddles[twiddle_index];
-
-                *output_cell = *output_cell + scratch[i] * twiddle;
-
-                twiddle_index += twiddle_stride;
-                if twiddle_index >= self.twiddles.len() {
-                    twiddle_index -= self.twiddles.len();
-                }
-            }
+            *output_cell = twiddle_dot(
+                scratch,
+                &self.twiddles,
+                twiddle_index,
+                twiddle_stride,
+                |twiddle| twiddle,
+                self.accumulation,
+            );
         }
     }
 }
+impl<T: DctNum> PlanIdentity<T> for Dct8Naive<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Dct8, self.len())
+    }
+}
+impl<T> MemoryUsage for Dct8Naive<T> {
+    fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+        twiddles_only(&self.twiddles)
+    }
+}
 impl<T> RequiredScratch for Dct8Naive<T> {
     fn get_scratch_len(&self) -> usize {
         self.len()
@@ -86,51 +108,76 @@ impl<T> Length for Dct8Naive<T> {
 /// ~~~
 pub struct Dst8Naive<T> {
     twiddles: Box<[T]>,
+    accumulation: Accumulation,
 }
 
 impl<T: DctNum> Dst8Naive<T> {
     /// Creates a new DST8 context that will process signals of length `len`
     pub fn new(len: usize) -> Self {
-        let constant_factor = std::f64::consts::PI / (len * 2 - 1) as f64;
-
-        let twiddles: Vec<T> = (0..len * 4 - 2)
+        let twiddle_len = if len == 0 {
+            0
+        } else {
+            derived_len(len, 4, -2, "twiddle table")
+        };
+        let constant_factor = std::f64::consts::PI / (twiddle_len / 2) as f64;
+
+        let twiddles: Vec<T> = (0..twiddle_len)
             .map(|i| (constant_factor * (i as f64 + 0.5)).sin())
             .map(|c| T::from_f64(c).unwrap())
             .collect();
 
         Self {
             twiddles: twiddles.into_boxed_slice(),
+            accumulation: Accumulation::Simple,
         }
     }
+
+    /// Sets how each output's sum of products is accumulated, which is `Accumulation::Simple` by default. See
+    /// [`Accumulation`](enum.Accumulation.html).
+    pub fn with_accumulation(mut self, accumulation: Accumulation) -> Self {
+        self.accumulation = accumulation;
+        self
+    }
 }
 
 impl<T: DctNum> Dst8<T> for Dst8Naive<T> {
+    plan_hooks!(T);
     fn process_dst8_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
         let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+        if buffer.is_empty() {
+            return;
+        }
         scratch.copy_from_slice(buffer);
 
         scratch[scratch.len() - 1] = scratch[scratch.len() - 1] * T::half();
 
         for k in 0..buffer.len() {
             let output_cell = buffer.get_mut(k).unwrap();
-            *output_cell = T::zero();
 
-            let mut twiddle_index = k;
+            let twiddle_index = k;
             let twiddle_stride = k * 2 + 1;
 
-            for i in 0..scratch.len() {
-                let twiddle = self.twiddles[twiddle_index];
-
-                *output_cell = *output_cell + scratch[i] * twiddle;
-
-                twiddle_index += twiddle_stride;
-                if twiddle_index >= self.twiddles.len() {
-                    twiddle_index -= self.twiddles.len();
-                }
-            }
+            *output_cell = twiddle_dot(
+                scratch,
+                &self.twiddles,
+                twiddle_index,
+                twiddle_stride,
+                |twiddle| twiddle,
+                self.accumulation,
+            );
         }
     }
 }
+impl<T: DctNum> PlanIdentity<T> for Dst8Naive<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Dst8, self.len())
+    }
+}
+impl<T> MemoryUsage for Dst8Naive<T> {
+    fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+        twiddles_only(&self.twiddles)
+    }
+}
 impl<T> RequiredScratch for Dst8Naive<T> {
     fn get_scratch_len(&self) -> usize {
         self.len()
@@ -0,0 +1,110 @@
+use rustfft::num_complex::Complex;
+
+pub mod transpose;
+
+// `Complex<T>` is `#[repr(C)]` with two `T` fields, so a `[T]` of even length has the same layout as a
+// `[Complex<T>]` of half the length, as long as the slice is aligned for `Complex<T>`. These checks are evaluated at
+// compile time for every `T`, and the compiler removes them
+fn assert_complex_layout<T>() {
+    assert_eq!(
+        std::mem::size_of::<Complex<T>>(),
+        2 * std::mem::size_of::<T>()
+    );
+    assert_eq!(
+        std::mem::align_of::<Complex<T>>(),
+        std::mem::align_of::<T>()
+    );
+}
+
+#[allow(unused)]
+pub fn into_complex<T>(buffer: &[T]) -> &[Complex<T>] {
+    assert_complex_layout::<T>();
+    let complex_len = buffer.len() / 2;
+
+    // Safety: `Complex<T>` is two consecutive `T`s, so any pair of initialized `T`s is a valid `Complex<T>`
+    let (prefix, complex_buffer, _) = unsafe { buffer.align_to::<Complex<T>>() };
+    assert!(
+        prefix.is_empty() && complex_buffer.len() >= complex_len,
+        "buffer is not aligned for Complex<T>"
+    );
+    &complex_buffer[..complex_len]
+}
+
+// Views the first `buffer.len() / 2 * 2` elements of `buffer` as complex numbers.
+//
+// Scratch buffers allocated as `[T]` are always aligned for `Complex<T>`, because both have the same alignment. The
+// assertion guards the reinterpretation in case that ever stops being true; callers who want the alignment
+// guaranteed by the type system can allocate their scratch as complex numbers and pass it through
+// `flatten_complex_scratch`
+pub fn into_complex_mut<T>(buffer: &mut [T]) -> &mut [Complex<T>] {
+    assert_complex_layout::<T>();
+    let complex_len = buffer.len() / 2;
+
+    // Safety: `Complex<T>` is two consecutive `T`s, so any pair of initialized `T`s is a valid `Complex<T>`
+    let (prefix, complex_buffer, _) = unsafe { buffer.align_to_mut::<Complex<T>>() };
+    assert!(
+        prefix.is_empty() && complex_buffer.len() >= complex_len,
+        "scratch is not aligned for Complex<T>. Allocate it with `flatten_complex_scratch` instead"
+    );
+    &mut complex_buffer[..complex_len]
+}
+
+/// Views a buffer of complex numbers as a flat scratch buffer that can be passed to any `process_*_with_scratch`
+/// method.
+///
+/// Plans that compute an FFT internally view part of their scratch as `Complex<T>`, so a scratch buffer has to be
+/// aligned for `Complex<T>`. A scratch buffer allocated as `Complex<T>` always is, so this is the way to supply one
+/// flat allocation with that guarantee. Allocate `(plan.get_scratch_len() + 1) / 2` complex numbers:
+///
+/// ~~~
+/// use rustdct::{flatten_complex_scratch, DctPlanner, RequiredScratch};
+/// use rustdct::num_complex::Complex;
+///
+/// let mut planner = DctPlanner::new();
+/// let dct = planner.plan_dct2(1234);
+///
+/// let mut complex_scratch = vec![Complex::new(0f32, 0f32); (dct.get_scratch_len() + 1) / 2];
+/// let mut buffer = vec![0f32; 1234];
+/// dct.process_dct2_with_scratch(&mut buffer, flatten_complex_scratch(&mut complex_scratch));
+/// ~~~
+pub fn flatten_complex_scratch<T>(scratch: &mut [Complex<T>]) -> &mut [T] {
+    assert_complex_layout::<T>();
+    let len = scratch.len() * 2;
+    let ptr = scratch.as_mut_ptr() as *mut T;
+
+    // Safety: `Complex<T>` is two consecutive `T`s, and its alignment is at least that of `T`
+    unsafe { std::slice::from_raw_parts_mut(ptr, len) }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn test_into_complex_mut() {
+        // an odd trailing element isn't part of the complex view
+        let mut buffer = [1f32, 2.0, 3.0, 4.0, 5.0];
+        let complex_buffer = into_complex_mut(&mut buffer);
+        assert_eq!(
+            complex_buffer,
+            &[Complex::new(1.0, 2.0), Complex::new(3.0, 4.0)]
+        );
+
+        complex_buffer[1].im = 6.0;
+        assert_eq!(buffer, [1.0, 2.0, 3.0, 6.0, 5.0]);
+    }
+
+    #[test]
+    fn test_flatten_complex_scratch_round_trip() {
+        let mut scratch = vec![Complex::new(1f64, 2.0), Complex::new(3.0, 4.0)];
+        let flat = flatten_complex_scratch(&mut scratch);
+        assert_eq!(flat, &[1.0, 2.0, 3.0, 4.0]);
+
+        flat[2] = 7.0;
+        assert_eq!(
+            into_complex(flat),
+            &[Complex::new(1.0, 2.0), Complex::new(7.0, 4.0)]
+        );
+        assert_eq!(scratch[1], Complex::new(7.0, 4.0));
+    }
+}
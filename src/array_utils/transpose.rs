@@ -0,0 +1,686 @@
+//! Fast out-of-place transposes of row-major matrices
+//!
+//! Separable 2D transforms process each row, transpose, and process each row again, so for large images, the transpose
+//! can take as long as the transforms themselves. The transposes in this module work on cache-sized blocks of the
+//! matrix at a time, and on x86 and x86_64, transpose `f32` and `f64` matrices with SSE or AVX kernels, chosen at
+//! runtime based on what the CPU supports. On aarch64, they use NEON kernels if the `neon` feature is enabled, which
+//! needs Rust 1.59 or newer, like RustFFT's own NEON support. Other element types and architectures use the
+//! cache-blocked scalar transpose.
+//!
+//! [`transpose_strided`](fn.transpose_strided.html) uses the same kernels to gather a strip of columns out of a larger
+//! matrix into contiguous rows, and to scatter them back, for column passes that don't transpose the whole matrix.
+
+#[cfg(any(
+    target_arch = "x86",
+    target_arch = "x86_64",
+    all(target_arch = "aarch64", feature = "neon")
+))]
+use std::any::TypeId;
+
+// The side length of the square blocks that the matrix is split into. Every kernel size must divide this evenly
+const BLOCK_SIZE: usize = 32;
+
+// The shape of one transpose: `height` rows of `width` elements are read from rows of the input that are
+// `input_stride` elements apart, and written as `width` rows of `height` elements that are `output_stride` elements
+// apart
+#[derive(Copy, Clone)]
+struct Shape {
+    width: usize,
+    height: usize,
+    input_stride: usize,
+    output_stride: usize,
+}
+
+/// Transposes the row-major `input` matrix, which has `height` rows of `width` elements, into `output`, which will have
+/// `width` rows of `height` elements.
+///
+/// ~~~
+/// use rustdct::transpose::transpose;
+///
+/// let input = [1, 2, 3,
+///              4, 5, 6];
+/// let mut output = [0; 6];
+/// transpose(&input, &mut output, 3, 2);
+/// assert_eq!(output, [1, 4,
+///                     2, 5,
+///                     3, 6]);
+/// ~~~
+///
+/// # Panics
+///
+/// Panics if `input.len()` or `output.len()` isn't equal to `width * height`.
+pub fn transpose<T: Copy + 'static>(input: &[T], output: &mut [T], width: usize, height: usize) {
+    let len = width.checked_mul(height);
+    assert!(
+        len == Some(input.len()) && len == Some(output.len()),
+        "The input and output must both have width * height elements. Got width = {}, height = {}, input len = {}, output len = {}",
+        width,
+        height,
+        input.len(),
+        output.len()
+    );
+
+    let shape = Shape {
+        width,
+        height,
+        input_stride: width,
+        output_stride: height,
+    };
+    transpose_dispatch(input, output, shape);
+}
+
+/// Transposes `height` rows of `width` elements, whose first elements are `input_stride` elements apart in `input`,
+/// into `width` rows of `height` elements, whose first elements are `output_stride` elements apart in `output`. The
+/// elements between the rows of either side are left alone.
+///
+/// With `width` smaller than `input_stride`, this gathers a strip of `width` columns out of a wider matrix, so that each
+/// column is contiguous, and with the arguments swapped, it scatters them back:
+///
+/// ~~~
+/// use rustdct::transpose::transpose_strided;
+///
+/// let mut matrix = [1, 2, 3,
+///                   4, 5, 6];
+///
+/// // gather the last two columns
+/// let mut columns = [0; 4];
+/// transpose_strided(&matrix[1..], 3, &mut columns, 2, 2, 2);
+/// assert_eq!(columns, [2, 5,
+///                      3, 6]);
+///
+/// // and scatter them back, doubled
+/// let doubled: Vec<i32> = columns.iter().map(|x| x * 2).collect();
+/// transpose_strided(&doubled, 2, &mut matrix[1..], 3, 2, 2);
+/// assert_eq!(matrix, [1, 4, 6,
+///                     4, 10, 12]);
+/// ~~~
+///
+/// # Panics
+///
+/// Panics if `input_stride` is less than `width`, if `output_stride` is less than `height`, or if either buffer is too
+/// short to hold all of its rows.
+pub fn transpose_strided<T: Copy + 'static>(
+    input: &[T],
+    input_stride: usize,
+    output: &mut [T],
+    output_stride: usize,
+    width: usize,
+    height: usize,
+) {
+    // the number of elements spanned by `rows` rows of `row_len` elements that are `stride` elements apart
+    let span = |rows: usize, row_len: usize, stride: usize| match rows {
+        0 => Some(0),
+        _ => (rows - 1).checked_mul(stride)?.checked_add(row_len),
+    };
+    let input_span = span(height, width, input_stride);
+    let output_span = span(width, height, output_stride);
+    assert!(
+        input_stride >= width
+            && output_stride >= height
+            && input_span.map_or(false, |span| span <= input.len())
+            && output_span.map_or(false, |span| span <= output.len()),
+        "The rows must fit in their strides and buffers. Got width = {}, height = {}, input stride = {}, output stride = {}, input len = {}, output len = {}",
+        width,
+        height,
+        input_stride,
+        output_stride,
+        input.len(),
+        output.len()
+    );
+
+    let shape = Shape {
+        width,
+        height,
+        input_stride,
+        output_stride,
+    };
+    transpose_dispatch(input, output, shape);
+}
+
+// Transposes with the fastest kernel for `T` and the CPU
+fn transpose_dispatch<T: Copy + 'static>(input: &[T], output: &mut [T], shape: Shape) {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if TypeId::of::<T>() == TypeId::of::<f32>() {
+            // Safety: T is f32
+            let input = unsafe { &*(input as *const [T] as *const [f32]) };
+            let output = unsafe { &mut *(output as *mut [T] as *mut [f32]) };
+            x86::transpose_f32(input, output, shape);
+            return;
+        }
+        if TypeId::of::<T>() == TypeId::of::<f64>() {
+            // Safety: T is f64
+            let input = unsafe { &*(input as *const [T] as *const [f64]) };
+            let output = unsafe { &mut *(output as *mut [T] as *mut [f64]) };
+            x86::transpose_f64(input, output, shape);
+            return;
+        }
+    }
+
+    #[cfg(all(target_arch = "aarch64", feature = "neon"))]
+    {
+        if TypeId::of::<T>() == TypeId::of::<f32>() {
+            // Safety: T is f32
+            let input = unsafe { &*(input as *const [T] as *const [f32]) };
+            let output = unsafe { &mut *(output as *mut [T] as *mut [f32]) };
+            aarch64::transpose_f32(input, output, shape);
+            return;
+        }
+        if TypeId::of::<T>() == TypeId::of::<f64>() {
+            // Safety: T is f64
+            let input = unsafe { &*(input as *const [T] as *const [f64]) };
+            let output = unsafe { &mut *(output as *mut [T] as *mut [f64]) };
+            aarch64::transpose_f64(input, output, shape);
+            return;
+        }
+    }
+
+    transpose_blocked(input, output, shape, 1, transpose_element);
+}
+
+// Transposes the single element at column `x` and row `y`
+fn transpose_element<T: Copy>(
+    input: &[T],
+    output: &mut [T],
+    input_stride: usize,
+    output_stride: usize,
+    x: usize,
+    y: usize,
+) {
+    output[x * output_stride + y] = input[y * input_stride + x];
+}
+
+// Transposes `input` into `output` one `BLOCK_SIZE` x `BLOCK_SIZE` block at a time, so that the rows of both the
+// input block and the output block stay in cache. Within each block, `kernel` transposes the `kernel_size` x
+// `kernel_size` square whose top left corner is at the given column and row. The rows and columns at the edges of
+// the matrix that don't fill a whole kernel are transposed one element at a time.
+fn transpose_blocked<T: Copy>(
+    input: &[T],
+    output: &mut [T],
+    shape: Shape,
+    kernel_size: usize,
+    kernel: impl Fn(&[T], &mut [T], usize, usize, usize, usize),
+) {
+    let Shape {
+        width,
+        height,
+        input_stride,
+        output_stride,
+    } = shape;
+    for block_y in (0..height).step_by(BLOCK_SIZE) {
+        let block_height = (height - block_y).min(BLOCK_SIZE);
+        let kernel_rows = block_height / kernel_size * kernel_size;
+
+        for block_x in (0..width).step_by(BLOCK_SIZE) {
+            let block_width = (width - block_x).min(BLOCK_SIZE);
+            let kernel_columns = block_width / kernel_size * kernel_size;
+
+            for y in (block_y..block_y + kernel_rows).step_by(kernel_size) {
+                for x in (block_x..block_x + kernel_columns).step_by(kernel_size) {
+                    kernel(input, output, input_stride, output_stride, x, y);
+                }
+            }
+
+            // the leftover columns on the right of the block, then the leftover rows at the bottom of the block
+            for y in block_y..block_y + block_height {
+                for x in block_x + kernel_columns..block_x + block_width {
+                    transpose_element(input, output, input_stride, output_stride, x, y);
+                }
+            }
+            for y in block_y + kernel_rows..block_y + block_height {
+                for x in block_x..block_x + kernel_columns {
+                    transpose_element(input, output, input_stride, output_stride, x, y);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+mod x86 {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+
+    use super::{transpose_blocked, transpose_element, Shape};
+
+    pub fn transpose_f32(input: &[f32], output: &mut [f32], shape: Shape) {
+        if is_x86_feature_detected!("avx") {
+            transpose_blocked(
+                input,
+                output,
+                shape,
+                8,
+                |input, output, input_stride, output_stride, x, y| {
+                    // Safety: We just checked that the CPU supports AVX
+                    unsafe {
+                        transpose_f32_8x8_avx(input, output, input_stride, output_stride, x, y)
+                    }
+                },
+            );
+        } else if is_x86_feature_detected!("sse") {
+            transpose_blocked(
+                input,
+                output,
+                shape,
+                4,
+                |input, output, input_stride, output_stride, x, y| {
+                    // Safety: We just checked that the CPU supports SSE
+                    unsafe {
+                        transpose_f32_4x4_sse(input, output, input_stride, output_stride, x, y)
+                    }
+                },
+            );
+        } else {
+            transpose_blocked(input, output, shape, 1, transpose_element);
+        }
+    }
+
+    pub fn transpose_f64(input: &[f64], output: &mut [f64], shape: Shape) {
+        if is_x86_feature_detected!("avx") {
+            transpose_blocked(
+                input,
+                output,
+                shape,
+                4,
+                |input, output, input_stride, output_stride, x, y| {
+                    // Safety: We just checked that the CPU supports AVX
+                    unsafe {
+                        transpose_f64_4x4_avx(input, output, input_stride, output_stride, x, y)
+                    }
+                },
+            );
+        } else if is_x86_feature_detected!("sse2") {
+            transpose_blocked(
+                input,
+                output,
+                shape,
+                2,
+                |input, output, input_stride, output_stride, x, y| {
+                    // Safety: We just checked that the CPU supports SSE2
+                    unsafe {
+                        transpose_f64_2x2_sse2(input, output, input_stride, output_stride, x, y)
+                    }
+                },
+            );
+        } else {
+            transpose_blocked(input, output, shape, 1, transpose_element);
+        }
+    }
+
+    // The kernels below slice out each row they load or store before touching it, so every access is bounds checked,
+    // and the only requirement for calling them is CPU support for their target feature
+
+    #[target_feature(enable = "sse")]
+    unsafe fn transpose_f32_4x4_sse(
+        input: &[f32],
+        output: &mut [f32],
+        input_stride: usize,
+        output_stride: usize,
+        x: usize,
+        y: usize,
+    ) {
+        let load = |row: usize| _mm_loadu_ps(input[(y + row) * input_stride + x..][..4].as_ptr());
+        let (row0, row1, row2, row3) = (load(0), load(1), load(2), load(3));
+
+        let low01 = _mm_unpacklo_ps(row0, row1);
+        let low23 = _mm_unpacklo_ps(row2, row3);
+        let high01 = _mm_unpackhi_ps(row0, row1);
+        let high23 = _mm_unpackhi_ps(row2, row3);
+
+        let columns = [
+            _mm_movelh_ps(low01, low23),
+            _mm_movehl_ps(low23, low01),
+            _mm_movelh_ps(high01, high23),
+            _mm_movehl_ps(high23, high01),
+        ];
+        for (column, &value) in columns.iter().enumerate() {
+            _mm_storeu_ps(
+                output[(x + column) * output_stride + y..][..4].as_mut_ptr(),
+                value,
+            );
+        }
+    }
+
+    #[target_feature(enable = "avx")]
+    unsafe fn transpose_f32_8x8_avx(
+        input: &[f32],
+        output: &mut [f32],
+        input_stride: usize,
+        output_stride: usize,
+        x: usize,
+        y: usize,
+    ) {
+        let load =
+            |row: usize| _mm256_loadu_ps(input[(y + row) * input_stride + x..][..8].as_ptr());
+        let rows = [
+            load(0),
+            load(1),
+            load(2),
+            load(3),
+            load(4),
+            load(5),
+            load(6),
+            load(7),
+        ];
+
+        // interleave pairs of rows, then pairs of pairs. each 128-bit lane now holds 4 elements of one column
+        let mut quads = [_mm256_setzero_ps(); 8];
+        for half in 0..2 {
+            let r = &rows[half * 4..half * 4 + 4];
+            let low01 = _mm256_unpacklo_ps(r[0], r[1]);
+            let high01 = _mm256_unpackhi_ps(r[0], r[1]);
+            let low23 = _mm256_unpacklo_ps(r[2], r[3]);
+            let high23 = _mm256_unpackhi_ps(r[2], r[3]);
+
+            quads[half * 4] = _mm256_shuffle_ps(low01, low23, 0x44);
+            quads[half * 4 + 1] = _mm256_shuffle_ps(low01, low23, 0xEE);
+            quads[half * 4 + 2] = _mm256_shuffle_ps(high01, high23, 0x44);
+            quads[half * 4 + 3] = _mm256_shuffle_ps(high01, high23, 0xEE);
+        }
+
+        // combine the lanes from the top and bottom halves of the square
+        for column in 0..4 {
+            let top = quads[column];
+            let bottom = quads[column + 4];
+            _mm256_storeu_ps(
+                output[(x + column) * output_stride + y..][..8].as_mut_ptr(),
+                _mm256_permute2f128_ps(top, bottom, 0x20),
+            );
+            _mm256_storeu_ps(
+                output[(x + column + 4) * output_stride + y..][..8].as_mut_ptr(),
+                _mm256_permute2f128_ps(top, bottom, 0x31),
+            );
+        }
+    }
+
+    #[target_feature(enable = "sse2")]
+    unsafe fn transpose_f64_2x2_sse2(
+        input: &[f64],
+        output: &mut [f64],
+        input_stride: usize,
+        output_stride: usize,
+        x: usize,
+        y: usize,
+    ) {
+        let row0 = _mm_loadu_pd(input[y * input_stride + x..][..2].as_ptr());
+        let row1 = _mm_loadu_pd(input[(y + 1) * input_stride + x..][..2].as_ptr());
+
+        _mm_storeu_pd(
+            output[x * output_stride + y..][..2].as_mut_ptr(),
+            _mm_unpacklo_pd(row0, row1),
+        );
+        _mm_storeu_pd(
+            output[(x + 1) * output_stride + y..][..2].as_mut_ptr(),
+            _mm_unpackhi_pd(row0, row1),
+        );
+    }
+
+    #[target_feature(enable = "avx")]
+    unsafe fn transpose_f64_4x4_avx(
+        input: &[f64],
+        output: &mut [f64],
+        input_stride: usize,
+        output_stride: usize,
+        x: usize,
+        y: usize,
+    ) {
+        let load =
+            |row: usize| _mm256_loadu_pd(input[(y + row) * input_stride + x..][..4].as_ptr());
+        let (row0, row1, row2, row3) = (load(0), load(1), load(2), load(3));
+
+        // each 128-bit lane of these holds 2 elements of one column
+        let low01 = _mm256_unpacklo_pd(row0, row1);
+        let high01 = _mm256_unpackhi_pd(row0, row1);
+        let low23 = _mm256_unpacklo_pd(row2, row3);
+        let high23 = _mm256_unpackhi_pd(row2, row3);
+
+        let columns = [
+            _mm256_permute2f128_pd(low01, low23, 0x20),
+            _mm256_permute2f128_pd(high01, high23, 0x20),
+            _mm256_permute2f128_pd(low01, low23, 0x31),
+            _mm256_permute2f128_pd(high01, high23, 0x31),
+        ];
+        for (column, &value) in columns.iter().enumerate() {
+            _mm256_storeu_pd(
+                output[(x + column) * output_stride + y..][..4].as_mut_ptr(),
+                value,
+            );
+        }
+    }
+}
+
+// The `neon` feature requires a newer compiler than the rest of the crate
+#[cfg(all(target_arch = "aarch64", feature = "neon"))]
+#[allow(clippy::incompatible_msrv)]
+mod aarch64 {
+    use std::arch::aarch64::*;
+    use std::arch::is_aarch64_feature_detected;
+
+    use super::{transpose_blocked, transpose_element, Shape};
+
+    pub fn transpose_f32(input: &[f32], output: &mut [f32], shape: Shape) {
+        if is_aarch64_feature_detected!("neon") {
+            transpose_blocked(
+                input,
+                output,
+                shape,
+                4,
+                |input, output, input_stride, output_stride, x, y| {
+                    // Safety: We just checked that the CPU supports NEON
+                    unsafe {
+                        transpose_f32_4x4_neon(input, output, input_stride, output_stride, x, y)
+                    }
+                },
+            );
+        } else {
+            transpose_blocked(input, output, shape, 1, transpose_element);
+        }
+    }
+
+    pub fn transpose_f64(input: &[f64], output: &mut [f64], shape: Shape) {
+        if is_aarch64_feature_detected!("neon") {
+            transpose_blocked(
+                input,
+                output,
+                shape,
+                2,
+                |input, output, input_stride, output_stride, x, y| {
+                    // Safety: We just checked that the CPU supports NEON
+                    unsafe {
+                        transpose_f64_2x2_neon(input, output, input_stride, output_stride, x, y)
+                    }
+                },
+            );
+        } else {
+            transpose_blocked(input, output, shape, 1, transpose_element);
+        }
+    }
+
+    // Like the x86 kernels, these slice out each row they load or store before touching it, so every access is bounds
+    // checked, and the only requirement for calling them is CPU support for NEON
+
+    #[target_feature(enable = "neon")]
+    unsafe fn transpose_f32_4x4_neon(
+        input: &[f32],
+        output: &mut [f32],
+        input_stride: usize,
+        output_stride: usize,
+        x: usize,
+        y: usize,
+    ) {
+        let load = |row: usize| vld1q_f32(input[(y + row) * input_stride + x..][..4].as_ptr());
+        let (row0, row1, row2, row3) = (load(0), load(1), load(2), load(3));
+
+        // interleave the even and odd elements of pairs of rows, so each pair of 64-bit halves holds 2 elements of one
+        // column, then combine the halves
+        let even01 = vreinterpretq_f64_f32(vtrn1q_f32(row0, row1));
+        let odd01 = vreinterpretq_f64_f32(vtrn2q_f32(row0, row1));
+        let even23 = vreinterpretq_f64_f32(vtrn1q_f32(row2, row3));
+        let odd23 = vreinterpretq_f64_f32(vtrn2q_f32(row2, row3));
+
+        let columns = [
+            vtrn1q_f64(even01, even23),
+            vtrn1q_f64(odd01, odd23),
+            vtrn2q_f64(even01, even23),
+            vtrn2q_f64(odd01, odd23),
+        ];
+        for (column, &value) in columns.iter().enumerate() {
+            vst1q_f32(
+                output[(x + column) * output_stride + y..][..4].as_mut_ptr(),
+                vreinterpretq_f32_f64(value),
+            );
+        }
+    }
+
+    #[target_feature(enable = "neon")]
+    unsafe fn transpose_f64_2x2_neon(
+        input: &[f64],
+        output: &mut [f64],
+        input_stride: usize,
+        output_stride: usize,
+        x: usize,
+        y: usize,
+    ) {
+        let row0 = vld1q_f64(input[y * input_stride + x..][..2].as_ptr());
+        let row1 = vld1q_f64(input[(y + 1) * input_stride + x..][..2].as_ptr());
+
+        vst1q_f64(
+            output[x * output_stride + y..][..2].as_mut_ptr(),
+            vtrn1q_f64(row0, row1),
+        );
+        vst1q_f64(
+            output[(x + 1) * output_stride + y..][..2].as_mut_ptr(),
+            vtrn2q_f64(row0, row1),
+        );
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    fn naive_transpose<T: Copy + Default>(input: &[T], width: usize, height: usize) -> Vec<T> {
+        let mut output = vec![T::default(); input.len()];
+        for y in 0..height {
+            for x in 0..width {
+                output[x * height + y] = input[y * width + x];
+            }
+        }
+        output
+    }
+
+    /// Verify that the transpose matches the naive transpose for element types with and without SIMD kernels, at
+    /// sizes that are and aren't multiples of the kernel and block sizes
+    #[test]
+    fn test_transpose() {
+        let sizes = [0, 1, 2, 3, 4, 5, 7, 8, 9, 15, 16, 31, 32, 33, 70];
+        for &width in sizes.iter() {
+            for &height in sizes.iter() {
+                let input: Vec<u32> = (0..width * height).map(|i| i as u32).collect();
+                let expected = naive_transpose(&input, width, height);
+
+                let mut actual = vec![0u32; input.len()];
+                transpose(&input, &mut actual, width, height);
+                assert_eq!(
+                    actual, expected,
+                    "u32, width = {}, height = {}",
+                    width, height
+                );
+
+                let input_f32: Vec<f32> = input.iter().map(|&i| i as f32).collect();
+                let expected_f32: Vec<f32> = expected.iter().map(|&i| i as f32).collect();
+                let mut actual_f32 = vec![0f32; input.len()];
+                transpose(&input_f32, &mut actual_f32, width, height);
+                assert_eq!(
+                    actual_f32, expected_f32,
+                    "f32, width = {}, height = {}",
+                    width, height
+                );
+
+                let input_f64: Vec<f64> = input.iter().map(|&i| i as f64).collect();
+                let expected_f64: Vec<f64> = expected.iter().map(|&i| i as f64).collect();
+                let mut actual_f64 = vec![0f64; input.len()];
+                transpose(&input_f64, &mut actual_f64, width, height);
+                assert_eq!(
+                    actual_f64, expected_f64,
+                    "f64, width = {}, height = {}",
+                    width, height
+                );
+            }
+        }
+    }
+
+    /// Verify that gathering a strip of columns out of a wider matrix and scattering it back matches the naive
+    /// transpose, for strips that are and aren't multiples of the kernel sizes, and that the elements outside the strip
+    /// are left alone
+    #[test]
+    fn test_transpose_strided() {
+        fn check<T: Copy + PartialEq + std::fmt::Debug + 'static>(convert: impl Fn(usize) -> T) {
+            let stride = 41;
+            for &height in [0, 1, 3, 4, 8, 16, 33].iter() {
+                for &(first_column, width) in [(0, 0), (0, 16), (5, 4), (7, 9), (25, 16)].iter() {
+                    let matrix: Vec<T> = (0..stride * height).map(&convert).collect();
+
+                    let mut columns: Vec<T> = (0..width * height).map(|_| convert(0)).collect();
+                    let strip = if height == 0 {
+                        &[][..]
+                    } else {
+                        &matrix[first_column..]
+                    };
+                    transpose_strided(strip, stride, &mut columns, height, width, height);
+                    for column in 0..width {
+                        for row in 0..height {
+                            assert_eq!(
+                                columns[column * height + row],
+                                matrix[row * stride + first_column + column],
+                                "width = {}, height = {}",
+                                width,
+                                height
+                            );
+                        }
+                    }
+
+                    let mut scattered: Vec<T> = (0..stride * height).map(|_| convert(0)).collect();
+                    let strip = if height == 0 {
+                        &mut [][..]
+                    } else {
+                        &mut scattered[first_column..]
+                    };
+                    transpose_strided(&columns, height, strip, stride, height, width);
+                    for (index, (&actual, &original)) in
+                        scattered.iter().zip(matrix.iter()).enumerate()
+                    {
+                        let column = index % stride;
+                        let expected = if column >= first_column && column < first_column + width {
+                            original
+                        } else {
+                            convert(0)
+                        };
+                        assert_eq!(actual, expected, "width = {}, height = {}", width, height);
+                    }
+                }
+            }
+        }
+        check(|i| i as u32);
+        check(|i| i as f32);
+        check(|i| i as f64);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_transpose_strided_short_input() {
+        let input = [0f32; 10];
+        let mut output = [0f32; 12];
+        transpose_strided(&input, 4, &mut output, 3, 4, 3);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_transpose_wrong_len() {
+        let input = [0f32; 6];
+        let mut output = [0f32; 6];
+        transpose(&input, &mut output, 4, 2);
+    }
+}
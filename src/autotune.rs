@@ -0,0 +1,456 @@
+//! Calibrates a [`PlannerCostModel`](../struct.PlannerCostModel.html) for the current machine, by benchmarking the
+//! naive and fast algorithms on either side of each crossover size.
+//!
+//! Calibration runs one measurement at a time, and a [`Calibration`](struct.Calibration.html) records every
+//! measurement it has finished. So calibration can be cancelled between any two measurements, and resumed later from
+//! where it stopped, even in another process, by saving the measurements and restoring them with
+//! [`Calibration::from_measurements`](struct.Calibration.html#method.from_measurements). Until a crossover's
+//! measurements are all finished, its cost model field keeps its default value.
+//!
+//! Calibration takes a while, so [`BackgroundCalibration`](struct.BackgroundCalibration.html) runs it on a background
+//! thread. Plan with the default cost model in the meantime, and switch to the calibrated one once it's complete:
+//!
+//! ~~~
+//! use rustdct::autotune::{BackgroundCalibration, Calibration};
+//! use rustdct::DctPlanner;
+//!
+//! let background = BackgroundCalibration::spawn::<f32>(Calibration::new(5));
+//!
+//! // The cost model only includes the crossovers that are already calibrated, so this plans immediately
+//! let mut planner = DctPlanner::<f32>::with_cost_model(background.cost_model());
+//! let dct4 = planner.plan_dct4(21);
+//!
+//! // Wait for the rest, and keep the measurements to skip calibration next time
+//! let calibration = background.join();
+//! assert!(calibration.is_complete());
+//! let mut planner = DctPlanner::<f32>::with_cost_model(calibration.cost_model());
+//! ~~~
+//!
+//! This module is only available when the `bench` feature is enabled. The cost models it produces depend on timing,
+//! so unlike the default cost model, they can differ from one run to the next.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::bench::measure_process_time;
+use crate::{Cancelled, DctNum, DctPlanner, PlannerCostModel};
+
+/// A field of [`PlannerCostModel`](../struct.PlannerCostModel.html) that can be calibrated.
+///
+/// Each of these fields is the size where the planner switches from a naive algorithm to a fast one. The other fields
+/// choose between two fast algorithms, and keep their default values.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CrossoverField {
+    /// `PlannerCostModel::dct1_min_fast_len`
+    Dct1,
+    /// `PlannerCostModel::dst1_min_fast_len`
+    Dst1,
+    /// `PlannerCostModel::dct4_even_min_fast_len`
+    Dct4Even,
+    /// `PlannerCostModel::dct4_odd_min_fast_len`
+    Dct4Odd,
+    /// `PlannerCostModel::dct6_min_fast_len`
+    Dct6,
+    /// `PlannerCostModel::dst6_min_fast_len`
+    Dst6,
+    /// `PlannerCostModel::dct8_min_fast_len`
+    Dct8,
+    /// `PlannerCostModel::dst8_min_fast_len`
+    Dst8,
+}
+
+impl CrossoverField {
+    /// Every calibrated field, in the order calibration measures them
+    pub const ALL: [CrossoverField; 8] = [
+        CrossoverField::Dct1,
+        CrossoverField::Dst1,
+        CrossoverField::Dct4Even,
+        CrossoverField::Dct4Odd,
+        CrossoverField::Dct6,
+        CrossoverField::Dst6,
+        CrossoverField::Dct8,
+        CrossoverField::Dst8,
+    ];
+
+    /// Returns the sizes that calibration measures for this field, in increasing order.
+    ///
+    /// The sizes skip the ones that the planner always computes with a butterfly, since the cost model doesn't affect
+    /// them.
+    pub fn candidate_lens(self) -> &'static [usize] {
+        match self {
+            CrossoverField::Dct1 | CrossoverField::Dst1 => &[17, 20, 24, 28, 32, 40, 48, 64],
+            CrossoverField::Dct4Even => &[6, 10, 12, 14, 20, 24, 28, 40, 48],
+            CrossoverField::Dct4Odd => &[3, 5, 7, 9, 11, 15, 21, 27, 45],
+            CrossoverField::Dct6 | CrossoverField::Dct8 | CrossoverField::Dst8 => {
+                &[5, 6, 8, 10, 12, 16, 24, 32]
+            }
+            CrossoverField::Dst6 => &[8, 16, 24, 32, 45, 64, 96],
+        }
+    }
+
+    fn set(self, cost_model: &mut PlannerCostModel, value: usize) {
+        let field = match self {
+            CrossoverField::Dct1 => &mut cost_model.dct1_min_fast_len,
+            CrossoverField::Dst1 => &mut cost_model.dst1_min_fast_len,
+            CrossoverField::Dct4Even => &mut cost_model.dct4_even_min_fast_len,
+            CrossoverField::Dct4Odd => &mut cost_model.dct4_odd_min_fast_len,
+            CrossoverField::Dct6 => &mut cost_model.dct6_min_fast_len,
+            CrossoverField::Dst6 => &mut cost_model.dst6_min_fast_len,
+            CrossoverField::Dct8 => &mut cost_model.dct8_min_fast_len,
+            CrossoverField::Dst8 => &mut cost_model.dst8_min_fast_len,
+        };
+        *field = value;
+    }
+
+    // Returns the median time of the transform this field applies to, planned with `cost_model`
+    fn measure<T: DctNum>(
+        self,
+        cost_model: PlannerCostModel,
+        len: usize,
+        iterations: usize,
+    ) -> Duration {
+        let mut planner = DctPlanner::<T>::with_cost_model(cost_model);
+        let stats = match self {
+            CrossoverField::Dct1 => measure_process_time(&planner.plan_dct1(len), iterations),
+            CrossoverField::Dst1 => measure_process_time(&planner.plan_dst1(len), iterations),
+            CrossoverField::Dct4Even | CrossoverField::Dct4Odd => {
+                measure_process_time(&planner.plan_dct4(len), iterations)
+            }
+            CrossoverField::Dct6 => measure_process_time(&planner.plan_dct6(len), iterations),
+            CrossoverField::Dst6 => measure_process_time(&planner.plan_dst6(len), iterations),
+            CrossoverField::Dct8 => measure_process_time(&planner.plan_dct8(len), iterations),
+            CrossoverField::Dst8 => measure_process_time(&planner.plan_dst8(len), iterations),
+        };
+        stats.median
+    }
+}
+
+/// The result of timing the naive and the fast algorithm for one size of one crossover.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CalibrationMeasurement {
+    /// The crossover that was measured
+    pub field: CrossoverField,
+    /// The size that was measured
+    pub len: usize,
+    /// The median time of the naive algorithm
+    pub naive_time: Duration,
+    /// The median time of the fast algorithm
+    pub fast_time: Duration,
+}
+
+/// The progress of calibrating a cost model: the measurements that are finished so far.
+///
+/// See the [module documentation](index.html) for an example.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Calibration {
+    iterations: usize,
+    measurements: Vec<CalibrationMeasurement>,
+}
+
+impl Calibration {
+    /// Creates a calibration that hasn't measured anything yet, and that times each algorithm `iterations` times.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `iterations` is 0.
+    pub fn new(iterations: usize) -> Self {
+        Self::from_measurements(iterations, Vec::new())
+    }
+
+    /// Restores a calibration from the measurements of an earlier one, so that it can be resumed. Measurements of
+    /// sizes that calibration doesn't measure are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `iterations` is 0.
+    pub fn from_measurements(iterations: usize, measurements: Vec<CalibrationMeasurement>) -> Self {
+        assert!(
+            iterations > 0,
+            "calibration requires at least one iteration"
+        );
+        Self {
+            iterations,
+            measurements,
+        }
+    }
+
+    /// Returns the measurements that are finished so far, in the order they were measured.
+    pub fn measurements(&self) -> &[CalibrationMeasurement] {
+        &self.measurements
+    }
+
+    /// Returns the number of measurements that a complete calibration has.
+    pub fn measurement_count(&self) -> usize {
+        CrossoverField::ALL
+            .iter()
+            .map(|field| field.candidate_lens().len())
+            .sum()
+    }
+
+    /// Returns true if every measurement is finished.
+    pub fn is_complete(&self) -> bool {
+        self.next_measurement().is_none()
+    }
+
+    /// Measures every size that isn't measured yet, and returns the calibrated cost model.
+    ///
+    /// `should_continue` is called before each measurement, and if it returns false, calibration stops and returns
+    /// `Err(Cancelled)`. The measurements that finished before that are kept, so calling `run` again resumes
+    /// calibration. Use the same `T` when resuming, since the crossovers depend on the element type.
+    pub fn run<T: DctNum>(
+        &mut self,
+        should_continue: &mut dyn FnMut() -> bool,
+    ) -> Result<PlannerCostModel, Cancelled> {
+        while let Some((field, len)) = self.next_measurement() {
+            if !should_continue() {
+                return Err(Cancelled);
+            }
+            let measurement = measure::<T>(field, len, self.iterations);
+            self.measurements.push(measurement);
+        }
+        Ok(self.cost_model())
+    }
+
+    /// Returns the default cost model, with every crossover whose measurements are all finished replaced by its
+    /// calibrated value.
+    ///
+    /// A crossover is calibrated to the smallest measured size where the fast algorithm is at least as fast as the
+    /// naive one, at that size and every larger measured size. If the naive algorithm is faster at the largest
+    /// measured size, the crossover is set just above it.
+    pub fn cost_model(&self) -> PlannerCostModel {
+        let mut cost_model = PlannerCostModel::default();
+        for &field in CrossoverField::ALL.iter() {
+            if let Some(crossover) = self.crossover(field) {
+                field.set(&mut cost_model, crossover);
+            }
+        }
+        cost_model
+    }
+
+    fn find(&self, field: CrossoverField, len: usize) -> Option<&CalibrationMeasurement> {
+        self.measurements
+            .iter()
+            .find(|measurement| measurement.field == field && measurement.len == len)
+    }
+
+    fn next_measurement(&self) -> Option<(CrossoverField, usize)> {
+        CrossoverField::ALL
+            .iter()
+            .flat_map(|&field| field.candidate_lens().iter().map(move |&len| (field, len)))
+            .find(|&(field, len)| self.find(field, len).is_none())
+    }
+
+    // Returns the calibrated value of `field`, or None if some of its sizes aren't measured yet
+    fn crossover(&self, field: CrossoverField) -> Option<usize> {
+        let lens = field.candidate_lens();
+        let mut crossover = lens[lens.len() - 1] + 1;
+        for &len in lens.iter().rev() {
+            let measurement = self.find(field, len)?;
+            if measurement.fast_time <= measurement.naive_time && crossover == len_after(lens, len)
+            {
+                crossover = len;
+            }
+        }
+        Some(crossover)
+    }
+}
+
+// Returns the candidate after `len`, or one more than the last candidate if `len` is the last one
+fn len_after(lens: &[usize], len: usize) -> usize {
+    let index = lens.iter().position(|&candidate| candidate == len).unwrap();
+    lens.get(index + 1)
+        .cloned()
+        .unwrap_or(lens[lens.len() - 1] + 1)
+}
+
+fn measure<T: DctNum>(
+    field: CrossoverField,
+    len: usize,
+    iterations: usize,
+) -> CalibrationMeasurement {
+    // Planning with a crossover of 0 always picks the fast algorithm, and a crossover of usize::MAX the naive one
+    let mut naive_model = PlannerCostModel::default();
+    field.set(&mut naive_model, std::usize::MAX);
+    let mut fast_model = PlannerCostModel::default();
+    field.set(&mut fast_model, 0);
+
+    CalibrationMeasurement {
+        field,
+        len,
+        naive_time: field.measure::<T>(naive_model, len, iterations),
+        fast_time: field.measure::<T>(fast_model, len, iterations),
+    }
+}
+
+/// A [`Calibration`](struct.Calibration.html) running on a background thread.
+///
+/// Dropping a `BackgroundCalibration` cancels it, after the measurement that's running finishes.
+pub struct BackgroundCalibration {
+    calibration: Arc<Mutex<Calibration>>,
+    cancelled: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl BackgroundCalibration {
+    /// Starts running `calibration` on a new thread, measuring transforms of element type `T`.
+    pub fn spawn<T: DctNum>(calibration: Calibration) -> Self {
+        let iterations = calibration.iterations;
+        let calibration = Arc::new(Mutex::new(calibration));
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        let thread = {
+            let calibration = Arc::clone(&calibration);
+            let cancelled = Arc::clone(&cancelled);
+            thread::spawn(move || {
+                // Measure without holding the lock, so that `cost_model` doesn't block until the measurement finishes
+                loop {
+                    let next = calibration.lock().unwrap().next_measurement();
+                    match next {
+                        Some((field, len)) if !cancelled.load(Ordering::Relaxed) => {
+                            let measurement = measure::<T>(field, len, iterations);
+                            calibration.lock().unwrap().measurements.push(measurement);
+                        }
+                        _ => break,
+                    }
+                }
+            })
+        };
+
+        Self {
+            calibration,
+            cancelled,
+            thread: Some(thread),
+        }
+    }
+
+    /// Returns the cost model of the measurements that are finished so far. Crossovers that aren't calibrated yet
+    /// keep their default values.
+    pub fn cost_model(&self) -> PlannerCostModel {
+        self.calibration.lock().unwrap().cost_model()
+    }
+
+    /// Returns a copy of the calibration as it is now, for saving its measurements.
+    pub fn calibration(&self) -> Calibration {
+        self.calibration.lock().unwrap().clone()
+    }
+
+    /// Returns true if every measurement is finished.
+    pub fn is_complete(&self) -> bool {
+        self.calibration.lock().unwrap().is_complete()
+    }
+
+    /// Waits for calibration to finish, and returns it.
+    pub fn join(mut self) -> Calibration {
+        self.wait()
+    }
+
+    /// Cancels calibration, waits for the measurement that's running to finish, and returns the measurements that are
+    /// finished. Pass the result to [`spawn`](#method.spawn) or [`Calibration::run`](struct.Calibration.html#method.run)
+    /// to resume it.
+    pub fn cancel(mut self) -> Calibration {
+        self.cancelled.store(true, Ordering::Relaxed);
+        self.wait()
+    }
+
+    fn wait(&mut self) -> Calibration {
+        if let Some(thread) = self.thread.take() {
+            thread.join().expect("calibration thread panicked");
+        }
+        self.calibration()
+    }
+}
+
+impl Drop for BackgroundCalibration {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    fn measurement(
+        field: CrossoverField,
+        len: usize,
+        naive: u64,
+        fast: u64,
+    ) -> CalibrationMeasurement {
+        CalibrationMeasurement {
+            field,
+            len,
+            naive_time: Duration::from_nanos(naive),
+            fast_time: Duration::from_nanos(fast),
+        }
+    }
+
+    #[test]
+    fn test_crossover() {
+        // the fast algorithm wins at 7 and 9, then loses at 11, so the crossover has to be after 11
+        let lens = CrossoverField::Dct4Odd.candidate_lens();
+        let measurements: Vec<_> = lens
+            .iter()
+            .map(|&len| match len {
+                7 | 9 => measurement(CrossoverField::Dct4Odd, len, 10, 5),
+                len if len <= 11 => measurement(CrossoverField::Dct4Odd, len, 5, 10),
+                len => measurement(CrossoverField::Dct4Odd, len, 10, 5),
+            })
+            .collect();
+        let calibration = Calibration::from_measurements(1, measurements.clone());
+        assert_eq!(calibration.cost_model().dct4_odd_min_fast_len, 15);
+        assert!(!calibration.is_complete());
+
+        // the other fields aren't measured, so they keep their defaults
+        let expected = PlannerCostModel {
+            dct4_odd_min_fast_len: 15,
+            ..PlannerCostModel::default()
+        };
+        assert_eq!(calibration.cost_model(), expected);
+
+        // if the naive algorithm wins at the largest size, the crossover is just above it
+        let mut slow = measurements;
+        *slow.last_mut().unwrap() = measurement(CrossoverField::Dct4Odd, 45, 5, 10);
+        let calibration = Calibration::from_measurements(1, slow);
+        assert_eq!(calibration.cost_model().dct4_odd_min_fast_len, 46);
+    }
+
+    #[test]
+    fn test_cancel_and_resume() {
+        let mut calibration = Calibration::new(1);
+
+        let mut remaining = 3;
+        let result = calibration.run::<f32>(&mut || {
+            remaining -= 1;
+            remaining > 0
+        });
+        assert_eq!(result, Err(Cancelled));
+        assert_eq!(calibration.measurements().len(), 2);
+
+        let first_measurements = calibration.measurements().to_vec();
+        let cost_model = calibration.run::<f32>(&mut || true).unwrap();
+        assert!(calibration.is_complete());
+        assert_eq!(
+            calibration.measurements().len(),
+            calibration.measurement_count()
+        );
+        assert_eq!(&calibration.measurements()[..2], &first_measurements[..]);
+        assert_eq!(cost_model, calibration.cost_model());
+
+        // a complete calibration doesn't measure anything else
+        let mut restored = Calibration::from_measurements(1, calibration.measurements().to_vec());
+        assert_eq!(restored.run::<f32>(&mut || false), Ok(cost_model));
+    }
+
+    #[test]
+    fn test_background_calibration() {
+        let calibration = BackgroundCalibration::spawn::<f64>(Calibration::new(1)).join();
+        assert!(calibration.is_complete());
+
+        // resuming a cancelled calibration finishes it
+        let cancelled = BackgroundCalibration::spawn::<f64>(Calibration::new(1)).cancel();
+        let resumed = BackgroundCalibration::spawn::<f64>(cancelled).join();
+        assert!(resumed.is_complete());
+    }
+}
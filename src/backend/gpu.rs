@@ -0,0 +1,478 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::f64;
+use std::sync::mpsc;
+use std::sync::Arc;
+
+use rustfft::Length;
+use wgpu::util::DeviceExt;
+
+use super::{BatchedType2And3, DctBackend};
+
+// Each invocation computes one output element, as the dot product of one row of the transform matrix with its chunk
+// of the input. The batches this backend is meant for have short transforms, so an O(n^2) matrix multiplication
+// keeps the GPU busier than a fast algorithm with several dependent passes would.
+const SHADER_SOURCE: &str = r#"
+struct Params {
+    len: u32,
+    total: u32,
+    row_stride: u32,
+    padding: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> matrix: array<f32>;
+@group(0) @binding(2) var<storage, read> input: array<f32>;
+@group(0) @binding(3) var<storage, read_write> output: array<f32>;
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.y * params.row_stride + id.x;
+    if (index >= params.total) {
+        return;
+    }
+
+    let chunk_start = index - index % params.len;
+    let row_start = (index % params.len) * params.len;
+
+    var sum = 0.0;
+    for (var i = 0u; i < params.len; i = i + 1u) {
+        sum = sum + matrix[row_start + i] * input[chunk_start + i];
+    }
+    output[index] = sum;
+}
+"#;
+
+const WORKGROUP_SIZE: usize = 64;
+const F32_SIZE: u64 = 4;
+
+/// Backend that computes batches of `f32` DCTs on the GPU, using `wgpu`. Only available when the `gpu` feature is
+/// enabled.
+///
+/// Like `DctPlanner`, the backend caches its plans, so planning the same size twice returns the same instance.
+///
+/// ~~~no_run
+/// // Computes a DCT Type 2 of size 8 on each of a million consecutive signals, on the GPU if one is available
+/// use rustdct::backend::{DctBackend, WgpuBackend};
+///
+/// let mut backend = WgpuBackend::new().expect("no GPU available");
+/// let dct = backend.plan_dct2_batch(8);
+///
+/// let mut data = vec![0f32; 8 * 1_000_000];
+/// dct.process_dct2_batch(&mut data);
+/// ~~~
+pub struct WgpuBackend {
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    cache: HashMap<usize, Arc<WgpuBatchedType2And3>>,
+}
+
+impl WgpuBackend {
+    /// Creates a new backend on the system's default GPU adapter.
+    ///
+    /// Returns `None` if no adapter or device is available.
+    pub fn new() -> Option<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let adapter =
+            pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))?;
+        let (device, queue) = pollster::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("rustdct"),
+                required_features: wgpu::Features::empty(),
+                required_limits:
+                    wgpu::Limits::downlevel_defaults().using_resolution(adapter.limits()),
+            },
+            None,
+        ))
+        .ok()?;
+
+        Some(Self::from_device(Arc::new(device), Arc::new(queue)))
+    }
+
+    /// Creates a new backend that uses an existing device and queue, so that it can share them with the rest of an
+    /// application.
+    pub fn from_device(device: Arc<wgpu::Device>, queue: Arc<wgpu::Queue>) -> Self {
+        Self {
+            device,
+            queue,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Returns a batched DCT2 and DCT3 instance which processes chunks of size `len`.
+    ///
+    /// Unlike `plan_dct2_batch`, this returns the concrete type, which has methods for keeping data on the GPU between
+    /// transforms.
+    pub fn plan_wgpu_dct2_batch(&mut self, len: usize) -> Arc<WgpuBatchedType2And3> {
+        let device = &self.device;
+        let queue = &self.queue;
+        let plan = self.cache.entry(len).or_insert_with(|| {
+            Arc::new(WgpuBatchedType2And3::new(
+                Arc::clone(device),
+                Arc::clone(queue),
+                len,
+            ))
+        });
+        Arc::clone(plan)
+    }
+}
+
+impl DctBackend<f32> for WgpuBackend {
+    fn plan_dct2_batch(&mut self, len: usize) -> Arc<dyn BatchedType2And3<f32>> {
+        self.plan_wgpu_dct2_batch(len)
+    }
+}
+
+/// A buffer of `f32` data stored on the GPU, created by
+/// [`WgpuBatchedType2And3::upload`](struct.WgpuBatchedType2And3.html#method.upload).
+pub struct WgpuBatchBuffer {
+    data: wgpu::Buffer,
+    output: wgpu::Buffer,
+    len: usize,
+}
+impl WgpuBatchBuffer {
+    /// Returns the number of `f32` elements in this buffer
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    /// Returns true if this buffer has no elements
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+/// Batched DCT2 and DCT3 implementation that runs on the GPU, planned by `WgpuBackend`.
+///
+/// `process_dct2_batch` and `process_dct3_batch` upload the data, transform it, and download the result, splitting very
+/// large batches into pieces that fit in the device's limits. To apply several transforms to the same data without
+/// copying it back and forth, use `upload`, `process_dct2_gpu` and `process_dct3_gpu`, and `download` directly.
+pub struct WgpuBatchedType2And3 {
+    device: Arc<wgpu::Device>,
+    queue: Arc<wgpu::Queue>,
+    pipeline: wgpu::ComputePipeline,
+    dct2_matrix: wgpu::Buffer,
+    dct3_matrix: wgpu::Buffer,
+    len: usize,
+    max_batch_elements: usize,
+}
+
+impl WgpuBatchedType2And3 {
+    fn new(device: Arc<wgpu::Device>, queue: Arc<wgpu::Queue>, len: usize) -> Self {
+        assert!(len > 0, "WgpuBatchedType2And3 requires a nonzero length");
+
+        // row k of the DCT2 matrix is cos(pi * (2n + 1) * k / 2len). the DCT3 matrix is its transpose, with the first
+        // column halved
+        let constant_factor = f64::consts::PI / (2 * len) as f64;
+        let mut dct2_matrix = vec![0f32; len * len];
+        let mut dct3_matrix = vec![0f32; len * len];
+        for k in 0..len {
+            for n in 0..len {
+                let twiddle_index = ((2 * n + 1) * k) % (4 * len);
+                let value = (constant_factor * twiddle_index as f64).cos();
+                dct2_matrix[k * len + n] = value as f32;
+                dct3_matrix[n * len + k] = if k == 0 { value * 0.5 } else { value } as f32;
+            }
+        }
+
+        let create_matrix = |label, matrix: &[f32]| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents: &to_bytes(matrix),
+                usage: wgpu::BufferUsages::STORAGE,
+            })
+        };
+        let dct2_matrix = create_matrix("rustdct dct2 matrix", &dct2_matrix);
+        let dct3_matrix = create_matrix("rustdct dct3 matrix", &dct3_matrix);
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("rustdct batched matrix multiply"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(SHADER_SOURCE)),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("rustdct batched matrix multiply"),
+            layout: None,
+            module: &shader,
+            entry_point: "main",
+        });
+
+        // each batch has to fit in a single storage binding, and be indexable by a u32
+        let limits = device.limits();
+        let max_elements = (limits.max_storage_buffer_binding_size as u64 / F32_SIZE)
+            .min(u32::MAX as u64) as usize;
+        let max_batch_elements = max_elements - max_elements % len;
+        assert!(
+            max_batch_elements > 0,
+            "Transform length {} is too large for this device",
+            len
+        );
+
+        Self {
+            device,
+            queue,
+            pipeline,
+            dct2_matrix,
+            dct3_matrix,
+            len,
+            max_batch_elements,
+        }
+    }
+
+    /// Returns the largest number of elements that a single `WgpuBatchBuffer` can hold for this transform. This is
+    /// always a multiple of `len()`.
+    pub fn max_batch_elements(&self) -> usize {
+        self.max_batch_elements
+    }
+
+    /// Copies `data` into a new buffer on the GPU.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data.len()` isn't a multiple of `self.len()`, or if it's larger than `self.max_batch_elements()`.
+    pub fn upload(&self, data: &[f32]) -> WgpuBatchBuffer {
+        validate_batch_len(data.len(), self.len);
+        assert!(
+            data.len() <= self.max_batch_elements,
+            "Provided data is too large for a single GPU buffer. Max len = {}, got data len = {}",
+            self.max_batch_elements,
+            data.len()
+        );
+
+        let size = data.len() as u64 * F32_SIZE;
+        let data_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("rustdct batch data"),
+                contents: &to_bytes(data),
+                usage: wgpu::BufferUsages::STORAGE
+                    | wgpu::BufferUsages::COPY_SRC
+                    | wgpu::BufferUsages::COPY_DST,
+            });
+        let output_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("rustdct batch output"),
+            size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+
+        WgpuBatchBuffer {
+            data: data_buffer,
+            output: output_buffer,
+            len: data.len(),
+        }
+    }
+
+    /// Copies the contents of `buffer` from the GPU into `output`, waiting for any transforms that are still running.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `output.len()` isn't equal to `buffer.len()`.
+    pub fn download(&self, buffer: &WgpuBatchBuffer, output: &mut [f32]) {
+        assert_eq!(
+            output.len(),
+            buffer.len,
+            "Output len must match the GPU buffer len. Expected {}, got {}",
+            buffer.len,
+            output.len()
+        );
+        if output.is_empty() {
+            return;
+        }
+
+        let size = buffer.len as u64 * F32_SIZE;
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("rustdct batch download"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        encoder.copy_buffer_to_buffer(&buffer.data, 0, &staging, 0, size);
+        self.queue.submit(Some(encoder.finish()));
+
+        let slice = staging.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .expect("The GPU device was lost while downloading")
+            .expect("Failed to map the GPU buffer for reading");
+
+        {
+            let bytes = slice.get_mapped_range();
+            for (element, chunk) in output.iter_mut().zip(bytes.chunks_exact(F32_SIZE as usize)) {
+                *element = f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+            }
+        }
+        staging.unmap();
+    }
+
+    /// Computes the DCT Type 2 on each consecutive chunk of `buffer`, in-place on the GPU.
+    ///
+    /// This only submits the work to the GPU. It's finished by the time a following `download` returns.
+    pub fn process_dct2_gpu(&self, buffer: &WgpuBatchBuffer) {
+        self.process_gpu(buffer, &self.dct2_matrix);
+    }
+
+    /// Computes the DCT Type 3 on each consecutive chunk of `buffer`, in-place on the GPU.
+    ///
+    /// This only submits the work to the GPU. It's finished by the time a following `download` returns.
+    pub fn process_dct3_gpu(&self, buffer: &WgpuBatchBuffer) {
+        self.process_gpu(buffer, &self.dct3_matrix);
+    }
+
+    fn process_gpu(&self, buffer: &WgpuBatchBuffer, matrix: &wgpu::Buffer) {
+        if buffer.is_empty() {
+            return;
+        }
+        validate_batch_len(buffer.len, self.len);
+
+        // the number of workgroups in each dimension is limited, so large batches are spread over a 2D grid of them
+        let max_workgroups = self.device.limits().max_compute_workgroups_per_dimension as usize;
+        let workgroups = buffer.len.div_ceil(WORKGROUP_SIZE);
+        let workgroups_x = workgroups.min(max_workgroups);
+        let workgroups_y = workgroups.div_ceil(workgroups_x);
+
+        let params = [
+            self.len as u32,
+            buffer.len as u32,
+            (workgroups_x * WORKGROUP_SIZE) as u32,
+            0,
+        ];
+        let params_bytes: Vec<u8> = params
+            .iter()
+            .flat_map(|x| x.to_le_bytes().to_vec())
+            .collect();
+        let params_buffer = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("rustdct batch params"),
+                contents: &params_bytes,
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: matrix.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: buffer.data.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: buffer.output.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: None,
+                timestamp_writes: None,
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(workgroups_x as u32, workgroups_y as u32, 1);
+        }
+        // the shader can't work in-place, so copy the output back into the data buffer
+        encoder.copy_buffer_to_buffer(
+            &buffer.output,
+            0,
+            &buffer.data,
+            0,
+            buffer.len as u64 * F32_SIZE,
+        );
+        self.queue.submit(Some(encoder.finish()));
+    }
+
+    fn process_batch(&self, data: &mut [f32], process: fn(&Self, &WgpuBatchBuffer)) {
+        validate_batch_len(data.len(), self.len);
+
+        for piece in data.chunks_mut(self.max_batch_elements) {
+            let buffer = self.upload(piece);
+            process(self, &buffer);
+            self.download(&buffer, piece);
+        }
+    }
+}
+
+impl BatchedType2And3<f32> for WgpuBatchedType2And3 {
+    fn process_dct2_batch(&self, data: &mut [f32]) {
+        self.process_batch(data, Self::process_dct2_gpu);
+    }
+    fn process_dct3_batch(&self, data: &mut [f32]) {
+        self.process_batch(data, Self::process_dct3_gpu);
+    }
+}
+impl Length for WgpuBatchedType2And3 {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+// Verifies that `data` contains a whole number of chunks
+fn validate_batch_len(data_len: usize, chunk_len: usize) {
+    assert!(
+        data_len % chunk_len == 0,
+        "Provided data must be a multiple of the transform size. Transform len = {}, got data len = {}",
+        chunk_len,
+        data_len
+    );
+}
+
+fn to_bytes(data: &[f32]) -> Vec<u8> {
+    data.iter().flat_map(|x| x.to_le_bytes().to_vec()).collect()
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::test_utils::{compare_float_vectors, random_signal};
+    use crate::DctPlanner;
+
+    #[test]
+    fn test_wgpu_backend() {
+        // CI machines often don't have a GPU, so there's nothing to test without one
+        let mut backend = match WgpuBackend::new() {
+            Some(backend) => backend,
+            None => return,
+        };
+        let mut planner = DctPlanner::new();
+
+        for &len in &[1, 8, 13, 64] {
+            let dct = backend.plan_dct2_batch(len);
+            assert_eq!(dct.len(), len);
+            let reference = planner.plan_dct2(len);
+
+            let mut expected = random_signal(len * 100);
+            let mut actual = expected.clone();
+            for chunk in expected.chunks_mut(len) {
+                reference.process_dct2(chunk);
+            }
+            dct.process_dct2_batch(&mut actual);
+            assert!(compare_float_vectors(&expected, &actual), "len = {}", len);
+
+            for chunk in expected.chunks_mut(len) {
+                reference.process_dct3(chunk);
+            }
+            dct.process_dct3_batch(&mut actual);
+            assert!(compare_float_vectors(&expected, &actual), "len = {}", len);
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! Backends for computing large batches of small DCTs.
+//!
+//! A backend plans `BatchedType2And3` instances, which compute the same DCT2 or DCT3 on every consecutive chunk of a
+//! buffer. `DctPlanner` is the default backend: its batches are computed on the CPU, with the same algorithms returned
+//! by `plan_dct2`. `Type2And3ConvertToFft` computes the FFTs of several chunks at once, and the other algorithms
+//! compute one chunk at a time. When the `gpu` feature is enabled, `WgpuBackend` computes batches on the GPU
+//! instead, which can be much faster for very large batches of short transforms.
+//!
+//! Code that's generic over `DctBackend` can switch between the two without any other changes:
+//!
+//! ~~~
+//! // Computes a DCT Type 2 of size 8 on each of 1000 consecutive signals
+//! use rustdct::backend::DctBackend;
+//! use rustdct::DctPlanner;
+//!
+//! fn transform_blocks<B: DctBackend<f32>>(backend: &mut B, data: &mut [f32]) {
+//!     let dct = backend.plan_dct2_batch(8);
+//!     dct.process_dct2_batch(data);
+//! }
+//!
+//! let mut planner = DctPlanner::new();
+//! let mut data = vec![0f32; 8 * 1000];
+//! transform_blocks(&mut planner, &mut data);
+//! ~~~
+
+use std::sync::Arc;
+
+use rustfft::Length;
+
+use crate::batch::{for_each_chunk_dct2, for_each_chunk_dct3};
+use crate::{DctNum, DctPlanner, TransformType2And3};
+
+// The `gpu` feature requires a much newer compiler than the rest of the crate
+#[cfg(feature = "gpu")]
+#[allow(clippy::incompatible_msrv)]
+mod gpu;
+#[cfg(feature = "gpu")]
+pub use self::gpu::{WgpuBackend, WgpuBatchBuffer, WgpuBatchedType2And3};
+
+/// A DCT2 and DCT3 that's applied to every consecutive chunk of length `len()` in a buffer.
+pub trait BatchedType2And3<T>: Length + Send + Sync {
+    /// Computes the DCT Type 2 on each consecutive chunk of `data`, in-place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data.len()` isn't a multiple of `self.len()`.
+    fn process_dct2_batch(&self, data: &mut [T]);
+
+    /// Computes the DCT Type 3 on each consecutive chunk of `data`, in-place.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data.len()` isn't a multiple of `self.len()`.
+    fn process_dct3_batch(&self, data: &mut [T]);
+}
+
+/// Plans batched transforms for a particular kind of hardware.
+pub trait DctBackend<T> {
+    /// Returns a batched DCT2 and DCT3 instance which processes chunks of size `len`.
+    fn plan_dct2_batch(&mut self, len: usize) -> Arc<dyn BatchedType2And3<T>>;
+}
+
+impl<T: DctNum> DctBackend<T> for DctPlanner<T> {
+    fn plan_dct2_batch(&mut self, len: usize) -> Arc<dyn BatchedType2And3<T>> {
+        self.plan_dct2_batch_cpu(len)
+    }
+}
+
+/// Batched DCT2 and DCT3 implementation that computes each chunk on the CPU with a `TransformType2And3` instance.
+///
+/// This is what `DctPlanner` returns from `plan_dct2_batch`, for sizes that it doesn't compute with
+/// `Type2And3ConvertToFft`. Each call to `process_dct2_batch` or `process_dct3_batch`
+/// allocates one scratch buffer, and reuses it for every chunk.
+pub struct CpuBatchedType2And3<T> {
+    inner: Arc<dyn TransformType2And3<T>>,
+}
+impl<T: DctNum> CpuBatchedType2And3<T> {
+    /// Creates a new batched DCT2 and DCT3 context that will process chunks of length `inner.len()`.
+    pub fn new(inner: Arc<dyn TransformType2And3<T>>) -> Self {
+        Self { inner }
+    }
+}
+impl<T: DctNum> BatchedType2And3<T> for CpuBatchedType2And3<T> {
+    fn process_dct2_batch(&self, data: &mut [T]) {
+        let mut scratch = vec![T::zero(); self.inner.get_scratch_len()];
+        for_each_chunk_dct2(self.inner.as_ref(), data, &mut scratch);
+    }
+    fn process_dct3_batch(&self, data: &mut [T]) {
+        let mut scratch = vec![T::zero(); self.inner.get_scratch_len()];
+        for_each_chunk_dct3(self.inner.as_ref(), data, &mut scratch);
+    }
+}
+impl<T> Length for CpuBatchedType2And3<T> {
+    fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::test_utils::{compare_float_vectors, random_signal};
+
+    #[test]
+    fn test_cpu_backend() {
+        let mut planner = DctPlanner::new();
+        // a butterfly, a split radix, and a size computed with Type2And3ConvertToFft
+        for &len in &[8, 32, 12] {
+            let dct = planner.plan_dct2_batch(len);
+            assert_eq!(dct.len(), len);
+
+            let mut expected = random_signal(len * 10);
+            let mut actual = expected.clone();
+            let reference = planner.plan_dct2(len);
+            for chunk in expected.chunks_mut(len) {
+                reference.process_dct2(chunk);
+            }
+            dct.process_dct2_batch(&mut actual);
+            assert!(compare_float_vectors(&expected, &actual), "len = {}", len);
+
+            for chunk in expected.chunks_mut(len) {
+                reference.process_dct3(chunk);
+            }
+            dct.process_dct3_batch(&mut actual);
+            assert!(compare_float_vectors(&expected, &actual), "len = {}", len);
+        }
+    }
+}
@@ -0,0 +1,304 @@
+//! Helpers for computing the same transform on many consecutive signals stored in a single buffer.
+//!
+//! Each `for_each_chunk_*` function splits `data` into chunks of length `plan.len()`, and transforms every chunk
+//! in-place, reusing the same scratch buffer. The length of `data` and the size of `scratch` are validated once up front,
+//! so that batch loops don't have to repeat this bookkeeping in user code.
+//!
+//! The `process_*_rows` functions do the same for the rows of a row-major matrix, and also check that the rows have the
+//! length of the transform.
+//!
+//! ~~~
+//! // Computes a DCT Type 2 of size 8 on each of 16 consecutive signals
+//! use rustdct::batch::for_each_chunk_dct2;
+//! use rustdct::{DctPlanner, RequiredScratch};
+//!
+//! let mut planner = DctPlanner::new();
+//! let dct = planner.plan_dct2(8);
+//!
+//! let mut data = vec![0f32; 8 * 16];
+//! let mut scratch = vec![0f32; dct.get_scratch_len()];
+//! for_each_chunk_dct2(dct.as_ref(), &mut data, &mut scratch);
+//! ~~~
+
+use crate::{Dct1, Dct2, Dct3, Dct4, Dct5, Dct6, Dct7, Dct8, DctNum};
+use crate::{Dst1, Dst2, Dst3, Dst4, Dst5, Dst6, Dst7, Dst8};
+
+// Verifies that `data` contains a whole number of chunks and that enough scratch was provided, then returns the sliced scratch
+fn validate_batch<'a, T>(
+    data: &[T],
+    chunk_len: usize,
+    scratch: &'a mut [T],
+    scratch_len: usize,
+) -> &'a mut [T] {
+    assert!(
+        chunk_len > 0 && data.chunks_exact(chunk_len).remainder().is_empty(),
+        "Provided data must be a multiple of the transform size. Transform len = {}, got data len = {}",
+        chunk_len,
+        data.len()
+    );
+    assert!(
+        scratch.len() >= scratch_len,
+        "Not enough scratch space was provided. Expected scratch len >= {}, got scratch len = {}",
+        scratch_len,
+        scratch.len()
+    );
+    &mut scratch[..scratch_len]
+}
+
+// Verifies that the rows of a matrix have the length of the transform
+pub(crate) fn validate_row_len(row_len: usize, transform_len: usize) {
+    assert_eq!(
+        row_len, transform_len,
+        "Provided row length must be equal to the transform size. Transform len = {}, got row len = {}",
+        transform_len, row_len
+    );
+}
+
+macro_rules! for_each_chunk_fn {
+    ($fn_name:ident, $rows_fn_name:ident, $trait_name:ident, $process_fn:ident, $summary:literal, $rows_summary:literal) => {
+        #[doc = $summary]
+        ///
+        /// The chunk size is `plan.len()`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `data.len()` isn't a multiple of `plan.len()`, or if `scratch.len()` is less than `plan.get_scratch_len()`.
+        pub fn $fn_name<T: DctNum, P: $trait_name<T> + ?Sized>(
+            plan: &P,
+            data: &mut [T],
+            scratch: &mut [T],
+        ) {
+            let scratch = validate_batch(data, plan.len(), scratch, plan.get_scratch_len());
+
+            for chunk in data.chunks_exact_mut(plan.len()) {
+                plan.$process_fn(chunk, scratch);
+            }
+        }
+
+        #[doc = $rows_summary]
+        ///
+        /// `matrix` is stored in row-major order, with rows of length `row_len`, which must be equal to `plan.len()`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `row_len` isn't equal to `plan.len()`, if `matrix.len()` isn't a multiple of `row_len`, or if
+        /// `scratch.len()` is less than `plan.get_scratch_len()`.
+        pub fn $rows_fn_name<T: DctNum, P: $trait_name<T> + ?Sized>(
+            plan: &P,
+            matrix: &mut [T],
+            row_len: usize,
+            scratch: &mut [T],
+        ) {
+            validate_row_len(row_len, plan.len());
+            $fn_name(plan, matrix, scratch);
+        }
+    };
+}
+
+for_each_chunk_fn!(
+    for_each_chunk_dct1,
+    process_dct1_rows,
+    Dct1,
+    process_dct1_with_scratch,
+    "Computes the DCT Type 1 on each consecutive chunk of `data`, in-place, using `scratch` as scratch space.",
+    "Computes the DCT Type 1 on each row of `matrix`, in-place, using `scratch` as scratch space."
+);
+for_each_chunk_fn!(
+    for_each_chunk_dct2,
+    process_dct2_rows,
+    Dct2,
+    process_dct2_with_scratch,
+    "Computes the DCT Type 2 on each consecutive chunk of `data`, in-place, using `scratch` as scratch space.",
+    "Computes the DCT Type 2 on each row of `matrix`, in-place, using `scratch` as scratch space."
+);
+for_each_chunk_fn!(
+    for_each_chunk_dct3,
+    process_dct3_rows,
+    Dct3,
+    process_dct3_with_scratch,
+    "Computes the DCT Type 3 on each consecutive chunk of `data`, in-place, using `scratch` as scratch space.",
+    "Computes the DCT Type 3 on each row of `matrix`, in-place, using `scratch` as scratch space."
+);
+for_each_chunk_fn!(
+    for_each_chunk_dct4,
+    process_dct4_rows,
+    Dct4,
+    process_dct4_with_scratch,
+    "Computes the DCT Type 4 on each consecutive chunk of `data`, in-place, using `scratch` as scratch space.",
+    "Computes the DCT Type 4 on each row of `matrix`, in-place, using `scratch` as scratch space."
+);
+for_each_chunk_fn!(
+    for_each_chunk_dct5,
+    process_dct5_rows,
+    Dct5,
+    process_dct5_with_scratch,
+    "Computes the DCT Type 5 on each consecutive chunk of `data`, in-place, using `scratch` as scratch space.",
+    "Computes the DCT Type 5 on each row of `matrix`, in-place, using `scratch` as scratch space."
+);
+for_each_chunk_fn!(
+    for_each_chunk_dct6,
+    process_dct6_rows,
+    Dct6,
+    process_dct6_with_scratch,
+    "Computes the DCT Type 6 on each consecutive chunk of `data`, in-place, using `scratch` as scratch space.",
+    "Computes the DCT Type 6 on each row of `matrix`, in-place, using `scratch` as scratch space."
+);
+for_each_chunk_fn!(
+    for_each_chunk_dct7,
+    process_dct7_rows,
+    Dct7,
+    process_dct7_with_scratch,
+    "Computes the DCT Type 7 on each consecutive chunk of `data`, in-place, using `scratch` as scratch space.",
+    "Computes the DCT Type 7 on each row of `matrix`, in-place, using `scratch` as scratch space."
+);
+for_each_chunk_fn!(
+    for_each_chunk_dct8,
+    process_dct8_rows,
+    Dct8,
+    process_dct8_with_scratch,
+    "Computes the DCT Type 8 on each consecutive chunk of `data`, in-place, using `scratch` as scratch space.",
+    "Computes the DCT Type 8 on each row of `matrix`, in-place, using `scratch` as scratch space."
+);
+
+for_each_chunk_fn!(
+    for_each_chunk_dst1,
+    process_dst1_rows,
+    Dst1,
+    process_dst1_with_scratch,
+    "Computes the DST Type 1 on each consecutive chunk of `data`, in-place, using `scratch` as scratch space.",
+    "Computes the DST Type 1 on each row of `matrix`, in-place, using `scratch` as scratch space."
+);
+for_each_chunk_fn!(
+    for_each_chunk_dst2,
+    process_dst2_rows,
+    Dst2,
+    process_dst2_with_scratch,
+    "Computes the DST Type 2 on each consecutive chunk of `data`, in-place, using `scratch` as scratch space.",
+    "Computes the DST Type 2 on each row of `matrix`, in-place, using `scratch` as scratch space."
+);
+for_each_chunk_fn!(
+    for_each_chunk_dst3,
+    process_dst3_rows,
+    Dst3,
+    process_dst3_with_scratch,
+    "Computes the DST Type 3 on each consecutive chunk of `data`, in-place, using `scratch` as scratch space.",
+    "Computes the DST Type 3 on each row of `matrix`, in-place, using `scratch` as scratch space."
+);
+for_each_chunk_fn!(
+    for_each_chunk_dst4,
+    process_dst4_rows,
+    Dst4,
+    process_dst4_with_scratch,
+    "Computes the DST Type 4 on each consecutive chunk of `data`, in-place, using `scratch` as scratch space.",
+    "Computes the DST Type 4 on each row of `matrix`, in-place, using `scratch` as scratch space."
+);
+for_each_chunk_fn!(
+    for_each_chunk_dst5,
+    process_dst5_rows,
+    Dst5,
+    process_dst5_with_scratch,
+    "Computes the DST Type 5 on each consecutive chunk of `data`, in-place, using `scratch` as scratch space.",
+    "Computes the DST Type 5 on each row of `matrix`, in-place, using `scratch` as scratch space."
+);
+for_each_chunk_fn!(
+    for_each_chunk_dst6,
+    process_dst6_rows,
+    Dst6,
+    process_dst6_with_scratch,
+    "Computes the DST Type 6 on each consecutive chunk of `data`, in-place, using `scratch` as scratch space.",
+    "Computes the DST Type 6 on each row of `matrix`, in-place, using `scratch` as scratch space."
+);
+for_each_chunk_fn!(
+    for_each_chunk_dst7,
+    process_dst7_rows,
+    Dst7,
+    process_dst7_with_scratch,
+    "Computes the DST Type 7 on each consecutive chunk of `data`, in-place, using `scratch` as scratch space.",
+    "Computes the DST Type 7 on each row of `matrix`, in-place, using `scratch` as scratch space."
+);
+for_each_chunk_fn!(
+    for_each_chunk_dst8,
+    process_dst8_rows,
+    Dst8,
+    process_dst8_with_scratch,
+    "Computes the DST Type 8 on each consecutive chunk of `data`, in-place, using `scratch` as scratch space.",
+    "Computes the DST Type 8 on each row of `matrix`, in-place, using `scratch` as scratch space."
+);
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::algorithm::{Type2And3Naive, Type2And3SplitRadix, Type4Naive};
+    use crate::test_utils::{compare_float_vectors, random_signal};
+    use crate::RequiredScratch;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_for_each_chunk_matches_individual() {
+        let len = 16;
+        let num_chunks = 5;
+        let dct = Type2And3SplitRadix::new(
+            Arc::new(Type2And3Naive::new(len / 2)),
+            Arc::new(Type2And3Naive::new(len / 4)),
+        );
+        let mut scratch = vec![0f32; dct.get_scratch_len()];
+
+        let mut expected = random_signal(len * num_chunks);
+        let mut actual = expected.clone();
+        for chunk in expected.chunks_mut(len) {
+            dct.process_dct2(chunk);
+        }
+        for_each_chunk_dct2(&dct, &mut actual, &mut scratch);
+        assert!(compare_float_vectors(&expected, &actual));
+
+        // make sure this also works through a trait object
+        let dct4: Box<dyn Dst4<f32>> = Box::new(Type4Naive::new(len));
+        let mut expected = random_signal(len * num_chunks);
+        let mut actual = expected.clone();
+        for chunk in expected.chunks_mut(len) {
+            dct4.process_dst4(chunk);
+        }
+        let mut scratch = vec![0f32; dct4.get_scratch_len()];
+        for_each_chunk_dst4(dct4.as_ref(), &mut actual, &mut scratch);
+        assert!(compare_float_vectors(&expected, &actual));
+    }
+
+    #[test]
+    fn test_process_rows_matches_for_each_chunk() {
+        let dct = Type4Naive::new(12);
+        let mut scratch = vec![0f32; dct.get_scratch_len()];
+
+        let mut expected = random_signal(12 * 7);
+        let mut actual = expected.clone();
+        for_each_chunk_dct4(&dct, &mut expected, &mut scratch);
+        process_dct4_rows(&dct, &mut actual, 12, &mut scratch);
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    #[should_panic(expected = "Provided row length must be equal to the transform size")]
+    fn test_process_rows_wrong_row_len() {
+        let dct = Type2And3Naive::new(8);
+        let mut data = vec![0f32; 32];
+        process_dct2_rows(&dct, &mut data, 16, &mut [0f32; 8]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_for_each_chunk_partial_chunk() {
+        let dct = Type2And3Naive::new(8);
+        let mut data = vec![0f32; 20];
+        for_each_chunk_dct3(&dct, &mut data, &mut []);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_for_each_chunk_small_scratch() {
+        let dct = Type2And3SplitRadix::new(
+            Arc::new(Type2And3Naive::new(4)),
+            Arc::new(Type2And3Naive::new(2)),
+        );
+        let mut data = vec![0f32; 16];
+        for_each_chunk_dct2(&dct, &mut data, &mut []);
+    }
+}
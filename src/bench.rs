@@ -0,0 +1,184 @@
+//! Utilities for measuring how long planned transforms take to run, on stable Rust.
+//!
+//! RustDCT's own benchmarks use the nightly-only `test` crate. This module provides a simpler measurement loop that
+//! works on any compiler RustDCT supports, so that downstream users can compare plans for their own sizes and
+//! hardware. It's only available when the `bench` feature is enabled.
+//!
+//! ~~~
+//! use rustdct::bench::measure_process_time;
+//! use rustdct::DctPlanner;
+//!
+//! let mut planner = DctPlanner::<f32>::new();
+//! let dct2 = planner.plan_dct2(1234);
+//!
+//! let stats = measure_process_time(&dct2, 100);
+//! println!("median time: {:?}", stats.median);
+//! ~~~
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::mdct::Mdct;
+use crate::{
+    Dct1, Dct5, Dct6And7, Dct8, DctNum, Dst1, Dst5, Dst6And7, Dst8, TransformType2And3,
+    TransformType4,
+};
+
+/// A transform that can be timed by [`measure_process_time`](fn.measure_process_time.html).
+///
+/// This is implemented for each of the trait objects returned by `DctPlanner`, and for `Arc`s of them. Umbrella
+/// traits are measured with their first transform: `TransformType2And3` runs the DCT2, `TransformType4` runs the
+/// DCT4, `Dct6And7` runs the DCT6, and `Dst6And7` runs the DST6.
+pub trait Measurable<T> {
+    /// Returns the length of the buffer that `process_for_measurement` expects
+    fn measured_buffer_len(&self) -> usize;
+
+    /// Returns the length of the scratch that `process_for_measurement` expects
+    fn measured_scratch_len(&self) -> usize;
+
+    /// Runs the transform once on `buffer`, using `scratch` as scratch space
+    fn process_for_measurement(&self, buffer: &mut [T], scratch: &mut [T]);
+}
+
+macro_rules! impl_measurable {
+    ($($trait_name:ident => $process_fn:ident),*) => {$(
+        impl<T: DctNum> Measurable<T> for dyn $trait_name<T> {
+            fn measured_buffer_len(&self) -> usize {
+                self.len()
+            }
+            fn measured_scratch_len(&self) -> usize {
+                self.get_scratch_len()
+            }
+            fn process_for_measurement(&self, buffer: &mut [T], scratch: &mut [T]) {
+                self.$process_fn(buffer, scratch)
+            }
+        }
+    )*};
+}
+impl_measurable!(
+    Dct1 => process_dct1_with_scratch,
+    Dst1 => process_dst1_with_scratch,
+    TransformType2And3 => process_dct2_with_scratch,
+    TransformType4 => process_dct4_with_scratch,
+    Dct5 => process_dct5_with_scratch,
+    Dst5 => process_dst5_with_scratch,
+    Dct6And7 => process_dct6_with_scratch,
+    Dst6And7 => process_dst6_with_scratch,
+    Dct8 => process_dct8_with_scratch,
+    Dst8 => process_dst8_with_scratch
+);
+
+impl<T: DctNum> Measurable<T> for dyn Mdct<T> {
+    // The buffer holds both halves of the input, followed by the output
+    fn measured_buffer_len(&self) -> usize {
+        self.len() * 3
+    }
+    fn measured_scratch_len(&self) -> usize {
+        self.get_scratch_len()
+    }
+    fn process_for_measurement(&self, buffer: &mut [T], scratch: &mut [T]) {
+        let (input, output) = buffer.split_at_mut(self.len() * 2);
+        let (input_a, input_b) = input.split_at(self.len());
+        self.process_mdct_with_scratch(input_a, input_b, output, scratch)
+    }
+}
+
+impl<T, M: Measurable<T> + ?Sized> Measurable<T> for Arc<M> {
+    fn measured_buffer_len(&self) -> usize {
+        (**self).measured_buffer_len()
+    }
+    fn measured_scratch_len(&self) -> usize {
+        (**self).measured_scratch_len()
+    }
+    fn process_for_measurement(&self, buffer: &mut [T], scratch: &mut [T]) {
+        (**self).process_for_measurement(buffer, scratch)
+    }
+}
+
+/// Statistics about the time taken by each run of a transform, returned by
+/// [`measure_process_time`](fn.measure_process_time.html).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ProcessTimeStats {
+    /// The number of timed runs
+    pub iterations: usize,
+    /// The fastest run
+    pub min: Duration,
+    /// The median run. This is usually the most stable statistic to compare between plans.
+    pub median: Duration,
+    /// The mean of all runs
+    pub mean: Duration,
+    /// The slowest run
+    pub max: Duration,
+}
+
+/// Runs `plan` on the same input `iterations` times, and returns statistics about how long each run took.
+///
+/// Every run starts from the same input, so the timing doesn't depend on how the data changes from one run to the
+/// next, and the scratch buffer is allocated once up front. One extra untimed run is done first, to warm up the cache.
+///
+/// Each run is timed individually with `std::time::Instant`, so for very small transforms the results include some
+/// timer overhead. Prefer comparing the `min` or `median` of two plans measured the same way, over comparing
+/// absolute numbers.
+///
+/// # Panics
+///
+/// Panics if `iterations` is 0.
+pub fn measure_process_time<T: DctNum, M: Measurable<T> + ?Sized>(
+    plan: &M,
+    iterations: usize,
+) -> ProcessTimeStats {
+    assert!(
+        iterations > 0,
+        "measure_process_time requires at least one iteration"
+    );
+
+    // Use a deterministic signal with values in [-1, 1]
+    let input: Vec<T> = (0..plan.measured_buffer_len())
+        .map(|i| T::from_f64((i % 17) as f64 / 8.0 - 1.0).unwrap())
+        .collect();
+    let mut buffer = input.clone();
+    let mut scratch = vec![T::zero(); plan.measured_scratch_len()];
+
+    plan.process_for_measurement(&mut buffer, &mut scratch);
+
+    let mut times = Vec::with_capacity(iterations);
+    for _ in 0..iterations {
+        buffer.copy_from_slice(&input);
+
+        let start = Instant::now();
+        plan.process_for_measurement(&mut buffer, &mut scratch);
+        times.push(start.elapsed());
+    }
+
+    times.sort();
+    let total: Duration = times.iter().sum();
+
+    ProcessTimeStats {
+        iterations,
+        min: times[0],
+        median: times[iterations / 2],
+        mean: total / iterations as u32,
+        max: times[iterations - 1],
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::mdct::window_fn;
+    use crate::DctPlanner;
+
+    #[test]
+    fn test_measure_process_time() {
+        let mut planner = DctPlanner::<f32>::new();
+
+        let stats = measure_process_time(&planner.plan_dct2(16), 10);
+        assert_eq!(stats.iterations, 10);
+        assert!(stats.min <= stats.median && stats.median <= stats.max);
+        assert!(stats.min <= stats.mean && stats.mean <= stats.max);
+
+        measure_process_time(&planner.plan_dct1(7), 1);
+        measure_process_time(&planner.plan_dst6(9), 1);
+        measure_process_time(&planner.plan_mdct(8, window_fn::mp3), 1);
+    }
+}
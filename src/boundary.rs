@@ -0,0 +1,101 @@
+use crate::DctType;
+
+/// How a signal is implicitly extended past one of its ends.
+///
+/// Every DCT and DST type treats its input as one period of a longer signal that's symmetric or antisymmetric around
+/// each end, so choosing a transform is the same as choosing the boundary condition at each end. `DctType::from_boundaries`
+/// maps a pair of boundary conditions to the transform type that implies them.
+///
+/// The symmetry is either around a sample ("whole-sample"), in which case the boundary sample isn't repeated, or
+/// halfway between two samples ("half-sample"), in which case the boundary sample is repeated. For example, the
+/// signal `a b c` extended to the left would be:
+///
+/// - `WholeSampleSymmetric`: `... c b | a b c`
+/// - `HalfSampleSymmetric`: `... b a | a b c`
+/// - `WholeSampleAntisymmetric`: `... -b 0 | a b c`, where the implied zero is one sample before `a`
+/// - `HalfSampleAntisymmetric`: `... -b -a | a b c`
+///
+/// These correspond to scipy's `reflect`, `mirror`, and `symmetric` style boundary modes, and to the boundary
+/// conditions of Neumann (symmetric) and Dirichlet (antisymmetric) problems.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BoundaryMode {
+    /// Even symmetry around the boundary sample
+    WholeSampleSymmetric,
+    /// Even symmetry around the point halfway between the boundary sample and the next sample outside the signal
+    HalfSampleSymmetric,
+    /// Odd symmetry around an implied zero sample just outside the signal
+    WholeSampleAntisymmetric,
+    /// Odd symmetry around the point halfway between the boundary sample and the next sample outside the signal
+    HalfSampleAntisymmetric,
+}
+
+// Each transform type, with the boundary conditions at the start and at the end of its input
+const BOUNDARY_TABLE: [(DctType, BoundaryMode, BoundaryMode); 16] = {
+    use BoundaryMode::*;
+    [
+        (DctType::Dct1, WholeSampleSymmetric, WholeSampleSymmetric),
+        (DctType::Dct2, HalfSampleSymmetric, HalfSampleSymmetric),
+        (
+            DctType::Dct3,
+            WholeSampleSymmetric,
+            WholeSampleAntisymmetric,
+        ),
+        (DctType::Dct4, HalfSampleSymmetric, HalfSampleAntisymmetric),
+        (DctType::Dct5, WholeSampleSymmetric, HalfSampleSymmetric),
+        (DctType::Dct6, HalfSampleSymmetric, WholeSampleSymmetric),
+        (DctType::Dct7, WholeSampleSymmetric, HalfSampleAntisymmetric),
+        (DctType::Dct8, HalfSampleSymmetric, WholeSampleAntisymmetric),
+        (
+            DctType::Dst1,
+            WholeSampleAntisymmetric,
+            WholeSampleAntisymmetric,
+        ),
+        (
+            DctType::Dst2,
+            HalfSampleAntisymmetric,
+            HalfSampleAntisymmetric,
+        ),
+        (
+            DctType::Dst3,
+            WholeSampleAntisymmetric,
+            WholeSampleSymmetric,
+        ),
+        (DctType::Dst4, HalfSampleAntisymmetric, HalfSampleSymmetric),
+        (
+            DctType::Dst5,
+            WholeSampleAntisymmetric,
+            HalfSampleAntisymmetric,
+        ),
+        (
+            DctType::Dst6,
+            HalfSampleAntisymmetric,
+            WholeSampleAntisymmetric,
+        ),
+        (DctType::Dst7, WholeSampleAntisymmetric, HalfSampleSymmetric),
+        (DctType::Dst8, HalfSampleAntisymmetric, WholeSampleSymmetric),
+    ]
+};
+
+impl DctType {
+    /// Returns the transform type whose input is implicitly extended with the `start` boundary condition before its
+    /// first sample, and the `end` boundary condition after its last sample.
+    ///
+    /// Every combination of boundary conditions corresponds to exactly one of the 16 transform types.
+    pub fn from_boundaries(start: BoundaryMode, end: BoundaryMode) -> Self {
+        BOUNDARY_TABLE
+            .iter()
+            .find(|(_, entry_start, entry_end)| *entry_start == start && *entry_end == end)
+            .map(|(kind, _, _)| *kind)
+            .unwrap()
+    }
+
+    /// Returns the boundary conditions that this transform type implies at the start and at the end of its input.
+    /// This is the inverse of `from_boundaries`.
+    pub fn boundaries(self) -> (BoundaryMode, BoundaryMode) {
+        BOUNDARY_TABLE
+            .iter()
+            .find(|(kind, _, _)| *kind == self)
+            .map(|(_, start, end)| (*start, *end))
+            .unwrap()
+    }
+}
@@ -0,0 +1,243 @@
+use rustfft::Length;
+
+use crate::scaling::scaling_factor;
+use crate::{
+    DctBuffers, DctNum, DctPlanner, DctTransform, DctType, RequiredScratch, ScratchLayout,
+};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+
+/// Describes a transform to plan: its type, its size, and the scale to apply to its output.
+///
+/// `Transform` is a shorthand for the planner's `plan_*` methods, for code that only needs to run a transform on a
+/// buffer. Its [`plan`](#method.plan) method plans the transform with a [`DctPlanner`](struct.DctPlanner.html), and
+/// returns a [`PlannedTransform`](struct.PlannedTransform.html), which computes it with a single `run` method, whatever
+/// the transform type.
+///
+/// ~~~
+/// // Computes a DCT Type 2 of size 1234, and then inverts it
+/// use rustdct::prelude::*;
+///
+/// let len = 1234;
+/// let mut planner = DctPlanner::new();
+/// let dct = Transform::dct2(len).plan(&mut planner);
+/// let idct = Transform::dct2(len).inverse().plan(&mut planner);
+/// assert_eq!(idct.kind(), DctType::Dct3);
+///
+/// let mut buffer = vec![1f32; len];
+/// dct.run(&mut buffer);
+/// idct.run(&mut buffer);
+/// ~~~
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transform {
+    kind: DctType,
+    len: usize,
+    scale: Option<f64>,
+}
+
+macro_rules! transform_constructors {
+    ($(($fn_name:ident, $kind:ident, $description:expr)),*) => {
+        $(
+            #[doc = $description]
+            pub fn $fn_name(len: usize) -> Self {
+                Self::new(DctType::$kind, len)
+            }
+        )*
+    };
+}
+
+impl Transform {
+    /// Describes a transform of type `kind` and size `len`, whose output isn't scaled.
+    pub fn new(kind: DctType, len: usize) -> Self {
+        Self {
+            kind,
+            len,
+            scale: None,
+        }
+    }
+
+    transform_constructors!(
+        (dct1, Dct1, "Describes a DCT Type 1 of size `len`."),
+        (dct2, Dct2, "Describes a DCT Type 2 of size `len`."),
+        (dct3, Dct3, "Describes a DCT Type 3 of size `len`."),
+        (dct4, Dct4, "Describes a DCT Type 4 of size `len`."),
+        (dct5, Dct5, "Describes a DCT Type 5 of size `len`."),
+        (dct6, Dct6, "Describes a DCT Type 6 of size `len`."),
+        (dct7, Dct7, "Describes a DCT Type 7 of size `len`."),
+        (dct8, Dct8, "Describes a DCT Type 8 of size `len`."),
+        (dst1, Dst1, "Describes a DST Type 1 of size `len`."),
+        (dst2, Dst2, "Describes a DST Type 2 of size `len`."),
+        (dst3, Dst3, "Describes a DST Type 3 of size `len`."),
+        (dst4, Dst4, "Describes a DST Type 4 of size `len`."),
+        (dst5, Dst5, "Describes a DST Type 5 of size `len`."),
+        (dst6, Dst6, "Describes a DST Type 6 of size `len`."),
+        (dst7, Dst7, "Describes a DST Type 7 of size `len`."),
+        (dst8, Dst8, "Describes a DST Type 8 of size `len`.")
+    );
+
+    /// Multiplies the output of the transform by `scale`, in addition to any scale that's already applied.
+    #[must_use]
+    pub fn scaled(mut self, scale: f64) -> Self {
+        self.scale = Some(self.scale.unwrap_or(1.0) * scale);
+        self
+    }
+
+    /// Describes the transform that inverts this one: Its type is `kind().inverse()`, and its output is scaled by
+    /// [`scaling_factor`](scaling/fn.scaling_factor.html), so that running this transform and then the returned one
+    /// recovers the original input. A scale that's already applied is inverted too.
+    #[must_use]
+    pub fn inverse(self) -> Self {
+        let scale = scaling_factor::<f64>(self.kind, self.len) / self.scale.unwrap_or(1.0);
+        Self {
+            kind: self.kind.inverse(),
+            len: self.len,
+            scale: Some(scale),
+        }
+    }
+
+    /// Returns the type of the transform.
+    pub fn kind(&self) -> DctType {
+        self.kind
+    }
+
+    /// Returns the size of the transform.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the size of the transform is 0.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Plans the transform with `planner`. The inner instance is the same one that the planner's `plan_*` method for
+    /// this type returns, so it's shared with every other plan of the same type and size.
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as the planner's `plan_*` method for this type, like a DCT Type 1 of size 1.
+    pub fn plan<T: DctNum>(self, planner: &mut DctPlanner<T>) -> PlannedTransform<T> {
+        PlannedTransform {
+            plan: planner.plan_boxed(self.kind, self.len),
+            kind: self.kind,
+            scale: self.scale.map(|scale| T::from_f64(scale).unwrap()),
+        }
+    }
+}
+
+/// A transform planned by [`Transform::plan`](struct.Transform.html#method.plan), which computes it with `run`.
+pub struct PlannedTransform<T> {
+    plan: Box<dyn DctTransform<T>>,
+    kind: DctType,
+    scale: Option<T>,
+}
+
+impl<T: DctNum> PlannedTransform<T> {
+    /// Returns the type of the transform that this plan computes.
+    pub fn kind(&self) -> DctType {
+        self.kind
+    }
+
+    /// Returns the factor that this plan multiplies the output of its transform by, or None if it isn't scaled.
+    pub fn scale(&self) -> Option<T> {
+        self.scale
+    }
+
+    /// Computes the transform on the provided buffer, in-place, and applies the scale.
+    ///
+    /// This method allocates a Vec<T> of scratch space. If you'd like to reuse that allocation between
+    /// multiple computations, consider calling `run_with_scratch` instead.
+    pub fn run(&self, buffer: &mut [T]) {
+        let mut scratch = vec![T::zero(); self.get_scratch_len()];
+        self.run_with_scratch(buffer, &mut scratch);
+    }
+
+    /// Computes the transform on the provided buffer, in-place, and applies the scale.
+    /// Uses the provided `scratch` buffer as scratch space.
+    pub fn run_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        self.plan.execute(DctBuffers::InPlace {
+            kind: self.kind,
+            buffer: &mut *buffer,
+            scratch,
+        });
+        if let Some(scale) = self.scale {
+            for element in buffer.iter_mut() {
+                *element = *element * scale;
+            }
+        }
+    }
+}
+impl<T: DctNum> MemoryUsage for PlannedTransform<T> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        self.plan.memory_usage_with_visitor(visitor)
+    }
+}
+impl<T: DctNum> RequiredScratch for PlannedTransform<T> {
+    fn get_scratch_len(&self) -> usize {
+        self.plan.get_scratch_len()
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        self.plan.get_complex_scratch_len()
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        self.plan.scratch_layout()
+    }
+}
+impl<T: DctNum> Length for PlannedTransform<T> {
+    fn len(&self) -> usize {
+        self.plan.transform_len()
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::test_utils::{compare_float_vectors, random_signal};
+
+    #[test]
+    fn test_planned_transform() {
+        let len = 21;
+        let mut planner = DctPlanner::new();
+        let input = random_signal(len);
+
+        // each type computes the same outputs as the planner's own instance
+        let mut expected = input.clone();
+        planner.plan_dst1(len).process_dst1(&mut expected);
+        let mut actual = input.clone();
+        Transform::dst1(len).plan(&mut planner).run(&mut actual);
+        assert_eq!(expected, actual);
+
+        let mut expected = input.clone();
+        planner.plan_dct7(len).process_dct7(&mut expected);
+        for value in expected.iter_mut() {
+            *value *= 3.0;
+        }
+        let mut actual = input.clone();
+        let dct7 = Transform::new(DctType::Dct7, len)
+            .scaled(1.5)
+            .scaled(2.0)
+            .plan(&mut planner);
+        assert_eq!(dct7.scale(), Some(3.0));
+        dct7.run(&mut actual);
+        assert!(compare_float_vectors(&expected, &actual));
+
+        // the inverse recovers the input, even if the forward transform is scaled
+        for &kind in &[DctType::Dct2, DctType::Dct6, DctType::Dst5] {
+            let forward = Transform::new(kind, len).scaled(0.25);
+            let inverse = forward.inverse();
+            assert_eq!(inverse.kind(), kind.inverse());
+
+            let mut buffer = input.clone();
+            forward.plan(&mut planner).run(&mut buffer);
+            inverse.plan(&mut planner).run(&mut buffer);
+            assert!(compare_float_vectors(&input, &buffer), "{:?}", kind);
+        }
+
+        let dct2 = Transform::dct2(len).plan(&mut planner);
+        assert_eq!(dct2.len(), len);
+        assert_eq!(dct2.scale(), None);
+        assert_eq!(
+            dct2.get_scratch_len(),
+            planner.plan_dct2(len).get_scratch_len()
+        );
+    }
+}
@@ -0,0 +1,228 @@
+use std::sync::Arc;
+
+use rustfft::Length;
+
+use crate::mdct::Mdct;
+use crate::scaling::scaling_factor;
+use crate::ScratchLayout;
+use crate::{Dct8, DctNum, DctType, Dst6And7, RequiredScratch, TransformType2And3};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+
+/// The transforms that a codec typically needs at one block size: A DCT2, a DST7, a DCT8, and a MDCT.
+///
+/// Returned by [`DctPlanner::plan_codec_block`](struct.DctPlanner.html#method.plan_codec_block). Every transform comes
+/// from the same planner, so they share inner transforms and FFTs wherever their algorithms allow it, and each instance
+/// is the same one that the planner's individual `plan_*` methods return for this size.
+///
+/// None of the transforms normalize their outputs. Each inverse is computed by the same instance as its forward
+/// transform, and the inverse's output needs to be multiplied by the scale returned by the matching `*_inverse_scale`
+/// method to recover the input:
+///
+/// - The DCT2 is inverted by the DCT3, with [`dct2_inverse_scale`](#method.dct2_inverse_scale), which is `2 / len`.
+/// - The DST7 is inverted by the DST6, with [`dst7_inverse_scale`](#method.dst7_inverse_scale), which is
+///   `2 / (len + 0.5)`.
+/// - The DCT8 is its own inverse, with [`dct8_inverse_scale`](#method.dct8_inverse_scale), which is `2 / (len + 0.5)`.
+/// - The MDCT is inverted by overlap-adding the IMDCTs of consecutive frames. The gain of the round trip depends on the
+///   window: It's 1 for the `_invertible` windows in [`window_fn`](mdct/window_fn/index.html).
+///
+/// ~~~
+/// // Plans the transforms for a codec with 64-sample blocks, and round-trips a block through the DST7
+/// use rustdct::mdct::window_fn;
+/// use rustdct::DctPlanner;
+///
+/// let mut planner = DctPlanner::new();
+/// let block = planner.plan_codec_block(64, window_fn::vorbis_invertible);
+///
+/// let mut buffer = vec![1f32; 64];
+/// block.dst7().process_dst7(&mut buffer);
+/// block.dst7().process_dst6(&mut buffer);
+/// for value in buffer.iter_mut() {
+///     *value = *value * block.dst7_inverse_scale();
+/// }
+/// assert!((buffer[10] - 1.0).abs() < 1e-4);
+/// ~~~
+pub struct CodecBlock<T> {
+    dct2: Arc<dyn TransformType2And3<T>>,
+    dst7: Arc<dyn Dst6And7<T>>,
+    dct8: Arc<dyn Dct8<T>>,
+    mdct: Arc<dyn Mdct<T>>,
+}
+
+impl<T: DctNum> CodecBlock<T> {
+    /// Creates a new bundle from its transforms, which must all have the same length.
+    pub fn new(
+        dct2: Arc<dyn TransformType2And3<T>>,
+        dst7: Arc<dyn Dst6And7<T>>,
+        dct8: Arc<dyn Dct8<T>>,
+        mdct: Arc<dyn Mdct<T>>,
+    ) -> Self {
+        let len = dct2.len();
+        assert!(
+            dst7.len() == len && dct8.len() == len && mdct.len() == len,
+            "Every transform in a codec block must have the same length. Got DCT2 len = {}, DST7 len = {}, DCT8 len = {}, MDCT len = {}",
+            len,
+            dst7.len(),
+            dct8.len(),
+            mdct.len()
+        );
+        Self {
+            dct2,
+            dst7,
+            dct8,
+            mdct,
+        }
+    }
+
+    /// Returns the DCT2 instance, which also computes the DCT3
+    pub fn dct2(&self) -> &Arc<dyn TransformType2And3<T>> {
+        &self.dct2
+    }
+
+    /// Returns the DST7 instance, which also computes the DST6
+    pub fn dst7(&self) -> &Arc<dyn Dst6And7<T>> {
+        &self.dst7
+    }
+
+    /// Returns the DCT8 instance
+    pub fn dct8(&self) -> &Arc<dyn Dct8<T>> {
+        &self.dct8
+    }
+
+    /// Returns the MDCT instance, which produces `len` outputs from `2 * len` inputs
+    pub fn mdct(&self) -> &Arc<dyn Mdct<T>> {
+        &self.mdct
+    }
+
+    /// Returns the factor that the DCT3's output needs to be multiplied by to invert the DCT2
+    pub fn dct2_inverse_scale(&self) -> T {
+        scaling_factor(DctType::Dct2, self.len())
+    }
+
+    /// Returns the factor that the DST6's output needs to be multiplied by to invert the DST7
+    pub fn dst7_inverse_scale(&self) -> T {
+        scaling_factor(DctType::Dst7, self.len())
+    }
+
+    /// Returns the factor that the DCT8's output needs to be multiplied by to invert another DCT8
+    pub fn dct8_inverse_scale(&self) -> T {
+        scaling_factor(DctType::Dct8, self.len())
+    }
+}
+impl<T> Length for CodecBlock<T> {
+    fn len(&self) -> usize {
+        self.dct2.len()
+    }
+}
+impl<T: DctNum> MemoryUsage for CodecBlock<T> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            inner_plans_bytes: visitor.inner_plan(&self.dct2)
+                + visitor.inner_plan(&self.dst7)
+                + visitor.inner_plan(&self.dct8)
+                + visitor.inner_plan(&self.mdct),
+            ..PlanMemory::default()
+        }
+    }
+}
+impl<T> RequiredScratch for CodecBlock<T> {
+    /// Returns the largest scratch length of the four transforms, so that one scratch buffer can be used for all of them
+    fn get_scratch_len(&self) -> usize {
+        self.dct2
+            .get_scratch_len()
+            .max(self.dst7.get_scratch_len())
+            .max(self.dct8.get_scratch_len())
+            .max(self.mdct.get_scratch_len())
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        self.dct2
+            .get_complex_scratch_len()
+            .max(self.dst7.get_complex_scratch_len())
+            .max(self.dct8.get_complex_scratch_len())
+            .max(self.mdct.get_complex_scratch_len())
+    }
+    /// Returns the layout of whichever transform needs the most scratch
+    fn scratch_layout(&self) -> ScratchLayout {
+        let layouts = [
+            self.dct2.scratch_layout(),
+            self.dst7.scratch_layout(),
+            self.dct8.scratch_layout(),
+            self.mdct.scratch_layout(),
+        ];
+        layouts
+            .iter()
+            .max_by_key(|layout| layout.total_len())
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::mdct::{verify_tdac, window_fn};
+    use crate::test_utils::{compare_float_vectors, random_signal};
+    use crate::DctPlanner;
+
+    #[test]
+    fn test_codec_block_roundtrips() {
+        for &len in &[4, 6, 16, 64, 100] {
+            let mut planner = DctPlanner::new();
+            let block = planner.plan_codec_block(len, window_fn::vorbis_invertible);
+            assert_eq!(block.len(), len);
+            assert!(Arc::ptr_eq(block.dct2(), &planner.plan_dct2(len)));
+
+            let input = random_signal(len);
+            let mut scratch = vec![0f32; block.get_scratch_len()];
+            let rescale = |buffer: &mut [f32], scale: f32| {
+                for value in buffer.iter_mut() {
+                    *value = *value * scale;
+                }
+            };
+
+            let mut buffer = input.clone();
+            block
+                .dct2()
+                .process_dct2_with_scratch(&mut buffer, &mut scratch);
+            block
+                .dct2()
+                .process_dct3_with_scratch(&mut buffer, &mut scratch);
+            rescale(&mut buffer, block.dct2_inverse_scale());
+            assert!(
+                compare_float_vectors(&input, &buffer),
+                "dct2, len = {}",
+                len
+            );
+
+            let mut buffer = input.clone();
+            block
+                .dst7()
+                .process_dst7_with_scratch(&mut buffer, &mut scratch);
+            block
+                .dst7()
+                .process_dst6_with_scratch(&mut buffer, &mut scratch);
+            rescale(&mut buffer, block.dst7_inverse_scale());
+            assert!(
+                compare_float_vectors(&input, &buffer),
+                "dst7, len = {}",
+                len
+            );
+
+            let mut buffer = input.clone();
+            block
+                .dct8()
+                .process_dct8_with_scratch(&mut buffer, &mut scratch);
+            block
+                .dct8()
+                .process_dct8_with_scratch(&mut buffer, &mut scratch);
+            rescale(&mut buffer, block.dct8_inverse_scale());
+            assert!(
+                compare_float_vectors(&input, &buffer),
+                "dct8, len = {}",
+                len
+            );
+
+            let gain = verify_tdac(block.mdct().as_ref(), 1e-3).unwrap();
+            assert!((gain - 1.0).abs() < 1e-3, "mdct, len = {}", len);
+        }
+    }
+}
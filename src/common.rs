@@ -1,10 +1,28 @@
+use std::error::Error;
+use std::fmt;
+
 use rustfft::num_traits::FloatConst;
 use rustfft::FftNum;
 
 /// Generic floating point number
+///
+/// Every type that implements `FftNum` and `FloatConst` automatically implements `DctNum`, so custom numeric types
+/// (like a software float, or a wider-precision float) only need to provide what RustFFT and `FloatConst` require.
+///
+/// The methods below are implemented by that blanket implementation, and can't be overridden. They compute their
+/// constants from the `f64` values with `FromPrimitive::from_f64`. For accurate results, a type's `from_f64` should
+/// return `Some` for every finite input, rounded to the nearest representable value. Types with more precision than
+/// `f64` will still work, but they'll be limited to `f64` precision in these constants and in the twiddle factors,
+/// which are computed the same way.
 pub trait DctNum: FftNum + FloatConst {
+    /// Returns 0.5
     fn half() -> Self;
+    /// Returns 2.0
     fn two() -> Self;
+    /// Returns sqrt(2)
+    fn sqrt_2() -> Self;
+    /// Returns 1 / sqrt(2)
+    fn frac_1_sqrt_2() -> Self;
 }
 
 impl<T: FftNum + FloatConst> DctNum for T {
@@ -14,9 +32,95 @@ impl<T: FftNum + FloatConst> DctNum for T {
     fn two() -> Self {
         Self::from_f64(2.0).unwrap()
     }
+    fn sqrt_2() -> Self {
+        Self::from_f64(std::f64::consts::SQRT_2).unwrap()
+    }
+    fn frac_1_sqrt_2() -> Self {
+        Self::from_f64(std::f64::consts::FRAC_1_SQRT_2).unwrap()
+    }
+}
+
+// The largest scratch length that `with_scratch` places on the stack
+const STACK_SCRATCH_LEN: usize = 64;
+
+// Runs `f` with a zeroed scratch buffer of length `len`. Scratch buffers of up to `STACK_SCRATCH_LEN` elements are
+// placed on the stack, so that the convenience process methods don't allocate when they're called in a hot loop
+#[inline]
+pub fn with_scratch<T: DctNum, R>(len: usize, f: impl FnOnce(&mut [T]) -> R) -> R {
+    if len == 0 {
+        f(&mut [])
+    } else if len <= STACK_SCRATCH_LEN {
+        let mut scratch = [T::zero(); STACK_SCRATCH_LEN];
+        f(&mut scratch[..len])
+    } else {
+        let mut scratch = vec![T::zero(); len];
+        f(&mut scratch)
+    }
+}
+
+// Runs `f` with the first `f64_len` elements of `scratch` viewed as f64s. The mixed-precision algorithms use this to
+// compute in f64 with the caller's f32 scratch, which must have at least `2 * f64_len + 1` elements, since each f64
+// takes up the space of two f32s, and we might need to skip one f32 to align the f64s
+pub fn with_f64_scratch<R>(
+    scratch: &mut [f32],
+    f64_len: usize,
+    f: impl FnOnce(&mut [f64]) -> R,
+) -> R {
+    // Safety: Every bit pattern is a valid f64, so it's fine to view the f32 scratch as f64s
+    let (_, f64_scratch, _) = unsafe { scratch.align_to_mut::<f64>() };
+
+    // `align_to_mut` is allowed to return a shorter middle slice than the maximum. That won't happen in practice,
+    // but if it ever does, fall back to allocating
+    match f64_scratch.get_mut(..f64_len) {
+        Some(f64_scratch) => f(f64_scratch),
+        None => f(&mut vec![0f64; f64_len]),
+    }
+}
+
+/// Returned by the cancellable process methods when the caller's `should_continue` callback asked to stop early.
+///
+/// When a transform is cancelled, the contents of the buffer and scratch are unspecified.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "The transform was cancelled before it completed")
+    }
+}
+impl Error for Cancelled {}
+
+// Whether the process methods check that their buffers have the right length. With the `unchecked-process` feature,
+// release builds skip the check, and a buffer of the wrong length causes a panic while indexing, or an incorrect
+// result. Algorithms whose unchecked indexing relies on the check, like the butterflies, always perform it
+pub const CHECK_BUFFER_LENS: bool = cfg!(debug_assertions) || !cfg!(feature = "unchecked-process");
+
+// Implements the transform trait methods that report an algorithm's `MemoryUsage` and `PlanIdentity<$t>` through
+// trait objects. Every algorithm calls this in each of its transform trait impls
+macro_rules! plan_hooks {
+    ($t:ty) => {
+        fn as_memory_usage(&self) -> Option<&dyn crate::MemoryUsage> {
+            Some(self)
+        }
+        fn known_plan_key(&self) -> Option<crate::PlanKey<$t>> {
+            Some(crate::PlanIdentity::<$t>::plan_key(self))
+        }
+    };
+}
+
+// The `get_complex_scratch_len` of a plan that passes its scratch buffer to an inner plan starting at element `offset`,
+// whose own complex scratch len is `inner_complex_len`: The whole `Complex<T>`s up to the end of the inner plan's
+// complex region. Rounding down keeps this at most half of the outer plan's scratch len
+pub(crate) fn offset_complex_scratch_len(offset: usize, inner_complex_len: usize) -> usize {
+    if inner_complex_len == 0 {
+        0
+    } else {
+        offset / 2 + inner_complex_len
+    }
 }
 
-// Validates the given buffer verifying that it has the correct length.
+// Validates the given buffer verifying that it has the correct length. This is only used by the butterflies, which
+// index the buffer without bounds checks, so it's performed even with the `unchecked-process` feature
 macro_rules! validate_buffer {
     ($buffer: expr,$expected_buffer_len: expr) => {{
         if $buffer.len() != $expected_buffer_len {
@@ -26,17 +130,57 @@ macro_rules! validate_buffer {
     }};
 }
 
+// Validates the given input, output, and scratch of an out-of-place transform by verifying that they have the correct length.
+// Then, slices the scratch down to just the required amount
+macro_rules! validate_buffers_outofplace {
+    ($input: expr, $output: expr, $scratch: expr, $expected_len: expr, $expected_scratch_len: expr) => {{
+        if crate::common::CHECK_BUFFER_LENS
+            && ($input.len() != $expected_len || $output.len() != $expected_len)
+        {
+            dct_error_outofplace(
+                $input.len(),
+                $output.len(),
+                $scratch.len(),
+                $expected_len,
+                $expected_scratch_len,
+            );
+            return;
+        }
+        if let Some(sliced_scratch) = $scratch.get_mut(0..$expected_scratch_len) {
+            sliced_scratch
+        } else {
+            dct_error_outofplace(
+                $input.len(),
+                $output.len(),
+                $scratch.len(),
+                $expected_len,
+                $expected_scratch_len,
+            );
+            return;
+        }
+    }};
+}
+
 // Validates the given buffer and scratch by verifying that they have the correct length. Then, slices the scratch down to just the required amount
+// The optional last argument is the value to return after reporting an error, for methods that don't return `()`
+// With a leading `@always`, the buffer length is checked even with the `unchecked-process` feature, for algorithms that
+// index the buffer without bounds checks. The scratch is always sliced, so its length is always checked
 macro_rules! validate_buffers {
-    ($buffer: expr, $scratch: expr, $expected_buffer_len: expr, $expected_scratch_len: expr) => {{
-        if $buffer.len() != $expected_buffer_len {
+    (@always $buffer: expr, $scratch: expr, $expected_buffer_len: expr, $expected_scratch_len: expr) => {
+        validate_buffers!(@always $buffer, $scratch, $expected_buffer_len, $expected_scratch_len, ())
+    };
+    (@always $buffer: expr, $scratch: expr, $expected_buffer_len: expr, $expected_scratch_len: expr, $error_return: expr) => {
+        validate_buffers!(@check true, $buffer, $scratch, $expected_buffer_len, $expected_scratch_len, $error_return)
+    };
+    (@check $check_len: expr, $buffer: expr, $scratch: expr, $expected_buffer_len: expr, $expected_scratch_len: expr, $error_return: expr) => {{
+        if $check_len && $buffer.len() != $expected_buffer_len {
             dct_error_inplace(
                 $buffer.len(),
                 $scratch.len(),
                 $expected_buffer_len,
                 $expected_scratch_len,
             );
-            return;
+            return $error_return;
         }
         if let Some(sliced_scratch) = $scratch.get_mut(0..$expected_scratch_len) {
             sliced_scratch
@@ -47,17 +191,37 @@ macro_rules! validate_buffers {
                 $expected_buffer_len,
                 $expected_scratch_len,
             );
-            return;
+            return $error_return;
         }
     }};
+    ($buffer: expr, $scratch: expr, $expected_buffer_len: expr, $expected_scratch_len: expr) => {
+        validate_buffers!(
+            $buffer,
+            $scratch,
+            $expected_buffer_len,
+            $expected_scratch_len,
+            ()
+        )
+    };
+    ($buffer: expr, $scratch: expr, $expected_buffer_len: expr, $expected_scratch_len: expr, $error_return: expr) => {
+        validate_buffers!(
+            @check crate::common::CHECK_BUFFER_LENS,
+            $buffer,
+            $scratch,
+            $expected_buffer_len,
+            $expected_scratch_len,
+            $error_return
+        )
+    };
 }
 
 // Validates the given buffer and scratch by verifying that they have the correct length. Then, slices the scratch down to just the required amount
 macro_rules! validate_buffers_mdct {
     ($buffer_a: expr, $buffer_b: expr, $buffer_c: expr, $scratch: expr, $expected_buffer_len: expr, $expected_scratch_len: expr) => {{
-        if $buffer_a.len() != $expected_buffer_len
-            || $buffer_b.len() != $expected_buffer_len
-            || $buffer_c.len() != $expected_buffer_len
+        if crate::common::CHECK_BUFFER_LENS
+            && ($buffer_a.len() != $expected_buffer_len
+                || $buffer_b.len() != $expected_buffer_len
+                || $buffer_c.len() != $expected_buffer_len)
         {
             mdct_error_inplace(
                 $buffer_a.len(),
@@ -85,6 +249,138 @@ macro_rules! validate_buffers_mdct {
     }};
 }
 
+// Validates a frame that's split into two slices, like a frame that wraps around the end of a ring buffer, along with
+// the output and the scratch by verifying that they have the correct length. Then, slices the scratch down to just the
+// required amount
+macro_rules! validate_buffers_ring {
+    ($first: expr, $second: expr, $output: expr, $scratch: expr, $expected_len: expr, $expected_scratch_len: expr) => {{
+        let input_len = $first.len().checked_add($second.len());
+        if crate::common::CHECK_BUFFER_LENS
+            && (input_len != Some($expected_len) || $output.len() != $expected_len)
+        {
+            dct_error_ring(
+                $first.len(),
+                $second.len(),
+                $output.len(),
+                $scratch.len(),
+                $expected_len,
+                $expected_scratch_len,
+            );
+            return;
+        }
+        if let Some(sliced_scratch) = $scratch.get_mut(0..$expected_scratch_len) {
+            sliced_scratch
+        } else {
+            dct_error_ring(
+                $first.len(),
+                $second.len(),
+                $output.len(),
+                $scratch.len(),
+                $expected_len,
+                $expected_scratch_len,
+            );
+            return;
+        }
+    }};
+}
+
+// Validates two buffers that are transformed together, like the two signals of `process_dct2_two`, along with the
+// scratch by verifying that they have the correct length. Then, slices the scratch down to just the required amount
+macro_rules! validate_buffers_two {
+    ($buffer_a: expr, $buffer_b: expr, $scratch: expr, $expected_len: expr, $expected_scratch_len: expr) => {{
+        if crate::common::CHECK_BUFFER_LENS
+            && ($buffer_a.len() != $expected_len || $buffer_b.len() != $expected_len)
+        {
+            dct_error_two(
+                $buffer_a.len(),
+                $buffer_b.len(),
+                $scratch.len(),
+                $expected_len,
+                $expected_scratch_len,
+            );
+            return;
+        }
+        if let Some(sliced_scratch) = $scratch.get_mut(0..$expected_scratch_len) {
+            sliced_scratch
+        } else {
+            dct_error_two(
+                $buffer_a.len(),
+                $buffer_b.len(),
+                $scratch.len(),
+                $expected_len,
+                $expected_scratch_len,
+            );
+            return;
+        }
+    }};
+}
+
+// Validates every channel of a planar buffer and the scratch by verifying that they have the correct length, before any
+// channel is processed. Then, slices the scratch down to just the required amount
+macro_rules! validate_buffers_planar {
+    ($channels: expr, $scratch: expr, $expected_len: expr, $expected_scratch_len: expr) => {{
+        if crate::common::CHECK_BUFFER_LENS {
+            if let Some(index) = $channels
+                .iter()
+                .position(|channel| channel.len() != $expected_len)
+            {
+                dct_error_planar(
+                    Some((index, $channels[index].len())),
+                    $scratch.len(),
+                    $expected_len,
+                    $expected_scratch_len,
+                );
+                return;
+            }
+        }
+        if let Some(sliced_scratch) = $scratch.get_mut(0..$expected_scratch_len) {
+            sliced_scratch
+        } else {
+            dct_error_planar(None, $scratch.len(), $expected_len, $expected_scratch_len);
+            return;
+        }
+    }};
+}
+
+// Computes `len * multiplier + offset`, for lengths derived from a transform size, like the size of a twiddle table or of an inner FFT.
+// Uses checked arithmetic so that huge sizes are rejected with a clear error when the algorithm is created, instead of
+// silently wrapping around in release builds.
+pub fn derived_len(len: usize, multiplier: usize, offset: isize, description: &str) -> usize {
+    let result = len.checked_mul(multiplier).and_then(|product| {
+        if offset >= 0 {
+            product.checked_add(offset as usize)
+        } else {
+            product.checked_sub((-offset) as usize)
+        }
+    });
+
+    match result {
+        Some(result) => result,
+        None => derived_len_error(len, multiplier, offset, description),
+    }
+}
+
+// Marked cold and inline never to keep all formatting code out of the many monomorphized constructors
+#[cold]
+#[inline(never)]
+fn derived_len_error(len: usize, multiplier: usize, offset: isize, description: &str) -> ! {
+    let sign = if offset < 0 { '-' } else { '+' };
+    let formula = format!("{} * {} {} {}", len, multiplier, sign, offset.abs());
+
+    // with a negative offset, the product can fit and still be smaller than the offset
+    if offset < 0 && len.checked_mul(multiplier).is_some() {
+        panic!(
+            "Unsupported transform size {}: size {} is too small for the {} length ({})",
+            len, len, description, formula
+        )
+    } else {
+        panic!(
+            "Unsupported transform size {}: the {} length ({}) doesn't fit in a usize",
+            len, description, formula
+        )
+    }
+}
+
 // Prints an error raised by an in-place FFT algorithm's `process_inplace` method
 // Marked cold and inline never to keep all formatting code out of the many monomorphized process_inplace methods
 #[cold]
@@ -109,6 +405,126 @@ pub fn dct_error_inplace(
     );
 }
 
+// Prints an error raised by an out-of-place process method, like `process_dct2_from`
+// Marked cold and inline never to keep all formatting code out of the many monomorphized process methods
+#[cold]
+#[inline(never)]
+pub fn dct_error_outofplace(
+    actual_input_len: usize,
+    actual_output_len: usize,
+    actual_scratch: usize,
+    expected_len: usize,
+    expected_scratch: usize,
+) {
+    assert!(
+        actual_input_len == expected_len,
+        "Provided input must be equal to the transform size. Expected len = {}, got len = {}",
+        expected_len,
+        actual_input_len
+    );
+    assert!(
+        actual_output_len == expected_len,
+        "Provided output must be equal to the transform size. Expected len = {}, got len = {}",
+        expected_len,
+        actual_output_len
+    );
+    assert!(
+        actual_scratch >= expected_scratch,
+        "Not enough scratch space was provided. Expected scratch len >= {}, got scratch len = {}",
+        expected_scratch,
+        actual_scratch
+    );
+}
+
+// Prints an error raised by a process method that takes its input as two slices, like `process_dct2_ring`
+// Marked cold and inline never to keep all formatting code out of the many monomorphized process methods
+#[cold]
+#[inline(never)]
+pub fn dct_error_ring(
+    actual_first_len: usize,
+    actual_second_len: usize,
+    actual_output_len: usize,
+    actual_scratch: usize,
+    expected_len: usize,
+    expected_scratch: usize,
+) {
+    assert!(
+        actual_first_len.checked_add(actual_second_len) == Some(expected_len),
+        "The two input slices must add up to the transform size. Expected len = {}, got lens {} + {}",
+        expected_len,
+        actual_first_len,
+        actual_second_len
+    );
+    assert!(
+        actual_output_len == expected_len,
+        "Provided output must be equal to the transform size. Expected len = {}, got len = {}",
+        expected_len,
+        actual_output_len
+    );
+    assert!(
+        actual_scratch >= expected_scratch,
+        "Not enough scratch space was provided. Expected scratch len >= {}, got scratch len = {}",
+        expected_scratch,
+        actual_scratch
+    );
+}
+
+// Prints an error raised by a process method that transforms two buffers together, like `process_dct2_two`
+// Marked cold and inline never to keep all formatting code out of the many monomorphized process methods
+#[cold]
+#[inline(never)]
+pub fn dct_error_two(
+    actual_a_len: usize,
+    actual_b_len: usize,
+    actual_scratch: usize,
+    expected_len: usize,
+    expected_scratch: usize,
+) {
+    assert!(
+        actual_a_len == expected_len,
+        "Provided buffer a must be equal to the transform size. Expected len = {}, got len = {}",
+        expected_len,
+        actual_a_len
+    );
+    assert!(
+        actual_b_len == expected_len,
+        "Provided buffer b must be equal to the transform size. Expected len = {}, got len = {}",
+        expected_len,
+        actual_b_len
+    );
+    assert!(
+        actual_scratch >= expected_scratch,
+        "Not enough scratch space was provided. Expected scratch len >= {}, got scratch len = {}",
+        expected_scratch,
+        actual_scratch
+    );
+}
+
+// Prints an error raised by a planar process method, like `process_dct2_planar`. `mismatch` is the index and length of
+// the first channel whose length is wrong, if there is one
+// Marked cold and inline never to keep all formatting code out of the many monomorphized process methods
+#[cold]
+#[inline(never)]
+pub fn dct_error_planar(
+    mismatch: Option<(usize, usize)>,
+    actual_scratch: usize,
+    expected_len: usize,
+    expected_scratch: usize,
+) {
+    if let Some((index, actual_len)) = mismatch {
+        panic!(
+            "Every channel must be equal to the transform size. Expected len = {}, but channel {} was len = {}",
+            expected_len, index, actual_len
+        );
+    }
+    assert!(
+        actual_scratch >= expected_scratch,
+        "Not enough scratch space was provided. Expected scratch len >= {}, got scratch len = {}",
+        expected_scratch,
+        actual_scratch
+    );
+}
+
 // Prints an error raised by an in-place FFT algorithm's `process_inplace` method
 // Marked cold and inline never to keep all formatting code out of the many monomorphized process_inplace methods
 #[cold]
@@ -146,3 +562,26 @@ pub fn mdct_error_inplace(
         actual_scratch
     );
 }
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = " * 2 + 1) doesn't fit in a usize")]
+    fn test_derived_len_overflow() {
+        derived_len(usize::MAX, 2, 1, "scratch");
+    }
+
+    #[test]
+    #[should_panic(expected = "size 0 is too small for the inner DCT length (0 * 2 - 1)")]
+    fn test_derived_len_underflow() {
+        derived_len(0, 2, -1, "inner DCT");
+    }
+
+    #[test]
+    fn test_derived_len() {
+        assert_eq!(derived_len(3, 2, -1, "inner DCT"), 5);
+        assert_eq!(derived_len(3, 4, 2, "twiddle table"), 14);
+    }
+}
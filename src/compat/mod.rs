@@ -0,0 +1,3 @@
+//! Compatibility layers that reproduce the conventions of other DCT libraries, for porting code that uses them
+
+pub mod scipy;
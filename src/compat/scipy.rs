@@ -0,0 +1,320 @@
+//! Functions with the same conventions as `scipy.fft.dct`, `idct`, `dst`, and `idst`, for porting Python code.
+//!
+//! Each function takes the transform type as an integer from 1 to 4 and a [`Norm`](enum.Norm.html) that corresponds to
+//! scipy's `norm` argument, and returns a new `Vec` with the same values that scipy would compute for a 1D input.
+//! The transforms are planned on a planner that's owned by the current thread, so repeated calls with the same type and
+//! size reuse the same plan.
+//!
+//! Unlike the rest of RustDCT, these functions allocate on every call, and they only support `f32` and `f64`. For
+//! performance-critical code, plan the transforms with a [`DctPlanner`](../../struct.DctPlanner.html) instead, and use
+//! [`scaling_factor`](../../scaling/fn.scaling_factor.html) to apply the scale.
+//!
+//! ~~~
+//! // scipy.fft.dct([1.0, 2.0, 3.0, 4.0], type=2, norm="ortho")
+//! use rustdct::compat::scipy::{dct, idct, Norm};
+//!
+//! let input = [1.0, 2.0, 3.0, 4.0];
+//! let output = dct(&input, 2, Norm::Ortho);
+//! assert!((output[0] - 5.0f64).abs() < 1e-12);
+//!
+//! let roundtrip = idct(&output, 2, Norm::Ortho);
+//! assert!((roundtrip[3] - 4.0f64).abs() < 1e-12);
+//! ~~~
+
+use std::cell::RefCell;
+
+use crate::{DctNum, DctPlanner, DctType};
+
+/// The normalization mode of a transform, equivalent to scipy's `norm` argument.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Norm {
+    /// The forward transform isn't scaled, and the inverse transform is scaled by `1 / n`, where `n` is the logical size
+    /// of the transform, like `2 * len` for the DCT2. This is scipy's `norm=None` or `norm="backward"`, and the default.
+    Backward,
+    /// Both directions are scaled so that the transform is orthonormal. This is scipy's `norm="ortho"`, with scipy's
+    /// default of `orthogonalize=True`.
+    Ortho,
+    /// The forward transform is scaled by `1 / n`, and the inverse transform isn't scaled. This is scipy's
+    /// `norm="forward"`.
+    Forward,
+}
+
+/// A float type that has a thread-local planner for the functions in this module. Implemented for `f32` and `f64`.
+pub trait ThreadLocalPlanner: DctNum {
+    /// Calls `f` with this thread's planner for this float type.
+    fn with_planner<R>(f: impl FnOnce(&mut DctPlanner<Self>) -> R) -> R;
+}
+
+macro_rules! thread_local_planner {
+    ($float:ty, $planner:ident) => {
+        thread_local! {
+            static $planner: RefCell<DctPlanner<$float>> = RefCell::new(DctPlanner::new());
+        }
+        impl ThreadLocalPlanner for $float {
+            fn with_planner<R>(f: impl FnOnce(&mut DctPlanner<Self>) -> R) -> R {
+                $planner.with(|planner| f(&mut planner.borrow_mut()))
+            }
+        }
+    };
+}
+thread_local_planner!(f32, F32_PLANNER);
+thread_local_planner!(f64, F64_PLANNER);
+
+/// Computes the DCT of the given type, like `scipy.fft.dct(x, type=kind, norm=norm)`.
+///
+/// # Panics
+///
+/// Panics if `kind` isn't 1, 2, 3, or 4, or if `kind` is 1 and `x` has fewer than 2 elements.
+pub fn dct<T: ThreadLocalPlanner>(x: &[T], kind: usize, norm: Norm) -> Vec<T> {
+    transform(x, dct_type(kind), norm, false)
+}
+
+/// Computes the inverse of the DCT of the given type, like `scipy.fft.idct(x, type=kind, norm=norm)`.
+///
+/// # Panics
+///
+/// Panics if `kind` isn't 1, 2, 3, or 4, or if `kind` is 1 and `x` has fewer than 2 elements.
+pub fn idct<T: ThreadLocalPlanner>(x: &[T], kind: usize, norm: Norm) -> Vec<T> {
+    transform(x, dct_type(kind), norm, true)
+}
+
+/// Computes the DST of the given type, like `scipy.fft.dst(x, type=kind, norm=norm)`.
+///
+/// # Panics
+///
+/// Panics if `kind` isn't 1, 2, 3, or 4.
+pub fn dst<T: ThreadLocalPlanner>(x: &[T], kind: usize, norm: Norm) -> Vec<T> {
+    transform(x, dst_type(kind), norm, false)
+}
+
+/// Computes the inverse of the DST of the given type, like `scipy.fft.idst(x, type=kind, norm=norm)`.
+///
+/// # Panics
+///
+/// Panics if `kind` isn't 1, 2, 3, or 4.
+pub fn idst<T: ThreadLocalPlanner>(x: &[T], kind: usize, norm: Norm) -> Vec<T> {
+    transform(x, dst_type(kind), norm, true)
+}
+
+// Returns the DCT type for scipy's `type` argument
+fn dct_type(kind: usize) -> DctType {
+    match kind {
+        1 => DctType::Dct1,
+        2 => DctType::Dct2,
+        3 => DctType::Dct3,
+        4 => DctType::Dct4,
+        _ => panic!("Invalid DCT type {}. Expected 1, 2, 3, or 4", kind),
+    }
+}
+
+// Returns the DST type for scipy's `type` argument
+fn dst_type(kind: usize) -> DctType {
+    match kind {
+        1 => DctType::Dst1,
+        2 => DctType::Dst2,
+        3 => DctType::Dst3,
+        4 => DctType::Dst4,
+        _ => panic!("Invalid DST type {}. Expected 1, 2, 3, or 4", kind),
+    }
+}
+
+// Computes the transform of type `kind`, or of its inverse type if `inverse` is true, with scipy's scaling
+fn transform<T: ThreadLocalPlanner>(x: &[T], kind: DctType, norm: Norm, inverse: bool) -> Vec<T> {
+    let kind = if inverse { kind.inverse() } else { kind };
+    let len = x.len();
+    if kind == DctType::Dct1 {
+        assert!(
+            len >= 2,
+            "The DCT Type 1 requires at least 2 elements. Got len = {}",
+            len
+        );
+    }
+
+    // scipy's unnormalized transforms are twice as large as ours, for every type from 1 to 4
+    let logical_len = match kind {
+        DctType::Dct1 => 2.0 * (len as f64 - 1.0),
+        DctType::Dst1 => 2.0 * (len as f64 + 1.0),
+        _ => 2.0 * len as f64,
+    };
+    let scale = match (norm, inverse) {
+        (Norm::Ortho, _) => 2.0 / logical_len.sqrt(),
+        (Norm::Backward, false) | (Norm::Forward, true) => 2.0,
+        (Norm::Backward, true) | (Norm::Forward, false) => 2.0 / logical_len,
+    };
+    let scale = T::from_f64(scale).unwrap();
+
+    // the orthonormal transforms scale the elements that appear in every basis function, or in every output, by sqrt(2)
+    let sqrt_2 = T::from_f64(std::f64::consts::SQRT_2).unwrap();
+    let mut buffer = x.to_vec();
+    if norm == Norm::Ortho && len > 0 {
+        match kind {
+            DctType::Dct1 => {
+                buffer[0] = buffer[0] * sqrt_2;
+                buffer[len - 1] = buffer[len - 1] * sqrt_2;
+            }
+            DctType::Dct3 => buffer[0] = buffer[0] * sqrt_2,
+            DctType::Dst3 => buffer[len - 1] = buffer[len - 1] * sqrt_2,
+            _ => {}
+        }
+    }
+
+    T::with_planner(|planner| planner.plan_and_process(kind, &mut buffer));
+
+    for value in buffer.iter_mut() {
+        *value = *value * scale;
+    }
+    if norm == Norm::Ortho && len > 0 {
+        match kind {
+            DctType::Dct1 => {
+                buffer[0] = buffer[0] / sqrt_2;
+                buffer[len - 1] = buffer[len - 1] / sqrt_2;
+            }
+            DctType::Dct2 => buffer[0] = buffer[0] / sqrt_2,
+            DctType::Dst2 => buffer[len - 1] = buffer[len - 1] / sqrt_2,
+            _ => {}
+        }
+    }
+    buffer
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::f64::consts::PI;
+
+    // scipy's unnormalized transforms, written out from the definitions in scipy's documentation
+    fn reference(kind: DctType, x: &[f64]) -> Vec<f64> {
+        let n = x.len();
+        let nf = n as f64;
+        (0..n)
+            .map(|k| {
+                let kf = k as f64;
+                let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+                let sum = |f: &dyn Fn(f64) -> f64, range: std::ops::Range<usize>| {
+                    range.map(|i| x[i] * f(i as f64)).sum::<f64>()
+                };
+                match kind {
+                    DctType::Dct1 => {
+                        x[0] + sign * x[n - 1]
+                            + 2.0 * sum(&|i| (PI * kf * i / (nf - 1.0)).cos(), 1..n - 1)
+                    }
+                    DctType::Dct2 => {
+                        2.0 * sum(&|i| (PI * kf * (2.0 * i + 1.0) / (2.0 * nf)).cos(), 0..n)
+                    }
+                    DctType::Dct3 => {
+                        x[0] + 2.0 * sum(&|i| (PI * (2.0 * kf + 1.0) * i / (2.0 * nf)).cos(), 1..n)
+                    }
+                    DctType::Dct4 => {
+                        2.0 * sum(
+                            &|i| (PI * (2.0 * kf + 1.0) * (2.0 * i + 1.0) / (4.0 * nf)).cos(),
+                            0..n,
+                        )
+                    }
+                    DctType::Dst1 => {
+                        2.0 * sum(&|i| (PI * (kf + 1.0) * (i + 1.0) / (nf + 1.0)).sin(), 0..n)
+                    }
+                    DctType::Dst2 => {
+                        2.0 * sum(
+                            &|i| (PI * (kf + 1.0) * (2.0 * i + 1.0) / (2.0 * nf)).sin(),
+                            0..n,
+                        )
+                    }
+                    DctType::Dst3 => {
+                        sign * x[n - 1]
+                            + 2.0
+                                * sum(
+                                    &|i| (PI * (2.0 * kf + 1.0) * (i + 1.0) / (2.0 * nf)).sin(),
+                                    0..n - 1,
+                                )
+                    }
+                    DctType::Dst4 => {
+                        2.0 * sum(
+                            &|i| (PI * (2.0 * kf + 1.0) * (2.0 * i + 1.0) / (4.0 * nf)).sin(),
+                            0..n,
+                        )
+                    }
+                    _ => unreachable!(),
+                }
+            })
+            .collect()
+    }
+
+    fn assert_close(expected: &[f64], actual: &[f64], description: &str) {
+        assert_eq!(expected.len(), actual.len(), "{}", description);
+        for (e, a) in expected.iter().zip(actual) {
+            assert!(
+                (e - a).abs() < 1e-9,
+                "{}: {:?} != {:?}",
+                description,
+                expected,
+                actual
+            );
+        }
+    }
+
+    #[test]
+    fn test_unnormalized_matches_reference() {
+        for len in 2..20 {
+            let x: Vec<f64> = (0..len).map(|i| ((i * 7 + 3) % 11) as f64 - 5.0).collect();
+            for kind in 1..=4 {
+                let description = format!("type {}, len {}", kind, len);
+                assert_close(
+                    &reference(dct_type(kind), &x),
+                    &dct(&x, kind, Norm::Backward),
+                    &description,
+                );
+                assert_close(
+                    &reference(dst_type(kind), &x),
+                    &dst(&x, kind, Norm::Backward),
+                    &description,
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_known_values() {
+        // scipy.fft.dct([1, 2, 3, 4]), with norm=None and norm="ortho"
+        let x = [1.0, 2.0, 3.0, 4.0];
+        assert_close(
+            &[20.0, -6.308644059797899, 0.0, -0.4483415291679655],
+            &dct(&x, 2, Norm::Backward),
+            "backward",
+        );
+        assert_close(
+            &[5.0, -2.230442497387663, 0.0, -0.15851266778110684],
+            &dct(&x, 2, Norm::Ortho),
+            "ortho",
+        );
+    }
+
+    #[test]
+    fn test_roundtrip_and_orthonormality() {
+        for len in 2..20 {
+            let x: Vec<f64> = (0..len).map(|i| ((i * 5 + 1) % 13) as f64 - 6.0).collect();
+            let energy = |v: &[f64]| v.iter().map(|e| e * e).sum::<f64>();
+            for kind in 1..=4 {
+                for &norm in &[Norm::Backward, Norm::Ortho, Norm::Forward] {
+                    let description = format!("type {}, len {}, {:?}", kind, len, norm);
+                    assert_close(&x, &idct(&dct(&x, kind, norm), kind, norm), &description);
+                    assert_close(&x, &idst(&dst(&x, kind, norm), kind, norm), &description);
+                }
+
+                // the orthonormal transforms preserve the energy of the signal
+                let description = format!("type {}, len {}", kind, len);
+                let ortho_dct = dct(&x, kind, Norm::Ortho);
+                let ortho_dst = dst(&x, kind, Norm::Ortho);
+                assert!(
+                    (energy(&x) - energy(&ortho_dct)).abs() < 1e-9,
+                    "{}",
+                    description
+                );
+                assert!(
+                    (energy(&x) - energy(&ortho_dst)).abs() < 1e-9,
+                    "{}",
+                    description
+                );
+            }
+        }
+    }
+}
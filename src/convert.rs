@@ -0,0 +1,187 @@
+//! Helpers for converting integer PCM audio samples into the floats that transforms operate on.
+//!
+//! Samples are scaled so that full scale maps to `[-1, 1)`: an `i16` sample is divided by 32768, and an `i32` sample is
+//! divided by 2147483648. Each sample is converted in `f64` and rounded once to `T`.
+//!
+//! ~~~
+//! // Converts a buffer of 16-bit PCM audio to floats, then computes its DCT2
+//! use rustdct::convert::pcm_to_float;
+//! use rustdct::DctPlanner;
+//!
+//! let pcm = vec![0i16; 1024];
+//! let mut buffer = vec![0f32; 1024];
+//! pcm_to_float(&pcm, &mut buffer);
+//!
+//! let mut planner = DctPlanner::new();
+//! let dct = planner.plan_dct2(1024);
+//! dct.process_dct2(&mut buffer);
+//! ~~~
+//!
+//! Plans that compute the DCT2 can also convert 16-bit samples while they read their input, with
+//! [`process_dct2_from_pcm`](../trait.TransformType2And3.html#method.process_dct2_from_pcm).
+
+use crate::DctNum;
+
+/// An integer PCM sample type that can be converted to a float.
+pub trait PcmSample: Copy {
+    /// The magnitude of the most negative sample. Dividing a sample by this maps it into `[-1, 1)`.
+    const FULL_SCALE: f64;
+
+    /// Returns this sample as a `f64`, without scaling.
+    fn to_f64(self) -> f64;
+}
+
+impl PcmSample for i16 {
+    const FULL_SCALE: f64 = 32768.0;
+
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+}
+
+impl PcmSample for i32 {
+    const FULL_SCALE: f64 = 2147483648.0;
+
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+}
+
+// Converts a single sample, plus `offset` quantization steps, to a float in the usual scale
+#[inline(always)]
+pub(crate) fn sample_to_float<S: PcmSample, T: DctNum>(sample: S, offset: f64) -> T {
+    T::from_f64((sample.to_f64() + offset) / S::FULL_SCALE).unwrap()
+}
+
+/// Converts each PCM sample in `input` to a float in `[-1, 1)`, and stores it in `output`.
+///
+/// # Panics
+///
+/// Panics if `input` and `output` have different lengths.
+pub fn pcm_to_float<S: PcmSample, T: DctNum>(input: &[S], output: &mut [T]) {
+    assert_eq!(
+        input.len(),
+        output.len(),
+        "Input and output must have the same length. Got input len = {}, output len = {}",
+        input.len(),
+        output.len()
+    );
+
+    for (sample, output) in input.iter().zip(output.iter_mut()) {
+        *output = sample_to_float(*sample, 0.0);
+    }
+}
+
+/// Converts each PCM sample in `input` to a float, like [`pcm_to_float`](fn.pcm_to_float.html), after adding
+/// triangular dither noise from `dither`.
+///
+/// The noise spans plus or minus one quantization step of the sample type. Adding it decorrelates the quantization
+/// error from the signal, so low-level signals and digital silence don't produce spurious tonal components or exact
+/// zeroes in the transformed output.
+///
+/// # Panics
+///
+/// Panics if `input` and `output` have different lengths.
+pub fn pcm_to_float_dithered<S: PcmSample, T: DctNum>(
+    input: &[S],
+    output: &mut [T],
+    dither: &mut TpdfDither,
+) {
+    assert_eq!(
+        input.len(),
+        output.len(),
+        "Input and output must have the same length. Got input len = {}, output len = {}",
+        input.len(),
+        output.len()
+    );
+
+    for (sample, output) in input.iter().zip(output.iter_mut()) {
+        *output = sample_to_float(*sample, dither.next_offset());
+    }
+}
+
+/// A source of triangular probability density function (TPDF) dither noise, for
+/// [`pcm_to_float_dithered`](fn.pcm_to_float_dithered.html).
+///
+/// The noise comes from a small deterministic generator, so the same seed always produces the same noise. It's meant
+/// for dithering, not for anything that needs statistically strong or unpredictable random numbers.
+#[derive(Clone, Debug)]
+pub struct TpdfDither {
+    state: u32,
+}
+
+impl TpdfDither {
+    /// Creates a new dither source, whose noise is determined by `seed`.
+    pub fn new(seed: u32) -> Self {
+        // xorshift never leaves the all-zeroes state, so remap it
+        let state = if seed == 0 { 0x9E37_79B9 } else { seed };
+        Self { state }
+    }
+
+    // Returns the next value of the xorshift32 generator, as a float in [0, 1)
+    fn next_uniform(&mut self) -> f64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        f64::from(self.state >> 8) / f64::from(1u32 << 24)
+    }
+
+    // Returns the next dither offset, in quantization steps. The difference of two uniform values has a triangular
+    // distribution between -1 and 1
+    fn next_offset(&mut self) -> f64 {
+        self.next_uniform() - self.next_uniform()
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn test_pcm_to_float() {
+        let input = [-32768i16, -16384, 0, 16384, 32767];
+        let mut output = [0f32; 5];
+        pcm_to_float(&input, &mut output);
+        assert_eq!(output, [-1.0, -0.5, 0.0, 0.5, 32767.0 / 32768.0]);
+
+        let input = [-2147483648i32, 0, 1 << 30];
+        let mut output = [0f64; 3];
+        pcm_to_float(&input, &mut output);
+        assert_eq!(output, [-1.0, 0.0, 0.5]);
+    }
+
+    /// Verify that dithered samples stay within one quantization step of the undithered samples, and that digital
+    /// silence no longer converts to exact zeroes
+    #[test]
+    fn test_pcm_to_float_dithered() {
+        let input: Vec<i16> = (0..1000).map(|i| (i % 7) as i16 - 3).collect();
+        let mut expected = vec![0f64; 1000];
+        pcm_to_float(&input, &mut expected);
+
+        let mut dither = TpdfDither::new(1234);
+        let mut actual = vec![0f64; 1000];
+        pcm_to_float_dithered(&input, &mut actual, &mut dither);
+
+        let step = 1.0 / 32768.0;
+        for (expected, actual) in expected.iter().zip(actual.iter()) {
+            assert!((expected - actual).abs() < step);
+        }
+        let mean_offset = expected
+            .iter()
+            .zip(actual.iter())
+            .map(|(expected, actual)| actual - expected)
+            .sum::<f64>()
+            / 1000.0;
+        assert!(mean_offset.abs() < step * 0.1);
+
+        let silence = vec![0i16; 100];
+        let mut output = vec![0f64; 100];
+        pcm_to_float_dithered(&silence, &mut output, &mut TpdfDither::new(0));
+        assert!(output.iter().all(|value| *value != 0.0));
+
+        // the same seed produces the same noise
+        let mut repeated = vec![0f64; 1000];
+        pcm_to_float_dithered(&input, &mut repeated, &mut TpdfDither::new(1234));
+        assert_eq!(actual, repeated);
+    }
+}
@@ -0,0 +1,96 @@
+/// The crossover sizes that a [`DctPlanner`](struct.DctPlanner.html) uses to choose between algorithms.
+///
+/// For each transform type, small sizes are fastest with a naive O(n^2) algorithm, and larger sizes are fastest with
+/// an algorithm that converts the problem to a FFT or to another transform. Each field of the cost model is the size
+/// where the planner switches from one to the other. The default values come from benchmarks of each algorithm, so
+/// they're a good choice for most machines.
+///
+/// The planner never measures anything while planning: Its decisions only depend on its cost model and on the sizes
+/// that are requested. So two planners with the same cost model always plan the same algorithms, on every machine,
+/// which makes planning reproducible in tests and CI. To tune the planner for a particular machine, benchmark the
+/// algorithms in the [`algorithm`](algorithm/index.html) module, and adjust the fields here.
+///
+/// The butterfly sizes and the power-of-two split radix DCT2 are always used when they apply, since they're faster
+/// than every alternative at every size they support.
+///
+/// Some fast algorithms don't support the smallest sizes, like a DCT3 of size 0, which has no half-size FFT. When a
+/// planner is created with a cost model, crossover sizes below the smallest size that their fast algorithm supports
+/// are raised to that size, so those sizes always use the naive algorithm.
+///
+/// ~~~
+/// // Plans a DCT1 of size 20 with the naive algorithm, instead of converting it to a FFT
+/// use rustdct::{DctPlanner, PlannerCostModel};
+///
+/// let cost_model = PlannerCostModel {
+///     dct1_min_fast_len: 100,
+///     ..PlannerCostModel::default()
+/// };
+/// let mut planner = DctPlanner::with_cost_model(cost_model);
+/// let dct1 = planner.plan_dct1(20);
+///
+/// let mut buffer = vec![0f32; 20];
+/// dct1.process_dct1(&mut buffer);
+/// ~~~
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PlannerCostModel {
+    /// DCT1 sizes at least this large, which don't have a butterfly, are converted to a FFT. Smaller sizes use the
+    /// naive algorithm.
+    pub dct1_min_fast_len: usize,
+    /// DST1 sizes at least this large, which don't have a butterfly, are converted to a FFT. Smaller sizes use the
+    /// naive algorithm.
+    pub dst1_min_fast_len: usize,
+    /// Even DCT3 and DST3 sizes at least this large, which aren't powers of two, are converted to a half-size FFT
+    /// instead of a full-size one. The DCT2 and DST2 always use the full-size FFT, since the half-size FFT makes them
+    /// slower at most sizes.
+    pub dct3_min_real_fft_len: usize,
+    /// Even DCT4 sizes at least this large, which don't have a butterfly, are converted to a smaller transform.
+    /// Smaller sizes use the naive algorithm.
+    pub dct4_even_min_fast_len: usize,
+    /// Power-of-two DCT4 sizes up to this size are split into half-size DCT4s. Larger sizes are converted to a
+    /// half-size DCT3.
+    pub dct4_max_split_radix_len: usize,
+    /// Odd DCT4 sizes at least this large are converted to a FFT. Smaller sizes use the naive algorithm.
+    pub dct4_odd_min_fast_len: usize,
+    /// DCT6 and DCT7 sizes at least this large, which don't have a butterfly, are converted to a DCT2. Smaller sizes
+    /// use the naive algorithm.
+    pub dct6_min_fast_len: usize,
+    /// DST6 and DST7 sizes at least this large, which don't have a butterfly, are converted to a FFT. Smaller sizes use
+    /// the naive algorithm.
+    pub dst6_min_fast_len: usize,
+    /// DCT8 sizes at least this large, which don't have a butterfly, are converted to a DCT2. Smaller sizes use the
+    /// naive algorithm.
+    pub dct8_min_fast_len: usize,
+    /// DST8 sizes at least this large, which don't have a butterfly, are converted to a DST2. Smaller sizes use the
+    /// naive algorithm.
+    pub dst8_min_fast_len: usize,
+}
+
+impl PlannerCostModel {
+    // Raises each crossover size to the smallest size that its fast algorithm supports. The DCT1 needs a non-empty
+    // inner FFT, and the even DCT3 and DCT4 algorithms need a non-empty half-size inner transform
+    pub(crate) fn clamped(self) -> Self {
+        Self {
+            dct1_min_fast_len: self.dct1_min_fast_len.max(3),
+            dct3_min_real_fft_len: self.dct3_min_real_fft_len.max(2),
+            dct4_even_min_fast_len: self.dct4_even_min_fast_len.max(2),
+            ..self
+        }
+    }
+}
+
+impl Default for PlannerCostModel {
+    fn default() -> Self {
+        Self {
+            dct1_min_fast_len: 10,
+            dst1_min_fast_len: 25,
+            dct3_min_real_fft_len: 64,
+            dct4_even_min_fast_len: 6,
+            dct4_max_split_radix_len: 64,
+            dct4_odd_min_fast_len: 7,
+            dct6_min_fast_len: 8,
+            dst6_min_fast_len: 45,
+            dct8_min_fast_len: 8,
+            dst8_min_fast_len: 8,
+        }
+    }
+}
@@ -0,0 +1,329 @@
+use std::sync::Arc;
+
+use rustfft::Length;
+
+use crate::common::offset_complex_scratch_len;
+use crate::transpose::{transpose, transpose_strided};
+use crate::{DctNum, RequiredScratch, ScratchLayout, TransformType2And3};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+
+// The number of lanes that are gathered and transformed together. Each row of a lane block is one run of this many
+// contiguous elements, so for f32 arrays, every gathered row reads a whole 64-byte cache line
+const LANE_WIDTH: usize = 16;
+
+/// How [`DctAxis`](struct.DctAxis.html) brings the elements along a non-contiguous axis together before transforming
+/// them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AxisStrategy {
+    /// Chooses `Gather` or `Transpose` based on the shape of the array. Lane blocks need at least 16 contiguous
+    /// elements per row to read whole cache lines, so this chooses `Gather` if the axis' stride is at least 16, and
+    /// `Transpose` otherwise, where the slab to transpose is no larger than one lane block.
+    Auto,
+    /// Copies 16 lanes at a time into a small contiguous buffer, transforms them, and copies them back. Needs `16 * len`
+    /// elements of scratch space, independently of the size of the array. For many shapes, this is faster than a full
+    /// transpose, because each element is only copied twice, and the lane buffer stays in the L1 cache.
+    Gather,
+    /// Transposes each slab of the array that spans the axis, so that the axis is contiguous, transforms every row of
+    /// the transposed slab, and transposes it back. Needs scratch space for a whole slab, which is `len` times the
+    /// axis' stride.
+    Transpose,
+}
+
+/// Computes DCT2s and DCT3s along one axis of a row-major multidimensional array, in place.
+///
+/// The array's shape is given as a list of dimensions, from the slowest-varying to the fastest-varying, like
+/// `[depth, height, width]` for a 3D volume. Every lane along the axis is transformed with the inner DCT, whose length
+/// must be the axis' dimension. Lanes along the last axis are contiguous, so they're transformed directly in the array.
+/// Lanes along any other axis are `stride` elements apart, where `stride` is the product of the later dimensions, and
+/// are brought together with the chosen [`AxisStrategy`](enum.AxisStrategy.html).
+///
+/// A separable multidimensional DCT is one `DctAxis` per axis, applied in any order.
+///
+/// ~~~
+/// // Computes the 3D DCT2 of a 16x32x64 volume
+/// use rustdct::dct2d::{AxisStrategy, DctAxis};
+/// use rustdct::DctPlanner;
+///
+/// let shape = [16, 32, 64];
+/// let mut volume = vec![0f32; 16 * 32 * 64];
+///
+/// let mut planner = DctPlanner::new();
+/// for axis in 0..shape.len() {
+///     let dct = DctAxis::new(planner.plan_dct2(shape[axis]), &shape, axis, AxisStrategy::Auto);
+///     dct.process_dct2_axis(&mut volume);
+/// }
+/// ~~~
+pub struct DctAxis<T> {
+    dct: Arc<dyn TransformType2And3<T>>,
+
+    // the number of slabs that span the axis, which is the product of the earlier dimensions
+    slab_count: usize,
+    // the distance between consecutive elements of a lane, which is the product of the later dimensions
+    stride: usize,
+    // never `Auto`
+    strategy: AxisStrategy,
+}
+
+impl<T: DctNum> DctAxis<T> {
+    /// Creates a new context that will transform axis `axis` of row-major arrays with shape `shape`, using `strategy`
+    /// for non-contiguous axes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `axis` isn't less than `shape.len()`, if `shape[axis]` isn't equal to `inner_dct.len()`, or if the
+    /// number of elements in the array doesn't fit in a usize.
+    pub fn new(
+        inner_dct: Arc<dyn TransformType2And3<T>>,
+        shape: &[usize],
+        axis: usize,
+        strategy: AxisStrategy,
+    ) -> Self {
+        assert!(
+            axis < shape.len(),
+            "The axis must be less than the number of dimensions. Got axis = {}, shape = {:?}",
+            axis,
+            shape
+        );
+        assert_eq!(
+            shape[axis],
+            inner_dct.len(),
+            "The inner DCT's len must be equal to the axis' dimension. Expected len = {}, got len = {}",
+            shape[axis],
+            inner_dct.len()
+        );
+        let product = |dimensions: &[usize]| {
+            dimensions
+                .iter()
+                .try_fold(1usize, |product, &dimension| product.checked_mul(dimension))
+        };
+        let element_count = product(shape);
+        assert!(
+            element_count.is_some(),
+            "The number of elements in an array of shape {:?} doesn't fit in a usize",
+            shape
+        );
+
+        let stride = product(&shape[axis + 1..]).unwrap();
+        let strategy = match strategy {
+            AxisStrategy::Auto if stride >= LANE_WIDTH => AxisStrategy::Gather,
+            AxisStrategy::Auto => AxisStrategy::Transpose,
+            strategy => strategy,
+        };
+        Self {
+            dct: inner_dct,
+            slab_count: product(&shape[..axis]).unwrap(),
+            stride,
+            strategy,
+        }
+    }
+
+    /// Returns the strategy that this context uses for non-contiguous axes. If it was created with
+    /// `AxisStrategy::Auto`, this is the strategy that was chosen.
+    pub fn strategy(&self) -> AxisStrategy {
+        self.strategy
+    }
+
+    /// Computes the DCT Type 2 of every lane along the axis of `array`, in-place.
+    ///
+    /// This method allocates a Vec<T> of scratch space. If you'd like to reuse that allocation between
+    /// multiple computations, consider calling `process_dct2_axis_with_scratch` instead.
+    ///
+    /// Does not normalize outputs.
+    pub fn process_dct2_axis(&self, array: &mut [T]) {
+        let mut scratch = vec![T::zero(); self.get_scratch_len()];
+        self.process_dct2_axis_with_scratch(array, &mut scratch);
+    }
+
+    /// Computes the DCT Type 2 of every lane along the axis of `array`, in-place. Uses the provided `scratch` buffer
+    /// as scratch space.
+    ///
+    /// Does not normalize outputs.
+    pub fn process_dct2_axis_with_scratch(&self, array: &mut [T], scratch: &mut [T]) {
+        self.process_array(array, scratch, |dct, buffer, scratch| {
+            dct.process_dct2_with_scratch(buffer, scratch)
+        });
+    }
+
+    /// Computes the DCT Type 3 of every lane along the axis of `array`, in-place.
+    ///
+    /// This method allocates a Vec<T> of scratch space. If you'd like to reuse that allocation between
+    /// multiple computations, consider calling `process_dct3_axis_with_scratch` instead.
+    ///
+    /// Does not normalize outputs.
+    pub fn process_dct3_axis(&self, array: &mut [T]) {
+        let mut scratch = vec![T::zero(); self.get_scratch_len()];
+        self.process_dct3_axis_with_scratch(array, &mut scratch);
+    }
+
+    /// Computes the DCT Type 3 of every lane along the axis of `array`, in-place. Uses the provided `scratch` buffer
+    /// as scratch space.
+    ///
+    /// Does not normalize outputs.
+    pub fn process_dct3_axis_with_scratch(&self, array: &mut [T], scratch: &mut [T]) {
+        self.process_array(array, scratch, |dct, buffer, scratch| {
+            dct.process_dct3_with_scratch(buffer, scratch)
+        });
+    }
+
+    // The number of scratch elements used to bring the lanes together, before the inner DCT's scratch
+    fn lane_buffer_len(&self) -> usize {
+        if self.stride == 1 {
+            0
+        } else {
+            match self.strategy {
+                AxisStrategy::Transpose => self.len() * self.stride,
+                _ => self.len() * self.stride.min(LANE_WIDTH),
+            }
+        }
+    }
+
+    fn process_array<F>(&self, array: &mut [T], scratch: &mut [T], process_fn: F)
+    where
+        F: Fn(&dyn TransformType2And3<T>, &mut [T], &mut [T]),
+    {
+        let len = self.len();
+        let slab_len = len * self.stride;
+        assert_eq!(
+            array.len(),
+            self.slab_count * slab_len,
+            "The array must have one element for each index of its shape. Expected array len = {}, got array len = {}",
+            self.slab_count * slab_len,
+            array.len()
+        );
+        assert!(
+            scratch.len() >= self.get_scratch_len(),
+            "Not enough scratch space was provided. Expected scratch len >= {}, got scratch len = {}",
+            self.get_scratch_len(),
+            scratch.len()
+        );
+        if slab_len == 0 {
+            return;
+        }
+
+        let (lane_buffer, inner_scratch) = scratch.split_at_mut(self.lane_buffer_len());
+        for slab in array.chunks_exact_mut(slab_len) {
+            if self.stride == 1 {
+                // the lane is contiguous, so we can process it directly in the array
+                process_fn(self.dct.as_ref(), slab, inner_scratch);
+            } else if self.strategy == AxisStrategy::Transpose {
+                transpose(slab, lane_buffer, self.stride, len);
+                for lane in lane_buffer.chunks_exact_mut(len) {
+                    process_fn(self.dct.as_ref(), lane, inner_scratch);
+                }
+                transpose(lane_buffer, slab, len, self.stride);
+            } else {
+                for first_lane in (0..self.stride).step_by(LANE_WIDTH) {
+                    let width = LANE_WIDTH.min(self.stride - first_lane);
+                    let lanes = &mut lane_buffer[..len * width];
+
+                    transpose_strided(&slab[first_lane..], self.stride, lanes, len, width, len);
+                    for lane in lanes.chunks_exact_mut(len) {
+                        process_fn(self.dct.as_ref(), lane, inner_scratch);
+                    }
+                    transpose_strided(lanes, len, &mut slab[first_lane..], self.stride, len, width);
+                }
+            }
+        }
+    }
+}
+impl<T> Length for DctAxis<T> {
+    fn len(&self) -> usize {
+        self.dct.len()
+    }
+}
+impl<T: DctNum> MemoryUsage for DctAxis<T> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            inner_plans_bytes: visitor.inner_plan(&self.dct),
+            ..PlanMemory::default()
+        }
+    }
+}
+impl<T: DctNum> RequiredScratch for DctAxis<T> {
+    fn get_scratch_len(&self) -> usize {
+        self.lane_buffer_len() + self.dct.get_scratch_len()
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        offset_complex_scratch_len(self.lane_buffer_len(), self.dct.get_complex_scratch_len())
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        ScratchLayout::new()
+            .with_region("lane buffer", self.lane_buffer_len(), false)
+            .with_inner(self.dct.scratch_layout())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algorithm::Type2And3Naive;
+    use crate::test_utils::{compare_float_vectors, random_signal};
+    use crate::Dct2;
+
+    // Computes the DCT2 of each lane along `axis` by copying it out one element at a time
+    fn reference_axis_dct2(array: &[f32], shape: &[usize], axis: usize) -> Vec<f32> {
+        let len = shape[axis];
+        let stride: usize = shape[axis + 1..].iter().product();
+        let dct = Type2And3Naive::new(len);
+        let mut output = array.to_vec();
+        for slab in output.chunks_exact_mut(len * stride) {
+            for lane_index in 0..stride {
+                let mut lane: Vec<f32> = (0..len).map(|i| slab[i * stride + lane_index]).collect();
+                dct.process_dct2(&mut lane);
+                for (i, element) in lane.into_iter().enumerate() {
+                    slab[i * stride + lane_index] = element;
+                }
+            }
+        }
+        output
+    }
+
+    /// Verify every axis of a few shapes against a lane-by-lane reference, with strides smaller than, equal to, not a
+    /// multiple of, and larger than the lane width
+    #[test]
+    fn test_axis_strategies() {
+        let shapes: [&[usize]; 5] = [&[7], &[5, 3], &[4, 16], &[3, 6, 7], &[2, 9, 20]];
+        for shape in shapes.iter() {
+            let input = random_signal(shape.iter().product());
+            for axis in 0..shape.len() {
+                let expected = reference_axis_dct2(&input, shape, axis);
+                for &strategy in &[
+                    AxisStrategy::Auto,
+                    AxisStrategy::Gather,
+                    AxisStrategy::Transpose,
+                ] {
+                    let inner = Arc::new(Type2And3Naive::new(shape[axis]));
+                    let dct = DctAxis::new(inner, shape, axis, strategy);
+                    assert_ne!(dct.strategy(), AxisStrategy::Auto);
+
+                    let mut actual = input.clone();
+                    dct.process_dct2_axis(&mut actual);
+                    assert!(
+                        compare_float_vectors(&expected, &actual),
+                        "shape = {:?}, axis = {}, strategy = {:?}",
+                        shape,
+                        axis,
+                        strategy
+                    );
+
+                    // the DCT3 inverts the DCT2, up to a scale of len / 2
+                    dct.process_dct3_axis(&mut actual);
+                    let scale = 2.0 / shape[axis] as f32;
+                    let roundtrip: Vec<f32> = actual.iter().map(|x| x * scale).collect();
+                    assert!(compare_float_vectors(&input, &roundtrip));
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_axis_wrong_len() {
+        DctAxis::<f32>::new(
+            Arc::new(Type2And3Naive::new(4)),
+            &[4, 5],
+            1,
+            AxisStrategy::Auto,
+        );
+    }
+}
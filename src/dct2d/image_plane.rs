@@ -0,0 +1,227 @@
+use std::convert::TryFrom;
+use std::ops::Deref;
+
+use ::image::{GrayImage, ImageBuffer, Luma};
+
+use super::{AxisStrategy, DctAxis};
+use crate::{DctPlanner, RequiredScratch};
+
+/// A grayscale image as a plane of `f32` values, which computes its own 2D DCT2 and DCT3 in place.
+///
+/// `Dct2dImage` converts images from the [`image`](https://docs.rs/image) crate into a row-major plane with one `f32`
+/// per pixel, from 0 to 255, and back. [`forward`](#method.forward) replaces the pixels with their 2D DCT2
+/// coefficients, and [`inverse`](#method.inverse) turns the coefficients back into pixels, so that
+/// `inverse(forward(x))` is `x`. In between, the coefficients can be edited with [`plane_mut`](#method.plane_mut), or
+/// rendered with [`log_magnitude_image`](#method.log_magnitude_image).
+///
+/// ~~~
+/// // Removes the high frequencies of a 64x48 image
+/// use image::GrayImage;
+/// use rustdct::dct2d::Dct2dImage;
+/// use rustdct::DctPlanner;
+///
+/// let image = GrayImage::new(64, 48);
+///
+/// let mut planner = DctPlanner::new();
+/// let mut dct = Dct2dImage::from_luma(&mut planner, &image);
+/// dct.forward();
+/// for (index, coefficient) in dct.plane_mut().iter_mut().enumerate() {
+///     if index % 64 >= 16 || index / 64 >= 16 {
+///         *coefficient = 0.0;
+///     }
+/// }
+/// dct.inverse();
+///
+/// let filtered: GrayImage = dct.to_gray_image();
+/// ~~~
+pub struct Dct2dImage {
+    width: usize,
+    height: usize,
+    plane: Vec<f32>,
+
+    rows: DctAxis<f32>,
+    columns: DctAxis<f32>,
+    scratch: Vec<f32>,
+}
+
+impl Dct2dImage {
+    /// Creates a plane from the pixels of a grayscale image, like a `GrayImage`. The 2D DCTs are planned with
+    /// `planner`.
+    pub fn from_luma<C: Deref<Target = [u8]>>(
+        planner: &mut DctPlanner<f32>,
+        image: &ImageBuffer<Luma<u8>, C>,
+    ) -> Self {
+        let plane = image.as_raw().iter().map(|&pixel| pixel as f32).collect();
+        Self::from_plane(
+            planner,
+            image.width() as usize,
+            image.height() as usize,
+            plane,
+        )
+    }
+
+    /// Creates a `width` x `height` plane from `plane`, which has one value per pixel, in row-major order. The 2D DCTs
+    /// are planned with `planner`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `plane.len()` isn't equal to `width * height`, or if `width` or `height` doesn't fit in a `u32`, which
+    /// is the largest image size that the `image` crate supports.
+    pub fn from_plane(
+        planner: &mut DctPlanner<f32>,
+        width: usize,
+        height: usize,
+        plane: Vec<f32>,
+    ) -> Self {
+        assert_eq!(
+            Some(plane.len()),
+            width.checked_mul(height),
+            "The plane must have width * height elements. Got width = {}, height = {}, plane len = {}",
+            width,
+            height,
+            plane.len()
+        );
+        assert!(
+            u32::try_from(width).is_ok() && u32::try_from(height).is_ok(),
+            "The width and height must fit in a u32. Got width = {}, height = {}",
+            width,
+            height
+        );
+
+        let shape = [height, width];
+        let rows = DctAxis::new(planner.plan_dct2(width), &shape, 1, AxisStrategy::Auto);
+        let columns = DctAxis::new(planner.plan_dct2(height), &shape, 0, AxisStrategy::Auto);
+        let scratch_len = rows.get_scratch_len().max(columns.get_scratch_len());
+        Self {
+            width,
+            height,
+            plane,
+            rows,
+            columns,
+            scratch: vec![0.0; scratch_len],
+        }
+    }
+
+    /// Returns the width of the image, in pixels.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the height of the image, in pixels.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns the plane in row-major order. Depending on the last call to `forward` or `inverse`, these are pixels or
+    /// DCT coefficients. The coefficient of horizontal frequency `x` and vertical frequency `y` is at index
+    /// `y * width + x`.
+    pub fn plane(&self) -> &[f32] {
+        &self.plane
+    }
+
+    /// Returns the plane in row-major order, for editing the pixels or the DCT coefficients in place.
+    pub fn plane_mut(&mut self) -> &mut [f32] {
+        &mut self.plane
+    }
+
+    /// Replaces the pixels with their 2D DCT Type 2 coefficients.
+    ///
+    /// Does not normalize outputs: The coefficient at index 0 is the sum of the pixels.
+    pub fn forward(&mut self) {
+        self.rows
+            .process_dct2_axis_with_scratch(&mut self.plane, &mut self.scratch);
+        self.columns
+            .process_dct2_axis_with_scratch(&mut self.plane, &mut self.scratch);
+    }
+
+    /// Replaces the 2D DCT Type 2 coefficients with the pixels they represent, by computing the 2D DCT Type 3 and
+    /// scaling it by `4 / (width * height)`, which undoes `forward`.
+    pub fn inverse(&mut self) {
+        self.columns
+            .process_dct3_axis_with_scratch(&mut self.plane, &mut self.scratch);
+        self.rows
+            .process_dct3_axis_with_scratch(&mut self.plane, &mut self.scratch);
+
+        let scale = 4.0 / (self.width as f32 * self.height as f32);
+        for element in self.plane.iter_mut() {
+            *element *= scale;
+        }
+    }
+
+    /// Converts the plane to a `GrayImage` of the same size, rounding each value to the nearest integer and clamping
+    /// it to the range 0 to 255.
+    pub fn to_gray_image(&self) -> GrayImage {
+        let pixels = self
+            .plane
+            .iter()
+            .map(|&value| value.round().clamp(0.0, 255.0) as u8)
+            .collect();
+        self.image_from_pixels(pixels)
+    }
+
+    /// Renders the DCT coefficients as a `GrayImage` of the same size, for viewing them.
+    ///
+    /// The coefficients of natural images span many orders of magnitude, so each pixel is `ln(1 + |coefficient|)`,
+    /// scaled so that the largest one is 255. The DC coefficient is in the top left corner, and frequencies increase
+    /// to the right and downwards. A plane of zeroes renders as black.
+    pub fn log_magnitude_image(&self) -> GrayImage {
+        let log_magnitudes: Vec<f32> = self
+            .plane
+            .iter()
+            .map(|coefficient| coefficient.abs().ln_1p())
+            .collect();
+        let max = log_magnitudes.iter().cloned().fold(0.0, f32::max);
+        let scale = if max > 0.0 { 255.0 / max } else { 0.0 };
+
+        let pixels = log_magnitudes
+            .iter()
+            .map(|&value| (value * scale).round().min(255.0) as u8)
+            .collect();
+        self.image_from_pixels(pixels)
+    }
+
+    fn image_from_pixels(&self, pixels: Vec<u8>) -> GrayImage {
+        // the sizes were checked to fit in a u32, and the plane to have width * height elements
+        GrayImage::from_raw(self.width as u32, self.height as u32, pixels).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_utils::compare_float_vectors;
+
+    /// Verify that the inverse undoes the forward transform, and that the images round trip
+    #[test]
+    fn test_image_roundtrip() {
+        let (width, height) = (37, 20);
+        let image =
+            GrayImage::from_fn(width, height, |x, y| Luma([((x * 7 + y * 13) % 256) as u8]));
+
+        let mut planner = DctPlanner::new();
+        let mut dct = Dct2dImage::from_luma(&mut planner, &image);
+        let pixels = dct.plane().to_vec();
+
+        dct.forward();
+        let sum: f32 = pixels.iter().sum();
+        assert!((dct.plane()[0] - sum).abs() < sum * 1e-5);
+        let magnitudes = dct.log_magnitude_image();
+        assert_eq!(magnitudes.dimensions(), (width, height));
+        assert_eq!(magnitudes.as_raw().iter().max(), Some(&255));
+
+        dct.inverse();
+        assert!(compare_float_vectors(&pixels, dct.plane()));
+        assert_eq!(dct.to_gray_image(), image);
+    }
+
+    #[test]
+    fn test_log_magnitude_zeroes() {
+        let mut planner = DctPlanner::new();
+        let dct = Dct2dImage::from_plane(&mut planner, 3, 2, vec![0.0; 6]);
+        assert!(dct
+            .log_magnitude_image()
+            .as_raw()
+            .iter()
+            .all(|&pixel| pixel == 0));
+    }
+}
@@ -0,0 +1,476 @@
+use rustfft::num_traits::ToPrimitive;
+
+use crate::memory::table_bytes;
+use crate::DctNum;
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+
+/// The natural (row-major) index of the coefficient at each position of JPEG's zigzag order.
+///
+/// Position `i` of a zigzag-ordered block holds the coefficient at row `JPEG_ZIGZAG[i] / 8`, column
+/// `JPEG_ZIGZAG[i] % 8` of the naturally ordered block.
+pub const JPEG_ZIGZAG: [usize; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20,
+    13, 6, 7, 14, 21, 28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59,
+    52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63,
+];
+
+const BLOCK_SIZE: usize = 8;
+const BLOCK_LEN: usize = BLOCK_SIZE * BLOCK_SIZE;
+
+type Block<T> = [[T; BLOCK_SIZE]; BLOCK_SIZE];
+
+/// Computes the forward DCT of 8x8 JPEG blocks, quantizes the coefficients, and writes them in zigzag order.
+///
+/// This is the inner loop of a JPEG encoder. Each block is loaded into an 8x8 array on the stack, and transformed
+/// with a hardcoded 8-point butterfly, which is applied to all 8 columns at once, so every step of the butterfly is an
+/// operation on a whole row of 8 elements, which the compiler turns into SIMD instructions. The block is then
+/// transposed in place, and the same butterfly transforms the rows. There are no calls to other DCT instances, and no
+/// scratch space. Finally, the coefficients are multiplied by a precomputed multiplier that folds the JPEG
+/// normalization and the division by the quantization step together, rounded, and written straight to their zigzag
+/// positions in the output.
+///
+/// Blocks are row-major, and should already be level shifted, so for 8-bit samples, each sample should have 128
+/// subtracted from it. The quantization table is in natural (row-major) order, not the zigzag order used by the DQT
+/// marker. Use [`JPEG_ZIGZAG`](constant.JPEG_ZIGZAG.html) to convert between the two.
+///
+/// ~~~
+/// // Encodes a 16x8 image, which is two JPEG blocks
+/// use rustdct::dct2d::Jpeg8x8Forward;
+///
+/// let quant_table = [16u16; 64];
+/// let jpeg = Jpeg8x8Forward::new(&quant_table);
+///
+/// let blocks = vec![-128f32; 128];
+/// let mut coefficients = vec![0i16; 128];
+/// jpeg.process_blocks(&blocks, &mut coefficients);
+/// assert_eq!(coefficients[0], -64);
+/// ~~~
+pub struct Jpeg8x8Forward<T> {
+    butterfly: Butterfly8<T>,
+
+    // for each coefficient in natural order, the JPEG normalization divided by the quantization step
+    multipliers: Box<[T]>,
+}
+
+impl<T: DctNum + ToPrimitive> Jpeg8x8Forward<T> {
+    /// Creates a new JPEG encoding context that quantizes the coefficients with `quant_table`, which is in natural
+    /// order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any entry of `quant_table` is 0.
+    pub fn new(quant_table: &[u16; 64]) -> Self {
+        validate_quant_table(quant_table);
+
+        // F(u, v) = C(u) * C(v) / 4 * DCT2(u, v), where C(0) = sqrt(1/2) and C(u) = 1 otherwise
+        let multipliers: Vec<T> = (0..BLOCK_LEN)
+            .map(|natural| {
+                let scale = jpeg_scale(natural) / 4.0 / f64::from(quant_table[natural]);
+                T::from_f64(scale).unwrap()
+            })
+            .collect();
+
+        Self {
+            butterfly: Butterfly8::new(),
+            multipliers: multipliers.into_boxed_slice(),
+        }
+    }
+
+    /// Encodes each 64-element block in `blocks`, and writes its quantized coefficients, in zigzag order, to the
+    /// corresponding 64 elements of `output`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `blocks.len()` isn't a multiple of 64, or if `output.len()` isn't equal to `blocks.len()`.
+    pub fn process_blocks(&self, blocks: &[T], output: &mut [i16]) {
+        validate_blocks(blocks.len(), output.len());
+
+        for (block, output) in blocks
+            .chunks_exact(BLOCK_LEN)
+            .zip(output.chunks_exact_mut(BLOCK_LEN))
+        {
+            let mut rows = [[T::zero(); BLOCK_SIZE]; BLOCK_SIZE];
+            for (row, input) in rows.iter_mut().zip(block.chunks_exact(BLOCK_SIZE)) {
+                row.copy_from_slice(input);
+            }
+
+            // transforming the columns leaves row v holding frequency v of every column. after transposing, row x
+            // holds column x, so transforming the columns again leaves row u holding horizontal frequency u
+            let mut coefficients = self.butterfly.dct2_columns(&rows);
+            transpose_in_place(&mut coefficients);
+            let coefficients = self.butterfly.dct2_columns(&coefficients);
+
+            for (u, column) in coefficients.iter().enumerate() {
+                for (v, coefficient) in column.iter().enumerate() {
+                    let natural = v * BLOCK_SIZE + u;
+                    output[ZIGZAG_POSITION[natural]] =
+                        quantize(*coefficient * self.multipliers[natural]);
+                }
+            }
+        }
+    }
+}
+
+/// Dequantizes zigzag-ordered 8x8 JPEG blocks, and computes their inverse DCTs.
+///
+/// This is the inverse of [`Jpeg8x8Forward`](struct.Jpeg8x8Forward.html), for JPEG decoders. The coefficients of each
+/// block are multiplied by their quantization step and by the JPEG normalization, and scattered from zigzag order into
+/// an 8x8 array on the stack, in a single pass. Then the transpose of the forward butterfly is applied to the columns
+/// and rows of the array, in the same way as the forward transform, and the samples are written to the output.
+///
+/// The output blocks are row-major and level shifted, so for 8-bit samples, 128 needs to be added to each sample,
+/// which then needs to be rounded and clamped to `0..=255`.
+///
+/// ~~~
+/// // Decodes a block whose only nonzero coefficient is the DC coefficient
+/// use rustdct::dct2d::Jpeg8x8Inverse;
+///
+/// let quant_table = [16u16; 64];
+/// let jpeg = Jpeg8x8Inverse::new(&quant_table);
+///
+/// let mut coefficients = vec![0i16; 64];
+/// coefficients[0] = -64;
+/// let mut block = vec![0f32; 64];
+/// jpeg.process_blocks(&coefficients, &mut block);
+/// assert!((block[10] + 128.0).abs() < 1e-3);
+/// ~~~
+pub struct Jpeg8x8Inverse<T> {
+    butterfly: Butterfly8<T>,
+
+    // for each coefficient in natural order, the quantization step times the JPEG normalization
+    multipliers: Box<[T]>,
+}
+
+impl<T: DctNum> Jpeg8x8Inverse<T> {
+    /// Creates a new JPEG decoding context that dequantizes the coefficients with `quant_table`, which is in natural
+    /// order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any entry of `quant_table` is 0.
+    pub fn new(quant_table: &[u16; 64]) -> Self {
+        validate_quant_table(quant_table);
+
+        // f(x, y) = 1/4 * sum over u and v of C(u) * C(v) * F(u, v) * cos((2x + 1)uπ/16) * cos((2y + 1)vπ/16)
+        let multipliers: Vec<T> = (0..BLOCK_LEN)
+            .map(|natural| {
+                let scale = jpeg_scale(natural) * f64::from(quant_table[natural]) / 4.0;
+                T::from_f64(scale).unwrap()
+            })
+            .collect();
+
+        Self {
+            butterfly: Butterfly8::new(),
+            multipliers: multipliers.into_boxed_slice(),
+        }
+    }
+
+    /// Decodes each 64-element block of zigzag-ordered coefficients in `coefficients`, and writes its samples to the
+    /// corresponding 64 elements of `output`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `coefficients.len()` isn't a multiple of 64, or if `output.len()` isn't equal to `coefficients.len()`.
+    pub fn process_blocks(&self, coefficients: &[i16], output: &mut [T]) {
+        validate_blocks(coefficients.len(), output.len());
+
+        for (coefficients, block) in coefficients
+            .chunks_exact(BLOCK_LEN)
+            .zip(output.chunks_exact_mut(BLOCK_LEN))
+        {
+            // row u holds horizontal frequency u, the reverse of the last step of the forward transform
+            let mut rows = [[T::zero(); BLOCK_SIZE]; BLOCK_SIZE];
+            for (coefficient, natural) in coefficients.iter().zip(JPEG_ZIGZAG.iter()) {
+                rows[natural % BLOCK_SIZE][natural / BLOCK_SIZE] =
+                    T::from_i16(*coefficient).unwrap() * self.multipliers[*natural];
+            }
+
+            let mut samples = self.butterfly.dct3_columns(&rows);
+            transpose_in_place(&mut samples);
+            let samples = self.butterfly.dct3_columns(&samples);
+
+            for (output, row) in block.chunks_exact_mut(BLOCK_SIZE).zip(samples.iter()) {
+                output.copy_from_slice(row);
+            }
+        }
+    }
+}
+
+// An unnormalized 8-point DCT2 and its transpose, the DCT3 without the halving of the first input, applied to the 8
+// columns of a block at once. Each step is a loop over the 8 lanes of a row, which the compiler vectorizes
+struct Butterfly8<T> {
+    // cos(kπ/16), for k in 0..8
+    twiddles: [T; BLOCK_SIZE],
+}
+
+impl<T: DctNum> Butterfly8<T> {
+    fn new() -> Self {
+        let mut twiddles = [T::zero(); BLOCK_SIZE];
+        for (k, twiddle) in twiddles.iter_mut().enumerate() {
+            *twiddle = T::from_f64((k as f64 * std::f64::consts::PI / 16.0).cos()).unwrap();
+        }
+        Self { twiddles }
+    }
+
+    // X(k) = sum over n of x(n) * cos((2n + 1)kπ/16). The even outputs are a 4-point DCT2 of x(n) + x(7 - n), and
+    // the odd outputs are a 4x4 matrix times x(n) - x(7 - n)
+    #[inline(always)]
+    fn dct2_columns(&self, input: &Block<T>) -> Block<T> {
+        let c = &self.twiddles;
+        let mut output = [[T::zero(); BLOCK_SIZE]; BLOCK_SIZE];
+        for lane in 0..BLOCK_SIZE {
+            let s0 = input[0][lane] + input[7][lane];
+            let s1 = input[1][lane] + input[6][lane];
+            let s2 = input[2][lane] + input[5][lane];
+            let s3 = input[3][lane] + input[4][lane];
+            let d0 = input[0][lane] - input[7][lane];
+            let d1 = input[1][lane] - input[6][lane];
+            let d2 = input[2][lane] - input[5][lane];
+            let d3 = input[3][lane] - input[4][lane];
+
+            let ss0 = s0 + s3;
+            let ss1 = s1 + s2;
+            let sd0 = s0 - s3;
+            let sd1 = s1 - s2;
+            output[0][lane] = ss0 + ss1;
+            output[4][lane] = (ss0 - ss1) * c[4];
+            output[2][lane] = sd0 * c[2] + sd1 * c[6];
+            output[6][lane] = sd0 * c[6] - sd1 * c[2];
+
+            output[1][lane] = d0 * c[1] + d1 * c[3] + d2 * c[5] + d3 * c[7];
+            output[3][lane] = d0 * c[3] - d1 * c[7] - d2 * c[1] - d3 * c[5];
+            output[5][lane] = d0 * c[5] - d1 * c[1] + d2 * c[7] + d3 * c[3];
+            output[7][lane] = d0 * c[7] - d1 * c[5] + d2 * c[3] - d3 * c[1];
+        }
+        output
+    }
+
+    // x(n) = sum over k of X(k) * cos((2n + 1)kπ/16), which is each step of `dct2_columns` transposed, in reverse
+    // order
+    #[inline(always)]
+    fn dct3_columns(&self, input: &Block<T>) -> Block<T> {
+        let c = &self.twiddles;
+        let mut output = [[T::zero(); BLOCK_SIZE]; BLOCK_SIZE];
+        for lane in 0..BLOCK_SIZE {
+            let x = |k: usize| input[k][lane];
+
+            let ss0 = x(0) + x(4) * c[4];
+            let ss1 = x(0) - x(4) * c[4];
+            let sd0 = x(2) * c[2] + x(6) * c[6];
+            let sd1 = x(2) * c[6] - x(6) * c[2];
+            let s0 = ss0 + sd0;
+            let s3 = ss0 - sd0;
+            let s1 = ss1 + sd1;
+            let s2 = ss1 - sd1;
+
+            let d0 = x(1) * c[1] + x(3) * c[3] + x(5) * c[5] + x(7) * c[7];
+            let d1 = x(1) * c[3] - x(3) * c[7] - x(5) * c[1] - x(7) * c[5];
+            let d2 = x(1) * c[5] - x(3) * c[1] + x(5) * c[7] + x(7) * c[3];
+            let d3 = x(1) * c[7] - x(3) * c[5] + x(5) * c[3] - x(7) * c[1];
+
+            output[0][lane] = s0 + d0;
+            output[7][lane] = s0 - d0;
+            output[1][lane] = s1 + d1;
+            output[6][lane] = s1 - d1;
+            output[2][lane] = s2 + d2;
+            output[5][lane] = s2 - d2;
+            output[3][lane] = s3 + d3;
+            output[4][lane] = s3 - d3;
+        }
+        output
+    }
+}
+
+#[inline(always)]
+fn transpose_in_place<T: Copy>(block: &mut Block<T>) {
+    // swap each element below the diagonal with its mirror above the diagonal
+    for y in 1..BLOCK_SIZE {
+        let (above, below) = block.split_at_mut(y);
+        for (element, above_row) in below[0].iter_mut().zip(above.iter_mut()) {
+            std::mem::swap(element, &mut above_row[y]);
+        }
+    }
+}
+
+// The zigzag position of each coefficient in natural order: The inverse of `JPEG_ZIGZAG`
+const ZIGZAG_POSITION: [usize; 64] = [
+    0, 1, 5, 6, 14, 15, 27, 28, 2, 4, 7, 13, 16, 26, 29, 42, 3, 8, 12, 17, 25, 30, 41, 43, 9, 11,
+    18, 24, 31, 40, 44, 53, 10, 19, 23, 32, 39, 45, 52, 54, 20, 22, 33, 38, 46, 51, 55, 60, 21, 34,
+    37, 47, 50, 56, 59, 61, 35, 36, 48, 49, 57, 58, 62, 63,
+];
+
+// Returns C(u) * C(v) for the coefficient at natural index `natural`, where C(0) = sqrt(1/2) and C(u) = 1 otherwise
+fn jpeg_scale(natural: usize) -> f64 {
+    let c = |index: usize| {
+        if index == 0 {
+            std::f64::consts::FRAC_1_SQRT_2
+        } else {
+            1.0
+        }
+    };
+    c(natural / BLOCK_SIZE) * c(natural % BLOCK_SIZE)
+}
+
+// Rounds a quantized coefficient to the nearest integer, with ties away from zero, saturating at the limits of i16.
+// NaN becomes 0. The clamping has to be explicit, because before Rust 1.45, casting an out-of-range float to an integer
+// was undefined behavior. Rounding by truncating `value ± 0.5` avoids `f64::round`, which is a libm call on targets
+// without SSE4.1
+fn quantize<T: DctNum + ToPrimitive>(value: T) -> i16 {
+    let value = match value.to_f64() {
+        Some(value) if !value.is_nan() => value,
+        _ => return 0,
+    };
+    if value >= f64::from(std::i16::MAX) {
+        std::i16::MAX
+    } else if value <= f64::from(std::i16::MIN) {
+        std::i16::MIN
+    } else if value < 0.0 {
+        (value - 0.5) as i16
+    } else {
+        (value + 0.5) as i16
+    }
+}
+
+fn validate_quant_table(quant_table: &[u16; 64]) {
+    assert!(
+        quant_table.iter().all(|step| *step > 0),
+        "Every entry of a JPEG quantization table must be at least 1"
+    );
+}
+
+fn validate_blocks(input_len: usize, output_len: usize) {
+    assert!(
+        input_len % BLOCK_LEN == 0 && input_len == output_len,
+        "JPEG blocks must have a multiple of 64 elements, and the input and output must have the same length. Got input len = {}, output len = {}",
+        input_len,
+        output_len
+    );
+}
+
+impl<T: DctNum> MemoryUsage for Jpeg8x8Forward<T> {
+    fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            twiddles_bytes: table_bytes(&self.multipliers),
+            ..PlanMemory::default()
+        }
+    }
+}
+impl<T: DctNum> MemoryUsage for Jpeg8x8Inverse<T> {
+    fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            twiddles_bytes: table_bytes(&self.multipliers),
+            ..PlanMemory::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::test_utils::random_signal;
+    use std::f64::consts::PI;
+
+    // The JPEG forward DCT of a block, in natural order, straight from the definition in the JPEG standard
+    fn reference_fdct(block: &[f32]) -> Vec<f64> {
+        let c = |index: usize| if index == 0 { 0.5f64.sqrt() } else { 1.0 };
+        let mut output = vec![0f64; 64];
+        for v in 0..8 {
+            for u in 0..8 {
+                let mut sum = 0.0;
+                for y in 0..8 {
+                    for x in 0..8 {
+                        sum += block[y * 8 + x] as f64
+                            * ((2 * x + 1) as f64 * u as f64 * PI / 16.0).cos()
+                            * ((2 * y + 1) as f64 * v as f64 * PI / 16.0).cos();
+                    }
+                }
+                output[v * 8 + u] = c(u) * c(v) / 4.0 * sum;
+            }
+        }
+        output
+    }
+
+    fn quant_table() -> [u16; 64] {
+        let mut table = [0u16; 64];
+        for (i, step) in table.iter_mut().enumerate() {
+            *step = 1 + (i as u16 * 7) % 23;
+        }
+        table
+    }
+
+    #[test]
+    fn test_zigzag_tables() {
+        for (position, natural) in JPEG_ZIGZAG.iter().enumerate() {
+            assert_eq!(ZIGZAG_POSITION[*natural], position);
+        }
+    }
+
+    /// Verify that the fused forward transform matches the JPEG definition, quantized and reordered
+    #[test]
+    fn test_jpeg_forward() {
+        let quant_table = quant_table();
+        let jpeg = Jpeg8x8Forward::new(&quant_table);
+
+        let input: Vec<f32> = random_signal(192)
+            .iter()
+            .map(|x| x * 25.0 - 128.0)
+            .collect();
+        let mut output = vec![0i16; 192];
+        jpeg.process_blocks(&input, &mut output);
+
+        for (block, output) in input.chunks_exact(64).zip(output.chunks_exact(64)) {
+            let expected = reference_fdct(block);
+            for (position, natural) in JPEG_ZIGZAG.iter().enumerate() {
+                let quantized = expected[*natural] / quant_table[*natural] as f64;
+                assert!(
+                    (output[position] as f64 - quantized).abs() <= 0.5 + 1e-3,
+                    "position = {}, expected = {}, actual = {}",
+                    position,
+                    quantized,
+                    output[position]
+                );
+            }
+        }
+    }
+
+    /// Verify that the inverse undoes the forward transform, up to the quantization error
+    #[test]
+    fn test_jpeg_roundtrip() {
+        let quant_table = [1u16; 64];
+        let forward = Jpeg8x8Forward::new(&quant_table);
+        let inverse = Jpeg8x8Inverse::new(&quant_table);
+
+        let input: Vec<f32> = random_signal(128)
+            .iter()
+            .map(|x| x * 25.0 - 128.0)
+            .collect();
+        let mut coefficients = vec![0i16; 128];
+        forward.process_blocks(&input, &mut coefficients);
+
+        let mut output = vec![0f32; 128];
+        inverse.process_blocks(&coefficients, &mut output);
+
+        // each coefficient is off by at most 0.5, and the transform is orthonormal, so the error's energy is at most
+        // 0.25 per sample
+        let error_energy: f32 = input
+            .iter()
+            .zip(output.iter())
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum();
+        assert!(
+            error_energy <= 0.25 * 128.0,
+            "error energy = {}",
+            error_energy
+        );
+    }
+
+    #[test]
+    fn test_quantize_saturates() {
+        assert_eq!(quantize(2.5f32), 3);
+        assert_eq!(quantize(-2.4f32), -2);
+        assert_eq!(quantize(1e9f32), std::i16::MAX);
+        assert_eq!(quantize(-1e9f32), std::i16::MIN);
+        assert_eq!(quantize(std::f32::INFINITY), std::i16::MAX);
+        assert_eq!(quantize(std::f32::NAN), 0);
+    }
+}
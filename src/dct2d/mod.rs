@@ -0,0 +1,17 @@
+//! Algorithms for computing two-dimensional and multidimensional DCTs, built on top of the one-dimensional DCT instances
+
+mod axis;
+// The `image` feature requires a newer compiler than the rest of the crate
+#[cfg(feature = "image")]
+#[allow(clippy::incompatible_msrv)]
+mod image_plane;
+mod jpeg;
+mod square;
+mod tile;
+
+pub use self::axis::{AxisStrategy, DctAxis};
+#[cfg(feature = "image")]
+pub use self::image_plane::Dct2dImage;
+pub use self::jpeg::{Jpeg8x8Forward, Jpeg8x8Inverse, JPEG_ZIGZAG};
+pub use self::square::Dct2dSquare;
+pub use self::tile::Dct2dTile;
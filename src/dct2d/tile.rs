@@ -0,0 +1,299 @@
+use std::sync::Arc;
+
+use rustfft::Length;
+
+use crate::common::offset_complex_scratch_len;
+use crate::{DctNum, RequiredScratch, ScratchLayout, TransformType2And3};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+
+/// Computes 2D DCT2s and DCT3s of square tiles inside of a larger row-major image, in place.
+///
+/// The tile size is the length of the provided inner DCT instance, so for example, an inner DCT of size 8 will process
+/// 8x8 tiles. Each row of the tile is transformed directly in the image buffer, and each column is gathered into
+/// scratch space, transformed, and written back, so the tile never has to be copied out of the image.
+///
+/// ~~~
+/// // Computes the 2D DCT2 of the 8x8 tile in the second tile row and third tile column of a 64x32 image
+/// use rustdct::dct2d::Dct2dTile;
+/// use rustdct::DctPlanner;
+///
+/// let (width, height) = (64, 32);
+/// let mut image = vec![0f32; width * height];
+///
+/// let mut planner = DctPlanner::new();
+/// let tile_dct = Dct2dTile::new(planner.plan_dct2(8));
+///
+/// tile_dct.process_dct2_2d_tile(&mut image, width, 2, 1);
+/// ~~~
+pub struct Dct2dTile<T> {
+    dct: Arc<dyn TransformType2And3<T>>,
+}
+
+impl<T: DctNum> Dct2dTile<T> {
+    /// Creates a new 2D tile context that will process tiles of size `inner_dct.len()` x `inner_dct.len()`
+    pub fn new(inner_dct: Arc<dyn TransformType2And3<T>>) -> Self {
+        Self { dct: inner_dct }
+    }
+
+    /// Computes the 2D DCT Type 2 of the tile at tile coordinates (`tile_x`, `tile_y`), in-place.
+    ///
+    /// `image` is a row-major image where each row begins `image_stride` elements after the previous one. The tile's
+    /// top left pixel is at column `tile_x * self.len()`, row `tile_y * self.len()`. Pixels outside the tile aren't modified.
+    ///
+    /// This method allocates a Vec<T> of scratch space. If you'd like to reuse that allocation between
+    /// multiple computations, consider calling `process_dct2_2d_tile_with_scratch` instead.
+    ///
+    /// Does not normalize outputs.
+    pub fn process_dct2_2d_tile(
+        &self,
+        image: &mut [T],
+        image_stride: usize,
+        tile_x: usize,
+        tile_y: usize,
+    ) {
+        let mut scratch = vec![T::zero(); self.get_scratch_len()];
+        self.process_dct2_2d_tile_with_scratch(image, image_stride, tile_x, tile_y, &mut scratch);
+    }
+
+    /// Computes the 2D DCT Type 2 of the tile at tile coordinates (`tile_x`, `tile_y`), in-place.
+    /// Uses the provided `scratch` buffer as scratch space.
+    ///
+    /// See [`process_dct2_2d_tile`](#method.process_dct2_2d_tile) for a description of the image layout.
+    ///
+    /// Does not normalize outputs.
+    pub fn process_dct2_2d_tile_with_scratch(
+        &self,
+        image: &mut [T],
+        image_stride: usize,
+        tile_x: usize,
+        tile_y: usize,
+        scratch: &mut [T],
+    ) {
+        self.process_tile(
+            image,
+            image_stride,
+            tile_x,
+            tile_y,
+            scratch,
+            |dct, buffer, scratch| dct.process_dct2_with_scratch(buffer, scratch),
+        );
+    }
+
+    /// Computes the 2D DCT Type 3 of the tile at tile coordinates (`tile_x`, `tile_y`), in-place.
+    ///
+    /// See [`process_dct2_2d_tile`](#method.process_dct2_2d_tile) for a description of the image layout.
+    ///
+    /// This method allocates a Vec<T> of scratch space. If you'd like to reuse that allocation between
+    /// multiple computations, consider calling `process_dct3_2d_tile_with_scratch` instead.
+    ///
+    /// Does not normalize outputs.
+    pub fn process_dct3_2d_tile(
+        &self,
+        image: &mut [T],
+        image_stride: usize,
+        tile_x: usize,
+        tile_y: usize,
+    ) {
+        let mut scratch = vec![T::zero(); self.get_scratch_len()];
+        self.process_dct3_2d_tile_with_scratch(image, image_stride, tile_x, tile_y, &mut scratch);
+    }
+
+    /// Computes the 2D DCT Type 3 of the tile at tile coordinates (`tile_x`, `tile_y`), in-place.
+    /// Uses the provided `scratch` buffer as scratch space.
+    ///
+    /// See [`process_dct2_2d_tile`](#method.process_dct2_2d_tile) for a description of the image layout.
+    ///
+    /// Does not normalize outputs.
+    pub fn process_dct3_2d_tile_with_scratch(
+        &self,
+        image: &mut [T],
+        image_stride: usize,
+        tile_x: usize,
+        tile_y: usize,
+        scratch: &mut [T],
+    ) {
+        self.process_tile(
+            image,
+            image_stride,
+            tile_x,
+            tile_y,
+            scratch,
+            |dct, buffer, scratch| dct.process_dct3_with_scratch(buffer, scratch),
+        );
+    }
+
+    fn process_tile<F>(
+        &self,
+        image: &mut [T],
+        image_stride: usize,
+        tile_x: usize,
+        tile_y: usize,
+        scratch: &mut [T],
+        process_fn: F,
+    ) where
+        F: Fn(&dyn TransformType2And3<T>, &mut [T], &mut [T]),
+    {
+        let tile_size = self.len();
+        let first_column = tile_x * tile_size;
+        let first_row = tile_y * tile_size;
+
+        assert!(
+            first_column + tile_size <= image_stride,
+            "Tile extends past the end of the image row. Tile columns = {}..{}, image stride = {}",
+            first_column,
+            first_column + tile_size,
+            image_stride
+        );
+        assert!(
+            (first_row + tile_size - 1) * image_stride + first_column + tile_size <= image.len(),
+            "Tile extends past the end of the image. Tile rows = {}..{}, image len = {}, image stride = {}",
+            first_row,
+            first_row + tile_size,
+            image.len(),
+            image_stride
+        );
+        assert!(
+            scratch.len() >= self.get_scratch_len(),
+            "Not enough scratch space was provided. Expected scratch len >= {}, got scratch len = {}",
+            self.get_scratch_len(),
+            scratch.len()
+        );
+
+        let (column_buffer, inner_scratch) = scratch.split_at_mut(tile_size);
+        let tile_start = first_row * image_stride + first_column;
+
+        // the rows are contiguous, so we can process them directly in the image buffer
+        for row in 0..tile_size {
+            let row_start = tile_start + row * image_stride;
+            process_fn(
+                self.dct.as_ref(),
+                &mut image[row_start..row_start + tile_size],
+                inner_scratch,
+            );
+        }
+
+        // the columns are strided, so gather each one into scratch, process it, and scatter it back
+        for column in 0..tile_size {
+            let column_start = tile_start + column;
+            for (row, element) in column_buffer.iter_mut().enumerate() {
+                *element = image[column_start + row * image_stride];
+            }
+
+            process_fn(self.dct.as_ref(), column_buffer, inner_scratch);
+
+            for (row, element) in column_buffer.iter().enumerate() {
+                image[column_start + row * image_stride] = *element;
+            }
+        }
+    }
+}
+impl<T> Length for Dct2dTile<T> {
+    fn len(&self) -> usize {
+        self.dct.len()
+    }
+}
+impl<T: DctNum> MemoryUsage for Dct2dTile<T> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            inner_plans_bytes: visitor.inner_plan(&self.dct),
+            ..PlanMemory::default()
+        }
+    }
+}
+impl<T> RequiredScratch for Dct2dTile<T> {
+    fn get_scratch_len(&self) -> usize {
+        self.dct.len() + self.dct.get_scratch_len()
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        offset_complex_scratch_len(self.dct.len(), self.dct.get_complex_scratch_len())
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        ScratchLayout::new()
+            .with_region("column buffer", self.dct.len(), false)
+            .with_inner(self.dct.scratch_layout())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::algorithm::Type2And3Naive;
+    use crate::test_utils::{compare_float_vectors, random_signal};
+    use crate::{Dct2, Dct3};
+
+    // Computes a 2D transform of a standalone square tile, by transforming the rows, transposing, transforming the rows again, and transposing back
+    fn reference_2d(tile: &mut [f32], size: usize, process_fn: &dyn Fn(&mut [f32])) {
+        for row in tile.chunks_exact_mut(size) {
+            process_fn(row);
+        }
+        let mut transposed = vec![0f32; size * size];
+        for row in 0..size {
+            for column in 0..size {
+                transposed[column * size + row] = tile[row * size + column];
+            }
+        }
+        for row in transposed.chunks_exact_mut(size) {
+            process_fn(row);
+        }
+        for row in 0..size {
+            for column in 0..size {
+                tile[column * size + row] = transposed[row * size + column];
+            }
+        }
+    }
+
+    #[test]
+    fn test_tile_matches_reference() {
+        for &size in &[4, 8, 16] {
+            let (tile_x, tile_y) = (1, 2);
+            let image_stride = size * 3 + 5;
+            let image_height = size * 4;
+
+            let input = random_signal(image_stride * image_height);
+            let naive = Type2And3Naive::new(size);
+            let tile_dct = Dct2dTile::new(Arc::new(Type2And3Naive::new(size)));
+
+            for &is_dct2 in &[true, false] {
+                let mut expected = input.clone();
+                let mut tile = vec![0f32; size * size];
+                for row in 0..size {
+                    let start = (tile_y * size + row) * image_stride + tile_x * size;
+                    tile[row * size..(row + 1) * size]
+                        .copy_from_slice(&expected[start..start + size]);
+                }
+                if is_dct2 {
+                    reference_2d(&mut tile, size, &|row| naive.process_dct2(row));
+                } else {
+                    reference_2d(&mut tile, size, &|row| naive.process_dct3(row));
+                }
+                for row in 0..size {
+                    let start = (tile_y * size + row) * image_stride + tile_x * size;
+                    expected[start..start + size]
+                        .copy_from_slice(&tile[row * size..(row + 1) * size]);
+                }
+
+                let mut actual = input.clone();
+                if is_dct2 {
+                    tile_dct.process_dct2_2d_tile(&mut actual, image_stride, tile_x, tile_y);
+                } else {
+                    tile_dct.process_dct3_2d_tile(&mut actual, image_stride, tile_x, tile_y);
+                }
+
+                assert!(
+                    compare_float_vectors(&expected, &actual),
+                    "size = {}, dct2 = {}",
+                    size,
+                    is_dct2
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_tile_out_of_bounds() {
+        let tile_dct = Dct2dTile::new(Arc::new(Type2And3Naive::new(8)));
+        let mut image = vec![0f32; 16 * 16];
+        tile_dct.process_dct2_2d_tile(&mut image, 16, 2, 0);
+    }
+}
@@ -0,0 +1,40 @@
+/// The definition of the DCT3 that a plan from `DctPlanner::plan_dct3_with_convention` follows.
+///
+/// This crate's DCT3 of `x` is `sum(x[n] * cos(pi * n * (k + 0.5) / len))`, with the first input weighted by one half.
+/// Other libraries use the same basis functions in the same order, but weight the first input differently, or scale
+/// every output. Planning with a convention folds that weighting into the transform, so results can be compared with
+/// the other library's directly.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Dct3Convention {
+    /// This crate's definition, which is the one given by Wang, with the first input weighted by one half. A plan with
+    /// this convention is the same instance that `plan_dct3` returns. This crate's DCT2 is its inverse, up to a scale
+    /// of `2 / len`.
+    Wang,
+    /// The definition found in most textbooks, without any weighting of the first input:
+    /// `sum(x[n] * cos(pi * n * (k + 0.5) / len))` over every input.
+    Unweighted,
+    /// FFTW's `FFTW_REDFT01`, which weights the first input by one, and every other input by two. This is twice this
+    /// crate's DCT3.
+    Fftw,
+    /// SciPy's `scipy.fft.dct` with `type=3`, and the default `norm="backward"`. This matches FFTW's definition exactly.
+    Scipy,
+    /// MATLAB's `idct`, which is orthonormal: It weights the first input by `sqrt(1 / len)` and every other input by
+    /// `sqrt(2 / len)`, so it's the exact inverse of MATLAB's `dct`. This is also SciPy's `norm="ortho"`.
+    Matlab,
+}
+
+impl Dct3Convention {
+    // Returns the factors that turn a DCT3 input in this convention into an input to this crate's DCT3: The first
+    // factor applies to the first input, and the second factor applies to every other input. Returns None for `Wang`.
+    // The DCT3 is linear, so scaling its input is the same as scaling its output
+    pub(crate) fn dct3_input_scales(self, len: usize) -> Option<(f64, f64)> {
+        let len = len as f64;
+        match self {
+            Dct3Convention::Wang => None,
+            // this crate's DCT3 weights the first input by one half, so doubling it undoes the weighting
+            Dct3Convention::Unweighted => Some((2.0, 1.0)),
+            Dct3Convention::Fftw | Dct3Convention::Scipy => Some((2.0, 2.0)),
+            Dct3Convention::Matlab => Some((2.0 / len.sqrt(), (2.0 / len).sqrt())),
+        }
+    }
+}
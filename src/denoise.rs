@@ -0,0 +1,215 @@
+//! Denoising by thresholding DCT coefficients.
+//!
+//! Smooth signals concentrate their energy in a few large DCT coefficients, while white noise spreads evenly over all
+//! of them. So zeroing the coefficients below a threshold, and transforming back, removes most of the noise and keeps
+//! most of the signal. The functions in this module do that with a [`Type2And3Pair`](../struct.Type2And3Pair.html),
+//! filtering the coefficients in the same pass that scales them.
+//!
+//! The threshold is always compared against orthonormal coefficients, whatever the pair's normalization. With an
+//! orthonormal DCT, white noise with standard deviation `sigma` has coefficients with the same standard deviation, so
+//! the classic choice of threshold, a small multiple of `sigma` like `3 * sigma`, works with every pair.
+//!
+//! ~~~
+//! // Removes noise from a slow cosine
+//! use rustdct::denoise::dct_threshold;
+//! use rustdct::{DctPlanner, Normalization};
+//!
+//! let len = 256;
+//! let mut planner = DctPlanner::new();
+//! let pair = planner.plan_dct2_pair(len, Normalization::Inverse);
+//!
+//! let mut signal: Vec<f64> = (0..len)
+//!     .map(|i| (i as f64 * 0.05).cos() + if i % 2 == 0 { 0.1 } else { -0.1 })
+//!     .collect();
+//! dct_threshold(&mut signal, 0.5, &pair);
+//! ~~~
+
+use rustfft::num_traits::Float;
+use rustfft::Length;
+
+use crate::{DctNum, Normalization, RequiredScratch, Type2And3Pair};
+
+/// How [`dct_threshold_with_scratch`](fn.dct_threshold_with_scratch.html) treats the coefficients it keeps.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Thresholding {
+    /// Coefficients whose magnitude is below the threshold are set to zero, and the rest are kept unchanged.
+    Hard,
+    /// Coefficients whose magnitude is below the threshold are set to zero, and the rest are moved towards zero by
+    /// the threshold. This avoids the discontinuity of hard thresholding at the threshold, which can cause ringing,
+    /// but it also shrinks the signal's large coefficients.
+    Soft,
+}
+
+/// Denoises `signal` in-place with hard thresholding: Every DCT coefficient except the first, whose orthonormal
+/// magnitude is below `threshold`, is set to zero.
+///
+/// The first coefficient is the signal's mean, which isn't affected by zero-mean noise, so it's always kept.
+///
+/// This method allocates a Vec<T> of scratch space. If you'd like to reuse that allocation between multiple
+/// computations, consider calling `dct_threshold_with_scratch` instead.
+///
+/// # Panics
+///
+/// Panics if `signal.len()` isn't equal to `pair.len()`.
+pub fn dct_threshold<T: DctNum + Float>(signal: &mut [T], threshold: T, pair: &Type2And3Pair<T>) {
+    let mut scratch = vec![T::zero(); pair.get_scratch_len()];
+    dct_threshold_with_scratch(signal, threshold, Thresholding::Hard, pair, &mut scratch);
+}
+
+/// Denoises `signal` in-place with soft thresholding: Every DCT coefficient except the first, whose orthonormal
+/// magnitude is below `threshold`, is set to zero, and the others are moved towards zero by `threshold`.
+///
+/// This method allocates a Vec<T> of scratch space. If you'd like to reuse that allocation between multiple
+/// computations, consider calling `dct_threshold_with_scratch` instead.
+///
+/// # Panics
+///
+/// Panics if `signal.len()` isn't equal to `pair.len()`.
+pub fn dct_soft_threshold<T: DctNum + Float>(
+    signal: &mut [T],
+    threshold: T,
+    pair: &Type2And3Pair<T>,
+) {
+    let mut scratch = vec![T::zero(); pair.get_scratch_len()];
+    dct_threshold_with_scratch(signal, threshold, Thresholding::Soft, pair, &mut scratch);
+}
+
+/// Denoises `signal` in-place by thresholding its DCT coefficients with `thresholding`. Uses the provided `scratch`
+/// buffer as scratch space, which must have a length of at least `pair.get_scratch_len()`.
+///
+/// # Panics
+///
+/// Panics if `signal.len()` isn't equal to `pair.len()`, or if `scratch` is too short.
+pub fn dct_threshold_with_scratch<T: DctNum + Float>(
+    signal: &mut [T],
+    threshold: T,
+    thresholding: Thresholding,
+    pair: &Type2And3Pair<T>,
+    scratch: &mut [T],
+) {
+    assert_eq!(
+        signal.len(),
+        pair.len(),
+        "dct_threshold requires a signal of the pair's length"
+    );
+
+    // `process_filtered` passes coefficients scaled like the pair's forward output, so convert the threshold to that
+    // scale. An orthonormal coefficient is the unnormalized one times sqrt(2 / len), or sqrt(1 / len) for the first
+    let threshold = match pair.normalization() {
+        Normalization::Orthonormal => threshold,
+        Normalization::Unnormalized | Normalization::Inverse => {
+            let len = T::from_usize(signal.len()).unwrap();
+            threshold * (len / T::two()).sqrt()
+        }
+    };
+
+    pair.process_filtered(signal, scratch, |index, coefficient| {
+        if index == 0 {
+            coefficient
+        } else if coefficient.abs() < threshold {
+            T::zero()
+        } else {
+            match thresholding {
+                Thresholding::Hard => coefficient,
+                Thresholding::Soft => coefficient - threshold * coefficient.signum(),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::DctPlanner;
+
+    // A signal made of three orthonormal DCT basis vectors, plus uniform noise in [-0.1, 0.1]
+    fn noisy_signal(planner: &mut DctPlanner<f64>, len: usize) -> (Vec<f64>, Vec<f64>) {
+        let mut clean = vec![0.0; len];
+        clean[0] = 20.0;
+        clean[1] = 10.0;
+        clean[5] = -6.0;
+        planner
+            .plan_dct2_pair(len, Normalization::Orthonormal)
+            .inverse(&mut clean);
+
+        let mut state = 0x2545_f491u32;
+        let noisy = clean
+            .iter()
+            .map(|&sample| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                sample + (state as f64 / u32::MAX as f64 - 0.5) * 0.2
+            })
+            .collect();
+        (clean, noisy)
+    }
+
+    fn close(a: &[f64], b: &[f64]) -> bool {
+        a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-9)
+    }
+
+    fn error(a: &[f64], b: &[f64]) -> f64 {
+        a.iter()
+            .zip(b)
+            .map(|(x, y)| (x - y) * (x - y))
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    #[test]
+    fn test_threshold_same_for_every_normalization() {
+        let len = 100;
+        let mut planner = DctPlanner::new();
+        let (_, noisy) = noisy_signal(&mut planner, len);
+
+        for &thresholding in &[Thresholding::Hard, Thresholding::Soft] {
+            let mut outputs = Vec::new();
+            for &normalization in &[
+                Normalization::Unnormalized,
+                Normalization::Inverse,
+                Normalization::Orthonormal,
+            ] {
+                let pair = planner.plan_dct2_pair(len, normalization);
+                let mut signal = noisy.clone();
+                let mut scratch = vec![0.0; pair.get_scratch_len()];
+                dct_threshold_with_scratch(&mut signal, 0.3, thresholding, &pair, &mut scratch);
+                outputs.push(signal);
+            }
+
+            // the unnormalized pair doesn't scale the inverse, so compare it after undoing that
+            let unnormalized: Vec<f64> = outputs[0].iter().map(|x| x * 2.0 / len as f64).collect();
+            assert!(close(&unnormalized, &outputs[1]));
+            assert!(close(&outputs[1], &outputs[2]));
+        }
+    }
+
+    #[test]
+    fn test_threshold_reduces_noise() {
+        let len = 128;
+        let mut planner = DctPlanner::new();
+        let (clean, noisy) = noisy_signal(&mut planner, len);
+        let pair = planner.plan_dct2_pair(len, Normalization::Inverse);
+
+        // the noise has a standard deviation of about 0.06, in the signal and in each orthonormal coefficient, so a
+        // threshold of 0.5 removes it from every coefficient except the three that make up the signal
+        let mut hard = noisy.clone();
+        dct_threshold(&mut hard, 0.5, &pair);
+        assert!(error(&hard, &clean) < 0.2 * error(&noisy, &clean));
+
+        // soft thresholding also shrinks the two large coefficients by the threshold
+        let mut soft = noisy.clone();
+        dct_soft_threshold(&mut soft, 0.5, &pair);
+        assert!(error(&soft, &clean) < 0.5 * 2f64.sqrt() + 0.2 * error(&noisy, &clean));
+
+        // a threshold of zero keeps every coefficient, and the mean is always kept
+        let mut unchanged = noisy.clone();
+        dct_threshold(&mut unchanged, 0.0, &pair);
+        assert!(close(&unchanged, &noisy));
+
+        let mut mean_only = noisy.clone();
+        dct_threshold(&mut mean_only, 1e9, &pair);
+        let mean = noisy.iter().sum::<f64>() / len as f64;
+        assert!(mean_only.iter().all(|&x| (x - mean).abs() < 1e-9));
+    }
+}
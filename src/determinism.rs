@@ -0,0 +1,211 @@
+//! Checks whether the instances from
+//! [`DctPlanner::new_deterministic`](../struct.DctPlanner.html#method.new_deterministic) reproduce a set of reference
+//! outputs bit for bit on the current platform.
+//!
+//! [`fingerprint`](fn.fingerprint.html) computes every transform type, at sizes that cover each of the planner's
+//! algorithms and the FFT algorithms they're converted to, in `f32` and `f64`, and hashes the bits of the outputs.
+//! [`self_test`](fn.self_test.html) compares the hash with [`REFERENCE_FINGERPRINT`](constant.REFERENCE_FINGERPRINT.html),
+//! which was computed on x86_64 Linux with RustFFT 6.4.1.
+//!
+//! The outputs depend on the twiddle factors, which are computed with the platform's `sin` and `cos`, and on the FFT
+//! algorithms that RustFFT chooses, which can change between RustFFT releases. So a failing self test doesn't always
+//! mean that a platform's arithmetic differs: It can also mean that the math library or the RustFFT version differs
+//! from the reference. This check is opt-in, for applications that pin their dependencies and want to verify a target,
+//! for example in CI. Applications that compare outputs between machines should pin the same RustFFT version everywhere.
+//!
+//! ~~~no_run
+//! use rustdct::determinism;
+//!
+//! assert!(determinism::self_test(), "this platform doesn't reproduce the reference outputs");
+//! ~~~
+
+use crate::mdct::window_fn;
+use crate::{DctNum, DctPlanner, RequiredScratch};
+
+/// The fingerprint of the reference outputs, computed on x86_64 Linux with RustFFT 6.4.1.
+pub const REFERENCE_FINGERPRINT: u64 = 0x811a_7a88_ebb6_d0c4;
+
+/// Returns true if this platform reproduces the reference outputs of the deterministic planner's instances bit for bit,
+/// which means that `fingerprint()` is equal to `REFERENCE_FINGERPRINT`. See the [module documentation](index.html) for
+/// why it can return false.
+pub fn self_test() -> bool {
+    fingerprint() == REFERENCE_FINGERPRINT
+}
+
+/// Computes a hash of the outputs of instances from deterministic `f32` and `f64` planners, for fixed inputs.
+pub fn fingerprint() -> u64 {
+    let mut hash = Fingerprint::new();
+    hash_plans(
+        &mut DctPlanner::<f32>::new_deterministic(),
+        &mut hash,
+        |value| value.to_bits() as u64,
+    );
+    hash_plans(
+        &mut DctPlanner::<f64>::new_deterministic(),
+        &mut hash,
+        f64::to_bits,
+    );
+    hash.finish()
+}
+
+// 64-bit FNV-1a, which is simple enough to be the same on every platform and every Rust version, unlike `DefaultHasher`
+struct Fingerprint(u64);
+impl Fingerprint {
+    fn new() -> Self {
+        Fingerprint(0xcbf2_9ce4_8422_2325)
+    }
+    fn write(&mut self, value: u64) {
+        for byte in value.to_le_bytes().iter() {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+// Returns `len` inputs from -1 to 1 with a fixed pseudorandom pattern. They're multiples of 1/1024, so they're exactly
+// representable as f32
+fn fixed_signal<T: DctNum>(len: usize) -> Vec<T> {
+    let mut state: u32 = 0x1234_5678;
+    (0..len)
+        .map(|_| {
+            state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+            let value = (state >> 16) % 2049;
+            T::from_f64(value as f64 / 1024.0 - 1.0).unwrap()
+        })
+        .collect()
+}
+
+// Runs `process` on a fixed signal of size `len` with enough scratch, and adds its output to the hash
+fn hash_transform<T: DctNum>(
+    hash: &mut Fingerprint,
+    to_bits: &impl Fn(T) -> u64,
+    len: usize,
+    scratch_len: usize,
+    process: impl Fn(&mut [T], &mut [T]),
+) {
+    let mut buffer = fixed_signal(len);
+    let mut scratch = vec![T::zero(); scratch_len];
+    process(&mut buffer, &mut scratch);
+
+    hash.write(len as u64);
+    for value in buffer {
+        hash.write(to_bits(value));
+    }
+}
+
+fn hash_plans<T: DctNum>(
+    planner: &mut DctPlanner<T>,
+    hash: &mut Fingerprint,
+    to_bits: impl Fn(T) -> u64,
+) {
+    // butterflies, split radix, real FFTs, and FFTs of sizes with small factors, a prime factor, and a large prime
+    let type2and3_lens = [
+        1, 2, 3, 4, 5, 8, 16, 17, 30, 64, 97, 100, 210, 256, 1000, 1031,
+    ];
+    for &len in type2and3_lens.iter() {
+        let dct = planner.plan_dct2(len);
+        let scratch_len = dct.get_scratch_len();
+        hash_transform(hash, &to_bits, len, scratch_len, |buffer, scratch| {
+            dct.process_dct2_with_scratch(buffer, scratch)
+        });
+        hash_transform(hash, &to_bits, len, scratch_len, |buffer, scratch| {
+            dct.process_dct3_with_scratch(buffer, scratch)
+        });
+        hash_transform(hash, &to_bits, len, scratch_len, |buffer, scratch| {
+            dct.process_dst2_with_scratch(buffer, scratch)
+        });
+        hash_transform(hash, &to_bits, len, scratch_len, |buffer, scratch| {
+            dct.process_dst3_with_scratch(buffer, scratch)
+        });
+    }
+
+    for &len in [1, 4, 6, 7, 15, 32, 48, 101, 128, 210].iter() {
+        let dct = planner.plan_dct4(len);
+        let scratch_len = dct.get_scratch_len();
+        hash_transform(hash, &to_bits, len, scratch_len, |buffer, scratch| {
+            dct.process_dct4_with_scratch(buffer, scratch)
+        });
+        hash_transform(hash, &to_bits, len, scratch_len, |buffer, scratch| {
+            dct.process_dst4_with_scratch(buffer, scratch)
+        });
+    }
+
+    for &len in [2, 5, 10, 17, 33, 64, 101].iter() {
+        let dct1 = planner.plan_dct1(len);
+        hash_transform(hash, &to_bits, len, dct1.get_scratch_len(), |b, s| {
+            dct1.process_dct1_with_scratch(b, s)
+        });
+        let dst1 = planner.plan_dst1(len);
+        hash_transform(hash, &to_bits, len, dst1.get_scratch_len(), |b, s| {
+            dst1.process_dst1_with_scratch(b, s)
+        });
+    }
+
+    for &len in [1, 5, 13, 25, 64].iter() {
+        let dct5 = planner.plan_dct5(len);
+        hash_transform(hash, &to_bits, len, dct5.get_scratch_len(), |b, s| {
+            dct5.process_dct5_with_scratch(b, s)
+        });
+        let dst5 = planner.plan_dst5(len);
+        hash_transform(hash, &to_bits, len, dst5.get_scratch_len(), |b, s| {
+            dst5.process_dst5_with_scratch(b, s)
+        });
+        let dct6 = planner.plan_dct6(len);
+        hash_transform(hash, &to_bits, len, dct6.get_scratch_len(), |b, s| {
+            dct6.process_dct6_with_scratch(b, s)
+        });
+        hash_transform(hash, &to_bits, len, dct6.get_scratch_len(), |b, s| {
+            dct6.process_dct7_with_scratch(b, s)
+        });
+        let dct8 = planner.plan_dct8(len);
+        hash_transform(hash, &to_bits, len, dct8.get_scratch_len(), |b, s| {
+            dct8.process_dct8_with_scratch(b, s)
+        });
+        let dst6 = planner.plan_dst6(len);
+        hash_transform(hash, &to_bits, len, dst6.get_scratch_len(), |b, s| {
+            dst6.process_dst6_with_scratch(b, s)
+        });
+        hash_transform(hash, &to_bits, len, dst6.get_scratch_len(), |b, s| {
+            dst6.process_dst7_with_scratch(b, s)
+        });
+        let dst8 = planner.plan_dst8(len);
+        hash_transform(hash, &to_bits, len, dst8.get_scratch_len(), |b, s| {
+            dst8.process_dst8_with_scratch(b, s)
+        });
+    }
+
+    for &len in [16, 60].iter() {
+        let mdct = planner.plan_mdct(len, window_fn::mp3);
+        let input: Vec<T> = fixed_signal(len * 2);
+        let mut output = vec![T::zero(); len];
+        let mut scratch = vec![T::zero(); mdct.get_scratch_len()];
+        mdct.process_mdct_with_scratch(&input[..len], &input[len..], &mut output, &mut scratch);
+
+        hash.write(len as u64);
+        for value in output {
+            hash.write(to_bits(value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    /// Verify that the fingerprint doesn't change between runs in the same process
+    #[test]
+    fn test_fingerprint_repeatable() {
+        assert_eq!(fingerprint(), fingerprint());
+    }
+
+    /// Verify that this platform reproduces the reference outputs. The reference depends on the math library and on the
+    /// RustFFT version, so this is opt-in: Run it with `cargo test --lib -- --ignored test_self_test`
+    #[test]
+    #[ignore]
+    fn test_self_test() {
+        assert_eq!(fingerprint(), REFERENCE_FINGERPRINT, "{:#x}", fingerprint());
+    }
+}
@@ -0,0 +1,178 @@
+//! Differential testing of planned transforms against the naive algorithms.
+//!
+//! The fast algorithms in RustDCT reorder, split, and recombine their inputs in ways that are easy to get subtly wrong
+//! for sizes that the regular tests don't happen to cover. This module computes a transform with both the algorithm
+//! chosen by `DctPlanner` and the corresponding naive O(n^2) algorithm, and reports the first output that differs by
+//! more than a tolerance that grows with the transform size. It's only available when the `differential` feature is
+//! enabled, and it's what the cargo-fuzz targets in the `fuzz` directory use to check arbitrary sizes and inputs.
+//!
+//! ~~~
+//! use rustdct::differential::check_planned;
+//! use rustdct::{DctPlanner, DctType};
+//!
+//! let mut planner = DctPlanner::new();
+//! let input: Vec<f64> = (0..37).map(|i| (i as f64).sin()).collect();
+//!
+//! assert!(check_planned(&mut planner, DctType::Dct4, &input).is_ok());
+//! ~~~
+
+use std::error::Error;
+use std::fmt;
+
+use crate::algorithm::*;
+use crate::{Dct1, Dct2, Dct3, Dct4, Dct5, Dct6, Dct7, Dct8};
+use crate::{DctPlanner, DctType};
+use crate::{Dst1, Dst2, Dst3, Dst4, Dst5, Dst6, Dst7, Dst8};
+
+/// Describes the first output where a planned transform disagreed with the naive algorithm.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Mismatch {
+    /// The transform type that was checked
+    pub kind: DctType,
+    /// The transform size
+    pub len: usize,
+    /// The index of the first output that differed
+    pub index: usize,
+    /// The output of the naive algorithm at `index`
+    pub expected: f64,
+    /// The output of the planned algorithm at `index`
+    pub actual: f64,
+    /// The largest difference that would have been accepted
+    pub tolerance: f64,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:?} of size {} differs from the naive algorithm at index {}: expected {}, got {} (tolerance {})",
+            self.kind, self.len, self.index, self.expected, self.actual, self.tolerance
+        )
+    }
+}
+impl Error for Mismatch {}
+
+/// Returns the smallest size that `check_planned` accepts for `kind`. The DCT1 needs at least two elements, and every
+/// other type needs at least one.
+pub fn min_len(kind: DctType) -> usize {
+    match kind {
+        DctType::Dct1 => 2,
+        _ => 1,
+    }
+}
+
+/// Computes the transform of type `kind` of `input` with the algorithm planned by `planner`, and with the naive
+/// algorithm, and compares the results.
+///
+/// The outputs are compared with a tolerance of `1e-10 * len * (1 + max |input|)`, since the rounding error of both
+/// algorithms grows with the size and magnitude of the input. Returns the first output that differs by more than that.
+///
+/// # Panics
+///
+/// Panics if `input.len()` is less than `min_len(kind)`.
+pub fn check_planned(
+    planner: &mut DctPlanner<f64>,
+    kind: DctType,
+    input: &[f64],
+) -> Result<(), Mismatch> {
+    let len = input.len();
+    assert!(
+        len >= min_len(kind),
+        "{:?} requires a size of at least {}. Got {}",
+        kind,
+        min_len(kind),
+        len
+    );
+
+    let mut expected = input.to_vec();
+    process_naive(kind, &mut expected);
+
+    let mut actual = input.to_vec();
+    planner.plan_and_process(kind, &mut actual);
+
+    let max_input = input.iter().fold(0f64, |max, x| max.max(x.abs()));
+    let tolerance = 1e-10 * len as f64 * (1.0 + max_input);
+
+    for (index, (&expected, &actual)) in expected.iter().zip(actual.iter()).enumerate() {
+        let difference = (expected - actual).abs();
+        if difference.is_nan() || difference > tolerance {
+            return Err(Mismatch {
+                kind,
+                len,
+                index,
+                expected,
+                actual,
+                tolerance,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn process_naive(kind: DctType, buffer: &mut [f64]) {
+    let len = buffer.len();
+    match kind {
+        DctType::Dct1 => Dct1Naive::new(len).process_dct1(buffer),
+        DctType::Dct2 => Type2And3Naive::new(len).process_dct2(buffer),
+        DctType::Dct3 => Type2And3Naive::new(len).process_dct3(buffer),
+        DctType::Dct4 => Type4Naive::new(len).process_dct4(buffer),
+        DctType::Dct5 => Dct5Naive::new(len).process_dct5(buffer),
+        DctType::Dct6 => Dct6And7Naive::new(len).process_dct6(buffer),
+        DctType::Dct7 => Dct6And7Naive::new(len).process_dct7(buffer),
+        DctType::Dct8 => Dct8Naive::new(len).process_dct8(buffer),
+        DctType::Dst1 => Dst1Naive::new(len).process_dst1(buffer),
+        DctType::Dst2 => Type2And3Naive::new(len).process_dst2(buffer),
+        DctType::Dst3 => Type2And3Naive::new(len).process_dst3(buffer),
+        DctType::Dst4 => Type4Naive::new(len).process_dst4(buffer),
+        DctType::Dst5 => Dst5Naive::new(len).process_dst5(buffer),
+        DctType::Dst6 => Dst6And7Naive::new(len).process_dst6(buffer),
+        DctType::Dst7 => Dst6And7Naive::new(len).process_dst7(buffer),
+        DctType::Dst8 => Dst8Naive::new(len).process_dst8(buffer),
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::test_utils::random_signal;
+    use rustfft::num_traits::Float;
+
+    const ALL_TYPES: [DctType; 16] = [
+        DctType::Dct1,
+        DctType::Dct2,
+        DctType::Dct3,
+        DctType::Dct4,
+        DctType::Dct5,
+        DctType::Dct6,
+        DctType::Dct7,
+        DctType::Dct8,
+        DctType::Dst1,
+        DctType::Dst2,
+        DctType::Dst3,
+        DctType::Dst4,
+        DctType::Dst5,
+        DctType::Dst6,
+        DctType::Dst7,
+        DctType::Dst8,
+    ];
+
+    #[test]
+    fn test_check_planned() {
+        let mut planner = DctPlanner::new();
+        for &kind in ALL_TYPES.iter() {
+            for len in min_len(kind)..40 {
+                let input: Vec<f64> = random_signal(len).into_iter().map(f64::from).collect();
+                if let Err(mismatch) = check_planned(&mut planner, kind, &input) {
+                    panic!("{}", mismatch);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_nan_is_a_mismatch() {
+        let mut planner = DctPlanner::new();
+        let input = vec![1.0, Float::nan(), 3.0];
+        assert!(check_planned(&mut planner, DctType::Dct2, &input).is_err());
+    }
+}
@@ -0,0 +1,47 @@
+/// The definition of the DST2 and DST3 that a plan from `DctPlanner::plan_dst2_with_convention` follows.
+///
+/// This crate's DST2 of `x` is `sum(x[n] * sin(pi * (k + 1) * (n + 0.5) / len))`, and its DST3 is the sum of
+/// `x[n] * sin(pi * (n + 1) * (k + 0.5) / len)`, with the last input weighted by one half. Other libraries use the
+/// same basis functions in the same order, so no signs or indexes need to change, but each one scales the outputs
+/// differently. Planning with a convention folds that scaling into the transform, so results can be compared with the
+/// other library's directly.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DstConvention {
+    /// This crate's definition, which is the one given by Wang. A plan with this convention is the same instance that
+    /// `plan_dst2` returns.
+    Wang,
+    /// FFTW's `FFTW_RODFT10` for the DST2, and `FFTW_RODFT01` for the DST3. Both are twice this crate's outputs, so
+    /// `dst3(dst2(x))` is `x * 4 * len`.
+    Fftw,
+    /// SciPy's `scipy.fft.dst` with `type=2` and `type=3`, and the default `norm="backward"`. These match FFTW's
+    /// definitions exactly.
+    Scipy,
+    /// The orthonormal scaling that MATLAB's `dct` and `idct` use, applied to the DST2 and DST3: The DST2 preserves the
+    /// energy of the signal, and the DST3 is its exact inverse. This is also SciPy's `norm="ortho"`.
+    Matlab,
+}
+
+impl DstConvention {
+    // Returns the factors that turn this crate's DST2 outputs into this convention's outputs: The first factor applies
+    // to every output except the last, and the second factor applies to the last output. Returns None for `Wang`.
+    pub(crate) fn dst2_output_scales(self, len: usize) -> Option<(f64, f64)> {
+        let len = len as f64;
+        match self {
+            DstConvention::Wang => None,
+            DstConvention::Fftw | DstConvention::Scipy => Some((2.0, 2.0)),
+            DstConvention::Matlab => Some(((2.0 / len).sqrt(), (1.0 / len).sqrt())),
+        }
+    }
+
+    // Returns the factors that turn a DST3 input in this convention into an input to this crate's DST3, in the same
+    // format as `dst2_output_scales`. The DST3 is linear, so scaling its input is the same as scaling its output
+    pub(crate) fn dst3_input_scales(self, len: usize) -> Option<(f64, f64)> {
+        let len = len as f64;
+        match self {
+            DstConvention::Wang => None,
+            DstConvention::Fftw | DstConvention::Scipy => Some((2.0, 2.0)),
+            // the orthonormal DST3 weights the last input by sqrt(1/len), and this crate's DST3 weights it by one half
+            DstConvention::Matlab => Some(((2.0 / len).sqrt(), 2.0 / len.sqrt())),
+        }
+    }
+}
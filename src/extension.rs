@@ -0,0 +1,358 @@
+//! Explicit versions of the symmetric extensions that the DCT and DST types imply.
+//!
+//! Every DCT and DST type treats its input as one period of a longer signal, which is symmetric or antisymmetric
+//! around each end of the input, as described by [`BoundaryMode`](../enum.BoundaryMode.html). The functions in this
+//! module build that longer signal, so that it can be inspected, padded onto a signal before filtering, or fed to a
+//! raw FFT in a custom pipeline.
+//!
+//! ~~~
+//! use rustdct::extension::{extend, extend_symmetric_half_sample, implied_period};
+//! use rustdct::{BoundaryMode, DctType};
+//!
+//! let signal = [1.0, 2.0, 3.0];
+//!
+//! let extended = extend_symmetric_half_sample(&signal, 2);
+//! assert_eq!(extended, vec![2.0, 1.0, 1.0, 2.0, 3.0, 3.0, 2.0]);
+//!
+//! let extended = extend(&signal, BoundaryMode::WholeSampleSymmetric, BoundaryMode::WholeSampleAntisymmetric, 2, 2);
+//! assert_eq!(extended, vec![3.0, 2.0, 1.0, 2.0, 3.0, 0.0, -3.0]);
+//!
+//! // The DFT of this period is twice the DCT2 of the signal, after a half-sample phase shift
+//! let period = implied_period(&signal, DctType::Dct2);
+//! assert_eq!(period, vec![1.0, 2.0, 3.0, 3.0, 2.0, 1.0]);
+//! ~~~
+
+use crate::{BoundaryMode, DctNum, DctType};
+
+/// Returns `signal` with `before` samples prepended and `after` samples appended, extended with the `start` boundary
+/// condition before its first sample and the `end` boundary condition after its last sample.
+///
+/// Extensions longer than the signal keep reflecting back and forth between the two boundaries, so the result is a
+/// window onto the same infinite signal that `DctType::from_boundaries(start, end)` implies. The original signal is
+/// always `result[before..before + signal.len()]`.
+///
+/// An empty signal is extended with zeros.
+pub fn extend<T: DctNum>(
+    signal: &[T],
+    start: BoundaryMode,
+    end: BoundaryMode,
+    before: usize,
+    after: usize,
+) -> Vec<T> {
+    let extension = Extension::new(signal, start, end);
+    let first = -(before as isize);
+    let last = (signal.len() + after) as isize;
+    (first..last).map(|index| extension.sample(index)).collect()
+}
+
+/// Returns `signal` with `len` samples of whole-sample symmetric extension at each end: `c b | a b c | b a`.
+///
+/// This is the extension implied by the DCT1.
+pub fn extend_symmetric_whole_sample<T: DctNum>(signal: &[T], len: usize) -> Vec<T> {
+    let mode = BoundaryMode::WholeSampleSymmetric;
+    extend(signal, mode, mode, len, len)
+}
+
+/// Returns `signal` with `len` samples of half-sample symmetric extension at each end: `b a | a b c | c b`.
+///
+/// This is the extension implied by the DCT2.
+pub fn extend_symmetric_half_sample<T: DctNum>(signal: &[T], len: usize) -> Vec<T> {
+    let mode = BoundaryMode::HalfSampleSymmetric;
+    extend(signal, mode, mode, len, len)
+}
+
+/// Returns `signal` with `len` samples of whole-sample antisymmetric extension at each end: `-b -a 0 | a b c | 0 -c -b`.
+///
+/// This is the extension implied by the DST1.
+pub fn extend_antisymmetric_whole_sample<T: DctNum>(signal: &[T], len: usize) -> Vec<T> {
+    let mode = BoundaryMode::WholeSampleAntisymmetric;
+    extend(signal, mode, mode, len, len)
+}
+
+/// Returns `signal` with `len` samples of half-sample antisymmetric extension at each end: `-b -a | a b c | -c -b`.
+///
+/// This is the extension implied by the DST2.
+pub fn extend_antisymmetric_half_sample<T: DctNum>(signal: &[T], len: usize) -> Vec<T> {
+    let mode = BoundaryMode::HalfSampleAntisymmetric;
+    extend(signal, mode, mode, len, len)
+}
+
+/// Returns the length of the period that `kind` implies for an input of length `len`. This is also the size of the
+/// DFT that `kind` is equivalent to.
+///
+/// Transform types that are symmetric at one end and antisymmetric at the other negate the signal after every
+/// reflection pair, so their period is twice the distance between reflections. For example, the period of a DCT2 of
+/// size N is 2N, and the period of a DCT4 of size N is 4N.
+pub fn implied_period_len(len: usize, kind: DctType) -> usize {
+    let (start, end) = kind.boundaries();
+    period_len(len, start, end)
+}
+
+/// Returns one period of the signal that `kind` implies for `signal`, starting at `signal[0]`.
+///
+/// The result has a length of `implied_period_len(signal.len(), kind)`, and begins with `signal`. The transform's
+/// output is the DFT of this period, scaled by 2 for types 1, 2, 5, and 6 or by 4 for types 3, 4, 7, and 8, and with
+/// the sample positions measured from the start boundary's center of symmetry instead of from `signal[0]`.
+///
+/// # Panics
+///
+/// Panics if `signal` is empty, or if `kind` is `Dct1` and `signal` has fewer than 2 elements, since those
+/// transforms don't exist.
+pub fn implied_period<T: DctNum>(signal: &[T], kind: DctType) -> Vec<T> {
+    let period = implied_period_len(signal.len(), kind);
+    assert!(
+        period > 0,
+        "{:?} of size {} has no implied period",
+        kind,
+        signal.len()
+    );
+    let (start, end) = kind.boundaries();
+    extend(signal, start, end, 0, period - signal.len())
+}
+
+// 1 for symmetric boundaries, -1 for antisymmetric ones
+fn sign(mode: BoundaryMode) -> isize {
+    match mode {
+        BoundaryMode::WholeSampleSymmetric | BoundaryMode::HalfSampleSymmetric => 1,
+        BoundaryMode::WholeSampleAntisymmetric | BoundaryMode::HalfSampleAntisymmetric => -1,
+    }
+}
+
+// Twice the position of the center of symmetry before the first sample. Reflecting index `i` around it gives
+// `start_center - i`
+fn start_center(mode: BoundaryMode) -> isize {
+    match mode {
+        BoundaryMode::WholeSampleSymmetric => 0,
+        BoundaryMode::HalfSampleSymmetric | BoundaryMode::HalfSampleAntisymmetric => -1,
+        BoundaryMode::WholeSampleAntisymmetric => -2,
+    }
+}
+
+// Twice the position of the center of symmetry after the last sample
+fn end_center(len: usize, mode: BoundaryMode) -> isize {
+    let len = len as isize;
+    match mode {
+        BoundaryMode::WholeSampleSymmetric => 2 * len - 2,
+        BoundaryMode::HalfSampleSymmetric | BoundaryMode::HalfSampleAntisymmetric => 2 * len - 1,
+        BoundaryMode::WholeSampleAntisymmetric => 2 * len,
+    }
+}
+
+// Reflecting around the start and then around the end shifts the signal by `end_center - start_center` samples, and
+// negates it if the boundaries have different signs, in which case it takes twice that shift to repeat
+fn period_len(len: usize, start: BoundaryMode, end: BoundaryMode) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    let translation = (end_center(len, end) - start_center(start)) as usize;
+    if sign(start) == sign(end) {
+        translation
+    } else {
+        2 * translation
+    }
+}
+
+struct Extension<'a, T> {
+    signal: &'a [T],
+    start: BoundaryMode,
+    end: BoundaryMode,
+    // The extended signal repeats exactly after this many samples
+    period: isize,
+}
+
+impl<'a, T: DctNum> Extension<'a, T> {
+    fn new(signal: &'a [T], start: BoundaryMode, end: BoundaryMode) -> Self {
+        Self {
+            signal,
+            start,
+            end,
+            period: period_len(signal.len(), start, end) as isize,
+        }
+    }
+
+    fn sample(&self, index: isize) -> T {
+        let len = self.signal.len() as isize;
+        if len == 0 {
+            return T::zero();
+        }
+        // A single sample with whole-sample symmetry at both ends reflects onto itself, so it's constant
+        if self.period == 0 {
+            return self.signal[0];
+        }
+
+        // Wrap the index into the first period, then reflect it back into the signal. Each pair of reflections moves
+        // it one translation closer, so this takes at most a few iterations
+        let mut index = ((index % self.period) + self.period) % self.period;
+        let mut negate = false;
+        loop {
+            if index < 0 {
+                if index == -1 && self.start == BoundaryMode::WholeSampleAntisymmetric {
+                    return T::zero();
+                }
+                index = start_center(self.start) - index;
+                negate ^= sign(self.start) < 0;
+            } else if index >= len {
+                if index == len && self.end == BoundaryMode::WholeSampleAntisymmetric {
+                    return T::zero();
+                }
+                index = end_center(self.signal.len(), self.end) - index;
+                negate ^= sign(self.end) < 0;
+            } else {
+                let sample = self.signal[index as usize];
+                return if negate { -sample } else { sample };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::DctPlanner;
+    use std::f64::consts::PI;
+
+    const ALL_TYPES: [DctType; 16] = [
+        DctType::Dct1,
+        DctType::Dct2,
+        DctType::Dct3,
+        DctType::Dct4,
+        DctType::Dct5,
+        DctType::Dct6,
+        DctType::Dct7,
+        DctType::Dct8,
+        DctType::Dst1,
+        DctType::Dst2,
+        DctType::Dst3,
+        DctType::Dst4,
+        DctType::Dst5,
+        DctType::Dst6,
+        DctType::Dst7,
+        DctType::Dst8,
+    ];
+
+    #[test]
+    fn test_extend_round_trip() {
+        for len in 1..8 {
+            let signal: Vec<f64> = (1..=len).map(|i| i as f64).collect();
+            for &kind in ALL_TYPES.iter() {
+                if kind == DctType::Dct1 && len < 2 {
+                    continue;
+                }
+                let (start, end) = kind.boundaries();
+                let before = 3 * len + 1;
+                let after = 2 * len + 5;
+                let extended = extend(&signal, start, end, before, after);
+                assert_eq!(extended.len(), before + len + after);
+                assert_eq!(&extended[before..before + len], signal.as_slice());
+
+                // the extension is (anti)periodic, and its period begins with the signal
+                let period = implied_period(&signal, kind);
+                assert_eq!(period.len(), implied_period_len(len, kind));
+                assert_eq!(&period[..len], signal.as_slice());
+                for (index, &sample) in extended.iter().enumerate() {
+                    let position = (index + period.len() * before - before) % period.len();
+                    assert_eq!(sample, period[position], "{:?} len {}", kind, len);
+                }
+
+                // the boundary conditions hold around both ends
+                let start_sign = sign(start) as f64;
+                let end_sign = sign(end) as f64;
+                for offset in 0..len {
+                    let reflected =
+                        (before as isize + start_center(start) - offset as isize) as usize;
+                    assert_eq!(extended[reflected], start_sign * signal[offset]);
+
+                    let from_end = len - 1 - offset;
+                    let reflected = before as isize + end_center(len, end) - from_end as isize;
+                    assert_eq!(extended[reflected as usize], end_sign * signal[from_end]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_named_extensions() {
+        let signal = [1.0, 2.0, 3.0];
+        assert_eq!(
+            extend_symmetric_whole_sample(&signal, 2),
+            vec![3.0, 2.0, 1.0, 2.0, 3.0, 2.0, 1.0]
+        );
+        assert_eq!(
+            extend_symmetric_half_sample(&signal, 2),
+            vec![2.0, 1.0, 1.0, 2.0, 3.0, 3.0, 2.0]
+        );
+        assert_eq!(
+            extend_antisymmetric_whole_sample(&signal, 2),
+            vec![-1.0, 0.0, 1.0, 2.0, 3.0, 0.0, -3.0]
+        );
+        assert_eq!(
+            extend_antisymmetric_half_sample(&signal, 2),
+            vec![-2.0, -1.0, 1.0, 2.0, 3.0, -3.0, -2.0]
+        );
+
+        let empty: [f64; 0] = [];
+        assert_eq!(extend_symmetric_half_sample(&empty, 2), vec![0.0; 4]);
+        assert_eq!(extend_symmetric_whole_sample(&[5.0], 2), vec![5.0; 5]);
+    }
+
+    // The DFT of the implied period, with positions measured from the start's center of symmetry, is the transform
+    #[test]
+    fn test_implied_period_matches_transform() {
+        let mut planner = DctPlanner::<f64>::new();
+        for len in 2..10 {
+            let signal: Vec<f64> = (0..len).map(|i| ((i * 7) % 5) as f64 - 1.5).collect();
+            for &kind in ALL_TYPES.iter() {
+                let mut expected = signal.clone();
+                match kind {
+                    DctType::Dct1 => planner.plan_dct1(len).process_dct1(&mut expected),
+                    DctType::Dct2 => planner.plan_dct2(len).process_dct2(&mut expected),
+                    DctType::Dct3 => planner.plan_dct3(len).process_dct3(&mut expected),
+                    DctType::Dct4 => planner.plan_dct4(len).process_dct4(&mut expected),
+                    DctType::Dct5 => planner.plan_dct5(len).process_dct5(&mut expected),
+                    DctType::Dct6 => planner.plan_dct6(len).process_dct6(&mut expected),
+                    DctType::Dct7 => planner.plan_dct7(len).process_dct7(&mut expected),
+                    DctType::Dct8 => planner.plan_dct8(len).process_dct8(&mut expected),
+                    DctType::Dst1 => planner.plan_dst1(len).process_dst1(&mut expected),
+                    DctType::Dst2 => planner.plan_dst2(len).process_dst2(&mut expected),
+                    DctType::Dst3 => planner.plan_dst3(len).process_dst3(&mut expected),
+                    DctType::Dst4 => planner.plan_dst4(len).process_dst4(&mut expected),
+                    DctType::Dst5 => planner.plan_dst5(len).process_dst5(&mut expected),
+                    DctType::Dst6 => planner.plan_dst6(len).process_dst6(&mut expected),
+                    DctType::Dst7 => planner.plan_dst7(len).process_dst7(&mut expected),
+                    DctType::Dst8 => planner.plan_dst8(len).process_dst8(&mut expected),
+                }
+
+                let period = implied_period(&signal, kind);
+                let center = start_center(kind.boundaries().0) as f64 / 2.0;
+                let is_sine = sign(kind.boundaries().0) < 0;
+                let (scale, frequency): (f64, fn(usize) -> usize) = match kind {
+                    DctType::Dct1 | DctType::Dct2 | DctType::Dct5 | DctType::Dct6 => (2.0, |k| k),
+                    DctType::Dst1 | DctType::Dst2 | DctType::Dst5 | DctType::Dst6 => {
+                        (2.0, |k| k + 1)
+                    }
+                    _ => (4.0, |k| 2 * k + 1),
+                };
+                for (k, &expected) in expected.iter().enumerate() {
+                    let dft: f64 = period
+                        .iter()
+                        .enumerate()
+                        .map(|(m, &sample)| {
+                            let angle = 2.0 * PI * (m as f64 - center) * frequency(k) as f64
+                                / period.len() as f64;
+                            sample * if is_sine { angle.sin() } else { angle.cos() }
+                        })
+                        .sum();
+                    assert!(
+                        (dft - scale * expected).abs() < 1e-9,
+                        "{:?} len {} k {}: {} != {}",
+                        kind,
+                        len,
+                        k,
+                        dft,
+                        scale * expected
+                    );
+                }
+            }
+        }
+    }
+}
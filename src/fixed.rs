@@ -0,0 +1,280 @@
+//! Fixed-point DCT Type 2 and DCT Type 3 in Q15 arithmetic, for embedded voice pipelines without a FPU.
+//!
+//! Telephony codecs, like the G.722.1 family, transform frames of 32, 64, 160, or 320 samples, stored as Q15 numbers:
+//! An `i16` value `x` represents the fraction `x / 32768`, from -1 up to (but not including) 1.
+//! [`Q15Dct2`](struct.Q15Dct2.html) computes the DCT2 and DCT3 of these sizes with integer arithmetic only. It's only
+//! available when the `fixed` feature is enabled.
+//!
+//! ~~~
+//! use rustdct::fixed::Q15Dct2;
+//!
+//! let dct = Q15Dct2::new(32);
+//!
+//! // a constant signal of 0.5
+//! let mut buffer = vec![16384i16; 32];
+//! dct.process_dct2(&mut buffer);
+//!
+//! // the unscaled DC coefficient would be 32 * 0.5 = 16, so with the default shift of 5, it's 16 / 32 = 0.5
+//! assert_eq!(buffer[0], 16384);
+//! assert!(buffer[1..].iter().all(|&coefficient| coefficient.abs() <= 1));
+//! ~~~
+
+use std::convert::TryFrom;
+use std::f64;
+
+use rustfft::Length;
+
+use crate::common::dct_error_inplace;
+use crate::RequiredScratch;
+
+/// The sizes that [`Q15Dct2`](struct.Q15Dct2.html) supports.
+pub const Q15_LENS: [usize; 4] = [32, 64, 160, 320];
+
+/// DCT Type 2 and DCT Type 3 of Q15 signals of size 32, 64, 160, or 320, computed with integer arithmetic.
+///
+/// Each output is computed as a sum of products of Q15 inputs and Q15 cosines, accumulated in an `i64` without any
+/// intermediate rounding, so the only errors come from the cosine table and the final rounding.
+///
+/// # Scaling
+///
+/// The transforms aren't normalized, like every other transform in RustDCT, so an output can be up to `len` times as
+/// large as the largest input. To keep it representable in Q15, each output is divided by `2^shift` and rounded to the
+/// nearest Q15 value, rounding halves up. So the DCT2 output `k` is
+/// `sum(input[n] * cos(pi * (2n + 1) * k / (2 * len))) / 2^shift`, and the DCT3 output is scaled the same way.
+///
+/// The default shift, from [`new`](#method.new), is `ceil(log2(len))`: 5 for size 32, 6 for size 64, 8 for size 160,
+/// and 9 for size 320. With it, no output can overflow, so the outputs never saturate. A DCT2 with the default shift,
+/// followed by a DCT3 with the default shift, returns the input scaled by `len / 2^(2 * shift + 1)`, which codecs
+/// usually undo with a final shift of their own.
+///
+/// # Saturation
+///
+/// [`with_shift`](#method.with_shift) allows smaller shifts, down to 0, which keep more precision for signals that are
+/// known to be small. Outputs that don't fit in Q15 with a smaller shift saturate to `i16::MIN` or `i16::MAX`, instead
+/// of wrapping around.
+///
+/// The cosine of 0 is 1, which isn't representable in Q15, so the cosine table saturates it to `32767 / 32768`. The
+/// table is computed with `f64` arithmetic when the instance is created, which uses software floating point on targets
+/// without a FPU. The process methods only use integer arithmetic.
+pub struct Q15Dct2 {
+    cosines: Box<[i16]>,
+    shift: u32,
+}
+
+impl Q15Dct2 {
+    /// Creates a new DCT2 and DCT3 context that will process signals of length `len`, with the default shift of
+    /// `ceil(log2(len))`, which never saturates.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` isn't one of the sizes in [`Q15_LENS`](constant.Q15_LENS.html).
+    pub fn new(len: usize) -> Self {
+        let shift = (len as u32).next_power_of_two().trailing_zeros();
+        Self::with_shift(len, shift)
+    }
+
+    /// Creates a new DCT2 and DCT3 context that will process signals of length `len`, dividing each output by
+    /// `2^shift`. Outputs that don't fit in Q15 saturate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` isn't one of the sizes in [`Q15_LENS`](constant.Q15_LENS.html), or if `shift` is larger than
+    /// 16.
+    pub fn with_shift(len: usize, shift: u32) -> Self {
+        assert!(
+            Q15_LENS.contains(&len),
+            "Q15Dct2 supports sizes {:?}. Got len = {}",
+            Q15_LENS,
+            len
+        );
+        assert!(
+            shift <= 16,
+            "The Q15Dct2 shift must be at most 16. Got shift = {}",
+            shift
+        );
+
+        // cos(pi * i / (2 * len)), for every angle the transforms use
+        let angle_constant = f64::consts::PI / (2 * len) as f64;
+        let cosines = (0..4 * len)
+            .map(|i| saturate((angle_constant * i as f64).cos() * 32768.0))
+            .collect();
+
+        Self { cosines, shift }
+    }
+
+    /// Returns the number of bits that each output is shifted right by.
+    pub fn shift(&self) -> u32 {
+        self.shift
+    }
+
+    /// Computes the DCT Type 2 of `buffer` in place. Allocates a scratch buffer of `len` elements.
+    pub fn process_dct2(&self, buffer: &mut [i16]) {
+        let mut scratch = vec![0; self.get_scratch_len()];
+        self.process_dct2_with_scratch(buffer, &mut scratch);
+    }
+
+    /// Computes the DCT Type 2 of `buffer` in place, using `scratch` as scratch space.
+    pub fn process_dct2_with_scratch(&self, buffer: &mut [i16], scratch: &mut [i16]) {
+        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+        scratch.copy_from_slice(buffer);
+
+        let table_len = self.cosines.len();
+        for (k, output) in buffer.iter_mut().enumerate() {
+            let mut sum = 0i64;
+            let mut index = k;
+            for &input in scratch.iter() {
+                sum += input as i64 * self.cosines[index] as i64;
+                index = (index + 2 * k) % table_len;
+            }
+
+            // the sum is in Q30
+            *output = round_shift(sum, 15 + self.shift);
+        }
+    }
+
+    /// Computes the DCT Type 3 of `buffer` in place. Allocates a scratch buffer of `len` elements.
+    pub fn process_dct3(&self, buffer: &mut [i16]) {
+        let mut scratch = vec![0; self.get_scratch_len()];
+        self.process_dct3_with_scratch(buffer, &mut scratch);
+    }
+
+    /// Computes the DCT Type 3 of `buffer` in place, using `scratch` as scratch space.
+    ///
+    /// Like RustDCT's floating-point DCT3, the first input is multiplied by 0.5.
+    pub fn process_dct3_with_scratch(&self, buffer: &mut [i16], scratch: &mut [i16]) {
+        let scratch = validate_buffers!(buffer, scratch, self.len(), self.get_scratch_len());
+        scratch.copy_from_slice(buffer);
+
+        let table_len = self.cosines.len();
+        for (n, output) in buffer.iter_mut().enumerate() {
+            // accumulate twice the sum, so that halving the first input doesn't lose a bit
+            let mut sum = scratch[0] as i64 * self.cosines[0] as i64;
+            let mut index = 2 * n + 1;
+            for &input in scratch[1..].iter() {
+                sum += 2 * input as i64 * self.cosines[index] as i64;
+                index = (index + 2 * n + 1) % table_len;
+            }
+
+            // the sum is twice a Q30 value
+            *output = round_shift(sum, 16 + self.shift);
+        }
+    }
+}
+impl Length for Q15Dct2 {
+    fn len(&self) -> usize {
+        self.cosines.len() / 4
+    }
+}
+impl RequiredScratch for Q15Dct2 {
+    fn get_scratch_len(&self) -> usize {
+        self.len()
+    }
+}
+
+// Divides `value` by `2^shift`, rounding halves up, and saturates the result to an i16
+fn round_shift(value: i64, shift: u32) -> i16 {
+    let rounded = (value + (1 << (shift - 1))) >> shift;
+    i16::try_from(rounded).unwrap_or(if rounded < 0 {
+        std::i16::MIN
+    } else {
+        std::i16::MAX
+    })
+}
+
+// Rounds `value` to the nearest i16, saturating values that are out of range
+fn saturate(value: f64) -> i16 {
+    value
+        .round()
+        .max(std::i16::MIN as f64)
+        .min(std::i16::MAX as f64) as i16
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    use crate::algorithm::Type2And3Naive;
+    use crate::{Dct2, Dct3};
+
+    // Returns a Q15 signal with a fixed pseudorandom pattern, using the full range
+    fn q15_signal(len: usize) -> Vec<i16> {
+        let mut state: u32 = 0x1234_5678;
+        (0..len)
+            .map(|_| {
+                state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (state >> 16) as i16
+            })
+            .collect()
+    }
+
+    // Compares `actual` with the floating-point transform of `input`, scaled by `2^-shift` and rounded to Q15
+    fn check_against_float(
+        input: &[i16],
+        actual: &[i16],
+        shift: u32,
+        process: impl Fn(&Type2And3Naive<f64>, &mut [f64]),
+    ) {
+        let mut expected: Vec<f64> = input.iter().map(|&x| x as f64).collect();
+        process(&Type2And3Naive::new(input.len()), &mut expected);
+
+        for (index, (&expected, &actual)) in expected.iter().zip(actual).enumerate() {
+            let expected = saturate(expected / (1u32 << shift) as f64);
+            assert!(
+                (expected as i32 - actual as i32).abs() <= 1,
+                "len = {}, shift = {}, index = {}: expected {}, got {}",
+                input.len(),
+                shift,
+                index,
+                expected,
+                actual
+            );
+        }
+    }
+
+    /// Verify that the fixed-point transforms are within one Q15 step of the floating-point ones
+    #[test]
+    fn test_q15_matches_float() {
+        for &len in Q15_LENS.iter() {
+            let dct = Q15Dct2::new(len);
+            let input = q15_signal(len);
+
+            let mut actual = input.clone();
+            dct.process_dct2(&mut actual);
+            check_against_float(&input, &actual, dct.shift(), |dct, buffer| {
+                dct.process_dct2(buffer)
+            });
+
+            let mut actual = input.clone();
+            dct.process_dct3(&mut actual);
+            check_against_float(&input, &actual, dct.shift(), |dct, buffer| {
+                dct.process_dct3(buffer)
+            });
+        }
+    }
+
+    /// Verify that outputs saturate instead of wrapping around when the shift is too small
+    #[test]
+    fn test_q15_saturation() {
+        let dct = Q15Dct2::with_shift(64, 2);
+
+        let mut buffer = vec![i16::MAX; 64];
+        dct.process_dct2(&mut buffer);
+        assert_eq!(buffer[0], i16::MAX);
+
+        let mut buffer = vec![i16::MIN; 64];
+        dct.process_dct2(&mut buffer);
+        assert_eq!(buffer[0], i16::MIN);
+
+        // small signals don't saturate, and keep the extra precision
+        let input: Vec<i16> = q15_signal(64).iter().map(|&x| x / 64).collect();
+        let mut actual = input.clone();
+        dct.process_dct2(&mut actual);
+        check_against_float(&input, &actual, 2, |dct, buffer| dct.process_dct2(buffer));
+    }
+
+    #[test]
+    #[should_panic(expected = "Q15Dct2 supports sizes")]
+    fn test_q15_unsupported_len() {
+        Q15Dct2::new(100);
+    }
+}
@@ -0,0 +1,96 @@
+use rustfft::Length;
+
+use crate::scaling::scaling_factor;
+use crate::{DctBuffers, DctNum, DctTransform, DctType, RequiredScratch, ScratchLayout};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+
+/// A planned transform that inverts another transform type, together with the scale that recovers the original input.
+///
+/// Each DCT and DST type is inverted by a transform of the same family: The DCT5, DCT8, DST5, and DST8 are their own
+/// inverses, and the DCT6/DCT7 and DST6/DST7 invert each other. None of them are normalized, so the inverse's output
+/// also has to be scaled by a factor that depends on the type and size. `InversePlan` computes the right transform and
+/// applies that scale, so `inverse(forward(x))` is equal to `x`.
+///
+/// ~~~
+/// // Computes a DCT Type 6 of size 100 and then inverts it
+/// use rustdct::{DctPlanner, DctType};
+///
+/// let mut planner = DctPlanner::new();
+/// let dct6 = planner.plan_dct6(100);
+/// let inverse = planner.plan_inverse_dct6(100);
+/// assert_eq!(inverse.kind(), DctType::Dct7);
+///
+/// let mut buffer = vec![1f32; 100];
+/// dct6.process_dct6(&mut buffer);
+/// inverse.process(&mut buffer);
+/// ~~~
+pub struct InversePlan<T> {
+    plan: Box<dyn DctTransform<T>>,
+    kind: DctType,
+    scale: T,
+}
+
+impl<T: DctNum> InversePlan<T> {
+    // `plan` must be able to compute `forward.inverse()`, at size `len`
+    pub(crate) fn new(plan: Box<dyn DctTransform<T>>, forward: DctType) -> Self {
+        let len = plan.transform_len();
+        Self {
+            plan,
+            kind: forward.inverse(),
+            scale: scaling_factor(forward, len),
+        }
+    }
+
+    /// Returns the transform type that this plan computes, which is the inverse of the forward transform type.
+    pub fn kind(&self) -> DctType {
+        self.kind
+    }
+
+    /// Returns the factor that this plan multiplies the output of its transform by.
+    pub fn scale(&self) -> T {
+        self.scale
+    }
+
+    /// Computes the inverse transform on the provided buffer, in-place, and applies the scale.
+    ///
+    /// This method allocates a Vec<T> of scratch space. If you'd like to reuse that allocation between
+    /// multiple computations, consider calling `process_with_scratch` instead.
+    pub fn process(&self, buffer: &mut [T]) {
+        let mut scratch = vec![T::zero(); self.get_scratch_len()];
+        self.process_with_scratch(buffer, &mut scratch);
+    }
+
+    /// Computes the inverse transform on the provided buffer, in-place, and applies the scale.
+    /// Uses the provided `scratch` buffer as scratch space.
+    pub fn process_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        self.plan.execute(DctBuffers::InPlace {
+            kind: self.kind,
+            buffer: &mut *buffer,
+            scratch,
+        });
+        for element in buffer.iter_mut() {
+            *element = *element * self.scale;
+        }
+    }
+}
+impl<T: DctNum> MemoryUsage for InversePlan<T> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        self.plan.memory_usage_with_visitor(visitor)
+    }
+}
+impl<T: DctNum> RequiredScratch for InversePlan<T> {
+    fn get_scratch_len(&self) -> usize {
+        self.plan.get_scratch_len()
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        self.plan.get_complex_scratch_len()
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        self.plan.scratch_layout()
+    }
+}
+impl<T: DctNum> Length for InversePlan<T> {
+    fn len(&self) -> usize {
+        self.plan.transform_len()
+    }
+}
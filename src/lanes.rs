@@ -0,0 +1,387 @@
+//! Element types that hold several independent samples, for transforming several signals with a single process call.
+//!
+//! [`F32x4`](struct.F32x4.html) holds 4 `f32` lanes and [`F64x2`](struct.F64x2.html) holds 2 `f64` lanes. Every
+//! arithmetic operation applies to each lane separately, so a plan for one of these types computes the same transform
+//! on each lane, with no changes to any algorithm. This is the structure-of-arrays layout: sample `n` of every signal
+//! is stored in element `n`, so 4 signals of length 256 become a single buffer of 256 `F32x4` elements.
+//!
+//! Both types are 16 bytes, the width of the SSE2 and NEON registers, and LLVM compiles their lane-wise arithmetic to
+//! vector instructions on stable Rust. The FFTs inside the FFT-based algorithms use RustFFT's scalar code for these
+//! types, instead of its hand-written SIMD kernels for `f32` and `f64`, so the butterflies, the split radix sizes, and
+//! the naive sizes gain the most from packing. [`pack_f32x4`](fn.pack_f32x4.html) and [`unpack_f32x4`](fn.unpack_f32x4.html), and their `f64`
+//! counterparts, convert between consecutive signals and the packed layout.
+//!
+//! This module is only available when the `lanes` feature is enabled.
+//!
+//! ~~~
+//! // Computes the DCT Type 2 of 4 signals of size 100 at once
+//! use rustdct::lanes::{pack_f32x4, unpack_f32x4, F32x4};
+//! use rustdct::DctPlanner;
+//!
+//! let signals: Vec<f32> = (0..400).map(|i| (i % 7) as f32).collect();
+//! let mut packed = pack_f32x4(&signals);
+//! assert_eq!(packed.len(), 100);
+//!
+//! let mut planner = DctPlanner::<F32x4>::new();
+//! planner.plan_dct2(100).process_dct2(&mut packed);
+//!
+//! let mut outputs = vec![0f32; 400];
+//! unpack_f32x4(&packed, &mut outputs);
+//! ~~~
+//!
+//! # Comparisons
+//!
+//! `DctNum` requires `Signed`, which has a few methods that don't apply lane by lane: `is_positive` and `is_negative`
+//! return true if they're true for every lane. `PartialEq` compares every lane, so two values are equal if all of their
+//! lanes are equal. The transforms themselves don't use any of these, and algorithms that need `Float`, like
+//! [`series::evaluate_series`](../series/fn.evaluate_series.html), aren't available for these types.
+
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+use rustfft::num_traits::{FloatConst, FromPrimitive, Num, One, Signed, Zero};
+
+// Implements `FloatConst` for a lanes type, with the scalar's constant in every lane
+macro_rules! lanes_float_const {
+    ($struct_name:ident, $scalar:ident, $($constant:ident),*) => {
+        impl FloatConst for $struct_name {
+            $(
+                fn $constant() -> Self {
+                    Self::splat($scalar::$constant())
+                }
+            )*
+        }
+    };
+}
+
+macro_rules! lanes_type {
+    (
+        $struct_name:ident, $scalar:ident, $lanes:expr, $struct_doc:literal,
+        $pack_fn:ident, $pack_doc:literal, $unpack_fn:ident, $unpack_doc:literal
+    ) => {
+        #[doc = $struct_doc]
+        ///
+        /// Arithmetic operations apply to each lane separately. See the [module documentation](index.html).
+        #[derive(Copy, Clone, Debug, Default, PartialEq)]
+        #[repr(C, align(16))]
+        pub struct $struct_name(pub [$scalar; $lanes]);
+
+        impl $struct_name {
+            /// The number of lanes
+            pub const LANES: usize = $lanes;
+
+            /// Returns a vector with `value` in every lane.
+            pub fn splat(value: $scalar) -> Self {
+                $struct_name([value; $lanes])
+            }
+
+            /// Returns the lanes as an array.
+            pub fn to_array(self) -> [$scalar; $lanes] {
+                self.0
+            }
+
+            // Applies `f` to each lane of `self` and `other`
+            #[inline(always)]
+            fn zip_with(self, other: Self, f: impl Fn($scalar, $scalar) -> $scalar) -> Self {
+                let mut result = self.0;
+                for (lane, other_lane) in result.iter_mut().zip(other.0.iter()) {
+                    *lane = f(*lane, *other_lane);
+                }
+                $struct_name(result)
+            }
+
+            // Applies `f` to each lane of `self`
+            #[inline(always)]
+            fn map(self, f: impl Fn($scalar) -> $scalar) -> Self {
+                let mut result = self.0;
+                for lane in result.iter_mut() {
+                    *lane = f(*lane);
+                }
+                $struct_name(result)
+            }
+        }
+
+        impl Add for $struct_name {
+            type Output = Self;
+            #[inline(always)]
+            fn add(self, other: Self) -> Self {
+                self.zip_with(other, |a, b| a + b)
+            }
+        }
+        impl Sub for $struct_name {
+            type Output = Self;
+            #[inline(always)]
+            fn sub(self, other: Self) -> Self {
+                self.zip_with(other, |a, b| a - b)
+            }
+        }
+        impl Mul for $struct_name {
+            type Output = Self;
+            #[inline(always)]
+            fn mul(self, other: Self) -> Self {
+                self.zip_with(other, |a, b| a * b)
+            }
+        }
+        impl Div for $struct_name {
+            type Output = Self;
+            #[inline(always)]
+            fn div(self, other: Self) -> Self {
+                self.zip_with(other, |a, b| a / b)
+            }
+        }
+        impl Rem for $struct_name {
+            type Output = Self;
+            #[inline(always)]
+            fn rem(self, other: Self) -> Self {
+                self.zip_with(other, |a, b| a % b)
+            }
+        }
+        impl Neg for $struct_name {
+            type Output = Self;
+            #[inline(always)]
+            fn neg(self) -> Self {
+                self.map(|a| -a)
+            }
+        }
+
+        impl Zero for $struct_name {
+            fn zero() -> Self {
+                Self::splat(0.0)
+            }
+            fn is_zero(&self) -> bool {
+                self.0.iter().all(|lane| *lane == 0.0)
+            }
+        }
+        impl One for $struct_name {
+            fn one() -> Self {
+                Self::splat(1.0)
+            }
+        }
+        impl Num for $struct_name {
+            type FromStrRadixErr = <$scalar as Num>::FromStrRadixErr;
+            fn from_str_radix(str: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
+                $scalar::from_str_radix(str, radix).map(Self::splat)
+            }
+        }
+        impl Signed for $struct_name {
+            fn abs(&self) -> Self {
+                self.map(|a| a.abs())
+            }
+            fn abs_sub(&self, other: &Self) -> Self {
+                self.zip_with(*other, |a, b| if a <= b { 0.0 } else { a - b })
+            }
+            fn signum(&self) -> Self {
+                self.map(|a| a.signum())
+            }
+            fn is_positive(&self) -> bool {
+                self.0.iter().all(|lane| lane.is_sign_positive())
+            }
+            fn is_negative(&self) -> bool {
+                self.0.iter().all(|lane| lane.is_sign_negative())
+            }
+        }
+        impl FromPrimitive for $struct_name {
+            fn from_i64(n: i64) -> Option<Self> {
+                $scalar::from_i64(n).map(Self::splat)
+            }
+            fn from_u64(n: u64) -> Option<Self> {
+                $scalar::from_u64(n).map(Self::splat)
+            }
+            fn from_f32(n: f32) -> Option<Self> {
+                $scalar::from_f32(n).map(Self::splat)
+            }
+            fn from_f64(n: f64) -> Option<Self> {
+                $scalar::from_f64(n).map(Self::splat)
+            }
+        }
+        lanes_float_const!(
+            $struct_name,
+            $scalar,
+            E,
+            FRAC_1_PI,
+            FRAC_1_SQRT_2,
+            FRAC_2_PI,
+            FRAC_2_SQRT_PI,
+            FRAC_PI_2,
+            FRAC_PI_3,
+            FRAC_PI_4,
+            FRAC_PI_6,
+            FRAC_PI_8,
+            LN_10,
+            LN_2,
+            LOG10_E,
+            LOG2_E,
+            PI,
+            SQRT_2
+        );
+
+        #[doc = $pack_doc]
+        ///
+        /// # Panics
+        ///
+        /// Panics if `signals.len()` isn't a multiple of the number of lanes.
+        pub fn $pack_fn(signals: &[$scalar]) -> Vec<$struct_name> {
+            let len = validate_lanes(signals.len(), $lanes);
+            (0..len)
+                .map(|n| {
+                    let mut element = $struct_name::zero();
+                    for (lane, value) in element.0.iter_mut().enumerate() {
+                        *value = signals[lane * len + n];
+                    }
+                    element
+                })
+                .collect()
+        }
+
+        #[doc = $unpack_doc]
+        ///
+        /// # Panics
+        ///
+        /// Panics if `signals.len()` isn't `packed.len()` times the number of lanes.
+        pub fn $unpack_fn(packed: &[$struct_name], signals: &mut [$scalar]) {
+            let len = validate_lanes(signals.len(), $lanes);
+            assert_eq!(
+                packed.len(),
+                len,
+                "Provided signals must have {} samples for each packed element. Expected signals len = {}, got signals len = {}",
+                $lanes,
+                packed.len() * $lanes,
+                signals.len()
+            );
+            for (n, element) in packed.iter().enumerate() {
+                for (lane, value) in element.0.iter().enumerate() {
+                    signals[lane * len + n] = *value;
+                }
+            }
+        }
+    };
+}
+
+// Verifies that `signals_len` is a whole number of lanes, and returns the length of each signal
+fn validate_lanes(signals_len: usize, lanes: usize) -> usize {
+    let is_whole = signals_len % lanes == 0;
+    assert!(
+        is_whole,
+        "Provided signals must be a multiple of the number of lanes. Lanes = {}, got signals len = {}",
+        lanes,
+        signals_len
+    );
+    signals_len / lanes
+}
+
+lanes_type!(
+    F32x4,
+    f32,
+    4,
+    "4 independent `f32` lanes.",
+    pack_f32x4,
+    "Packs 4 consecutive signals of equal length into a single signal of `F32x4` elements, where lane `i` of each \
+     element is from signal `i`.",
+    unpack_f32x4,
+    "Unpacks each lane of `packed` into 4 consecutive signals in `signals`, reversing \
+     [`pack_f32x4`](fn.pack_f32x4.html)."
+);
+lanes_type!(
+    F64x2,
+    f64,
+    2,
+    "2 independent `f64` lanes.",
+    pack_f64x2,
+    "Packs 2 consecutive signals of equal length into a single signal of `F64x2` elements, where lane `i` of each \
+     element is from signal `i`.",
+    unpack_f64x2,
+    "Unpacks each lane of `packed` into 2 consecutive signals in `signals`, reversing \
+     [`pack_f64x2`](fn.pack_f64x2.html)."
+);
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    use crate::test_utils::{compare_float_vectors, random_signal};
+    use crate::DctPlanner;
+
+    /// Verify that each lane of a planned transform matches the same transform planned for `f32`
+    #[test]
+    fn test_lanes_match_scalar() {
+        let mut planner = DctPlanner::<F32x4>::new();
+        let mut scalar_planner = DctPlanner::<f32>::new();
+        for &len in [1, 2, 3, 4, 8, 16, 17, 64, 100, 101].iter() {
+            let signals: Vec<f32> = random_signal(len * F32x4::LANES);
+
+            macro_rules! check_lanes {
+                ($planner_fn:ident, $process_fn:ident) => {
+                    let mut packed = pack_f32x4(&signals);
+                    planner.$planner_fn(len).$process_fn(&mut packed);
+                    let mut actual = vec![0f32; signals.len()];
+                    unpack_f32x4(&packed, &mut actual);
+
+                    let scalar_plan = scalar_planner.$planner_fn(len);
+                    for (signal, actual) in signals.chunks(len).zip(actual.chunks(len)) {
+                        let mut expected = signal.to_vec();
+                        scalar_plan.$process_fn(&mut expected);
+                        assert!(
+                            compare_float_vectors(&expected, actual),
+                            "{}, len = {}",
+                            stringify!($process_fn),
+                            len
+                        );
+                    }
+                };
+            }
+            // the DCT1 is undefined for size 1
+            if len > 1 {
+                check_lanes!(plan_dct1, process_dct1);
+            }
+            check_lanes!(plan_dct2, process_dct2);
+            check_lanes!(plan_dct3, process_dct3);
+            check_lanes!(plan_dct4, process_dct4);
+            check_lanes!(plan_dct6, process_dct6);
+            check_lanes!(plan_dct8, process_dct8);
+            check_lanes!(plan_dst2, process_dst2);
+            check_lanes!(plan_dst4, process_dst4);
+            check_lanes!(plan_dst7, process_dst7);
+        }
+    }
+
+    /// Verify that the `f64` lanes stay independent, even when their magnitudes are very different
+    #[test]
+    fn test_f64x2_lanes_are_independent() {
+        let len = 64;
+        let small: Vec<f64> = (0..len).map(|n| (n as f64 * 0.37).sin() * 1e-6).collect();
+        let large: Vec<f64> = (0..len).map(|n| (n as f64 * 0.11).cos() * 1e6).collect();
+        let signals = [small.clone(), large.clone()].concat();
+
+        let mut packed = pack_f64x2(&signals);
+        DctPlanner::<F64x2>::new()
+            .plan_dct2(len)
+            .process_dct2(&mut packed);
+        let mut actual = vec![0f64; signals.len()];
+        unpack_f64x2(&packed, &mut actual);
+
+        let dct = DctPlanner::<f64>::new().plan_dct2(len);
+        for (mut expected, actual) in vec![small, large].into_iter().zip(actual.chunks(len)) {
+            dct.process_dct2(&mut expected);
+            for (expected, actual) in expected.iter().zip(actual) {
+                assert!((expected - actual).abs() <= expected.abs() * 1e-12 + 1e-18);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pack_round_trip() {
+        let signals: Vec<f32> = (0..12).map(|i| i as f32).collect();
+        let packed = pack_f32x4(&signals);
+        assert_eq!(packed[0], F32x4([0.0, 3.0, 6.0, 9.0]));
+        assert_eq!(packed[2].to_array(), [2.0, 5.0, 8.0, 11.0]);
+
+        let mut unpacked = vec![0f32; 12];
+        unpack_f32x4(&packed, &mut unpacked);
+        assert_eq!(unpacked, signals);
+    }
+
+    #[test]
+    #[should_panic(expected = "Provided signals must be a multiple of the number of lanes")]
+    fn test_pack_wrong_len() {
+        pack_f32x4(&[0.0; 6]);
+    }
+}
@@ -1,7 +1,11 @@
+#![cfg_attr(feature = "portable-simd", feature(portable_simd))]
+
 pub use rustfft;
 pub use rustfft::num_complex;
 pub use rustfft::num_traits;
 
+use std::sync::Arc;
+
 use rustfft::Length;
 
 #[macro_use]
@@ -12,311 +16,1003 @@ pub mod mdct;
 
 pub mod algorithm;
 
+pub mod dct2d;
+
+pub mod batch;
+
+pub mod compat;
+
+pub mod convert;
+
+pub mod backend;
+
+pub mod scaling;
+
+pub mod determinism;
+
+pub mod modal;
+
+pub mod pipeline;
+
+pub mod series;
+
+pub mod synthesis;
+
+pub mod mapping;
+
+pub mod denoise;
+
+pub mod extension;
+
+pub mod prelude;
+
+#[cfg(feature = "bench")]
+pub mod autotune;
+#[cfg(feature = "bench")]
+pub mod bench;
+
+#[cfg(feature = "differential")]
+pub mod differential;
+
+#[cfg(feature = "fixed")]
+pub mod fixed;
+
+#[cfg(feature = "lanes")]
+pub mod lanes;
+
+#[cfg(feature = "rayon")]
+pub mod parallel;
+
 mod array_utils;
 
+mod boundary;
+mod builder;
+mod codec;
+mod cost_model;
+mod dct3_convention;
+mod dst_convention;
+mod inverse;
+mod memory;
+mod output_order;
+mod pair;
 mod plan;
+mod plan_key;
+mod plan_quality;
+mod progress;
+mod scratch_layout;
+#[cfg(feature = "portable-simd")]
+mod simd;
+mod sliding;
+mod transform;
+mod try_process;
 mod twiddles;
-pub use crate::common::DctNum;
+use crate::common::{
+    dct_error_outofplace, dct_error_planar, dct_error_ring, dct_error_two, with_scratch,
+};
+pub use crate::common::{Cancelled, DctNum};
+
+pub use self::boundary::BoundaryMode;
+pub use self::builder::{PlannedTransform, Transform};
+pub use self::codec::CodecBlock;
+pub use self::cost_model::PlannerCostModel;
+pub use self::dct3_convention::Dct3Convention;
+pub use self::dst_convention::DstConvention;
+pub use self::inverse::InversePlan;
+pub use self::memory::{MemoryUsage, MemoryVisitor, PlanMemory};
+pub use self::output_order::OutputOrder;
+pub use self::pair::{Normalization, Type2And3Pair};
+pub use self::plan::{DctPlanner, ScratchLimitError};
+pub use self::plan_key::{FromPlanKey, PlanIdentity, PlanKey, PlanKind};
+pub use self::plan_quality::{PlanQuality, PoorPlanError, PoorPlanPolicy, QualityIssue};
+pub use self::progress::{PlanningStage, ProgressObserver};
+pub use self::scratch_layout::{ScratchLayout, ScratchRegion};
+pub use self::sliding::SlidingDct2;
+pub use self::transform::{DctBuffers, DctTransform};
+pub use self::try_process::{BufferLenError, TryProcess};
 
-pub use self::plan::DctPlanner;
+pub use self::array_utils::{flatten_complex_scratch, transpose};
 
 #[cfg(test)]
 mod test_utils;
 
+/// Identifies one of the transform types supported by RustDCT
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DctType {
+    Dct1,
+    Dct2,
+    Dct3,
+    Dct4,
+    Dct5,
+    Dct6,
+    Dct7,
+    Dct8,
+    Dst1,
+    Dst2,
+    Dst3,
+    Dst4,
+    Dst5,
+    Dst6,
+    Dst7,
+    Dst8,
+}
+
+impl DctType {
+    /// Returns the transform type which inverts this one, up to a scale factor.
+    ///
+    /// Types 2 and 3 are inverses of each other, as are types 6 and 7. Every other type is its own inverse.
+    pub fn inverse(self) -> Self {
+        match self {
+            DctType::Dct2 => DctType::Dct3,
+            DctType::Dct3 => DctType::Dct2,
+            DctType::Dct6 => DctType::Dct7,
+            DctType::Dct7 => DctType::Dct6,
+            DctType::Dst2 => DctType::Dst3,
+            DctType::Dst3 => DctType::Dst2,
+            DctType::Dst6 => DctType::Dst7,
+            DctType::Dst7 => DctType::Dst6,
+            other => other,
+        }
+    }
+}
+
 pub trait RequiredScratch {
+    #[must_use]
     fn get_scratch_len(&self) -> usize;
+
+    /// Returns how many elements at the start of the scratch buffer, counted in `Complex<T>`s, this instance views as
+    /// complex numbers. The scratch buffer must be aligned for `Complex<T>` when this is nonzero, which
+    /// [`flatten_complex_scratch`] guarantees.
+    ///
+    /// `get_scratch_len()` is always at least twice this value. The default implementation returns 0, which is
+    /// correct for algorithms that never compute an FFT. Plans that hand their whole scratch buffer to another plan
+    /// forward that plan's value, and plans that store their own data at the start of the scratch and pass the rest to
+    /// another plan, like the 2D transforms and `plan_mdct`'s IMDCT, add their own data's length to it.
+    #[must_use]
+    fn get_complex_scratch_len(&self) -> usize {
+        0
+    }
+
+    /// Returns how this instance partitions its scratch buffer into named regions.
+    ///
+    /// The default implementation returns a single region named `"scratch"`, covering the whole scratch buffer. The
+    /// FFT-based algorithms, and the plans that store their own data in scratch before passing the rest to an inner
+    /// plan, describe each region separately.
+    #[must_use]
+    fn scratch_layout(&self) -> ScratchLayout {
+        ScratchLayout::new().with_region("scratch", self.get_scratch_len(), false)
+    }
+}
+impl<P: RequiredScratch + ?Sized> RequiredScratch for &P {
+    fn get_scratch_len(&self) -> usize {
+        (**self).get_scratch_len()
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        (**self).get_complex_scratch_len()
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        (**self).scratch_layout()
+    }
+}
+impl<P: RequiredScratch + ?Sized> RequiredScratch for Arc<P> {
+    fn get_scratch_len(&self) -> usize {
+        (**self).get_scratch_len()
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        (**self).get_complex_scratch_len()
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        (**self).scratch_layout()
+    }
+}
+impl<P: RequiredScratch + ?Sized> RequiredScratch for Box<P> {
+    fn get_scratch_len(&self) -> usize {
+        (**self).get_scratch_len()
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        (**self).get_complex_scratch_len()
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        (**self).scratch_layout()
+    }
+}
+
+/// Returns the largest scratch length required by any of the given plans, or 0 if `plans` is empty.
+///
+/// A scratch buffer of this length can be passed to the `process_*_with_scratch` methods of every plan in `plans`.
+/// Plans of different transform types can be mixed, since `RequiredScratch` is implemented for `Arc`, `Box`, and
+/// references to plans:
+///
+/// ~~~
+/// use rustdct::{max_scratch_of, DctPlanner};
+///
+/// let mut planner = DctPlanner::<f32>::new();
+/// let dct2 = planner.plan_dct2(100);
+/// let dct4 = planner.plan_dct4(64);
+///
+/// let scratch = vec![0f32; max_scratch_of(&[&dct2, &dct4])];
+/// ~~~
+#[must_use]
+pub fn max_scratch_of(plans: &[&dyn RequiredScratch]) -> usize {
+    plans
+        .iter()
+        .map(|plan| plan.get_scratch_len())
+        .max()
+        .unwrap_or(0)
 }
 
 /// A trait for algorithms which compute the Discrete Cosine Transform Type 1 (DCT1)
 pub trait Dct1<T: DctNum>: RequiredScratch + Length + Sync + Send {
     /// Computes the DCT Type 1 on the provided buffer, in-place.
     ///
-    /// This method may allocate a Vec<T> of scratch space as needed. If you'd like to reuse that allocation between
-    /// multiple computations, consider calling `process_dct1_with_scratch` instead.
+    /// Scratch space of up to 64 elements is placed on the stack. Otherwise, this method allocates a Vec<T> of scratch
+    /// space. If you'd like to reuse that allocation between multiple computations, consider calling
+    /// `process_dct1_with_scratch` instead.
     ///
     /// Does not normalize outputs.
     fn process_dct1(&self, buffer: &mut [T]) {
-        let mut scratch = vec![T::zero(); self.get_scratch_len()];
-        self.process_dct1_with_scratch(buffer, &mut scratch);
+        with_scratch(self.get_scratch_len(), |scratch| {
+            self.process_dct1_with_scratch(buffer, scratch)
+        });
     }
     /// Computes the DCT Type 1 on the provided buffer, in-place. Uses the provided `scratch` buffer as scratch space.
     ///
     /// Does not normalize outputs.
     fn process_dct1_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]);
+    /// Returns this instance as a [`MemoryUsage`](trait.MemoryUsage.html), so that trait objects like
+    /// `Arc<dyn Dct1<T>>` can report its memory.
+    ///
+    /// The default implementation returns None, and trait objects report that the instance doesn't use any memory.
+    /// Every algorithm in this crate returns itself. Like `known_plan_key`, each transform trait has this method.
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        None
+    }
+
+    /// Returns this instance's [`PlanKey`](struct.PlanKey.html), so that trait objects like `Arc<dyn Dct1<T>>` can
+    /// report it through [`PlanIdentity`](trait.PlanIdentity.html).
+    ///
+    /// The default implementation returns None, and trait objects report an opaque key, which isn't equal to any key.
+    /// Every algorithm in this crate returns its key. Each transform trait has this method, so an algorithm that
+    /// implements several of them returns its key from each one.
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        None
+    }
 }
 
 /// A trait for algorithms which compute the Discrete Cosine Transform Type 2 (DCT2)
 pub trait Dct2<T: DctNum>: RequiredScratch + Length + Sync + Send {
     /// Computes the DCT Type 2 on the provided buffer, in-place.
     ///
-    /// This method may allocate a Vec<T> of scratch space as needed. If you'd like to reuse that allocation between
-    /// multiple computations, consider calling `process_dct2_with_scratch` instead.
+    /// Scratch space of up to 64 elements is placed on the stack. Otherwise, this method allocates a Vec<T> of scratch
+    /// space. If you'd like to reuse that allocation between multiple computations, consider calling
+    /// `process_dct2_with_scratch` instead.
     ///
     /// Does not normalize outputs.
     fn process_dct2(&self, buffer: &mut [T]) {
-        let mut scratch = vec![T::zero(); self.get_scratch_len()];
-        self.process_dct2_with_scratch(buffer, &mut scratch);
+        with_scratch(self.get_scratch_len(), |scratch| {
+            self.process_dct2_with_scratch(buffer, scratch)
+        });
     }
     /// Computes the DCT Type 2 on the provided buffer, in-place. Uses the provided `scratch` buffer as scratch space.
     ///
     /// Does not normalize outputs.
     fn process_dct2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]);
+
+    /// Computes the DCT Type 2 of `input`, and stores the result in `output`. Uses the provided `scratch` buffer as
+    /// scratch space, which must be at least `get_scratch_len()` elements long.
+    ///
+    /// `input` is never modified, so it can come from read-only memory, like a memory-mapped file. The default
+    /// implementation copies `input` into `output` and computes the transform in-place, but some algorithms, like
+    /// `Type2And3Naive` and `Type2And3ConvertToFft`, read directly from `input` and skip the copy.
+    ///
+    /// Does not normalize outputs.
+    fn process_dct2_from(&self, input: &[T], output: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers_outofplace!(
+            input,
+            output,
+            scratch,
+            self.len(),
+            self.get_scratch_len()
+        );
+        output.copy_from_slice(input);
+        self.process_dct2_with_scratch(output, scratch);
+    }
+
+    /// Computes the DCT Type 2 of a frame that's split into two slices, and stores the result in `output`. Uses the
+    /// provided `scratch` buffer as scratch space, which must be at least `get_scratch_len()` elements long.
+    ///
+    /// The frame is `first` followed by `second`, so a frame that wraps around the end of a ring buffer can be
+    /// transformed without first copying it into a contiguous buffer. The lengths of `first` and `second` must add up
+    /// to `len()`, and either one may be empty. Like the MDCT's two input slices, neither slice is modified.
+    ///
+    /// Does not normalize outputs.
+    fn process_dct2_ring(&self, first: &[T], second: &[T], output: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers_ring!(
+            first,
+            second,
+            output,
+            scratch,
+            self.len(),
+            self.get_scratch_len()
+        );
+        let (output_first, output_second) = output.split_at_mut(first.len());
+        output_first.copy_from_slice(first);
+        output_second.copy_from_slice(second);
+        self.process_dct2_with_scratch(output, scratch);
+    }
+
+    /// Computes the DCT Type 2 of two signals of length `len()`, `a` and `b`, in-place. Uses the provided `scratch`
+    /// buffer as scratch space, which must be at least `get_scratch_len()` elements long.
+    ///
+    /// The default implementation computes the two transforms one after the other. `Type2And3ConvertToFft` puts `a` in
+    /// the real part and `b` in the imaginary part of a single FFT, and separates the two outputs afterwards, so it
+    /// computes both transforms for about the cost of one, which suits stereo and other pairwise workloads.
+    /// [`DctPlanner::plan_dct2_two`](struct.DctPlanner.html#method.plan_dct2_two) plans an instance that uses it.
+    ///
+    /// With the `unchecked-process` feature, the lengths of `a` and `b` aren't checked in release builds. If `b` is
+    /// longer than `a`, `Type2And3ConvertToFft` only transforms its first `len()` elements, and silently returns an
+    /// incorrect result instead of panicking.
+    ///
+    /// Does not normalize outputs.
+    fn process_dct2_two(&self, a: &mut [T], b: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers_two!(a, b, scratch, self.len(), self.get_scratch_len());
+        self.process_dct2_with_scratch(a, scratch);
+        self.process_dct2_with_scratch(b, scratch);
+    }
+
+    /// Computes the DCT Type 2 on each channel of a planar multi-channel signal, in-place, using the provided `scratch`
+    /// buffer as scratch space, which must be at least `get_scratch_len()` elements long.
+    ///
+    /// Every channel must have length `len()`. All of the lengths are validated before any channel is processed, so if
+    /// one of them is wrong, none of the channels are modified. Processing every channel with one call keeps the plan's
+    /// precomputed data in cache between channels.
+    ///
+    /// Does not normalize outputs.
+    fn process_dct2_planar(&self, channels: &mut [&mut [T]], scratch: &mut [T]) {
+        let scratch =
+            validate_buffers_planar!(channels, scratch, self.len(), self.get_scratch_len());
+        for channel in channels.iter_mut() {
+            self.process_dct2_with_scratch(channel, scratch);
+        }
+    }
+
+    /// Computes the DCT Type 2 on the provided buffer, in-place, using the provided `scratch` buffer as scratch space.
+    /// Calls `should_continue` periodically, and stops early, returning `Err(Cancelled)`, if it returns false.
+    ///
+    /// How often `should_continue` is called depends on the algorithm: Recursive algorithms like `Type2And3SplitRadix`
+    /// call it before each level of recursion, while the default implementation only calls it once, before starting.
+    /// If the transform is cancelled, the contents of `buffer` are unspecified.
+    ///
+    /// Does not normalize outputs.
+    fn process_dct2_cancellable(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        should_continue: &mut dyn FnMut() -> bool,
+    ) -> Result<(), Cancelled> {
+        if !should_continue() {
+            return Err(Cancelled);
+        }
+        self.process_dct2_with_scratch(buffer, scratch);
+        Ok(())
+    }
+    /// Returns this instance as a [`MemoryUsage`](trait.MemoryUsage.html), or None if it doesn't report one. See
+    /// [`Dct1::as_memory_usage`](trait.Dct1.html#method.as_memory_usage).
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        None
+    }
+    /// Returns this instance's [`PlanKey`](struct.PlanKey.html), or None if it doesn't report one. See
+    /// [`Dct1::known_plan_key`](trait.Dct1.html#method.known_plan_key).
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        None
+    }
 }
 
 /// A trait for algorithms which compute the Discrete Cosine Transform Type 3 (DCT3)
 pub trait Dct3<T: DctNum>: RequiredScratch + Length + Sync + Send {
     /// Computes the DCT Type 3 on the provided buffer, in-place.
     ///
-    /// This method may allocate a Vec<T> of scratch space as needed. If you'd like to reuse that allocation between
-    /// multiple computations, consider calling `process_dct3_with_scratch` instead.
+    /// Scratch space of up to 64 elements is placed on the stack. Otherwise, this method allocates a Vec<T> of scratch
+    /// space. If you'd like to reuse that allocation between multiple computations, consider calling
+    /// `process_dct3_with_scratch` instead.
     ///
     /// Does not normalize outputs.
     fn process_dct3(&self, buffer: &mut [T]) {
-        let mut scratch = vec![T::zero(); self.get_scratch_len()];
-        self.process_dct3_with_scratch(buffer, &mut scratch);
+        with_scratch(self.get_scratch_len(), |scratch| {
+            self.process_dct3_with_scratch(buffer, scratch)
+        });
     }
     /// Computes the DCT Type 3 on the provided buffer, in-place. Uses the provided `scratch` buffer as scratch space.
     ///
     /// Does not normalize outputs.
     fn process_dct3_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]);
+
+    /// Computes the DCT Type 3 on the provided buffer, in-place, and multiplies each output by `scale`. Uses the
+    /// provided `scratch` buffer as scratch space.
+    ///
+    /// This is meant for normalizing inverse transforms, like scaling by `2 / len` to undo a DCT Type 2. The default
+    /// implementation scales the output in a separate pass over the buffer, but the FFT-based, split radix, and naive
+    /// algorithms apply `scale` while they write their outputs, so the scaling costs almost nothing.
+    fn process_dct3_scaled(&self, buffer: &mut [T], scratch: &mut [T], scale: T) {
+        self.process_dct3_with_scratch(buffer, scratch);
+        for element in buffer.iter_mut() {
+            *element = *element * scale;
+        }
+    }
+
+    /// Computes the DCT Type 3 of `input`, and stores the result in `output`. Uses the provided `scratch` buffer as
+    /// scratch space, which must be at least `get_scratch_len()` elements long.
+    ///
+    /// `input` is never modified, so it can come from read-only memory, like a memory-mapped file. The default
+    /// implementation copies `input` into `output` and computes the transform in-place, but some algorithms, like
+    /// `Type2And3Naive` and `Type2And3ConvertToFft`, read directly from `input` and skip the copy.
+    ///
+    /// Does not normalize outputs.
+    fn process_dct3_from(&self, input: &[T], output: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers_outofplace!(
+            input,
+            output,
+            scratch,
+            self.len(),
+            self.get_scratch_len()
+        );
+        output.copy_from_slice(input);
+        self.process_dct3_with_scratch(output, scratch);
+    }
+
+    /// Computes the DCT Type 3 on each channel of a planar multi-channel signal, in-place, using the provided `scratch`
+    /// buffer as scratch space, which must be at least `get_scratch_len()` elements long.
+    ///
+    /// Every channel must have length `len()`. All of the lengths are validated before any channel is processed, so if
+    /// one of them is wrong, none of the channels are modified. Processing every channel with one call keeps the plan's
+    /// precomputed data in cache between channels.
+    ///
+    /// Does not normalize outputs.
+    fn process_dct3_planar(&self, channels: &mut [&mut [T]], scratch: &mut [T]) {
+        let scratch =
+            validate_buffers_planar!(channels, scratch, self.len(), self.get_scratch_len());
+        for channel in channels.iter_mut() {
+            self.process_dct3_with_scratch(channel, scratch);
+        }
+    }
+
+    /// Computes the DCT Type 3 on the provided buffer, in-place, using the provided `scratch` buffer as scratch space.
+    /// Calls `should_continue` periodically, and stops early, returning `Err(Cancelled)`, if it returns false.
+    ///
+    /// How often `should_continue` is called depends on the algorithm: Recursive algorithms like `Type2And3SplitRadix`
+    /// call it before each level of recursion, while the default implementation only calls it once, before starting.
+    /// If the transform is cancelled, the contents of `buffer` are unspecified.
+    ///
+    /// Does not normalize outputs.
+    fn process_dct3_cancellable(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        should_continue: &mut dyn FnMut() -> bool,
+    ) -> Result<(), Cancelled> {
+        if !should_continue() {
+            return Err(Cancelled);
+        }
+        self.process_dct3_with_scratch(buffer, scratch);
+        Ok(())
+    }
+    /// Returns this instance as a [`MemoryUsage`](trait.MemoryUsage.html), or None if it doesn't report one. See
+    /// [`Dct1::as_memory_usage`](trait.Dct1.html#method.as_memory_usage).
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        None
+    }
+    /// Returns this instance's [`PlanKey`](struct.PlanKey.html), or None if it doesn't report one. See
+    /// [`Dct1::known_plan_key`](trait.Dct1.html#method.known_plan_key).
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        None
+    }
 }
 
 /// A trait for algorithms which compute the Discrete Cosine Transform Type 4 (DCT4)
 pub trait Dct4<T: DctNum>: RequiredScratch + Length + Sync + Send {
     /// Computes the DCT Type 4 on the provided buffer, in-place.
     ///
-    /// This method may allocate a Vec<T> of scratch space as needed. If you'd like to reuse that allocation between
-    /// multiple computations, consider calling `process_dst4_with_scratch` instead.
+    /// Scratch space of up to 64 elements is placed on the stack. Otherwise, this method allocates a Vec<T> of scratch
+    /// space. If you'd like to reuse that allocation between multiple computations, consider calling
+    /// `process_dst4_with_scratch` instead.
     ///
     /// Does not normalize outputs.
     fn process_dct4(&self, buffer: &mut [T]) {
-        let mut scratch = vec![T::zero(); self.get_scratch_len()];
-        self.process_dct4_with_scratch(buffer, &mut scratch);
+        with_scratch(self.get_scratch_len(), |scratch| {
+            self.process_dct4_with_scratch(buffer, scratch)
+        });
     }
     /// Computes the DCT Type 4 on the provided buffer, in-place. Uses the provided `scratch` buffer as scratch space.
     ///
     /// Does not normalize outputs.
     fn process_dct4_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]);
+    /// Returns this instance as a [`MemoryUsage`](trait.MemoryUsage.html), or None if it doesn't report one. See
+    /// [`Dct1::as_memory_usage`](trait.Dct1.html#method.as_memory_usage).
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        None
+    }
+    /// Returns this instance's [`PlanKey`](struct.PlanKey.html), or None if it doesn't report one. See
+    /// [`Dct1::known_plan_key`](trait.Dct1.html#method.known_plan_key).
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        None
+    }
 }
 
 /// A trait for algorithms which compute the Discrete Cosine Transform Type 5 (DCT5)
 pub trait Dct5<T: DctNum>: RequiredScratch + Length + Sync + Send {
     /// Computes the DCT Type 5 on the provided buffer, in-place.
     ///
-    /// This method may allocate a Vec<T> of scratch space as needed. If you'd like to reuse that allocation between
-    /// multiple computations, consider calling `process_dct5_with_scratch` instead.
+    /// Scratch space of up to 64 elements is placed on the stack. Otherwise, this method allocates a Vec<T> of scratch
+    /// space. If you'd like to reuse that allocation between multiple computations, consider calling
+    /// `process_dct5_with_scratch` instead.
     ///
     /// Does not normalize outputs.
     fn process_dct5(&self, buffer: &mut [T]) {
-        let mut scratch = vec![T::zero(); self.get_scratch_len()];
-        self.process_dct5_with_scratch(buffer, &mut scratch);
+        with_scratch(self.get_scratch_len(), |scratch| {
+            self.process_dct5_with_scratch(buffer, scratch)
+        });
     }
     /// Computes the DCT Type 5 on the provided buffer, in-place. Uses the provided `scratch` buffer as scratch space.
     ///
     /// Does not normalize outputs.
     fn process_dct5_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]);
+    /// Returns this instance as a [`MemoryUsage`](trait.MemoryUsage.html), or None if it doesn't report one. See
+    /// [`Dct1::as_memory_usage`](trait.Dct1.html#method.as_memory_usage).
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        None
+    }
+    /// Returns this instance's [`PlanKey`](struct.PlanKey.html), or None if it doesn't report one. See
+    /// [`Dct1::known_plan_key`](trait.Dct1.html#method.known_plan_key).
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        None
+    }
 }
 
 /// A trait for algorithms which compute the Discrete Cosine Transform Type 6 (DCT6)
 pub trait Dct6<T: DctNum>: RequiredScratch + Length + Sync + Send {
     /// Computes the DCT Type 6 on the provided buffer, in-place.
     ///
-    /// This method may allocate a Vec<T> of scratch space as needed. If you'd like to reuse that allocation between
-    /// multiple computations, consider calling `process_dct6_with_scratch` instead.
+    /// Scratch space of up to 64 elements is placed on the stack. Otherwise, this method allocates a Vec<T> of scratch
+    /// space. If you'd like to reuse that allocation between multiple computations, consider calling
+    /// `process_dct6_with_scratch` instead.
     ///
     /// Does not normalize outputs.
     fn process_dct6(&self, buffer: &mut [T]) {
-        let mut scratch = vec![T::zero(); self.get_scratch_len()];
-        self.process_dct6_with_scratch(buffer, &mut scratch);
+        with_scratch(self.get_scratch_len(), |scratch| {
+            self.process_dct6_with_scratch(buffer, scratch)
+        });
     }
     /// Computes the DCT Type 6 on the provided buffer, in-place. Uses the provided `scratch` buffer as scratch space.
     ///
     /// Does not normalize outputs.
     fn process_dct6_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]);
+    /// Returns this instance as a [`MemoryUsage`](trait.MemoryUsage.html), or None if it doesn't report one. See
+    /// [`Dct1::as_memory_usage`](trait.Dct1.html#method.as_memory_usage).
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        None
+    }
+    /// Returns this instance's [`PlanKey`](struct.PlanKey.html), or None if it doesn't report one. See
+    /// [`Dct1::known_plan_key`](trait.Dct1.html#method.known_plan_key).
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        None
+    }
 }
 
 /// A trait for algorithms which compute the Discrete Cosine Transform Type 7 (DCT7)
 pub trait Dct7<T: DctNum>: RequiredScratch + Length + Sync + Send {
     /// Computes the DCT Type 7 on the provided buffer, in-place.
     ///
-    /// This method may allocate a Vec<T> of scratch space as needed. If you'd like to reuse that allocation between
-    /// multiple computations, consider calling `process_dct7_with_scratch` instead.
+    /// Scratch space of up to 64 elements is placed on the stack. Otherwise, this method allocates a Vec<T> of scratch
+    /// space. If you'd like to reuse that allocation between multiple computations, consider calling
+    /// `process_dct7_with_scratch` instead.
     ///
     /// Does not normalize outputs.
     fn process_dct7(&self, buffer: &mut [T]) {
-        let mut scratch = vec![T::zero(); self.get_scratch_len()];
-        self.process_dct7_with_scratch(buffer, &mut scratch);
+        with_scratch(self.get_scratch_len(), |scratch| {
+            self.process_dct7_with_scratch(buffer, scratch)
+        });
     }
     /// Computes the DCT Type 7 on the provided buffer, in-place. Uses the provided `scratch` buffer as scratch space.
     ///
     /// Does not normalize outputs.
     fn process_dct7_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]);
+    /// Returns this instance as a [`MemoryUsage`](trait.MemoryUsage.html), or None if it doesn't report one. See
+    /// [`Dct1::as_memory_usage`](trait.Dct1.html#method.as_memory_usage).
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        None
+    }
+    /// Returns this instance's [`PlanKey`](struct.PlanKey.html), or None if it doesn't report one. See
+    /// [`Dct1::known_plan_key`](trait.Dct1.html#method.known_plan_key).
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        None
+    }
 }
 
 /// A trait for algorithms which compute the Discrete Cosine Transform Type 8 (DCT8)
 pub trait Dct8<T: DctNum>: RequiredScratch + Length + Sync + Send {
     /// Computes the DCT Type 8 on the provided buffer, in-place.
     ///
-    /// This method may allocate a Vec<T> of scratch space as needed. If you'd like to reuse that allocation between
-    /// multiple computations, consider calling `process_dct8_with_scratch` instead.
+    /// Scratch space of up to 64 elements is placed on the stack. Otherwise, this method allocates a Vec<T> of scratch
+    /// space. If you'd like to reuse that allocation between multiple computations, consider calling
+    /// `process_dct8_with_scratch` instead.
     ///
     /// Does not normalize outputs.
     fn process_dct8(&self, buffer: &mut [T]) {
-        let mut scratch = vec![T::zero(); self.get_scratch_len()];
-        self.process_dct8_with_scratch(buffer, &mut scratch);
+        with_scratch(self.get_scratch_len(), |scratch| {
+            self.process_dct8_with_scratch(buffer, scratch)
+        });
     }
     /// Computes the DCT Type 8 on the provided buffer, in-place. Uses the provided `scratch` buffer as scratch space.
     ///
     /// Does not normalize outputs.
     fn process_dct8_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]);
+    /// Returns this instance as a [`MemoryUsage`](trait.MemoryUsage.html), or None if it doesn't report one. See
+    /// [`Dct1::as_memory_usage`](trait.Dct1.html#method.as_memory_usage).
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        None
+    }
+    /// Returns this instance's [`PlanKey`](struct.PlanKey.html), or None if it doesn't report one. See
+    /// [`Dct1::known_plan_key`](trait.Dct1.html#method.known_plan_key).
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        None
+    }
 }
 
 /// A trait for algorithms which compute the Discrete Sine Transform Type 1 (DST1)
 pub trait Dst1<T: DctNum>: RequiredScratch + Length + Sync + Send {
     /// Computes the DST Type 1 on the provided buffer, in-place.
     ///
-    /// This method may allocate a Vec<T> of scratch space as needed. If you'd like to reuse that allocation between
-    /// multiple computations, consider calling `process_dst1_with_scratch` instead.
+    /// Scratch space of up to 64 elements is placed on the stack. Otherwise, this method allocates a Vec<T> of scratch
+    /// space. If you'd like to reuse that allocation between multiple computations, consider calling
+    /// `process_dst1_with_scratch` instead.
     ///
     /// Does not normalize outputs.
     fn process_dst1(&self, buffer: &mut [T]) {
-        let mut scratch = vec![T::zero(); self.get_scratch_len()];
-        self.process_dst1_with_scratch(buffer, &mut scratch);
+        with_scratch(self.get_scratch_len(), |scratch| {
+            self.process_dst1_with_scratch(buffer, scratch)
+        });
     }
     /// Computes the DST Type 1 on the provided buffer, in-place. Uses the provided `scratch` buffer as scratch space.
     ///
     /// Does not normalize outputs.
     fn process_dst1_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]);
+    /// Returns this instance as a [`MemoryUsage`](trait.MemoryUsage.html), or None if it doesn't report one. See
+    /// [`Dct1::as_memory_usage`](trait.Dct1.html#method.as_memory_usage).
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        None
+    }
+    /// Returns this instance's [`PlanKey`](struct.PlanKey.html), or None if it doesn't report one. See
+    /// [`Dct1::known_plan_key`](trait.Dct1.html#method.known_plan_key).
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        None
+    }
 }
 
 /// A trait for algorithms which compute the Discrete Sine Transform Type 2 (DST2)
 pub trait Dst2<T: DctNum>: RequiredScratch + Length + Sync + Send {
     /// Computes the DST Type 2 on the provided buffer, in-place.
     ///
-    /// This method may allocate a Vec<T> of scratch space as needed. If you'd like to reuse that allocation between
-    /// multiple computations, consider calling `process_dst2_with_scratch` instead.
+    /// Scratch space of up to 64 elements is placed on the stack. Otherwise, this method allocates a Vec<T> of scratch
+    /// space. If you'd like to reuse that allocation between multiple computations, consider calling
+    /// `process_dst2_with_scratch` instead.
     ///
     /// Does not normalize outputs.
     fn process_dst2(&self, buffer: &mut [T]) {
-        let mut scratch = vec![T::zero(); self.get_scratch_len()];
-        self.process_dst2_with_scratch(buffer, &mut scratch);
+        with_scratch(self.get_scratch_len(), |scratch| {
+            self.process_dst2_with_scratch(buffer, scratch)
+        });
     }
     /// Computes the DST Type 2 on the provided buffer, in-place. Uses the provided `scratch` buffer as scratch space.
     ///
     /// Does not normalize outputs.
     fn process_dst2_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]);
+
+    /// Computes the DST Type 2 on the provided buffer, in-place, using the provided `scratch` buffer as scratch space.
+    /// Calls `should_continue` periodically, and stops early, returning `Err(Cancelled)`, if it returns false.
+    ///
+    /// How often `should_continue` is called depends on the algorithm: Recursive algorithms like `Type2And3SplitRadix`
+    /// call it before each level of recursion, while the default implementation only calls it once, before starting.
+    /// If the transform is cancelled, the contents of `buffer` are unspecified.
+    ///
+    /// Does not normalize outputs.
+    fn process_dst2_cancellable(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        should_continue: &mut dyn FnMut() -> bool,
+    ) -> Result<(), Cancelled> {
+        if !should_continue() {
+            return Err(Cancelled);
+        }
+        self.process_dst2_with_scratch(buffer, scratch);
+        Ok(())
+    }
+    /// Returns this instance as a [`MemoryUsage`](trait.MemoryUsage.html), or None if it doesn't report one. See
+    /// [`Dct1::as_memory_usage`](trait.Dct1.html#method.as_memory_usage).
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        None
+    }
+    /// Returns this instance's [`PlanKey`](struct.PlanKey.html), or None if it doesn't report one. See
+    /// [`Dct1::known_plan_key`](trait.Dct1.html#method.known_plan_key).
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        None
+    }
 }
 
 /// A trait for algorithms which compute the Discrete Sine Transform Type 3 (DST3)
 pub trait Dst3<T: DctNum>: RequiredScratch + Length + Sync + Send {
     /// Computes the DST Type 3 on the provided buffer, in-place.
     ///
-    /// This method may allocate a Vec<T> of scratch space as needed. If you'd like to reuse that allocation between
-    /// multiple computations, consider calling `process_dst3_with_scratch` instead.
+    /// Scratch space of up to 64 elements is placed on the stack. Otherwise, this method allocates a Vec<T> of scratch
+    /// space. If you'd like to reuse that allocation between multiple computations, consider calling
+    /// `process_dst3_with_scratch` instead.
     ///
     /// Does not normalize outputs.
     fn process_dst3(&self, buffer: &mut [T]) {
-        let mut scratch = vec![T::zero(); self.get_scratch_len()];
-        self.process_dst3_with_scratch(buffer, &mut scratch);
+        with_scratch(self.get_scratch_len(), |scratch| {
+            self.process_dst3_with_scratch(buffer, scratch)
+        });
     }
     /// Computes the DST Type 3 on the provided buffer, in-place. Uses the provided `scratch` buffer as scratch space.
     ///
     /// Does not normalize outputs.
     fn process_dst3_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]);
+
+    /// Computes the DST Type 3 on the provided buffer, in-place, and multiplies each output by `scale`. Uses the
+    /// provided `scratch` buffer as scratch space.
+    ///
+    /// This is meant for normalizing inverse transforms, like scaling by `2 / len` to undo a DST Type 2. The default
+    /// implementation scales the output in a separate pass over the buffer, but the FFT-based, split radix, and naive
+    /// algorithms apply `scale` while they write their outputs, so the scaling costs almost nothing.
+    fn process_dst3_scaled(&self, buffer: &mut [T], scratch: &mut [T], scale: T) {
+        self.process_dst3_with_scratch(buffer, scratch);
+        for element in buffer.iter_mut() {
+            *element = *element * scale;
+        }
+    }
+
+    /// Computes the DST Type 3 on the provided buffer, in-place, using the provided `scratch` buffer as scratch space.
+    /// Calls `should_continue` periodically, and stops early, returning `Err(Cancelled)`, if it returns false.
+    ///
+    /// How often `should_continue` is called depends on the algorithm: Recursive algorithms like `Type2And3SplitRadix`
+    /// call it before each level of recursion, while the default implementation only calls it once, before starting.
+    /// If the transform is cancelled, the contents of `buffer` are unspecified.
+    ///
+    /// Does not normalize outputs.
+    fn process_dst3_cancellable(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        should_continue: &mut dyn FnMut() -> bool,
+    ) -> Result<(), Cancelled> {
+        if !should_continue() {
+            return Err(Cancelled);
+        }
+        self.process_dst3_with_scratch(buffer, scratch);
+        Ok(())
+    }
+    /// Returns this instance as a [`MemoryUsage`](trait.MemoryUsage.html), or None if it doesn't report one. See
+    /// [`Dct1::as_memory_usage`](trait.Dct1.html#method.as_memory_usage).
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        None
+    }
+    /// Returns this instance's [`PlanKey`](struct.PlanKey.html), or None if it doesn't report one. See
+    /// [`Dct1::known_plan_key`](trait.Dct1.html#method.known_plan_key).
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        None
+    }
 }
 
 /// A trait for algorithms which compute the Discrete Sine Transform Type 4 (DST4)
 pub trait Dst4<T: DctNum>: RequiredScratch + Length + Sync + Send {
     /// Computes the DST Type 4 on the provided buffer, in-place.
     ///
-    /// This method may allocate a Vec<T> of scratch space as needed. If you'd like to reuse that allocation between
-    /// multiple computations, consider calling `process_dst4_with_scratch` instead.
+    /// Scratch space of up to 64 elements is placed on the stack. Otherwise, this method allocates a Vec<T> of scratch
+    /// space. If you'd like to reuse that allocation between multiple computations, consider calling
+    /// `process_dst4_with_scratch` instead.
     ///
     /// Does not normalize outputs.
     fn process_dst4(&self, buffer: &mut [T]) {
-        let mut scratch = vec![T::zero(); self.get_scratch_len()];
-        self.process_dst4_with_scratch(buffer, &mut scratch);
+        with_scratch(self.get_scratch_len(), |scratch| {
+            self.process_dst4_with_scratch(buffer, scratch)
+        });
     }
     /// Computes the DST Type 4 on the provided buffer, in-place. Uses the provided `scratch` buffer as scratch space.
     ///
     /// Does not normalize outputs.
     fn process_dst4_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]);
+    /// Returns this instance as a [`MemoryUsage`](trait.MemoryUsage.html), or None if it doesn't report one. See
+    /// [`Dct1::as_memory_usage`](trait.Dct1.html#method.as_memory_usage).
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        None
+    }
+    /// Returns this instance's [`PlanKey`](struct.PlanKey.html), or None if it doesn't report one. See
+    /// [`Dct1::known_plan_key`](trait.Dct1.html#method.known_plan_key).
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        None
+    }
 }
 
 /// A trait for algorithms which compute the Discrete Cosine Transform Type 5 (DST5)
 pub trait Dst5<T: DctNum>: RequiredScratch + Length + Sync + Send {
     /// Computes the DST Type 5 on the provided buffer, in-place.
     ///
-    /// This method may allocate a Vec<T> of scratch space as needed. If you'd like to reuse that allocation between
-    /// multiple computations, consider calling `process_dst4_with_scratch` instead.
+    /// Scratch space of up to 64 elements is placed on the stack. Otherwise, this method allocates a Vec<T> of scratch
+    /// space. If you'd like to reuse that allocation between multiple computations, consider calling
+    /// `process_dst4_with_scratch` instead.
     ///
     /// Does not normalize outputs.
     fn process_dst5(&self, buffer: &mut [T]) {
-        let mut scratch = vec![T::zero(); self.get_scratch_len()];
-        self.process_dst5_with_scratch(buffer, &mut scratch);
+        with_scratch(self.get_scratch_len(), |scratch| {
+            self.process_dst5_with_scratch(buffer, scratch)
+        });
     }
     /// Computes the DST Type 5 on the provided buffer, in-place. Uses the provided `scratch` buffer as scratch space.
     ///
     /// Does not normalize outputs.
     fn process_dst5_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]);
+    /// Returns this instance as a [`MemoryUsage`](trait.MemoryUsage.html), or None if it doesn't report one. See
+    /// [`Dct1::as_memory_usage`](trait.Dct1.html#method.as_memory_usage).
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        None
+    }
+    /// Returns this instance's [`PlanKey`](struct.PlanKey.html), or None if it doesn't report one. See
+    /// [`Dct1::known_plan_key`](trait.Dct1.html#method.known_plan_key).
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        None
+    }
 }
 
 /// A trait for algorithms which compute the Discrete Cosine Transform Type 6 (DST6)
 pub trait Dst6<T: DctNum>: RequiredScratch + Length + Sync + Send {
     /// Computes the DST Type 6 on the provided buffer, in-place.
     ///
-    /// This method may allocate a Vec<T> of scratch space as needed. If you'd like to reuse that allocation between
-    /// multiple computations, consider calling `process_dst6_with_scratch` instead.
+    /// Scratch space of up to 64 elements is placed on the stack. Otherwise, this method allocates a Vec<T> of scratch
+    /// space. If you'd like to reuse that allocation between multiple computations, consider calling
+    /// `process_dst6_with_scratch` instead.
     ///
     /// Does not normalize outputs.
     fn process_dst6(&self, buffer: &mut [T]) {
-        let mut scratch = vec![T::zero(); self.get_scratch_len()];
-        self.process_dst6_with_scratch(buffer, &mut scratch);
+        with_scratch(self.get_scratch_len(), |scratch| {
+            self.process_dst6_with_scratch(buffer, scratch)
+        });
     }
     /// Computes the DST Type 6 on the provided buffer, in-place. Uses the provided `scratch` buffer as scratch space.
     ///
     /// Does not normalize outputs.
     fn process_dst6_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]);
+    /// Returns this instance as a [`MemoryUsage`](trait.MemoryUsage.html), or None if it doesn't report one. See
+    /// [`Dct1::as_memory_usage`](trait.Dct1.html#method.as_memory_usage).
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        None
+    }
+    /// Returns this instance's [`PlanKey`](struct.PlanKey.html), or None if it doesn't report one. See
+    /// [`Dct1::known_plan_key`](trait.Dct1.html#method.known_plan_key).
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        None
+    }
 }
 
 /// A trait for algorithms which compute the Discrete Cosine Transform Type 7 (DST7)
 pub trait Dst7<T: DctNum>: RequiredScratch + Length + Sync + Send {
     /// Computes the DST Type 7 on the provided buffer, in-place.
     ///
-    /// This method may allocate a Vec<T> of scratch space as needed. If you'd like to reuse that allocation between
-    /// multiple computations, consider calling `process_dst7_with_scratch` instead.
+    /// Scratch space of up to 64 elements is placed on the stack. Otherwise, this method allocates a Vec<T> of scratch
+    /// space. If you'd like to reuse that allocation between multiple computations, consider calling
+    /// `process_dst7_with_scratch` instead.
     ///
     /// Does not normalize outputs.
     fn process_dst7(&self, buffer: &mut [T]) {
-        let mut scratch = vec![T::zero(); self.get_scratch_len()];
-        self.process_dst7_with_scratch(buffer, &mut scratch);
+        with_scratch(self.get_scratch_len(), |scratch| {
+            self.process_dst7_with_scratch(buffer, scratch)
+        });
     }
     /// Computes the DST Type 7 on the provided buffer, in-place. Uses the provided `scratch` buffer as scratch space.
     ///
     /// Does not normalize outputs.
     fn process_dst7_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]);
+    /// Returns this instance as a [`MemoryUsage`](trait.MemoryUsage.html), or None if it doesn't report one. See
+    /// [`Dct1::as_memory_usage`](trait.Dct1.html#method.as_memory_usage).
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        None
+    }
+    /// Returns this instance's [`PlanKey`](struct.PlanKey.html), or None if it doesn't report one. See
+    /// [`Dct1::known_plan_key`](trait.Dct1.html#method.known_plan_key).
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        None
+    }
 }
 
 /// A trait for algorithms which compute the Discrete Cosine Transform Type 8 (DST8)
 pub trait Dst8<T: DctNum>: RequiredScratch + Length + Sync + Send {
     /// Computes the DST Type 8 on the provided buffer, in-place.
     ///
-    /// This method may allocate a Vec<T> of scratch space as needed. If you'd like to reuse that allocation between
-    /// multiple computations, consider calling `process_dst8_with_scratch` instead.
+    /// Scratch space of up to 64 elements is placed on the stack. Otherwise, this method allocates a Vec<T> of scratch
+    /// space. If you'd like to reuse that allocation between multiple computations, consider calling
+    /// `process_dst8_with_scratch` instead.
     ///
     /// Does not normalize outputs.
     fn process_dst8(&self, buffer: &mut [T]) {
-        let mut scratch = vec![T::zero(); self.get_scratch_len()];
-        self.process_dst8_with_scratch(buffer, &mut scratch);
+        with_scratch(self.get_scratch_len(), |scratch| {
+            self.process_dst8_with_scratch(buffer, scratch)
+        });
     }
     /// Computes the DST Type 8 on the provided buffer, in-place. Uses the provided `scratch` buffer as scratch space.
     ///
     /// Does not normalize outputs.
     fn process_dst8_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]);
+    /// Returns this instance as a [`MemoryUsage`](trait.MemoryUsage.html), or None if it doesn't report one. See
+    /// [`Dct1::as_memory_usage`](trait.Dct1.html#method.as_memory_usage).
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        None
+    }
+    /// Returns this instance's [`PlanKey`](struct.PlanKey.html), or None if it doesn't report one. See
+    /// [`Dct1::known_plan_key`](trait.Dct1.html#method.known_plan_key).
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        None
+    }
 }
 
 /// A trait for algorithms that can compute all of DCT2, DCT3, DST2, DST3, all in one struct
-pub trait TransformType2And3<T: DctNum>: Dct2<T> + Dct3<T> + Dst2<T> + Dst3<T> {}
+pub trait TransformType2And3<T: DctNum>: Dct2<T> + Dct3<T> + Dst2<T> + Dst3<T> {
+    /// Computes both the DCT Type 2 and the DST Type 2 of `input`, and stores them in `dct_output` and `dst_output`.
+    /// Uses the provided `scratch` buffer as scratch space, which must be at least `get_scratch_len()` elements long.
+    ///
+    /// The default implementation computes the two transforms separately, but `Type2And3ConvertToFft` computes both
+    /// with a single FFT, which is about 25% faster than two separate calls.
+    ///
+    /// Does not normalize outputs.
+    fn process_dct2_dst2(
+        &self,
+        input: &[T],
+        dct_output: &mut [T],
+        dst_output: &mut [T],
+        scratch: &mut [T],
+    ) {
+        let scratch = validate_buffers_outofplace!(
+            input,
+            dct_output,
+            scratch,
+            self.len(),
+            self.get_scratch_len()
+        );
+        if dst_output.len() != self.len() {
+            dct_error_outofplace(
+                input.len(),
+                dst_output.len(),
+                scratch.len(),
+                self.len(),
+                self.get_scratch_len(),
+            );
+            return;
+        }
+
+        self.process_dct2_from(input, dct_output, scratch);
+        dst_output.copy_from_slice(input);
+        self.process_dst2_with_scratch(dst_output, scratch);
+    }
+
+    /// Converts the 16-bit PCM samples in `input` to floats in `[-1, 1)`, computes the DCT Type 2 of the converted
+    /// samples, and stores the result in `output`. Uses the provided `scratch` buffer as scratch space, which must be at
+    /// least `get_scratch_len()` elements long.
+    ///
+    /// The samples are scaled the same way as [`convert::pcm_to_float`](convert/fn.pcm_to_float.html). The default
+    /// implementation converts `input` into `output` and then transforms it in-place, but `Type2And3ConvertToFft`
+    /// converts each sample while it reorders its input, so the samples are only read once.
+    ///
+    /// Does not normalize outputs.
+    fn process_dct2_from_pcm(&self, input: &[i16], output: &mut [T], scratch: &mut [T]) {
+        let scratch = validate_buffers_outofplace!(
+            input,
+            output,
+            scratch,
+            self.len(),
+            self.get_scratch_len()
+        );
+
+        convert::pcm_to_float(input, output);
+        self.process_dct2_with_scratch(output, scratch);
+    }
+}
 
 /// A trait for algorithms that can compute both DCT4 and DST4, all in one struct
 pub trait TransformType4<T: DctNum>: Dct4<T> + Dst4<T> {}
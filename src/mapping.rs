@@ -0,0 +1,501 @@
+//! Cosine sums that aren't exactly one of the DCTs, expressed in terms of the transforms that are.
+//!
+//! Code that was ported from a FFT-based "pseudo-DCT", or written from a paper, often contains a loop like
+//!
+//! `output[k] = sum(input[n] * cos(PI * (n + a) * (k + b) / d))`
+//!
+//! where `a` and `b` are 0 or 1/2, and `d` doesn't match the length of the input. Whether that's a DCT1, DCT2, DCT3, or
+//! DCT4, and of which size, isn't obvious, and each type weights its first and last inputs differently.
+//! [`CosineSum`](struct.CosineSum.html) describes such a loop, [`CosineSum::mapping`](struct.CosineSum.html#method.mapping)
+//! chooses the transform type and the zero-padded length that compute it, and [`CosineSumPlan`](struct.CosineSumPlan.html)
+//! computes it with a planned transform, in O(nlogn).
+//!
+//! Before replacing the loop, [`validate_kernel`](fn.validate_kernel.html) checks that a plan matches the loop's own
+//! kernel formula, on random input.
+//!
+//! ~~~
+//! // Replaces `output[k] = sum(input[n] * cos(PI * n * (k + 0.5) / 12))` for 10 inputs and 6 outputs
+//! use rustdct::mapping::{validate_kernel, CosineSum, CosineSumPlan, Offset};
+//! use rustdct::{DctPlanner, DctType};
+//!
+//! let sum = CosineSum {
+//!     input_len: 10,
+//!     output_len: 6,
+//!     input_offset: Offset::Zero,
+//!     output_offset: Offset::Half,
+//!     denominator: 12,
+//! };
+//! let mapping = sum.mapping().unwrap();
+//! assert_eq!((mapping.kind, mapping.len), (DctType::Dct3, 12));
+//!
+//! let mut planner = DctPlanner::new();
+//! let plan = CosineSumPlan::new(&mut planner, sum).unwrap();
+//! let kernel = |n: usize, k: usize| (std::f64::consts::PI * n as f64 * (k as f64 + 0.5) / 12.0).cos();
+//! assert!(validate_kernel(&plan, kernel, 1e-10).is_ok());
+//!
+//! let input = vec![1.0; 10];
+//! let mut output = vec![0.0; 6];
+//! plan.process(&input, &mut output);
+//! ~~~
+
+use std::error::Error;
+use std::fmt;
+
+use rustfft::num_traits::Float;
+
+use crate::common::{offset_complex_scratch_len, with_scratch};
+use crate::{
+    DctBuffers, DctNum, DctPlanner, DctTransform, DctType, RequiredScratch, ScratchLayout,
+};
+
+/// An offset of the input or output index in a [`CosineSum`](struct.CosineSum.html).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Offset {
+    /// The index is used as-is.
+    Zero,
+    /// The index is offset by 1/2, so each sample sits halfway between the points of an unoffset grid.
+    Half,
+}
+
+/// A sum of cosines, computed for `output_len` outputs from `input_len` inputs:
+///
+/// `output[k] = sum(input[n] * cos(PI * (n + input_offset) * (k + output_offset) / denominator))`, for `n` in
+/// `0..input_len`
+///
+/// where the offsets are 0 or 1/2. Every input has a weight of 1, unlike the DCT1 and DCT3, which halve some of their
+/// inputs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CosineSum {
+    /// The number of inputs
+    pub input_len: usize,
+    /// The number of outputs
+    pub output_len: usize,
+    /// The offset of the input index `n`
+    pub input_offset: Offset,
+    /// The offset of the output index `k`
+    pub output_offset: Offset,
+    /// The denominator of the cosine's argument, in units of `PI`
+    pub denominator: usize,
+}
+
+/// The transform that computes a [`CosineSum`](struct.CosineSum.html): The input is zero-padded to `len`, its weights
+/// are adjusted to match the transform's, and the first `output_len` outputs of the transform are the sum.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Mapping {
+    /// The transform type. One of `Dct1`, `Dct2`, `Dct3`, or `Dct4`
+    pub kind: DctType,
+    /// The size of the transform
+    pub len: usize,
+}
+
+/// Returned by [`CosineSum::mapping`](struct.CosineSum.html#method.mapping) when no transform computes the sum.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MappingError {
+    /// The denominator is 0, so the sum is undefined.
+    ZeroDenominator,
+    /// There are more inputs than the transform has. Beyond that, the cosines repeat, so the inputs would alias.
+    InputTooLong {
+        /// The requested number of inputs
+        input_len: usize,
+        /// The size of the transform that the sum maps to
+        max_len: usize,
+    },
+    /// There are more outputs than the transform has.
+    OutputTooLong {
+        /// The requested number of outputs
+        output_len: usize,
+        /// The size of the transform that the sum maps to
+        max_len: usize,
+    },
+}
+
+impl fmt::Display for MappingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MappingError::ZeroDenominator => {
+                write!(f, "The denominator of a cosine sum can't be 0")
+            }
+            MappingError::InputTooLong { input_len, max_len } => write!(
+                f,
+                "The cosine sum has {} inputs, but the transform it maps to only has {}",
+                input_len, max_len
+            ),
+            MappingError::OutputTooLong {
+                output_len,
+                max_len,
+            } => write!(
+                f,
+                "The cosine sum has {} outputs, but the transform it maps to only has {}",
+                output_len, max_len
+            ),
+        }
+    }
+}
+impl Error for MappingError {}
+
+impl CosineSum {
+    /// Returns the transform type and size that compute this sum.
+    ///
+    /// The offsets choose the type: With no offsets, it's a DCT1 of size `denominator + 1`. An input offset of 1/2
+    /// makes it a DCT2, an output offset of 1/2 makes it a DCT3, and both make it a DCT4, all of size `denominator`.
+    ///
+    /// Returns an error if the denominator is 0, or if there are more inputs or outputs than the transform has.
+    pub fn mapping(&self) -> Result<Mapping, MappingError> {
+        if self.denominator == 0 {
+            return Err(MappingError::ZeroDenominator);
+        }
+        let mapping = match (self.input_offset, self.output_offset) {
+            (Offset::Zero, Offset::Zero) => Mapping {
+                kind: DctType::Dct1,
+                len: self.denominator + 1,
+            },
+            (Offset::Half, Offset::Zero) => Mapping {
+                kind: DctType::Dct2,
+                len: self.denominator,
+            },
+            (Offset::Zero, Offset::Half) => Mapping {
+                kind: DctType::Dct3,
+                len: self.denominator,
+            },
+            (Offset::Half, Offset::Half) => Mapping {
+                kind: DctType::Dct4,
+                len: self.denominator,
+            },
+        };
+        if self.input_len > mapping.len {
+            Err(MappingError::InputTooLong {
+                input_len: self.input_len,
+                max_len: mapping.len,
+            })
+        } else if self.output_len > mapping.len {
+            Err(MappingError::OutputTooLong {
+                output_len: self.output_len,
+                max_len: mapping.len,
+            })
+        } else {
+            Ok(mapping)
+        }
+    }
+}
+
+/// Computes a [`CosineSum`](struct.CosineSum.html) with the transform that it maps to.
+///
+/// See the [module documentation](index.html) for an example.
+pub struct CosineSumPlan<T> {
+    sum: CosineSum,
+    mapping: Mapping,
+    plan: Box<dyn DctTransform<T>>,
+}
+
+impl<T: DctNum> CosineSumPlan<T> {
+    /// Plans the transform that computes `sum` with `planner`. Returns an error if no transform computes it. See
+    /// [`CosineSum::mapping`](struct.CosineSum.html#method.mapping).
+    pub fn new(planner: &mut DctPlanner<T>, sum: CosineSum) -> Result<Self, MappingError> {
+        let mapping = sum.mapping()?;
+        let plan: Box<dyn DctTransform<T>> = match mapping.kind {
+            DctType::Dct1 => Box::new(planner.plan_dct1(mapping.len)),
+            DctType::Dct2 => Box::new(planner.plan_dct2(mapping.len)),
+            DctType::Dct3 => Box::new(planner.plan_dct3(mapping.len)),
+            _ => Box::new(planner.plan_dct4(mapping.len)),
+        };
+        Ok(Self { sum, mapping, plan })
+    }
+
+    /// Returns the sum that this plan computes.
+    pub fn sum(&self) -> CosineSum {
+        self.sum
+    }
+
+    /// Returns the transform type and size that this plan computes the sum with.
+    pub fn mapping(&self) -> Mapping {
+        self.mapping
+    }
+
+    /// Computes the sum of `input`, and writes it to `output`.
+    ///
+    /// This method uses a scratch buffer on the stack for small transforms, and allocates one otherwise. If you'd like
+    /// to reuse that allocation between multiple computations, consider calling `process_with_scratch` instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `input.len()` isn't `sum().input_len`, or if `output.len()` isn't `sum().output_len`.
+    pub fn process(&self, input: &[T], output: &mut [T]) {
+        with_scratch(self.get_scratch_len(), |scratch| {
+            self.process_with_scratch(input, output, scratch)
+        });
+    }
+
+    /// Computes the sum of `input`, and writes it to `output`. Uses the provided `scratch` buffer as scratch space.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `input.len()` isn't `sum().input_len`, if `output.len()` isn't `sum().output_len`, or if
+    /// `scratch.len()` is less than `self.get_scratch_len()`.
+    pub fn process_with_scratch(&self, input: &[T], output: &mut [T], scratch: &mut [T]) {
+        assert_eq!(
+            input.len(),
+            self.sum.input_len,
+            "The input must have input_len elements. Expected input len = {}, got input len = {}",
+            self.sum.input_len,
+            input.len()
+        );
+        assert_eq!(
+            output.len(),
+            self.sum.output_len,
+            "The output must have output_len elements. Expected output len = {}, got output len = {}",
+            self.sum.output_len,
+            output.len()
+        );
+        assert!(
+            scratch.len() >= self.get_scratch_len(),
+            "Not enough scratch space was provided. Expected scratch len >= {}, got scratch len = {}",
+            self.get_scratch_len(),
+            scratch.len()
+        );
+
+        let (buffer, inner_scratch) = scratch.split_at_mut(self.mapping.len);
+        buffer[..input.len()].copy_from_slice(input);
+        for element in buffer[input.len()..].iter_mut() {
+            *element = T::zero();
+        }
+
+        // the DCT1 halves its first and last inputs, and the DCT3 halves its first input, so double them to give every
+        // input a weight of 1. a zero-padded last input stays zero
+        if self.mapping.kind == DctType::Dct1 || self.mapping.kind == DctType::Dct3 {
+            if let Some(first) = buffer.first_mut() {
+                *first = *first * T::two();
+            }
+        }
+        if self.mapping.kind == DctType::Dct1 {
+            if let Some(last) = buffer.last_mut() {
+                *last = *last * T::two();
+            }
+        }
+
+        self.plan.execute(DctBuffers::InPlace {
+            kind: self.mapping.kind,
+            buffer,
+            scratch: &mut inner_scratch[..self.plan.get_scratch_len()],
+        });
+        output.copy_from_slice(&buffer[..output.len()]);
+    }
+}
+impl<T: DctNum> RequiredScratch for CosineSumPlan<T> {
+    fn get_scratch_len(&self) -> usize {
+        self.mapping.len + self.plan.get_scratch_len()
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        offset_complex_scratch_len(self.mapping.len, self.plan.get_complex_scratch_len())
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        ScratchLayout::new()
+            .with_region("mapped buffer", self.mapping.len, false)
+            .with_inner(self.plan.scratch_layout())
+    }
+}
+
+/// Returned by [`validate_kernel`](fn.validate_kernel.html) when a kernel formula doesn't match a plan.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct KernelMismatch<T> {
+    /// The index of the first output that differs by more than the tolerance
+    pub index: usize,
+    /// The output of the plan
+    pub expected: T,
+    /// The output of the kernel formula
+    pub actual: T,
+}
+
+impl<T: fmt::Debug> fmt::Display for KernelMismatch<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "The kernel formula doesn't match the plan at output {}. Expected {:?}, got {:?}",
+            self.index, self.expected, self.actual
+        )
+    }
+}
+impl<T: fmt::Debug> Error for KernelMismatch<T> {}
+
+/// Checks that `kernel` computes the same sum as `plan`, on a random input.
+///
+/// `kernel(n, k)` is the weight of input `n` in output `k`, as written in the loop that the plan replaces. This
+/// evaluates `output[k] = sum(input[n] * kernel(n, k))` directly, in O(n^2), and compares it to the output of the plan.
+/// An output matches if it's within `tolerance` times the largest magnitude of the plan's outputs. The input is
+/// generated deterministically, so the same arguments always give the same result.
+///
+/// Returns the first output that doesn't match, if any.
+pub fn validate_kernel<T, K>(
+    plan: &CosineSumPlan<T>,
+    kernel: K,
+    tolerance: T,
+) -> Result<(), KernelMismatch<T>>
+where
+    T: DctNum + Float,
+    K: Fn(usize, usize) -> T,
+{
+    let sum = plan.sum();
+
+    // xorshift32, scaled to [-1, 1)
+    let mut state = 0x9E37_79B9u32;
+    let input: Vec<T> = (0..sum.input_len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            T::from_f64(state as f64 / 2_147_483_648.0 - 1.0).unwrap()
+        })
+        .collect();
+
+    let mut expected = vec![T::zero(); sum.output_len];
+    plan.process(&input, &mut expected);
+
+    let scale = expected
+        .iter()
+        .fold(T::zero(), |largest, value| largest.max(value.abs()));
+    for (k, &expected) in expected.iter().enumerate() {
+        let actual = input
+            .iter()
+            .enumerate()
+            .fold(T::zero(), |total, (n, &value)| total + value * kernel(n, k));
+        if (actual - expected).abs() > tolerance * scale {
+            return Err(KernelMismatch {
+                index: k,
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    // Evaluates `sum` directly, for every combination of offsets and lengths
+    fn naive_kernel(sum: CosineSum) -> impl Fn(usize, usize) -> f64 {
+        let offset = |offset: Offset| match offset {
+            Offset::Zero => 0.0,
+            Offset::Half => 0.5,
+        };
+        move |n, k| {
+            (std::f64::consts::PI
+                * (n as f64 + offset(sum.input_offset))
+                * (k as f64 + offset(sum.output_offset))
+                / sum.denominator as f64)
+                .cos()
+        }
+    }
+
+    /// Verify that every mappable sum matches its kernel, including inputs and outputs that fill the whole transform
+    #[test]
+    fn test_cosine_sum_plan() {
+        let offsets = [Offset::Zero, Offset::Half];
+        let mut planner = DctPlanner::new();
+        for denominator in 1..12 {
+            for &input_offset in offsets.iter() {
+                for &output_offset in offsets.iter() {
+                    let max_len = if input_offset == Offset::Zero && output_offset == Offset::Zero {
+                        denominator + 1
+                    } else {
+                        denominator
+                    };
+                    for input_len in 0..=max_len {
+                        for &output_len in [0, 1, max_len / 2, max_len].iter() {
+                            let sum = CosineSum {
+                                input_len,
+                                output_len,
+                                input_offset,
+                                output_offset,
+                                denominator,
+                            };
+                            let plan = CosineSumPlan::new(&mut planner, sum).unwrap();
+                            assert_eq!(plan.mapping().len, max_len);
+                            assert_eq!(
+                                validate_kernel(&plan, naive_kernel(sum), 1e-10),
+                                Ok(()),
+                                "{:?}",
+                                sum
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_mapping_errors() {
+        let sum = CosineSum {
+            input_len: 8,
+            output_len: 8,
+            input_offset: Offset::Half,
+            output_offset: Offset::Zero,
+            denominator: 8,
+        };
+        assert_eq!(
+            sum.mapping(),
+            Ok(Mapping {
+                kind: DctType::Dct2,
+                len: 8
+            })
+        );
+        assert_eq!(
+            CosineSum {
+                denominator: 0,
+                ..sum
+            }
+            .mapping(),
+            Err(MappingError::ZeroDenominator)
+        );
+        assert_eq!(
+            CosineSum {
+                input_len: 9,
+                ..sum
+            }
+            .mapping(),
+            Err(MappingError::InputTooLong {
+                input_len: 9,
+                max_len: 8
+            })
+        );
+        assert_eq!(
+            CosineSum {
+                output_len: 10,
+                ..sum
+            }
+            .mapping(),
+            Err(MappingError::OutputTooLong {
+                output_len: 10,
+                max_len: 8
+            })
+        );
+        assert!(CosineSum {
+            input_len: 9,
+            output_offset: Offset::Zero,
+            input_offset: Offset::Zero,
+            ..sum
+        }
+        .mapping()
+        .is_ok());
+    }
+
+    /// Verify that a kernel that halves the first input, like a textbook DCT1, is caught
+    #[test]
+    fn test_validate_kernel_mismatch() {
+        let sum = CosineSum {
+            input_len: 9,
+            output_len: 9,
+            input_offset: Offset::Zero,
+            output_offset: Offset::Zero,
+            denominator: 8,
+        };
+        let plan = CosineSumPlan::new(&mut DctPlanner::new(), sum).unwrap();
+        let exact = naive_kernel(sum);
+        let halved = |n: usize, k: usize| (if n == 0 { 0.5 } else { 1.0 }) * exact(n, k);
+
+        let mismatch = validate_kernel(&plan, halved, 1e-10).unwrap_err();
+        assert_eq!(mismatch.index, 0);
+        assert!(validate_kernel(&plan, exact, 1e-10).is_ok());
+    }
+}
@@ -0,0 +1,245 @@
+use std::slice::ChunksExact;
+use std::sync::Arc;
+
+use crate::common::derived_len;
+use crate::mdct::Mdct;
+use crate::DctNum;
+
+/// Splits a long signal into overlapping frames, and computes the MDCT of each frame.
+///
+/// A MDCT of length `len` takes `2 * len` input samples. The analyzer computes a frame every `hop` samples, so
+/// consecutive frames overlap by `2 * len - hop` samples. For the usual MDCT setup, where the IMDCT output can be
+/// overlap-added back into the original signal, `hop` should be `len`.
+///
+/// The signal can be passed to [`analyze`](#method.analyze) in pieces of any size. Samples that aren't yet part of a
+/// complete frame are kept in an internal buffer, and combined with the samples from the next call, so splitting a
+/// signal into pieces produces the same frames as analyzing it all at once. The first frame starts at the first
+/// sample: To fully reconstruct the first `len` samples with the IMDCT, prepend `len` zeroes to the signal.
+///
+/// ~~~
+/// // Computes the MDCT frames of a signal of length 10000, with frames of length 512
+/// use rustdct::DctPlanner;
+/// use rustdct::mdct::{window_fn, MdctAnalyzer};
+///
+/// let len = 512;
+/// let mut planner = DctPlanner::new();
+/// let mdct = planner.plan_mdct(len, window_fn::mp3);
+///
+/// let mut analyzer = MdctAnalyzer::new(mdct, len);
+///
+/// let signal = vec![0f32; 10000];
+/// for frame in analyzer.analyze(&signal) {
+///     assert_eq!(frame.len(), len);
+/// }
+/// for frame in analyzer.flush() {
+///     assert_eq!(frame.len(), len);
+/// }
+/// ~~~
+pub struct MdctAnalyzer<T> {
+    plan: Arc<dyn Mdct<T>>,
+    hop: usize,
+
+    // samples that haven't been consumed by a complete frame yet. never holds more than `2 * len - 1` samples
+    pending: Vec<T>,
+    // the output of the most recent call to `analyze` or `flush`
+    frames: Vec<T>,
+    // holds the halves of frames that straddle `pending` and the new signal
+    staging: Box<[T]>,
+    scratch: Box<[T]>,
+}
+
+impl<T: DctNum> MdctAnalyzer<T> {
+    /// Creates a new analyzer that computes a frame with `plan` every `hop` samples.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `hop` is 0, or if `hop` is greater than `2 * plan.len()`.
+    pub fn new(plan: Arc<dyn Mdct<T>>, hop: usize) -> Self {
+        let frame_len = derived_len(plan.len(), 2, 0, "MDCT frame");
+        assert!(
+            hop > 0 && hop <= frame_len,
+            "The MDCT analyzer's hop must be between 1 and the frame length. Got hop = {}, frame length = {}",
+            hop,
+            frame_len
+        );
+
+        Self {
+            pending: Vec::with_capacity(frame_len),
+            frames: Vec::new(),
+            staging: vec![T::zero(); frame_len].into_boxed_slice(),
+            scratch: vec![T::zero(); plan.get_scratch_len()].into_boxed_slice(),
+            plan,
+            hop,
+        }
+    }
+
+    /// Returns the number of samples between the starts of consecutive frames.
+    pub fn hop(&self) -> usize {
+        self.hop
+    }
+
+    /// Appends `signal` to the samples passed to previous calls, and computes the MDCT of every frame that's now
+    /// complete. Returns an iterator over the coefficients of each new frame, in order.
+    ///
+    /// Samples at the end of `signal` that aren't part of a complete frame yet are kept until the next call to
+    /// `analyze` or `flush`.
+    pub fn analyze(&mut self, signal: &[T]) -> ChunksExact<'_, T> {
+        self.process_frames(signal);
+        self.frames.chunks_exact(self.plan.len())
+    }
+
+    /// Pads the buffered samples with zeroes, so that every sample passed to `analyze` has been included in every
+    /// frame that overlaps it, and computes the MDCT of the remaining frames. Returns an iterator over the
+    /// coefficients of each new frame, in order.
+    ///
+    /// Afterwards, the analyzer is empty, and can be used to analyze a new signal.
+    pub fn flush(&mut self) -> ChunksExact<'_, T> {
+        let frame_len = self.plan.len() * 2;
+        let pending_len = self.pending.len();
+
+        let padding_len = if pending_len > 0 {
+            // the last frame that overlaps the last pending sample starts at the last multiple of `hop` before it
+            let last_start = (pending_len - 1) / self.hop * self.hop;
+            last_start + frame_len - pending_len
+        } else {
+            0
+        };
+
+        self.process_frames(&vec![T::zero(); padding_len]);
+        self.pending.clear();
+
+        self.frames.chunks_exact(self.plan.len())
+    }
+
+    /// Discards any buffered samples, so that the next call to `analyze` starts a new signal.
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.frames.clear();
+    }
+
+    // Computes every frame that's complete after appending `signal` to the pending samples, and stores them in
+    // `self.frames`
+    fn process_frames(&mut self, signal: &[T]) {
+        let len = self.plan.len();
+        let frame_len = len * 2;
+
+        let pending_len = self.pending.len();
+        let total_len = pending_len + signal.len();
+        let num_frames = if total_len >= frame_len {
+            (total_len - frame_len) / self.hop + 1
+        } else {
+            0
+        };
+
+        self.frames.clear();
+        self.frames.resize(num_frames * len, T::zero());
+
+        for (frame_index, output) in self.frames.chunks_exact_mut(len).enumerate() {
+            let start = frame_index * self.hop;
+            let (staging_a, staging_b) = self.staging.split_at_mut(len);
+            let input_a = stream_segment(&self.pending, signal, start, staging_a);
+            let input_b = stream_segment(&self.pending, signal, start + len, staging_b);
+
+            self.plan
+                .process_mdct_with_scratch(input_a, input_b, output, &mut self.scratch);
+        }
+
+        // keep everything from the start of the next frame onwards
+        let next_start = num_frames * self.hop;
+        if next_start < pending_len {
+            self.pending.drain(..next_start);
+            self.pending.extend_from_slice(signal);
+        } else {
+            self.pending.clear();
+            self.pending
+                .extend_from_slice(&signal[next_start - pending_len..]);
+        }
+    }
+}
+
+// Returns the `staging.len()` samples starting at `start` of the concatenation of `pending` and `signal`. If they're
+// all in one of the two, this borrows them directly. Otherwise, they're copied into `staging`.
+fn stream_segment<'a, T: Copy>(
+    pending: &'a [T],
+    signal: &'a [T],
+    start: usize,
+    staging: &'a mut [T],
+) -> &'a [T] {
+    let end = start + staging.len();
+    if end <= pending.len() {
+        &pending[start..end]
+    } else if start >= pending.len() {
+        &signal[start - pending.len()..end - pending.len()]
+    } else {
+        let (from_pending, from_signal) = staging.split_at_mut(pending.len() - start);
+        from_pending.copy_from_slice(&pending[start..]);
+        from_signal.copy_from_slice(&signal[..from_signal.len()]);
+        staging
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::mdct::{window_fn, MdctNaive};
+    use crate::test_utils::{compare_float_vectors, random_signal};
+    use rustfft::Length;
+
+    fn expected_frames(mdct: &MdctNaive<f32>, signal: &[f32], hop: usize) -> Vec<f32> {
+        let len = mdct.len();
+        let mut frames = Vec::new();
+        let mut start = 0;
+        while start + len * 2 <= signal.len() {
+            let mut output = vec![0f32; len];
+            mdct.process_mdct_with_scratch(
+                &signal[start..start + len],
+                &signal[start + len..start + len * 2],
+                &mut output,
+                &mut [],
+            );
+            frames.extend_from_slice(&output);
+            start += hop;
+        }
+        frames
+    }
+
+    /// Verify that analyzing a signal in pieces gives the same frames as computing each frame directly
+    #[test]
+    fn test_mdct_analyzer() {
+        let len = 4;
+        for &hop in &[1, 3, 4, 8] {
+            let signal = random_signal(50);
+            let mdct = MdctNaive::new(len, window_fn::mp3);
+
+            // the flushed frames are the same as the frames of the zero-padded signal
+            let mut padded_signal = signal.clone();
+            padded_signal.resize(50 + len * 2, 0f32);
+            let mut expected = expected_frames(&mdct, &padded_signal, hop);
+            let last_start = (signal.len() - 1) / hop * hop;
+            expected.truncate((last_start / hop + 1) * len);
+
+            for &piece_len in &[1, 5, 7, 50] {
+                let mut analyzer =
+                    MdctAnalyzer::new(Arc::new(MdctNaive::new(len, window_fn::mp3)), hop);
+
+                let mut actual = Vec::new();
+                for piece in signal.chunks(piece_len) {
+                    for frame in analyzer.analyze(piece) {
+                        actual.extend_from_slice(frame);
+                    }
+                }
+                for frame in analyzer.flush() {
+                    actual.extend_from_slice(frame);
+                }
+
+                assert!(
+                    compare_float_vectors(&expected, &actual),
+                    "hop = {}, piece_len = {}",
+                    hop,
+                    piece_len
+                );
+                assert_eq!(analyzer.flush().len(), 0);
+            }
+        }
+    }
+}
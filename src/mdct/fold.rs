@@ -0,0 +1,217 @@
+use crate::DctNum;
+
+/// Windows a MDCT input and folds it into the input of a DCT Type 4 of half its size.
+///
+/// This is the first step of `MdctViaDct4`: Running a DCT4 on `output` afterwards produces the MDCT of the input. The
+/// input is split into four quarters `(a, b)` in `input_a` and `(c, d)` in `input_b`, each multiplied by its part of
+/// `window`, and folded into `(-Cr - D, A - Br)`, where `r` means reversed. This is useful on its own for building
+/// custom filterbanks, for example ones that apply a different transform to the folded signal.
+///
+/// # Panics
+///
+/// Panics if `output.len()` isn't even, if `input_a` and `input_b` don't have the same length as `output`, or if
+/// `window.len()` isn't `2 * output.len()`.
+pub fn fold_input<T: DctNum>(input_a: &[T], input_b: &[T], window: &[T], output: &mut [T]) {
+    validate_fold_lengths(input_a.len(), input_b.len(), window.len(), output.len());
+    fold_input_with(input_a, input_b, window, output, |x, window_val| {
+        x * window_val
+    });
+}
+
+/// Unfolds the output of a DCT Type 4 into a windowed IMDCT output, and adds it to `output_a` and `output_b`.
+///
+/// This is the last step of `MdctViaDct4`'s IMDCT: The IMDCT of a frame is the DCT4 of the frame, unfolded by this
+/// function. It's the transpose of [`fold_input`](fn.fold_input.html), so `input` is expanded from `(x, y)` into
+/// `(y, -yr, -xr, -x)`, multiplied by `window`, and added to the first half of the output in `output_a` and the
+/// second half in `output_b`. Like the IMDCT, it doesn't overwrite the output, so consecutive frames can be
+/// overlap-added directly.
+///
+/// # Panics
+///
+/// Panics if `input.len()` isn't even, if `output_a` and `output_b` don't have the same length as `input`, or if
+/// `window.len()` isn't `2 * input.len()`.
+pub fn unfold_output<T: DctNum>(input: &[T], window: &[T], output_a: &mut [T], output_b: &mut [T]) {
+    validate_fold_lengths(output_a.len(), output_b.len(), window.len(), input.len());
+    unfold_output_with(input, window, output_a, output_b, |output, val| {
+        output + val
+    });
+}
+
+fn validate_fold_lengths(a_len: usize, b_len: usize, window_len: usize, len: usize) {
+    assert!(
+        len % 2 == 0,
+        "The folded length must be even. Got len = {}",
+        len
+    );
+    assert!(
+        a_len == len && b_len == len && len.checked_mul(2) == Some(window_len),
+        "Both halves of the MDCT signal must have the folded length, and the window must be twice as long. Got folded len = {}, half lengths = {} and {}, window len = {}",
+        len,
+        a_len,
+        b_len,
+        window_len
+    );
+}
+
+// Folds the MDCT input into the DCT4 input, using `apply_window` to combine each input value with its window value.
+// Passing the window step as a closure lets the compiler remove the multiply entirely when the input is pre-windowed,
+// and lets the mixed-precision MDCT convert its f32 input to f64 in the same pass. The lengths must already be validated
+pub(crate) fn fold_input_with<I: Copy, T: DctNum, F: Fn(I, T) -> T>(
+    input_a: &[I],
+    input_b: &[I],
+    window: &[T],
+    output: &mut [T],
+    apply_window: F,
+) {
+    let len = output.len();
+    let group_size = len / 2;
+
+    //we're going to divide input_a into two subgroups, (a,b), and input_b into two subgroups: (c,d)
+    //then scale them by the window function, then combine them into two subgroups: (-D-Cr, A-Br) where R means reversed
+    let group_a_iter = input_a
+        .iter()
+        .zip(window.iter())
+        .map(|(a, window_val)| apply_window(*a, *window_val))
+        .take(group_size);
+    let group_b_rev_iter = input_a
+        .iter()
+        .zip(window.iter())
+        .map(|(b, window_val)| apply_window(*b, *window_val))
+        .rev()
+        .take(group_size);
+    let group_c_rev_iter = input_b
+        .iter()
+        .zip(&window[len..])
+        .map(|(c, window_val)| apply_window(*c, *window_val))
+        .rev()
+        .skip(group_size);
+    let group_d_iter = input_b
+        .iter()
+        .zip(&window[len..])
+        .map(|(d, window_val)| apply_window(*d, *window_val))
+        .skip(group_size);
+
+    //the first half of the dct input is -Cr - D
+    for (element, (cr_val, d_val)) in output.iter_mut().zip(group_c_rev_iter.zip(group_d_iter)) {
+        *element = -cr_val - d_val;
+    }
+
+    //the second half of the dct input is is A - Br
+    for (element, (a_val, br_val)) in output[group_size..]
+        .iter_mut()
+        .zip(group_a_iter.zip(group_b_rev_iter))
+    {
+        *element = a_val - br_val;
+    }
+}
+
+// Unfolds the DCT4 output into the IMDCT output, using `accumulate` to add each windowed value to its output element.
+// The mixed-precision MDCT uses this to add its f64 results to its f32 output. The lengths must already be validated
+pub(crate) fn unfold_output_with<T: DctNum, O: Copy, F: Fn(O, T) -> O>(
+    input: &[T],
+    window: &[T],
+    output_a: &mut [O],
+    output_b: &mut [O],
+    accumulate: F,
+) {
+    let len = input.len();
+    let group_size = len / 2;
+
+    //copy the second half of the DCT output into the result
+    for ((output, window_val), val) in output_a
+        .iter_mut()
+        .zip(window)
+        .zip(input[group_size..].iter())
+    {
+        *output = accumulate(*output, *val * *window_val);
+    }
+
+    //copy the second half of the DCT output again, but this time reversed and negated
+    for ((output, window_val), val) in output_a
+        .iter_mut()
+        .zip(window)
+        .skip(group_size)
+        .zip(input[group_size..].iter().rev())
+    {
+        *output = accumulate(*output, -(*val * *window_val));
+    }
+
+    //copy the first half of the DCT output into the result, reversde+negated
+    for ((output, window_val), val) in output_b
+        .iter_mut()
+        .zip(&window[len..])
+        .zip(input[..group_size].iter().rev())
+    {
+        *output = accumulate(*output, -(*val * *window_val));
+    }
+
+    //copy the first half of the DCT output again, but this time not reversed
+    for ((output, window_val), val) in output_b
+        .iter_mut()
+        .zip(&window[len..])
+        .skip(group_size)
+        .zip(input[..group_size].iter())
+    {
+        *output = accumulate(*output, -(*val * *window_val));
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::algorithm::Type4Naive;
+    use crate::mdct::{window_fn, Mdct, MdctNaive};
+    use crate::test_utils::{compare_float_vectors, random_signal};
+    use crate::{Dct4, RequiredScratch};
+
+    #[test]
+    fn test_fold_matches_naive_mdct() {
+        for half_len in 1..10 {
+            let len = half_len * 2;
+            let window = window_fn::mp3::<f32>(len * 2);
+            let naive_mdct = MdctNaive::new(len, window_fn::mp3);
+            let dct4 = Type4Naive::new(len);
+
+            let input = random_signal(len * 2);
+            let (input_a, input_b) = input.split_at(len);
+
+            let mut scratch = vec![0f32; naive_mdct.get_scratch_len()];
+
+            let mut expected = vec![0f32; len];
+            naive_mdct.process_mdct_with_scratch(input_a, input_b, &mut expected, &mut scratch);
+
+            let mut actual = vec![0f32; len];
+            fold_input(input_a, input_b, &window, &mut actual);
+            dct4.process_dct4(&mut actual);
+            assert!(compare_float_vectors(&expected, &actual), "len = {}", len);
+
+            // the unfold should add to the existing output, just like the IMDCT
+            let mut expected = random_signal(len * 2);
+            let mut actual = expected.clone();
+            {
+                let (output_a, output_b) = expected.split_at_mut(len);
+                naive_mdct.process_imdct_with_scratch(
+                    &input[..len],
+                    output_a,
+                    output_b,
+                    &mut scratch,
+                );
+            }
+            {
+                let mut dct_buffer = input[..len].to_vec();
+                dct4.process_dct4(&mut dct_buffer);
+                let (output_a, output_b) = actual.split_at_mut(len);
+                unfold_output(&dct_buffer, &window, output_a, output_b);
+            }
+            assert!(compare_float_vectors(&expected, &actual), "len = {}", len);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_fold_wrong_window_len() {
+        let input = vec![0f32; 4];
+        let mut output = vec![0f32; 4];
+        fold_input(&input, &input, &input, &mut output);
+    }
+}
@@ -0,0 +1,257 @@
+use std::sync::Arc;
+
+use rustfft::Length;
+
+use crate::common::{derived_len, mdct_error_inplace, with_f64_scratch};
+use crate::mdct::fold::{fold_input_with, unfold_output_with};
+use crate::mdct::{window_from_fn, Mdct, WindowMode};
+use crate::memory::table_bytes;
+use crate::RequiredScratch;
+use crate::ScratchLayout;
+use crate::TransformType4;
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
+
+/// MDCT implementation that takes `f32` inputs and outputs, but computes the transform in `f64`
+///
+/// This computes the same MDCT as [`MdctViaDct4`](struct.MdctViaDct4.html), with an `f64` window and an `f64` inner
+/// DCT4. The input is converted to `f64` while it's windowed and folded, and the IMDCT output is accumulated in `f64`
+/// while it's unfolded, before it's rounded to `f32`, so the conversions don't need any buffers beyond the scratch.
+/// This is useful for high-resolution audio decoders, which want `f64` precision internally, but `f32` PCM frames.
+///
+/// ~~~
+/// // Computes a mixed-precision MDCT of input size 1234, using the MP3 window function
+/// use rustdct::mdct::{Mdct, MdctMixedPrecision, window_fn};
+/// use rustdct::{DctPlanner, RequiredScratch};
+///
+/// let len = 1234;
+///
+/// let mut f64_planner = DctPlanner::<f64>::new();
+/// let mdct = MdctMixedPrecision::new(f64_planner.plan_dct4(len), window_fn::mp3);
+///
+/// let input = vec![0f32; len * 2];
+/// let (input_a, input_b) = input.split_at(len);
+/// let mut output = vec![0f32; len];
+/// let mut scratch = vec![0f32; mdct.get_scratch_len()];
+///
+/// mdct.process_mdct_with_scratch(input_a, input_b, &mut output, &mut scratch);
+/// ~~~
+pub struct MdctMixedPrecision {
+    dct: Arc<dyn TransformType4<f64>>,
+    window: Box<[f64]>,
+    window_mode: WindowMode,
+    f64_scratch_len: usize,
+    scratch_len: usize,
+}
+
+impl MdctMixedPrecision {
+    /// Creates a new mixed-precision MDCT context that will process signals of length `inner_dct.len() * 2`, with an
+    /// output of length `inner_dct.len()`
+    ///
+    /// `inner_dct.len()` must be even.
+    ///
+    /// `window_fn` is a function that takes a `size` and returns a `Vec` containing `size` window values.
+    /// See the [`window_fn`](window_fn/index.html) module for provided window functions.
+    pub fn new<F>(inner_dct: Arc<dyn TransformType4<f64>>, window_fn: F) -> Self
+    where
+        F: FnOnce(usize) -> Vec<f64>,
+    {
+        Self::with_window_mode(inner_dct, window_fn, WindowMode::Internal)
+    }
+
+    /// Creates a new mixed-precision MDCT context that will process signals of length `inner_dct.len() * 2`, with an
+    /// output of length `inner_dct.len()`
+    ///
+    /// If `window_mode` is `WindowMode::External`, the forward MDCT doesn't apply the window function, and instead
+    /// expects its input to be windowed by the caller. See [`WindowMode`](enum.WindowMode.html) for details.
+    pub fn with_window_mode<F>(
+        inner_dct: Arc<dyn TransformType4<f64>>,
+        window_fn: F,
+        window_mode: WindowMode,
+    ) -> Self
+    where
+        F: FnOnce(usize) -> Vec<f64>,
+    {
+        let len = inner_dct.len();
+        let is_even = len % 2 == 0;
+        assert!(is_even, "The MDCT inner_dct.len() must be even");
+        let window = window_from_fn(derived_len(len, 2, 0, "window"), window_fn);
+
+        let f64_scratch_len = len
+            .checked_add(inner_dct.get_scratch_len())
+            .expect("The scratch length of the inner DCT doesn't fit in a usize");
+
+        // Each f64 takes up the space of two f32s, and we might need to skip one f32 to align the f64s
+        let scratch_len = derived_len(f64_scratch_len, 2, 1, "scratch");
+
+        Self {
+            dct: inner_dct,
+            window,
+            window_mode,
+            f64_scratch_len,
+            scratch_len,
+        }
+    }
+}
+impl Mdct<f32> for MdctMixedPrecision {
+    plan_hooks!(f32);
+    fn process_mdct_with_scratch(
+        &self,
+        input_a: &[f32],
+        input_b: &[f32],
+        output: &mut [f32],
+        scratch: &mut [f32],
+    ) {
+        let scratch = validate_buffers_mdct!(
+            input_a,
+            input_b,
+            output,
+            scratch,
+            self.len(),
+            self.get_scratch_len()
+        );
+
+        with_f64_scratch(scratch, self.f64_scratch_len, |f64_scratch| {
+            let (dct_buffer, dct_scratch) = f64_scratch.split_at_mut(self.len());
+            match self.window_mode {
+                WindowMode::Internal => fold_input_with(
+                    input_a,
+                    input_b,
+                    &self.window,
+                    dct_buffer,
+                    |x, window_val| x as f64 * window_val,
+                ),
+                WindowMode::External => {
+                    fold_input_with(input_a, input_b, &self.window, dct_buffer, |x, _| x as f64)
+                }
+            }
+
+            self.dct.process_dct4_with_scratch(dct_buffer, dct_scratch);
+
+            for (element, f64_element) in output.iter_mut().zip(dct_buffer.iter()) {
+                *element = *f64_element as f32;
+            }
+        });
+    }
+
+    fn process_imdct_with_scratch(
+        &self,
+        input: &[f32],
+        output_a: &mut [f32],
+        output_b: &mut [f32],
+        scratch: &mut [f32],
+    ) {
+        let scratch = validate_buffers_mdct!(
+            input,
+            output_a,
+            output_b,
+            scratch,
+            self.len(),
+            self.get_scratch_len()
+        );
+
+        with_f64_scratch(scratch, self.f64_scratch_len, |f64_scratch| {
+            let (dct_buffer, dct_scratch) = f64_scratch.split_at_mut(self.len());
+            for (f64_element, element) in dct_buffer.iter_mut().zip(input.iter()) {
+                *f64_element = *element as f64;
+            }
+
+            self.dct.process_dct4_with_scratch(dct_buffer, dct_scratch);
+
+            unfold_output_with(
+                dct_buffer,
+                &self.window,
+                output_a,
+                output_b,
+                |output, val| (output as f64 + val) as f32,
+            );
+        });
+    }
+}
+impl Length for MdctMixedPrecision {
+    fn len(&self) -> usize {
+        self.dct.len()
+    }
+}
+impl PlanIdentity<f32> for MdctMixedPrecision {
+    fn plan_key(&self) -> PlanKey<f32> {
+        let window: Vec<f32> = self.window.iter().map(|&value| value as f32).collect();
+        PlanKey::new(PlanKind::Mdct, self.len()).with_window(self.window_mode, &window)
+    }
+}
+impl MemoryUsage for MdctMixedPrecision {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            twiddles_bytes: 0,
+            window_bytes: table_bytes(&self.window),
+            inner_plans_bytes: visitor.inner_plan(&self.dct),
+        }
+    }
+}
+impl RequiredScratch for MdctMixedPrecision {
+    fn get_scratch_len(&self) -> usize {
+        self.scratch_len
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        // the inner plan's buffer and scratch are `f64`s packed into the `f32` scratch, so they can't be described as
+        // regions of it
+        ScratchLayout::new().with_region("f64 scratch", self.scratch_len, false)
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    use crate::algorithm::Type4Naive;
+    use crate::mdct::{window_fn, MdctViaDct4};
+    use crate::test_utils::{compare_float_vectors, random_signal};
+
+    /// Verify that the mixed-precision MDCT and IMDCT match the f32 ones, in both window modes
+    #[test]
+    fn test_mdct_mixed_precision() {
+        for &window_mode in &[WindowMode::Internal, WindowMode::External] {
+            for len in (2..20).step_by(2) {
+                let mdct = MdctMixedPrecision::with_window_mode(
+                    Arc::new(Type4Naive::new(len)),
+                    window_fn::mp3,
+                    window_mode,
+                );
+                let expected_mdct = MdctViaDct4::with_window_mode(
+                    Arc::new(Type4Naive::new(len)),
+                    window_fn::mp3,
+                    window_mode,
+                );
+                let mut scratch = vec![0f32; mdct.get_scratch_len()];
+                let mut expected_scratch = vec![0f32; expected_mdct.get_scratch_len()];
+
+                let input: Vec<f32> = random_signal(len * 2);
+                let (input_a, input_b) = input.split_at(len);
+                let mut expected = vec![0f32; len];
+                let mut actual = vec![0f32; len];
+                expected_mdct.process_mdct_with_scratch(
+                    input_a,
+                    input_b,
+                    &mut expected,
+                    &mut expected_scratch,
+                );
+                mdct.process_mdct_with_scratch(input_a, input_b, &mut actual, &mut scratch);
+                assert!(compare_float_vectors(&expected, &actual), "len = {}", len);
+
+                // the IMDCT adds to the existing output
+                let mut expected: Vec<f32> = random_signal(len * 2);
+                let mut actual = expected.clone();
+                let (expected_a, expected_b) = expected.split_at_mut(len);
+                expected_mdct.process_imdct_with_scratch(
+                    input_a,
+                    expected_a,
+                    expected_b,
+                    &mut expected_scratch,
+                );
+                let (actual_a, actual_b) = actual.split_at_mut(len);
+                mdct.process_imdct_with_scratch(input_a, actual_a, actual_b, &mut scratch);
+                assert!(compare_float_vectors(&expected, &actual), "len = {}", len);
+            }
+        }
+    }
+}
@@ -2,9 +2,13 @@ use std::f64;
 
 use rustfft::Length;
 
-use crate::common::mdct_error_inplace;
+use crate::common::{derived_len, mdct_error_inplace};
+use crate::mdct::{window_from_fill, window_from_fn, Mdct, WindowMode};
+use crate::memory::table_bytes;
+use crate::DctNum;
 use crate::RequiredScratch;
-use crate::{mdct::Mdct, DctNum};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
 
 /// Naive O(n^2 ) MDCT implementation
 ///
@@ -13,7 +17,6 @@ use crate::{mdct::Mdct, DctNum};
 /// ~~~
 /// // Computes a naive MDCT of output size 124, using the MP3 window function
 /// use rustdct::mdct::{Mdct, MdctNaive, window_fn};
-/// use rustdct::RequiredScratch;
 ///
 /// let len = 124;
 ///
@@ -22,13 +25,13 @@ use crate::{mdct::Mdct, DctNum};
 /// let input = vec![0f32; len * 2];
 /// let (input_a, input_b) = input.split_at(len);
 /// let mut output = vec![0f32; len];
-/// let mut scratch = vec![0f32; dct.get_scratch_len()];
 ///
-/// dct.process_mdct_with_scratch(input_a, input_b, &mut output, &mut scratch);
+/// dct.process_mdct(input_a, input_b, &mut output);
 /// ~~~
 pub struct MdctNaive<T> {
     twiddles: Box<[T]>,
     window: Box<[T]>,
+    window_mode: WindowMode,
 }
 
 impl<T: DctNum> MdctNaive<T> {
@@ -43,33 +46,63 @@ impl<T: DctNum> MdctNaive<T> {
     where
         F: FnOnce(usize) -> Vec<T>,
     {
+        Self::with_window_mode(output_len, window_fn, WindowMode::Internal)
+    }
+
+    /// Creates a new MDCT context that will process inputs of length `output_len * 2` and produce
+    /// outputs of length `output_len`
+    ///
+    /// If `window_mode` is `WindowMode::External`, the forward MDCT doesn't apply the window function, and instead
+    /// expects its input to be windowed by the caller. See [`WindowMode`](enum.WindowMode.html) for details.
+    pub fn with_window_mode<F>(output_len: usize, window_fn: F, window_mode: WindowMode) -> Self
+    where
+        F: FnOnce(usize) -> Vec<T>,
+    {
+        let window = window_from_fn(Self::window_len(output_len), window_fn);
+        Self::from_window(output_len, window, window_mode)
+    }
+
+    /// Creates a new MDCT context that will process inputs of length `output_len * 2` and produce
+    /// outputs of length `output_len`
+    ///
+    /// Instead of returning a `Vec`, `fill_window` writes the window values into the slice it's given, which has a length
+    /// of `output_len * 2` and is initially zeroed. Otherwise, this is the same as
+    /// [`with_window_mode`](#method.with_window_mode).
+    pub fn with_window_fill<F>(output_len: usize, fill_window: F, window_mode: WindowMode) -> Self
+    where
+        F: FnOnce(&mut [T]),
+    {
+        let window = window_from_fill(Self::window_len(output_len), fill_window);
+        Self::from_window(output_len, window, window_mode)
+    }
+
+    fn window_len(output_len: usize) -> usize {
         assert!(
             output_len % 2 == 0,
             "The MDCT len must be even. Got {}",
             output_len
         );
+        derived_len(output_len, 2, 0, "window")
+    }
 
+    fn from_window(output_len: usize, window: Box<[T]>, window_mode: WindowMode) -> Self {
         let constant_factor = 0.5f64 * f64::consts::PI / (output_len as f64);
-        let twiddles: Vec<T> = (0..output_len * 4)
+        let twiddle_len = derived_len(output_len, 4, 0, "twiddle table");
+        let twiddles: Vec<T> = (0..twiddle_len)
             .map(|i| (constant_factor * (i as f64 + 0.5_f64)).cos())
             .map(|c| T::from_f64(c).unwrap())
             .collect();
 
-        let window = window_fn(output_len * 2);
-        assert_eq!(
-            window.len(),
-            output_len * 2,
-            "Window function returned incorrect number of values"
-        );
-
         Self {
             twiddles: twiddles.into_boxed_slice(),
-            window: window.into_boxed_slice(),
+            window,
+            window_mode,
         }
     }
 }
 
 impl<T: DctNum> Mdct<T> for MdctNaive<T> {
+    plan_hooks!(T);
     fn process_mdct_with_scratch(
         &self,
         input_a: &[T],
@@ -88,18 +121,25 @@ impl<T: DctNum> Mdct<T> for MdctNaive<T> {
 
         let output_len = output.len();
         let half_output = output.len() / 2;
+        let apply_window = self.window_mode == WindowMode::Internal;
 
         for k in 0..output_len {
             let output_cell = output.get_mut(k).unwrap();
             *output_cell = T::zero();
 
-            let mut twiddle_index = (half_output + k * (output_len + 1)) % self.twiddles.len();
+            let mut twiddle_index =
+                first_mdct_twiddle_index(half_output, k, output_len, self.twiddles.len());
             let twiddle_stride = k * 2 + 1;
 
             for i in 0..input_a.len() {
                 let twiddle = self.twiddles[twiddle_index];
 
-                *output_cell = *output_cell + input_a[i] * self.window[i] * twiddle;
+                let input_val = if apply_window {
+                    input_a[i] * self.window[i]
+                } else {
+                    input_a[i]
+                };
+                *output_cell = *output_cell + input_val * twiddle;
 
                 twiddle_index += twiddle_stride;
                 if twiddle_index >= self.twiddles.len() {
@@ -110,7 +150,12 @@ impl<T: DctNum> Mdct<T> for MdctNaive<T> {
             for i in 0..input_b.len() {
                 let twiddle = self.twiddles[twiddle_index];
 
-                *output_cell = *output_cell + input_b[i] * self.window[i + output_len] * twiddle;
+                let input_val = if apply_window {
+                    input_b[i] * self.window[i + output_len]
+                } else {
+                    input_b[i]
+                };
+                *output_cell = *output_cell + input_val * twiddle;
 
                 twiddle_index += twiddle_stride;
                 if twiddle_index >= self.twiddles.len() {
@@ -184,11 +229,37 @@ impl<T: DctNum> Mdct<T> for MdctNaive<T> {
         }
     }
 }
+// Computes `(half_output + k * (output_len + 1)) % twiddle_len` using wide arithmetic, because the product can overflow
+// a usize long before the twiddle table does, especially on 32-bit targets
+fn first_mdct_twiddle_index(
+    half_output: usize,
+    k: usize,
+    output_len: usize,
+    twiddle_len: usize,
+) -> usize {
+    let wide_index = half_output as u128 + k as u128 * (output_len as u128 + 1);
+    (wide_index % twiddle_len as u128) as usize
+}
+
 impl<T> Length for MdctNaive<T> {
     fn len(&self) -> usize {
         self.twiddles.len() / 4
     }
 }
+impl<T: DctNum> PlanIdentity<T> for MdctNaive<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Mdct, self.len()).with_window(self.window_mode, &self.window)
+    }
+}
+impl<T> MemoryUsage for MdctNaive<T> {
+    fn memory_usage_with_visitor(&self, _visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            twiddles_bytes: table_bytes(&self.twiddles),
+            window_bytes: table_bytes(&self.window),
+            inner_plans_bytes: 0,
+        }
+    }
+}
 impl<T> RequiredScratch for MdctNaive<T> {
     fn get_scratch_len(&self) -> usize {
         0
@@ -489,4 +560,54 @@ mod unit_tests {
         let window = window_fn(output.len());
         output.iter().zip(window).map(|(e, w)| e * w).collect()
     }
+
+    /// Verify that the starting twiddle index doesn't overflow for sizes where `k * (output_len + 1)` exceeds a 32-bit usize
+    #[test]
+    fn test_first_twiddle_index_large() {
+        for &(output_len, k) in &[(4, 0), (4, 3), (12, 7)] {
+            let expected = (output_len / 2 + k * (output_len + 1)) % (output_len * 4);
+            let actual = first_mdct_twiddle_index(output_len / 2, k, output_len, output_len * 4);
+            assert_eq!(expected, actual);
+        }
+
+        // (output_len / 2 + output_len^2 - 1) mod (4 * output_len) == output_len / 2 - 1, because output_len is a multiple of 4
+        let output_len = 1 << 20;
+        let k = output_len - 1;
+        let actual = first_mdct_twiddle_index(output_len / 2, k, output_len, output_len * 4);
+        assert_eq!(actual, output_len / 2 - 1);
+    }
+
+    /// Stream a signal through a MDCT/IMDCT pair one hop at a time, and verify that the output is delayed by exactly `latency_samples()`
+    #[test]
+    fn test_latency_samples() {
+        let len = 4;
+        let mdct = MdctNaive::new(len, window_fn::mp3_invertible);
+        assert_eq!(mdct.latency_samples(), len);
+        assert_eq!(mdct.required_overlap(), len);
+
+        let num_hops = 5;
+        let input = random_signal(len * num_hops);
+
+        // flush the stream with enough zeroes to complete the overlap
+        let mut stream = input.clone();
+        stream.extend(vec![0f32; mdct.required_overlap()]);
+
+        let mut history = vec![0f32; len];
+        let mut overlap = vec![0f32; mdct.required_overlap()];
+        let mut output = Vec::new();
+        for hop in stream.chunks(len) {
+            let mut spectrum = vec![0f32; len];
+            mdct.process_mdct_with_scratch(&history, hop, &mut spectrum, &mut []);
+            history.copy_from_slice(hop);
+
+            let mut next_overlap = vec![0f32; len];
+            mdct.process_imdct_with_scratch(&spectrum, &mut overlap, &mut next_overlap, &mut []);
+            output.extend_from_slice(&overlap);
+            overlap = next_overlap;
+        }
+
+        let latency = mdct.latency_samples();
+        assert!(output[..latency].iter().all(|sample| sample.abs() < 0.0001));
+        assert!(compare_float_vectors(&input, &output[latency..]));
+    }
 }
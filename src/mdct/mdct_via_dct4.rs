@@ -2,16 +2,29 @@ use std::sync::Arc;
 
 use rustfft::Length;
 
-use crate::common::mdct_error_inplace;
-use crate::mdct::Mdct;
+use crate::common::{derived_len, mdct_error_inplace, offset_complex_scratch_len};
+use crate::mdct::fold::fold_input_with;
+use crate::mdct::{unfold_output, window_from_fill, window_from_fn, Mdct, WindowMode};
+use crate::memory::table_bytes;
 use crate::RequiredScratch;
+use crate::ScratchLayout;
 use crate::{DctNum, TransformType4};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+use crate::{PlanIdentity, PlanKey, PlanKind};
+
+// IMDCTs up to this size keep their DCT4 buffer on the stack, if their inner DCT4 doesn't need any scratch. This is the
+// size of the largest DCT4 butterfly
+const MAX_STACK_LEN: usize = 32;
 
 /// MDCT implementation that converts the problem to a DCT Type 4 of the same size.
 ///
 /// It is much easier to express a MDCT as a DCT Type 4 than it is to express it as a FFT, so converting the MDCT
 /// to a DCT4 before converting it to a FFT results in greatly simplified code
 ///
+/// If the inner DCT4 doesn't need any scratch, and its size is 32 or less, like the DCT4 butterflies that the planner
+/// uses for sizes 4, 8, 16, and 32, `get_scratch_len()` returns 0. So the `process_mdct` and `process_imdct` methods,
+/// which don't take a scratch buffer, never allocate for those sizes, and are safe to call from realtime threads.
+///
 /// ~~~
 /// // Computes a MDCT of input size 1234 via a DCT4, using the MP3 window function
 /// use rustdct::mdct::{Mdct, MdctViaDct4, window_fn};
@@ -34,6 +47,7 @@ use crate::{DctNum, TransformType4};
 pub struct MdctViaDct4<T> {
     dct: Arc<dyn TransformType4<T>>,
     window: Box<[T]>,
+    window_mode: WindowMode,
     scratch_len: usize,
 }
 
@@ -48,25 +62,68 @@ impl<T: DctNum> MdctViaDct4<T> {
     where
         F: FnOnce(usize) -> Vec<T>,
     {
-        let len = inner_dct.len();
+        Self::with_window_mode(inner_dct, window_fn, WindowMode::Internal)
+    }
 
-        assert!(len % 2 == 0, "The MDCT inner_dct.len() must be even");
+    /// Creates a new MDCT context that will process signals of length `inner_dct.len() * 2`, with an output of length `inner_dct.len()`
+    ///
+    /// If `window_mode` is `WindowMode::External`, the forward MDCT doesn't apply the window function, and instead
+    /// expects its input to be windowed by the caller. See [`WindowMode`](enum.WindowMode.html) for details.
+    pub fn with_window_mode<F>(
+        inner_dct: Arc<dyn TransformType4<T>>,
+        window_fn: F,
+        window_mode: WindowMode,
+    ) -> Self
+    where
+        F: FnOnce(usize) -> Vec<T>,
+    {
+        let window = window_from_fn(Self::window_len(&inner_dct), window_fn);
+        Self::from_window(inner_dct, window, window_mode)
+    }
 
-        let window = window_fn(len * 2);
-        assert_eq!(
-            window.len(),
-            len * 2,
-            "Window function returned incorrect number of values"
-        );
+    /// Creates a new MDCT context that will process signals of length `inner_dct.len() * 2`, with an output of length `inner_dct.len()`
+    ///
+    /// Instead of returning a `Vec`, `fill_window` writes the window values into the slice it's given, which has a length
+    /// of `inner_dct.len() * 2` and is initially zeroed. The slice is the MDCT's own window storage, so no intermediate
+    /// buffer is allocated. Otherwise, this is the same as [`with_window_mode`](#method.with_window_mode).
+    pub fn with_window_fill<F>(
+        inner_dct: Arc<dyn TransformType4<T>>,
+        fill_window: F,
+        window_mode: WindowMode,
+    ) -> Self
+    where
+        F: FnOnce(&mut [T]),
+    {
+        let window = window_from_fill(Self::window_len(&inner_dct), fill_window);
+        Self::from_window(inner_dct, window, window_mode)
+    }
 
+    fn window_len(inner_dct: &Arc<dyn TransformType4<T>>) -> usize {
+        let len = inner_dct.len();
+        assert!(len % 2 == 0, "The MDCT inner_dct.len() must be even");
+        derived_len(len, 2, 0, "window")
+    }
+
+    fn from_window(
+        inner_dct: Arc<dyn TransformType4<T>>,
+        window: Box<[T]>,
+        window_mode: WindowMode,
+    ) -> Self {
+        let scratch_len = if inner_dct.len() <= MAX_STACK_LEN && inner_dct.get_scratch_len() == 0 {
+            0
+        } else {
+            inner_dct.len() + inner_dct.get_scratch_len()
+        };
         Self {
-            scratch_len: len + inner_dct.get_scratch_len(),
+            scratch_len,
             dct: inner_dct,
-            window: window.into_boxed_slice(),
+            window,
+            window_mode,
         }
     }
 }
 impl<T: DctNum> Mdct<T> for MdctViaDct4<T> {
+    plan_hooks!(T);
     fn process_mdct_with_scratch(
         &self,
         input_a: &[T],
@@ -83,45 +140,15 @@ impl<T: DctNum> Mdct<T> for MdctViaDct4<T> {
             self.get_scratch_len()
         );
 
-        let group_size = self.len() / 2;
-
-        //we're going to divide input_a into two subgroups, (a,b), and input_b into two subgroups: (c,d)
-        //then scale them by the window function, then combine them into two subgroups: (-D-Cr, A-Br) where R means reversed
-        let group_a_iter = input_a
-            .iter()
-            .zip(self.window.iter())
-            .map(|(a, window_val)| *a * *window_val)
-            .take(group_size);
-        let group_b_rev_iter = input_a
-            .iter()
-            .zip(self.window.iter())
-            .map(|(b, window_val)| *b * *window_val)
-            .rev()
-            .take(group_size);
-        let group_c_rev_iter = input_b
-            .iter()
-            .zip(&self.window[self.len()..])
-            .map(|(c, window_val)| *c * *window_val)
-            .rev()
-            .skip(group_size);
-        let group_d_iter = input_b
-            .iter()
-            .zip(&self.window[self.len()..])
-            .map(|(d, window_val)| *d * *window_val)
-            .skip(group_size);
-
-        //the first half of the dct input is -Cr - D
-        for (element, (cr_val, d_val)) in output.iter_mut().zip(group_c_rev_iter.zip(group_d_iter))
-        {
-            *element = -cr_val - d_val;
-        }
-
-        //the second half of the dct input is is A - Br
-        for (element, (a_val, br_val)) in output[group_size..]
-            .iter_mut()
-            .zip(group_a_iter.zip(group_b_rev_iter))
-        {
-            *element = a_val - br_val;
+        match self.window_mode {
+            WindowMode::Internal => {
+                fold_input_with(input_a, input_b, &self.window, output, |x, window_val| {
+                    x * window_val
+                })
+            }
+            WindowMode::External => {
+                fold_input_with(input_a, input_b, &self.window, output, |x, _| x)
+            }
         }
 
         self.dct.process_dct4_with_scratch(output, scratch);
@@ -143,49 +170,22 @@ impl<T: DctNum> Mdct<T> for MdctViaDct4<T> {
             self.get_scratch_len()
         );
 
-        let (dct_buffer, dct_scratch) = scratch.split_at_mut(self.len());
-        dct_buffer.copy_from_slice(input);
+        if self.scratch_len == 0 {
+            let zero = T::zero();
+            let mut stack_buffer = [zero; MAX_STACK_LEN];
+            let dct_buffer = &mut stack_buffer[..self.len()];
+            dct_buffer.copy_from_slice(input);
 
-        self.dct.process_dct4_with_scratch(dct_buffer, dct_scratch);
+            self.dct.process_dct4_with_scratch(dct_buffer, &mut []);
 
-        let group_size = self.len() / 2;
+            unfold_output(dct_buffer, &self.window, output_a, output_b);
+        } else {
+            let (dct_buffer, dct_scratch) = scratch.split_at_mut(self.len());
+            dct_buffer.copy_from_slice(input);
 
-        //copy the second half of the DCT output into the result
-        for ((output, window_val), val) in output_a
-            .iter_mut()
-            .zip(&self.window[..])
-            .zip(dct_buffer[group_size..].iter())
-        {
-            *output = *output + *val * *window_val;
-        }
-
-        //copy the second half of the DCT output again, but this time reversed and negated
-        for ((output, window_val), val) in output_a
-            .iter_mut()
-            .zip(&self.window[..])
-            .skip(group_size)
-            .zip(dct_buffer[group_size..].iter().rev())
-        {
-            *output = *output - *val * *window_val;
-        }
-
-        //copy the first half of the DCT output into the result, reversde+negated
-        for ((output, window_val), val) in output_b
-            .iter_mut()
-            .zip(&self.window[self.len()..])
-            .zip(dct_buffer[..group_size].iter().rev())
-        {
-            *output = *output - *val * *window_val;
-        }
+            self.dct.process_dct4_with_scratch(dct_buffer, dct_scratch);
 
-        //copy the first half of the DCT output again, but this time not reversed
-        for ((output, window_val), val) in output_b
-            .iter_mut()
-            .zip(&self.window[self.len()..])
-            .skip(group_size)
-            .zip(dct_buffer[..group_size].iter())
-        {
-            *output = *output - *val * *window_val;
+            unfold_output(dct_buffer, &self.window, output_a, output_b);
         }
     }
 }
@@ -194,10 +194,41 @@ impl<T> Length for MdctViaDct4<T> {
         self.dct.len()
     }
 }
-impl<T> RequiredScratch for MdctViaDct4<T> {
+impl<T: DctNum> PlanIdentity<T> for MdctViaDct4<T> {
+    fn plan_key(&self) -> PlanKey<T> {
+        PlanKey::new(PlanKind::Mdct, self.len()).with_window(self.window_mode, &self.window)
+    }
+}
+impl<T: DctNum> MemoryUsage for MdctViaDct4<T> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            twiddles_bytes: 0,
+            window_bytes: table_bytes(&self.window),
+            inner_plans_bytes: visitor.inner_plan(&self.dct),
+        }
+    }
+}
+impl<T: DctNum> RequiredScratch for MdctViaDct4<T> {
     fn get_scratch_len(&self) -> usize {
         self.scratch_len
     }
+    fn get_complex_scratch_len(&self) -> usize {
+        // the IMDCT passes the scratch after its DCT4 buffer to the inner DCT4, while the MDCT passes the whole scratch
+        if self.scratch_len == 0 {
+            0
+        } else {
+            offset_complex_scratch_len(self.len(), self.dct.get_complex_scratch_len())
+        }
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        if self.scratch_len == 0 {
+            ScratchLayout::new()
+        } else {
+            ScratchLayout::new()
+                .with_region("DCT4 buffer", self.len(), false)
+                .with_inner(self.dct.scratch_layout())
+        }
+    }
 }
 
 #[cfg(test)]
@@ -253,6 +284,46 @@ mod unit_tests {
         }
     }
 
+    /// Verify that filling the window in place gives the same MDCT as returning it from a window function
+    #[test]
+    fn test_mdct_window_fill() {
+        for &window_mode in &[WindowMode::Internal, WindowMode::External] {
+            for i in 1..6 {
+                let output_len = i * 2;
+                let input = random_signal(output_len * 2);
+                let (input_a, input_b) = input.split_at(output_len);
+
+                let fill_mp3 = |window: &mut [f32]| {
+                    window.copy_from_slice(&window_fn::mp3(window.len()));
+                };
+                let expected_mdct =
+                    MdctNaive::with_window_mode(output_len, window_fn::mp3, window_mode);
+                let naive_mdct = MdctNaive::with_window_fill(output_len, fill_mp3, window_mode);
+                let fast_mdct = MdctViaDct4::with_window_fill(
+                    Arc::new(Type4Naive::new(output_len)),
+                    fill_mp3,
+                    window_mode,
+                );
+
+                let mut expected = vec![0f32; output_len];
+                let mut naive_output = vec![0f32; output_len];
+                let mut fast_output = vec![0f32; output_len];
+                expected_mdct.process_mdct_with_scratch(input_a, input_b, &mut expected, &mut []);
+                naive_mdct.process_mdct_with_scratch(input_a, input_b, &mut naive_output, &mut []);
+                let mut fast_scratch = vec![0f32; fast_mdct.get_scratch_len()];
+                fast_mdct.process_mdct_with_scratch(
+                    input_a,
+                    input_b,
+                    &mut fast_output,
+                    &mut fast_scratch,
+                );
+
+                assert_eq!(expected, naive_output, "i = {}", i);
+                assert!(compare_float_vectors(&expected, &fast_output), "i = {}", i);
+            }
+        }
+    }
+
     /// Verify that our fast implementation of the MDCT and IMDCT gives the same output as the slow version, for many different inputs
     #[test]
     fn test_imdct_via_dct4() {
@@ -299,4 +370,90 @@ mod unit_tests {
             }
         }
     }
+
+    /// Verify that with an external window, windowing the input ourselves gives the same output as the internal window
+    #[test]
+    fn test_mdct_external_window() {
+        for i in 1..11 {
+            let output_len = i * 2;
+
+            let input = random_signal(output_len * 2);
+            let window: Vec<f32> = window_fn::mp3(output_len * 2);
+            let windowed_input: Vec<f32> = input.iter().zip(&window).map(|(x, w)| x * w).collect();
+
+            let internal_mdct =
+                MdctViaDct4::new(Arc::new(Type4Naive::new(output_len)), window_fn::mp3);
+            let external_mdct = MdctViaDct4::with_window_mode(
+                Arc::new(Type4Naive::new(output_len)),
+                window_fn::mp3,
+                WindowMode::External,
+            );
+            let naive_mdct =
+                MdctNaive::with_window_mode(output_len, window_fn::mp3, WindowMode::External);
+
+            let mut scratch = vec![0f32; internal_mdct.get_scratch_len()];
+
+            let mut expected = vec![0f32; output_len];
+            let (input_a, input_b) = input.split_at(output_len);
+            internal_mdct.process_mdct_with_scratch(input_a, input_b, &mut expected, &mut scratch);
+
+            let (windowed_a, windowed_b) = windowed_input.split_at(output_len);
+            let mut external_output = vec![0f32; output_len];
+            external_mdct.process_mdct_with_scratch(
+                windowed_a,
+                windowed_b,
+                &mut external_output,
+                &mut scratch,
+            );
+            let mut naive_output = vec![0f32; output_len];
+            naive_mdct.process_mdct_with_scratch(
+                windowed_a,
+                windowed_b,
+                &mut naive_output,
+                &mut [],
+            );
+
+            assert!(
+                compare_float_vectors(&expected, &external_output),
+                "i = {}",
+                i
+            );
+            assert!(compare_float_vectors(&expected, &naive_output), "i = {}", i);
+        }
+    }
+
+    /// Verify that the sizes of the DCT4 butterflies need no scratch, and match the naive MDCT and IMDCT
+    #[test]
+    fn test_mdct_via_dct4_scratch_free() {
+        for &len in [4, 8, 16, 32].iter() {
+            let naive_mdct = MdctNaive::new(len, window_fn::vorbis);
+            let fast_mdct =
+                MdctViaDct4::new(crate::DctPlanner::new().plan_dct4(len), window_fn::vorbis);
+            assert_eq!(fast_mdct.get_scratch_len(), 0, "len = {}", len);
+
+            let input = random_signal(len * 2);
+            let (input_a, input_b) = input.split_at(len);
+            let mut naive_output = vec![0f32; len];
+            let mut fast_output = vec![0f32; len];
+            naive_mdct.process_mdct_with_scratch(input_a, input_b, &mut naive_output, &mut []);
+            fast_mdct.process_mdct_with_scratch(input_a, input_b, &mut fast_output, &mut []);
+            assert!(
+                compare_float_vectors(&naive_output, &fast_output),
+                "len = {}",
+                len
+            );
+
+            let mut naive_inverse = vec![0f32; len * 2];
+            let mut fast_inverse = vec![0f32; len * 2];
+            let (naive_a, naive_b) = naive_inverse.split_at_mut(len);
+            naive_mdct.process_imdct_with_scratch(&naive_output, naive_a, naive_b, &mut []);
+            let (fast_a, fast_b) = fast_inverse.split_at_mut(len);
+            fast_mdct.process_imdct_with_scratch(&fast_output, fast_a, fast_b, &mut []);
+            assert!(
+                compare_float_vectors(&naive_inverse, &fast_inverse),
+                "len = {}",
+                len
+            );
+        }
+    }
 }
@@ -0,0 +1,130 @@
+use crate::DctNum;
+
+/// Mixes two MDCT frames together in the MDCT domain, writing `frame_a * gain_a + frame_b * gain_b` to `output`.
+///
+/// Because the MDCT is linear, running the IMDCT on the mixed frame and overlap-adding it is equivalent to running the
+/// IMDCT on each frame separately, scaling each result, and summing them -- but it only requires one IMDCT instead of two.
+///
+/// Mixing is only valid when both frames were computed by MDCTs with the same length and the same window function.
+/// In particular, frames from different block sizes or from a window switch (for example, a long-to-short transition
+/// window in an audio codec) can't be mixed coefficient-by-coefficient: The time-domain aliasing in each frame depends
+/// on its window, so it will no longer cancel out during overlap-add. Frames like these must be mixed in the time domain.
+///
+/// # Panics
+///
+/// Panics if `frame_a`, `frame_b`, and `output` don't all have the same length.
+pub fn mix_frames<T: DctNum>(frame_a: &[T], frame_b: &[T], gain_a: T, gain_b: T, output: &mut [T]) {
+    assert!(
+        frame_a.len() == output.len() && frame_b.len() == output.len(),
+        "All three MDCT frames must have the same length. Got frame_a.len() = {}, frame_b.len() = {}, output.len() = {}",
+        frame_a.len(),
+        frame_b.len(),
+        output.len()
+    );
+
+    for ((a, b), out) in frame_a.iter().zip(frame_b.iter()).zip(output.iter_mut()) {
+        *out = *a * gain_a + *b * gain_b;
+    }
+}
+
+/// Crossfades from one sequence of MDCT frames to another, in the MDCT domain.
+///
+/// `frames_a`, `frames_b`, and `output` each contain a sequence of consecutive frames of length `frame_len`. If there
+/// are `n` frames, frame `i` of the output is mixed with `gain_b = (i + 1) / (n + 1)` and `gain_a = 1 - gain_b`.
+/// Since each output sample is the overlap-add of two consecutive frames, the resulting time-domain gain ramps
+/// smoothly from the last gain before the crossfade to the first gain after it, following the shape of the window.
+///
+/// The same window constraints as [`mix_frames`](fn.mix_frames.html) apply: Every pair of frames being mixed must have
+/// been computed by MDCTs with the same length and window.
+///
+/// # Panics
+///
+/// Panics if `frames_a`, `frames_b`, and `output` don't all have the same length, or if that length isn't a multiple
+/// of `frame_len`.
+pub fn crossfade_frames<T: DctNum>(
+    frames_a: &[T],
+    frames_b: &[T],
+    frame_len: usize,
+    output: &mut [T],
+) {
+    assert!(
+        frames_a.len() == output.len() && frames_b.len() == output.len(),
+        "All three frame sequences must have the same length. Got frames_a.len() = {}, frames_b.len() = {}, output.len() = {}",
+        frames_a.len(),
+        frames_b.len(),
+        output.len()
+    );
+    assert!(
+        frame_len > 0 && output.chunks_exact(frame_len).remainder().is_empty(),
+        "The frame sequences must contain a whole number of frames. Got len = {}, frame_len = {}",
+        output.len(),
+        frame_len
+    );
+
+    let num_frames = output.len() / frame_len;
+    let denominator = T::from_usize(num_frames + 1).unwrap();
+
+    for (i, ((frame_a, frame_b), output_frame)) in frames_a
+        .chunks_exact(frame_len)
+        .zip(frames_b.chunks_exact(frame_len))
+        .zip(output.chunks_exact_mut(frame_len))
+        .enumerate()
+    {
+        let gain_b = T::from_usize(i + 1).unwrap() / denominator;
+        let gain_a = T::one() - gain_b;
+
+        mix_frames(frame_a, frame_b, gain_a, gain_b, output_frame);
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::mdct::{window_fn, Mdct, MdctNaive};
+    use crate::test_utils::{compare_float_vectors, random_signal};
+
+    /// Verify that mixing in the MDCT domain is equivalent to mixing the IMDCT outputs
+    #[test]
+    fn test_mix_frames_matches_time_domain() {
+        let len = 8;
+        let mdct = MdctNaive::new(len, window_fn::mp3);
+
+        let frame_a = random_signal(len);
+        let frame_b: Vec<f32> = random_signal(len * 2)[len..].to_vec();
+        let (gain_a, gain_b) = (0.25, -1.5);
+
+        let mut mixed_frame = vec![0f32; len];
+        mix_frames(&frame_a, &frame_b, gain_a, gain_b, &mut mixed_frame);
+
+        let imdct = |frame: &[f32]| {
+            let mut output = vec![0f32; len * 2];
+            let (output_a, output_b) = output.split_at_mut(len);
+            mdct.process_imdct_with_scratch(frame, output_a, output_b, &mut []);
+            output
+        };
+
+        let expected: Vec<f32> = imdct(&frame_a)
+            .iter()
+            .zip(imdct(&frame_b).iter())
+            .map(|(a, b)| a * gain_a + b * gain_b)
+            .collect();
+        let actual = imdct(&mixed_frame);
+
+        println!("expected: {:?}", expected);
+        println!("actual:   {:?}", actual);
+        assert!(compare_float_vectors(&expected, &actual));
+    }
+
+    #[test]
+    fn test_crossfade_frames() {
+        let frame_len = 2;
+        let frames_a = vec![1f32; frame_len * 3];
+        let frames_b = vec![5f32; frame_len * 3];
+
+        let mut output = vec![0f32; frame_len * 3];
+        crossfade_frames(&frames_a, &frames_b, frame_len, &mut output);
+
+        let expected = vec![2f32, 2f32, 3f32, 3f32, 4f32, 4f32];
+        assert!(compare_float_vectors(&expected, &output));
+    }
+}
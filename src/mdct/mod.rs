@@ -1,10 +1,70 @@
 use rustfft::Length;
 
+mod analyzer;
+mod fold;
+mod mdct_mixed_precision;
 mod mdct_naive;
 mod mdct_via_dct4;
+mod mix;
+mod spectrum;
+mod verify;
 
 pub mod window_fn;
 
+/// Controls whether a MDCT applies its analysis window to the input, or expects the caller to have done it already
+///
+/// Some encoders already window their data as part of an earlier analysis stage. With `WindowMode::External`, the
+/// forward MDCT skips its window multiply entirely, and the caller is responsible for multiplying the input by the
+/// analysis window before calling `process_mdct_with_scratch`. This saves a pass over the data, and allows the
+/// analysis window to change from frame to frame.
+///
+/// The window mode only affects the forward MDCT: The IMDCT always applies the window that was provided when the
+/// MDCT was created, since it has to be applied before the result is added to the output.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WindowMode {
+    /// The MDCT multiplies its input by its window function. This is the default.
+    Internal,
+    /// The caller guarantees that the MDCT input has already been windowed, so the MDCT doesn't apply its window function.
+    External,
+}
+
+/// Controls whether the planner rescales the MDCT windows it's given. Set it with
+/// [`DctPlanner::set_mdct_window_scaling`](../struct.DctPlanner.html#method.set_mdct_window_scaling).
+///
+/// The built-in window functions come in two scales: `mp3`, `vorbis`, and `one` reconstruct the input of a MDCT and
+/// IMDCT round trip multiplied by `len / 2` or `len`, so the caller has to undo that scale, while their `_invertible`
+/// versions reconstruct the input exactly. Custom windows can have any scale.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WindowScaling {
+    /// The planner uses each window exactly as the window function returns it. This is the default.
+    AsProvided,
+    /// The planner rescales each window with [`window_fn::scale_orthonormal`](window_fn/fn.scale_orthonormal.html),
+    /// so that a MDCT and IMDCT round trip with 50% overlap reconstructs its input with a gain of 1, whichever window
+    /// function was used. With `WindowMode::External`, the caller should window the MDCT's input with a window that's
+    /// rescaled the same way.
+    Orthonormal,
+}
+impl WindowScaling {
+    // Rescales `window` according to this scaling
+    pub(crate) fn apply<T: DctNum>(self, window: &mut [T]) {
+        if self == WindowScaling::Orthonormal {
+            window_fn::scale_orthonormal(window);
+        }
+    }
+
+    // Wraps `window_fn`, so that the window it returns is rescaled according to this scaling
+    pub(crate) fn wrap_fn<T: DctNum, F>(self, window_fn: F) -> impl FnOnce(usize) -> Vec<T>
+    where
+        F: FnOnce(usize) -> Vec<T>,
+    {
+        move |len| {
+            let mut window = window_fn(len);
+            self.apply(&mut window);
+            window
+        }
+    }
+}
+
 /// An umbrella trait for algorithms which compute the Modified Discrete Cosine Transform (MDCT)
 pub trait Mdct<T: DctNum>: RequiredScratch + Length + Sync + Send {
     /// Computes the MDCT on the `input` buffer and places the result in the `output` buffer.
@@ -12,6 +72,27 @@ pub trait Mdct<T: DctNum>: RequiredScratch + Length + Sync + Send {
     ///
     /// To make overlapping array segments easier, this method DOES NOT modify the input buffer.
     ///
+    /// If the MDCT was created with `WindowMode::External`, the input must already be multiplied by the window.
+    ///
+    /// Scratch space of up to 64 elements is placed on the stack. Otherwise, this method allocates a Vec<T> of scratch
+    /// space. If you'd like to reuse that allocation between multiple computations, consider calling
+    /// `process_mdct_with_scratch` instead.
+    ///
+    /// Normalization depends on which window function was chosen when planning the mdct --
+    /// each built-in window function documents whether it does normalization or not.
+    fn process_mdct(&self, input_a: &[T], input_b: &[T], output: &mut [T]) {
+        with_scratch(self.get_scratch_len(), |scratch| {
+            self.process_mdct_with_scratch(input_a, input_b, output, scratch)
+        });
+    }
+
+    /// Computes the MDCT on the `input` buffer and places the result in the `output` buffer.
+    /// Uses `input_a` for the first half of the input, and `input_b` for the second half of the input
+    ///
+    /// To make overlapping array segments easier, this method DOES NOT modify the input buffer.
+    ///
+    /// If the MDCT was created with `WindowMode::External`, the input must already be multiplied by the window.
+    ///
     /// Normalization depends on which window function was chosen when planning the mdct --
     /// each built-in window function documents whether it does normalization or not.
     fn process_mdct_with_scratch(
@@ -23,7 +104,26 @@ pub trait Mdct<T: DctNum>: RequiredScratch + Length + Sync + Send {
     );
 
     /// Computes the IMDCT on the `input` buffer and places the result in the `output` buffer.
-    /// Puts the first half of the output in `output_a`, and puts the first half of the output in `output_b`.
+    /// Puts the first half of the output in `output_a`, and puts the second half of the output in `output_b`.
+    ///
+    /// Since the IMDCT is designed with overlapping output segments in mind, this method DOES NOT zero
+    /// out the output buffer before writing like most other DCT algorithms. Instead, it sums
+    /// the result of the IMDCT with what's already in the output buffer.
+    ///
+    /// Scratch space of up to 64 elements is placed on the stack. Otherwise, this method allocates a Vec<T> of scratch
+    /// space. If you'd like to reuse that allocation between multiple computations, consider calling
+    /// `process_imdct_with_scratch` instead.
+    ///
+    /// Normalization depends on which window function was chosen when planning the mdct --
+    /// each built-in window function documents whether it does normalization or not.
+    fn process_imdct(&self, input: &[T], output_a: &mut [T], output_b: &mut [T]) {
+        with_scratch(self.get_scratch_len(), |scratch| {
+            self.process_imdct_with_scratch(input, output_a, output_b, scratch)
+        });
+    }
+
+    /// Computes the IMDCT on the `input` buffer and places the result in the `output` buffer.
+    /// Puts the first half of the output in `output_a`, and puts the second half of the output in `output_b`.
     ///
     /// Since the IMDCT is designed with overlapping output segments in mind, this method DOES NOT zero
     /// out the output buffer before writing like most other DCT algorithms. Instead, it sums
@@ -38,9 +138,67 @@ pub trait Mdct<T: DctNum>: RequiredScratch + Length + Sync + Send {
         output_b: &mut [T],
         scratch: &mut [T],
     );
+
+    /// Returns the algorithmic delay of a MDCT/IMDCT round trip, in samples.
+    ///
+    /// An input sample isn't fully reconstructed until the IMDCT output of the frame after it has been overlap-added,
+    /// so a streaming decoder's output lags its input by `len()` samples.
+    fn latency_samples(&self) -> usize {
+        self.len()
+    }
+
+    /// Returns the number of samples that consecutive MDCT frames overlap by.
+    ///
+    /// Each IMDCT produces `2 * len()` samples, and the second half must be overlap-added with the first half of the next
+    /// frame's output before it's complete. To flush a stream, a decoder needs to process one more frame (typically
+    /// of zeroes) to complete the last `required_overlap()` samples.
+    fn required_overlap(&self) -> usize {
+        self.len()
+    }
+    /// Returns this instance as a [`MemoryUsage`](../trait.MemoryUsage.html), or None if it doesn't report one. See
+    /// [`Dct1::as_memory_usage`](../trait.Dct1.html#method.as_memory_usage).
+    fn as_memory_usage(&self) -> Option<&dyn MemoryUsage> {
+        None
+    }
+    /// Returns this instance's [`PlanKey`](../struct.PlanKey.html), or None if it doesn't report one. See
+    /// [`Dct1::known_plan_key`](../trait.Dct1.html#method.known_plan_key).
+    fn known_plan_key(&self) -> Option<PlanKey<T>> {
+        None
+    }
 }
 
-use crate::{DctNum, RequiredScratch};
+use crate::common::with_scratch;
+use crate::{DctNum, MemoryUsage, PlanKey, RequiredScratch};
+
+// Calls `window_fn` to create a window of length `len`, and checks that it returned the right number of values
+pub(crate) fn window_from_fn<T, F>(len: usize, window_fn: F) -> Box<[T]>
+where
+    F: FnOnce(usize) -> Vec<T>,
+{
+    let window = window_fn(len);
+    assert_eq!(
+        window.len(),
+        len,
+        "Window function returned incorrect number of values"
+    );
+    window.into_boxed_slice()
+}
+
+// Creates a zeroed window of length `len`, and lets `fill_window` write the window values into it
+pub(crate) fn window_from_fill<T: DctNum, F>(len: usize, fill_window: F) -> Box<[T]>
+where
+    F: FnOnce(&mut [T]),
+{
+    let mut window = vec![T::zero(); len].into_boxed_slice();
+    fill_window(&mut window);
+    window
+}
 
+pub use self::analyzer::MdctAnalyzer;
+pub use self::fold::{fold_input, unfold_output};
+pub use self::mdct_mixed_precision::MdctMixedPrecision;
 pub use self::mdct_naive::MdctNaive;
 pub use self::mdct_via_dct4::MdctViaDct4;
+pub use self::mix::{crossfade_frames, mix_frames};
+pub use self::spectrum::{bin_frequency, compensate_window_power, power_spectrum};
+pub use self::verify::{verify_tdac, TdacError};
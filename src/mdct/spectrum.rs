@@ -0,0 +1,164 @@
+use crate::DctNum;
+
+/// Returns the center frequency of MDCT coefficient `index`, in the same units as `sample_rate`, for a MDCT with `len`
+/// output coefficients.
+///
+/// A MDCT with `len` coefficients splits the band from 0 to the Nyquist frequency into `len` equal bins, and its basis
+/// functions are offset by half a bin: Coefficient `k` is centered on `(k + 0.5) * sample_rate / (2 * len)`. So unlike
+/// a DFT, no coefficient is centered on DC or on the Nyquist frequency.
+///
+/// ~~~
+/// // A MDCT with 1024 coefficients at 48 kHz has bins 23.4375 Hz wide
+/// use rustdct::mdct::bin_frequency;
+///
+/// assert_eq!(bin_frequency(0, 1024, 48000.0), 11.71875);
+/// assert_eq!(bin_frequency(1023, 1024, 48000.0), 23988.28125);
+/// ~~~
+pub fn bin_frequency(index: usize, len: usize, sample_rate: f64) -> f64 {
+    (index as f64 + 0.5) * sample_rate / (2 * len) as f64
+}
+
+/// Computes an approximate power spectrum from one frame of MDCT coefficients, by squaring each coefficient.
+///
+/// The MDCT is a real transform, so each coefficient only measures the part of the signal that's in phase with its
+/// cosine basis function. For a steady sinusoid, the coefficients near its frequency rise and fall from one frame to
+/// the next as its phase changes relative to the frame, and a single frame's power can be anywhere from almost zero
+/// to about twice the average. So this is only a useful power estimate when it's averaged: over several frames, over
+/// neighboring coefficients, or both. If a phase-independent spectrum of a single frame is needed, compute a DCT2 or a
+/// FFT of the windowed frame instead.
+///
+/// To convert the result into the units of the input signal, see
+/// [`compensate_window_power`](fn.compensate_window_power.html). To find the frequency of each element, see
+/// [`bin_frequency`](fn.bin_frequency.html).
+///
+/// # Panics
+///
+/// Panics if `coefficients` and `output` have different lengths.
+pub fn power_spectrum<T: DctNum>(coefficients: &[T], output: &mut [T]) {
+    assert_eq!(
+        coefficients.len(),
+        output.len(),
+        "The coefficients and output must have the same length. Got coefficients len = {}, output len = {}",
+        coefficients.len(),
+        output.len()
+    );
+
+    for (power, coefficient) in output.iter_mut().zip(coefficients.iter()) {
+        *power = *coefficient * *coefficient;
+    }
+}
+
+/// Rescales a power spectrum computed by [`power_spectrum`](fn.power_spectrum.html) to compensate for the gain of the
+/// MDCT and its analysis window, so that the sum of the spectrum estimates the mean square of the input signal.
+///
+/// `window` is the window that the MDCT's input was multiplied by, with `2 * spectrum.len()` elements: For a plan
+/// created with `WindowMode::Internal`, it's the output of the plan's window function. Each element of the spectrum is
+/// multiplied by `2 / (len * sum(window[i]^2))`, where `len` is `spectrum.len()`.
+///
+/// Like the power spectrum itself, the compensated spectrum only matches the signal's power on average: For example,
+/// averaged over many frames, a sinusoid with amplitude `A` has a total power of `A^2 / 2`, spread over a few
+/// coefficients around its frequency.
+///
+/// ~~~
+/// // Estimates the power of a sinusoid with amplitude 0.5 from the MDCT of several consecutive frames
+/// use rustdct::mdct::{compensate_window_power, power_spectrum, window_fn};
+/// use rustdct::{DctPlanner, RequiredScratch};
+///
+/// let len = 256;
+/// let mut planner = DctPlanner::new();
+/// let mdct = planner.plan_mdct(len, window_fn::vorbis);
+/// let mut scratch = vec![0.0; mdct.get_scratch_len()];
+///
+/// let signal: Vec<f64> = (0..len * 65).map(|i| 0.5 * (i as f64 * 0.3).sin()).collect();
+/// let mut total_power = 0.0;
+/// let mut coefficients = vec![0.0; len];
+/// let mut spectrum = vec![0.0; len];
+/// for frame in signal.windows(len * 2).step_by(len) {
+///     mdct.process_mdct_with_scratch(&frame[..len], &frame[len..], &mut coefficients, &mut scratch);
+///     power_spectrum(&coefficients, &mut spectrum);
+///     compensate_window_power(&mut spectrum, &window_fn::vorbis(len * 2));
+///     total_power += spectrum.iter().sum::<f64>() / 64.0;
+/// }
+/// assert!((total_power - 0.125).abs() < 0.01);
+/// ~~~
+///
+/// # Panics
+///
+/// Panics if `window.len()` isn't `2 * spectrum.len()`.
+pub fn compensate_window_power<T: DctNum>(spectrum: &mut [T], window: &[T]) {
+    assert_eq!(
+        window.len(),
+        spectrum.len() * 2,
+        "The window must be twice as long as the spectrum. Expected window len = {}, got window len = {}",
+        spectrum.len() * 2,
+        window.len()
+    );
+
+    let window_energy = window
+        .iter()
+        .fold(T::zero(), |sum, value| sum + *value * *value);
+    let scale = T::two() / (T::from_usize(spectrum.len()).unwrap() * window_energy);
+    for power in spectrum.iter_mut() {
+        *power = *power * scale;
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::mdct::{window_fn, Mdct, MdctNaive};
+    use std::f64::consts::PI;
+
+    /// Verify that the compensated power of a sinusoid, averaged over its phase, is `amplitude^2 / 2` for each of the
+    /// built-in windows, whether its frequency is centered on a coefficient or between two coefficients
+    #[test]
+    fn test_compensate_window_power() {
+        let len = 32;
+        let amplitude = 3.0;
+        let windows: [fn(usize) -> Vec<f64>; 4] = [
+            window_fn::mp3,
+            window_fn::vorbis,
+            window_fn::one,
+            window_fn::invertible,
+        ];
+        for make_window in windows.iter() {
+            let mdct = MdctNaive::new(len, make_window);
+            let window = make_window(len * 2);
+
+            for &bin in &[5.0, 5.25, 5.5] {
+                let num_phases = 64;
+                let mut average_power = 0.0;
+                for phase_index in 0..num_phases {
+                    let phase = 2.0 * PI * phase_index as f64 / num_phases as f64;
+                    let input: Vec<f64> = (0..len * 2)
+                        .map(|i| {
+                            amplitude * (PI * (bin + 0.5) * i as f64 / len as f64 + phase).cos()
+                        })
+                        .collect();
+
+                    let mut coefficients = vec![0.0; len];
+                    mdct.process_mdct_with_scratch(
+                        &input[..len],
+                        &input[len..],
+                        &mut coefficients,
+                        &mut [],
+                    );
+                    let mut spectrum = vec![0.0; len];
+                    power_spectrum(&coefficients, &mut spectrum);
+                    compensate_window_power(&mut spectrum, &window);
+
+                    average_power += spectrum.iter().sum::<f64>() / num_phases as f64;
+                }
+
+                let expected_power = amplitude * amplitude / 2.0;
+                assert!(
+                    (average_power - expected_power).abs() < expected_power * 0.02,
+                    "bin = {}, expected {}, got {}",
+                    bin,
+                    expected_power,
+                    average_power
+                );
+            }
+        }
+    }
+}
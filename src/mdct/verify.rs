@@ -0,0 +1,193 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::mdct::Mdct;
+use crate::DctNum;
+
+// The number of overlapping frames in the test signal. Every frame but the first and last has both of its halves
+// completed by an overlap-add
+const NUM_FRAMES: usize = 5;
+
+/// Describes why a MDCT plan failed [`verify_tdac`](fn.verify_tdac.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TdacError<T> {
+    /// The overlap-added IMDCT output had no component in the direction of the input, so the input can't be
+    /// reconstructed with any scale. For example, this happens if the window is all zeroes.
+    NoReconstruction,
+    /// The overlap-added IMDCT output, divided by the reconstruction gain, differed from the input by more than the
+    /// tolerance.
+    Mismatch {
+        /// The index of the first sample that differed, counted from the start of the first fully reconstructed sample
+        index: usize,
+        /// The input sample at `index`
+        expected: T,
+        /// The reconstructed sample at `index`, divided by the reconstruction gain
+        actual: T,
+        /// The reconstruction gain: the scale that the round trip applied to the input
+        gain: T,
+    },
+}
+
+impl<T: fmt::Debug> fmt::Display for TdacError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TdacError::NoReconstruction => {
+                write!(f, "The MDCT round trip didn't reconstruct the input at any scale")
+            }
+            TdacError::Mismatch {
+                index,
+                expected,
+                actual,
+                gain,
+            } => write!(
+                f,
+                "The MDCT round trip differs from the input at index {}: expected {:?}, got {:?} (reconstruction gain {:?})",
+                index, expected, actual, gain
+            ),
+        }
+    }
+}
+impl<T: fmt::Debug> Error for TdacError<T> {}
+
+/// Checks that a MDCT plan and its window satisfy time-domain aliasing cancellation (TDAC), so that overlap-adding the
+/// IMDCT of consecutive MDCT frames reconstructs the original signal.
+///
+/// This computes the MDCT of several overlapping frames of a pseudorandom signal, with a hop of `plan.len()`, then
+/// overlap-adds the IMDCT of each frame. Every sample covered by two frames should be reconstructed up to a constant
+/// gain. The gain depends on the window: It's 1 for the `_invertible` windows in [`window_fn`](window_fn/index.html),
+/// `len / 2` for `window_fn::mp3`, and `len` for `window_fn::one`. It's also 1 for every window that was rescaled
+/// with `WindowScaling::Orthonormal`.
+///
+/// The reconstructed samples are divided by the gain, and compared to the input, which has samples between -1 and 1.
+/// Returns the gain if every sample is within `tolerance` of the input. Otherwise, returns the first sample that isn't.
+///
+/// Plans created with `WindowMode::External` expect the caller to window their input, so they only pass if their window
+/// would reconstruct the signal by being applied once, in the IMDCT.
+///
+/// ~~~
+/// // Checks that the Vorbis window reconstructs its input, and that a window with a typo doesn't
+/// use rustdct::mdct::{verify_tdac, window_fn};
+/// use rustdct::DctPlanner;
+///
+/// let mut planner = DctPlanner::new();
+/// let mdct = planner.plan_mdct(64, window_fn::vorbis_invertible);
+/// let gain = verify_tdac(mdct.as_ref(), 1e-4).unwrap();
+/// assert!((gain - 1.0f32).abs() < 1e-4);
+///
+/// let mut planner = DctPlanner::new();
+/// let mdct = planner.plan_mdct(64, |len| {
+///     let mut window = window_fn::vorbis_invertible(len);
+///     window[3] = 0.5;
+///     window
+/// });
+/// assert!(verify_tdac(mdct.as_ref(), 1e-4).is_err());
+/// ~~~
+pub fn verify_tdac<T: DctNum>(plan: &dyn Mdct<T>, tolerance: T) -> Result<T, TdacError<T>> {
+    let len = plan.len();
+    if len == 0 {
+        return Ok(T::one());
+    }
+
+    let signal = pseudorandom_signal(len * (NUM_FRAMES + 1));
+    let mut frame = vec![T::zero(); len];
+    let mut reconstructed = vec![T::zero(); signal.len()];
+    let mut scratch = vec![T::zero(); plan.get_scratch_len()];
+
+    for i in 0..NUM_FRAMES {
+        let (input_a, input_b) = signal[len * i..len * (i + 2)].split_at(len);
+        plan.process_mdct_with_scratch(input_a, input_b, &mut frame, &mut scratch);
+
+        let (output_a, output_b) = reconstructed[len * i..len * (i + 2)].split_at_mut(len);
+        plan.process_imdct_with_scratch(&frame, output_a, output_b, &mut scratch);
+    }
+
+    // the first and last `len` samples are only covered by one frame, so they aren't reconstructed
+    let expected = &signal[len..signal.len() - len];
+    let actual = &reconstructed[len..reconstructed.len() - len];
+
+    // the least-squares estimate of the gain, which is exact if the round trip reconstructs the input
+    let (correlation, energy) = expected.iter().zip(actual.iter()).fold(
+        (T::zero(), T::zero()),
+        |(correlation, energy), (&expected, &actual)| {
+            (
+                correlation + expected * actual,
+                energy + expected * expected,
+            )
+        },
+    );
+    let gain = correlation / energy;
+    if gain.is_zero() || is_nan(gain) {
+        return Err(TdacError::NoReconstruction);
+    }
+
+    for (index, (&expected, &actual)) in expected.iter().zip(actual.iter()).enumerate() {
+        let actual = actual / gain;
+        let difference = (expected - actual).abs();
+        if is_nan(difference) || (tolerance - difference).is_negative() {
+            return Err(TdacError::Mismatch {
+                index,
+                expected,
+                actual,
+                gain,
+            });
+        }
+    }
+    Ok(gain)
+}
+
+// `DctNum` doesn't provide `is_nan`, but NaN is the only value that isn't equal to itself
+#[allow(clippy::eq_op)]
+fn is_nan<T: DctNum>(value: T) -> bool {
+    value != value
+}
+
+// Returns `len` samples between -1 and 1 from a fixed xorshift sequence, so the check is reproducible
+fn pseudorandom_signal<T: DctNum>(len: usize) -> Vec<T> {
+    let mut state = 0x2545_F491u32;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            T::from_f64(f64::from(state) / 4294967295.0 * 2.0 - 1.0).unwrap()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::mdct::{window_fn, MdctNaive, WindowMode};
+
+    #[test]
+    fn test_verify_tdac() {
+        for &len in &[2, 6, 16, 50] {
+            let invertible = MdctNaive::new(len, window_fn::mp3_invertible);
+            let gain: f64 = verify_tdac(&invertible, 1e-10).unwrap();
+            assert!((gain - 1.0).abs() < 1e-10, "len = {}", len);
+
+            let mp3 = MdctNaive::new(len, window_fn::mp3);
+            let gain: f64 = verify_tdac(&mp3, 1e-10).unwrap();
+            assert!((gain - len as f64 / 2.0).abs() < 1e-8, "len = {}", len);
+
+            // a linear ramp doesn't satisfy the Princen-Bradley condition
+            let ramp = MdctNaive::new(len, |window_len| {
+                (0..window_len)
+                    .map(|i| (i + 1) as f64 / window_len as f64)
+                    .collect()
+            });
+            match verify_tdac(&ramp, 1e-6) {
+                Err(TdacError::Mismatch { .. }) => {}
+                other => panic!("len = {}, got {:?}", len, other),
+            }
+
+            let zero = MdctNaive::new(len, |window_len| vec![0f64; window_len]);
+            assert_eq!(verify_tdac(&zero, 1e-6), Err(TdacError::NoReconstruction));
+
+            // an externally windowed MDCT only applies its window once, so a window that's meant to be applied twice
+            // doesn't reconstruct the input
+            let external = MdctNaive::with_window_mode(len, window_fn::mp3, WindowMode::External);
+            assert!(verify_tdac(&external, 1e-6).is_err(), "len = {}", len);
+        }
+    }
+}
@@ -52,6 +52,45 @@ pub fn vorbis_invertible<T: DctNum>(len: usize) -> Vec<T> {
         .collect()
 }
 
+/// Rescales `window` in place, so that a MDCT and IMDCT with 50% overlap reconstruct their input with a gain of 1.
+///
+/// Overlap-adding the IMDCT of consecutive MDCT frames reconstructs the input scaled by
+/// `len / 4 * (window[i]^2 + window[i + len / 2]^2)`, where `len` is the window length, so this divides the window by
+/// the square root of that gain. Then the MDCT is orthonormal, and any window that satisfies the Princen-Bradley
+/// condition can replace any other without changing the scale of the output. For example, this turns `mp3` into
+/// `mp3_invertible`, and `one` into `invertible`.
+///
+/// For windows that don't satisfy the Princen-Bradley condition, `window[i]^2 + window[i + len / 2]^2` isn't constant,
+/// so no scale reconstructs the input, and the average over `i` is used instead. A window that's all zeroes is left
+/// unchanged.
+pub fn scale_orthonormal<T: DctNum>(window: &mut [T]) {
+    let power_sum = window
+        .iter()
+        .fold(T::zero(), |sum, value| sum + *value * *value);
+    if power_sum == T::zero() {
+        return;
+    }
+
+    // the gain is len / 4 times the average of the sums, and there are len / 2 sums. It's len / 4 for windows like `mp3`
+    let gain = power_sum * T::half();
+    let estimate = (window.len() as f64 / 4.0).sqrt();
+    let scale = T::one() / newton_sqrt(gain, estimate);
+    for value in window.iter_mut() {
+        *value = *value * scale;
+    }
+}
+
+// Returns the square root of the positive `value` with Newton's method, since `DctNum` doesn't have a square root.
+// It converges from any positive `estimate`, in a few iterations from one within a factor of 2, and in one more
+// iteration for every further factor of 2
+fn newton_sqrt<T: DctNum>(value: T, estimate: f64) -> T {
+    let mut root = T::from_f64(estimate).unwrap();
+    for _ in 0..64 {
+        root = (root + value / root) * T::half();
+    }
+    root
+}
+
 /// MDCT window function which is all ones (IE, no windowing will be applied)
 pub fn one<T: DctNum>(len: usize) -> Vec<T> {
     (0..len).map(|_| T::one()).collect()
@@ -88,4 +127,34 @@ mod unit_tests {
             }
         }
     }
+
+    /// Verify that scaling the built-in windows produces their invertible versions
+    #[test]
+    fn test_scale_orthonormal() {
+        let pairs: [(fn(usize) -> Vec<f32>, fn(usize) -> Vec<f32>); 3] = [
+            (mp3, mp3_invertible),
+            (vorbis, vorbis_invertible),
+            (one, invertible),
+        ];
+        for &(window_fn, invertible_fn) in pairs.iter() {
+            for half_size in 1..20 {
+                let mut window = window_fn(half_size * 2);
+                scale_orthonormal(&mut window);
+
+                // scaling an already orthonormal window doesn't change it
+                let expected = invertible_fn(half_size * 2);
+                let mut rescaled = expected.clone();
+                scale_orthonormal(&mut rescaled);
+
+                for i in 0..half_size * 2 {
+                    assert!(fuzzy_cmp(window[i], expected[i], 1e-6));
+                    assert!(fuzzy_cmp(rescaled[i], expected[i], 1e-6));
+                }
+            }
+        }
+
+        let mut zeroes = vec![0f32; 8];
+        scale_orthonormal(&mut zeroes);
+        assert_eq!(zeroes, vec![0f32; 8]);
+    }
 }
@@ -0,0 +1,178 @@
+use std::collections::HashSet;
+use std::ops::Add;
+use std::sync::Arc;
+
+use crate::mdct::Mdct;
+use crate::{Dct1, Dct2, Dct3, Dct4, Dct5, Dct6, Dct6And7, Dct7, Dct8, DctNum};
+use crate::{
+    Dst1, Dst2, Dst3, Dst4, Dst5, Dst6, Dst6And7, Dst7, Dst8, TransformType2And3, TransformType4,
+};
+
+/// The memory used by the precomputed data of a plan, in bytes.
+///
+/// Returned by [`MemoryUsage::memory_usage`](trait.MemoryUsage.html#method.memory_usage).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PlanMemory {
+    /// The size of the plan's twiddle factors, and any other precomputed tables that don't belong in `window_bytes`.
+    pub twiddles_bytes: usize,
+    /// The size of the plan's window function, for MDCT plans.
+    pub window_bytes: usize,
+    /// The total size of the plan's inner plans, including their own inner plans. An inner plan that's shared with
+    /// another part of the plan is only counted once.
+    pub inner_plans_bytes: usize,
+}
+
+impl PlanMemory {
+    /// Returns the sum of all the fields.
+    pub fn total_bytes(&self) -> usize {
+        self.twiddles_bytes + self.window_bytes + self.inner_plans_bytes
+    }
+}
+
+impl Add for PlanMemory {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            twiddles_bytes: self.twiddles_bytes + other.twiddles_bytes,
+            window_bytes: self.window_bytes + other.window_bytes,
+            inner_plans_bytes: self.inner_plans_bytes + other.inner_plans_bytes,
+        }
+    }
+}
+
+/// Keeps track of which inner plans have already been counted while computing a plan's memory usage.
+///
+/// The planner shares inner plans between the instances it creates. To count each shared plan once when auditing
+/// several plans together, pass the same visitor to each plan's `memory_usage_with_visitor`:
+///
+/// ~~~
+/// use rustdct::{DctPlanner, MemoryUsage, MemoryVisitor};
+///
+/// let mut planner = DctPlanner::<f32>::new();
+/// let dct2 = planner.plan_dct2(1024);
+/// let dct4 = planner.plan_dct4(1024);
+///
+/// let mut visitor = MemoryVisitor::new();
+/// let total = visitor.inner_plan(&dct2) + visitor.inner_plan(&dct4);
+/// assert!(total <= dct2.memory_usage().total_bytes() + dct4.memory_usage().total_bytes());
+/// ~~~
+#[derive(Debug, Default)]
+pub struct MemoryVisitor {
+    visited: HashSet<usize>,
+    visited_tables: HashSet<usize>,
+}
+
+impl MemoryVisitor {
+    /// Creates a visitor that hasn't counted any plans yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the total memory used by `plan`, including its own inner plans, or 0 if this visitor has already
+    /// counted `plan`.
+    pub fn inner_plan<P: MemoryUsage + ?Sized>(&mut self, plan: &Arc<P>) -> usize {
+        let address = &**plan as *const P as *const () as usize;
+        if self.visited.insert(address) {
+            plan.memory_usage_with_visitor(self).total_bytes()
+        } else {
+            0
+        }
+    }
+
+    // Returns the number of bytes in `table`, or 0 if this visitor has already counted it. Used for tables that the
+    // planner shares between instances
+    pub(crate) fn shared_table<E>(&mut self, table: &[E]) -> usize {
+        if self.visited_tables.insert(table.as_ptr() as usize) {
+            table_bytes(table)
+        } else {
+            0
+        }
+    }
+}
+
+/// Reports the memory used by a plan's precomputed data.
+///
+/// This counts twiddle factors, window functions, and other tables that are computed when the plan is created, so that
+/// applications with strict memory budgets can audit what the planner built. It doesn't count scratch space, which
+/// is provided by the caller, or the memory used by inner FFT plans, which RustFFT doesn't report.
+pub trait MemoryUsage {
+    /// Returns the memory used by this plan, using `visitor` to count each inner plan only once.
+    ///
+    /// Implementations should count their inner plans with [`MemoryVisitor::inner_plan`](struct.MemoryVisitor.html#method.inner_plan).
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory;
+
+    /// Returns the memory used by this plan. Inner plans that are shared by different parts of this plan are only
+    /// counted once.
+    fn memory_usage(&self) -> PlanMemory {
+        self.memory_usage_with_visitor(&mut MemoryVisitor::new())
+    }
+}
+impl<P: MemoryUsage + ?Sized> MemoryUsage for &P {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        (**self).memory_usage_with_visitor(visitor)
+    }
+}
+impl<P: MemoryUsage + ?Sized> MemoryUsage for Arc<P> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        (**self).memory_usage_with_visitor(visitor)
+    }
+}
+impl<P: MemoryUsage + ?Sized> MemoryUsage for Box<P> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        (**self).memory_usage_with_visitor(visitor)
+    }
+}
+
+// Trait objects report the memory of the algorithms that implement `as_memory_usage`, and no memory for the others.
+// `$hook_trait` is the transform trait whose `as_memory_usage` a trait object calls. The traits that combine several
+// transform traits use the first one
+macro_rules! impl_trait_object_memory_usage {
+    ($(($trait_name:ident, $hook_trait:ident)),*) => {
+        $(
+            impl<T: DctNum> MemoryUsage for dyn $trait_name<T> {
+                fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+                    $hook_trait::as_memory_usage(self)
+                        .map_or_else(PlanMemory::default, |plan| plan.memory_usage_with_visitor(visitor))
+                }
+            }
+        )*
+    };
+}
+
+impl_trait_object_memory_usage!(
+    (Dct1, Dct1),
+    (Dct2, Dct2),
+    (Dct3, Dct3),
+    (Dct4, Dct4),
+    (Dct5, Dct5),
+    (Dct6, Dct6),
+    (Dct7, Dct7),
+    (Dct8, Dct8),
+    (Dst1, Dst1),
+    (Dst2, Dst2),
+    (Dst3, Dst3),
+    (Dst4, Dst4),
+    (Dst5, Dst5),
+    (Dst6, Dst6),
+    (Dst7, Dst7),
+    (Dst8, Dst8),
+    (TransformType2And3, Dct2),
+    (TransformType4, Dct4),
+    (Dct6And7, Dct6),
+    (Dst6And7, Dst6),
+    (Mdct, Mdct)
+);
+
+// Returns the number of bytes in `table`
+pub(crate) fn table_bytes<E>(table: &[E]) -> usize {
+    std::mem::size_of_val(table)
+}
+
+// Returns a `PlanMemory` for a plan whose only precomputed data is `table`
+pub(crate) fn twiddles_only<E>(table: &[E]) -> PlanMemory {
+    PlanMemory {
+        twiddles_bytes: table_bytes(table),
+        ..PlanMemory::default()
+    }
+}
@@ -0,0 +1,62 @@
+//! Sine series expansions, for modal analysis of systems with fixed ends.
+//!
+//! A deflection `f(x)` of a string or simply supported beam of length `L` with both ends fixed at zero can be expanded
+//! into the sine series `f(x) = b_1 sin(1πx/L) + b_2 sin(2πx/L) + ...`, where each term is the shape of one vibration
+//! mode. When `f` is sampled at the `N` interior points of a uniform grid, `x_j = j * L / (N + 1)` for `j` in `1..=N`,
+//! the first `N` coefficients of the series are a scaled DST Type 1 of the samples. The functions in this module
+//! compute that DST1 with a plan from a `DctPlanner`, so repeated calls with the same size share a cached plan.
+//!
+//! ~~~
+//! // Expands a deflection sampled at 9 interior points into its sine series, then reconstructs it
+//! use rustdct::modal::{sine_series_coefficients, sine_series_values};
+//! use rustdct::DctPlanner;
+//!
+//! let mut planner = DctPlanner::new();
+//! let mut values = vec![0f32, 1.0, 2.0, 3.0, 4.0, 3.0, 2.0, 1.0, 0.5];
+//!
+//! sine_series_coefficients(&mut planner, &mut values);
+//! // `values[k]` is now the amplitude of mode `k + 1`
+//!
+//! sine_series_values(&mut planner, &mut values);
+//! // `values` contains the original samples again
+//! ~~~
+
+use crate::{DctNum, DctPlanner};
+
+/// Replaces the samples in `values` with the coefficients of their sine series, in-place.
+///
+/// `values[j]` is the deflection at grid point `j + 1`, and after this returns, `values[k]` is the amplitude of mode
+/// `k + 1`. The coefficients are scaled by `2 / (N + 1)`, so they're the true amplitudes of the series, and
+/// `sine_series_values` reconstructs the samples without any further scaling.
+pub fn sine_series_coefficients<T: DctNum>(planner: &mut DctPlanner<T>, values: &mut [T]) {
+    let dst = planner.plan_dst1(values.len());
+    dst.process_dst1(values);
+
+    let scale = T::two() / T::from_usize(values.len() + 1).unwrap();
+    for value in values.iter_mut() {
+        *value = *value * scale;
+    }
+}
+
+/// Replaces the sine series coefficients in `coefficients` with the samples of the series at each grid point, in-place.
+///
+/// This is the inverse of `sine_series_coefficients`: `coefficients[k]` is the amplitude of mode `k + 1`, and after
+/// this returns, `coefficients[j]` is the deflection at grid point `j + 1`.
+pub fn sine_series_values<T: DctNum>(planner: &mut DctPlanner<T>, coefficients: &mut [T]) {
+    let dst = planner.plan_dst1(coefficients.len());
+    dst.process_dst1(coefficients);
+}
+
+/// Returns the frequencies, in cycles per unit of time, of the `len` modes that can be represented on a grid with `len`
+/// interior points, for a medium of length `domain_length` where waves travel at `wave_speed`.
+///
+/// Mode `k` is a standing wave with `k` half-wavelengths across the domain, so its frequency is
+/// `k * wave_speed / (2 * domain_length)`. Element `k` of the result is the frequency of mode `k + 1`, matching the
+/// order of the coefficients returned by `sine_series_coefficients`. This assumes that waves of every frequency travel
+/// at the same speed, as they do on a taut string; dispersive systems like beams need their own frequency relation.
+pub fn modal_frequencies<T: DctNum>(len: usize, domain_length: T, wave_speed: T) -> Vec<T> {
+    let fundamental = wave_speed / (T::two() * domain_length);
+    (1..=len)
+        .map(|mode| T::from_usize(mode).unwrap() * fundamental)
+        .collect()
+}
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+
+/// The order that a DCT2 planned with `DctPlanner::plan_dct2_with_output_order` writes its output coefficients in.
+///
+/// Some codecs store DCT coefficients in a permuted order. Choosing the order when planning lets the DCT write each
+/// coefficient directly to its final position during its last pass over the data, instead of requiring a separate
+/// permutation pass afterwards.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OutputOrder {
+    /// The usual order: Output `i` is coefficient `i`.
+    Natural,
+    /// All of the even coefficients in ascending order, followed by all of the odd coefficients in ascending order.
+    EvensThenOdds,
+    /// A custom permutation: Output `i` is coefficient `order[i]`. The permutation is stored in an `Arc` so that it can
+    /// be kept by the planned DCT.
+    Custom(Arc<[usize]>),
+}
+
+impl OutputOrder {
+    /// Returns the coefficient stored at each output position, for a transform of size `len`. In other words, output
+    /// `i` is coefficient `self.coefficient_order(len)[i]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is a `Custom` order that isn't a permutation of `0..len`.
+    pub fn coefficient_order(&self, len: usize) -> Vec<usize> {
+        match self {
+            OutputOrder::Natural => (0..len).collect(),
+            OutputOrder::EvensThenOdds => (0..len).step_by(2).chain((1..len).step_by(2)).collect(),
+            OutputOrder::Custom(order) => {
+                assert_eq!(
+                    order.len(),
+                    len,
+                    "The custom output order must have one entry for each coefficient. Got order len = {}, transform len = {}",
+                    order.len(),
+                    len
+                );
+                let mut seen = vec![false; len];
+                for &coefficient in order.iter() {
+                    assert!(
+                        coefficient < len && !seen[coefficient],
+                        "The custom output order must be a permutation of 0..{}. Got {:?}",
+                        len,
+                        order
+                    );
+                    seen[coefficient] = true;
+                }
+                order.to_vec()
+            }
+        }
+    }
+
+    // Returns the output position of each coefficient, or None if the order is natural
+    pub(crate) fn destinations(&self, len: usize) -> Option<Box<[usize]>> {
+        if *self == OutputOrder::Natural {
+            return None;
+        }
+
+        let mut destinations = vec![0; len];
+        for (position, coefficient) in self.coefficient_order(len).into_iter().enumerate() {
+            destinations[coefficient] = position;
+        }
+        Some(destinations.into_boxed_slice())
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn test_coefficient_order() {
+        assert_eq!(OutputOrder::Natural.coefficient_order(4), vec![0, 1, 2, 3]);
+        assert_eq!(
+            OutputOrder::EvensThenOdds.coefficient_order(5),
+            vec![0, 2, 4, 1, 3]
+        );
+        assert_eq!(OutputOrder::Natural.destinations(4), None);
+        assert_eq!(
+            OutputOrder::EvensThenOdds.destinations(5).unwrap().as_ref(),
+            &[0, 3, 1, 4, 2]
+        );
+
+        let custom = OutputOrder::Custom(vec![2, 0, 1].into());
+        assert_eq!(custom.coefficient_order(3), vec![2, 0, 1]);
+        assert_eq!(custom.destinations(3).unwrap().as_ref(), &[1, 2, 0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_custom_order_not_a_permutation() {
+        OutputOrder::Custom(vec![0, 0, 1].into()).coefficient_order(3);
+    }
+}
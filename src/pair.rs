@@ -0,0 +1,304 @@
+use std::sync::Arc;
+
+use rustfft::Length;
+
+use crate::{DctNum, RequiredScratch, ScratchLayout, TransformType2And3};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+
+/// Describes how a [`Type2And3Pair`](struct.Type2And3Pair.html) scales the outputs of its forward and inverse transforms.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Normalization {
+    /// Neither direction is scaled. `inverse(forward(x))` is equal to `x * len / 2`.
+    Unnormalized,
+    /// The forward transform isn't scaled, and the inverse transform's output is scaled by `2 / len`, so that
+    /// `inverse(forward(x))` is equal to `x`.
+    Inverse,
+    /// Both directions are scaled so that the transform is orthonormal: The forward transform preserves the energy of
+    /// the signal, and `inverse(forward(x))` is equal to `x`.
+    Orthonormal,
+}
+
+/// A planned DCT2 and its inverse, the DCT3, sharing a single underlying DCT instance.
+///
+/// Every `TransformType2And3` instance can compute both the DCT2 and the DCT3 using the same twiddle factors, so a
+/// forward/inverse pipeline only needs to plan and store one DCT. `Type2And3Pair` wraps that instance, and applies
+/// the scaling described by its [`Normalization`](enum.Normalization.html) in each direction.
+///
+/// ~~~
+/// // Computes a DCT Type 2 of size 1234 and then inverts it
+/// use rustdct::{DctPlanner, Normalization};
+///
+/// let mut planner = DctPlanner::new();
+/// let pair = planner.plan_dct2_pair(1234, Normalization::Inverse);
+///
+/// let mut buffer = vec![0f32; 1234];
+/// pair.forward(&mut buffer);
+/// pair.inverse(&mut buffer);
+/// ~~~
+pub struct Type2And3Pair<T> {
+    dct: Arc<dyn TransformType2And3<T>>,
+    normalization: Normalization,
+    forward_scale: T,
+    forward_first_scale: T,
+    inverse_scale: T,
+    inverse_first_scale: T,
+}
+
+impl<T: DctNum> Type2And3Pair<T> {
+    /// Creates a new transform pair that will process signals of length `dct.len()`, using `dct` for both directions
+    pub fn new(dct: Arc<dyn TransformType2And3<T>>, normalization: Normalization) -> Self {
+        let len = dct.len() as f64;
+
+        // DCT3(DCT2(x)) == x * len / 2, so the two directions' scales need to multiply to 2 / len.
+        // For the orthonormal version, the first element also needs an extra factor of sqrt(1/2) going forward and sqrt(2) going backward
+        let (forward_scale, forward_first_scale, inverse_scale, inverse_first_scale) =
+            match normalization {
+                Normalization::Unnormalized => (1.0, 1.0, 1.0, 1.0),
+                Normalization::Inverse => (1.0, 1.0, 2.0 / len, 2.0 / len),
+                Normalization::Orthonormal => {
+                    let scale = (2.0 / len).sqrt();
+                    (scale, (1.0 / len).sqrt(), scale, 2.0 / len.sqrt())
+                }
+            };
+
+        Self {
+            dct,
+            normalization,
+            forward_scale: T::from_f64(forward_scale).unwrap(),
+            forward_first_scale: T::from_f64(forward_first_scale).unwrap(),
+            inverse_scale: T::from_f64(inverse_scale).unwrap(),
+            inverse_first_scale: T::from_f64(inverse_first_scale).unwrap(),
+        }
+    }
+
+    /// Returns the normalization used by this pair
+    pub fn normalization(&self) -> Normalization {
+        self.normalization
+    }
+
+    /// Returns the DCT instance used for both directions
+    pub fn inner(&self) -> &Arc<dyn TransformType2And3<T>> {
+        &self.dct
+    }
+
+    /// Computes the forward transform (a DCT Type 2) on the provided buffer, in-place, and applies the forward scaling.
+    ///
+    /// This method allocates a Vec<T> of scratch space. If you'd like to reuse that allocation between
+    /// multiple computations, consider calling `forward_with_scratch` instead.
+    pub fn forward(&self, buffer: &mut [T]) {
+        let mut scratch = vec![T::zero(); self.get_scratch_len()];
+        self.forward_with_scratch(buffer, &mut scratch);
+    }
+
+    /// Computes the forward transform (a DCT Type 2) on the provided buffer, in-place, and applies the forward scaling.
+    /// Uses the provided `scratch` buffer as scratch space.
+    pub fn forward_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        self.dct.process_dct2_with_scratch(buffer, scratch);
+
+        if self.normalization == Normalization::Orthonormal {
+            Self::scale(buffer, self.forward_first_scale, self.forward_scale);
+        }
+    }
+
+    /// Computes the inverse transform (a DCT Type 3) on the provided buffer, in-place, and applies the inverse scaling.
+    ///
+    /// This method allocates a Vec<T> of scratch space. If you'd like to reuse that allocation between
+    /// multiple computations, consider calling `inverse_with_scratch` instead.
+    pub fn inverse(&self, buffer: &mut [T]) {
+        let mut scratch = vec![T::zero(); self.get_scratch_len()];
+        self.inverse_with_scratch(buffer, &mut scratch);
+    }
+
+    /// Computes the inverse transform (a DCT Type 3) on the provided buffer, in-place, and applies the inverse scaling.
+    /// Uses the provided `scratch` buffer as scratch space.
+    pub fn inverse_with_scratch(&self, buffer: &mut [T], scratch: &mut [T]) {
+        // The orthonormal scaling has to be applied to the DCT3's input, since the first element is scaled differently
+        match self.normalization {
+            Normalization::Unnormalized => self.dct.process_dct3_with_scratch(buffer, scratch),
+            // Every output is scaled the same way, so the DCT3 can scale its outputs while it writes them
+            Normalization::Inverse => {
+                self.dct
+                    .process_dct3_scaled(buffer, scratch, self.inverse_scale)
+            }
+            Normalization::Orthonormal => {
+                Self::scale(buffer, self.inverse_first_scale, self.inverse_scale);
+                self.dct.process_dct3_with_scratch(buffer, scratch);
+            }
+        }
+    }
+
+    /// Filters the provided buffer in the DCT domain, in-place: Computes the forward transform, passes each
+    /// coefficient to `filter` along with its index, and computes the inverse transform of the returned coefficients.
+    /// Uses the provided `scratch` buffer as scratch space.
+    ///
+    /// The coefficients passed to `filter` are scaled the same way as the output of `forward`. This is equivalent
+    /// to calling `forward`, modifying each coefficient, and calling `inverse`, but the normalization and the
+    /// filter are applied in a single pass over the data.
+    pub fn process_filtered(
+        &self,
+        buffer: &mut [T],
+        scratch: &mut [T],
+        mut filter: impl FnMut(usize, T) -> T,
+    ) {
+        self.dct.process_dct2_with_scratch(buffer, scratch);
+
+        // The DCT3 is linear, so the Inverse normalization's output scale can be applied to its input instead
+        let (forward_first_scale, forward_scale) = match self.normalization {
+            Normalization::Orthonormal => (self.forward_first_scale, self.forward_scale),
+            _ => (T::one(), T::one()),
+        };
+        let (inverse_first_scale, inverse_scale) = match self.normalization {
+            Normalization::Unnormalized => (T::one(), T::one()),
+            _ => (self.inverse_first_scale, self.inverse_scale),
+        };
+
+        for (i, element) in buffer.iter_mut().enumerate() {
+            let (forward, inverse) = if i == 0 {
+                (forward_first_scale, inverse_first_scale)
+            } else {
+                (forward_scale, inverse_scale)
+            };
+            *element = filter(i, *element * forward) * inverse;
+        }
+
+        self.dct.process_dct3_with_scratch(buffer, scratch);
+    }
+
+    fn scale(buffer: &mut [T], first_scale: T, scale: T) {
+        if let Some((first, rest)) = buffer.split_first_mut() {
+            *first = *first * first_scale;
+            for element in rest {
+                *element = *element * scale;
+            }
+        }
+    }
+}
+impl<T> Length for Type2And3Pair<T> {
+    fn len(&self) -> usize {
+        self.dct.len()
+    }
+}
+impl<T: DctNum> MemoryUsage for Type2And3Pair<T> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            inner_plans_bytes: visitor.inner_plan(&self.dct),
+            ..PlanMemory::default()
+        }
+    }
+}
+impl<T> RequiredScratch for Type2And3Pair<T> {
+    fn get_scratch_len(&self) -> usize {
+        self.dct.get_scratch_len()
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        self.dct.get_complex_scratch_len()
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        self.dct.scratch_layout()
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::algorithm::Type2And3Naive;
+    use crate::test_utils::{compare_float_vectors, fuzzy_cmp, random_signal};
+    use crate::Dct2;
+
+    #[test]
+    fn test_pair_unnormalized() {
+        let len = 11;
+        let pair = Type2And3Pair::new(
+            Arc::new(Type2And3Naive::new(len)),
+            Normalization::Unnormalized,
+        );
+
+        let input = random_signal(len);
+        let mut expected = input.clone();
+        Type2And3Naive::new(len).process_dct2(&mut expected);
+
+        let mut actual = input.clone();
+        pair.forward(&mut actual);
+        assert!(compare_float_vectors(&expected, &actual));
+
+        pair.inverse(&mut actual);
+        let expected: Vec<f32> = input.iter().map(|x| x * len as f32 / 2.0).collect();
+        assert!(compare_float_vectors(&expected, &actual));
+    }
+
+    #[test]
+    fn test_pair_roundtrip() {
+        for &normalization in &[Normalization::Inverse, Normalization::Orthonormal] {
+            for len in 1..20 {
+                let pair = Type2And3Pair::new(Arc::new(Type2And3Naive::new(len)), normalization);
+
+                let input = random_signal(len);
+                let mut buffer = input.clone();
+                pair.forward(&mut buffer);
+                pair.inverse(&mut buffer);
+
+                assert!(
+                    compare_float_vectors(&input, &buffer),
+                    "normalization = {:?}, len = {}",
+                    normalization,
+                    len
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_pair_orthonormal_preserves_energy() {
+        let len = 16;
+        let pair = Type2And3Pair::new(
+            Arc::new(Type2And3Naive::new(len)),
+            Normalization::Orthonormal,
+        );
+
+        let input = random_signal(len);
+        let mut output = input.clone();
+        pair.forward(&mut output);
+
+        let input_energy: f32 = input.iter().map(|x| x * x).sum();
+        let output_energy: f32 = output.iter().map(|x| x * x).sum();
+        assert!(fuzzy_cmp(input_energy / output_energy, 1.0, 0.0001));
+    }
+
+    #[test]
+    fn test_pair_process_filtered() {
+        let len = 13;
+        let filter = |i: usize, coefficient: f32| {
+            if i < 5 {
+                coefficient * (i as f32 + 1.0)
+            } else {
+                0.0
+            }
+        };
+
+        for &normalization in &[
+            Normalization::Unnormalized,
+            Normalization::Inverse,
+            Normalization::Orthonormal,
+        ] {
+            let pair = Type2And3Pair::new(Arc::new(Type2And3Naive::new(len)), normalization);
+
+            let input = random_signal(len);
+            let mut expected = input.clone();
+            pair.forward(&mut expected);
+            for (i, element) in expected.iter_mut().enumerate() {
+                *element = filter(i, *element);
+            }
+            pair.inverse(&mut expected);
+
+            let mut actual = input.clone();
+            let mut scratch = vec![0f32; pair.get_scratch_len()];
+            pair.process_filtered(&mut actual, &mut scratch, filter);
+
+            assert!(
+                compare_float_vectors(&expected, &actual),
+                "normalization = {:?}",
+                normalization
+            );
+        }
+    }
+}
@@ -0,0 +1,362 @@
+//! Helpers for computing the same transform on many consecutive signals stored in a single buffer, in parallel.
+//!
+//! # Thread safety
+//!
+//! Every plan is immutable once it's created: The process methods take `&self`, and only write to the buffers and
+//! scratch that are passed to them. No algorithm has interior mutability, so a single plan, like the `Arc` returned by
+//! the planner, can process any number of signals on any number of threads at the same time, as long as each thread
+//! uses its own buffers and scratch. This is why every transform trait requires `Sync` and `Send`.
+//!
+//! Each `par_process_*` function in this module uses that pattern: It splits `data` into chunks of length `plan.len()`,
+//! and transforms the chunks in-place on rayon's thread pool, sharing the plan between its threads. Each rayon job
+//! allocates its own scratch buffer, so there's no scratch parameter. The outputs are identical to the outputs of the
+//! `for_each_chunk_*` functions in the [`batch`](../batch/index.html) module.
+//!
+//! The `par_process_*_rows` functions transform each row of a row-major matrix instead, and take their scratch from a
+//! single buffer provided by the caller, which is split into one slice per rayon job. This avoids allocating in the
+//! parallel loop, and lets the caller choose how many jobs to split the rows into.
+//!
+//! This module is only available when the `rayon` feature is enabled.
+//!
+//! ~~~
+//! // Computes a DCT Type 2 of size 256 on each of 1000 consecutive signals, in parallel
+//! use rustdct::parallel::par_process_dct2;
+//! use rustdct::DctPlanner;
+//!
+//! let mut planner = DctPlanner::new();
+//! let dct = planner.plan_dct2(256);
+//!
+//! let mut data = vec![0f32; 256 * 1000];
+//! par_process_dct2(dct.as_ref(), &mut data);
+//! ~~~
+//!
+//! ~~~
+//! // Computes a DCT Type 2 on each row of a 1000x256 matrix, with one slice of scratch for each thread
+//! use rustdct::parallel::par_process_dct2_rows;
+//! use rustdct::{DctPlanner, RequiredScratch};
+//!
+//! let mut planner = DctPlanner::new();
+//! let dct = planner.plan_dct2(256);
+//!
+//! let mut matrix = vec![0f32; 256 * 1000];
+//! let mut scratch = vec![0f32; dct.get_scratch_len() * rayon::current_num_threads()];
+//! par_process_dct2_rows(dct.as_ref(), &mut matrix, 256, &mut scratch);
+//! ~~~
+
+use rayon::prelude::*;
+
+use crate::batch::validate_row_len;
+use crate::{Dct1, Dct2, Dct3, Dct4, Dct5, Dct6, Dct7, Dct8, DctNum};
+use crate::{Dst1, Dst2, Dst3, Dst4, Dst5, Dst6, Dst7, Dst8};
+
+// Verifies that `data` contains a whole number of chunks
+fn validate_chunks<T>(data: &[T], chunk_len: usize) {
+    assert!(
+        chunk_len > 0 && data.chunks_exact(chunk_len).remainder().is_empty(),
+        "Provided data must be a multiple of the transform size. Transform len = {}, got data len = {}",
+        chunk_len,
+        data.len()
+    );
+}
+
+// Returns the number of elements in each group of rows processed by one rayon job, which is a whole number of rows.
+// There's one group for each slice of scratch, or for each thread if the plan doesn't need scratch
+fn row_group_len(
+    matrix_len: usize,
+    row_len: usize,
+    scratch_len: usize,
+    plan_scratch_len: usize,
+) -> usize {
+    assert!(
+        scratch_len >= plan_scratch_len,
+        "Not enough scratch space was provided. Expected scratch len >= {}, got scratch len = {}",
+        plan_scratch_len,
+        scratch_len
+    );
+    let num_groups = scratch_len
+        .checked_div(plan_scratch_len)
+        .unwrap_or_else(rayon::current_num_threads);
+    let num_rows = matrix_len / row_len;
+    let rows_per_group = (num_rows + num_groups - 1) / num_groups;
+    rows_per_group.max(1) * row_len
+}
+
+macro_rules! par_process_fn {
+    ($fn_name:ident, $rows_fn_name:ident, $trait_name:ident, $process_fn:ident, $summary:literal, $rows_summary:literal) => {
+        #[doc = $summary]
+        ///
+        /// The chunk size is `plan.len()`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `data.len()` isn't a multiple of `plan.len()`.
+        pub fn $fn_name<T: DctNum, P: $trait_name<T> + ?Sized>(plan: &P, data: &mut [T]) {
+            validate_chunks(data, plan.len());
+
+            let scratch_len = plan.get_scratch_len();
+            data.par_chunks_exact_mut(plan.len()).for_each_init(
+                || vec![T::zero(); scratch_len],
+                |scratch, chunk| plan.$process_fn(chunk, scratch),
+            );
+        }
+
+        #[doc = $rows_summary]
+        ///
+        /// `matrix` is stored in row-major order, with rows of length `row_len`, which must be equal to `plan.len()`.
+        /// `scratch` is split into slices of `plan.get_scratch_len()` elements, and the rows are split into as many
+        /// contiguous groups as there are slices, so that each rayon job reuses its own slice for all of its rows.
+        /// Any remainder of `scratch` is unused. If the plan doesn't need scratch, the rows are split into one group
+        /// per thread of rayon's thread pool.
+        ///
+        /// # Panics
+        ///
+        /// Panics if `row_len` isn't equal to `plan.len()`, if `matrix.len()` isn't a multiple of `row_len`, or if
+        /// `scratch.len()` is less than `plan.get_scratch_len()`.
+        pub fn $rows_fn_name<T: DctNum, P: $trait_name<T> + ?Sized>(
+            plan: &P,
+            matrix: &mut [T],
+            row_len: usize,
+            scratch: &mut [T],
+        ) {
+            validate_row_len(row_len, plan.len());
+            validate_chunks(matrix, row_len);
+
+            let scratch_len = plan.get_scratch_len();
+            let group_len = row_group_len(matrix.len(), row_len, scratch.len(), scratch_len);
+            if matrix.is_empty() {
+                return;
+            }
+            if scratch_len == 0 {
+                matrix.par_chunks_mut(group_len).for_each(|rows| {
+                    for row in rows.chunks_exact_mut(row_len) {
+                        plan.$process_fn(row, &mut []);
+                    }
+                });
+            } else {
+                matrix
+                    .par_chunks_mut(group_len)
+                    .zip(scratch.par_chunks_exact_mut(scratch_len))
+                    .for_each(|(rows, scratch)| {
+                        for row in rows.chunks_exact_mut(row_len) {
+                            plan.$process_fn(row, scratch);
+                        }
+                    });
+            }
+        }
+    };
+}
+
+par_process_fn!(
+    par_process_dct1,
+    par_process_dct1_rows,
+    Dct1,
+    process_dct1_with_scratch,
+    "Computes the DCT Type 1 on each consecutive chunk of `data`, in-place, in parallel.",
+    "Computes the DCT Type 1 on each row of `matrix`, in-place, in parallel, using slices of `scratch` as scratch space."
+);
+par_process_fn!(
+    par_process_dct2,
+    par_process_dct2_rows,
+    Dct2,
+    process_dct2_with_scratch,
+    "Computes the DCT Type 2 on each consecutive chunk of `data`, in-place, in parallel.",
+    "Computes the DCT Type 2 on each row of `matrix`, in-place, in parallel, using slices of `scratch` as scratch space."
+);
+par_process_fn!(
+    par_process_dct3,
+    par_process_dct3_rows,
+    Dct3,
+    process_dct3_with_scratch,
+    "Computes the DCT Type 3 on each consecutive chunk of `data`, in-place, in parallel.",
+    "Computes the DCT Type 3 on each row of `matrix`, in-place, in parallel, using slices of `scratch` as scratch space."
+);
+par_process_fn!(
+    par_process_dct4,
+    par_process_dct4_rows,
+    Dct4,
+    process_dct4_with_scratch,
+    "Computes the DCT Type 4 on each consecutive chunk of `data`, in-place, in parallel.",
+    "Computes the DCT Type 4 on each row of `matrix`, in-place, in parallel, using slices of `scratch` as scratch space."
+);
+par_process_fn!(
+    par_process_dct5,
+    par_process_dct5_rows,
+    Dct5,
+    process_dct5_with_scratch,
+    "Computes the DCT Type 5 on each consecutive chunk of `data`, in-place, in parallel.",
+    "Computes the DCT Type 5 on each row of `matrix`, in-place, in parallel, using slices of `scratch` as scratch space."
+);
+par_process_fn!(
+    par_process_dct6,
+    par_process_dct6_rows,
+    Dct6,
+    process_dct6_with_scratch,
+    "Computes the DCT Type 6 on each consecutive chunk of `data`, in-place, in parallel.",
+    "Computes the DCT Type 6 on each row of `matrix`, in-place, in parallel, using slices of `scratch` as scratch space."
+);
+par_process_fn!(
+    par_process_dct7,
+    par_process_dct7_rows,
+    Dct7,
+    process_dct7_with_scratch,
+    "Computes the DCT Type 7 on each consecutive chunk of `data`, in-place, in parallel.",
+    "Computes the DCT Type 7 on each row of `matrix`, in-place, in parallel, using slices of `scratch` as scratch space."
+);
+par_process_fn!(
+    par_process_dct8,
+    par_process_dct8_rows,
+    Dct8,
+    process_dct8_with_scratch,
+    "Computes the DCT Type 8 on each consecutive chunk of `data`, in-place, in parallel.",
+    "Computes the DCT Type 8 on each row of `matrix`, in-place, in parallel, using slices of `scratch` as scratch space."
+);
+
+par_process_fn!(
+    par_process_dst1,
+    par_process_dst1_rows,
+    Dst1,
+    process_dst1_with_scratch,
+    "Computes the DST Type 1 on each consecutive chunk of `data`, in-place, in parallel.",
+    "Computes the DST Type 1 on each row of `matrix`, in-place, in parallel, using slices of `scratch` as scratch space."
+);
+par_process_fn!(
+    par_process_dst2,
+    par_process_dst2_rows,
+    Dst2,
+    process_dst2_with_scratch,
+    "Computes the DST Type 2 on each consecutive chunk of `data`, in-place, in parallel.",
+    "Computes the DST Type 2 on each row of `matrix`, in-place, in parallel, using slices of `scratch` as scratch space."
+);
+par_process_fn!(
+    par_process_dst3,
+    par_process_dst3_rows,
+    Dst3,
+    process_dst3_with_scratch,
+    "Computes the DST Type 3 on each consecutive chunk of `data`, in-place, in parallel.",
+    "Computes the DST Type 3 on each row of `matrix`, in-place, in parallel, using slices of `scratch` as scratch space."
+);
+par_process_fn!(
+    par_process_dst4,
+    par_process_dst4_rows,
+    Dst4,
+    process_dst4_with_scratch,
+    "Computes the DST Type 4 on each consecutive chunk of `data`, in-place, in parallel.",
+    "Computes the DST Type 4 on each row of `matrix`, in-place, in parallel, using slices of `scratch` as scratch space."
+);
+par_process_fn!(
+    par_process_dst5,
+    par_process_dst5_rows,
+    Dst5,
+    process_dst5_with_scratch,
+    "Computes the DST Type 5 on each consecutive chunk of `data`, in-place, in parallel.",
+    "Computes the DST Type 5 on each row of `matrix`, in-place, in parallel, using slices of `scratch` as scratch space."
+);
+par_process_fn!(
+    par_process_dst6,
+    par_process_dst6_rows,
+    Dst6,
+    process_dst6_with_scratch,
+    "Computes the DST Type 6 on each consecutive chunk of `data`, in-place, in parallel.",
+    "Computes the DST Type 6 on each row of `matrix`, in-place, in parallel, using slices of `scratch` as scratch space."
+);
+par_process_fn!(
+    par_process_dst7,
+    par_process_dst7_rows,
+    Dst7,
+    process_dst7_with_scratch,
+    "Computes the DST Type 7 on each consecutive chunk of `data`, in-place, in parallel.",
+    "Computes the DST Type 7 on each row of `matrix`, in-place, in parallel, using slices of `scratch` as scratch space."
+);
+par_process_fn!(
+    par_process_dst8,
+    par_process_dst8_rows,
+    Dst8,
+    process_dst8_with_scratch,
+    "Computes the DST Type 8 on each consecutive chunk of `data`, in-place, in parallel.",
+    "Computes the DST Type 8 on each row of `matrix`, in-place, in parallel, using slices of `scratch` as scratch space."
+);
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    use crate::batch::{for_each_chunk_dct2, for_each_chunk_dct4, for_each_chunk_dst3};
+    use crate::test_utils::random_signal;
+    use crate::{DctPlanner, RequiredScratch};
+
+    /// Verify that the parallel functions produce exactly the same outputs as the sequential batch functions
+    #[test]
+    fn test_par_process_matches_batch() {
+        let mut planner = DctPlanner::new();
+        for &len in [1, 4, 17, 64, 100].iter() {
+            let data: Vec<f32> = random_signal(len * 200);
+
+            let dct2 = planner.plan_dct2(len);
+            let mut expected = data.clone();
+            let mut scratch = vec![0f32; dct2.get_scratch_len()];
+            for_each_chunk_dct2(dct2.as_ref(), &mut expected, &mut scratch);
+            let mut actual = data.clone();
+            par_process_dct2(dct2.as_ref(), &mut actual);
+            assert_eq!(expected, actual, "dct2 len = {}", len);
+
+            let mut expected = data.clone();
+            for_each_chunk_dst3(dct2.as_ref(), &mut expected, &mut scratch);
+            let mut actual = data.clone();
+            par_process_dst3(dct2.as_ref(), &mut actual);
+            assert_eq!(expected, actual, "dst3 len = {}", len);
+
+            let dct4 = planner.plan_dct4(len);
+            let mut expected = data.clone();
+            let mut scratch = vec![0f32; dct4.get_scratch_len()];
+            for_each_chunk_dct4(dct4.as_ref(), &mut expected, &mut scratch);
+            let mut actual = data;
+            par_process_dct4(dct4.as_ref(), &mut actual);
+            assert_eq!(expected, actual, "dct4 len = {}", len);
+        }
+    }
+
+    /// Verify that the parallel row functions produce exactly the same outputs as the sequential batch functions, for
+    /// any number of scratch slices
+    #[test]
+    fn test_par_process_rows_matches_batch() {
+        let mut planner = DctPlanner::new();
+        for &len in [4, 17, 100].iter() {
+            let data: Vec<f32> = random_signal(len * 50);
+
+            let dct2 = planner.plan_dct2(len);
+            let scratch_len = dct2.get_scratch_len();
+            let mut expected = data.clone();
+            let mut scratch = vec![0f32; scratch_len];
+            for_each_chunk_dct2(dct2.as_ref(), &mut expected, &mut scratch);
+
+            for &num_slices in [1, 3, 8, 64].iter() {
+                let mut actual = data.clone();
+                let mut scratch = vec![0f32; scratch_len * num_slices + 1];
+                par_process_dct2_rows(dct2.as_ref(), &mut actual, len, &mut scratch);
+                assert_eq!(expected, actual, "len = {}, slices = {}", len, num_slices);
+            }
+        }
+
+        let dct2 = planner.plan_dct2(8);
+        let mut empty: Vec<f32> = Vec::new();
+        par_process_dct2_rows(dct2.as_ref(), &mut empty, 8, &mut []);
+    }
+
+    #[test]
+    #[should_panic(expected = "Not enough scratch space was provided")]
+    fn test_par_process_rows_small_scratch() {
+        let mut planner = DctPlanner::new();
+        let dct = planner.plan_dct4(100);
+        let mut data = vec![0f32; 400];
+        par_process_dct4_rows(dct.as_ref(), &mut data, 100, &mut []);
+    }
+
+    #[test]
+    #[should_panic(expected = "Provided data must be a multiple of the transform size")]
+    fn test_par_process_wrong_len() {
+        let mut planner = DctPlanner::new();
+        let dct = planner.plan_dct2(8);
+        let mut data = vec![0f32; 12];
+        par_process_dct2(dct.as_ref(), &mut data);
+    }
+}
@@ -0,0 +1,437 @@
+//! Pipelines that combine several steps around a planned transform into a single reusable type.
+//!
+//! ~~~
+//! // Computes the DCT2 spectrogram of a signal of length 10000, with frames of length 256 and a hop of 128
+//! use rustdct::mdct::window_fn;
+//! use rustdct::pipeline::CosineSpectrogram;
+//!
+//! let spectrogram = CosineSpectrogram::new(256, 128, window_fn::mp3);
+//!
+//! let signal = vec![0f32; 10000];
+//! let frames = spectrogram.process(&signal);
+//! assert_eq!(frames.len(), spectrogram.num_frames(signal.len()));
+//! assert_eq!(frames[0].len(), 256);
+//! ~~~
+
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::{DctNum, DctPlanner, TransformType2And3};
+
+/// Describes why a window and hop failed [`verify_cola`](fn.verify_cola.html).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ColaError<T> {
+    /// The overlap-added windows summed to zero, so a signal can't be reconstructed from its windowed frames at any
+    /// scale. For example, this happens if the window is all zeroes.
+    NoReconstruction,
+    /// The overlap-added windows weren't constant: The sum at `index` differed from the average sum by more than the
+    /// tolerance.
+    Mismatch {
+        /// The index of the first sample whose sum differed, counted from the start of a frame. This is always less
+        /// than the hop.
+        index: usize,
+        /// The average of the overlap-added windows, over every index
+        expected: T,
+        /// The sum of the overlap-added windows at `index`
+        actual: T,
+    },
+}
+
+impl<T: fmt::Debug> fmt::Display for ColaError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ColaError::NoReconstruction => write!(f, "The overlap-added windows sum to zero"),
+            ColaError::Mismatch {
+                index,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "The overlap-added windows aren't constant: expected {:?}, got {:?} at index {}",
+                expected, actual, index
+            ),
+        }
+    }
+}
+impl<T: fmt::Debug> Error for ColaError<T> {}
+
+/// Checks that `window` satisfies the constant overlap-add (COLA) condition with a hop of `hop`: Copies of the window
+/// that start every `hop` samples sum to the same constant at every sample, so overlap-adding windowed frames
+/// reconstructs the signal, multiplied by that constant.
+///
+/// Returns the constant if every sum is within `tolerance` of the average sum. Otherwise, returns the first sum that
+/// isn't. For example, a periodic Hann window is COLA with a hop of half its length, and its constant is 1. The sine
+/// window `window_fn::mp3` isn't COLA at that hop, since it's designed to be applied twice, so only its square is.
+///
+/// ~~~
+/// // Checks that the Hann window is COLA at 50% overlap, and that the sine window isn't
+/// use rustdct::mdct::window_fn;
+/// use rustdct::pipeline::verify_cola;
+///
+/// let hann: Vec<f64> = (0..64)
+///     .map(|i| 0.5 - 0.5 * (2.0 * std::f64::consts::PI * i as f64 / 64.0).cos())
+///     .collect();
+/// let gain = verify_cola(&hann, 32, 1e-10).unwrap();
+/// assert!((gain - 1.0).abs() < 1e-10);
+///
+/// assert!(verify_cola(&window_fn::mp3::<f64>(64), 32, 1e-4).is_err());
+/// ~~~
+///
+/// # Panics
+///
+/// Panics if `hop` is 0 or greater than `window.len()`.
+pub fn verify_cola<T: DctNum>(window: &[T], hop: usize, tolerance: T) -> Result<T, ColaError<T>> {
+    assert!(
+        hop > 0 && hop <= window.len(),
+        "The hop must be between 1 and the window length. Got hop = {}, window length = {}",
+        hop,
+        window.len()
+    );
+
+    // every sample has the same sum as the sample `hop` samples earlier, so one hop's worth of sums covers every sample
+    let sums: Vec<T> = (0..hop)
+        .map(|index| {
+            window[index..]
+                .iter()
+                .step_by(hop)
+                .fold(T::zero(), |sum, &value| sum + value)
+        })
+        .collect();
+    let expected =
+        sums.iter().fold(T::zero(), |total, &sum| total + sum) / T::from_usize(hop).unwrap();
+    if expected.is_zero() || is_nan(expected) {
+        return Err(ColaError::NoReconstruction);
+    }
+
+    for (index, &actual) in sums.iter().enumerate() {
+        let difference = (expected - actual).abs();
+        if is_nan(difference) || (tolerance - difference).is_negative() {
+            return Err(ColaError::Mismatch {
+                index,
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(expected)
+}
+
+// `DctNum` doesn't provide `is_nan`, but NaN is the only value that isn't equal to itself
+#[allow(clippy::eq_op)]
+fn is_nan<T: DctNum>(value: T) -> bool {
+    value != value
+}
+
+/// Splits a signal into overlapping frames, multiplies each frame by a window, and computes the DCT Type 2 of each
+/// windowed frame.
+///
+/// Frames start every `hop` samples, beginning at the first sample. The last frame is padded with zeroes if it extends
+/// past the end of the signal, so that every sample is part of at least one frame.
+///
+/// `process` takes `&self`, so one spectrogram can be shared between threads.
+pub struct CosineSpectrogram<T> {
+    dct: Arc<dyn TransformType2And3<T>>,
+    hop: usize,
+    window: Box<[T]>,
+}
+
+impl<T: DctNum> CosineSpectrogram<T> {
+    /// Creates a new spectrogram with frames of length `frame_len`, computed every `hop` samples.
+    ///
+    /// `window_fn` is a function that takes a `size` and returns a `Vec` containing `size` window values. Each frame is
+    /// multiplied by the window before its DCT2 is computed. The functions in the
+    /// [`window_fn`](../mdct/window_fn/index.html) module can be used here too: `window_fn::mp3` is a sine window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `frame_len` is 0, if `hop` is 0 or greater than `frame_len`, or if `window_fn` returns the wrong number
+    /// of window values.
+    pub fn new<F>(frame_len: usize, hop: usize, window_fn: F) -> Self
+    where
+        F: FnOnce(usize) -> Vec<T>,
+    {
+        let mut planner = DctPlanner::new();
+        Self::with_dct(planner.plan_dct2(frame_len), hop, window_fn)
+    }
+
+    /// Creates a new spectrogram that computes the DCT2 of each frame with `dct`, so the frame length is `dct.len()`.
+    /// This lets several spectrograms share plans from the same `DctPlanner`.
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`new`](#method.new).
+    pub fn with_dct<F>(dct: Arc<dyn TransformType2And3<T>>, hop: usize, window_fn: F) -> Self
+    where
+        F: FnOnce(usize) -> Vec<T>,
+    {
+        let frame_len = dct.len();
+        assert!(
+            frame_len > 0,
+            "The spectrogram's frame length must be nonzero"
+        );
+        assert!(
+            hop > 0 && hop <= frame_len,
+            "The spectrogram's hop must be between 1 and the frame length. Got hop = {}, frame length = {}",
+            hop,
+            frame_len
+        );
+
+        let window = window_fn(frame_len);
+        assert_eq!(
+            window.len(),
+            frame_len,
+            "Window function returned incorrect number of values. Expected {}, got {}",
+            frame_len,
+            window.len()
+        );
+
+        Self {
+            dct,
+            hop,
+            window: window.into_boxed_slice(),
+        }
+    }
+
+    /// Returns the number of samples in each frame, which is also the number of coefficients in each output frame.
+    pub fn frame_len(&self) -> usize {
+        self.window.len()
+    }
+
+    /// Returns the number of samples between the starts of consecutive frames.
+    pub fn hop(&self) -> usize {
+        self.hop
+    }
+
+    /// Returns the number of frames that `process` computes for a signal of length `signal_len`.
+    pub fn num_frames(&self, signal_len: usize) -> usize {
+        if signal_len == 0 {
+            0
+        } else if signal_len <= self.frame_len() {
+            1
+        } else {
+            // round up, so that the last samples get a zero-padded frame
+            (signal_len - self.frame_len() + self.hop - 1) / self.hop + 1
+        }
+    }
+
+    /// Computes the DCT2 of every windowed frame of `signal`. Returns the coefficients of each frame, in order.
+    pub fn process(&self, signal: &[T]) -> Vec<Vec<T>> {
+        let frame_len = self.frame_len();
+        let mut scratch = vec![T::zero(); self.dct.get_scratch_len()];
+
+        (0..self.num_frames(signal.len()))
+            .map(|frame_index| {
+                let start = frame_index * self.hop;
+                let end = signal.len().min(start + frame_len);
+
+                let mut frame = vec![T::zero(); frame_len];
+                for ((output, input), window) in frame
+                    .iter_mut()
+                    .zip(signal[start..end].iter())
+                    .zip(self.window.iter())
+                {
+                    *output = *input * *window;
+                }
+
+                self.dct.process_dct2_with_scratch(&mut frame, &mut scratch);
+                frame
+            })
+            .collect()
+    }
+
+    /// Checks that this spectrogram's window and hop satisfy the constant overlap-add condition, with
+    /// [`verify_cola`](fn.verify_cola.html). If they do, returns the gain to pass to
+    /// [`overlap_add`](#method.overlap_add).
+    pub fn verify_cola(&self, tolerance: T) -> Result<T, ColaError<T>> {
+        verify_cola(&self.window, self.hop, tolerance)
+    }
+
+    /// Inverts `frames`, as computed by `process`, and overlap-adds them into a signal of length `signal_len`.
+    ///
+    /// Each frame is inverted with a scaled DCT3, which recovers the windowed samples, and then the frames are added
+    /// together at their original positions. If the window and hop are COLA, dividing the sum by the `gain` returned
+    /// from [`verify_cola`](#method.verify_cola) reconstructs the original signal.
+    ///
+    /// The first `frame_len - hop` samples, and the samples after the start of the last frame, are covered by fewer
+    /// frames than the rest of the signal, so they aren't reconstructed. To reconstruct the whole signal, pad it with
+    /// `frame_len - hop` zeroes on both sides before calling `process`.
+    ///
+    /// ~~~
+    /// // Computes the spectrogram of a padded signal with a Hann window, and reconstructs the signal from it
+    /// use rustdct::pipeline::CosineSpectrogram;
+    ///
+    /// let spectrogram = CosineSpectrogram::new(64, 32, |len| {
+    ///     (0..len)
+    ///         .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / len as f32).cos())
+    ///         .collect()
+    /// });
+    /// let gain = spectrogram.verify_cola(1e-4).unwrap();
+    ///
+    /// let signal: Vec<f32> = (0..1000).map(|i| (i as f32 * 0.1).sin()).collect();
+    /// let padding = spectrogram.frame_len() - spectrogram.hop();
+    /// let mut padded = vec![0f32; padding];
+    /// padded.extend_from_slice(&signal);
+    /// padded.resize(signal.len() + 2 * padding, 0f32);
+    ///
+    /// let frames = spectrogram.process(&padded);
+    /// let reconstructed = spectrogram.overlap_add(&frames, padded.len(), gain);
+    /// for (expected, actual) in signal.iter().zip(reconstructed[padding..].iter()) {
+    ///     assert!((expected - actual).abs() < 1e-3);
+    /// }
+    /// ~~~
+    ///
+    /// # Panics
+    ///
+    /// Panics if any frame's length isn't `frame_len()`, or if `frames` has more frames than `num_frames(signal_len)`.
+    pub fn overlap_add(&self, frames: &[Vec<T>], signal_len: usize, gain: T) -> Vec<T> {
+        let frame_len = self.frame_len();
+        assert!(
+            frames.len() <= self.num_frames(signal_len),
+            "Too many frames for a signal of length {}. Expected at most {}, got {}",
+            signal_len,
+            self.num_frames(signal_len),
+            frames.len()
+        );
+
+        // DCT3(DCT2(x)) == x * len / 2, so fold the 2 / len into the gain
+        let scale = T::two() / (T::from_usize(frame_len).unwrap() * gain);
+
+        let mut signal = vec![T::zero(); signal_len];
+        let mut frame = vec![T::zero(); frame_len];
+        let mut scratch = vec![T::zero(); self.dct.get_scratch_len()];
+        for (frame_index, coefficients) in frames.iter().enumerate() {
+            assert_eq!(
+                coefficients.len(),
+                frame_len,
+                "Frame {} has the wrong length. Expected {}, got {}",
+                frame_index,
+                frame_len,
+                coefficients.len()
+            );
+
+            frame.copy_from_slice(coefficients);
+            self.dct.process_dct3_with_scratch(&mut frame, &mut scratch);
+
+            let start = frame_index * self.hop;
+            let end = signal_len.min(start + frame_len);
+            for (output, input) in signal[start..end].iter_mut().zip(frame.iter()) {
+                *output = *output + *input * scale;
+            }
+        }
+        signal
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::algorithm::Type2And3Naive;
+    use crate::mdct::window_fn;
+    use crate::test_utils::{compare_float_vectors, random_signal};
+    use crate::Dct2;
+    use std::f64::consts::PI;
+
+    /// Verify that each frame matches a DCT2 of the windowed, zero-padded samples, for hops that do and don't evenly
+    /// divide the signal
+    #[test]
+    fn test_cosine_spectrogram() {
+        let frame_len = 8;
+        let naive_dct = Type2And3Naive::new(frame_len);
+        let window = window_fn::mp3::<f32>(frame_len);
+
+        for &hop in &[1, 3, 8] {
+            for &signal_len in &[0, 5, 8, 9, 30] {
+                let signal = random_signal(signal_len);
+                let spectrogram = CosineSpectrogram::new(frame_len, hop, window_fn::mp3);
+                let frames = spectrogram.process(&signal);
+                assert_eq!(frames.len(), spectrogram.num_frames(signal_len));
+
+                let mut padded_signal = signal.clone();
+                padded_signal.resize(signal_len + frame_len, 0f32);
+                for (frame_index, actual) in frames.iter().enumerate() {
+                    let start = frame_index * hop;
+                    assert!(start < signal_len);
+
+                    let mut expected: Vec<f32> = padded_signal[start..start + frame_len]
+                        .iter()
+                        .zip(window.iter())
+                        .map(|(sample, window)| sample * window)
+                        .collect();
+                    naive_dct.process_dct2_with_scratch(&mut expected, &mut vec![0f32; frame_len]);
+
+                    assert!(
+                        compare_float_vectors(&expected, actual),
+                        "hop = {}, signal_len = {}, frame = {}",
+                        hop,
+                        signal_len,
+                        frame_index
+                    );
+                }
+
+                // every sample is part of a frame
+                if signal_len > 0 {
+                    let last_start = (frames.len() - 1) * hop;
+                    assert!(last_start + frame_len >= signal_len);
+                }
+            }
+        }
+    }
+
+    /// Verify that common windows are COLA at the hops they're designed for, and not at others
+    #[test]
+    fn test_verify_cola() {
+        for &len in &[4, 16, 50] {
+            let hann: Vec<f64> = (0..len)
+                .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f64 / len as f64).cos())
+                .collect();
+            let gain = verify_cola(&hann, len / 2, 1e-10).unwrap();
+            assert!((gain - 1.0).abs() < 1e-10, "len = {}", len);
+
+            // the square of the sine window is COLA, but the sine window itself isn't
+            let sine = window_fn::mp3::<f64>(len);
+            let sine_squared: Vec<f64> = sine.iter().map(|value| value * value).collect();
+            assert!(verify_cola(&sine_squared, len / 2, 1e-10).is_ok());
+            if len > 4 {
+                assert!(verify_cola(&sine, len / 2, 1e-6).is_err(), "len = {}", len);
+            }
+
+            // a rectangular window is COLA at any hop that divides its length
+            let rectangle = vec![1f64; len];
+            assert_eq!(verify_cola(&rectangle, len, 1e-10), Ok(1.0));
+            assert_eq!(verify_cola(&rectangle, 2, 1e-10), Ok(len as f64 / 2.0));
+            match verify_cola(&rectangle, len - 1, 1e-10) {
+                Err(ColaError::Mismatch { index: 0, .. }) => {}
+                other => panic!("len = {}, got {:?}", len, other),
+            }
+
+            assert_eq!(
+                verify_cola(&vec![0f64; len], len / 2, 1e-10),
+                Err(ColaError::NoReconstruction)
+            );
+        }
+    }
+
+    /// Verify that overlap-adding the frames of a COLA spectrogram reconstructs the fully covered samples
+    #[test]
+    fn test_overlap_add() {
+        let frame_len = 16;
+        let signal = random_signal(100);
+        for &hop in &[4, 8, 16] {
+            let spectrogram = CosineSpectrogram::new(frame_len, hop, |len| vec![1f32; len]);
+            let gain = spectrogram.verify_cola(1e-6).unwrap();
+
+            let frames = spectrogram.process(&signal);
+            let reconstructed = spectrogram.overlap_add(&frames, signal.len(), gain);
+            assert_eq!(reconstructed.len(), signal.len());
+
+            let last_start = (frames.len() - 1) * hop;
+            let covered = frame_len - hop..last_start;
+            assert!(
+                compare_float_vectors(&signal[covered.clone()], &reconstructed[covered]),
+                "hop = {}",
+                hop
+            );
+        }
+    }
+}
@@ -1,17 +1,236 @@
 use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
+use crate::algorithm::type1_butterflies::*;
 use crate::algorithm::type2and3_butterflies::*;
+use crate::algorithm::type4_butterflies::*;
+use crate::algorithm::type5to8_butterflies::*;
 use crate::algorithm::*;
+use crate::backend::{BatchedType2And3, CpuBatchedType2And3};
 use crate::mdct::*;
 use crate::{
-    Dct1, Dct5, Dct6And7, Dct8, Dst1, Dst5, Dst6And7, Dst8, TransformType2And3, TransformType4,
+    Dct1, Dct2, Dct5, Dct6And7, Dct8, Dst1, Dst5, Dst6And7, Dst8, TransformType2And3,
+    TransformType4,
 };
-use rustfft::FftPlanner;
+use rustfft::num_complex::Complex;
+use rustfft::{Fft, FftPlanner, FftPlannerScalar};
 
-use crate::DctNum;
+use crate::common::derived_len;
+use crate::plan_quality::{largest_prime_factor, POOR_NAIVE_LEN, POOR_PRIME_FACTOR};
+use crate::twiddles::{TwiddleCache, TwiddleTable};
+use crate::{
+    BoundaryMode, CodecBlock, Dct3Convention, DctNum, DctTransform, DctType, DstConvention,
+    InversePlan, Normalization, OutputOrder, PlannerCostModel, PlanningStage, ProgressObserver,
+    RequiredScratch, Type2And3Pair,
+};
+use crate::{PlanQuality, PoorPlanError, PoorPlanPolicy, QualityIssue};
+
+/// Returned by the bounded planning methods, like [`DctPlanner::plan_dct2_bounded`](struct.DctPlanner.html#method.plan_dct2_bounded),
+/// when no algorithm for the requested size fits in the requested amount of scratch space.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ScratchLimitError {
+    /// The transform that was requested
+    pub kind: DctType,
+    /// The requested size
+    pub len: usize,
+    /// The largest scratch length that was allowed
+    pub max_scratch_len: usize,
+    /// The smallest scratch length of any algorithm for this size
+    pub min_scratch_len: usize,
+}
+
+impl fmt::Display for ScratchLimitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "No {:?} algorithm of size {} fits in a scratch len of {}. The smallest scratch len for this size is {}",
+            self.kind, self.len, self.max_scratch_len, self.min_scratch_len
+        )
+    }
+}
+impl Error for ScratchLimitError {}
 
 const DCT2_BUTTERFLIES: [usize; 5] = [2, 3, 4, 8, 16];
+const DCT4_BUTTERFLIES: [usize; 4] = [4, 8, 16, 32];
+const MIN_TYPE1_BUTTERFLY: usize = 2;
+const MAX_TYPE1_BUTTERFLY: usize = 16;
+// Types 5 through 8 use their butterflies for every size up to this one, including size 0, regardless of the cost model
+const MAX_TYPE5TO8_BUTTERFLY: usize = 4;
+
+// RustFFT computes sizes whose prime factors are all in this list with its mixed radix algorithms and butterflies.
+// Sizes with a larger prime factor go through Rader's or Bluestein's algorithm, which benchmark several times slower
+const FAST_FFT_FACTORS: [usize; 4] = [2, 3, 5, 7];
+
+// Returns true if `len` is a product of `FAST_FFT_FACTORS`. Returns false for None, which is a length that overflowed
+fn is_fast_fft_len(len: Option<usize>) -> bool {
+    let mut remaining = match len {
+        Some(len) if len > 0 => len,
+        _ => return false,
+    };
+    for &factor in FAST_FFT_FACTORS.iter() {
+        while remaining % factor == 0 {
+            remaining /= factor;
+        }
+    }
+    remaining == 1
+}
+
+// Returns the smallest product of `FAST_FFT_FACTORS` that's at least `min`, or None if it doesn't fit in a usize.
+// Each product of the odd factors is padded with factors of 2, so this only visits O(log(min)^3) candidates
+fn next_fast_fft_len(min: usize) -> Option<usize> {
+    let mut best: Option<usize> = None;
+    let mut power7 = Some(1usize);
+    while let Some(product7) = power7 {
+        let mut power5 = Some(product7);
+        while let Some(product5) = power5 {
+            let mut power3 = Some(product5);
+            while let Some(product3) = power3 {
+                let mut candidate = Some(product3);
+                while let Some(small) = candidate.filter(|&candidate| candidate < min) {
+                    candidate = small.checked_mul(2);
+                }
+                if let Some(candidate) = candidate {
+                    best = Some(best.map_or(candidate, |best| best.min(candidate)));
+                }
+                if product3 >= min {
+                    break;
+                }
+                power3 = product3.checked_mul(3);
+            }
+            if product5 >= min {
+                break;
+            }
+            power5 = product5.checked_mul(5);
+        }
+        if product7 >= min {
+            break;
+        }
+        power7 = product7.checked_mul(7);
+    }
+    best
+}
+
+// The fast algorithms for each transform type reduce a transform of size `len` to FFTs whose sizes have the same odd
+// prime factors as `len * multiplier + offset`. Returns `(multiplier, offset)`, or None for types without a fast algorithm
+fn fast_fft_len_mapping(kind: DctType) -> Option<(usize, isize)> {
+    match kind {
+        DctType::Dct1 => Some((1, -1)),
+        DctType::Dst1 => Some((1, 1)),
+        DctType::Dct2 | DctType::Dct3 | DctType::Dst2 | DctType::Dst3 => Some((1, 0)),
+        DctType::Dct4 | DctType::Dst4 => Some((1, 0)),
+        DctType::Dct5 | DctType::Dst5 => None,
+        DctType::Dct6 | DctType::Dct7 | DctType::Dst8 => Some((2, -1)),
+        DctType::Dct8 | DctType::Dst6 | DctType::Dst7 => Some((2, 1)),
+    }
+}
+
+// Computes `len * multiplier + offset`, or None if it overflows or is negative
+fn mapped_len(len: usize, multiplier: usize, offset: isize) -> Option<usize> {
+    let product = len.checked_mul(multiplier)?;
+    if offset >= 0 {
+        product.checked_add(offset as usize)
+    } else {
+        product.checked_sub((-offset) as usize)
+    }
+}
+
+// Wraps a newly created algorithm instance in an `Arc`, reporting its creation to the planner's progress observer. With
+// the `log` feature enabled, this also emits a debug event describing the instance:
+// `planned!(planner, transform, len, [inner_transform, inner_len,] algorithm)`
+macro_rules! planned {
+    ($planner:expr, $transform:expr, $len:expr, $algorithm:expr) => {
+        planned!(@describe $planner, $transform, $len, None::<(&str, usize)>, $algorithm)
+    };
+    ($planner:expr, $transform:expr, $len:expr, $inner_transform:expr, $inner_len:expr, $algorithm:expr) => {
+        planned!(@describe $planner, $transform, $len, Some(($inner_transform, $inner_len)), $algorithm)
+    };
+    (@describe $planner:expr, $transform:expr, $len:expr, $inner:expr, $algorithm:expr) => {{
+        // evaluate the description before the algorithm, since creating it may consume the inner instances
+        let (transform, len, inner) = ($transform, $len, $inner);
+        let algorithm = crate::progress::observe_stage(
+            $planner.progress_observer.clone(),
+            PlanningStage::TwiddleGeneration { transform, len },
+            || $algorithm,
+        );
+        #[cfg(feature = "log")]
+        crate::plan::log_planned(transform, len, inner, &algorithm);
+        #[cfg(not(feature = "log"))]
+        let _ = inner;
+        Arc::new(algorithm)
+    }};
+}
+
+// Defines `try_plan_*` methods, which check the plan quality of their transform type and size before planning it:
+// `try_plan_methods! { #[doc] try_name => plan_name, transform type, instance type; ... }`
+macro_rules! try_plan_methods {
+    ($($(#[$attr:meta])* $try_name:ident => $plan_name:ident, $kind:ident, $instance:ty;)*) => {
+        $(
+            $(#[$attr])*
+            pub fn $try_name(&mut self, len: usize) -> Result<$instance, PoorPlanError> {
+                self.check_plan_quality(len, DctType::$kind)?;
+                Ok(self.$plan_name(len))
+            }
+        )*
+    };
+}
+
+// Emits a debug event describing a newly planned algorithm instance, so that performance investigations can see which
+// algorithm, inner size, and scratch length the planner chose for each size
+#[cfg(feature = "log")]
+pub(crate) fn log_planned<A: crate::RequiredScratch>(
+    transform: &str,
+    len: usize,
+    inner: Option<(&str, usize)>,
+    algorithm: &A,
+) {
+    // strip the module path and type parameters from the type name. The `log` feature already requires a newer compiler
+    // than the rest of the crate
+    #[allow(clippy::incompatible_msrv)]
+    let type_name = std::any::type_name::<A>();
+    let type_name = type_name.split('<').next().unwrap_or(type_name);
+    let algorithm_name = type_name.rsplit("::").next().unwrap_or(type_name);
+
+    match inner {
+        Some((inner_transform, inner_len)) => log::debug!(
+            "planned {} of size {} with {}, inner {} of size {}, scratch len {}",
+            transform,
+            len,
+            algorithm_name,
+            inner_transform,
+            inner_len,
+            algorithm.get_scratch_len()
+        ),
+        None => log::debug!(
+            "planned {} of size {} with {}, scratch len {}",
+            transform,
+            len,
+            algorithm_name,
+            algorithm.get_scratch_len()
+        ),
+    }
+}
+
+// Plans the FFTs that the DCT algorithms are converted to. Deterministic planners use RustFFT's scalar planner, since the
+// SIMD planner chooses SSE, AVX, or NEON kernels at runtime, which add and multiply in a different order on each
+// architecture
+enum InnerFftPlanner<T: DctNum> {
+    Simd(FftPlanner<T>),
+    Scalar(FftPlannerScalar<T>),
+}
+impl<T: DctNum> InnerFftPlanner<T> {
+    fn plan_fft_forward(&mut self, len: usize) -> Arc<dyn Fft<T>> {
+        match self {
+            InnerFftPlanner::Simd(planner) => planner.plan_fft_forward(len),
+            InnerFftPlanner::Scalar(planner) => planner.plan_fft_forward(len),
+        }
+    }
+}
+
+// The planner settings that a cached MDCT was planned with
+type MdctCacheKey = (usize, WindowMode, WindowScaling, Accumulation);
 
 /// The DCT planner is used to make new DCT algorithm instances.
 ///
@@ -40,29 +259,109 @@ const DCT2_BUTTERFLIES: [usize; 5] = [2, 3, 4, 8, 16];
 ///
 /// Each DCT instance owns `Arc`s to its shared internal data, rather than borrowing it from the planner, so it's
 /// perfectly safe to drop the planner after creating DCT instances.
+///
+/// Some algorithms internally work with lengths that are a multiple of the transform size, like an inner FFT of size
+/// `2 * (len + 1)` for the DST1, or a twiddle table of size `4 * len`. If one of these lengths doesn't fit in a `usize`,
+/// the planner panics with a message describing which length overflowed, before allocating anything for that size.
+///
+/// If a function passed to the planner panics, like the window function passed to `plan_mdct`, the panic propagates
+/// out of the planning method. Instances are only added to the planner's caches once they're fully constructed, so if
+/// the panic is caught, the planner can still be used: The instances it cached before the panic are complete, and the
+/// instance that panicked will be planned from scratch by the next call.
 pub struct DctPlanner<T: DctNum> {
-    fft_planner: FftPlanner<T>,
-
-    dct1_cache: HashMap<usize, Arc<dyn Dct1<T>>>,
-    dst1_cache: HashMap<usize, Arc<dyn Dst1<T>>>,
-    dct23_cache: HashMap<usize, Arc<dyn TransformType2And3<T>>>,
-    dct4_cache: HashMap<usize, Arc<dyn TransformType4<T>>>,
-    dct5_cache: HashMap<usize, Arc<dyn Dct5<T>>>,
-    dst5_cache: HashMap<usize, Arc<dyn Dst5<T>>>,
-    dct6_cache: HashMap<usize, Arc<dyn Dct6And7<T>>>,
-    dst6_cache: HashMap<usize, Arc<dyn Dst6And7<T>>>,
-    dct8_cache: HashMap<usize, Arc<dyn Dct8<T>>>,
-    dst8_cache: HashMap<usize, Arc<dyn Dst8<T>>>,
-
-    mdct_cache: HashMap<usize, Arc<dyn Mdct<T>>>,
+    fft_planner: InnerFftPlanner<T>,
+    cost_model: PlannerCostModel,
+    accumulation: Accumulation,
+    mdct_window_scaling: WindowScaling,
+    progress_observer: Option<Arc<dyn ProgressObserver>>,
+    poor_plan_policy: PoorPlanPolicy,
+    // Set while a request that missed the cache is being planned, so that the inner requests it makes can tell that
+    // they aren't the outermost one
+    planning: Arc<AtomicBool>,
+
+    dct1_cache: HashMap<(usize, Accumulation), Arc<dyn Dct1<T>>>,
+    dst1_cache: HashMap<(usize, Accumulation), Arc<dyn Dst1<T>>>,
+    dct23_cache: HashMap<(usize, Accumulation), Arc<dyn TransformType2And3<T>>>,
+    // DCT3 sizes that use a different instance than the DCT2 of the same size
+    dct3_cache: HashMap<(usize, Accumulation), Arc<dyn TransformType2And3<T>>>,
+    dct4_cache: HashMap<(usize, Accumulation), Arc<dyn TransformType4<T>>>,
+    dct5_cache: HashMap<(usize, Accumulation), Arc<dyn Dct5<T>>>,
+    dst5_cache: HashMap<(usize, Accumulation), Arc<dyn Dst5<T>>>,
+    dct6_cache: HashMap<(usize, Accumulation), Arc<dyn Dct6And7<T>>>,
+    dst6_cache: HashMap<(usize, Accumulation), Arc<dyn Dst6And7<T>>>,
+    dct8_cache: HashMap<(usize, Accumulation), Arc<dyn Dct8<T>>>,
+    dst8_cache: HashMap<(usize, Accumulation), Arc<dyn Dst8<T>>>,
+
+    mdct_cache: HashMap<MdctCacheKey, Arc<dyn Mdct<T>>>,
+
+    // Twiddle tables shared by every instance of the same algorithm and size, like a DCT2 and a reordered DCT2
+    twiddle_cache: TwiddleCache<T>,
+
+    // Only used by DctPlanner<f32>, to plan the f64 inner instances of mixed-precision algorithms
+    mixed_precision_planner: Option<Box<DctPlanner<f64>>>,
+    mixed_precision_cache: HashMap<(usize, Accumulation), Arc<dyn TransformType2And3<T>>>,
+    mixed_precision_mdct_cache: HashMap<(usize, WindowScaling, Accumulation), Arc<dyn Mdct<T>>>,
 }
 impl<T: DctNum> DctPlanner<T> {
     pub fn new() -> Self {
+        Self::with_cost_model(PlannerCostModel::default())
+    }
+
+    /// Creates a new planner that chooses algorithms with `cost_model`, instead of the default cost model. See
+    /// [`PlannerCostModel`](struct.PlannerCostModel.html).
+    ///
+    /// Crossover sizes below the smallest size that their fast algorithm supports are raised to that size, so
+    /// [`cost_model`](#method.cost_model) returns the raised values.
+    pub fn with_cost_model(cost_model: PlannerCostModel) -> Self {
+        Self::with_fft_planner(cost_model, InnerFftPlanner::Simd(FftPlanner::new()))
+    }
+
+    /// Creates a new planner whose instances produce reproducible outputs, for applications like distributed
+    /// computations that compare results from different machines.
+    ///
+    /// Every algorithm in this crate performs a fixed sequence of floating-point additions and multiplications, which
+    /// only depends on its size, so given the same inputs and twiddle factors, it computes the same bits everywhere:
+    /// Rust never fuses a multiplication and an addition into a FMA instruction, and the naive algorithms split their
+    /// sums into partial sums the same way on every platform. The planner chooses algorithms from the transform sizes and the default cost model alone.
+    /// The remaining platform-dependent parts are RustFFT's SIMD kernels, which are chosen at runtime and whose
+    /// operation order differs between SSE, AVX, and NEON, so this planner plans every FFT with RustFFT's scalar
+    /// planner instead, which makes large FFT-based sizes slower than the instances from `new()`.
+    ///
+    /// This isn't a guarantee that the outputs are bit-identical on every platform. The twiddle factors, in this crate
+    /// and in RustFFT, are computed from the `f64` sine and cosine, which come from the platform's math library, and
+    /// Rust doesn't guarantee that every math library rounds them the same way. The FFT algorithms that RustFFT's
+    /// scalar planner chooses can also change between RustFFT releases. So machines that compare outputs should pin the
+    /// same RustFFT version, and can check a target with [`determinism::self_test`](determinism/fn.self_test.html),
+    /// which compares the outputs of a deterministic planner's instances against reference outputs bit for bit.
+    /// Targets without SSE2, like i586, round intermediate `f32` and `f64` results differently, and aren't supported.
+    pub fn new_deterministic() -> Self {
+        Self::with_fft_planner(
+            PlannerCostModel::default(),
+            InnerFftPlanner::Scalar(FftPlannerScalar::new()),
+        )
+    }
+
+    /// Returns true if this planner was created with [`new_deterministic`](#method.new_deterministic).
+    pub fn is_deterministic(&self) -> bool {
+        match self.fft_planner {
+            InnerFftPlanner::Simd(_) => false,
+            InnerFftPlanner::Scalar(_) => true,
+        }
+    }
+
+    fn with_fft_planner(cost_model: PlannerCostModel, fft_planner: InnerFftPlanner<T>) -> Self {
         Self {
-            fft_planner: FftPlanner::new(),
+            fft_planner,
+            cost_model: cost_model.clamped(),
+            accumulation: Accumulation::Simple,
+            mdct_window_scaling: WindowScaling::AsProvided,
+            progress_observer: None,
+            poor_plan_policy: PoorPlanPolicy::Allow,
+            planning: Arc::new(AtomicBool::new(false)),
             dct1_cache: HashMap::new(),
             dst1_cache: HashMap::new(),
             dct23_cache: HashMap::new(),
+            dct3_cache: HashMap::new(),
             dct4_cache: HashMap::new(),
             dct5_cache: HashMap::new(),
             dst5_cache: HashMap::new(),
@@ -71,138 +370,1005 @@ impl<T: DctNum> DctPlanner<T> {
             dct8_cache: HashMap::new(),
             dst8_cache: HashMap::new(),
             mdct_cache: HashMap::new(),
+            twiddle_cache: TwiddleCache::new(),
+            mixed_precision_planner: None,
+            mixed_precision_cache: HashMap::new(),
+            mixed_precision_mdct_cache: HashMap::new(),
+        }
+    }
+
+    /// Sets how the naive algorithms that this planner creates accumulate their sums. See
+    /// [`Accumulation`](algorithm/enum.Accumulation.html).
+    ///
+    /// This only affects instances planned after the call. Instances are cached separately for each accumulation, so
+    /// after switching it, the planning methods return instances that use the new accumulation, including composite
+    /// instances whose inner instances are naive.
+    pub fn set_accumulation(&mut self, accumulation: Accumulation) {
+        self.accumulation = accumulation;
+    }
+
+    /// Returns how the naive algorithms that this planner creates accumulate their sums.
+    pub fn accumulation(&self) -> Accumulation {
+        self.accumulation
+    }
+
+    /// Sets whether this planner rescales the windows of the MDCTs it creates. See
+    /// [`WindowScaling`](mdct/enum.WindowScaling.html).
+    ///
+    /// With `WindowScaling::Orthonormal`, a MDCT and IMDCT round trip with 50% overlap reconstructs its input with a
+    /// gain of 1, so callers don't need to undo a window-dependent scale, and windows can be swapped without any other
+    /// code changes.
+    ///
+    /// This only affects instances planned after the call. MDCTs are cached separately for each window scaling, so
+    /// after switching the scaling, `plan_mdct` returns an instance with the new scaling, not one that was cached with
+    /// the old one.
+    ///
+    /// ~~~
+    /// // The MP3 window normally reconstructs the input multiplied by len / 2, but with orthonormal scaling, it
+    /// // reconstructs the input exactly
+    /// use rustdct::mdct::{verify_tdac, window_fn, WindowScaling};
+    /// use rustdct::DctPlanner;
+    ///
+    /// let mut planner = DctPlanner::new();
+    /// planner.set_mdct_window_scaling(WindowScaling::Orthonormal);
+    /// let mdct = planner.plan_mdct(64, window_fn::mp3);
+    ///
+    /// let gain: f32 = verify_tdac(mdct.as_ref(), 1e-4).unwrap();
+    /// assert!((gain - 1.0).abs() < 1e-4);
+    /// ~~~
+    pub fn set_mdct_window_scaling(&mut self, window_scaling: WindowScaling) {
+        self.mdct_window_scaling = window_scaling;
+    }
+
+    /// Returns whether this planner rescales the windows of the MDCTs it creates.
+    pub fn mdct_window_scaling(&self) -> WindowScaling {
+        self.mdct_window_scaling
+    }
+
+    /// Sets an observer that this planner reports its progress to, while it plans FFTs and generates twiddle factors.
+    /// See [`ProgressObserver`](trait.ProgressObserver.html). Replaces the previous observer, if any.
+    ///
+    /// Planning huge sizes, like a DCT of size 2^24, can take a noticeable amount of time, so applications with a user
+    /// interface can use this to display progress, and long setups can be profiled stage by stage.
+    pub fn set_progress_observer(&mut self, observer: Arc<dyn ProgressObserver>) {
+        if let Some(inner) = self.mixed_precision_planner.as_mut() {
+            inner.set_progress_observer(Arc::clone(&observer));
+        }
+        self.progress_observer = Some(observer);
+    }
+
+    /// Removes the observer set by [`set_progress_observer`](#method.set_progress_observer), if any.
+    pub fn remove_progress_observer(&mut self) {
+        if let Some(inner) = self.mixed_precision_planner.as_mut() {
+            inner.remove_progress_observer();
+        }
+        self.progress_observer = None;
+    }
+
+    /// Returns the cost model that this planner uses to choose algorithms.
+    pub fn cost_model(&self) -> PlannerCostModel {
+        self.cost_model
+    }
+
+    /// Sets what this planner does when it plans a size whose [`plan_quality`](#method.plan_quality) is `Poor`. See
+    /// [`PoorPlanPolicy`](enum.PoorPlanPolicy.html).
+    ///
+    /// The policy is applied when a requested size is planned, not when it's returned from the cache, so it doesn't
+    /// apply to sizes that were planned before it was set. It's only applied to the requested transform, not to the inner
+    /// instances that the planner creates for it. To refuse poor sizes instead of planning them, use the `try_plan_*`
+    /// methods, like [`try_plan_dct2`](#method.try_plan_dct2), which check every request.
+    pub fn set_poor_plan_policy(&mut self, policy: PoorPlanPolicy) {
+        if let Some(inner) = self.mixed_precision_planner.as_mut() {
+            inner.set_poor_plan_policy(policy);
+        }
+        self.poor_plan_policy = policy;
+    }
+
+    /// Returns what this planner does when it plans a size whose plan quality is `Poor`.
+    pub fn poor_plan_policy(&self) -> PoorPlanPolicy {
+        self.poor_plan_policy
+    }
+
+    /// Returns how fast this planner's algorithm for transforms of type `kind` and size `len` is, compared to nearby
+    /// sizes, along with the reason when it isn't one of the fastest.
+    ///
+    /// Sizes that [`is_fast_size`](#method.is_fast_size) accepts, and size 0, are `Good`. Sizes that are converted to
+    /// FFTs with a prime factor larger than 7 are `Acceptable`, unless the prime factor is larger than 2^20, which
+    /// makes them `Poor`: RustFFT computes those with Bluestein's algorithm, which is often 10 times slower than a
+    /// nearby fast size, and needs several times as much scratch. The DCT5 and DST5 only have a naive O(n^2) algorithm
+    /// above their butterflies, which is `Acceptable` up to size 256, and `Poor` above that.
+    ///
+    /// ~~~
+    /// use rustdct::{DctPlanner, DctType, PlanQuality, QualityIssue};
+    ///
+    /// let planner = DctPlanner::<f32>::new();
+    /// assert_eq!(planner.plan_quality(1024, DctType::Dct2), PlanQuality::Good);
+    ///
+    /// let issue = QualityIssue::LargePrimeFactor { fft_len: 1009, prime_factor: 1009 };
+    /// assert_eq!(planner.plan_quality(1009, DctType::Dct2), PlanQuality::Acceptable(issue));
+    ///
+    /// // 2^20 + 7 is prime
+    /// assert!(planner.plan_quality(1_048_583, DctType::Dct2).is_poor());
+    /// ~~~
+    pub fn plan_quality(&self, len: usize, kind: DctType) -> PlanQuality {
+        if len == 0 || self.is_fast_size(len, kind) {
+            return PlanQuality::Good;
+        }
+        match fast_fft_len_mapping(kind) {
+            Some((multiplier, offset)) => {
+                // a size whose FFT length overflows panics while planning, so its quality doesn't matter
+                let fft_len = mapped_len(len, multiplier, offset).unwrap_or(std::usize::MAX);
+                let prime_factor = largest_prime_factor(fft_len);
+                let issue = QualityIssue::LargePrimeFactor {
+                    fft_len,
+                    prime_factor,
+                };
+                if prime_factor > POOR_PRIME_FACTOR {
+                    PlanQuality::Poor(issue)
+                } else {
+                    PlanQuality::Acceptable(issue)
+                }
+            }
+            None if len <= MAX_TYPE5TO8_BUTTERFLY => PlanQuality::Good,
+            None if len <= POOR_NAIVE_LEN => PlanQuality::Acceptable(QualityIssue::NaiveOnly),
+            None => PlanQuality::Poor(QualityIssue::NaiveOnly),
+        }
+    }
+
+    /// Returns the [`plan_quality`](#method.plan_quality) of transforms of type `kind` and size `len`, or an error if
+    /// it's `Poor`. The error includes the size that [`recommended_fast_size_above`](#method.recommended_fast_size_above)
+    /// recommends padding to, so services can check a requested size before planning it.
+    ///
+    /// ~~~
+    /// use rustdct::{DctPlanner, DctType};
+    ///
+    /// let planner = DctPlanner::<f32>::new();
+    /// let error = planner.check_plan_quality(1_048_583, DctType::Dct2).unwrap_err();
+    /// assert_eq!(error.recommended_len, Some(1_049_760));
+    /// ~~~
+    pub fn check_plan_quality(
+        &self,
+        len: usize,
+        kind: DctType,
+    ) -> Result<PlanQuality, PoorPlanError> {
+        match self.plan_quality(len, kind) {
+            PlanQuality::Poor(issue) => Err(PoorPlanError {
+                kind,
+                len,
+                issue,
+                recommended_len: self.recommended_fast_size_above(len, kind),
+            }),
+            quality => Ok(quality),
+        }
+    }
+
+    try_plan_methods! {
+        /// Returns a DCT Type 1 instance which processes signals of size `len`, like [`plan_dct1`](#method.plan_dct1),
+        /// or an error if its [`plan_quality`](#method.plan_quality) is `Poor`.
+        try_plan_dct1 => plan_dct1, Dct1, Arc<dyn Dct1<T>>;
+        /// Returns a DCT Type 2 instance which processes signals of size `len`, like [`plan_dct2`](#method.plan_dct2),
+        /// or an error if its [`plan_quality`](#method.plan_quality) is `Poor`.
+        ///
+        /// Services that would rather fail than run a much slower transform can plan with the `try_plan_*` methods.
+        /// The error includes the size to pad to:
+        ///
+        /// ~~~
+        /// use rustdct::DctPlanner;
+        ///
+        /// let mut planner = DctPlanner::<f32>::new();
+        /// assert!(planner.try_plan_dct2(1009).is_ok());
+        ///
+        /// // 2^20 + 7 is prime
+        /// let error = planner.try_plan_dct2(1_048_583).err().unwrap();
+        /// assert_eq!(error.recommended_len, Some(1_049_760));
+        /// ~~~
+        try_plan_dct2 => plan_dct2, Dct2, Arc<dyn TransformType2And3<T>>;
+        /// Returns a DCT Type 3 instance which processes signals of size `len`, like [`plan_dct3`](#method.plan_dct3),
+        /// or an error if its [`plan_quality`](#method.plan_quality) is `Poor`.
+        try_plan_dct3 => plan_dct3, Dct3, Arc<dyn TransformType2And3<T>>;
+        /// Returns a DCT Type 4 instance which processes signals of size `len`, like [`plan_dct4`](#method.plan_dct4),
+        /// or an error if its [`plan_quality`](#method.plan_quality) is `Poor`.
+        try_plan_dct4 => plan_dct4, Dct4, Arc<dyn TransformType4<T>>;
+        /// Returns a DCT Type 5 instance which processes signals of size `len`, like [`plan_dct5`](#method.plan_dct5),
+        /// or an error if its [`plan_quality`](#method.plan_quality) is `Poor`.
+        try_plan_dct5 => plan_dct5, Dct5, Arc<dyn Dct5<T>>;
+        /// Returns a DCT Type 6 instance which processes signals of size `len`, like [`plan_dct6`](#method.plan_dct6),
+        /// or an error if its [`plan_quality`](#method.plan_quality) is `Poor`.
+        try_plan_dct6 => plan_dct6, Dct6, Arc<dyn Dct6And7<T>>;
+        /// Returns a DCT Type 7 instance which processes signals of size `len`, like [`plan_dct7`](#method.plan_dct7),
+        /// or an error if its [`plan_quality`](#method.plan_quality) is `Poor`.
+        try_plan_dct7 => plan_dct7, Dct7, Arc<dyn Dct6And7<T>>;
+        /// Returns a DCT Type 8 instance which processes signals of size `len`, like [`plan_dct8`](#method.plan_dct8),
+        /// or an error if its [`plan_quality`](#method.plan_quality) is `Poor`.
+        try_plan_dct8 => plan_dct8, Dct8, Arc<dyn Dct8<T>>;
+        /// Returns a DST Type 1 instance which processes signals of size `len`, like [`plan_dst1`](#method.plan_dst1),
+        /// or an error if its [`plan_quality`](#method.plan_quality) is `Poor`.
+        try_plan_dst1 => plan_dst1, Dst1, Arc<dyn Dst1<T>>;
+        /// Returns a DST Type 2 instance which processes signals of size `len`, like [`plan_dst2`](#method.plan_dst2),
+        /// or an error if its [`plan_quality`](#method.plan_quality) is `Poor`.
+        try_plan_dst2 => plan_dst2, Dst2, Arc<dyn TransformType2And3<T>>;
+        /// Returns a DST Type 3 instance which processes signals of size `len`, like [`plan_dst3`](#method.plan_dst3),
+        /// or an error if its [`plan_quality`](#method.plan_quality) is `Poor`.
+        try_plan_dst3 => plan_dst3, Dst3, Arc<dyn TransformType2And3<T>>;
+        /// Returns a DST Type 4 instance which processes signals of size `len`, like [`plan_dst4`](#method.plan_dst4),
+        /// or an error if its [`plan_quality`](#method.plan_quality) is `Poor`.
+        try_plan_dst4 => plan_dst4, Dst4, Arc<dyn TransformType4<T>>;
+        /// Returns a DST Type 5 instance which processes signals of size `len`, like [`plan_dst5`](#method.plan_dst5),
+        /// or an error if its [`plan_quality`](#method.plan_quality) is `Poor`.
+        try_plan_dst5 => plan_dst5, Dst5, Arc<dyn Dst5<T>>;
+        /// Returns a DST Type 6 instance which processes signals of size `len`, like [`plan_dst6`](#method.plan_dst6),
+        /// or an error if its [`plan_quality`](#method.plan_quality) is `Poor`.
+        try_plan_dst6 => plan_dst6, Dst6, Arc<dyn Dst6And7<T>>;
+        /// Returns a DST Type 7 instance which processes signals of size `len`, like [`plan_dst7`](#method.plan_dst7),
+        /// or an error if its [`plan_quality`](#method.plan_quality) is `Poor`.
+        try_plan_dst7 => plan_dst7, Dst7, Arc<dyn Dst6And7<T>>;
+        /// Returns a DST Type 8 instance which processes signals of size `len`, like [`plan_dst8`](#method.plan_dst8),
+        /// or an error if its [`plan_quality`](#method.plan_quality) is `Poor`.
+        try_plan_dst8 => plan_dst8, Dst8, Arc<dyn Dst8<T>>;
+    }
+
+    // Plans a request for transforms of type `kind` and size `len` that missed the cache, then applies the poor plan
+    // policy to it if it's the outermost request. Inner requests and cache hits don't apply it, so each poor size the
+    // caller asks for is reported once, rather than once for every inner transform it's built from
+    fn plan_uncached<R>(
+        &mut self,
+        len: usize,
+        kind: DctType,
+        plan: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        if self.planning.swap(true, Ordering::Relaxed) {
+            return plan(self);
+        }
+        // the guard clears the flag even if planning panics, so that a caught panic doesn't turn later requests into
+        // inner ones
+        let _outermost = OutermostRequest(Arc::clone(&self.planning));
+        let result = plan(self);
+        self.apply_poor_plan_policy(len, kind);
+        result
+    }
+
+    // without the `log` feature, `Allow` is the only policy
+    #[cfg_attr(not(feature = "log"), allow(unused_variables))]
+    fn apply_poor_plan_policy(&self, len: usize, kind: DctType) {
+        match self.poor_plan_policy {
+            PoorPlanPolicy::Allow => {}
+            #[cfg(feature = "log")]
+            PoorPlanPolicy::Warn => {
+                if let PlanQuality::Poor(issue) = self.plan_quality(len, kind) {
+                    log::warn!(
+                        "planned {:?} of size {}, which is much slower than nearby sizes: {}",
+                        kind,
+                        len,
+                        issue
+                    );
+                }
+            }
+        }
+    }
+
+    /// Returns true if this planner has a fast algorithm for transforms of type `kind` and size `len`.
+    ///
+    /// A size is fast if the planner computes it with a butterfly, or with a naive algorithm below the cost model's
+    /// crossover size, where benchmarks show that the naive algorithm is fastest, or by converting it to FFTs whose sizes
+    /// only have prime factors of 2, 3, 5, and 7. Other sizes are converted to FFTs with a larger prime factor, which are
+    /// several times slower than nearby fast sizes. The DCT5 and DST5 only have a naive algorithm, so none of their
+    /// sizes are fast, and neither is size 0.
+    pub fn is_fast_size(&self, len: usize, kind: DctType) -> bool {
+        let (multiplier, offset) = match fast_fft_len_mapping(kind) {
+            Some(mapping) if len > 0 => mapping,
+            _ => return false,
+        };
+        let is_butterfly = match kind {
+            DctType::Dct1 | DctType::Dst1 => {
+                (MIN_TYPE1_BUTTERFLY..=MAX_TYPE1_BUTTERFLY).contains(&len)
+            }
+            // the other butterfly sizes are products of 2 and 3, which are fast FFT lengths anyway
+            _ => false,
+        };
+        is_butterfly
+            || len < self.min_fast_len(len, kind)
+            || is_fast_fft_len(mapped_len(len, multiplier, offset))
+    }
+
+    /// Returns the smallest size that's at least `len`, for which this planner has a fast algorithm for transforms of
+    /// type `kind`, as defined by [`is_fast_size`](#method.is_fast_size). Applications that are free to pad their
+    /// signals can use this to choose the padded size.
+    ///
+    /// Returns None for the DCT5 and DST5, which don't have any fast sizes, and if there's no fast size between `len`
+    /// and `usize::MAX`.
+    ///
+    /// ~~~
+    /// use rustdct::{DctPlanner, DctType};
+    ///
+    /// let planner = DctPlanner::<f32>::new();
+    ///
+    /// // 1009 is prime, and the next product of 2, 3, 5, and 7 is 1024
+    /// assert_eq!(planner.recommended_fast_size_above(1009, DctType::Dct2), Some(1024));
+    /// // the DCT8 of size 1012 is converted to a DCT2 of size 2025 = 3^4 * 5^2
+    /// assert_eq!(planner.recommended_fast_size_above(1009, DctType::Dct8), Some(1012));
+    /// assert_eq!(planner.recommended_fast_size_above(1009, DctType::Dct5), None);
+    /// ~~~
+    pub fn recommended_fast_size_above(&self, len: usize, kind: DctType) -> Option<usize> {
+        let (multiplier, offset) = fast_fft_len_mapping(kind)?;
+        let mut size = len.max(1);
+        loop {
+            if self.is_fast_size(size, kind) {
+                return Some(size);
+            }
+            // skip to the first size whose FFT length reaches the next fast FFT length. that FFT length may not map back
+            // to a whole size, like an even length for a DCT8, so check the skipped-to size like any other
+            let fft_len = mapped_len(size, multiplier, offset)?;
+            let next_fft_len = next_fast_fft_len(fft_len.checked_add(1)?)?;
+            let next_size_times_multiplier = mapped_len(next_fft_len, 1, -offset)?;
+            size = next_size_times_multiplier / multiplier
+                + (next_size_times_multiplier % multiplier != 0) as usize;
+        }
+    }
+
+    // Returns the cost model's crossover size for transforms of type `kind` and size `len`: Smaller sizes use a naive
+    // algorithm. Returns 0 for types that don't use a naive algorithm for any size with a fast algorithm
+    fn min_fast_len(&self, len: usize, kind: DctType) -> usize {
+        let model = &self.cost_model;
+        match kind {
+            DctType::Dct1 => model.dct1_min_fast_len,
+            DctType::Dst1 => model.dst1_min_fast_len,
+            DctType::Dct4 | DctType::Dst4 if len % 2 == 0 => model.dct4_even_min_fast_len,
+            DctType::Dct4 | DctType::Dst4 => model.dct4_odd_min_fast_len,
+            DctType::Dct6 | DctType::Dct7 => model.dct6_min_fast_len,
+            DctType::Dct8 => model.dct8_min_fast_len,
+            DctType::Dst6 | DctType::Dst7 => model.dst6_min_fast_len,
+            DctType::Dst8 => model.dst8_min_fast_len,
+            _ => 0,
         }
     }
 
     /// Returns a DCT Type 1 instance which processes signals of size `len`.
     /// If this is called multiple times, it will attempt to re-use internal data between instances
     pub fn plan_dct1(&mut self, len: usize) -> Arc<dyn Dct1<T>> {
-        if self.dct1_cache.contains_key(&len) {
-            Arc::clone(self.dct1_cache.get(&len).unwrap())
+        let key = (len, self.accumulation);
+        if self.dct1_cache.contains_key(&key) {
+            Arc::clone(self.dct1_cache.get(&key).unwrap())
         } else {
-            let result = self.plan_new_dct1(len);
-            self.dct1_cache.insert(len, Arc::clone(&result));
+            let result =
+                self.plan_uncached(len, DctType::Dct1, |planner| planner.plan_new_dct1(len));
+            self.dct1_cache.insert(key, Arc::clone(&result));
             result
         }
     }
 
     fn plan_new_dct1(&mut self, len: usize) -> Arc<dyn Dct1<T>> {
-        //benchmarking shows that below about 10, it's faster to just use the naive DCT1 algorithm
-        if len < 10 {
-            Arc::new(Dct1Naive::new(len))
+        //below the cost model's crossover size, which defaults to 10, it's faster to just use the naive DCT1 algorithm
+        //and the butterflies are faster than both up to their max size
+        if (MIN_TYPE1_BUTTERFLY..=MAX_TYPE1_BUTTERFLY).contains(&len) {
+            self.plan_dct1_butterfly(len)
+        } else if len < 3 || len < self.cost_model.dct1_min_fast_len {
+            // the FFT algorithm needs a non-empty FFT, so the smallest sizes are always naive, whatever the cost model says
+            planned!(
+                self,
+                "DCT1",
+                len,
+                Dct1Naive::new(len).with_accumulation(self.accumulation)
+            )
         } else {
-            let fft = self.fft_planner.plan_fft_forward((len - 1) * 2);
-            Arc::new(Dct1ConvertToFft::new(fft))
+            // Both the even and the odd outputs of the symmetric extension's FFT are real, so they can be computed
+            // together with one complex FFT of half the extension's size
+            let fft_len = len - 1;
+            derived_len(fft_len, 2, 0, "scratch");
+            let fft = self.plan_fft(fft_len);
+            planned!(
+                self,
+                "DCT1",
+                len,
+                "FFT",
+                fft_len,
+                Dct1ConvertToRealFft::new(fft)
+            )
+        }
+    }
+
+    fn plan_dct1_butterfly(&mut self, len: usize) -> Arc<dyn Dct1<T>> {
+        match len {
+            2 => planned!(self, "DCT1", len, Dct1Butterfly2::new()),
+            3 => planned!(self, "DCT1", len, Dct1Butterfly3::new()),
+            4 => planned!(self, "DCT1", len, Dct1Butterfly4::new()),
+            5 => planned!(self, "DCT1", len, Dct1Butterfly5::new()),
+            6 => planned!(self, "DCT1", len, Dct1Butterfly6::new()),
+            7 => planned!(self, "DCT1", len, Dct1Butterfly7::new()),
+            8 => planned!(self, "DCT1", len, Dct1Butterfly8::new()),
+            9 => planned!(self, "DCT1", len, Dct1Butterfly9::new()),
+            10 => planned!(self, "DCT1", len, Dct1Butterfly10::new()),
+            11 => planned!(self, "DCT1", len, Dct1Butterfly11::new()),
+            12 => planned!(self, "DCT1", len, Dct1Butterfly12::new()),
+            13 => planned!(self, "DCT1", len, Dct1Butterfly13::new()),
+            14 => planned!(self, "DCT1", len, Dct1Butterfly14::new()),
+            15 => planned!(self, "DCT1", len, Dct1Butterfly15::new()),
+            16 => planned!(self, "DCT1", len, Dct1Butterfly16::new()),
+            _ => panic!("Invalid butterfly size for DCT1: {}", len),
         }
     }
 
     /// Returns a DCT Type 2 instance which processes signals of size `len`.
     /// If this is called multiple times, it will attempt to re-use internal data between instances
     pub fn plan_dct2(&mut self, len: usize) -> Arc<dyn TransformType2And3<T>> {
-        if self.dct23_cache.contains_key(&len) {
-            Arc::clone(self.dct23_cache.get(&len).unwrap())
+        let key = (len, self.accumulation);
+        if self.dct23_cache.contains_key(&key) {
+            Arc::clone(self.dct23_cache.get(&key).unwrap())
         } else {
-            let result = self.plan_new_dct2(len);
-            self.dct23_cache.insert(len, Arc::clone(&result));
+            let result =
+                self.plan_uncached(len, DctType::Dct2, |planner| planner.plan_new_dct2(len));
+            self.dct23_cache.insert(key, Arc::clone(&result));
             result
         }
     }
 
     fn plan_new_dct2(&mut self, len: usize) -> Arc<dyn TransformType2And3<T>> {
+        // every non-butterfly algorithm needs a twiddle table of size 4 * len. check it before recursively planning smaller sizes
+        derived_len(len, 4, 0, "twiddle table");
+
         if DCT2_BUTTERFLIES.contains(&len) {
             self.plan_dct2_butterfly(len)
         } else if len.is_power_of_two() && len > 2 {
             let half_dct = self.plan_dct2(len / 2);
             let quarter_dct = self.plan_dct2(len / 4);
-            Arc::new(Type2And3SplitRadix::new(half_dct, quarter_dct))
+            planned!(
+                self,
+                "DCT2",
+                len,
+                "DCT2",
+                len / 2,
+                self.split_radix_with_shared_twiddles(half_dct, quarter_dct)
+            )
+        } else if len > 1 && len % 2 == 1 && len / 2 < MAX_TYPE5TO8_BUTTERFLY {
+            // Benchmarking shows that for the odd sizes whose DCT6 and DCT8 halves are butterflies, splitting into them
+            // is faster than converting to a FFT. With larger halves, converting to a FFT is faster
+            let dct6 = self.plan_dct6(len / 2 + 1);
+            let dct8 = self.plan_dct8(len / 2);
+            planned!(
+                self,
+                "DCT2",
+                len,
+                "DCT6",
+                len / 2 + 1,
+                Type2And3ConvertToType6And8::new(dct6, dct8)
+            )
         } else {
-            // Benchmarking shows that it's always faster
-            let fft = self.fft_planner.plan_fft_forward(len);
-            Arc::new(Type2And3ConvertToFft::new(fft))
+            // Benchmarking shows that it's always faster for the remaining sizes
+            self.plan_dct2_convert_to_fft(len)
         }
     }
 
+    fn plan_dct2_convert_to_fft(
+        &mut self,
+        len: usize,
+    ) -> Arc<Type2And3ConvertToFft<T, Arc<[Complex<T>]>>> {
+        let fft = self.plan_fft(len);
+        planned!(
+            self,
+            "DCT2",
+            len,
+            "FFT",
+            len,
+            self.convert_to_fft_with_shared_twiddles(fft)
+        )
+    }
+
+    // Creates a `Type2And3SplitRadix` instance that shares its twiddle table with every other split radix instance of
+    // the same size from this planner
+    fn split_radix_with_shared_twiddles(
+        &mut self,
+        half_dct: Arc<dyn TransformType2And3<T>>,
+        quarter_dct: Arc<dyn TransformType2And3<T>>,
+    ) -> Type2And3SplitRadix<T, Arc<[Complex<T>]>> {
+        let len = half_dct.len() * 2;
+        let twiddles =
+            self.twiddle_cache
+                .get_or_compute(TwiddleTable::Type2And3SplitRadix, len, || {
+                    Type2And3SplitRadix::<T>::compute_twiddles(len)
+                });
+        Type2And3SplitRadix::with_shared_twiddles(half_dct, quarter_dct, twiddles)
+    }
+
+    // Creates a `Type2And3ConvertToFft` instance that shares its twiddle table with every other instance of the same
+    // algorithm and size from this planner
+    fn convert_to_fft_with_shared_twiddles(
+        &mut self,
+        fft: Arc<dyn Fft<T>>,
+    ) -> Type2And3ConvertToFft<T, Arc<[Complex<T>]>> {
+        let len = fft.len();
+        let twiddles =
+            self.twiddle_cache
+                .get_or_compute(TwiddleTable::Type2And3ConvertToFft, len, || {
+                    Type2And3ConvertToFft::<T>::compute_twiddles(len)
+                });
+        Type2And3ConvertToFft::with_shared_twiddles(fft, twiddles)
+    }
+
+    // Returns a batched DCT2 and DCT3 instance which processes chunks of size `len`. Sizes that `plan_dct2` computes
+    // with `Type2And3ConvertToFft` use its batched implementation, which computes the FFTs of several chunks with each
+    // call to the inner FFT. Other sizes compute one chunk at a time, with the instance returned by `plan_dct2`
+    pub(crate) fn plan_dct2_batch_cpu(&mut self, len: usize) -> Arc<dyn BatchedType2And3<T>> {
+        // these are the sizes that `plan_new_dct2` doesn't compute with `Type2And3ConvertToFft`
+        let other_algorithm = DCT2_BUTTERFLIES.contains(&len) || (len.is_power_of_two() && len > 2);
+
+        if other_algorithm {
+            Arc::new(CpuBatchedType2And3::new(self.plan_dct2(len)))
+        } else {
+            derived_len(len, 4, 0, "twiddle table");
+            self.plan_dct2_convert_to_fft(len)
+        }
+    }
+
+    /// Returns a DCT Type 2 instance which processes signals of size `len`, using the provided `inner` instance for
+    /// every sub-problem of size `inner.len()`.
+    ///
+    /// This allows a custom DCT2/DCT3 implementation to be used as a building block of the split-radix algorithm:
+    /// The planner builds a split-radix structure down to `inner.len()`, plans the remaining smaller sub-problems
+    /// normally, and uses `inner` for the rest. The returned instance is not cached, and it isn't re-used by
+    /// later calls to `plan_dct2`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` and `inner.len()` aren't powers of two, if `inner.len()` is smaller than 2, or if `len` is
+    /// smaller than `inner.len()`.
+    pub fn plan_dct2_with_inner(
+        &mut self,
+        len: usize,
+        inner: Arc<dyn TransformType2And3<T>>,
+    ) -> Arc<dyn TransformType2And3<T>> {
+        let inner_len = inner.len();
+        // the split radix sub-problems are half and a quarter of each size, so an inner size of 1 would leave
+        // sub-problems of size 0
+        assert!(
+            len.is_power_of_two() && inner_len.is_power_of_two() && inner_len >= 2 && len >= inner_len,
+            "plan_dct2_with_inner requires power-of-two sizes with len >= inner.len() >= 2. Got len = {}, inner.len() = {}",
+            len,
+            inner_len
+        );
+
+        self.plan_split_radix_with_inner(len, &inner)
+    }
+
+    fn plan_split_radix_with_inner(
+        &mut self,
+        len: usize,
+        inner: &Arc<dyn TransformType2And3<T>>,
+    ) -> Arc<dyn TransformType2And3<T>> {
+        if len == inner.len() {
+            Arc::clone(inner)
+        } else if len < inner.len() {
+            self.plan_dct2(len)
+        } else {
+            let half_dct = self.plan_split_radix_with_inner(len / 2, inner);
+            let quarter_dct = self.plan_split_radix_with_inner(len / 4, inner);
+            planned!(
+                self,
+                "DCT2",
+                len,
+                "DCT2",
+                len / 2,
+                self.split_radix_with_shared_twiddles(half_dct, quarter_dct)
+            )
+        }
+    }
+
+    // Plans an inner FFT, reporting it to the progress observer
+    fn plan_fft(&mut self, len: usize) -> Arc<dyn Fft<T>> {
+        let fft_planner = &mut self.fft_planner;
+        crate::progress::observe_stage(
+            self.progress_observer.clone(),
+            PlanningStage::FftPlanning { len },
+            || fft_planner.plan_fft_forward(len),
+        )
+    }
+
     fn plan_dct2_butterfly(&mut self, len: usize) -> Arc<dyn TransformType2And3<T>> {
         match len {
-            2 => Arc::new(Type2And3Butterfly2::new()),
-            3 => Arc::new(Type2And3Butterfly3::new()),
-            4 => Arc::new(Type2And3Butterfly4::new()),
-            8 => Arc::new(Type2And3Butterfly8::new()),
-            16 => Arc::new(Type2And3Butterfly16::new()),
+            2 => planned!(self, "DCT2", len, Type2And3Butterfly2::new()),
+            3 => planned!(self, "DCT2", len, Type2And3Butterfly3::new()),
+            4 => planned!(self, "DCT2", len, Type2And3Butterfly4::new()),
+            8 => planned!(self, "DCT2", len, Type2And3Butterfly8::new()),
+            16 => planned!(self, "DCT2", len, Type2And3Butterfly16::new()),
             _ => panic!("Invalid butterfly size for DCT2: {}", len),
         }
     }
 
     /// Returns DCT Type 3 instance which processes signals of size `len`.
     /// If this is called multiple times, it will attempt to re-use internal data between instances
+    ///
+    /// For most sizes, this is the same instance returned by `plan_dct2(len)`. Even sizes at least as large as the
+    /// cost model's `dct3_min_real_fft_len`, which aren't butterflies or powers of two, are computed with a half-size
+    /// FFT instead, which makes the DCT3 faster but the DCT2 slower. So at those sizes, the DCT3 and DST3 use a
+    /// different instance than the DCT2 and DST2.
     pub fn plan_dct3(&mut self, len: usize) -> Arc<dyn TransformType2And3<T>> {
-        self.plan_dct2(len)
+        if !self.uses_dct3_real_fft(len) {
+            return self.plan_dct2(len);
+        }
+
+        let key = (len, self.accumulation);
+        if self.dct3_cache.contains_key(&key) {
+            Arc::clone(self.dct3_cache.get(&key).unwrap())
+        } else {
+            let result =
+                self.plan_uncached(len, DctType::Dct3, |planner| planner.plan_new_dct3(len));
+            self.dct3_cache.insert(key, Arc::clone(&result));
+            result
+        }
+    }
+
+    fn plan_new_dct3(&mut self, len: usize) -> Arc<dyn TransformType2And3<T>> {
+        derived_len(len, 4, 0, "twiddle table");
+
+        // Packing the real signal into a half-size FFT makes the DCT3 about 10-35% faster than converting it to a
+        // full-size FFT, and halves the scratch space. It makes the DCT2 slower at most sizes, which is why this
+        // instance is only used for the DCT3 and DST3
+        let fft = self.plan_fft(len / 2);
+        planned!(
+            self,
+            "DCT3",
+            len,
+            "FFT",
+            len / 2,
+            Type2And3ConvertToRealFft::new(fft)
+        )
+    }
+
+    // Returns true if `plan_dct3` computes `len` with `Type2And3ConvertToRealFft`, instead of returning the DCT2
+    // instance. Size 0 doesn't have a half-size FFT, whatever the cost model says
+    fn uses_dct3_real_fft(&self, len: usize) -> bool {
+        len > 0
+            && len % 2 == 0
+            && len >= self.cost_model.dct3_min_real_fft_len
+            && !DCT2_BUTTERFLIES.contains(&len)
+            && !len.is_power_of_two()
+    }
+
+    /// Returns a DCT Type 3 instance which processes signals of size `len`, and weights its first input to match the
+    /// definition of another library, described by `convention`. See [`Dct3Convention`](enum.Dct3Convention.html).
+    ///
+    /// The DCT2, DST2, and DST3 of the returned instance are unchanged. With `Dct3Convention::Wang`, this returns the
+    /// same instance as `plan_dct3(len)`. Otherwise, the returned instance wraps that instance, and isn't cached.
+    pub fn plan_dct3_with_convention(
+        &mut self,
+        len: usize,
+        convention: Dct3Convention,
+    ) -> Arc<dyn TransformType2And3<T>> {
+        let dct = self.plan_dct3(len);
+        if convention == Dct3Convention::Wang {
+            dct
+        } else {
+            planned!(
+                self,
+                "DCT3",
+                len,
+                "DCT3",
+                len,
+                Type2And3WithDct3Convention::new(dct, convention)
+            )
+        }
+    }
+
+    /// Returns a DCT2/DCT3 transform pair which processes signals of size `len`, using the given `normalization`.
+    ///
+    /// The forward and inverse directions share the same DCT instance, which is the same instance returned by
+    /// `plan_dct2(len)`.
+    pub fn plan_dct2_pair(&mut self, len: usize, normalization: Normalization) -> Type2And3Pair<T> {
+        Type2And3Pair::new(self.plan_dct2(len), normalization)
+    }
+
+    /// Returns the DCT2, DST7, DCT8, and MDCT instances that a codec needs for blocks of size `len`, bundled together.
+    ///
+    /// Each instance is the same one returned by `plan_dct2(len)`, `plan_dst7(len)`, `plan_dct8(len)`, and
+    /// `plan_mdct(len, window_fn)`, so they share inner transforms and FFTs through this planner's caches. See
+    /// [`CodecBlock`](struct.CodecBlock.html) for how each transform's output is scaled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is odd, since the MDCT requires an even length.
+    pub fn plan_codec_block<F>(&mut self, len: usize, window_fn: F) -> CodecBlock<T>
+    where
+        F: (FnOnce(usize) -> Vec<T>),
+    {
+        CodecBlock::new(
+            self.plan_dct2(len),
+            self.plan_dst7(len),
+            self.plan_dct8(len),
+            self.plan_mdct(len, window_fn),
+        )
+    }
+
+    /// Returns DCT Type 2 instances for every size in `lens`, in a map from size to instance.
+    ///
+    /// This is equivalent to calling `plan_dct2` for each size, but the sizes are planned from largest to smallest. Large
+    /// sizes plan their sub-problems and inner FFTs along the way, so planning them first lets the smaller sizes be
+    /// found in the cache instead of being planned twice. Duplicate sizes are only planned once.
+    pub fn plan_dct2_many(
+        &mut self,
+        lens: &[usize],
+    ) -> HashMap<usize, Arc<dyn TransformType2And3<T>>> {
+        let mut sorted_lens = lens.to_vec();
+        sorted_lens.sort_unstable_by(|a, b| b.cmp(a));
+        sorted_lens.dedup();
+
+        sorted_lens
+            .into_iter()
+            .map(|len| (len, self.plan_dct2(len)))
+            .collect()
+    }
+
+    /// Returns a DCT Type 2 instance which processes signals of size `len`, and whose
+    /// [`process_dct2_two`](trait.Dct2.html#method.process_dct2_two) computes the DCT2s of two signals with a single
+    /// FFT.
+    ///
+    /// Sizes below 2, and sizes with a butterfly, use the instance from `plan_dct2`, since two butterflies are faster
+    /// than one FFT. Every other size is converted to a FFT of the same size, even where `plan_dct2` uses a different
+    /// algorithm for single signals. The returned instance is not cached, but its FFT and twiddle factors are shared
+    /// with instances returned by `plan_dct2`.
+    pub fn plan_dct2_two(&mut self, len: usize) -> Arc<dyn TransformType2And3<T>> {
+        if len < 2 || DCT2_BUTTERFLIES.contains(&len) {
+            self.plan_dct2(len)
+        } else {
+            derived_len(len, 4, 0, "twiddle table");
+            self.plan_dct2_convert_to_fft(len)
+        }
+    }
+
+    /// Returns a DCT Type 2 instance which processes signals of size `len`, and writes its output coefficients in the
+    /// given `order`.
+    ///
+    /// Where possible, the reordering is fused into the final pass of the algorithm, so that each coefficient is
+    /// written directly to its destination and the reordering costs nothing extra. The returned instance is not
+    /// cached, but its sub-problems are shared with instances returned by `plan_dct2`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `order` is a `Custom` order that isn't a permutation of `0..len`.
+    pub fn plan_dct2_with_output_order(
+        &mut self,
+        len: usize,
+        order: OutputOrder,
+    ) -> Arc<dyn Dct2<T>> {
+        if order == OutputOrder::Natural || len < 2 || DCT2_BUTTERFLIES.contains(&len) {
+            // butterflies don't have a separate final pass, so they need to be reordered afterwards
+            planned!(
+                self,
+                "DCT2",
+                len,
+                "DCT2",
+                len,
+                Dct2WithOutputOrder::new(self.plan_dct2(len), &order)
+            )
+        } else if len.is_power_of_two() {
+            let half_dct = self.plan_dct2(len / 2);
+            let quarter_dct = self.plan_dct2(len / 4);
+            let split_radix = self.split_radix_with_shared_twiddles(half_dct, quarter_dct);
+            planned!(
+                self,
+                "DCT2",
+                len,
+                "DCT2",
+                len / 2,
+                Dct2WithOutputOrder::from_split_radix(split_radix, &order)
+            )
+        } else {
+            derived_len(len, 4, 0, "twiddle table");
+            let fft = self.plan_fft(len);
+            let convert_to_fft = self.convert_to_fft_with_shared_twiddles(fft);
+            planned!(
+                self,
+                "DCT2",
+                len,
+                "FFT",
+                len,
+                Dct2WithOutputOrder::from_convert_to_fft(convert_to_fft, &order)
+            )
+        }
     }
 
     /// Returns a DCT Type 4 instance which processes signals of size `len`.
     /// If this is called multiple times, it will attempt to re-use internal data between instances
     pub fn plan_dct4(&mut self, len: usize) -> Arc<dyn TransformType4<T>> {
-        if self.dct4_cache.contains_key(&len) {
-            Arc::clone(self.dct4_cache.get(&len).unwrap())
+        let key = (len, self.accumulation);
+        if self.dct4_cache.contains_key(&key) {
+            Arc::clone(self.dct4_cache.get(&key).unwrap())
         } else {
-            let result = self.plan_new_dct4(len);
-            self.dct4_cache.insert(len, Arc::clone(&result));
+            let result =
+                self.plan_uncached(len, DctType::Dct4, |planner| planner.plan_new_dct4(len));
+            self.dct4_cache.insert(key, Arc::clone(&result));
             result
         }
     }
 
+    /// Returns a DCT Type 4 instance which processes signals of size `inner.len() * 2`, using the provided `inner`
+    /// instance to compute the DCT3 and DST3 it's built from.
+    ///
+    /// This allows a custom DCT3 implementation to be used as the building block of an even-sized DCT4. The returned
+    /// instance is not cached, and it isn't re-used by later calls to `plan_dct4`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `inner.len()` is 0.
+    pub fn plan_dct4_with_inner(
+        &mut self,
+        inner: Arc<dyn TransformType2And3<T>>,
+    ) -> Arc<dyn TransformType4<T>> {
+        // the conversion reads the first and last elements of each half, so an inner size of 0 can't be converted
+        assert!(
+            inner.len() >= 1,
+            "plan_dct4_with_inner requires inner.len() >= 1. Got inner.len() = {}",
+            inner.len()
+        );
+        let len = derived_len(inner.len(), 2, 0, "DCT4");
+        planned!(
+            self,
+            "DCT4",
+            len,
+            "DCT3",
+            inner.len(),
+            Type4ConvertToType3Even::new(inner)
+        )
+    }
+
     fn plan_new_dct4(&mut self, len: usize) -> Arc<dyn TransformType4<T>> {
-        //if we have an even size, we can use the DCT4 Via DCT3 algorithm
-        if len % 2 == 0 {
-            //benchmarking shows that below 6, it's faster to just use the naive DCT4 algorithm
-            if len < 6 {
-                Arc::new(Type4Naive::new(len))
+        if DCT4_BUTTERFLIES.contains(&len) {
+            //benchmarking shows that the butterflies are faster than both the naive and DCT4 via DCT3 algorithms
+            self.plan_dct4_butterfly(len)
+        } else if len % 2 == 0 {
+            //if we have an even size, we can use the DCT4 Via DCT3 algorithm
+            //below the cost model's crossover size, which defaults to 6, it's faster to just use the naive DCT4 algorithm
+            if len < self.cost_model.dct4_even_min_fast_len {
+                planned!(
+                    self,
+                    "DCT4",
+                    len,
+                    Type4Naive::new(len).with_accumulation(self.accumulation)
+                )
+            } else if len.is_power_of_two() && len <= self.cost_model.dct4_max_split_radix_len {
+                //benchmarking shows that splitting the largest butterfly's double is slightly faster than going
+                //through a DCT3, but above that, the DCT3's FFT-based algorithms win by 5-30%
+                let inner_dct = self.plan_dct4(len / 2);
+                planned!(
+                    self,
+                    "DCT4",
+                    len,
+                    "DCT4",
+                    len / 2,
+                    Type4SplitRadix::new(inner_dct)
+                )
             } else {
+                // check the twiddle table size before planning the inner DCT3
+                derived_len(len, 8, 0, "twiddle table");
+
                 let inner_dct = self.plan_dct3(len / 2);
-                Arc::new(Type4ConvertToType3Even::new(inner_dct))
+                planned!(
+                    self,
+                    "DCT4",
+                    len,
+                    "DCT3",
+                    len / 2,
+                    Type4ConvertToType3Even::new(inner_dct)
+                )
             }
         } else {
             //odd size, so we can use the "DCT4 via FFT odd" algorithm
-            //benchmarking shows that below about 7, it's faster to just use the naive DCT4 algorithm
-            if len < 7 {
-                Arc::new(Type4Naive::new(len))
+            //below the cost model's crossover size, which defaults to 7, it's faster to just use the naive DCT4 algorithm
+            if len < self.cost_model.dct4_odd_min_fast_len {
+                planned!(
+                    self,
+                    "DCT4",
+                    len,
+                    Type4Naive::new(len).with_accumulation(self.accumulation)
+                )
             } else {
-                let fft = self.fft_planner.plan_fft_forward(len);
-                Arc::new(Type4ConvertToFftOdd::new(fft))
+                derived_len(len, 2, 0, "scratch");
+
+                let fft = self.plan_fft(len);
+                planned!(
+                    self,
+                    "DCT4",
+                    len,
+                    "FFT",
+                    len,
+                    Type4ConvertToFftOdd::new(fft)
+                )
             }
         }
     }
 
+    fn plan_dct4_butterfly(&mut self, len: usize) -> Arc<dyn TransformType4<T>> {
+        match len {
+            4 => planned!(self, "DCT4", len, Type4Butterfly4::new()),
+            8 => planned!(self, "DCT4", len, Type4Butterfly8::new()),
+            16 => planned!(self, "DCT4", len, Type4Butterfly16::new()),
+            32 => planned!(self, "DCT4", len, Type4Butterfly32::new()),
+            _ => panic!("Invalid butterfly size for DCT4: {}", len),
+        }
+    }
+
     /// Returns a DCT Type 5 instance which processes signals of size `len`.
     /// If this is called multiple times, it will attempt to re-use internal data between instances
     pub fn plan_dct5(&mut self, len: usize) -> Arc<dyn Dct5<T>> {
-        if self.dct5_cache.contains_key(&len) {
-            Arc::clone(self.dct5_cache.get(&len).unwrap())
+        let key = (len, self.accumulation);
+        if self.dct5_cache.contains_key(&key) {
+            Arc::clone(self.dct5_cache.get(&key).unwrap())
         } else {
-            let result = self.plan_new_dct5(len);
-            self.dct5_cache.insert(len, Arc::clone(&result));
+            let result =
+                self.plan_uncached(len, DctType::Dct5, |planner| planner.plan_new_dct5(len));
+            self.dct5_cache.insert(key, Arc::clone(&result));
             result
         }
     }
 
     fn plan_new_dct5(&mut self, len: usize) -> Arc<dyn Dct5<T>> {
-        Arc::new(Dct5Naive::new(len))
+        if len <= MAX_TYPE5TO8_BUTTERFLY {
+            self.plan_dct5_butterfly(len)
+        } else {
+            planned!(
+                self,
+                "DCT5",
+                len,
+                Dct5Naive::new(len).with_accumulation(self.accumulation)
+            )
+        }
+    }
+
+    fn plan_dct5_butterfly(&mut self, len: usize) -> Arc<dyn Dct5<T>> {
+        match len {
+            0 => planned!(self, "DCT5", len, Dct5Butterfly0::new()),
+            1 => planned!(self, "DCT5", len, Dct5Butterfly1::new()),
+            2 => planned!(self, "DCT5", len, Dct5Butterfly2::new()),
+            3 => planned!(self, "DCT5", len, Dct5Butterfly3::new()),
+            4 => planned!(self, "DCT5", len, Dct5Butterfly4::new()),
+            _ => panic!("Invalid butterfly size for DCT5: {}", len),
+        }
     }
 
     /// Returns a DCT Type 6 instance which processes signals of size `len`.
     /// If this is called multiple times, it will attempt to re-use internal data between instances
     pub fn plan_dct6(&mut self, len: usize) -> Arc<dyn Dct6And7<T>> {
-        if self.dct6_cache.contains_key(&len) {
-            Arc::clone(self.dct6_cache.get(&len).unwrap())
+        let key = (len, self.accumulation);
+        if self.dct6_cache.contains_key(&key) {
+            Arc::clone(self.dct6_cache.get(&key).unwrap())
         } else {
-            let result = self.plan_new_dct6(len);
-            self.dct6_cache.insert(len, Arc::clone(&result));
+            let result =
+                self.plan_uncached(len, DctType::Dct6, |planner| planner.plan_new_dct6(len));
+            self.dct6_cache.insert(key, Arc::clone(&result));
             result
         }
     }
 
     fn plan_new_dct6(&mut self, len: usize) -> Arc<dyn Dct6And7<T>> {
-        Arc::new(Dct6And7Naive::new(len))
+        if len <= MAX_TYPE5TO8_BUTTERFLY {
+            self.plan_dct6_butterfly(len)
+        } else if len < self.cost_model.dct6_min_fast_len {
+            planned!(
+                self,
+                "DCT6",
+                len,
+                Dct6And7Naive::new(len).with_accumulation(self.accumulation)
+            )
+        } else {
+            let inner_len = derived_len(len, 2, -1, "inner DCT");
+            let inner_dct = self.plan_dct2(inner_len);
+            planned!(
+                self,
+                "DCT6",
+                len,
+                "DCT2",
+                inner_len,
+                Dct6And7ConvertToType2And3::new(inner_dct)
+            )
+        }
+    }
+
+    fn plan_dct6_butterfly(&mut self, len: usize) -> Arc<dyn Dct6And7<T>> {
+        match len {
+            0 => planned!(self, "DCT6", len, Dct6And7Butterfly0::new()),
+            1 => planned!(self, "DCT6", len, Dct6And7Butterfly1::new()),
+            2 => planned!(self, "DCT6", len, Dct6And7Butterfly2::new()),
+            3 => planned!(self, "DCT6", len, Dct6And7Butterfly3::new()),
+            4 => planned!(self, "DCT6", len, Dct6And7Butterfly4::new()),
+            _ => panic!("Invalid butterfly size for DCT6: {}", len),
+        }
     }
 
     /// Returns DCT Type 7 instance which processes signals of size `len`.
@@ -214,38 +1380,111 @@ impl<T: DctNum> DctPlanner<T> {
     /// Returns a DCT Type 8 instance which processes signals of size `len`.
     /// If this is called multiple times, it will attempt to re-use internal data between instances
     pub fn plan_dct8(&mut self, len: usize) -> Arc<dyn Dct8<T>> {
-        if self.dct8_cache.contains_key(&len) {
-            Arc::clone(self.dct8_cache.get(&len).unwrap())
+        let key = (len, self.accumulation);
+        if self.dct8_cache.contains_key(&key) {
+            Arc::clone(self.dct8_cache.get(&key).unwrap())
         } else {
-            let result = self.plan_new_dct8(len);
-            self.dct8_cache.insert(len, Arc::clone(&result));
+            let result =
+                self.plan_uncached(len, DctType::Dct8, |planner| planner.plan_new_dct8(len));
+            self.dct8_cache.insert(key, Arc::clone(&result));
             result
         }
     }
 
     fn plan_new_dct8(&mut self, len: usize) -> Arc<dyn Dct8<T>> {
-        Arc::new(Dct8Naive::new(len))
+        if len <= MAX_TYPE5TO8_BUTTERFLY {
+            self.plan_dct8_butterfly(len)
+        } else if len < self.cost_model.dct8_min_fast_len {
+            planned!(
+                self,
+                "DCT8",
+                len,
+                Dct8Naive::new(len).with_accumulation(self.accumulation)
+            )
+        } else {
+            let inner_len = derived_len(len, 2, 1, "inner DCT");
+            let inner_dct = self.plan_dct2(inner_len);
+            planned!(
+                self,
+                "DCT8",
+                len,
+                "DCT2",
+                inner_len,
+                Dct8ConvertToType2And3::new(inner_dct)
+            )
+        }
+    }
+
+    fn plan_dct8_butterfly(&mut self, len: usize) -> Arc<dyn Dct8<T>> {
+        match len {
+            0 => planned!(self, "DCT8", len, Dct8Butterfly0::new()),
+            1 => planned!(self, "DCT8", len, Dct8Butterfly1::new()),
+            2 => planned!(self, "DCT8", len, Dct8Butterfly2::new()),
+            3 => planned!(self, "DCT8", len, Dct8Butterfly3::new()),
+            4 => planned!(self, "DCT8", len, Dct8Butterfly4::new()),
+            _ => panic!("Invalid butterfly size for DCT8: {}", len),
+        }
     }
 
     /// Returns a DST Type 1 instance which processes signals of size `len`.
     /// If this is called multiple times, it will attempt to re-use internal data between instances
     pub fn plan_dst1(&mut self, len: usize) -> Arc<dyn Dst1<T>> {
-        if self.dst1_cache.contains_key(&len) {
-            Arc::clone(self.dst1_cache.get(&len).unwrap())
+        let key = (len, self.accumulation);
+        if self.dst1_cache.contains_key(&key) {
+            Arc::clone(self.dst1_cache.get(&key).unwrap())
         } else {
-            let result = self.plan_new_dst1(len);
-            self.dst1_cache.insert(len, Arc::clone(&result));
+            let result =
+                self.plan_uncached(len, DctType::Dst1, |planner| planner.plan_new_dst1(len));
+            self.dst1_cache.insert(key, Arc::clone(&result));
             result
         }
     }
 
     fn plan_new_dst1(&mut self, len: usize) -> Arc<dyn Dst1<T>> {
-        //benchmarking shows that below about 25, it's faster to just use the naive DCT1 algorithm
-        if len < 25 {
-            Arc::new(Dst1Naive::new(len))
+        //below the cost model's crossover size, which defaults to 25, it's faster to just use the naive DST1 algorithm
+        //and the butterflies are faster than both up to their max size
+        if (MIN_TYPE1_BUTTERFLY..=MAX_TYPE1_BUTTERFLY).contains(&len) {
+            self.plan_dst1_butterfly(len)
+        } else if len < self.cost_model.dst1_min_fast_len {
+            planned!(
+                self,
+                "DST1",
+                len,
+                Dst1Naive::new(len).with_accumulation(self.accumulation)
+            )
         } else {
-            let fft = self.fft_planner.plan_fft_forward((len + 1) * 2);
-            Arc::new(Dst1ConvertToFft::new(fft))
+            let fft_len = derived_len(len, 2, 2, "inner FFT");
+            derived_len(fft_len, 2, 0, "scratch");
+            let fft = self.plan_fft(fft_len);
+            planned!(
+                self,
+                "DST1",
+                len,
+                "FFT",
+                fft_len,
+                Dst1ConvertToFft::new(fft)
+            )
+        }
+    }
+
+    fn plan_dst1_butterfly(&mut self, len: usize) -> Arc<dyn Dst1<T>> {
+        match len {
+            2 => planned!(self, "DST1", len, Dst1Butterfly2::new()),
+            3 => planned!(self, "DST1", len, Dst1Butterfly3::new()),
+            4 => planned!(self, "DST1", len, Dst1Butterfly4::new()),
+            5 => planned!(self, "DST1", len, Dst1Butterfly5::new()),
+            6 => planned!(self, "DST1", len, Dst1Butterfly6::new()),
+            7 => planned!(self, "DST1", len, Dst1Butterfly7::new()),
+            8 => planned!(self, "DST1", len, Dst1Butterfly8::new()),
+            9 => planned!(self, "DST1", len, Dst1Butterfly9::new()),
+            10 => planned!(self, "DST1", len, Dst1Butterfly10::new()),
+            11 => planned!(self, "DST1", len, Dst1Butterfly11::new()),
+            12 => planned!(self, "DST1", len, Dst1Butterfly12::new()),
+            13 => planned!(self, "DST1", len, Dst1Butterfly13::new()),
+            14 => planned!(self, "DST1", len, Dst1Butterfly14::new()),
+            15 => planned!(self, "DST1", len, Dst1Butterfly15::new()),
+            16 => planned!(self, "DST1", len, Dst1Butterfly16::new()),
+            _ => panic!("Invalid butterfly size for DST1: {}", len),
         }
     }
 
@@ -255,10 +1494,36 @@ impl<T: DctNum> DctPlanner<T> {
         self.plan_dct2(len)
     }
 
+    /// Returns a DST Type 2 and DST Type 3 instance which processes signals of size `len`, and scales its DST2 and DST3
+    /// to match the definitions of another library, described by `convention`. See
+    /// [`DstConvention`](enum.DstConvention.html).
+    ///
+    /// The DCT2 and DCT3 of the returned instance are unchanged. With `DstConvention::Wang`, this returns the same
+    /// instance as `plan_dst2(len)`. Otherwise, the returned instance wraps that instance, and isn't cached.
+    pub fn plan_dst2_with_convention(
+        &mut self,
+        len: usize,
+        convention: DstConvention,
+    ) -> Arc<dyn TransformType2And3<T>> {
+        let dst = self.plan_dst2(len);
+        if convention == DstConvention::Wang {
+            dst
+        } else {
+            planned!(
+                self,
+                "DST2",
+                len,
+                "DST2",
+                len,
+                Type2And3WithDstConvention::new(dst, convention)
+            )
+        }
+    }
+
     /// Returns DST Type 3 instance which processes signals of size `len`.
     /// If this is called multiple times, it will attempt to re-use internal data between instances
     pub fn plan_dst3(&mut self, len: usize) -> Arc<dyn TransformType2And3<T>> {
-        self.plan_dct2(len)
+        self.plan_dct3(len)
     }
 
     /// Returns DST Type 4 instance which processes signals of size `len`.
@@ -270,37 +1535,90 @@ impl<T: DctNum> DctPlanner<T> {
     /// Returns a DST Type 5 instance which processes signals of size `len`.
     /// If this is called multiple times, it will attempt to re-use internal data between instances
     pub fn plan_dst5(&mut self, len: usize) -> Arc<dyn Dst5<T>> {
-        if self.dst5_cache.contains_key(&len) {
-            Arc::clone(self.dst5_cache.get(&len).unwrap())
+        let key = (len, self.accumulation);
+        if self.dst5_cache.contains_key(&key) {
+            Arc::clone(self.dst5_cache.get(&key).unwrap())
         } else {
-            let result = self.plan_new_dst5(len);
-            self.dst5_cache.insert(len, Arc::clone(&result));
+            let result =
+                self.plan_uncached(len, DctType::Dst5, |planner| planner.plan_new_dst5(len));
+            self.dst5_cache.insert(key, Arc::clone(&result));
             result
         }
     }
 
     fn plan_new_dst5(&mut self, len: usize) -> Arc<dyn Dst5<T>> {
-        Arc::new(Dst5Naive::new(len))
+        if len <= MAX_TYPE5TO8_BUTTERFLY {
+            self.plan_dst5_butterfly(len)
+        } else {
+            planned!(
+                self,
+                "DST5",
+                len,
+                Dst5Naive::new(len).with_accumulation(self.accumulation)
+            )
+        }
+    }
+
+    fn plan_dst5_butterfly(&mut self, len: usize) -> Arc<dyn Dst5<T>> {
+        match len {
+            0 => planned!(self, "DST5", len, Dst5Butterfly0::new()),
+            1 => planned!(self, "DST5", len, Dst5Butterfly1::new()),
+            2 => planned!(self, "DST5", len, Dst5Butterfly2::new()),
+            3 => planned!(self, "DST5", len, Dst5Butterfly3::new()),
+            4 => planned!(self, "DST5", len, Dst5Butterfly4::new()),
+            _ => panic!("Invalid butterfly size for DST5: {}", len),
+        }
     }
 
     /// Returns a DST Type 6 instance which processes signals of size `len`.
     /// If this is called multiple times, it will attempt to re-use internal data between instances
     pub fn plan_dst6(&mut self, len: usize) -> Arc<dyn Dst6And7<T>> {
-        if self.dst6_cache.contains_key(&len) {
-            Arc::clone(self.dst6_cache.get(&len).unwrap())
+        let key = (len, self.accumulation);
+        if self.dst6_cache.contains_key(&key) {
+            Arc::clone(self.dst6_cache.get(&key).unwrap())
         } else {
-            let result = self.plan_new_dst6(len);
-            self.dst6_cache.insert(len, Arc::clone(&result));
+            let result =
+                self.plan_uncached(len, DctType::Dst6, |planner| planner.plan_new_dst6(len));
+            self.dst6_cache.insert(key, Arc::clone(&result));
             result
         }
     }
 
     fn plan_new_dst6(&mut self, len: usize) -> Arc<dyn Dst6And7<T>> {
-        if len < 45 {
-            Arc::new(Dst6And7Naive::new(len))
+        // Dst6And7ConvertToType2And3 is also an option here, but benchmarking shows that it's slower than converting
+        // directly to a FFT of the same size, since it has to do the extra work of a DST2
+        if len <= MAX_TYPE5TO8_BUTTERFLY {
+            self.plan_dst6_butterfly(len)
+        } else if len < self.cost_model.dst6_min_fast_len {
+            planned!(
+                self,
+                "DST6",
+                len,
+                Dst6And7Naive::new(len).with_accumulation(self.accumulation)
+            )
         } else {
-            let fft = self.fft_planner.plan_fft_forward(len * 2 + 1);
-            Arc::new(Dst6And7ConvertToFft::new(fft))
+            let fft_len = derived_len(len, 2, 1, "inner FFT");
+            derived_len(fft_len, 2, 0, "scratch");
+            let fft = self.plan_fft(fft_len);
+            planned!(
+                self,
+                "DST6",
+                len,
+                "FFT",
+                fft_len,
+                Dst6And7ConvertToFft::new(fft)
+            )
+        }
+    }
+
+    fn plan_dst6_butterfly(&mut self, len: usize) -> Arc<dyn Dst6And7<T>> {
+        match len {
+            0 => planned!(self, "DST6", len, Dst6And7Butterfly0::new()),
+            1 => planned!(self, "DST6", len, Dst6And7Butterfly1::new()),
+            2 => planned!(self, "DST6", len, Dst6And7Butterfly2::new()),
+            3 => planned!(self, "DST6", len, Dst6And7Butterfly3::new()),
+            4 => planned!(self, "DST6", len, Dst6And7Butterfly4::new()),
+            _ => panic!("Invalid butterfly size for DST6: {}", len),
         }
     }
 
@@ -313,17 +1631,165 @@ impl<T: DctNum> DctPlanner<T> {
     /// Returns a DST Type 8 instance which processes signals of size `len`.
     /// If this is called multiple times, it will attempt to re-use internal data between instances
     pub fn plan_dst8(&mut self, len: usize) -> Arc<dyn Dst8<T>> {
-        if self.dst8_cache.contains_key(&len) {
-            Arc::clone(self.dst8_cache.get(&len).unwrap())
+        let key = (len, self.accumulation);
+        if self.dst8_cache.contains_key(&key) {
+            Arc::clone(self.dst8_cache.get(&key).unwrap())
         } else {
-            let result = self.plan_new_dst8(len);
-            self.dst8_cache.insert(len, Arc::clone(&result));
+            let result =
+                self.plan_uncached(len, DctType::Dst8, |planner| planner.plan_new_dst8(len));
+            self.dst8_cache.insert(key, Arc::clone(&result));
             result
         }
     }
 
     fn plan_new_dst8(&mut self, len: usize) -> Arc<dyn Dst8<T>> {
-        Arc::new(Dst8Naive::new(len))
+        if len <= MAX_TYPE5TO8_BUTTERFLY {
+            self.plan_dst8_butterfly(len)
+        } else if len < self.cost_model.dst8_min_fast_len {
+            planned!(
+                self,
+                "DST8",
+                len,
+                Dst8Naive::new(len).with_accumulation(self.accumulation)
+            )
+        } else {
+            let inner_len = derived_len(len, 2, -1, "inner DST");
+            let inner_dst = self.plan_dst2(inner_len);
+            planned!(
+                self,
+                "DST8",
+                len,
+                "DST2",
+                inner_len,
+                Dst8ConvertToType2And3::new(inner_dst)
+            )
+        }
+    }
+
+    fn plan_dst8_butterfly(&mut self, len: usize) -> Arc<dyn Dst8<T>> {
+        match len {
+            0 => planned!(self, "DST8", len, Dst8Butterfly0::new()),
+            1 => planned!(self, "DST8", len, Dst8Butterfly1::new()),
+            2 => planned!(self, "DST8", len, Dst8Butterfly2::new()),
+            3 => planned!(self, "DST8", len, Dst8Butterfly3::new()),
+            4 => planned!(self, "DST8", len, Dst8Butterfly4::new()),
+            _ => panic!("Invalid butterfly size for DST8: {}", len),
+        }
+    }
+
+    /// Returns a plan that inverts a DCT Type 5 of size `len`, by computing a DCT Type 5 and scaling the output.
+    /// The inner DCT5 instance is shared with `plan_dct5(len)`.
+    pub fn plan_inverse_dct5(&mut self, len: usize) -> InversePlan<T> {
+        InversePlan::new(Box::new(self.plan_dct5(len)), DctType::Dct5)
+    }
+
+    /// Returns a plan that inverts a DCT Type 6 of size `len`, by computing a DCT Type 7 and scaling the output.
+    /// The inner DCT7 instance is shared with `plan_dct7(len)`.
+    pub fn plan_inverse_dct6(&mut self, len: usize) -> InversePlan<T> {
+        InversePlan::new(Box::new(self.plan_dct7(len)), DctType::Dct6)
+    }
+
+    /// Returns a plan that inverts a DCT Type 7 of size `len`, by computing a DCT Type 6 and scaling the output.
+    /// The inner DCT6 instance is shared with `plan_dct6(len)`.
+    pub fn plan_inverse_dct7(&mut self, len: usize) -> InversePlan<T> {
+        InversePlan::new(Box::new(self.plan_dct6(len)), DctType::Dct7)
+    }
+
+    /// Returns a plan that inverts a DCT Type 8 of size `len`, by computing a DCT Type 8 and scaling the output.
+    /// The inner DCT8 instance is shared with `plan_dct8(len)`.
+    pub fn plan_inverse_dct8(&mut self, len: usize) -> InversePlan<T> {
+        InversePlan::new(Box::new(self.plan_dct8(len)), DctType::Dct8)
+    }
+
+    /// Returns a plan that inverts a DST Type 5 of size `len`, by computing a DST Type 5 and scaling the output.
+    /// The inner DST5 instance is shared with `plan_dst5(len)`.
+    pub fn plan_inverse_dst5(&mut self, len: usize) -> InversePlan<T> {
+        InversePlan::new(Box::new(self.plan_dst5(len)), DctType::Dst5)
+    }
+
+    /// Returns a plan that inverts a DST Type 6 of size `len`, by computing a DST Type 7 and scaling the output.
+    /// The inner DST7 instance is shared with `plan_dst7(len)`.
+    pub fn plan_inverse_dst6(&mut self, len: usize) -> InversePlan<T> {
+        InversePlan::new(Box::new(self.plan_dst7(len)), DctType::Dst6)
+    }
+
+    /// Returns a plan that inverts a DST Type 7 of size `len`, by computing a DST Type 6 and scaling the output.
+    /// The inner DST6 instance is shared with `plan_dst6(len)`.
+    pub fn plan_inverse_dst7(&mut self, len: usize) -> InversePlan<T> {
+        InversePlan::new(Box::new(self.plan_dst6(len)), DctType::Dst7)
+    }
+
+    /// Returns a plan that inverts a DST Type 8 of size `len`, by computing a DST Type 8 and scaling the output.
+    /// The inner DST8 instance is shared with `plan_dst8(len)`.
+    pub fn plan_inverse_dst8(&mut self, len: usize) -> InversePlan<T> {
+        InversePlan::new(Box::new(self.plan_dst8(len)), DctType::Dst8)
+    }
+
+    /// Computes, in-place, the transform that implicitly extends `buffer` with the `start` boundary condition before its
+    /// first element and the `end` boundary condition after its last element, and returns the type of that transform.
+    ///
+    /// The transform type is chosen with [`DctType::from_boundaries`](enum.DctType.html#method.from_boundaries), and
+    /// planned with this planner, so repeated calls with the same size and boundaries reuse the same cached instance.
+    /// Like every other transform in RustDCT, the output isn't normalized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if both boundaries are `WholeSampleSymmetric` and `buffer.len()` is less than 2, since the DCT Type 1
+    /// requires at least two elements.
+    pub fn process_with_boundaries(
+        &mut self,
+        buffer: &mut [T],
+        start: BoundaryMode,
+        end: BoundaryMode,
+    ) -> DctType {
+        let kind = DctType::from_boundaries(start, end);
+        self.plan_and_process(kind, buffer);
+        kind
+    }
+
+    // Plans a transform of type `kind` and size `len`, boxed so that every type has the same return type
+    pub(crate) fn plan_boxed(&mut self, kind: DctType, len: usize) -> Box<dyn DctTransform<T>> {
+        match kind {
+            DctType::Dct1 => Box::new(self.plan_dct1(len)),
+            DctType::Dct2 => Box::new(self.plan_dct2(len)),
+            DctType::Dct3 => Box::new(self.plan_dct3(len)),
+            DctType::Dct4 => Box::new(self.plan_dct4(len)),
+            DctType::Dct5 => Box::new(self.plan_dct5(len)),
+            DctType::Dct6 => Box::new(self.plan_dct6(len)),
+            DctType::Dct7 => Box::new(self.plan_dct7(len)),
+            DctType::Dct8 => Box::new(self.plan_dct8(len)),
+            DctType::Dst1 => Box::new(self.plan_dst1(len)),
+            DctType::Dst2 => Box::new(self.plan_dst2(len)),
+            DctType::Dst3 => Box::new(self.plan_dst3(len)),
+            DctType::Dst4 => Box::new(self.plan_dst4(len)),
+            DctType::Dst5 => Box::new(self.plan_dst5(len)),
+            DctType::Dst6 => Box::new(self.plan_dst6(len)),
+            DctType::Dst7 => Box::new(self.plan_dst7(len)),
+            DctType::Dst8 => Box::new(self.plan_dst8(len)),
+        }
+    }
+
+    // Plans a transform of type `kind` and the size of `buffer`, and runs it on `buffer`
+    pub(crate) fn plan_and_process(&mut self, kind: DctType, buffer: &mut [T]) {
+        let len = buffer.len();
+        match kind {
+            DctType::Dct1 => self.plan_dct1(len).process_dct1(buffer),
+            DctType::Dct2 => self.plan_dct2(len).process_dct2(buffer),
+            DctType::Dct3 => self.plan_dct3(len).process_dct3(buffer),
+            DctType::Dct4 => self.plan_dct4(len).process_dct4(buffer),
+            DctType::Dct5 => self.plan_dct5(len).process_dct5(buffer),
+            DctType::Dct6 => self.plan_dct6(len).process_dct6(buffer),
+            DctType::Dct7 => self.plan_dct7(len).process_dct7(buffer),
+            DctType::Dct8 => self.plan_dct8(len).process_dct8(buffer),
+            DctType::Dst1 => self.plan_dst1(len).process_dst1(buffer),
+            DctType::Dst2 => self.plan_dst2(len).process_dst2(buffer),
+            DctType::Dst3 => self.plan_dst3(len).process_dst3(buffer),
+            DctType::Dst4 => self.plan_dst4(len).process_dst4(buffer),
+            DctType::Dst5 => self.plan_dst5(len).process_dst5(buffer),
+            DctType::Dst6 => self.plan_dst6(len).process_dst6(buffer),
+            DctType::Dst7 => self.plan_dst7(len).process_dst7(buffer),
+            DctType::Dst8 => self.plan_dst8(len).process_dst8(buffer),
+        }
     }
 
     /// Returns a MDCT instance which processes inputs of size ` len * 2` and produces outputs of size `len`.
@@ -336,21 +1802,344 @@ impl<T: DctNum> DctPlanner<T> {
     where
         F: (FnOnce(usize) -> Vec<T>),
     {
-        if self.mdct_cache.contains_key(&len) {
-            Arc::clone(self.mdct_cache.get(&len).unwrap())
+        self.plan_mdct_with_window_mode(len, window_fn, WindowMode::Internal)
+    }
+
+    /// Returns a MDCT instance which processes inputs of size ` len * 2` and produces outputs of size `len`.
+    ///
+    /// If `window_mode` is `WindowMode::External`, the forward MDCT won't apply `window_fn` to its input, and the
+    /// caller is expected to window the input itself. The IMDCT still uses `window_fn`. See the
+    /// [`WindowMode`](mdct/enum.WindowMode.html) documentation for details.
+    ///
+    /// If this is called multiple times, it will attempt to re-use internal data between instances
+    pub fn plan_mdct_with_window_mode<F>(
+        &mut self,
+        len: usize,
+        window_fn: F,
+        window_mode: WindowMode,
+    ) -> Arc<dyn Mdct<T>>
+    where
+        F: (FnOnce(usize) -> Vec<T>),
+    {
+        let window_fn = self.mdct_window_scaling.wrap_fn(window_fn);
+        self.plan_mdct_cached(len, window_mode, |inner_dct4| {
+            MdctViaDct4::with_window_mode(inner_dct4, window_fn, window_mode)
+        })
+    }
+
+    /// Returns a MDCT instance which processes inputs of size ` len * 2` and produces outputs of size `len`.
+    ///
+    /// Instead of returning a `Vec`, `fill_window` writes the `len * 2` window values into the slice it's given, which
+    /// is the MDCT's own window storage, so no intermediate buffer is allocated. Otherwise, this is the same as
+    /// [`plan_mdct_with_window_mode`](#method.plan_mdct_with_window_mode).
+    ///
+    /// ~~~
+    /// // Plans a MDCT with a sine window, computing each window value in place
+    /// use rustdct::mdct::WindowMode;
+    /// use rustdct::DctPlanner;
+    ///
+    /// let mut planner = DctPlanner::new();
+    /// let mdct = planner.plan_mdct_with_window_fill(
+    ///     64,
+    ///     |window: &mut [f32]| {
+    ///         let len = window.len() as f32;
+    ///         for (n, value) in window.iter_mut().enumerate() {
+    ///             *value = (std::f32::consts::PI * (n as f32 + 0.5) / len).sin();
+    ///         }
+    ///     },
+    ///     WindowMode::Internal,
+    /// );
+    /// ~~~
+    ///
+    /// If this is called multiple times, it will attempt to re-use internal data between instances
+    pub fn plan_mdct_with_window_fill<F>(
+        &mut self,
+        len: usize,
+        fill_window: F,
+        window_mode: WindowMode,
+    ) -> Arc<dyn Mdct<T>>
+    where
+        F: FnOnce(&mut [T]),
+    {
+        let window_scaling = self.mdct_window_scaling;
+        let fill_window = |window: &mut [T]| {
+            fill_window(window);
+            window_scaling.apply(window);
+        };
+        self.plan_mdct_cached(len, window_mode, |inner_dct4| {
+            MdctViaDct4::with_window_fill(inner_dct4, fill_window, window_mode)
+        })
+    }
+
+    /// Returns a DCT Type 2 instance which processes signals of size `len`, and whose `get_scratch_len()` is at most
+    /// `max_scratch_len`. Returns an error if no algorithm for this size fits.
+    ///
+    /// This is meant for threads that can't allocate, like realtime audio threads: Allocate a scratch buffer of
+    /// `max_scratch_len` elements ahead of time, and call the `process_*_with_scratch` methods with it. No algorithm
+    /// allocates inside those methods. If `max_scratch_len` is 64 or less, the `process_*` methods that don't take a
+    /// scratch buffer don't allocate either, since they place their scratch on the stack.
+    ///
+    /// If the instance returned by [`plan_dct2`](#method.plan_dct2) fits, it's returned. Otherwise, this tries the
+    /// algorithms that need less scratch, from fastest to slowest: Even sizes can be converted to a half-size FFT,
+    /// and every size can use the naive algorithm, which is O(n^2) but only needs `len` elements of scratch. The
+    /// butterfly sizes and the power-of-two sizes need no more than `len` elements to begin with, and the butterflies
+    /// need none at all.
+    ///
+    /// Instances that are planned to fit the bound are not cached, but the inner instances they use are shared with
+    /// the other planning methods. The returned instance can also compute the DCT3, DST2, and DST3.
+    ///
+    /// ~~~
+    /// // Plans a DCT2 of size 100 that can run with 100 elements of scratch
+    /// use rustdct::{DctPlanner, RequiredScratch};
+    ///
+    /// let mut planner = DctPlanner::new();
+    /// let dct2 = planner.plan_dct2_bounded(100, 100).unwrap();
+    /// assert!(dct2.get_scratch_len() <= 100);
+    ///
+    /// // allocated ahead of time, outside of the realtime thread
+    /// let mut scratch = vec![0f32; 100];
+    /// let mut buffer = vec![0f32; 100];
+    /// dct2.process_dct2_with_scratch(&mut buffer, &mut scratch);
+    ///
+    /// // every non-butterfly algorithm needs at least `len` elements of scratch
+    /// assert!(planner.plan_dct2_bounded(100, 99).is_err());
+    /// ~~~
+    pub fn plan_dct2_bounded(
+        &mut self,
+        len: usize,
+        max_scratch_len: usize,
+    ) -> Result<Arc<dyn TransformType2And3<T>>, ScratchLimitError> {
+        let dct = self.plan_dct2(len);
+        if dct.get_scratch_len() <= max_scratch_len {
+            return Ok(dct);
+        }
+
+        // the half-size FFT is slower for the DCT2, but it needs half the scratch space
+        if len > 0 && len % 2 == 0 {
+            let fft = self.plan_fft(len / 2);
+            let real_fft_dct = Type2And3ConvertToRealFft::new(fft);
+            if real_fft_dct.get_scratch_len() <= max_scratch_len {
+                return Ok(planned!(self, "DCT2", len, "FFT", len / 2, real_fft_dct));
+            }
+        }
+
+        if len <= max_scratch_len {
+            Ok(planned!(
+                self,
+                "DCT2",
+                len,
+                Type2And3Naive::new(len).with_accumulation(self.accumulation)
+            ))
         } else {
-            let result = self.plan_new_mdct(len, window_fn);
-            self.mdct_cache.insert(len, Arc::clone(&result));
-            result
+            Err(ScratchLimitError {
+                kind: DctType::Dct2,
+                len,
+                max_scratch_len,
+                min_scratch_len: len,
+            })
         }
     }
 
-    fn plan_new_mdct<F>(&mut self, len: usize, window_fn: F) -> Arc<dyn Mdct<T>>
+    /// Returns a DCT Type 4 instance which processes signals of size `len`, and whose `get_scratch_len()` is at most
+    /// `max_scratch_len`. Returns an error if no algorithm for this size fits.
+    ///
+    /// If the instance returned by [`plan_dct4`](#method.plan_dct4) fits, it's returned. Otherwise, even sizes are
+    /// converted to a DCT3 of half the size, which needs `len` elements of scratch when its inner DCT3 is planned with
+    /// [`plan_dct2_bounded`](#method.plan_dct2_bounded), and odd sizes fall back to the naive algorithm, which also
+    /// needs `len` elements. See `plan_dct2_bounded` for how to process the returned instance without allocating.
+    ///
+    /// Instances that are planned to fit the bound are not cached, but the inner instances they use are shared with
+    /// the other planning methods. The returned instance can also compute the DST4.
+    pub fn plan_dct4_bounded(
+        &mut self,
+        len: usize,
+        max_scratch_len: usize,
+    ) -> Result<Arc<dyn TransformType4<T>>, ScratchLimitError> {
+        let dct = self.plan_dct4(len);
+        if dct.get_scratch_len() <= max_scratch_len {
+            return Ok(dct);
+        }
+
+        if len > max_scratch_len {
+            Err(ScratchLimitError {
+                kind: DctType::Dct4,
+                len,
+                max_scratch_len,
+                min_scratch_len: len,
+            })
+        } else if len % 2 == 0 {
+            // the DCT3 only needs half as much scratch as this does, so it always fits
+            derived_len(len, 8, 0, "twiddle table");
+            let inner_dct = self.plan_dct2_bounded(len / 2, max_scratch_len)?;
+            Ok(planned!(
+                self,
+                "DCT4",
+                len,
+                "DCT3",
+                len / 2,
+                Type4ConvertToType3Even::new(inner_dct)
+            ))
+        } else {
+            Ok(planned!(
+                self,
+                "DCT4",
+                len,
+                Type4Naive::new(len).with_accumulation(self.accumulation)
+            ))
+        }
+    }
+
+    /// Returns a MDCT instance which processes inputs of size `len * 2` and produces outputs of size `len`, and
+    /// whose `get_scratch_len()` is at most `max_scratch_len`.
+    ///
+    /// The MDCT returned by [`plan_mdct`](#method.plan_mdct) needs `len` elements of scratch, plus the scratch of its
+    /// inner DCT4, which is planned with [`plan_dct4_bounded`](#method.plan_dct4_bounded). If that doesn't fit, this
+    /// returns a [`MdctNaive`](mdct/struct.MdctNaive.html), which is O(n^2) but doesn't need any scratch, so unlike the
+    /// other bounded planning methods, this never fails. See `plan_dct2_bounded` for how to process the returned
+    /// instance without allocating.
+    ///
+    /// The returned instance is not cached.
+    pub fn plan_mdct_bounded<F>(
+        &mut self,
+        len: usize,
+        window_fn: F,
+        max_scratch_len: usize,
+    ) -> Arc<dyn Mdct<T>>
     where
         F: (FnOnce(usize) -> Vec<T>),
     {
-        //benchmarking shows that using the inner dct4 algorithm is always faster than computing the naive algorithm
-        let inner_dct4 = self.plan_dct4(len);
-        Arc::new(MdctViaDct4::new(inner_dct4, window_fn))
+        let window_fn = self.mdct_window_scaling.wrap_fn(window_fn);
+        if len <= max_scratch_len {
+            if let Ok(inner_dct4) = self.plan_dct4_bounded(len, max_scratch_len - len) {
+                return planned!(
+                    self,
+                    "MDCT",
+                    len,
+                    "DCT4",
+                    len,
+                    MdctViaDct4::new(inner_dct4, window_fn)
+                );
+            }
+        }
+        planned!(self, "MDCT", len, MdctNaive::new(len, window_fn))
+    }
+
+    fn plan_mdct_cached<F>(
+        &mut self,
+        len: usize,
+        window_mode: WindowMode,
+        create: F,
+    ) -> Arc<dyn Mdct<T>>
+    where
+        F: FnOnce(Arc<dyn TransformType4<T>>) -> MdctViaDct4<T>,
+    {
+        let key = (
+            len,
+            window_mode,
+            self.mdct_window_scaling,
+            self.accumulation,
+        );
+        if self.mdct_cache.contains_key(&key) {
+            Arc::clone(self.mdct_cache.get(&key).unwrap())
+        } else {
+            //benchmarking shows that using the inner dct4 algorithm is always faster than computing the naive algorithm
+            let inner_dct4 = self.plan_dct4(len);
+            // `create` calls the caller's window function, which may panic. The MDCT is only cached after it returns,
+            // so a caught panic doesn't leave a half-built instance in the cache
+            let result: Arc<dyn Mdct<T>> =
+                planned!(self, "MDCT", len, "DCT4", len, create(inner_dct4));
+            self.mdct_cache.insert(key, Arc::clone(&result));
+            result
+        }
+    }
+}
+
+// Clears a planner's `planning` flag when the outermost request that set it finishes or unwinds
+struct OutermostRequest(Arc<AtomicBool>);
+impl Drop for OutermostRequest {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
+
+impl DctPlanner<f32> {
+    /// Returns a DCT Type 2 instance which processes `f32` signals of size `len`, but computes the transform in `f64`.
+    /// See [`Type2And3MixedPrecision`](algorithm/struct.Type2And3MixedPrecision.html).
+    ///
+    /// The inner `f64` instance is planned the same way `plan_dct2` would plan it. The returned instance can also
+    /// compute the DCT3, DST2, and DST3. If this is called multiple times, it will attempt to re-use internal data
+    /// between instances.
+    pub fn plan_dct2_mixed_precision(&mut self, len: usize) -> Arc<dyn TransformType2And3<f32>> {
+        let key = (len, self.accumulation);
+        if self.mixed_precision_cache.contains_key(&key) {
+            Arc::clone(self.mixed_precision_cache.get(&key).unwrap())
+        } else {
+            let inner = self.mixed_precision_planner().plan_dct2(len);
+            let result: Arc<dyn TransformType2And3<f32>> = planned!(
+                self,
+                "DCT2",
+                len,
+                "DCT2",
+                len,
+                Type2And3MixedPrecision::new(inner)
+            );
+            self.mixed_precision_cache.insert(key, Arc::clone(&result));
+            result
+        }
+    }
+
+    /// Returns a MDCT instance which processes `f32` inputs of size `len * 2` and produces `f32` outputs of size `len`,
+    /// but computes the transform in `f64`. See [`MdctMixedPrecision`](mdct/struct.MdctMixedPrecision.html).
+    ///
+    /// `window_fn` is a function that takes a `size` and returns a `Vec` containing `size` `f64` window values.
+    /// See the [`window_fn`](mdct/window_fn/index.html) module for provided window functions.
+    ///
+    /// The inner `f64` DCT4 instance is planned the same way `plan_mdct` would plan it. If this is called multiple
+    /// times, it will attempt to re-use internal data between instances
+    pub fn plan_mdct_mixed_precision<F>(&mut self, len: usize, window_fn: F) -> Arc<dyn Mdct<f32>>
+    where
+        F: (FnOnce(usize) -> Vec<f64>),
+    {
+        let key = (len, self.mdct_window_scaling, self.accumulation);
+        if self.mixed_precision_mdct_cache.contains_key(&key) {
+            Arc::clone(self.mixed_precision_mdct_cache.get(&key).unwrap())
+        } else {
+            let inner_dct4 = self.mixed_precision_planner().plan_dct4(len);
+            let window_fn = self.mdct_window_scaling.wrap_fn(window_fn);
+            let result: Arc<dyn Mdct<f32>> = planned!(
+                self,
+                "MDCT",
+                len,
+                "DCT4",
+                len,
+                MdctMixedPrecision::new(inner_dct4, window_fn)
+            );
+            self.mixed_precision_mdct_cache
+                .insert(key, Arc::clone(&result));
+            result
+        }
+    }
+
+    // Returns the planner for the f64 inner instances of mixed-precision algorithms, creating it on first use
+    fn mixed_precision_planner(&mut self) -> &mut DctPlanner<f64> {
+        // the inner planner chooses its algorithms the same way this one does
+        let cost_model = self.cost_model;
+        let deterministic = self.is_deterministic();
+        let observer = self.progress_observer.clone();
+        let poor_plan_policy = self.poor_plan_policy;
+        let accumulation = self.accumulation;
+        let planner = self.mixed_precision_planner.get_or_insert_with(|| {
+            let mut planner = if deterministic {
+                DctPlanner::new_deterministic()
+            } else {
+                DctPlanner::with_cost_model(cost_model)
+            };
+            planner.progress_observer = observer;
+            planner.poor_plan_policy = poor_plan_policy;
+            Box::new(planner)
+        });
+        // the accumulation can change between calls, and the inner planner caches its instances separately for each one
+        planner.accumulation = accumulation;
+        planner
     }
 }
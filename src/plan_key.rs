@@ -0,0 +1,265 @@
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use crate::mdct::{Mdct, WindowMode};
+use crate::{Dct1, Dct2, Dct3, Dct4, Dct5, Dct6, Dct6And7, Dct7, Dct8, DctNum};
+use crate::{Dct3Convention, DstConvention};
+use crate::{
+    Dst1, Dst2, Dst3, Dst4, Dst5, Dst6, Dst6And7, Dst7, Dst8, TransformType2And3, TransformType4,
+};
+
+/// The set of transforms that a plan computes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PlanKind {
+    /// Computes the DCT1, like the plans returned by `DctPlanner::plan_dct1`
+    Dct1,
+    /// Computes the DCT2, DCT3, DST2, and DST3, like the plans returned by `DctPlanner::plan_dct2`
+    Type2And3,
+    /// Computes only the DCT2, like the plans returned by `DctPlanner::plan_dct2_with_output_order`
+    Dct2,
+    /// Computes the DCT4 and DST4, like the plans returned by `DctPlanner::plan_dct4`
+    Type4,
+    /// Computes the DCT5, like the plans returned by `DctPlanner::plan_dct5`
+    Dct5,
+    /// Computes the DCT6 and DCT7, like the plans returned by `DctPlanner::plan_dct6`
+    Dct6And7,
+    /// Computes the DCT8, like the plans returned by `DctPlanner::plan_dct8`
+    Dct8,
+    /// Computes the DST1, like the plans returned by `DctPlanner::plan_dst1`
+    Dst1,
+    /// Computes the DST5, like the plans returned by `DctPlanner::plan_dst5`
+    Dst5,
+    /// Computes the DST6 and DST7, like the plans returned by `DctPlanner::plan_dst6`
+    Dst6And7,
+    /// Computes the DST8, like the plans returned by `DctPlanner::plan_dst8`
+    Dst8,
+    /// Computes the MDCT and IMDCT, like the plans returned by `DctPlanner::plan_mdct`
+    Mdct,
+}
+
+/// Identifies what a plan computes: the kind of transform, its length, and any options that change its output, like the
+/// output order of a DCT2 or the window of a MDCT.
+///
+/// Two plans with equal keys compute the same outputs, up to rounding error, even if they use different algorithms or
+/// came from different planners. This makes `PlanKey` suitable as the key of a cache of data derived from plans, which
+/// doesn't need to keep the plans or the planner around.
+///
+/// ~~~
+/// use rustdct::{DctPlanner, PlanIdentity, PlanKind};
+///
+/// let dct2 = DctPlanner::<f32>::new().plan_dct2(100);
+/// let other_dct2 = DctPlanner::<f32>::new().plan_dct2(100);
+/// assert_eq!(dct2.plan_key(), other_dct2.plan_key());
+/// assert_eq!(dct2.plan_key().kind, PlanKind::Type2And3);
+///
+/// let derived_data = vec![(dct2.plan_key(), "some derived data")];
+/// let found = derived_data.iter().find(|(key, _)| *key == other_dct2.plan_key());
+/// assert_eq!(found.map(|(_, data)| *data), Some("some derived data"));
+/// ~~~
+///
+/// `PlanKey` implements `Hash` and `PartialEq`, but not `Eq`: MDCT windows are compared with `==`, so for float types,
+/// a key whose window contains a NaN isn't equal to itself. A hash table that needs `Eq` can wrap the key in a type
+/// that implements it, if the application never creates such windows.
+///
+/// Algorithms from outside this crate don't have to report a key. Trait objects like `Arc<dyn Dct2<T>>` of such an
+/// algorithm report an opaque key instead, which isn't equal to any key, including itself. See
+/// [`Dct1::known_plan_key`](trait.Dct1.html#method.known_plan_key).
+#[derive(Clone, Debug)]
+pub struct PlanKey<T> {
+    /// The set of transforms that the plan computes.
+    pub kind: PlanKind,
+    /// The length of the transform. For MDCT plans, this is the number of output coefficients.
+    pub len: usize,
+
+    // For DCT2 plans with a custom output order, the output index of each coefficient
+    output_order: Option<Arc<[usize]>>,
+    // For MDCT plans, the window mode and window
+    window: Option<(WindowMode, Arc<[T]>)>,
+    // For DST2/DST3 plans that follow another library's convention, that convention
+    dst_convention: Option<DstConvention>,
+    // For DCT3 plans that follow another library's convention, that convention
+    dct3_convention: Option<Dct3Convention>,
+    // True for plans that don't report their own key
+    opaque: bool,
+}
+
+impl<T> PlanKey<T> {
+    /// Creates a key for a plan that computes the transforms described by `kind`, with length `len`, and no other
+    /// options.
+    pub fn new(kind: PlanKind, len: usize) -> Self {
+        Self {
+            kind,
+            len,
+            output_order: None,
+            window: None,
+            dst_convention: None,
+            dct3_convention: None,
+            opaque: false,
+        }
+    }
+
+    // Adds the output index of each coefficient of a DCT2 plan with a custom output order
+    pub(crate) fn with_output_order(mut self, destinations: &[usize]) -> Self {
+        self.output_order = Some(destinations.into());
+        self
+    }
+
+    // Adds the window mode and window of a MDCT plan
+    pub(crate) fn with_window(mut self, window_mode: WindowMode, window: &[T]) -> Self
+    where
+        T: Clone,
+    {
+        self.window = Some((window_mode, window.into()));
+        self
+    }
+
+    // Adds the convention of a DST2/DST3 plan. This crate's own convention doesn't change the key
+    pub(crate) fn with_dst_convention(mut self, convention: DstConvention) -> Self {
+        if convention != DstConvention::Wang {
+            self.dst_convention = Some(convention);
+        }
+        self
+    }
+
+    // Adds the convention of a DCT3 plan. This crate's own convention doesn't change the key
+    pub(crate) fn with_dct3_convention(mut self, convention: Dct3Convention) -> Self {
+        if convention != Dct3Convention::Wang {
+            self.dct3_convention = Some(convention);
+        }
+        self
+    }
+}
+
+impl<T: PartialEq> PartialEq for PlanKey<T> {
+    fn eq(&self, other: &Self) -> bool {
+        !self.opaque
+            && !other.opaque
+            && self.kind == other.kind
+            && self.len == other.len
+            && self.output_order == other.output_order
+            && self.window == other.window
+            && self.dst_convention == other.dst_convention
+            && self.dct3_convention == other.dct3_convention
+    }
+}
+
+impl<T> Hash for PlanKey<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // the window values can't be hashed, since T might be a float. equal keys still have equal hashes, since
+        // they have equal window modes and window lengths
+        self.kind.hash(state);
+        self.len.hash(state);
+        self.output_order.hash(state);
+        self.window
+            .as_ref()
+            .map(|(window_mode, window)| (*window_mode, window.len()))
+            .hash(state);
+        self.dst_convention.hash(state);
+        self.dct3_convention.hash(state);
+    }
+}
+
+/// Reports what a plan computes, as a [`PlanKey`](struct.PlanKey.html).
+pub trait PlanIdentity<T> {
+    /// Returns a key which is equal to the key of every other plan that computes the same outputs.
+    fn plan_key(&self) -> PlanKey<T>;
+
+    /// Returns an owned butterfly of type `B` that computes the same outputs as this plan, or None if `B` computes a
+    /// different transform or length.
+    ///
+    /// Calls to the returned butterfly are statically dispatched, and can be inlined into the caller, which avoids the
+    /// `Arc` indirection and dynamic dispatch of a planned transform in hot loops over tiny sizes. See
+    /// [`FromPlanKey`](trait.FromPlanKey.html) for an example.
+    fn to_owned_butterfly<B: FromPlanKey<T>>(&self) -> Option<B>
+    where
+        Self: Sized,
+    {
+        B::from_plan_key(&self.plan_key())
+    }
+}
+impl<T, P: PlanIdentity<T> + ?Sized> PlanIdentity<T> for &P {
+    fn plan_key(&self) -> PlanKey<T> {
+        (**self).plan_key()
+    }
+}
+impl<T, P: PlanIdentity<T> + ?Sized> PlanIdentity<T> for Arc<P> {
+    fn plan_key(&self) -> PlanKey<T> {
+        (**self).plan_key()
+    }
+}
+impl<T, P: PlanIdentity<T> + ?Sized> PlanIdentity<T> for Box<P> {
+    fn plan_key(&self) -> PlanKey<T> {
+        (**self).plan_key()
+    }
+}
+
+// Returns `key` if the plan reported one, or an opaque key of kind `kind` if it didn't
+fn trait_object_key<T>(key: Option<PlanKey<T>>, kind: PlanKind, len: usize) -> PlanKey<T> {
+    key.unwrap_or_else(|| PlanKey {
+        opaque: true,
+        ..PlanKey::new(kind, len)
+    })
+}
+
+// `$hook_trait` is the transform trait whose `known_plan_key` a trait object calls. The traits that combine several
+// transform traits use the first one
+macro_rules! impl_trait_object_identity {
+    ($(($trait_name:ident, $hook_trait:ident, $kind:ident)),*) => {
+        $(
+            impl<T: DctNum> PlanIdentity<T> for dyn $trait_name<T> {
+                fn plan_key(&self) -> PlanKey<T> {
+                    trait_object_key($hook_trait::known_plan_key(self), PlanKind::$kind, self.len())
+                }
+            }
+        )*
+    };
+}
+
+impl_trait_object_identity!(
+    (Dct1, Dct1, Dct1),
+    (Dct2, Dct2, Dct2),
+    (Dct3, Dct3, Type2And3),
+    (Dct4, Dct4, Type4),
+    (Dct5, Dct5, Dct5),
+    (Dct6, Dct6, Dct6And7),
+    (Dct7, Dct7, Dct6And7),
+    (Dct8, Dct8, Dct8),
+    (Dst1, Dst1, Dst1),
+    (Dst2, Dst2, Type2And3),
+    (Dst3, Dst3, Type2And3),
+    (Dst4, Dst4, Type4),
+    (Dst5, Dst5, Dst5),
+    (Dst6, Dst6, Dst6And7),
+    (Dst7, Dst7, Dst6And7),
+    (Dst8, Dst8, Dst8),
+    (TransformType2And3, Dct2, Type2And3),
+    (TransformType4, Dct4, Type4),
+    (Dct6And7, Dct6, Dct6And7),
+    (Dst6And7, Dst6, Dst6And7),
+    (Mdct, Mdct, Mdct)
+);
+
+/// Implemented by the butterflies in [`rustdct::algorithm`](algorithm/index.html), which can be created from the key of
+/// any plan that computes the same transform with the same length.
+///
+/// Use it through [`PlanIdentity::to_owned_butterfly`](trait.PlanIdentity.html#method.to_owned_butterfly), to turn a
+/// planned transform of a known small size into a concrete type:
+///
+/// ~~~
+/// use rustdct::algorithm::type2and3_butterflies::Type2And3Butterfly8;
+/// use rustdct::{Dct2, DctPlanner, PlanIdentity};
+///
+/// let mut planner = DctPlanner::<f32>::new();
+/// let dct2 = planner.plan_dct2(8);
+///
+/// let butterfly: Type2And3Butterfly8<f32> = dct2.to_owned_butterfly().unwrap();
+/// let mut buffer = vec![1f32; 8];
+/// butterfly.process_dct2_with_scratch(&mut buffer, &mut []);
+///
+/// // a butterfly can't be created from a plan of a different size
+/// assert!(planner.plan_dct2(16).to_owned_butterfly::<Type2And3Butterfly8<f32>>().is_none());
+/// ~~~
+pub trait FromPlanKey<T>: Sized {
+    /// Creates the butterfly if it computes the same outputs as plans with this key, or returns None otherwise.
+    fn from_plan_key(key: &PlanKey<T>) -> Option<Self>;
+}
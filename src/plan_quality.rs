@@ -0,0 +1,160 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::DctType;
+
+// FFT sizes with a prime factor larger than this are `Poor`. RustFFT computes huge primes with Bluestein's algorithm,
+// which pads them to a power-of-two FFT more than twice as long, with scratch to match
+pub(crate) const POOR_PRIME_FACTOR: usize = 1 << 20;
+
+// DCT5 and DST5 sizes larger than this are `Poor`, since their only algorithm is O(n^2)
+pub(crate) const POOR_NAIVE_LEN: usize = 256;
+
+/// Why a size isn't planned with one of the fastest algorithms, returned as part of a
+/// [`PlanQuality`](enum.PlanQuality.html).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum QualityIssue {
+    /// The size is converted to FFTs whose largest prime factor is `prime_factor`. `fft_len` is the size of the FFT
+    /// that the transform is equivalent to, although some algorithms compute it with a FFT of half or twice that size.
+    /// RustFFT has butterflies for prime factors up to 31, and computes larger ones with Rader's or Bluestein's
+    /// algorithm, which are several times slower than nearby sizes, and need more scratch.
+    LargePrimeFactor { fft_len: usize, prime_factor: usize },
+    /// The transform type only has a naive O(n^2) algorithm for sizes above its butterflies.
+    NaiveOnly,
+}
+
+impl fmt::Display for QualityIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            QualityIssue::LargePrimeFactor {
+                fft_len,
+                prime_factor,
+            } => write!(
+                f,
+                "The inner FFT of size {} has a prime factor of {}",
+                fft_len, prime_factor
+            ),
+            QualityIssue::NaiveOnly => write!(f, "The only algorithm for this size is O(n^2)"),
+        }
+    }
+}
+
+/// How fast a planner's algorithm for a size is compared to the nearby sizes, returned by
+/// [`DctPlanner::plan_quality`](struct.DctPlanner.html#method.plan_quality).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PlanQuality {
+    /// The size is planned with one of the fastest algorithms, as defined by
+    /// [`DctPlanner::is_fast_size`](struct.DctPlanner.html#method.is_fast_size).
+    Good,
+    /// The size is slower than nearby fast sizes, but not dramatically.
+    Acceptable(QualityIssue),
+    /// The size is much slower than nearby fast sizes, like a prime size larger than 2^20, or a DCT5 larger than 256.
+    /// Applications that can pad their signals should pad them to
+    /// [`DctPlanner::recommended_fast_size_above`](struct.DctPlanner.html#method.recommended_fast_size_above).
+    Poor(QualityIssue),
+}
+
+impl PlanQuality {
+    /// Returns the reason this size isn't `Good`, or None if it is.
+    pub fn issue(&self) -> Option<QualityIssue> {
+        match *self {
+            PlanQuality::Good => None,
+            PlanQuality::Acceptable(issue) | PlanQuality::Poor(issue) => Some(issue),
+        }
+    }
+
+    /// Returns true for `Poor`.
+    pub fn is_poor(&self) -> bool {
+        match self {
+            PlanQuality::Poor(_) => true,
+            _ => false,
+        }
+    }
+}
+
+/// What a [`DctPlanner`](struct.DctPlanner.html) does when it plans a size whose
+/// [`plan_quality`](struct.DctPlanner.html#method.plan_quality) is `Poor`.
+///
+/// Services that would rather fail than run a much slower transform should plan with the planner's `try_plan_*`
+/// methods, like [`try_plan_dct2`](struct.DctPlanner.html#method.try_plan_dct2), which return a
+/// [`PoorPlanError`](struct.PoorPlanError.html) instead of planning a poor size.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PoorPlanPolicy {
+    /// Plan the size anyway. This is the default.
+    Allow,
+    /// Plan the size anyway, and emit a warning with the `log` crate. Only available with the `log` feature.
+    #[cfg(feature = "log")]
+    Warn,
+}
+
+/// Returned by [`DctPlanner::check_plan_quality`](struct.DctPlanner.html#method.check_plan_quality) and the planner's
+/// `try_plan_*` methods when a size's plan quality is `Poor`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PoorPlanError {
+    /// The transform that was requested
+    pub kind: DctType,
+    /// The requested size
+    pub len: usize,
+    /// Why the size is poor
+    pub issue: QualityIssue,
+    /// The smallest size above `len` that's fast, if there is one
+    pub recommended_len: Option<usize>,
+}
+
+impl fmt::Display for PoorPlanError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "The {:?} of size {} would be much slower than nearby sizes: {}",
+            self.kind, self.len, self.issue
+        )?;
+        if let Some(recommended_len) = self.recommended_len {
+            write!(f, ". Consider padding to size {}", recommended_len)?;
+        }
+        Ok(())
+    }
+}
+impl Error for PoorPlanError {}
+
+// Returns the largest prime factor of `len`, or 1 for 0 and 1
+pub(crate) fn largest_prime_factor(mut len: usize) -> usize {
+    if len < 2 {
+        return 1;
+    }
+    let mut largest = 1;
+    let mut factor = 2;
+    while factor <= len / factor {
+        while len % factor == 0 {
+            len /= factor;
+            largest = factor;
+        }
+        factor += if factor == 2 { 1 } else { 2 };
+    }
+    if len > 1 {
+        len
+    } else {
+        largest
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn test_largest_prime_factor() {
+        let expected = [
+            (0, 1),
+            (1, 1),
+            (2, 2),
+            (12, 3),
+            (49, 7),
+            (1009, 1009),
+            (1024, 2),
+            (2 * 1_048_583, 1_048_583),
+        ];
+        for &(len, factor) in expected.iter() {
+            assert_eq!(largest_prime_factor(len), factor, "len = {}", len);
+        }
+    }
+}
@@ -0,0 +1,22 @@
+//! Re-exports the planner, the high-level [`Transform`](../struct.Transform.html) builder, and the traits that are
+//! needed to call the methods of planned transforms, so that a single import covers the common uses of RustDCT.
+//!
+//! ~~~
+//! // Computes a DCT Type 4 of size 1234 with the planner's own instance
+//! use rustdct::prelude::*;
+//!
+//! let mut planner = DctPlanner::new();
+//! let dct4 = planner.plan_dct4(1234);
+//!
+//! let mut buffer = vec![0f32; dct4.len()];
+//! let mut scratch = vec![0f32; dct4.get_scratch_len()];
+//! dct4.process_dct4_with_scratch(&mut buffer, &mut scratch);
+//! ~~~
+
+pub use rustfft::Length;
+
+pub use crate::mdct::Mdct;
+pub use crate::{Dct1, Dct2, Dct3, Dct4, Dct5, Dct6, Dct6And7, Dct7, Dct8};
+pub use crate::{DctNum, DctPlanner, DctTransform, DctType, Normalization, RequiredScratch};
+pub use crate::{Dst1, Dst2, Dst3, Dst4, Dst5, Dst6, Dst6And7, Dst7, Dst8};
+pub use crate::{PlannedTransform, Transform, TransformType2And3, TransformType4};
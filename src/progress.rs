@@ -0,0 +1,77 @@
+use std::sync::Arc;
+
+/// A stage of planning, reported to a [`ProgressObserver`](trait.ProgressObserver.html).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PlanningStage {
+    /// Planning an inner FFT of size `len` with RustFFT's planner. RustFFT caches the FFTs it plans, so this stage is
+    /// only slow the first time each size is planned.
+    FftPlanning { len: usize },
+    /// Creating an algorithm instance of size `len` for `transform`, like `"DCT2"` or `"MDCT"`. This stage starts after
+    /// the instance's inner FFTs and DCTs are planned, so its time is spent computing the instance's twiddle factors,
+    /// and for MDCTs, its window.
+    TwiddleGeneration { transform: &'static str, len: usize },
+}
+
+/// Receives progress reports from a [`DctPlanner`](struct.DctPlanner.html), for displaying the progress of planning
+/// huge sizes, or for profiling setup time.
+///
+/// Set it with [`DctPlanner::set_progress_observer`](struct.DctPlanner.html#method.set_progress_observer). The planner
+/// calls the observer synchronously, from the thread that's planning, before and after each stage. Stages can be
+/// nested, for example when creating an instance plans another instance, but every `stage_finished` call matches the
+/// most recent `stage_started` call that hasn't finished yet. If planning panics, the unfinished stages are never
+/// finished.
+///
+/// ~~~
+/// // Measures how long the planner spends planning FFTs
+/// use std::sync::{Arc, Mutex};
+/// use std::time::{Duration, Instant};
+/// use rustdct::{DctPlanner, PlanningStage, ProgressObserver};
+///
+/// #[derive(Default)]
+/// struct Profiler {
+///     started: Mutex<Vec<Instant>>,
+///     fft_time: Mutex<Duration>,
+/// }
+/// impl ProgressObserver for Profiler {
+///     fn stage_started(&self, _stage: PlanningStage) {
+///         self.started.lock().unwrap().push(Instant::now());
+///     }
+///     fn stage_finished(&self, stage: PlanningStage) {
+///         let started = self.started.lock().unwrap().pop().unwrap();
+///         if let PlanningStage::FftPlanning { .. } = stage {
+///             *self.fft_time.lock().unwrap() += started.elapsed();
+///         }
+///     }
+/// }
+///
+/// let profiler = Arc::new(Profiler::default());
+/// let mut planner = DctPlanner::<f32>::new();
+/// planner.set_progress_observer(profiler.clone());
+/// planner.plan_dct2(100_000);
+///
+/// println!("planning FFTs took {:?}", profiler.fft_time.lock().unwrap());
+/// ~~~
+pub trait ProgressObserver: Send + Sync {
+    /// Called when the planner starts `stage`.
+    fn stage_started(&self, stage: PlanningStage);
+
+    /// Called when the planner finishes `stage`. Does nothing by default.
+    fn stage_finished(&self, _stage: PlanningStage) {}
+}
+
+// Runs `f`, reporting `stage` to `observer` before and after it
+pub(crate) fn observe_stage<R>(
+    observer: Option<Arc<dyn ProgressObserver>>,
+    stage: PlanningStage,
+    f: impl FnOnce() -> R,
+) -> R {
+    match observer {
+        Some(observer) => {
+            observer.stage_started(stage);
+            let result = f();
+            observer.stage_finished(stage);
+            result
+        }
+        None => f(),
+    }
+}
@@ -0,0 +1,76 @@
+//! Utilities for working out the scale of each transform's output.
+//!
+//! None of the transforms in RustDCT normalize their outputs, so applying a transform followed by its inverse
+//! multiplies the signal by a constant that depends on the transform type and size. The functions in this module
+//! expose those constants, so that callers don't have to determine them empirically.
+//!
+//! ~~~
+//! // Computes a DCT Type 2 of size 100, then inverts it with a DCT Type 3 and rescales the result
+//! use rustdct::scaling::scaling_factor;
+//! use rustdct::{DctPlanner, DctType};
+//!
+//! let len = 100;
+//! let mut planner = DctPlanner::new();
+//! let dct = planner.plan_dct2(len);
+//!
+//! let mut buffer = vec![1f32; len];
+//! dct.process_dct2(&mut buffer);
+//! dct.process_dct3(&mut buffer);
+//!
+//! let scale: f32 = scaling_factor(DctType::Dct2, len);
+//! for value in buffer.iter_mut() {
+//!     *value = *value * scale;
+//! }
+//! ~~~
+
+use crate::{DctNum, DctType};
+
+/// Returns the factor that the output of the inverse transform needs to be multiplied by to recover the original input.
+///
+/// In other words, if `forward` is a transform of type `kind` and `inverse` is a transform of type `kind.inverse()`,
+/// then `inverse(forward(x)) * scaling_factor(kind, len)` is equal to `x`. Since the factor only depends on which
+/// pair of transforms is used, `scaling_factor(kind, len)` is always equal to `scaling_factor(kind.inverse(), len)`.
+pub fn scaling_factor<T: DctNum>(kind: DctType, len: usize) -> T {
+    let len = len as f64;
+    let denominator = match kind {
+        DctType::Dct1 => len - 1.0,
+        DctType::Dst1 => len + 1.0,
+        DctType::Dct2
+        | DctType::Dct3
+        | DctType::Dct4
+        | DctType::Dst2
+        | DctType::Dst3
+        | DctType::Dst4 => len,
+        DctType::Dct5 | DctType::Dct6 | DctType::Dct7 | DctType::Dst8 => len - 0.5,
+        DctType::Dct8 | DctType::Dst5 | DctType::Dst6 | DctType::Dst7 => len + 0.5,
+    };
+    T::from_f64(2.0 / denominator).unwrap()
+}
+
+/// Returns the ratio between the energy of the output of a transform of type `kind` and the energy of its input, if
+/// that ratio is the same for every input.
+///
+/// The ratio is only constant when the unnormalized transform matrix is symmetric, which is the case for the DST1,
+/// DCT4, DST4, DCT8, and DST5. For those types, the ratio is `1 / scaling_factor(kind, len)`. For every other type,
+/// the ratio depends on the input, and this returns `None`.
+pub fn expected_energy_ratio<T: DctNum>(kind: DctType, len: usize) -> Option<T> {
+    match kind {
+        DctType::Dst1 | DctType::Dct4 | DctType::Dst4 | DctType::Dct8 | DctType::Dst5 => {
+            Some(T::one() / scaling_factor::<T>(kind, len))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the ratio between the energy (the sum of squares) of `output` and the energy of `input`.
+///
+/// This is useful for checking Parseval's theorem against the values returned by `expected_energy_ratio`.
+/// If `input` contains only zeroes, the result is infinite or NaN.
+pub fn energy_ratio<T: DctNum>(input: &[T], output: &[T]) -> T {
+    let energy = |buffer: &[T]| {
+        buffer
+            .iter()
+            .fold(T::zero(), |sum, value| sum + *value * *value)
+    };
+    energy(output) / energy(input)
+}
@@ -0,0 +1,132 @@
+/// A region of a plan's scratch buffer, returned as part of a [`ScratchLayout`](struct.ScratchLayout.html).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ScratchRegion {
+    /// What the plan stores in this region, like `"FFT buffer"` or `"FFT scratch"`
+    pub name: &'static str,
+    /// The index of the region's first element in the scratch buffer
+    pub offset: usize,
+    /// The number of elements in the region. Complex regions hold `len / 2` complex numbers.
+    pub len: usize,
+    /// Whether the plan views this region as `Complex<T>`, which requires it to be aligned for `Complex<T>`
+    pub complex: bool,
+    /// 0 for the regions of the plan itself, 1 for the regions of its inner plan, 2 for the inner plan's inner plan,
+    /// and so on
+    pub depth: usize,
+}
+
+/// Describes how a plan partitions its scratch buffer into named regions, for debugging memory usage, or for
+/// allocators that want to place the regions separately.
+///
+/// Returned by [`RequiredScratch::scratch_layout`](trait.RequiredScratch.html#method.scratch_layout). The regions are
+/// in order, don't overlap, and together cover the whole scratch buffer, so their lengths add up to the plan's
+/// `get_scratch_len()`. A plan that hands part of its scratch to an inner plan lists the inner plan's regions after its
+/// own, with a larger `depth`.
+///
+/// ~~~
+/// use rustdct::{DctPlanner, RequiredScratch};
+///
+/// let mut planner = DctPlanner::<f32>::new();
+/// let dct4 = planner.plan_dct4(1001);
+///
+/// let layout = dct4.scratch_layout();
+/// assert_eq!(layout.total_len(), dct4.get_scratch_len());
+/// for region in layout.regions() {
+///     println!("{:>2} {:<12} {} elements", region.depth, region.name, region.len);
+/// }
+/// ~~~
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScratchLayout {
+    regions: Vec<ScratchRegion>,
+}
+
+impl ScratchLayout {
+    /// Creates a layout without any regions, for a plan that doesn't need scratch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a region of `len` elements after the existing ones, and returns the layout. Regions with a length of 0
+    /// are skipped.
+    #[must_use]
+    pub fn with_region(mut self, name: &'static str, len: usize, complex: bool) -> Self {
+        if len > 0 {
+            let offset = self.total_len();
+            self.regions.push(ScratchRegion {
+                name,
+                offset,
+                len,
+                complex,
+                depth: 0,
+            });
+        }
+        self
+    }
+
+    /// Appends the regions of an inner plan's layout after the existing ones, one level deeper, and returns the layout.
+    #[must_use]
+    pub fn with_inner(mut self, inner: ScratchLayout) -> Self {
+        let offset = self.total_len();
+        self.regions
+            .extend(inner.regions.into_iter().map(|region| ScratchRegion {
+                offset: offset + region.offset,
+                depth: region.depth + 1,
+                ..region
+            }));
+        self
+    }
+
+    /// Returns the regions in order.
+    pub fn regions(&self) -> &[ScratchRegion] {
+        &self.regions
+    }
+
+    /// Returns the first region named `name`, if there is one.
+    pub fn region(&self, name: &str) -> Option<&ScratchRegion> {
+        self.regions.iter().find(|region| region.name == name)
+    }
+
+    /// Returns the total number of elements in all regions.
+    pub fn total_len(&self) -> usize {
+        self.regions
+            .last()
+            .map_or(0, |region| region.offset + region.len)
+    }
+}
+
+// The layout of the algorithms that convert a transform to a FFT: `buffer_len` elements of FFT buffer, followed by the
+// FFT's own scratch, both viewed as complex numbers
+pub(crate) fn fft_layout(buffer_len: usize, scratch_len: usize) -> ScratchLayout {
+    ScratchLayout::new()
+        .with_region("FFT buffer", buffer_len, true)
+        .with_region("FFT scratch", scratch_len - buffer_len, true)
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+
+    #[test]
+    fn test_scratch_layout() {
+        let inner = ScratchLayout::new()
+            .with_region("FFT buffer", 20, true)
+            .with_region("FFT scratch", 0, true);
+        let layout = ScratchLayout::new()
+            .with_region("lane buffer", 7, false)
+            .with_inner(inner);
+
+        assert_eq!(layout.total_len(), 27);
+        assert_eq!(layout.regions().len(), 2);
+        assert_eq!(
+            layout.region("FFT buffer"),
+            Some(&ScratchRegion {
+                name: "FFT buffer",
+                offset: 7,
+                len: 20,
+                complex: true,
+                depth: 1,
+            })
+        );
+        assert_eq!(layout.region("FFT scratch"), None);
+        assert_eq!(ScratchLayout::new().total_len(), 0);
+    }
+}
@@ -0,0 +1,147 @@
+//! Evaluation of discrete cosine series at arbitrary points, for resampling to non-uniform grids.
+//!
+//! The DCT Type 2 of `N` samples `x[n]` gives the coefficients of the cosine series that interpolates them:
+//!
+//! `f(t) = X[0] / N + (2 / N) * sum(X[k] * cos(PI * k * (t + 0.5) / N))`, for `k` in `1..N`,
+//!
+//! where `t` is a position measured in samples, so `f(n) == x[n]` at every sample. The DCT3 evaluates this series at
+//! the sample positions, but resampling to a different grid needs it at other positions, which
+//! [`evaluate_series`](fn.evaluate_series.html) computes with the Clenshaw recurrence, in O(N) per point. The
+//! `1 / N` and `2 / N` scales are applied internally, so the coefficients can come straight from `process_dct2`.
+//!
+//! ~~~
+//! // Resamples 8 samples to a grid that's denser in the middle
+//! use rustdct::series::evaluate_series;
+//! use rustdct::DctPlanner;
+//!
+//! let mut planner = DctPlanner::new();
+//! let mut coefficients = vec![1f64, 3.0, 4.0, 2.0, 0.0, -1.0, 0.5, 2.0];
+//! planner.plan_dct2(8).process_dct2(&mut coefficients);
+//!
+//! let points = [0.0, 2.0, 3.25, 3.5, 3.75, 5.0, 7.0];
+//! let mut resampled = vec![0.0; points.len()];
+//! evaluate_series(&coefficients, &points, &mut resampled);
+//!
+//! // the points that are on the original grid reproduce the original samples
+//! assert!((resampled[0] - 1.0).abs() < 1e-10);
+//! assert!((resampled[1] - 4.0).abs() < 1e-10);
+//! ~~~
+
+use rustfft::num_traits::Float;
+
+use crate::DctNum;
+
+/// Evaluates the cosine series with the DCT Type 2 coefficients `coeffs` at each of `points`, and writes the values to
+/// `out`.
+///
+/// `coeffs` is the unnormalized output of a DCT2 of `coeffs.len()` samples, and each point is a position measured in
+/// samples, so point `n` evaluates to sample `n`, and point `n + 0.5` is halfway between samples `n` and `n + 1`. Points
+/// outside of `-0.5..coeffs.len() - 0.5` evaluate the even, periodic extension of the samples that the DCT2 implies.
+/// See the [module documentation](index.html) for the series.
+///
+/// If `coeffs` is empty, every value is zero.
+///
+/// # Panics
+///
+/// Panics if `points.len()` isn't equal to `out.len()`.
+pub fn evaluate_series<T: DctNum + Float>(coeffs: &[T], points: &[T], out: &mut [T]) {
+    assert_eq!(
+        points.len(),
+        out.len(),
+        "The number of points must match the number of outputs. Expected out len = {}, got out len = {}",
+        points.len(),
+        out.len()
+    );
+
+    let (first, rest) = match coeffs.split_first() {
+        Some(split) => split,
+        None => {
+            for value in out.iter_mut() {
+                *value = T::zero();
+            }
+            return;
+        }
+    };
+
+    let len = T::from_usize(coeffs.len()).unwrap();
+    let first_scale = T::one() / len;
+    let scale = T::two() / len;
+    let angle_scale = T::from_f64(std::f64::consts::PI).unwrap() / len;
+
+    for (point, value) in points.iter().zip(out.iter_mut()) {
+        // cos(k * angle) is the Chebyshev polynomial T_k(cos(angle)), so the series can be summed from the highest
+        // coefficient down with b_k = a_k + 2 * cos(angle) * b_(k+1) - b_(k+2)
+        let cosine = ((*point + T::half()) * angle_scale).cos();
+        let doubled_cosine = cosine * T::two();
+
+        let mut b1 = T::zero();
+        let mut b2 = T::zero();
+        for coefficient in rest.iter().rev() {
+            let b0 = *coefficient * scale + doubled_cosine * b1 - b2;
+            b2 = b1;
+            b1 = b0;
+        }
+        *value = *first * first_scale + cosine * b1 - b2;
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::algorithm::Type2And3Naive;
+    use crate::test_utils::{compare_float_vectors, random_signal};
+    use crate::Dct2;
+    use std::f32::consts::PI;
+
+    /// Verify that the series reproduces the samples on the grid, and matches the direct sum between them
+    #[test]
+    fn test_evaluate_series() {
+        for len in 1..20 {
+            let samples: Vec<f32> = random_signal(len);
+            let mut coeffs = samples.clone();
+            Type2And3Naive::new(len).process_dct2(&mut coeffs);
+
+            let grid: Vec<f32> = (0..len).map(|n| n as f32).collect();
+            let mut actual = vec![0.0; len];
+            evaluate_series(&coeffs, &grid, &mut actual);
+            assert!(compare_float_vectors(&samples, &actual), "len = {}", len);
+
+            let points = [
+                -0.5,
+                0.3,
+                len as f32 / 3.0,
+                len as f32 - 0.5,
+                len as f32 + 2.0,
+            ];
+            let expected: Vec<f32> = points
+                .iter()
+                .map(|t| {
+                    coeffs[0] / len as f32
+                        + (1..len)
+                            .map(|k| {
+                                let angle = PI * k as f32 * (t + 0.5) / len as f32;
+                                2.0 / len as f32 * coeffs[k] * angle.cos()
+                            })
+                            .sum::<f32>()
+                })
+                .collect();
+            let mut actual = vec![0.0; points.len()];
+            evaluate_series(&coeffs, &points, &mut actual);
+            assert!(compare_float_vectors(&expected, &actual), "len = {}", len);
+        }
+    }
+
+    #[test]
+    fn test_evaluate_empty_series() {
+        let mut out = vec![1f32; 3];
+        evaluate_series(&[], &[0.0, 1.0, 2.0], &mut out);
+        assert_eq!(out, vec![0.0; 3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "The number of points must match the number of outputs")]
+    fn test_evaluate_series_wrong_out_len() {
+        let mut out = vec![0f32; 2];
+        evaluate_series(&[1.0, 2.0], &[0.0, 1.0, 2.0], &mut out);
+    }
+}
@@ -0,0 +1,232 @@
+//! Portable SIMD kernels for the butterflies and the pre- and post-processing loops, written with `std::simd`.
+//!
+//! These are only compiled when the `portable-simd` feature is enabled, which requires a nightly compiler. Unlike the
+//! x86 kernels in `array_utils::transpose`, they compile to the vector instructions of any target that has them,
+//! including wasm and RISC-V, without any per-architecture code.
+//!
+//! Each kernel performs exactly the same floating-point operations as the scalar loop it replaces, in the same order,
+//! lane by lane, so the outputs are bit-identical, and the deterministic planner's outputs don't change. Each
+//! function returns true if it handled the element type, and false for element types other than `f32` and `f64`, in
+//! which case the caller runs its scalar loop.
+
+use std::any::TypeId;
+use std::simd::Simd;
+
+use rustfft::num_complex::Complex;
+
+// The number of elements in each vector. 4 lanes of `f64` fill an AVX register, and every target that std::simd supports
+// lowers wider vectors into several registers of its own width
+const LANES: usize = 4;
+
+// Reinterprets `slice` as a slice of `U`. `T` and `U` must be the same type
+unsafe fn cast<T, U>(slice: &[T]) -> &[U] {
+    &*(slice as *const [T] as *const [U])
+}
+unsafe fn cast_mut<T, U>(slice: &mut [T]) -> &mut [U] {
+    &mut *(slice as *mut [T] as *mut [U])
+}
+
+// Reinterprets a slice of complex numbers as a slice of interleaved real and imaginary parts
+fn flatten<T>(slice: &[Complex<T>]) -> &[T] {
+    // Safety: `Complex<T>` is `repr(C)`, with the real part followed by the imaginary part
+    unsafe { std::slice::from_raw_parts(slice.as_ptr() as *const T, slice.len() * 2) }
+}
+fn flatten_mut<T>(slice: &mut [Complex<T>]) -> &mut [T] {
+    // Safety: `Complex<T>` is `repr(C)`, with the real part followed by the imaginary part
+    unsafe { std::slice::from_raw_parts_mut(slice.as_mut_ptr() as *mut T, slice.len() * 2) }
+}
+
+// Runs `$kernel` with the arguments reinterpreted as `f32` or `f64` slices, if `$t` is one of them, and returns true.
+// Otherwise, returns false
+macro_rules! dispatch {
+    ($t:ty, $kernel:ident, ($($slice:ident),*), ($($slice_mut:ident),*)) => {{
+        if TypeId::of::<$t>() == TypeId::of::<f32>() {
+            // Safety: T is f32
+            unsafe { $kernel::f32($(cast($slice)),*, $(cast_mut($slice_mut)),*) };
+            true
+        } else if TypeId::of::<$t>() == TypeId::of::<f64>() {
+            // Safety: T is f64
+            unsafe { $kernel::f64($(cast($slice)),*, $(cast_mut($slice_mut)),*) };
+            true
+        } else {
+            false
+        }
+    }};
+}
+
+/// Computes `sums[i] = buffer[i] + buffer[len - 1 - i]` and `differences[i] = buffer[i] - buffer[len - 1 - i]`, for
+/// each `i` in `0..sums.len()`, where `len` is `buffer.len()`. `sums` and `differences` must have the same length, and
+/// `buffer` must be at least twice as long.
+pub fn fold_mirrored<T: 'static>(buffer: &[T], sums: &mut [T], differences: &mut [T]) -> bool {
+    dispatch!(T, fold_mirrored_kernel, (buffer), (sums, differences))
+}
+
+/// Computes `output[k] = (values[k] * twiddles[k]).re` for each `k` in `0..output.len()`. `values` and `twiddles`
+/// must be at least as long as `output`.
+pub fn rotate_real<T: 'static>(
+    values: &[Complex<T>],
+    twiddles: &[Complex<T>],
+    output: &mut [T],
+) -> bool {
+    let values = flatten(values);
+    let twiddles = flatten(twiddles);
+    dispatch!(T, rotate_real_kernel, (values, twiddles), (output))
+}
+
+/// Computes `output[i] = Complex::new(input[i], input[len - i]) * twiddles[i] * half` for each `i` in `1..len`, where
+/// `len` is `input.len()`, and `half` is 0.5. `twiddles` and `output` must have the same length as `input`. `output[0]`
+/// isn't modified.
+pub fn twiddle_mirrored_pairs<T: 'static>(
+    input: &[T],
+    twiddles: &[Complex<T>],
+    output: &mut [Complex<T>],
+) -> bool {
+    let twiddles = flatten(twiddles);
+    let output = flatten_mut(output);
+    dispatch!(
+        T,
+        twiddle_mirrored_pairs_kernel,
+        (input, twiddles),
+        (output)
+    )
+}
+
+macro_rules! kernels {
+    ($t:ident) => {
+        pub fn $t(buffer: &[$t], sums: &mut [$t], differences: &mut [$t]) {
+            let len = buffer.len();
+            let vector_end = sums.len() / LANES * LANES;
+            for i in (0..vector_end).step_by(LANES) {
+                let lower = Simd::<$t, LANES>::from_slice(&buffer[i..]);
+                let upper = Simd::<$t, LANES>::from_slice(&buffer[len - i - LANES..]).reverse();
+                (lower + upper).copy_to_slice(&mut sums[i..]);
+                (lower - upper).copy_to_slice(&mut differences[i..]);
+            }
+            for i in vector_end..sums.len() {
+                sums[i] = buffer[i] + buffer[len - 1 - i];
+                differences[i] = buffer[i] - buffer[len - 1 - i];
+            }
+        }
+    };
+}
+mod fold_mirrored_kernel {
+    use super::*;
+    kernels!(f32);
+    kernels!(f64);
+}
+
+macro_rules! kernels {
+    ($t:ident) => {
+        pub fn $t(values: &[$t], twiddles: &[$t], output: &mut [$t]) {
+            let vector_end = output.len() / LANES * LANES;
+            for k in (0..vector_end).step_by(LANES) {
+                let (value_re, value_im) = Simd::<$t, LANES>::from_slice(&values[2 * k..])
+                    .deinterleave(Simd::from_slice(&values[2 * k + LANES..]));
+                let (twiddle_re, twiddle_im) = Simd::<$t, LANES>::from_slice(&twiddles[2 * k..])
+                    .deinterleave(Simd::from_slice(&twiddles[2 * k + LANES..]));
+                (value_re * twiddle_re - value_im * twiddle_im).copy_to_slice(&mut output[k..]);
+            }
+            for k in vector_end..output.len() {
+                let value = Complex::new(values[2 * k], values[2 * k + 1]);
+                let twiddle = Complex::new(twiddles[2 * k], twiddles[2 * k + 1]);
+                output[k] = (value * twiddle).re;
+            }
+        }
+    };
+}
+mod rotate_real_kernel {
+    use super::*;
+    kernels!(f32);
+    kernels!(f64);
+}
+
+macro_rules! kernels {
+    ($t:ident) => {
+        pub fn $t(input: &[$t], twiddles: &[$t], output: &mut [$t]) {
+            let len = input.len();
+            let half = Simd::<$t, LANES>::splat(0.5);
+            let mut i = 1;
+            while i + LANES <= len {
+                let re = Simd::<$t, LANES>::from_slice(&input[i..]);
+                let im = Simd::<$t, LANES>::from_slice(&input[len - i - LANES + 1..]).reverse();
+                let (twiddle_re, twiddle_im) = Simd::<$t, LANES>::from_slice(&twiddles[2 * i..])
+                    .deinterleave(Simd::from_slice(&twiddles[2 * i + LANES..]));
+
+                let product_re = re * twiddle_re - im * twiddle_im;
+                let product_im = re * twiddle_im + im * twiddle_re;
+                let (first, second) = (product_re * half).interleave(product_im * half);
+                first.copy_to_slice(&mut output[2 * i..]);
+                second.copy_to_slice(&mut output[2 * i + LANES..]);
+                i += LANES;
+            }
+            for i in i..len {
+                let c = Complex::new(input[i], input[len - i]);
+                let twiddle = Complex::new(twiddles[2 * i], twiddles[2 * i + 1]);
+                let product = c * twiddle * 0.5;
+                output[2 * i] = product.re;
+                output[2 * i + 1] = product.im;
+            }
+        }
+    };
+}
+mod twiddle_mirrored_pairs_kernel {
+    use super::*;
+    kernels!(f32);
+    kernels!(f64);
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::test_utils::random_signal;
+
+    fn random_complex(len: usize) -> Vec<Complex<f32>> {
+        let values: Vec<f32> = random_signal(len * 2);
+        values
+            .chunks_exact(2)
+            .map(|pair| Complex::new(pair[0], pair[1]))
+            .collect()
+    }
+
+    /// Verify that the kernels are bit-identical to the scalar loops they replace, including the scalar tails
+    #[test]
+    fn test_kernels_match_scalar() {
+        for len in 1..40 {
+            let buffer: Vec<f32> = random_signal(len * 2 + 1);
+            let mut sums = vec![0f32; len];
+            let mut differences = vec![0f32; len];
+            assert!(fold_mirrored(&buffer, &mut sums, &mut differences));
+            for i in 0..len {
+                assert_eq!(sums[i], buffer[i] + buffer[len * 2 - i]);
+                assert_eq!(differences[i], buffer[i] - buffer[len * 2 - i]);
+            }
+
+            let values = random_complex(len);
+            let twiddles = random_complex(len);
+            let mut output = vec![0f32; len];
+            assert!(rotate_real(&values, &twiddles, &mut output));
+            for k in 0..len {
+                assert_eq!(output[k], (values[k] * twiddles[k]).re);
+            }
+
+            let input: Vec<f32> = random_signal(len);
+            let mut output = vec![Complex::new(0f32, 0f32); len];
+            assert!(twiddle_mirrored_pairs(&input, &twiddles, &mut output));
+            assert_eq!(output[0], Complex::new(0.0, 0.0));
+            for i in 1..len {
+                let c = Complex::new(input[i], input[len - i]);
+                assert_eq!(output[i], c * twiddles[i] * 0.5);
+            }
+        }
+    }
+
+    /// Verify that other element types are left to the scalar loops
+    #[test]
+    fn test_unsupported_type() {
+        let buffer = [1i32, 2, 3, 4];
+        let mut sums = [0i32; 2];
+        let mut differences = [0i32; 2];
+        assert!(!fold_mirrored(&buffer, &mut sums, &mut differences));
+        assert_eq!(sums, [0, 0]);
+    }
+}
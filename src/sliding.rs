@@ -0,0 +1,259 @@
+use std::sync::Arc;
+
+use rustfft::num_complex::Complex;
+use rustfft::Length;
+
+use crate::common::derived_len;
+use crate::{twiddles, DctNum, DctPlanner, TransformType2And3};
+
+/// Computes the DCT Type 2 of a window that slides over a signal one sample at a time.
+///
+/// Each call to [`push`](#method.push) appends a sample to the window, drops the oldest one, and returns the DCT2 of
+/// the `len` most recent samples, oldest first. Instead of recomputing the whole transform, the coefficients are
+/// updated with a recurrence that costs O(len) per sample. The window starts out filled with zeroes, so the first
+/// `len - 1` outputs are the DCT2s of the samples pushed so far, preceded by zeroes.
+///
+/// The recurrence accumulates rounding error with every sample, so every
+/// [`resync_interval`](#method.resync_interval) samples, the coefficients are recomputed from the window with a full
+/// DCT2 and DST2. By default, that happens every `len` samples, which adds O(log(len)) amortized work per sample.
+///
+/// ~~~
+/// // Computes the DCT2 of every 64-sample window of a signal
+/// use rustdct::SlidingDct2;
+///
+/// let mut sliding_dct = SlidingDct2::new(64);
+///
+/// let signal = vec![0f32; 1000];
+/// for &sample in signal.iter() {
+///     let coefficients = sliding_dct.push(sample);
+///     assert_eq!(coefficients.len(), 64);
+/// }
+/// ~~~
+pub struct SlidingDct2<T> {
+    plan: Arc<dyn TransformType2And3<T>>,
+
+    // the last `len` samples, as a ring buffer whose oldest sample is at `oldest`
+    window: Box<[T]>,
+    oldest: usize,
+
+    // element `k` is the sum of `x[n] * e^(i * pi * k * (n + 0.5) / len)` over the window. the real parts are the DCT2
+    // coefficients, and the imaginary parts are needed to update them
+    state: Box<[Complex<T>]>,
+    // the DCT2 coefficients, copied out of `state` after every push
+    coefficients: Box<[T]>,
+
+    // `e^(-i * pi * k / len)`, which shifts the window one sample towards the start
+    shift_twiddles: Box<[Complex<T>]>,
+    // `e^(-i * pi * k / (2 * len))`, which applies the sample that enters and the sample that leaves the window
+    sample_twiddles: Box<[Complex<T>]>,
+
+    resync_interval: usize,
+    pushes_since_resync: usize,
+
+    // holds the window in order while it's resynced, followed by the plan's scratch
+    resync_buffer: Box<[T]>,
+}
+
+impl<T: DctNum> SlidingDct2<T> {
+    /// Creates a new sliding DCT2 over a window of `len` samples, which are all zero initially.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` is 0.
+    pub fn new(len: usize) -> Self {
+        assert!(len > 0, "The sliding DCT2 requires a non-empty window");
+        derived_len(len, 4, 0, "twiddle table");
+
+        let plan = DctPlanner::new().plan_dct2(len);
+
+        let shift_twiddles: Vec<Complex<T>> = (0..len)
+            .map(|k| twiddles::single_twiddle(k, len * 2))
+            .collect();
+        let sample_twiddles: Vec<Complex<T>> = (0..len)
+            .map(|k| twiddles::single_twiddle(k, len * 4))
+            .collect();
+        let resync_len = derived_len(len, 1, plan.get_scratch_len() as isize, "scratch");
+
+        Self {
+            window: vec![T::zero(); len].into_boxed_slice(),
+            oldest: 0,
+            state: vec![Complex::new(T::zero(), T::zero()); len].into_boxed_slice(),
+            coefficients: vec![T::zero(); len].into_boxed_slice(),
+            shift_twiddles: shift_twiddles.into_boxed_slice(),
+            sample_twiddles: sample_twiddles.into_boxed_slice(),
+            resync_interval: len,
+            pushes_since_resync: 0,
+            resync_buffer: vec![T::zero(); resync_len].into_boxed_slice(),
+            plan,
+        }
+    }
+
+    /// Returns the number of samples between the full recomputations of the coefficients.
+    pub fn resync_interval(&self) -> usize {
+        self.resync_interval
+    }
+
+    /// Sets the number of samples between the full recomputations of the coefficients. Smaller intervals keep the
+    /// coefficients closer to a freshly computed DCT2, at the cost of more work per sample. An interval of 1
+    /// recomputes the coefficients for every sample.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `interval` is 0.
+    pub fn set_resync_interval(&mut self, interval: usize) {
+        assert!(
+            interval > 0,
+            "The sliding DCT2's resync interval must be at least 1"
+        );
+        self.resync_interval = interval;
+    }
+
+    /// Appends `sample` to the window, drops the oldest sample, and returns the DCT2 of the new window.
+    pub fn push(&mut self, sample: T) -> &[T] {
+        let len = self.len();
+        let leaving = self.window[self.oldest];
+        self.window[self.oldest] = sample;
+        self.oldest = if self.oldest + 1 == len {
+            0
+        } else {
+            self.oldest + 1
+        };
+
+        self.pushes_since_resync += 1;
+        if self.pushes_since_resync >= self.resync_interval {
+            self.resync();
+        } else {
+            // shifting the window one sample towards the start multiplies each element by `e^(-i * pi * k / len)`.
+            // the leaving sample was at position -1 after the shift, and the entering sample is at position
+            // `len - 1`, and their phases only differ by `pi * k`
+            let even_term = sample - leaving;
+            let odd_term = -(sample + leaving);
+            for (k, ((state, shift), half_shift)) in self
+                .state
+                .iter_mut()
+                .zip(self.shift_twiddles.iter())
+                .zip(self.sample_twiddles.iter())
+                .enumerate()
+            {
+                let term = if k % 2 == 0 { even_term } else { odd_term };
+                *state = *state * shift + *half_shift * term;
+            }
+        }
+
+        for (coefficient, state) in self.coefficients.iter_mut().zip(self.state.iter()) {
+            *coefficient = state.re;
+        }
+        &self.coefficients
+    }
+
+    /// Returns the DCT2 of the current window, which is the same as the output of the most recent call to `push`.
+    pub fn coefficients(&self) -> &[T] {
+        &self.coefficients
+    }
+
+    /// Clears the window back to zeroes.
+    pub fn reset(&mut self) {
+        for sample in self.window.iter_mut() {
+            *sample = T::zero();
+        }
+        for state in self.state.iter_mut() {
+            *state = Complex::new(T::zero(), T::zero());
+        }
+        for coefficient in self.coefficients.iter_mut() {
+            *coefficient = T::zero();
+        }
+        self.oldest = 0;
+        self.pushes_since_resync = 0;
+    }
+
+    // Recomputes the state from the window. The real parts are the DCT2 of the window, and the imaginary part of
+    // element `k` is the DST2 output `k - 1`
+    fn resync(&mut self) {
+        let len = self.len();
+        let (window_buffer, scratch) = self.resync_buffer.split_at_mut(len);
+
+        let (newest, oldest) = self.window.split_at(self.oldest);
+        window_buffer[..oldest.len()].copy_from_slice(oldest);
+        window_buffer[oldest.len()..].copy_from_slice(newest);
+        self.plan.process_dst2_with_scratch(window_buffer, scratch);
+        self.state[0].im = T::zero();
+        for (state, sine) in self.state[1..].iter_mut().zip(window_buffer.iter()) {
+            state.im = *sine;
+        }
+
+        window_buffer[..oldest.len()].copy_from_slice(oldest);
+        window_buffer[oldest.len()..].copy_from_slice(newest);
+        self.plan.process_dct2_with_scratch(window_buffer, scratch);
+        for (state, cosine) in self.state.iter_mut().zip(window_buffer.iter()) {
+            state.re = *cosine;
+        }
+
+        self.pushes_since_resync = 0;
+    }
+}
+impl<T> Length for SlidingDct2<T> {
+    fn len(&self) -> usize {
+        self.window.len()
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::algorithm::Type2And3Naive;
+    use crate::test_utils::{compare_float_vectors, random_signal};
+    use crate::Dct2;
+
+    // Returns the DCT2 of the `len` samples ending at `end`, with zeroes before the start of the signal
+    fn expected_window_dct2(signal: &[f32], end: usize, len: usize) -> Vec<f32> {
+        let mut window = vec![0f32; len];
+        for (i, sample) in window.iter_mut().enumerate() {
+            if end + i >= len {
+                *sample = signal[end + i - len];
+            }
+        }
+        Type2And3Naive::new(len).process_dct2(&mut window);
+        window
+    }
+
+    /// Verify that the sliding DCT2 gives the same output as computing the DCT2 of each window directly
+    #[test]
+    fn test_sliding_dct2() {
+        for &len in &[1, 2, 3, 7, 16, 25] {
+            for &interval in &[1, 5, 1000] {
+                let signal = random_signal(3 * len + 10);
+                let mut sliding_dct = SlidingDct2::new(len);
+                sliding_dct.set_resync_interval(interval);
+
+                for (i, &sample) in signal.iter().enumerate() {
+                    let actual = sliding_dct.push(sample).to_vec();
+                    let expected = expected_window_dct2(&signal, i + 1, len);
+                    assert!(
+                        compare_float_vectors(&expected, &actual),
+                        "len = {}, interval = {}, i = {}",
+                        len,
+                        interval,
+                        i
+                    );
+                }
+            }
+        }
+    }
+
+    /// Verify that the resync keeps the coefficients accurate over a long signal
+    #[test]
+    fn test_sliding_dct2_resync() {
+        let len = 32;
+        let signal = random_signal(20000);
+        let mut sliding_dct = SlidingDct2::new(len);
+        for &sample in signal.iter() {
+            sliding_dct.push(sample);
+        }
+
+        let expected = expected_window_dct2(&signal, signal.len(), len);
+        assert!(compare_float_vectors(&expected, sliding_dct.coefficients()));
+
+        sliding_dct.reset();
+        assert!(sliding_dct.push(0.0).iter().all(|c| *c == 0.0));
+    }
+}
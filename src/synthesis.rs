@@ -0,0 +1,249 @@
+//! Additive synthesis with the type 3 transforms.
+//!
+//! A sum of cosines at the frequencies `k / (2 * len)` cycles per sample is a DCT3 of their cosine parts, plus a DST3
+//! of their sine parts. [`SineBank`](struct.SineBank.html) converts amplitude/phase pairs into those two inputs, so a
+//! whole bank of oscillators can be synthesized one frame at a time in O(nlogn), instead of evaluating each oscillator
+//! at each sample.
+//!
+//! ~~~
+//! // Synthesizes a frame of 256 samples containing two partials
+//! use rustdct::synthesis::SineBank;
+//! use rustdct::DctPlanner;
+//!
+//! let len = 256;
+//! let mut planner = DctPlanner::new();
+//! let bank = SineBank::new(planner.plan_dct3(len));
+//!
+//! // one (amplitude, phase) pair per partial. partial 10 is 10 / 512 cycles per sample
+//! let mut partials = vec![(0f32, 0f32); len];
+//! partials[10] = (1.0, 0.0);
+//! partials[37] = (0.25, std::f32::consts::FRAC_PI_2);
+//!
+//! let mut frame = vec![0f32; len];
+//! bank.process(&partials, &mut frame);
+//! ~~~
+
+use std::f64;
+use std::sync::Arc;
+
+use rustfft::num_complex::Complex;
+use rustfft::num_traits::Float;
+use rustfft::Length;
+
+use crate::common::{offset_complex_scratch_len, with_scratch};
+use crate::memory::table_bytes;
+use crate::{twiddles, DctNum, RequiredScratch, ScratchLayout, TransformType2And3};
+use crate::{MemoryUsage, MemoryVisitor, PlanMemory};
+
+/// Synthesizes frames of `len` samples from `len` sinusoidal partials, using a DCT3 and a DST3 of size `len`.
+///
+/// Partial `k` has the frequency `k / (2 * len)` cycles per sample, or `k * sample_rate / (2 * len)`, so the partials
+/// evenly cover the band from DC up to, but not including, the Nyquist frequency. Each partial is an
+/// `(amplitude, phase)` pair, and each output sample is
+///
+/// `output[n] = sum(amplitude[k] * cos(PI * k * n / len + phase[k]))`
+///
+/// so the phases are measured at the first sample of the frame. To continue every partial into the next frame without
+/// a discontinuity, call [`advance_phases`](#method.advance_phases) between frames.
+///
+/// Frequencies between the partials can't be represented directly. The output isn't normalized or windowed: An
+/// amplitude of 1 produces a sinusoid with a peak of 1.
+pub struct SineBank<T> {
+    dct: Arc<dyn TransformType2And3<T>>,
+    twiddles: Box<[Complex<T>]>,
+}
+
+impl<T: DctNum + Float> SineBank<T> {
+    /// Creates a new sine bank that will synthesize frames of `inner_dct.len()` samples, from `inner_dct.len()`
+    /// partials. `inner_dct` computes both the DCT3 and the DST3, so the instance returned by `DctPlanner::plan_dct3`
+    /// can be used directly.
+    pub fn new(inner_dct: Arc<dyn TransformType2And3<T>>) -> Self {
+        // the DCT3 and DST3 basis functions are offset by half a sample, so the phases are rotated back to the first
+        // sample by `-PI * k / (2 * len)`
+        let len = inner_dct.len();
+        let twiddles = (0..len)
+            .map(|k| twiddles::single_twiddle(k, len * 4))
+            .collect::<Vec<Complex<T>>>()
+            .into_boxed_slice();
+
+        Self {
+            dct: inner_dct,
+            twiddles,
+        }
+    }
+
+    /// Synthesizes one frame from `partials`, and writes it to `output`. `partials` is a slice of
+    /// `(amplitude, phase)` pairs, with one pair per partial.
+    ///
+    /// This method uses a scratch buffer on the stack for small frames, and allocates one otherwise. If you'd like to
+    /// reuse that allocation between multiple frames, consider calling `process_with_scratch` instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `partials.len()` or `output.len()` isn't `self.len()`.
+    pub fn process(&self, partials: &[(T, T)], output: &mut [T]) {
+        with_scratch(self.get_scratch_len(), |scratch| {
+            self.process_with_scratch(partials, output, scratch)
+        });
+    }
+
+    /// Synthesizes one frame from `partials`, and writes it to `output`. Uses the provided `scratch` buffer as scratch
+    /// space.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `partials.len()` or `output.len()` isn't `self.len()`, or if `scratch.len()` is less than
+    /// `self.get_scratch_len()`.
+    pub fn process_with_scratch(&self, partials: &[(T, T)], output: &mut [T], scratch: &mut [T]) {
+        let len = self.len();
+        assert_eq!(
+            partials.len(),
+            len,
+            "There must be one partial per output sample. Expected partials len = {}, got partials len = {}",
+            len,
+            partials.len()
+        );
+        assert_eq!(
+            output.len(),
+            len,
+            "The output must have len elements. Expected output len = {}, got output len = {}",
+            len,
+            output.len()
+        );
+        assert!(
+            scratch.len() >= self.get_scratch_len(),
+            "Not enough scratch space was provided. Expected scratch len >= {}, got scratch len = {}",
+            self.get_scratch_len(),
+            scratch.len()
+        );
+
+        let (sine_parts, inner_scratch) = scratch.split_at_mut(len);
+
+        // a * cos(theta + phase) = a * cos(phase) * cos(theta) - a * sin(phase) * sin(theta). the DCT3 scales its
+        // first input by 1/2, and the sine of partial k is input k - 1 of the DST3. the DST3's last input is the
+        // Nyquist frequency, which has no partial
+        for (k, ((&(amplitude, phase), twiddle), cosine_part)) in partials
+            .iter()
+            .zip(self.twiddles.iter())
+            .zip(output.iter_mut())
+            .enumerate()
+        {
+            let rotated = Complex::new(phase.cos(), phase.sin()) * twiddle;
+            *cosine_part = amplitude * rotated.re;
+            if k > 0 {
+                sine_parts[k - 1] = -amplitude * rotated.im;
+            }
+        }
+        output[0] = output[0] * T::two();
+        sine_parts[len - 1] = T::zero();
+
+        self.dct.process_dct3_with_scratch(output, inner_scratch);
+        self.dct
+            .process_dst3_with_scratch(sine_parts, inner_scratch);
+
+        for (sample, sine_sample) in output.iter_mut().zip(sine_parts.iter()) {
+            *sample = *sample + *sine_sample;
+        }
+    }
+
+    /// Advances the phase of each partial by one frame of `self.len()` samples, so that the next frame continues where
+    /// the last one left off.
+    ///
+    /// Over a frame, partial `k` advances by `PI * k` radians, so this adds `PI` to the phases of the odd partials,
+    /// and leaves the even partials unchanged. The phases stay in the range `[-PI, PI]` if they started there.
+    pub fn advance_phases(&self, partials: &mut [(T, T)]) {
+        let pi = T::from_f64(f64::consts::PI).unwrap();
+        for (_, phase) in partials.iter_mut().skip(1).step_by(2) {
+            *phase = if phase.is_sign_positive() {
+                *phase - pi
+            } else {
+                *phase + pi
+            };
+        }
+    }
+}
+impl<T> Length for SineBank<T> {
+    fn len(&self) -> usize {
+        self.dct.len()
+    }
+}
+impl<T> RequiredScratch for SineBank<T> {
+    fn get_scratch_len(&self) -> usize {
+        self.dct.len() + self.dct.get_scratch_len()
+    }
+    fn get_complex_scratch_len(&self) -> usize {
+        offset_complex_scratch_len(self.dct.len(), self.dct.get_complex_scratch_len())
+    }
+    fn scratch_layout(&self) -> ScratchLayout {
+        ScratchLayout::new()
+            .with_region("sine parts", self.dct.len(), false)
+            .with_inner(self.dct.scratch_layout())
+    }
+}
+impl<T: DctNum> MemoryUsage for SineBank<T> {
+    fn memory_usage_with_visitor(&self, visitor: &mut MemoryVisitor) -> PlanMemory {
+        PlanMemory {
+            twiddles_bytes: table_bytes(&self.twiddles),
+            window_bytes: 0,
+            inner_plans_bytes: visitor.inner_plan(&self.dct),
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::algorithm::Type2And3Naive;
+    use crate::test_utils::{compare_float_vectors, random_signal};
+    use std::f32::consts::PI;
+
+    // Evaluates each partial directly at each of the first `num_samples` samples
+    fn synthesize_naive(partials: &[(f32, f32)], num_samples: usize) -> Vec<f32> {
+        let len = partials.len();
+        (0..num_samples)
+            .map(|n| {
+                partials
+                    .iter()
+                    .enumerate()
+                    .map(|(k, &(amplitude, phase))| {
+                        amplitude * (PI * (k * n) as f32 / len as f32 + phase).cos()
+                    })
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// Verify that the sine bank gives the same output as evaluating each partial directly, and that advancing the
+    /// phases continues each partial into the next frame
+    #[test]
+    fn test_sine_bank() {
+        for len in 1..20 {
+            let bank = SineBank::new(Arc::new(Type2And3Naive::new(len)));
+
+            // random_signal is in [0, 10), so map the phases to [-PI, PI)
+            let signal = random_signal(len * 2);
+            let (amplitudes, phases) = signal.split_at(len);
+            let mut partials: Vec<(f32, f32)> = amplitudes
+                .iter()
+                .zip(phases.iter())
+                .map(|(&amplitude, &phase)| (amplitude, phase * PI / 5.0 - PI))
+                .collect();
+
+            let mut expected = synthesize_naive(&partials, len * 2);
+            let expected_next = expected.split_off(len);
+
+            let mut actual = vec![0.0; len];
+            bank.process(&partials, &mut actual);
+            assert!(compare_float_vectors(&expected, &actual), "len = {}", len);
+
+            bank.advance_phases(&mut partials);
+            assert!(partials.iter().all(|&(_, phase)| phase.abs() <= PI));
+            bank.process(&partials, &mut actual);
+            assert!(
+                compare_float_vectors(&expected_next, &actual),
+                "next frame, len = {}",
+                len
+            );
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use std::sync::Arc;
+
+use crate::mdct::Mdct;
+use crate::{Dct1, Dct2, Dct5, Dct6And7, Dct8, DctNum, DctType, Dst1, Dst5, Dst6And7, Dst8};
+use crate::{MemoryUsage, PlanIdentity, RequiredScratch, TransformType2And3, TransformType4};
+
+/// The buffers for one call to [`DctTransform::execute`](trait.DctTransform.html#tymethod.execute). The variant
+/// selects which transform the plan computes.
+pub enum DctBuffers<'a, T> {
+    /// Computes the transform `kind` on `buffer`, in-place.
+    InPlace {
+        kind: DctType,
+        buffer: &'a mut [T],
+        scratch: &'a mut [T],
+    },
+    /// Computes the MDCT of `input_a` followed by `input_b`, and writes it to `output`.
+    Mdct {
+        input_a: &'a [T],
+        input_b: &'a [T],
+        output: &'a mut [T],
+        scratch: &'a mut [T],
+    },
+    /// Computes the IMDCT of `input`, and adds it to `output_a` followed by `output_b`.
+    Imdct {
+        input: &'a [T],
+        output_a: &'a mut [T],
+        output_b: &'a mut [T],
+        scratch: &'a mut [T],
+    },
+}
+
+/// A common interface for plans of every transform type, so that plans of different types can be stored together.
+///
+/// `DctTransform` is implemented for the `Arc`s returned by every `DctPlanner` method, so they can be boxed as a
+/// `Box<dyn DctTransform<T>>`. The specific traits like `Dct2` are still the best way to use a plan whose type is known,
+/// since they catch transform type mismatches at compile time.
+///
+/// ~~~
+/// use rustdct::mdct::window_fn;
+/// use rustdct::{DctBuffers, DctPlanner, DctTransform, DctType};
+///
+/// let mut planner = DctPlanner::new();
+/// let plans: Vec<(DctType, Box<dyn DctTransform<f32>>)> = vec![
+///     (DctType::Dct2, Box::new(planner.plan_dct2(100))),
+///     (DctType::Dct4, Box::new(planner.plan_dct4(64))),
+/// ];
+///
+/// for (kind, plan) in plans.iter() {
+///     let mut buffer = vec![0f32; plan.transform_len()];
+///     let mut scratch = vec![0f32; plan.get_scratch_len()];
+///     plan.execute(DctBuffers::InPlace { kind: *kind, buffer: &mut buffer, scratch: &mut scratch });
+/// }
+///
+/// let mdct: Box<dyn DctTransform<f32>> = Box::new(planner.plan_mdct(32, window_fn::mp3));
+/// let input = vec![0f32; 64];
+/// let mut output = vec![0f32; 32];
+/// let mut scratch = vec![0f32; mdct.get_scratch_len()];
+/// mdct.execute(DctBuffers::Mdct {
+///     input_a: &input[..32],
+///     input_b: &input[32..],
+///     output: &mut output,
+///     scratch: &mut scratch,
+/// });
+/// ~~~
+pub trait DctTransform<T: DctNum>:
+    RequiredScratch + MemoryUsage + PlanIdentity<T> + Send + Sync
+{
+    /// Returns the length of the transform. For MDCT plans, this is the number of output coefficients.
+    fn transform_len(&self) -> usize;
+
+    /// Computes the transform selected by `buffers`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this plan can't compute the selected transform, for example if a DCT4 plan is given
+    /// `DctBuffers::InPlace { kind: DctType::Dct2, .. }`, or if the buffers have the wrong lengths.
+    fn execute(&self, buffers: DctBuffers<'_, T>);
+}
+
+fn unsupported<T>(plan_description: &str, buffers: &DctBuffers<'_, T>) -> ! {
+    let requested = match buffers {
+        DctBuffers::InPlace { kind, .. } => format!("{:?}", kind),
+        DctBuffers::Mdct { .. } => "MDCT".to_owned(),
+        DctBuffers::Imdct { .. } => "IMDCT".to_owned(),
+    };
+    panic!("A {} plan can't compute a {}", plan_description, requested);
+}
+
+macro_rules! impl_dct_transform {
+    ($trait_name:ident, $plan_description:expr, $(($kind:ident, $method:ident)),*) => {
+        impl<T: DctNum> DctTransform<T> for Arc<dyn $trait_name<T>> {
+            fn transform_len(&self) -> usize {
+                self.len()
+            }
+            fn execute(&self, buffers: DctBuffers<'_, T>) {
+                match buffers {
+                    $(
+                        DctBuffers::InPlace {
+                            kind: DctType::$kind,
+                            buffer,
+                            scratch,
+                        } => self.$method(buffer, scratch),
+                    )*
+                    other => unsupported($plan_description, &other),
+                }
+            }
+        }
+    };
+}
+
+impl_dct_transform!(Dct1, "DCT1", (Dct1, process_dct1_with_scratch));
+impl_dct_transform!(Dct2, "DCT2", (Dct2, process_dct2_with_scratch));
+impl_dct_transform!(
+    TransformType2And3,
+    "DCT2/DCT3",
+    (Dct2, process_dct2_with_scratch),
+    (Dct3, process_dct3_with_scratch),
+    (Dst2, process_dst2_with_scratch),
+    (Dst3, process_dst3_with_scratch)
+);
+impl_dct_transform!(
+    TransformType4,
+    "DCT4",
+    (Dct4, process_dct4_with_scratch),
+    (Dst4, process_dst4_with_scratch)
+);
+impl_dct_transform!(Dct5, "DCT5", (Dct5, process_dct5_with_scratch));
+impl_dct_transform!(
+    Dct6And7,
+    "DCT6/DCT7",
+    (Dct6, process_dct6_with_scratch),
+    (Dct7, process_dct7_with_scratch)
+);
+impl_dct_transform!(Dct8, "DCT8", (Dct8, process_dct8_with_scratch));
+impl_dct_transform!(Dst1, "DST1", (Dst1, process_dst1_with_scratch));
+impl_dct_transform!(Dst5, "DST5", (Dst5, process_dst5_with_scratch));
+impl_dct_transform!(
+    Dst6And7,
+    "DST6/DST7",
+    (Dst6, process_dst6_with_scratch),
+    (Dst7, process_dst7_with_scratch)
+);
+impl_dct_transform!(Dst8, "DST8", (Dst8, process_dst8_with_scratch));
+
+impl<T: DctNum> DctTransform<T> for Arc<dyn Mdct<T>> {
+    fn transform_len(&self) -> usize {
+        self.len()
+    }
+    fn execute(&self, buffers: DctBuffers<'_, T>) {
+        match buffers {
+            DctBuffers::Mdct {
+                input_a,
+                input_b,
+                output,
+                scratch,
+            } => self.process_mdct_with_scratch(input_a, input_b, output, scratch),
+            DctBuffers::Imdct {
+                input,
+                output_a,
+                output_b,
+                scratch,
+            } => self.process_imdct_with_scratch(input, output_a, output_b, scratch),
+            other => unsupported("MDCT", &other),
+        }
+    }
+}
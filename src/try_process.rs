@@ -0,0 +1,232 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::common::with_scratch;
+use crate::{DctBuffers, DctNum, DctTransform, DctType};
+
+/// Returned by the [`TryProcess`](trait.TryProcess.html) methods when a buffer doesn't fit the plan.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BufferLenError {
+    /// The buffer's length wasn't the plan's length.
+    Buffer {
+        /// The transform that was requested
+        kind: DctType,
+        /// The plan's length
+        expected: usize,
+        /// The buffer's length
+        actual: usize,
+    },
+    /// The scratch was too short, and it didn't have enough capacity to be lengthened without reallocating.
+    Scratch {
+        /// The transform that was requested
+        kind: DctType,
+        /// The plan's required scratch length
+        expected: usize,
+        /// The scratch's capacity
+        capacity: usize,
+    },
+}
+
+impl BufferLenError {
+    /// Returns the transform that was requested.
+    pub fn kind(&self) -> DctType {
+        match *self {
+            BufferLenError::Buffer { kind, .. } | BufferLenError::Scratch { kind, .. } => kind,
+        }
+    }
+}
+
+impl fmt::Display for BufferLenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BufferLenError::Buffer {
+                kind,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Wrong buffer length for a {:?}. Expected len {}, got len {}",
+                kind, expected, actual
+            ),
+            BufferLenError::Scratch {
+                kind,
+                expected,
+                capacity,
+            } => write!(
+                f,
+                "Not enough scratch capacity for a {:?}. Expected len {}, got capacity {}",
+                kind, expected, capacity
+            ),
+        }
+    }
+}
+impl Error for BufferLenError {}
+
+/// Fallible versions of the in-place process methods, which return a [`BufferLenError`](enum.BufferLenError.html)
+/// instead of panicking when a buffer has the wrong length.
+///
+/// The buffers are `Vec`s, and neither of them is ever reallocated. The buffer's length is checked but never changed.
+/// If the scratch is shorter than the plan needs, it's lengthened with zeroes, but only if it already has the capacity
+/// to do so. So a scratch `Vec` created with `Vec::with_capacity(plan.get_scratch_len())` can be reused for every call.
+///
+/// `TryProcess` is implemented for everything that implements [`DctTransform`](trait.DctTransform.html), which
+/// includes the `Arc`s returned by every `DctPlanner` method.
+///
+/// ~~~
+/// // Reports a buffer of the wrong length as an error, instead of panicking
+/// use rustdct::{BufferLenError, DctPlanner, DctType, RequiredScratch, TryProcess};
+///
+/// let mut planner = DctPlanner::new();
+/// let dct2 = planner.plan_dct2(100);
+///
+/// let mut buffer = vec![0f32; 100];
+/// let mut scratch = Vec::with_capacity(dct2.get_scratch_len());
+/// dct2.try_process_with_scratch(DctType::Dct2, &mut buffer, &mut scratch).unwrap();
+///
+/// let mut short_buffer = vec![0f32; 99];
+/// let error = dct2.try_process(DctType::Dct2, &mut short_buffer).unwrap_err();
+/// assert_eq!(error, BufferLenError::Buffer { kind: DctType::Dct2, expected: 100, actual: 99 });
+/// ~~~
+pub trait TryProcess<T: DctNum> {
+    /// Computes the transform `kind` on `buffer`, in-place.
+    ///
+    /// This method uses a scratch buffer on the stack for small transforms, and allocates one otherwise. If you'd like
+    /// to reuse that allocation between multiple computations, consider calling `try_process_with_scratch` instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this plan can't compute `kind`, like [`DctTransform::execute`](trait.DctTransform.html#tymethod.execute).
+    fn try_process(&self, kind: DctType, buffer: &mut Vec<T>) -> Result<(), BufferLenError>;
+
+    /// Computes the transform `kind` on `buffer`, in-place. Uses the provided `scratch` buffer as scratch space,
+    /// lengthening it within its capacity if it's too short.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this plan can't compute `kind`, like [`DctTransform::execute`](trait.DctTransform.html#tymethod.execute).
+    fn try_process_with_scratch(
+        &self,
+        kind: DctType,
+        buffer: &mut Vec<T>,
+        scratch: &mut Vec<T>,
+    ) -> Result<(), BufferLenError>;
+}
+
+impl<T: DctNum, P: DctTransform<T> + ?Sized> TryProcess<T> for P {
+    fn try_process(&self, kind: DctType, buffer: &mut Vec<T>) -> Result<(), BufferLenError> {
+        check_buffer_len(self, kind, buffer)?;
+        with_scratch(self.get_scratch_len(), |scratch| {
+            self.execute(DctBuffers::InPlace {
+                kind,
+                buffer,
+                scratch,
+            })
+        });
+        Ok(())
+    }
+
+    fn try_process_with_scratch(
+        &self,
+        kind: DctType,
+        buffer: &mut Vec<T>,
+        scratch: &mut Vec<T>,
+    ) -> Result<(), BufferLenError> {
+        check_buffer_len(self, kind, buffer)?;
+
+        let scratch_len = self.get_scratch_len();
+        if scratch.len() < scratch_len {
+            if scratch.capacity() < scratch_len {
+                return Err(BufferLenError::Scratch {
+                    kind,
+                    expected: scratch_len,
+                    capacity: scratch.capacity(),
+                });
+            }
+            scratch.resize(scratch_len, T::zero());
+        }
+
+        self.execute(DctBuffers::InPlace {
+            kind,
+            buffer,
+            scratch,
+        });
+        Ok(())
+    }
+}
+
+fn check_buffer_len<T: DctNum, P: DctTransform<T> + ?Sized>(
+    plan: &P,
+    kind: DctType,
+    buffer: &[T],
+) -> Result<(), BufferLenError> {
+    if buffer.len() == plan.transform_len() {
+        Ok(())
+    } else {
+        Err(BufferLenError::Buffer {
+            kind,
+            expected: plan.transform_len(),
+            actual: buffer.len(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::*;
+    use crate::algorithm::Type4Naive;
+    use crate::test_utils::{compare_float_vectors, random_signal};
+    use crate::{Dct4, DctPlanner, RequiredScratch};
+
+    #[test]
+    fn test_try_process() {
+        let len = 20;
+        let mut planner = DctPlanner::new();
+        let dct4 = planner.plan_dct4(len);
+        let naive = Type4Naive::new(len);
+
+        let input = random_signal(len);
+        let mut expected = input.clone();
+        naive.process_dct4(&mut expected);
+
+        let mut actual = input.clone();
+        dct4.try_process(DctType::Dct4, &mut actual).unwrap();
+        assert!(compare_float_vectors(&expected, &actual));
+
+        // an empty scratch with enough capacity is lengthened without reallocating
+        let mut scratch = Vec::with_capacity(dct4.get_scratch_len());
+        let scratch_ptr = scratch.as_ptr();
+        let mut actual = input.clone();
+        dct4.try_process_with_scratch(DctType::Dct4, &mut actual, &mut scratch)
+            .unwrap();
+        assert!(compare_float_vectors(&expected, &actual));
+        assert_eq!(scratch.len(), dct4.get_scratch_len());
+        assert_eq!(scratch.as_ptr(), scratch_ptr);
+
+        let mut long_buffer = random_signal(len + 1);
+        assert_eq!(
+            dct4.try_process(DctType::Dst4, &mut long_buffer),
+            Err(BufferLenError::Buffer {
+                kind: DctType::Dst4,
+                expected: len,
+                actual: len + 1
+            })
+        );
+        assert_eq!(long_buffer.len(), len + 1);
+
+        let mut small_scratch = Vec::with_capacity(1);
+        let error = dct4
+            .try_process_with_scratch(DctType::Dct4, &mut actual, &mut small_scratch)
+            .unwrap_err();
+        assert_eq!(error.kind(), DctType::Dct4);
+        match error {
+            BufferLenError::Scratch {
+                expected, capacity, ..
+            } => {
+                assert_eq!(expected, dct4.get_scratch_len());
+                assert!(capacity < expected);
+            }
+            other => panic!("got {:?}", other),
+        }
+        assert!(small_scratch.is_empty());
+    }
+}
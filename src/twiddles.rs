@@ -1,5 +1,9 @@
 use rustfft::num_complex::Complex;
+use std::collections::HashMap;
 use std::f64;
+use std::mem::MaybeUninit;
+use std::slice;
+use std::sync::Arc;
 
 use crate::DctNum;
 
@@ -38,6 +42,66 @@ pub fn single_twiddle_halfoffset<T: DctNum>(i: usize, fft_len: usize) -> Complex
     }
 }
 
+// Initializes every element of `storage` with `twiddle(i)`, and returns the initialized slice.
+// `twiddle_len` is the number of twiddles the algorithm needs, and `storage` must have exactly that length
+pub fn fill_storage<T: DctNum>(
+    storage: &mut [MaybeUninit<Complex<T>>],
+    twiddle_len: usize,
+    twiddle: impl Fn(usize) -> Complex<T>,
+) -> &[Complex<T>] {
+    assert_eq!(
+        storage.len(),
+        twiddle_len,
+        "Twiddle storage must have exactly twiddle_len_for(len) elements. Expected {}, got {}",
+        twiddle_len,
+        storage.len()
+    );
+
+    for (i, element) in storage.iter_mut().enumerate() {
+        *element = MaybeUninit::new(twiddle(i));
+    }
+
+    // Safety: every element was initialized above, and MaybeUninit<T> has the same layout as T
+    unsafe { slice::from_raw_parts(storage.as_ptr() as *const Complex<T>, storage.len()) }
+}
+
+// The twiddle tables that the planner shares between instances. Each algorithm's table only depends on the transform
+// size, so every instance of the same algorithm and size can use the same table
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TwiddleTable {
+    Type2And3ConvertToFft,
+    Type2And3SplitRadix,
+}
+
+// Twiddle tables that have already been computed, keyed by the table and the transform size. The planner creates
+// several instances of the same algorithm and size, like separate DCT2 and DST2 instances, or DCT2 instances with
+// different output orders, and this lets them share one allocation
+pub struct TwiddleCache<T> {
+    tables: HashMap<(TwiddleTable, usize), Arc<[Complex<T>]>>,
+}
+
+impl<T: DctNum> TwiddleCache<T> {
+    pub fn new() -> Self {
+        Self {
+            tables: HashMap::new(),
+        }
+    }
+
+    // Returns the cached table for `table` and `len`, computing it with `compute` if it isn't cached yet
+    pub fn get_or_compute(
+        &mut self,
+        table: TwiddleTable,
+        len: usize,
+        compute: impl FnOnce() -> Vec<Complex<T>>,
+    ) -> Arc<[Complex<T>]> {
+        let entry = self
+            .tables
+            .entry((table, len))
+            .or_insert_with(|| compute().into());
+        Arc::clone(entry)
+    }
+}
+
 #[cfg(test)]
 mod unit_tests {
     use super::*;
@@ -80,4 +144,19 @@ mod unit_tests {
             );
         }
     }
+    #[test]
+    fn test_twiddle_cache() {
+        let mut cache = TwiddleCache::<f32>::new();
+        let a = cache.get_or_compute(TwiddleTable::Type2And3SplitRadix, 16, || {
+            vec![Complex::new(1.0, 0.0); 4]
+        });
+        let b = cache.get_or_compute(TwiddleTable::Type2And3SplitRadix, 16, || {
+            panic!("already cached")
+        });
+        let c = cache.get_or_compute(TwiddleTable::Type2And3ConvertToFft, 16, || {
+            vec![Complex::new(1.0, 0.0); 16]
+        });
+        assert!(Arc::ptr_eq(&a, &b));
+        assert_eq!(c.len(), 16);
+    }
 }
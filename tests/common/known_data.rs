@@ -1,3 +1,5 @@
+use rustdct::DctType;
+
 pub struct KnownTestValues {
     pub input: Vec<f64>,
     pub expected_output: Vec<f64>,
@@ -518,3 +520,1351 @@ pub fn known_values_dst4() -> Vec<KnownTestValues> {
         },
     ]
 }
+
+// known input/output values for DCT Type 5
+pub fn known_values_dct5() -> Vec<KnownTestValues> {
+    vec![
+        KnownTestValues {
+            input: vec![0.0],
+            expected_output: vec![0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0],
+            expected_output: vec![0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![1.0],
+            expected_output: vec![0.5],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0],
+            expected_output: vec![1.5, 0.0],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0],
+            expected_output: vec![2.5, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0, 1.0],
+            expected_output: vec![3.5, 0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0, 1.0, 1.0],
+            expected_output: vec![4.5, 0.0, 0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![3.3],
+            expected_output: vec![1.65],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9],
+            expected_output: vec![7.55, -1.3],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9],
+            expected_output: vec![5.65, 5.0103, -3.7103],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9, 6.25],
+            expected_output: vec![11.9, 0.1203, 5.9458, -6.2411],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9, 6.25, 0.6],
+            expected_output: vec![12.5, 2.1509, 1.7946, 5.6, -8.3705],
+        },
+    ]
+}
+
+// known input/output values for DCT Type 6
+pub fn known_values_dct6() -> Vec<KnownTestValues> {
+    vec![
+        KnownTestValues {
+            input: vec![0.0],
+            expected_output: vec![0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0],
+            expected_output: vec![0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![1.0],
+            expected_output: vec![0.5],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0],
+            expected_output: vec![1.5, 0.0],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0],
+            expected_output: vec![2.5, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0, 1.0],
+            expected_output: vec![3.5, 0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0, 1.0, 1.0],
+            expected_output: vec![4.5, 0.0, 0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![3.3],
+            expected_output: vec![1.65],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9],
+            expected_output: vec![6.25, -1.3],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9],
+            expected_output: vec![8.25, 1.7966, -4.7034],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9, 6.25],
+            expected_output: vec![10.425, 2.3457, 0.2896, -7.7811],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9, 6.25, 0.6],
+            expected_output: vec![13.85, 1.2931, 2.7487, -2.375, -9.4055],
+        },
+    ]
+}
+
+// known input/output values for DCT Type 7
+pub fn known_values_dct7() -> Vec<KnownTestValues> {
+    vec![
+        KnownTestValues {
+            input: vec![0.0],
+            expected_output: vec![0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0],
+            expected_output: vec![0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![1.0],
+            expected_output: vec![0.5],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0],
+            expected_output: vec![1.0, -0.5],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0],
+            expected_output: vec![1.618, -0.618, 0.5],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0, 1.0],
+            expected_output: vec![2.247, -0.8019, 0.555, -0.5],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0, 1.0, 1.0],
+            expected_output: vec![2.8794, -1.0, 0.6527, -0.5321, 0.5],
+        },
+        KnownTestValues {
+            input: vec![3.3],
+            expected_output: vec![1.65],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9],
+            expected_output: vec![4.6, -4.25],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9],
+            expected_output: vec![5.8361, 1.3639, -6.15],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9, 6.25],
+            expected_output: vec![7.1718, 0.7779, 4.0253, -12.4],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9, 6.25, 0.6],
+            expected_output: vec![8.9679, -1.0, 5.9955, -0.6384, -11.8],
+        },
+    ]
+}
+
+// known input/output values for DCT Type 8
+pub fn known_values_dct8() -> Vec<KnownTestValues> {
+    vec![
+        KnownTestValues {
+            input: vec![0.0],
+            expected_output: vec![0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0],
+            expected_output: vec![0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![1.0],
+            expected_output: vec![0.866],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0],
+            expected_output: vec![1.5388, -0.3633],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0],
+            expected_output: vec![2.1906, -0.627, 0.2408],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0, 1.0],
+            expected_output: vec![2.8356, -0.866, 0.4195, -0.182],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0, 1.0, 1.0],
+            expected_output: vec![3.4776, -1.0948, 0.577, -0.3213, 0.1468],
+        },
+        KnownTestValues {
+            input: vec![3.3],
+            expected_output: vec![2.8579],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9],
+            expected_output: vec![6.6064, -3.6715],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9],
+            expected_output: vec![7.0057, 1.8725, -5.8057],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9, 6.25],
+            expected_output: vec![9.2757, -0.9093, 3.8165, -9.8694],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9, 6.25, 0.6],
+            expected_output: vec![10.7454, -0.9486, 3.3872, -0.4135, -10.7707],
+        },
+    ]
+}
+
+// known input/output values for DST Type 5
+pub fn known_values_dst5() -> Vec<KnownTestValues> {
+    vec![
+        KnownTestValues {
+            input: vec![0.0],
+            expected_output: vec![0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0],
+            expected_output: vec![0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![1.0],
+            expected_output: vec![0.866],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0],
+            expected_output: vec![1.5388, -0.3633],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0],
+            expected_output: vec![2.1906, -0.2408, 0.627],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0, 1.0],
+            expected_output: vec![2.8356, -0.182, 0.866, -0.4195],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0, 1.0, 1.0],
+            expected_output: vec![3.4776, -0.1468, 1.0948, -0.3213, 0.577],
+        },
+        KnownTestValues {
+            input: vec![3.3],
+            expected_output: vec![2.8579],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9],
+            expected_output: vec![6.6064, -3.6715],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9],
+            expected_output: vec![7.5077, 2.1428, -5.0334],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9, 6.25],
+            expected_output: vec![8.4237, 2.8958, 3.161, -10.4643],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9, 6.25, 0.6],
+            expected_output: vec![10.1628, 1.4852, 7.1649, -3.1581, -8.7873],
+        },
+    ]
+}
+
+// known input/output values for DST Type 6
+pub fn known_values_dst6() -> Vec<KnownTestValues> {
+    vec![
+        KnownTestValues {
+            input: vec![0.0],
+            expected_output: vec![0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0],
+            expected_output: vec![0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![1.0],
+            expected_output: vec![0.866],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0],
+            expected_output: vec![1.5388, 0.3633],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0],
+            expected_output: vec![2.1906, 0.2408, 0.627],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0, 1.0],
+            expected_output: vec![2.8356, 0.182, 0.866, 0.4195],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0, 1.0, 1.0],
+            expected_output: vec![3.4776, 0.1468, 1.0948, 0.3213, 0.577],
+        },
+        KnownTestValues {
+            input: vec![3.3],
+            expected_output: vec![2.8579],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9],
+            expected_output: vec![7.5509, -0.3294],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9],
+            expected_output: vec![5.6984, 6.9923, -2.2199],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9, 6.25],
+            expected_output: vec![8.3845, 1.7255, 9.916, -5.2186],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9, 6.25, 0.6],
+            expected_output: vec![9.5176, 1.8196, 6.2451, 8.0997, -6.7463],
+        },
+    ]
+}
+
+// known input/output values for DST Type 7
+pub fn known_values_dst7() -> Vec<KnownTestValues> {
+    vec![
+        KnownTestValues {
+            input: vec![0.0],
+            expected_output: vec![0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0],
+            expected_output: vec![0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![1.0],
+            expected_output: vec![0.866],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0],
+            expected_output: vec![1.5388, 0.3633],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0],
+            expected_output: vec![2.1906, 0.627, 0.2408],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0, 1.0],
+            expected_output: vec![2.8356, 0.866, 0.4195, 0.182],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0, 1.0, 1.0],
+            expected_output: vec![3.4776, 1.0948, 0.577, 0.3213, 0.1468],
+        },
+        KnownTestValues {
+            input: vec![3.3],
+            expected_output: vec![2.8579],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9],
+            expected_output: vec![7.5509, -0.3294],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9],
+            expected_output: vec![4.1923, 7.2627, -3.9964],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9, 6.25],
+            expected_output: vec![9.4307, 2.5548, 6.8948, -7.4722],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9, 6.25, 0.6],
+            expected_output: vec![8.9627, 5.0001, 3.7314, 4.9402, -10.0178],
+        },
+    ]
+}
+
+// known input/output values for DST Type 8
+pub fn known_values_dst8() -> Vec<KnownTestValues> {
+    vec![
+        KnownTestValues {
+            input: vec![0.0],
+            expected_output: vec![0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0],
+            expected_output: vec![0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![0.0, 0.0, 0.0, 0.0, 0.0],
+            expected_output: vec![0.0, 0.0, 0.0, 0.0, 0.0],
+        },
+        KnownTestValues {
+            input: vec![1.0],
+            expected_output: vec![0.5],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0],
+            expected_output: vec![1.0, 0.5],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0],
+            expected_output: vec![1.618, 0.618, 0.5],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0, 1.0],
+            expected_output: vec![2.247, 0.8019, 0.555, 0.5],
+        },
+        KnownTestValues {
+            input: vec![1.0, 1.0, 1.0, 1.0, 1.0],
+            expected_output: vec![2.8794, 1.0, 0.6527, 0.5321, 0.5],
+        },
+        KnownTestValues {
+            input: vec![3.3],
+            expected_output: vec![1.65],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9],
+            expected_output: vec![4.6, 0.35],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9],
+            expected_output: vec![4.843, 5.443, -3.55],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9, 6.25],
+            expected_output: vec![5.8261, 4.671, 5.97, -7.625],
+        },
+        KnownTestValues {
+            input: vec![3.3, 5.9, -1.9, 6.25, 0.6],
+            expected_output: vec![8.2406, 3.175, 6.4781, 4.9687, -10.45],
+        },
+    ]
+}
+
+// Returns the small known values for `kind`
+pub fn known_values(kind: DctType) -> Vec<KnownTestValues> {
+    match kind {
+        DctType::Dct1 => known_values_dct1(),
+        DctType::Dct2 => known_values_dct2(),
+        DctType::Dct3 => known_values_dct3(),
+        DctType::Dct4 => known_values_dct4(),
+        DctType::Dct5 => known_values_dct5(),
+        DctType::Dct6 => known_values_dct6(),
+        DctType::Dct7 => known_values_dct7(),
+        DctType::Dct8 => known_values_dct8(),
+        DctType::Dst1 => known_values_dst1(),
+        DctType::Dst2 => known_values_dst2(),
+        DctType::Dst3 => known_values_dst3(),
+        DctType::Dst4 => known_values_dst4(),
+        DctType::Dst5 => known_values_dst5(),
+        DctType::Dst6 => known_values_dst6(),
+        DctType::Dst7 => known_values_dst7(),
+        DctType::Dst8 => known_values_dst8(),
+    }
+}
+
+// Known answers at sizes large enough for the planner to choose its FFT-based, real FFT-based, and split radix
+// algorithms, for every transform type. They were computed from the definitions of each type with 40 digits of
+// precision, and rounded to the nearest f64, so they can be compared with a much tighter tolerance than the small
+// known values above.
+//
+// FFTW's REDFT and RODFT types 1-4, and SciPy's `dct` and `dst`, which follow FFTW, compute exactly twice these outputs.
+pub const KNOWN_ANSWER_LENS: [usize; 6] = [6, 7, 16, 17, 24, 64];
+
+// The input of every known answer of size `len`: multiples of 0.25 between -2.5 and 3.0, which f32 represents exactly
+pub fn known_answer_input(len: usize) -> Vec<f64> {
+    (0..len)
+        .map(|n| ((n * 37 + 11) % 23) as f64 / 4.0 - 2.5)
+        .collect()
+}
+
+// Returns the known answers for `kind`, one for each size in `KNOWN_ANSWER_LENS`
+pub fn known_answers(kind: DctType) -> Vec<KnownTestValues> {
+    let outputs = match kind {
+        DctType::Dct1 => DCT1_KNOWN_ANSWERS,
+        DctType::Dct2 => DCT2_KNOWN_ANSWERS,
+        DctType::Dct3 => DCT3_KNOWN_ANSWERS,
+        DctType::Dct4 => DCT4_KNOWN_ANSWERS,
+        DctType::Dct5 => DCT5_KNOWN_ANSWERS,
+        DctType::Dct6 => DCT6_KNOWN_ANSWERS,
+        DctType::Dct7 => DCT7_KNOWN_ANSWERS,
+        DctType::Dct8 => DCT8_KNOWN_ANSWERS,
+        DctType::Dst1 => DST1_KNOWN_ANSWERS,
+        DctType::Dst2 => DST2_KNOWN_ANSWERS,
+        DctType::Dst3 => DST3_KNOWN_ANSWERS,
+        DctType::Dst4 => DST4_KNOWN_ANSWERS,
+        DctType::Dst5 => DST5_KNOWN_ANSWERS,
+        DctType::Dst6 => DST6_KNOWN_ANSWERS,
+        DctType::Dst7 => DST7_KNOWN_ANSWERS,
+        DctType::Dst8 => DST8_KNOWN_ANSWERS,
+    };
+    KNOWN_ANSWER_LENS
+        .iter()
+        .zip(outputs.iter())
+        .map(|(&len, output)| KnownTestValues {
+            input: known_answer_input(len),
+            expected_output: output.to_vec(),
+        })
+        .collect()
+}
+
+#[rustfmt::skip]
+const DCT1_KNOWN_ANSWERS: [&[f64]; 6] = [
+    &[
+        1.875, -3.2725424859373686, 0.0, -0.4774575140626314, 0.0, 6.875,
+    ],
+    &[
+        1.25, -1.7900635094610966, -2.875, 2.25, -2.875, 2.540063509461097, 5.75,
+    ],
+    &[
+        3.625, 0.9741450128067164, -5.252886381444955, 1.3793052317185792, -3.8475009865634346, -4.125,
+        -1.7768477176559476, 4.9101577188353955, 0.6010386637890075, -2.254305231718579, 2.875, -10.922722154524454,
+        4.651847717655948, 6.663419422882343, 5.624348704219383, 1.125,
+    ],
+    &[
+        3.5, 1.5283265511804134, -5.3123073119398985, 0.3961310294171356, -1.6841360081773518, -5.446237076831603,
+        -2.200429736099266, 1.1229555123280328, 5.75, -4.039914438813057, 2.200429736099266, -1.79528821642537,
+        -9.81586399182265, 7.724073920280195, 5.3123073119398985, 6.509952718864253, 0.0,
+    ],
+    &[
+        5.75, 0.8909127963697226, 0.0, -6.525840590366365, 0.0, -0.5107135724038087, 0.0, -9.456589487016974, 0.0,
+        -3.417007829027112, 0.0, 8.397120011899172, 0.0, -2.6823104787437586, 0.0, 1.2999627483016591, 0.0,
+        -14.212564111293903, 0.0, 12.159721779897655, 0.0, 12.057308732383712, 0.0, 4.0,
+    ],
+    &[
+        15.5, 0.2950682565366095, -0.04521690063917526, 0.8151855091398007, 1.3708638860753133, 6.952709630210651,
+        -9.969624499141796, -3.3101550295193416, -4.238705455583975, -2.016013953027446, -7.098546419028093,
+        -1.132747425072658, 3.4602907187095133, -1.4399033015800153, 0.6935348609620536, -0.4380645179799597,
+        4.202609628785258, -9.578766562261274, -2.875, -6.717282565419219, -2.5898261838986127, -15.625,
+        -0.22295570098202822, 9.06995394616561, -4.038769974958267, 0.6632453362103943, -9.216514546504856,
+        -14.040022146012559, 20.30951109586825, 4.956218115851862, 5.383394474100063, 2.494163251698779,
+        7.835963600224407, -0.5402394443713197, -5.238977000407365, 0.5300294929979711, -2.875, -1.1690013038359,
+        -6.093084337974174, 7.292083346890873, -1.7441296793732868, 3.692495609278191, -2.875, 8.16947602478137,
+        -7.676758387684873, -5.818963900959996, -8.496625189738184, -4.149645969639272, -22.077287554238087,
+        -25.71987446347863, 41.503026327118995, 4.706591161362239, 9.403201599870423, 2.135098705681599, -2.875,
+        7.250352028091322, 13.4969540431697, 2.914443944958366, 8.511324692607355, 4.5776860359150096,
+        9.991996835528575, -5.44411981261306, 5.709350067132867, -1.75,
+    ],
+];
+#[rustfmt::skip]
+const DCT2_KNOWN_ANSWERS: [&[f64]; 6] = [
+    &[
+        2.25, -3.0178958157276963, 0.0, 1.590990257669732, 0.0, 5.467385558510874,
+    ],
+    &[
+        0.5, -0.5470775557033749, -3.585066360687718, 3.8670322029572346, -1.2794953702488079, 2.327797905604638,
+        5.18057099043891,
+    ],
+    &[
+        4.25, 0.5696737109958605, -4.780950270739635, 0.5866523499688913, -2.200429736099266, -4.392407741424519,
+        1.1217693515927376, 4.159056103456996, 4.065863991822648, -4.838710521165673, 5.639515362318575,
+        -7.249387832670772, 5.3123073119398985, 10.70805371561499, 3.1945288398627127, -0.9279868598405949,
+    ],
+    &[
+        3.0, 2.2151138824552583, -5.892258385989033, 0.6432375971135167, -1.4027940491636453, -4.479845534671887,
+        -0.9894333528006186, 2.9268151101787896, 5.892258385989033, -1.5867291124090408, -0.9894333528006186,
+        3.655010465373226, -8.353897689626006, 10.44330922985331, 8.353897689626006, 3.2516895191929898,
+        -1.4027940491636453,
+    ],
+    &[
+        6.0, 0.793304876318111, 0.0, -6.5872414884569634, 0.0, -0.27592343698620353, 0.0, -8.106994753481075, 0.0,
+        0.8870480488083291, 0.0, 10.38082817096682, 0.0, -2.6272132300576643, 0.0, 2.3363030118471992, 0.0,
+        -6.359844097848461, 0.0, 20.684944560614866, 0.0, 7.3050040088072725, 0.0, -2.567640629290856,
+    ],
+    &[
+        16.75, -0.7011348540720298, 0.9232061192041305, -0.1424335751285503, 2.0662908411949203, 5.618501499929871,
+        -9.146445830695574, -5.041758823787579, -2.70819078246966, -3.4259260380910876, -4.8030206978677255,
+        -2.592966142238858, 5.981708104611082, -3.1008107059546943, 2.5385814675559297, -2.7766599985558393,
+        5.3123073119398985, -11.400931034937685, -2.018088819444711, -7.098896140109342, -0.5861229324889983,
+        -12.667211876213084, 1.9205231871121327, 11.776016091331599, -1.3232171708631248, -1.1742923037990969,
+        -2.1264076663711875, -11.93340115044113, 24.388337869605074, 8.917650221401125, 2.922012433581971,
+        2.7493248272507373, 4.065863991822648, -0.36440743943947046, -8.166477670750874, 1.176677438447533,
+        -2.4020416223260774, 0.38210444934179827, -3.547696861320368, 7.013165107222587, 1.9803344431716776,
+        0.21144401503105897, 0.2848827035505726, 1.1517621667562925, -1.9321883645436082, -12.526552844353063,
+        1.8290890883329065, -7.064444762565556, 2.200429736099266, -16.444520489458807, 51.67401287622719,
+        18.26776065981735, -3.1972895939217265, 5.922431599366664, -3.5621629243294914, 6.711188248984279,
+        13.614994472920923, -0.8486090882496943, 2.2910654126853744, -0.5987893528234144, 2.517782674886156,
+        -7.65701778813578, -1.9519553384590085, 0.5848502588982671,
+    ],
+];
+#[rustfmt::skip]
+const DCT3_KNOWN_ANSWERS: [&[f64]; 6] = [
+    &[
+        0.46626588975987115, -3.862436867076458, 0.6966168031527453, -0.2946930145060612, -1.3875631329235418,
+        5.131810321593445,
+    ],
+    &[
+        0.4823510558857052, -2.6534899492332, -2.7445185928619322, 3.125, -3.2532069871324647, 0.7979382841444055,
+        5.120926189197486,
+    ],
+    &[
+        3.3297250960260185, -2.4905072520150933, -3.6070432834048813, 1.4426605009769808, -5.089432106024016,
+        -3.651412622421132, -1.2226535466575505, 4.687107014157011, 1.5777414253293327, -3.8948971326503052,
+        4.964532053663254, -10.660643435521932, 0.16905916165099816, 7.389655918610162, 6.340752989621738,
+        2.715355218659415,
+    ],
+    &[
+        3.306485594920548, -1.5591191449855564, -4.8435949507314024, 1.9742310586693015, -4.0042343557499915,
+        -4.382338412381878, -2.570906510746437, 2.1390463396354282, 5.125, -2.9477950872275063, 0.0632925571670165,
+        2.0221501645717597, -12.249387017089214, 4.573837344878819, 6.298644094944405, 6.7855067218875345,
+        2.394181602237174,
+    ],
+    &[
+        3.893065389502611, 0.483571893073533, -2.6908114450410747, -5.463004564983615, 1.0630607628131916,
+        -0.5762264348511893, -1.4393712570552224, -9.716629417887518, 1.5574171608657332, -4.658616044183334,
+        1.208460300330532, 8.148047979697662, 0.2343833517848328, -2.729769017956476, -0.3941892112265479,
+        1.3586336997180002, 0.8734734150404789, -10.868376056859757, -8.424210622987443, 14.694308802406942,
+        0.2592201338880695, 9.495451886100524, 5.123523262179316, 1.568586035630753,
+    ],
+    &[
+        10.313639641312381, -3.6699379889267885, 3.112321081374576, -1.9569581194610515, 6.956663866414119,
+        -0.3740236988186949, -9.696112931053333, -1.698395597758403, -4.69820931000814, -2.098084534962241,
+        -8.15369693775359, 3.242908417707072, 0.5900920787701397, 0.05879841676546334, -0.8288433040100696,
+        2.0791578387378933, 1.0962767253956405, -9.620400218904495, -2.3789515363811313, -6.848196558377217,
+        -3.173808988332715, -16.82532355190652, 4.338637419493271, 6.587201402280029, -3.6163809955711272,
+        0.30085679617540306, -10.337299503753156, -12.90368238501439, 21.91685164222406, 3.1380768299424338,
+        6.684535343703367, 1.32444771851821, 8.895641505319873, -1.2071241055321524, -4.551733388961088,
+        -0.2974048175673293, -2.0850298030717207, -1.6228446202866453, -5.758682996068928, 5.645650020149533,
+        0.3483966556073792, 1.7805748919579067, -0.8386433637506907, 4.907503749652534, -1.398403238326148,
+        -10.810169075379845, -3.3587555010378614, -10.148602198754409, -8.418809987683074, -40.55533388982345,
+        32.80830951206473, 17.05600609941037, 4.378924504005412, 7.329300073332533, -3.0961218976973854,
+        1.60047219197607, 15.211083037074046, 4.536971532219718, 7.794630543767105, 4.103173744573066,
+        10.74567357781166, -0.46309884963708114, 0.4799184599400829, 2.1263685768948006,
+    ],
+];
+#[rustfmt::skip]
+const DCT4_KNOWN_ANSWERS: [&[f64]; 6] = [
+    &[
+        0.251203624115214, -3.147522911355111, 2.4194819808177104, -0.388479621235296, 3.02582212340624,
+        4.351101845235833,
+    ],
+    &[
+        0.5327075130230615, -3.043792267117649, 0.024182483862537837, 2.8284271247461903, -1.9359433229876948,
+        6.022630740256543, 2.1344809590094997,
+    ],
+    &[
+        3.3169598294468585, -2.986578075062525, -2.934559386153143, 1.2580266105596283, -5.472558670630573,
+        -0.7681950709625555, 1.6792153042679006, 6.788578376572606, -2.7907912613864405, 1.0790407209100517,
+        0.6386827564217913, -5.543545368565193, 12.830257012047554, 5.360715027075532, 2.056818365782128,
+        -2.5030698390022295,
+    ],
+    &[
+        3.366409611083206, -2.2288860590033215, -4.1122359986800445, 1.9466269416845297, -4.626178635632003,
+        -2.5406130673170755, 0.5318802096029414, 5.367002950700421, 3.181980515339464, -4.116308134650519,
+        4.295205194326812, -3.950670169680647, -1.3688951422931908, 14.14510770153179, 3.1276189381977053,
+        2.883364717589962, -3.708279644869107,
+    ],
+    &[
+        3.89768079743894, 0.3280015374076895, -3.4182928857843056, -4.6529742941759356, 1.4071143788911602,
+        -0.5443645401917809, -3.1403190168037205, -7.272222352283324, 4.460492146564964, -3.428318251831869,
+        7.8162056445864385, 5.67619073583575, -2.3639326999866754, -2.028393801890811, 2.253728752105289,
+        0.8957093895146244, -1.8232124145121065, -8.305846277598132, 14.647690416544771, 11.681379583238554,
+        -0.17972684140330014, 8.151488093690014, -5.5714487886885555, 1.398574766459713,
+    ],
+    &[
+        10.30522592831672, -3.645385829733311, 3.073159538187642, -1.9140790330633388, 6.910334203564972,
+        -1.958880011639497, -9.830675388502064, -1.471663122313726, -4.752100691019633, -1.9948546165667849,
+        -7.356967347222655, 5.414342177238, -0.547434544032683, 1.0766824140515783, -1.8826479968680268,
+        3.329774180429659, -3.288380326265388, -8.516840542107646, -3.0016281047644697, -4.796230823253075,
+        -4.6823859309816465, -10.786250998650173, 12.213932024251612, 4.904725954014967, -3.566920920318851,
+        1.8305177430850064, -11.662006931096192, 5.269908079658384, 24.471317576327305, -0.7314831679613588,
+        6.721345440033332, -0.17576645406711722, 6.5555705651232214, -8.828978086303083, -1.4682443837454162,
+        -1.3592091370626396, -0.18175114462202305, -2.4508200871571484, 0.9888550058874693, 7.373429955148468,
+        -1.7607705201372619, 2.6288860679526924, -2.2229841647079356, 4.515907658535707, -12.710188915464869,
+        -1.9662399498188772, -5.3042774881678545, 1.773862345285563, -13.063851610032216, 14.063844744491064,
+        52.409747525352024, -7.422809849563295, 8.660230940824107, -3.196791888142334, 2.338263556755617,
+        10.58169996016172, 9.137950792901414, -3.8156401802074176, 4.765684328913902, -2.493558426134204,
+        1.9906480655635357, -11.331156960726577, 5.555102432909462, -4.90415888337844,
+    ],
+];
+#[rustfmt::skip]
+const DCT5_KNOWN_ANSWERS: [&[f64]; 6] = [
+    &[
+        2.125, -3.1082619226113515, -0.939240435546771, 0.6448458046865151, -1.840589110081668, 4.868245663553275,
+    ],
+    &[
+        0.375, -0.5344880743581109, -4.36251472319256, 2.7223978318435096, -2.100756712201898, -0.23002547609378085,
+        5.13038715400284,
+    ],
+    &[
+        4.125, 0.6979392788513467, -4.751813442932274, 0.36193132978926196, -2.157272928536704, -5.513792062295934,
+        -1.3464549550148392, 2.2709346279834293, 4.616880748731904, -5.3201038995669645, 5.33898432566287,
+        -8.961581584404579, -2.0976347170363585, 7.637098728617434, 6.269880758075605, 2.830003792075802,
+    ],
+    &[
+        2.875, 2.336344191152549, -5.799987336224406, 0.35213299574339807, -1.2909100562807472, -5.125824268764482,
+        -3.134363651042703, 0.370063397581637, 5.376823346001231, -0.8533412998889428, -2.1821370786355727, 4.0,
+        -11.941433248621808, 2.5660985163952077, 6.825264268014584, 6.577005203810054, 2.5492650207600005,
+    ],
+    &[
+        5.875, 0.8398121530667062, 0.29285069908701683, -6.537131496322889, -0.44958476457523733, -0.42843090171368725,
+        0.47632473949066706, -8.61249176096211, -2.2209617970420172, -1.1858444213383927, -3.2575967734561297,
+        8.908012820688409, 1.7482152615509403, -2.31158146509199, -1.374981660986063, 1.5453797446476694,
+        1.061680231889738, -8.448169285443287, -11.28515072137363, 14.309481006326836, 1.0830992563744637,
+        8.736791116303381, 5.616154324667412, 1.4941236942121918,
+    ],
+    &[
+        16.625, -0.7003212638766111, 0.9266472513534866, -0.1271178876150236, 2.1191760388084027, 6.231392924279312,
+        -8.422731148958228, -4.997628982642149, -2.7661754712275184, -3.486205615558058, -5.250146460669748,
+        -3.736773421758651, 5.48239878098283, -3.0974488675196774, 2.5136484850050094, -2.707216764079815,
+        6.54580863388643, -10.186798330814169, -2.2504172820093613, -7.730725954632969, -1.1787907934064723,
+        -15.156621402508724, -3.7224239263339047, 9.265931106572795, -1.3151793904464544, -2.1069320921033845,
+        -3.332790010878715, -19.569563112284516, 16.72559933834391, 9.214417660488314, 4.0619266353752215,
+        3.5076753656317456, 6.078444811944186, 3.617305098878744, -6.700979734254105, 0.515521456608685,
+        -2.8300654947923842, -0.431426855787171, -6.4721999025496615, 3.972418003923813, 2.0969894123183384,
+        0.581979671675017, 0.4689444404488742, 2.6070002199379396, 2.3953054157359603, -12.685648789079496,
+        -1.7371434313867626, -11.958666480537548, -4.324897703154609, -42.33720856405096, 25.792529060678657,
+        22.408611798543834, 2.532142507971713, 8.793848249788823, -3.222710956072038, 1.073044811035179,
+        14.708976765010874, 5.337329117535189, 7.296414295961788, 4.445287511145775, 10.418071883454429,
+        -0.00017489336648521877, 0.2453712020779176, 2.187973028952173,
+    ],
+];
+#[rustfmt::skip]
+const DCT6_KNOWN_ANSWERS: [&[f64]; 6] = [
+    &[
+        2.0, -3.1082619226113515, 0.939240435546771, 0.6448458046865151, 1.840589110081668, 4.868245663553275,
+    ],
+    &[
+        1.375, -1.7618822675676769, -1.9804001792858514, 3.213941134537352, -1.5100941783138258, 4.659804062637911,
+        3.227357287207263,
+    ],
+    &[
+        3.75, 0.8439357167529091, -5.251583760825891, 1.5617117170820394, -3.8012920600062103, -2.763481723738498,
+        0.7907018116447657, 6.1831874080147795, 0.037211177574241326, -1.4773485432382816, 2.9137806552969243,
+        -6.848893821851797, 11.487986965351253, 6.571165470471284, 1.7486863376810733, -2.019785096776279,
+    ],
+    &[
+        3.625, 1.4030191331259678, -5.3726153959096346, 0.6571872975740376, -1.777603052984168, -4.56732169341133,
+        0.1209301154753814, 3.438576506846552, 5.614470012724297, -4.1923620090927765, 3.115462136662154, -1.75,
+        -3.676495488201692, 13.73870124852925, 4.1660290907109445, 2.4796093387924283, -3.1077675961131535,
+    ],
+    &[
+        5.875, 0.8398121530667062, -0.29285069908701683, -6.537131496322889, 0.44958476457523733, -0.42843090171368725,
+        -0.47632473949066706, -8.61249176096211, 2.2209617970420172, -1.1858444213383927, 3.2575967734561297,
+        8.908012820688409, -1.7482152615509403, -2.31158146509199, 1.374981660986063, 1.5453797446476694,
+        -1.061680231889738, -8.448169285443287, 11.28515072137363, 14.309481006326836, -1.0830992563744637,
+        8.736791116303381, -5.616154324667412, 1.4941236942121918,
+    ],
+    &[
+        15.625, 0.2943229581404594, -0.04922104439064907, 0.8009710906434151, 1.3077094740206556, 6.3167040175712685,
+        -10.662241734861263, -3.336411661959733, -4.180313596633708, -1.9529048609859732, -6.5804854523298655,
+        0.0009773877842504757, 3.879887844948518, -1.4531316659606948, 0.7330395422701985, -0.595831929840266,
+        2.8398211008039644, -10.599296940384555, -2.5225101491221382, -6.085823121870192, -1.9245451811909142,
+        -12.477530681711663, 5.344385895418847, 10.72206290083329, -3.8547202323714456, 1.4959406642404633,
+        -7.1384853162252835, -5.249024075659322, 26.172380747235756, 3.8288061328536798, 4.499654184286351,
+        1.5783583747965084, 5.212858764079987, -4.333965357053891, -5.746969275941961, 1.1411691171183278,
+        -2.4052177001890884, 0.036137614986241025, -2.548154307487239, 9.267725475632426, -2.160980055983943,
+        3.344821909997669, -3.0268126022745148, 5.3240738549377085, -11.073377495354368, -4.0198261852187205,
+        -4.180593003043568, 0.4502836910220546, -10.105632478668381, 5.859055977800548, 55.074757972739015,
+        -3.667617723338247, 6.215647213313809, -0.9450969189101112, 0.3315034153486693, 11.129943244475319,
+        9.018885209572826, -2.93686880641195, 3.8205137693179223, -1.5423800442982198, 1.2060305612684203,
+        -10.345024619313424, 4.402378284798139, -3.7764254665619172,
+    ],
+];
+#[rustfmt::skip]
+const DCT7_KNOWN_ANSWERS: [&[f64]; 6] = [
+    &[
+        0.19029750645064342, -3.613566759617923, 1.3535697050414892, -1.5580733767921524, 1.002772924917943, 6.625,
+    ],
+    &[
+        0.4784586490845159, -3.2574273743346613, -1.2200504044661737, 2.1045724755698427, -3.6672051426623375,
+        3.9366517968088144, 4.875,
+    ],
+    &[
+        3.2767431716386377, -2.799089250086951, -3.1339828678854635, 1.2315277577400525, -5.821085894476047,
+        -2.6885409657359594, -0.8690888394331786, 6.221779997837257, -2.29749171839932, 0.1259213559607299,
+        0.6892643481211751, -10.655045107365748, 6.311280998861215, 6.024401454523508, 6.008405558700092, 0.625,
+    ],
+    &[
+        3.318419248860166, -2.0126199553744497, -4.284538833946873, 1.8328610493582398, -4.654467932775257, -4.0,
+        -2.029066900076406, 3.5056995247742706, 3.869917532652672, -4.467968430387847, 3.631269054914052,
+        -4.336947343431798, -8.445180040853122, 8.780119308461385, 4.666929192731703, 7.000574525093266, -0.625,
+    ],
+    &[
+        3.821657561215967, 0.5191460146867553, -3.1553865775875543, -4.906639022202337, 0.9981156094583907,
+        -0.33257241721945807, -2.838356586425974, -8.858927174450105, 2.286694733309432, -5.990098326325961,
+        4.769281967813675, 6.255731155404328, -0.8847169309405256, -2.847425335685544, 0.9343512869135315,
+        1.0277986449070236, -1.0381695864614204, -15.459948317607015, 3.6243055513950946, 10.501379445927832,
+        0.7746948084069183, 11.767486199613133, 0.03159729585381686, 3.875,
+    ],
+    &[
+        10.226691346974674, -3.6203190663067737, 3.0734394886062733, -1.9017552070446098, 7.1739582649986,
+        -1.1189161444454594, -9.471653290889375, -1.5375643785809217, -4.800029942127206, -2.1484328071437226,
+        -8.24242274517475, 4.449380885789049, -0.6708529473074349, 1.0776628199060385, -1.9347312782489805,
+        3.8772741111619746, -1.7126918390760029, -8.09028729447628, -3.544762471394976, -5.400466508846466,
+        -5.744696502477147, -15.26756681535823, 7.383274488718081, 4.43798138784869, -4.178403091273095,
+        1.1726312293227863, -15.538460834230778, -4.257738209547315, 21.12225385190783, 0.8009435297146384,
+        7.411332583891032, 1.007030518692227, 9.777884440231208, -5.520371347126782, -1.6190683896112055,
+        -1.8637552750249589, -0.7250829609267043, -4.052919016875982, -2.759584972246232, 6.110737374592135,
+        -1.297551021529171, 2.9486281900047304, -1.9480345557573, 7.97021976921831, -9.970684012282149,
+        -4.331362744130211, -9.805033630739679, -2.8526587105098002, -27.346313225774345, -18.202330336309146,
+        42.46313596245453, 1.1388845952309576, 11.506639625760592, -0.2004109438937602, -1.4431421522132069,
+        7.1446452821737845, 13.978197763059434, 1.9475023399524947, 9.466415542693891, 3.708177846009699,
+        10.823468049923358, -6.553649230090696, 6.850312610153835, -2.875,
+    ],
+];
+#[rustfmt::skip]
+const DCT8_KNOWN_ANSWERS: [&[f64]; 6] = [
+    &[
+        0.5129250935777144, -3.498938613317324, 1.8349517944391693, 0.5751650960095546, 0.756993045914512,
+        5.563974297340813,
+    ],
+    &[
+        0.5441292342314958, -2.5592769227392242, -1.5155444566227676, 4.009129014268058, -2.238884015024996,
+        3.6401240156145103, 4.804521836182574,
+    ],
+    &[
+        3.372993205567004, -2.691102034358611, -3.4215340598566075, 1.512937613957065, -4.924061252050488,
+        -1.948557158514987, 1.3731798980338676, 5.957938353579039, 0.7659652517583986, -2.976792832401561,
+        4.919577179774529, -8.084946971600584, 7.917839531336558, 9.691320682647929, 3.10311459839883,
+        -1.1163217878486789,
+    ],
+    &[
+        3.3588974508858356, -1.787757564942922, -4.692674471809436, 2.1398771616640957, -4.083137655845908,
+        -3.1652746695588125, -0.11421451864419277, 4.436032939985132, 4.908486952269323, -3.328682893045284,
+        1.3225168176580646, 1.4882593159943311, -7.532457637251928, 12.327827101990929, 7.126916072598695,
+        3.3814017643187144, -1.6655817566050595,
+    ],
+    &[
+        3.9695241906250285, 0.3015423366158581, -2.974588601399864, -5.2403352552512885, 1.4979581559037383,
+        -0.7011574529875086, -1.8741634199163795, -8.436913193688925, 3.8250594043663537, -2.3973278539643124,
+        4.59277798219297, 8.46855899480596, -1.5758282255222558, -2.4938362477087592, 1.0540969420977238,
+        1.8413733971850599, -0.32598355554706704, -7.592654464602869, 3.499267246945394, 19.906531526529104,
+        -0.9753292642254735, 7.9962781277406485, -0.7257264394412274, -2.3494953905755054,
+    ],
+    &[
+        10.392127288796734, -3.694841601054724, 3.1122809665631883, -1.9691293479523675, 6.715945370749128,
+        -1.212422873365908, -10.093027592391076, -1.6347496751796362, -4.651189454694102, -1.9684397582528677,
+        -7.339233779371094, 4.280885101700191, 0.7564105752979544, 0.06227246209440593, -0.7874669156207349,
+        1.6944662882736163, -0.47568489030731953, -10.25891538776668, -1.901598232822847, -6.2171816706697935,
+        -2.367685200430889, -12.99038105676658, 9.715981175933173, 7.635956753359493, -3.284176516461335,
+        1.0845133530899274, -7.855352111500513, -3.7116665510552225, 27.287581136395094, 1.8447582586662339,
+        5.854317456842145, 0.37695792872584566, 6.3152006700945735, -5.1069132797641945, -5.129437389726597,
+        0.3099981273490099, -1.6102893226814703, -0.5816504148209853, -2.3480715529190896, 8.060984819768397,
+        -0.02281585185603145, 1.4477086686968605, -1.002182017609455, 2.8550812979310654, -5.562904302977869,
+        -9.828742017960014, 0.4388540453729529, -5.264377728233522, -0.6980189033380257, -12.244164681615596,
+        55.885335770758324, 11.010533787952996, -0.5657972941086734, 4.266902825971408, -2.7962260859631813,
+        7.198395849902366, 13.578991434819931, -1.71476945624693, 2.881178679430175, -1.03324242603763,
+        2.7896600322634484, -8.09904994144396, -1.5864965862057234, 0.4547542191192811,
+    ],
+];
+#[rustfmt::skip]
+const DST1_KNOWN_ANSWERS: [&[f64]; 6] = [
+    &[
+        1.6429823503255587, -3.850127040466164, 0.47023512662351397, 0.0580949472098781, 0.18059048205282324,
+        5.798816409352855,
+    ],
+    &[
+        1.5418026928455129, -1.9696699141100893, -3.322884058098383, 3.25, -2.7015637145387403, 3.0303300858899105,
+        5.163123036405155,
+    ],
+    &[
+        4.66768458021236, 0.6953216653271924, -2.9505417760929853, 2.3261659327373447, -4.275328244502589,
+        -4.447686865301942, -2.199351181460954, 4.602896412065307, 1.6263225103663073, -3.5157418996404437,
+        4.9755826731301775, -9.182163049474935, 5.967597803624881, 9.948178122002668, 3.9905826991601896,
+        -0.738223329347534,
+    ],
+    &[
+        4.352373976373649, 1.9925275435151348, -3.970671475544961, 2.5797876785436706, -2.7794572740761514,
+        -4.763139720814412, -3.679166849078502, 1.6086449536600347, 5.25, -2.908142793740767, 0.6180350334328908,
+        1.7320508075688772, -9.332415154294074, 10.687786930863993, 7.720671475544961, 4.225009697822996,
+        -1.3016333636490347,
+    ],
+    &[
+        3.9736362109663257, 3.9009557391877117, 1.3105458952782942, -4.523024052346575, 0.7694208842938134,
+        0.5102399621108469, 0.5312770432893007, -9.98521094892952, 0.3939369649921628, -5.530959333642512,
+        0.3021980876024023, 8.182782769175859, 0.2347656264543731, -2.6058547342482097, 0.18163563200134022,
+        1.69217270705032, 0.13743866304819252, -9.142442845136511, 0.09898200219943032, 19.582415296627552,
+        0.0641890900919317, 8.72911504299225, 0.03158234461152704, -2.1281314350489438,
+    ],
+    &[
+        10.582633775229343, -0.5114828727342797, 4.497898862267313, -1.6506141023924632, 8.91861112346966,
+        8.312982335609613, -3.5597428233103234, 1.0444901720727655, -2.2022279185333336, 0.24443064933502504,
+        -8.8792604066814, 0.7316113106530484, 0.46733922078734624, 0.5491604381465148, -0.5169761654073725,
+        3.1236047096853863, 4.909872513744535, -6.812062803738914, -1.2331823566743414, -5.944773564276524,
+        -2.1421121007427733, -18.872594895741035, 0.2907075536420401, 5.761707975150523, -3.56540575446492,
+        0.42952196792265895, -10.677032332281742, -15.674472480790827, 20.30501307077083, 3.1652063472039673,
+        6.776636908314506, 1.4478758205230398, 8.964004694012973, -1.4365559661371947, -4.600672659009533,
+        -0.1540061371411558, -1.9525779062228388, -1.3433626288302132, -4.560396808690432, 6.801714626465983,
+        0.45814498117399727, 1.7938748167382352, -0.776989968170984, 3.881176968635635, -4.076106619682752,
+        -10.909471062929736, -1.6532949594875233, -7.632777852435298, -4.033148809205709, -20.48108116933314,
+        51.790230218094585, 14.72306475836712, 1.6449443099074392, 5.5237386792337615, -2.3640609104616974,
+        7.04164403682302, 14.684743643268677, -0.3656264179671142, 3.748382049139219, -0.3301620542559935,
+        3.4505233305158396, -7.729954741839158, -1.5296204566482288, 0.49560692111773547,
+    ],
+];
+#[rustfmt::skip]
+const DST2_KNOWN_ANSWERS: [&[f64]; 6] = [
+    &[
+        1.4488887394336025, -3.75, 0.5303300858899106, -2.165063509461097, 0.38822856765378116, 6.75,
+    ],
+    &[
+        1.8101015848627093, -2.54684165530448, -2.482401973956065, 1.6976135073694438, -4.417503558818774,
+        3.362079856636774, 5.0,
+    ],
+    &[
+        4.707298879148296, 0.6498443317495465, -2.2917512340378203, 2.8712922870559314, -4.3964650708771975,
+        -4.2895510276139515, -4.085853990688979, 5.126524163602469, -1.9020040075494988, -0.21975202119016962,
+        1.2722786004154205, -12.013250209759768, 4.300884652615573, 5.545643639395874, 6.130431038195396, 0.75,
+    ],
+    &[
+        4.482420873471515, 1.808819898708403, -3.250664111981963, 3.038067518841017, -2.545333665655558,
+        -4.618605248404361, -4.966247852367632, 0.8569688286934471, 4.154590429738062, -4.523731087278975,
+        3.5484726730763763, -3.8230062006155126, -10.437605595424571, 6.991026090792456, 4.322511333402665,
+        7.137730111804415, -0.5,
+    ],
+    &[
+        3.8224470746696277, 4.218100300284405, 1.2814577238707532, -3.7850617653444893, 0.777751418184496,
+        1.6507847746139475, 0.5652416747898558, -9.25, 0.44998811156820784, -8.776600038212143, 0.3791637017701516,
+        5.656854249492381, 0.332517642073628, -2.9828280064369848, 0.30067244346752264, 1.299038105676658,
+        0.2787463465248913, -16.91966044659002, 0.26401103123975755, 9.29641368660664, 0.2548977895520796,
+        11.75932220453247, 0.2505364177018249, 4.0,
+    ],
+    &[
+        10.411306251346373, -0.49590067991745185, 4.39870377580456, -1.8171356256029474, 9.26435334246488,
+        8.358196209332267, -2.775275329029252, 1.762498213528114, -1.618776148729237, 0.9875015013748516,
+        -9.133501478587235, 1.6204474946218608, -0.7112775494887085, 1.7279412180054892, -1.728994245097818,
+        5.638948053158993, 4.321487009045095, -3.483218641126229, -0.9924408403393778, -2.6814695442449663,
+        -2.648344097824893, -19.256257092637608, -0.2828026637848763, 3.419522895646817, -4.1179270746376035,
+        2.800872649664475, -17.10149650165611, -16.098727722965563, 13.525743614450347, -0.7002487134359361,
+        6.006238868859757, 0.1767766952966369, 11.497550599871762, -2.3085718328119404, -0.7334163741112237,
+        -1.4432874535913038, -0.24623144857197918, -4.16725510247735, -4.856099128941928, 4.931286492473396,
+        -1.362004906773662, 3.038196371683161, -1.944326198599181, 9.855502880000225, -7.382292153000856,
+        -3.283709023702076, -9.535299395657956, -2.065130711062717, -29.125949666013703, -27.48757467383558,
+        36.83975092457525, 0.7666050960602382, 10.984570370714232, -0.22198059792341557, -2.5526035913920118,
+        5.766964257094615, 13.703130797788631, 2.0065965325663844, 9.513667050873455, 3.8937328819209887,
+        11.223195689369655, -6.309583644919223, 6.975383394628457, -2.75,
+    ],
+];
+#[rustfmt::skip]
+const DST3_KNOWN_ANSWERS: [&[f64]; 6] = [
+    &[
+        2.3821419025120605, -2.9571067811865475, -0.20790728833729313, -0.006945394013951104, 1.5428932188134525,
+        5.1811800081887185,
+    ],
+    &[
+        1.6268521890136047, -0.8957911859973833, -3.313661300311297, 2.375, -2.082610977829471, 4.436577553874278,
+        3.560206457004058,
+    ],
+    &[
+        2.5483606396331044, 4.0307785919667625, -3.7498143924814924, 1.9947132283006912, -2.876393166767621,
+        -4.679082152921589, -2.798297241489121, 4.818517454292669, 0.6740934637953452, -1.905973595580729,
+        2.9015600373770702, -8.373286472212243, 10.138894883324893, 7.256113269466321, 2.4488927729126435,
+        -1.8544833270070624,
+    ],
+    &[
+        2.265739225338172, 4.611336809842986, -3.3589920757894256, 0.7500102065491332, -0.44467304076980735,
+        -5.5198290335684295, -3.2855673094102205, 0.5577930420322412, 5.875, -3.9521678568172387, 2.634725014541968,
+        -1.8096616651367226, -5.736556938492416, 12.855088896585455, 5.011952505003049, 3.1391208405588613,
+        -2.954936140375033,
+    ],
+    &[
+        3.7436446072334015, 3.0218644899757483, 4.205039114362018, -4.390813039696454, -0.23892121057861154,
+        0.08800763146084936, 1.9911330148770396, -9.24891231494017, -1.155384457833016, -4.309741448227732,
+        -0.9038344189335381, 8.414970263103296, -0.00023279978681746714, -2.524599320249311, 0.5733366777504615,
+        1.5140936897350656, -0.739845631052781, -10.755102530534318, 8.518303960215707, 14.770145473308776,
+        -0.2009283095955199, 9.536727053148573, -5.098900411340025, 1.5767701882339962,
+    ],
+    &[
+        9.935670703996744, 3.6509339652094113, 1.8296426104945456, 1.8324382165887771, 1.5612760383389281,
+        13.319641042872204, -1.849357292096948, 0.17568369212706036, -1.7509110363002782, 0.5284394494966552,
+        -6.4704021669361325, -3.6850163543799392, 2.614250071206449, -0.9499346755451044, 1.0729818372994495,
+        0.14199870779536136, 7.479143159867471, -6.2470629127924555, -1.532810390302981, -5.836557343010337,
+        -1.4690701631435439, -16.971143042170464, -4.349198600083501, 7.797599882394165, -3.9447783040513515,
+        0.7339755490206011, -9.410429094878712, -16.689303338718805, 18.572762275069643, 4.950438713343719,
+        5.4788432622466905, 2.616642846357262, 7.901694546127765, -0.7667444687107747, -5.279205895155336,
+        0.6762450199902852, -2.7447064446262175, -0.8359877732161234, -4.814292507838416, 8.30227128936204,
+        -1.6682695200770983, 3.6875284324132753, -2.8091405279922546, 6.652630177130747, -10.030734034623233,
+        -5.436198548384676, -6.416916870156425, -1.8409758322412486, -14.631740490144406, -2.5422067158499084,
+        53.89845463824114, 0.20188558621100974, 7.799183964214285, 0.21324882872074727, 0.3160879347305399,
+        11.378582892421006, 10.40250884980986, -1.7945397506918401, 4.585835535920736, -0.8539896682192651,
+        1.7797264621835216, -10.162916441585134, 4.441087677622494, -3.7504211969748193,
+    ],
+];
+#[rustfmt::skip]
+const DST4_KNOWN_ANSWERS: [&[f64]; 6] = [
+    &[
+        2.621782966712432, -2.1676006876978287, -1.8034771189284922, 0.08419753401855319, -2.6199250482965923,
+        4.72933770545339,
+    ],
+    &[
+        0.6965878697173777, 1.0430416950482482, -4.962096466884152, 1.7677669529663689, -2.4780478022237573,
+        -1.12959993360784, 5.038621944081186,
+    ],
+    &[
+        2.889085164572392, 4.0124290962590505, -2.6151473125664633, 1.1373836525617966, -0.040457152890716594,
+        -5.5776977741975, -4.1067899652955955, -0.32446559151972043, 4.8274739933874145, -5.493824157103002,
+        5.550920699632964, -9.032709169947, -4.278423486572734, 6.4040291448994715, 6.231400556773723,
+        2.966748612970927,
+    ],
+    &[
+        1.4868532962123246, 5.597953545843205, -3.125513091190023, 0.7977433668826288, 0.8733526862964819,
+        -4.187799415259488, -5.104442514830734, -2.919277886660948, 4.242640687119285, -0.2510025445517939,
+        -2.7140498904610073, 4.658264263886236, -12.6492556566599, 0.21477741012300766, 5.8633150753059935,
+        6.5752049448366465, 2.693316618560017,
+    ],
+    &[
+        3.7431201798309006, 2.8785758467522715, 4.953019387326888, -3.552022584563924, -0.5458908297719932,
+        0.16535857120408293, 3.7460755994296315, -6.74188350928815, -3.987068135441961, -2.9991172689541687,
+        -7.422128332860813, 6.04190579620171, 2.7064622330213837, -1.704982910210816, -1.9461676772568344,
+        1.1901033576867086, 2.1066844319202853, -8.031381540762595, -14.380569552307714, 11.942628890708399,
+        0.43643277584844203, 8.404872464586967, 5.822658431782633, 1.648708691295656,
+    ],
+    &[
+        10.935033117357042, 2.6619142482707963, 2.771409684973277, 0.8748871012791574, 1.9364023066253546,
+        12.572646451122585, 0.8480951535557276, -0.6916279723283459, 0.3662442151642787, -0.13940553699844752,
+        -3.588881358765125, -6.578317877334066, 4.095829036094705, -2.564690946371713, 3.044716512885984,
+        -2.390295275442821, 10.77096032760886, -4.207581902980372, 1.011214264109089, -5.3543209124150835,
+        2.184414649086917, -14.658853641193476, -11.313140981511772, 4.769520907477108, -0.9724327858355257,
+        -1.805570764340986, -1.5379471925440684, -27.035031290278088, 5.152280162102437, 5.753271014932644,
+        2.769776861601962, 2.2555881433224454, 6.206784121043908, 6.724709566318947, -4.610836116175101,
+        0.9183572400813205, -2.3650221973543375, -0.017398033362483155, -7.665984278069789, 1.9394699088816487,
+        1.710267914575711, 0.6184121363639332, 0.4695917084889428, 3.258878917811777, 5.159898990041778,
+        -10.930258596967047, -1.1272917426330844, -11.666117122753107, -3.7080826265434847, -48.360773998340655,
+        16.79754249582426, 20.374085563779158, 2.1780645555049527, 8.551451657957394, -3.609057931913452,
+        -0.47644891440831916, 13.898278183253202, 5.335875093451184, 7.345082261405516, 4.530624489238831,
+        10.739481958901177, 0.3574086410615574, 0.4007221017018016, 2.3117242189735263,
+    ],
+];
+#[rustfmt::skip]
+const DST5_KNOWN_ANSWERS: [&[f64]; 6] = [
+    &[
+        1.2012593326626149, -3.7702820185770154, 1.310330865282055, -0.9512270462238612, 2.7546803580348036,
+        4.676137377276725,
+    ],
+    &[
+        1.6077048903530509, -2.9109549309873017, -1.6592878000927536, 2.320997277574416, -2.598076211353316,
+        5.874724588988266, 2.4701159917679467,
+    ],
+    &[
+        4.708109751881455, 0.21310786138325205, -2.5558117492462378, 2.230836916736827, -5.282547520272099,
+        -3.619545408568152, -1.6929771215611893, 6.28871406809094, -2.2475665182694766, 0.5533078631875805,
+        0.649519052838329, -7.356069640838192, 11.697895415617316, 6.1395659773638815, 2.735809116663805,
+        -2.3441038052360663,
+    ],
+    &[
+        4.508926897815467, 1.369943395499884, -3.5429390447316056, 2.5921846173123337, -3.6164240537720405,
+        -4.6045147854925474, -3.102250649255945, 3.2086818978767786, 3.969878756278321, -4.272555771993283,
+        3.9790541351236053, -4.188064345382212, -3.61153017248316, 13.526055075637947, 4.016901074025334,
+        3.5255856856546175, -3.5614361572659905,
+    ],
+    &[
+        3.921988431045998, 4.040489189094934, 0.6638724155404879, -4.150965618331022, 0.7651042771977505,
+        0.9800384639665569, -1.0695823984675603, -9.479417076880209, 1.2410385589729895, -6.948673721064993,
+        4.07867441265364, 6.377021897670752, -0.9279115959589472, -2.574942264623839, 1.5306146687361295,
+        1.0468526563587228, -1.688129662605617, -11.005996105039367, 12.140156443013556, 12.374214112839294,
+        0.6548212173885603, 8.966078168672063, -5.070693638911461, 1.478392179133294,
+    ],
+    &[
+        10.488508644245341, -0.48458735382077467, 4.444222583788214, -1.6425028994583266, 9.41106473368202,
+        7.63902376575741, -3.6162939620690553, 1.1583472230064602, -2.3072471922173388, 0.09756091384327667,
+        -9.320033945953307, 2.1233518106857607, -0.6608351519032744, 1.5917648451655968, -1.6810000060642745,
+        5.281851607497243, 2.196802478635894, -5.589953575537848, -2.5114579217110693, -4.4322603761190065,
+        -4.94921409530869, -18.013937475035533, 3.717443073530727, 4.079197272958753, -4.225272615885927,
+        1.4307511057002265, -16.42409743460596, -7.118590807642989, 20.0461579588874, 0.9018056173170776,
+        7.504245027334853, 1.135338984131725, 9.780568718005968, -5.769281807242129, -1.5707810263544189,
+        -1.7308048405976018, -0.5807202657865094, -3.584686005668386, -1.3412199653360626, 6.877715308338317,
+        -1.2634520337287554, 2.9462000818917917, -1.948557158514987, 6.0575830859679325, -12.033095112399614,
+        -3.606005047044401, -7.58335327688474, -0.5432313499549496, -18.208372750195775, 5.802415863026897,
+        52.283828011798484, -3.646624858611689, 10.115333912208872, -2.077020162361231, 2.2428515164055547,
+        10.91916661799379, 10.549292351167367, -2.7018868063798576, 5.524412004328499, -1.8068327036680718,
+        2.5587379374868573, -11.156642176484715, 5.593567256598965, -4.878303552562426,
+    ],
+];
+#[rustfmt::skip]
+const DST6_KNOWN_ANSWERS: [&[f64]; 6] = [
+    &[
+        1.88714352527432, -3.6778528199400236, -0.32153712943889484, -0.7544118634621294, -2.211399065741939,
+        5.008358387393009,
+    ],
+    &[
+        1.7047428094376655, -1.4704914811610166, -4.028740053470407, 2.476004660529625, -3.680607966083864,
+        -0.13875727571288776, 5.056335894552401,
+    ],
+    &[
+        4.655233589605662, 1.1320577409567172, -2.6731617942208072, 2.864399020301442, -3.2663953889372883,
+        -4.781942088760051, -4.36639044568695, 2.739938520412647, 2.127403121606562, -4.298858614985872,
+        5.412658773652741, -11.025917619085128, -2.123604505857144, 6.292819738223847, 6.324548007327493,
+        2.8551111268093443,
+    ],
+    &[
+        4.313106189277439, 2.4284368564836627, -3.653045722738648, 2.9344932896883247, -1.6852230751148976,
+        -4.495171095463948, -5.196121520695122, -0.9902113067965329, 4.790518533425531, -2.512643557131835,
+        -0.473765485934183, 2.838701827269538, -13.337430422662946, 2.2256338742410007, 5.457485276379971,
+        6.796946293296926, 2.5419146509283035,
+    ],
+    &[
+        3.873932175094337, 4.056539721630622, 1.9276391087531053, -4.118731687785695, 0.7812341198419027,
+        1.0287259596054754, 2.1649039653512663, -9.413859356684998, -0.39870560245036346, -6.865668002218617,
+        -3.3993436508729964, 6.478235680328499, 1.4926659506973798, -2.454548609921957, -1.0514114735199216,
+        1.1876500332776643, 2.100530811503407, -10.843264138666646, -11.781762643389797, 12.560793961924315,
+        -0.3413311329728843, 9.178906271632961, 5.345971422935953, 1.720503677705974,
+    ],
+    &[
+        10.505535655122147, -0.5226908195119001, 4.453170416009596, -1.8173959254226586, 8.747443065457302,
+        9.001524887807763, -2.7000906051106073, 1.6501994077660338, -1.525431853572078, 1.1347622009540905,
+        -8.588072212339288, 0.21489461710446148, 0.3811823862765964, 0.6727953144286855, -0.5187511959017826,
+        3.2995242381103207, 6.874107039141421, -4.606171807070697, 0.33640320534842616, -4.314570285422626,
+        0.24984615106176725, -19.223792640682316, -3.8166140943368703, 4.471299879634088, -3.3786344049444224,
+        1.34477375237343, -9.971582445745893, -23.38535926837955, 12.272576906745858, 1.05753037480394,
+        5.295821693604728, 0.25375913112992987, 9.73530942174483, 2.1787103235633873, -3.0945650727806053,
+        0.09545328595306984, -1.6231504844110478, -1.3358377611106649, -7.39727154498002, 3.877984248022012,
+        0.14048945894562917, 1.8363784211812193, -0.8660254037844386, 5.904139924014816, 1.4581454199905084,
+        -9.310043582480333, -2.8367697719723237, -9.771059180552323, -8.103896569420206, -47.542600800797416,
+        24.293863520011538, 15.559670839430677, 3.956881967656756, 7.159186649698144, -3.5823819247126574,
+        0.03402432306050455, 14.46814274461147, 4.5547840117371035, 7.838547083446253, 4.195899663022752,
+        11.071306603812253, -0.10567256017696289, 0.6321286041713531, 2.250613325448597,
+    ],
+];
+#[rustfmt::skip]
+const DST7_KNOWN_ANSWERS: [&[f64]; 6] = [
+    &[
+        2.5754777643592206, -2.510144877474164, -1.2916705021463046, 0.9073861061258379, -0.5601778631527802,
+        5.656403495977805,
+    ],
+    &[
+        0.6795086975438082, 0.6010353930764688, -4.763139720814412, 2.687240731209552, -1.685788081660745,
+        1.7063216305543063, 5.484281783022565,
+    ],
+    &[
+        2.9677035119174957, 3.7775677209186047, -3.031552627754488, 0.8395247024393381, -1.0188151387345452,
+        -5.845671475544961, -2.4299421754340065, 1.9505799017042638, 4.7221033460800435, -5.080868873145021,
+        5.519119274308026, -8.020455660373354, 3.306243898113861, 10.759661161485903, 4.0939832945438,
+        -0.5395446057190092,
+    ],
+    &[
+        1.5685324102386067, 5.400152175270744, -3.5979361326642083, 0.6010353930764688, -0.013088189164745734,
+        -4.921175363874324, -4.169018756835278, -0.4122556948200602, 5.4476120130419226, -0.839102045418892,
+        -1.685788081660745, 3.8417183569348157, -9.800058715505624, 8.614408310447443, 8.795887585807694,
+        4.117410142421203, -1.0316141919082504,
+    ],
+    &[
+        3.8263964155153065, 2.8930538609094514, 4.520926998439517, -4.145013688367583, -0.6556251993811123,
+        -0.021826691206865707, 2.4389177746548123, -7.957709998472717, -3.412658255468564, -2.0389340543405536,
+        -4.279287897777294, 8.743836778830454, 1.817939724094936, -2.2810081447478594, -0.867517093012702,
+        2.0041053635577817, 0.46678093246600844, -7.472260809900987, -3.3980534642876474, 19.98953724537548,
+        1.0408869844206836, 8.044965623379566, 0.7579603699865534, -2.3334448580398184,
+    ],
+    &[
+        11.013991716550048, 2.68955404667786, 2.8036329714809107, 0.9492544046391941, 2.2040783424194506,
+        12.31382115023569, -0.07958610248366339, -1.3667714862786962, -0.25518408961683886, -0.9505717661381263,
+        -4.287856741436898, -6.241981757445505, 4.4060122367687855, -2.654934979064087, 2.948834287831263,
+        -2.3075630749271276, 9.497053244697566, -6.591028148512566, -0.7196716672196218, -6.8560209125845715,
+        -0.033512706294287035, -15.804963619066005, -7.815710984601908, 7.331357097790858, -1.171561095111232,
+        -2.1587813941043086, -3.1859449602577485, -21.830435053939315, 14.470199919014616, 9.014696039164514,
+        4.167153632800605, 3.62054737967892, 6.185315487927231, 3.452541202456789, -6.843531044587037,
+        0.6591660022618192, -2.706753973219168, -0.22065460335818177, -5.492676749349506, 5.307665475756846,
+        2.3258164017303904, 0.5955703403108711, 0.5209029618188519, 1.935401040252273, -0.27757987637701975,
+        -13.243868100681544, -0.2033141960509937, -9.434372196208898, -0.7679966974543765, -24.24367901493337,
+        46.570076476693934, 21.605361200081944, -0.7140818480762273, 7.168444686503249, -3.0114807346871317,
+        6.51549103622162, 14.649972822948598, 0.5165271236584242, 3.17851949465857, 0.10334095850542321,
+        3.1837640980529986, -7.281241289885077, -1.8904961127844095, 0.6247717410059662,
+    ],
+];
+#[rustfmt::skip]
+const DST8_KNOWN_ANSWERS: [&[f64]; 6] = [
+    &[
+        2.444705312298958, -2.710855041786249, -0.7809288662164635, -1.1123100184642971, -0.6119415063330409, 7.0,
+    ],
+    &[
+        1.6571134342065206, -0.48262790824276364, -3.7110725224956833, 1.5941261584318551, -3.522730931134793,
+        1.9368117303869528, 5.875,
+    ],
+    &[
+        2.4718363827867678, 4.282325232740749, -3.361578294624153, 2.3664635925482242, -1.9116374248421595,
+        -4.6952183384270425, -4.8351056298166775, 3.057308824549596, 0.9503727905053869, -2.550849749741068,
+        3.358330919524467, -11.939481414974514, 2.5827588390892298, 6.097844973857826, 5.7384155379309085, 1.25,
+    ],
+    &[
+        2.185393255456946, 4.828335365022067, -2.914430865524251, 0.9897523595639938, 0.5037134088856932, -5.0,
+        -4.58591322761447, -1.9565958718710674, 5.414523231442035, -3.8090561504827014, 1.9284653455950438,
+        -1.1956457984458932, -11.506982723706475, 5.810439261329737, 4.907042988776046, 6.639582248194432, 0.125,
+    ],
+    &[
+        3.6600174955282396, 3.0167560111175282, 4.6584285428893075, -3.8282168069139266, -0.1543066149651757,
+        0.36301632318606525, 3.4322728468882184, -8.338724321757775, -1.822081838033725, -5.568640914434621,
+        -4.38206199364992, 6.615343818097199, 1.2218022005651337, -2.5288725887166716, -0.63111572477573,
+        1.3183606406381045, 1.318275719750072, -15.188401007801215, -3.359662617460974, 10.76058938904074,
+        -0.5195827987078594, 12.019736909560603, 0.21896223398844658, 4.125,
+    ],
+    &[
+        9.856735029832635, 3.6230118495256374, 1.7977522577243135, 1.752666775704218, 1.2966392243765947,
+        13.620006430090962, -0.9242484307847757, 0.8394708082034738, -1.1263441540579013, 1.3618587823932355,
+        -5.846534402242825, -4.072304855997757, 2.3530982875008486, -0.8382640435791715, 1.1354294434389838,
+        0.1250735892263574, 8.998055624134272, -3.9057458543450125, 0.11580829472676732, -4.289042373957992,
+        0.8552842067839118, -16.551877107668442, -8.24257108320142, 5.979566853544221, -3.742344068012539,
+        1.4623428319841278, -8.412004777457312, -23.77902393571358, 10.282341016372401, 2.6656514678404175,
+        4.030441311542574, 1.3977565455414935, 8.745727155810272, 2.77899225980075, -3.848533715312409,
+        0.9090067279753065, -2.3953795272976315, -1.0006875925789822, -7.905963288125293, 5.753961977827384,
+        -1.888355996285256, 3.7807863536017687, -2.9237322432240553, 9.662239834569855, -4.9588572691545645,
+        -4.584657282622474, -8.172225142931266, -3.471468180783214, -23.11167647375268, -34.47527109224785,
+        35.07344513741726, 4.161944254370518, 8.886698338864212, 2.1017181811108134, -3.8824579579278224,
+        5.8285480207425655, 13.169696442256994, 2.9736738714389648, 8.556494830042498, 4.7570306689686666,
+        10.388458096549932, -5.196379881296373, 5.834709716063166, -1.625,
+    ],
+];
+
+pub struct KnownIntegerMatrix {
+    pub kind: DctType,
+    pub matrix: Vec<Vec<i32>>,
+}
+
+// Integer transform matrices from video codec specifications. Row `k` of each matrix approximates the `k`th
+// orthonormal basis function of `kind`, scaled by `64 * sqrt(N)`. HEVC rounds some DCT2 entries away from the nearest
+// integer to make the rows closer to orthogonal, so entries can be up to 1.5 units from the scaled basis function
+pub fn known_codec_matrices() -> Vec<KnownIntegerMatrix> {
+    vec![
+        // HEVC (ITU-T H.265) 4-point DCT2
+        KnownIntegerMatrix {
+            kind: DctType::Dct2,
+            matrix: vec![
+                vec![64, 64, 64, 64],
+                vec![83, 36, -36, -83],
+                vec![64, -64, -64, 64],
+                vec![36, -83, 83, -36],
+            ],
+        },
+        // HEVC (ITU-T H.265) 8-point DCT2
+        KnownIntegerMatrix {
+            kind: DctType::Dct2,
+            matrix: vec![
+                vec![64, 64, 64, 64, 64, 64, 64, 64],
+                vec![89, 75, 50, 18, -18, -50, -75, -89],
+                vec![83, 36, -36, -83, -83, -36, 36, 83],
+                vec![75, -18, -89, -50, 50, 89, 18, -75],
+                vec![64, -64, -64, 64, 64, -64, -64, 64],
+                vec![50, -89, 18, 75, -75, -18, 89, -50],
+                vec![36, -83, 83, -36, -36, 83, -83, 36],
+                vec![18, -50, 75, -89, 89, -75, 50, -18],
+            ],
+        },
+        // HEVC (ITU-T H.265) 4-point DST7, used for intra-predicted 4x4 luma blocks
+        KnownIntegerMatrix {
+            kind: DctType::Dst7,
+            matrix: vec![
+                vec![29, 55, 74, 84],
+                vec![74, 74, 0, -74],
+                vec![84, -29, -74, 55],
+                vec![55, -84, 74, -29],
+            ],
+        },
+        // VVC (ITU-T H.266) 4-point DCT8, used by multiple transform selection
+        KnownIntegerMatrix {
+            kind: DctType::Dct8,
+            matrix: vec![
+                vec![84, 74, 55, 29],
+                vec![74, 0, -74, -74],
+                vec![55, -74, -29, 84],
+                vec![29, -74, 84, -55],
+            ],
+        },
+    ]
+}
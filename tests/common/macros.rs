@@ -46,6 +46,47 @@ macro_rules! dct_test_inverse {
     )
 }
 
+macro_rules! dct_test_inverse_plan {
+    ($reference_fn:ident, $inverse_planner_fn:ident, $inverse_kind:ident, $first_size:expr) => (
+        // Test that the planned inverse of the slow fn yields the original data, without any extra scaling
+        let mut planner = DctPlanner::new();
+        for len in $first_size..20 {
+            let input = random_signal(len);
+            let mut buffer = $reference_fn(&input);
+
+            let inverse = planner.$inverse_planner_fn(len);
+            assert_eq!(inverse.kind(), DctType::$inverse_kind);
+            inverse.process(&mut buffer);
+
+            println!("input:   {:?}", input);
+            println!("inverse: {:?}", buffer);
+
+            assert!(compare_float_vectors(&input, &buffer), "len = {}", len);
+        }
+    )
+}
+
+macro_rules! dct_test_dst_via_dct {
+    ($reference_fn:ident, $naive_struct:ident, $process_fn: ident, $dct_planner_fn:ident) => (
+        // Test that `DstViaDct` computes the DST from the DCT of the same type, whether the inner DCT is naive or planned
+        let mut planner = DctPlanner::new();
+        for len in 1..20 {
+            let input = random_signal(len);
+            let reference_output = $reference_fn(&input);
+
+            let naive_dst = DstViaDct::new(Arc::new($naive_struct::new(len)));
+            let mut naive_buffer = input.clone();
+            naive_dst.$process_fn(&mut naive_buffer);
+            assert!(compare_float_vectors(&reference_output, &naive_buffer), "naive, len = {}", len);
+
+            let planned_dst = DstViaDct::new(planner.$dct_planner_fn(len));
+            let mut planned_buffer = input.clone();
+            planned_dst.$process_fn(&mut planned_buffer);
+            assert!(compare_float_vectors(&reference_output, &planned_buffer), "planned, len = {}", len);
+        }
+    )
+}
+
 macro_rules! dct_test_with_planner {
     ($reference_fn:ident, $naive_struct:ident, $process_fn: ident, $planner_fn:ident, $first_size:expr) => {
         // Compare our naive struct against the output from the planner
@@ -86,7 +127,7 @@ macro_rules! dct_test_with_planner {
 pub mod test_mdct {
     use super::*;
     use rustdct::{
-        mdct::{Mdct, MdctNaive},
+        mdct::{verify_tdac, Mdct, MdctNaive},
         RequiredScratch,
     };
 
@@ -187,5 +228,15 @@ pub mod test_mdct {
             "len = {}",
             len
         );
+
+        // the public check should agree, and find the same gain that this test scales by
+        let gain = verify_tdac(mdct.as_ref(), 1e-4)
+            .unwrap_or_else(|error| panic!("len = {}: {}", len, error));
+        assert!(
+            (gain * scale_factor - 1.0).abs() < 1e-4,
+            "len = {}, gain = {}",
+            len,
+            gain
+        );
     }
 }
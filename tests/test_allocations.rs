@@ -0,0 +1,182 @@
+//! Verifies that the `process_*_with_scratch` methods of planned transforms never allocate, as long as they're given
+//! `get_scratch_len()` elements of scratch.
+//!
+//! This is its own test binary because it installs a counting global allocator.
+
+// `const` thread_local initializers aren't available on the minimum supported rust version
+#![allow(clippy::missing_const_for_thread_local)]
+
+extern crate rustdct;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use rustdct::mdct::window_fn;
+use rustdct::DctPlanner;
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = Cell::new(0);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+// Returns the number of allocations made on this thread while running `f`
+fn count_allocations<F: FnOnce()>(f: F) -> usize {
+    let before = ALLOCATIONS.with(|count| count.get());
+    f();
+    ALLOCATIONS.with(|count| count.get()) - before
+}
+
+// Runs `process` on a buffer of `len` elements with exactly `scratch_len` elements of scratch, and asserts that it didn't allocate
+fn assert_no_allocations<F: Fn(&mut [f32], &mut [f32])>(
+    name: &str,
+    len: usize,
+    scratch_len: usize,
+    process: F,
+) {
+    let mut buffer = vec![1f32; len];
+    let mut scratch = vec![0f32; scratch_len];
+
+    let allocations = count_allocations(|| process(&mut buffer, &mut scratch));
+    assert_eq!(
+        allocations, 0,
+        "{} allocated during processing, len = {}",
+        name, len
+    );
+}
+
+#[test]
+fn test_process_with_scratch_does_not_allocate() {
+    // cover butterflies, naive algorithms, split radix towers, and FFT conversions with both small and large FFT scratch
+    let sizes = [
+        1, 2, 3, 4, 5, 6, 7, 8, 9, 12, 16, 17, 23, 32, 50, 64, 97, 100, 128, 256, 1234,
+    ];
+    let mut planner = DctPlanner::new();
+
+    for &len in sizes.iter() {
+        // the DCT1 is undefined for len == 1
+        if len > 1 {
+            let dct1 = planner.plan_dct1(len);
+            assert_no_allocations("dct1", len, dct1.get_scratch_len(), |b, s| {
+                dct1.process_dct1_with_scratch(b, s)
+            });
+        }
+        let dst1 = planner.plan_dst1(len);
+        assert_no_allocations("dst1", len, dst1.get_scratch_len(), |b, s| {
+            dst1.process_dst1_with_scratch(b, s)
+        });
+
+        let dct2 = planner.plan_dct2(len);
+        let scratch_len = dct2.get_scratch_len();
+        assert_no_allocations("dct2", len, scratch_len, |b, s| {
+            dct2.process_dct2_with_scratch(b, s)
+        });
+        assert_no_allocations("dct3", len, scratch_len, |b, s| {
+            dct2.process_dct3_with_scratch(b, s)
+        });
+        assert_no_allocations("dst2", len, scratch_len, |b, s| {
+            dct2.process_dst2_with_scratch(b, s)
+        });
+        assert_no_allocations("dst3", len, scratch_len, |b, s| {
+            dct2.process_dst3_with_scratch(b, s)
+        });
+
+        let dct4 = planner.plan_dct4(len);
+        let scratch_len = dct4.get_scratch_len();
+        assert_no_allocations("dct4", len, scratch_len, |b, s| {
+            dct4.process_dct4_with_scratch(b, s)
+        });
+        assert_no_allocations("dst4", len, scratch_len, |b, s| {
+            dct4.process_dst4_with_scratch(b, s)
+        });
+
+        let dct5 = planner.plan_dct5(len);
+        assert_no_allocations("dct5", len, dct5.get_scratch_len(), |b, s| {
+            dct5.process_dct5_with_scratch(b, s)
+        });
+        let dst5 = planner.plan_dst5(len);
+        assert_no_allocations("dst5", len, dst5.get_scratch_len(), |b, s| {
+            dst5.process_dst5_with_scratch(b, s)
+        });
+
+        let dct6 = planner.plan_dct6(len);
+        let scratch_len = dct6.get_scratch_len();
+        assert_no_allocations("dct6", len, scratch_len, |b, s| {
+            dct6.process_dct6_with_scratch(b, s)
+        });
+        assert_no_allocations("dct7", len, scratch_len, |b, s| {
+            dct6.process_dct7_with_scratch(b, s)
+        });
+        let dst6 = planner.plan_dst6(len);
+        let scratch_len = dst6.get_scratch_len();
+        assert_no_allocations("dst6", len, scratch_len, |b, s| {
+            dst6.process_dst6_with_scratch(b, s)
+        });
+        assert_no_allocations("dst7", len, scratch_len, |b, s| {
+            dst6.process_dst7_with_scratch(b, s)
+        });
+
+        let dct8 = planner.plan_dct8(len);
+        assert_no_allocations("dct8", len, dct8.get_scratch_len(), |b, s| {
+            dct8.process_dct8_with_scratch(b, s)
+        });
+        let dst8 = planner.plan_dst8(len);
+        assert_no_allocations("dst8", len, dst8.get_scratch_len(), |b, s| {
+            dst8.process_dst8_with_scratch(b, s)
+        });
+
+        // the MDCT requires an even size. its buffers are split into three parts: two for the input, and one for the output
+        if len % 2 == 0 {
+            let mdct = planner.plan_mdct(len, window_fn::mp3);
+            let scratch_len = mdct.get_scratch_len();
+            assert_no_allocations("mdct", len * 3, scratch_len, |b, s| {
+                let (input, output) = b.split_at_mut(len * 2);
+                let (input_a, input_b) = input.split_at(len);
+                mdct.process_mdct_with_scratch(input_a, input_b, output, s)
+            });
+            assert_no_allocations("imdct", len * 3, scratch_len, |b, s| {
+                let (input, output) = b.split_at_mut(len);
+                let (output_a, output_b) = output.split_at_mut(len);
+                mdct.process_imdct_with_scratch(input, output_a, output_b, s)
+            });
+        }
+    }
+}
+
+#[test]
+fn test_small_convenience_methods_do_not_allocate() {
+    // every algorithm planned for these sizes needs at most 64 elements of scratch, so the scratch goes on the stack
+    let mut planner = DctPlanner::new();
+    for len in 1..=16 {
+        let dct2 = planner.plan_dct2(len);
+        let dct4 = planner.plan_dct4(len);
+        let dst1 = planner.plan_dst1(len);
+        assert!(dct2.get_scratch_len() <= 64 && dct4.get_scratch_len() <= 64);
+
+        let mut buffer = vec![1f32; len];
+        let allocations = count_allocations(|| {
+            dct2.process_dct2(&mut buffer);
+            dct2.process_dct3(&mut buffer);
+            dct4.process_dct4(&mut buffer);
+            dst1.process_dst1(&mut buffer);
+        });
+        assert_eq!(allocations, 0, "len = {}", len);
+    }
+}
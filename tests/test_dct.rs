@@ -4,12 +4,29 @@ extern crate rustdct;
 #[macro_use]
 mod common;
 
+use rustdct::algorithm::type1_butterflies::Dct1Butterfly5;
+use rustdct::algorithm::type2and3_butterflies::{
+    Type2And3Butterfly16, Type2And3Butterfly4, Type2And3Butterfly8,
+};
+use rustdct::algorithm::type4_butterflies::Type4Butterfly16;
+use rustdct::algorithm::type5to8_butterflies::Dct6And7Butterfly3;
 use rustdct::algorithm::{
-    Dct1Naive, Dct5Naive, Dct6And7Naive, Dct8Naive, Dst1Naive, Dst5Naive, Dst6And7Naive, Dst8Naive,
-    Type2And3Naive, Type4Naive,
+    Accumulation, Dct1ConvertToFft, Dct1Naive, Dct5Naive, Dct6And7Naive, Dct8Naive, Dst1Naive,
+    Dst5Naive, Dst6And7Naive, Dst8Naive, DstViaDct, Type2And3Naive, Type4Naive,
+};
+use rustdct::mdct::{verify_tdac, window_fn, Mdct, MdctNaive, WindowMode, WindowScaling};
+use rustdct::modal;
+use rustdct::num_complex::Complex;
+use rustdct::num_traits::ToPrimitive;
+use rustdct::rustfft::FftPlanner;
+use rustdct::scaling::{energy_ratio, expected_energy_ratio, scaling_factor};
+use rustdct::{
+    flatten_complex_scratch, max_scratch_of, Dct3Convention, DctBuffers, DctNum, DctPlanner,
+    DctTransform, DctType, DstConvention, MemoryUsage, MemoryVisitor, Normalization, OutputOrder,
+    PlanIdentity, PlanKey, PlanKind, PlanMemory, PlanQuality, PlannerCostModel, PlanningStage,
+    PoorPlanPolicy, ProgressObserver, QualityIssue, RequiredScratch, ScratchLimitError,
+    TransformType2And3, Type2And3Pair,
 };
-use rustdct::mdct::window_fn;
-use rustdct::DctPlanner;
 use rustdct::{
     Dct1, Dct2, Dct3, Dct4, Dct5, Dct6, Dct7, Dct8, Dst1, Dst2, Dst3, Dst4, Dst5, Dst6, Dst7, Dst8,
 };
@@ -20,6 +37,9 @@ use crate::common::reference_impls::*;
 use crate::common::{compare_float_vectors, random_signal};
 
 use std::f32;
+use std::hash::{Hash, Hasher};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
 
 fn inverse_scale_dct1(len: usize) -> f64 {
     2.0 / (len - 1) as f64
@@ -73,23 +93,41 @@ fn test_dct4_accuracy() {
 }
 #[test]
 fn test_dct5_accuracy() {
-    dct_test_with_planner!(reference_dct5, Dct5Naive, process_dct5, plan_dct5, 1);
+    dct_test_with_known_data!(reference_dct5, Dct5Naive, process_dct5, known_values_dct5);
+    dct_test_with_planner!(reference_dct5, Dct5Naive, process_dct5, plan_dct5, 0);
     dct_test_inverse!(reference_dct5, reference_dct5, inverse_scale_minushalf, 1);
+    dct_test_inverse_plan!(reference_dct5, plan_inverse_dct5, Dct5, 1);
 }
 #[test]
 fn test_dct6_accuracy() {
-    dct_test_with_planner!(reference_dct6, Dct6And7Naive, process_dct6, plan_dct6, 1);
+    dct_test_with_known_data!(
+        reference_dct6,
+        Dct6And7Naive,
+        process_dct6,
+        known_values_dct6
+    );
+    dct_test_with_planner!(reference_dct6, Dct6And7Naive, process_dct6, plan_dct6, 0);
     dct_test_inverse!(reference_dct6, reference_dct7, inverse_scale_minushalf, 1);
+    dct_test_inverse_plan!(reference_dct6, plan_inverse_dct6, Dct7, 1);
 }
 #[test]
 fn test_dct7_accuracy() {
-    dct_test_with_planner!(reference_dct7, Dct6And7Naive, process_dct7, plan_dct7, 1);
+    dct_test_with_known_data!(
+        reference_dct7,
+        Dct6And7Naive,
+        process_dct7,
+        known_values_dct7
+    );
+    dct_test_with_planner!(reference_dct7, Dct6And7Naive, process_dct7, plan_dct7, 0);
     dct_test_inverse!(reference_dct7, reference_dct6, inverse_scale_minushalf, 1);
+    dct_test_inverse_plan!(reference_dct7, plan_inverse_dct7, Dct6, 1);
 }
 #[test]
 fn test_dct8_accuracy() {
-    dct_test_with_planner!(reference_dct8, Dct8Naive, process_dct8, plan_dct8, 6);
+    dct_test_with_known_data!(reference_dct8, Dct8Naive, process_dct8, known_values_dct8);
+    dct_test_with_planner!(reference_dct8, Dct8Naive, process_dct8, plan_dct8, 0);
     dct_test_inverse!(reference_dct8, reference_dct8, inverse_scale_plushalf, 1);
+    dct_test_inverse_plan!(reference_dct8, plan_inverse_dct8, Dct8, 1);
 }
 
 #[test]
@@ -108,6 +146,7 @@ fn test_dst2_accuracy() {
     );
     dct_test_with_planner!(reference_dst2, Type2And3Naive, process_dst2, plan_dst2, 1);
     dct_test_inverse!(reference_dst2, reference_dst3, inverse_scale_normal, 1);
+    dct_test_dst_via_dct!(reference_dst2, Type2And3Naive, process_dst2, plan_dct2);
 }
 #[test]
 fn test_dst3_accuracy() {
@@ -119,32 +158,229 @@ fn test_dst3_accuracy() {
     );
     dct_test_with_planner!(reference_dst3, Type2And3Naive, process_dst3, plan_dst3, 1);
     dct_test_inverse!(reference_dst3, reference_dst2, inverse_scale_normal, 1);
+    dct_test_dst_via_dct!(reference_dst3, Type2And3Naive, process_dst3, plan_dct3);
 }
 #[test]
 fn test_dst4_accuracy() {
     dct_test_with_known_data!(reference_dst4, Type4Naive, process_dst4, known_values_dst4);
     dct_test_with_planner!(reference_dst4, Type4Naive, process_dst4, plan_dst4, 1);
     dct_test_inverse!(reference_dst4, reference_dst4, inverse_scale_normal, 1);
+    dct_test_dst_via_dct!(reference_dst4, Type4Naive, process_dst4, plan_dct4);
 }
 #[test]
 fn test_dst5_accuracy() {
-    dct_test_with_planner!(reference_dst5, Dst5Naive, process_dst5, plan_dst5, 1);
+    dct_test_with_known_data!(reference_dst5, Dst5Naive, process_dst5, known_values_dst5);
+    dct_test_with_planner!(reference_dst5, Dst5Naive, process_dst5, plan_dst5, 0);
     dct_test_inverse!(reference_dst5, reference_dst5, inverse_scale_plushalf, 1);
+    dct_test_inverse_plan!(reference_dst5, plan_inverse_dst5, Dst5, 1);
 }
 #[test]
 fn test_dst6_accuracy() {
-    dct_test_with_planner!(reference_dst6, Dst6And7Naive, process_dst6, plan_dst6, 1);
+    dct_test_with_known_data!(
+        reference_dst6,
+        Dst6And7Naive,
+        process_dst6,
+        known_values_dst6
+    );
+    dct_test_with_planner!(reference_dst6, Dst6And7Naive, process_dst6, plan_dst6, 0);
     dct_test_inverse!(reference_dst6, reference_dst7, inverse_scale_plushalf, 1);
+    dct_test_inverse_plan!(reference_dst6, plan_inverse_dst6, Dst7, 1);
 }
 #[test]
 fn test_dst7_accuracy() {
-    dct_test_with_planner!(reference_dst7, Dst6And7Naive, process_dst7, plan_dst7, 6);
+    dct_test_with_known_data!(
+        reference_dst7,
+        Dst6And7Naive,
+        process_dst7,
+        known_values_dst7
+    );
+    dct_test_with_planner!(reference_dst7, Dst6And7Naive, process_dst7, plan_dst7, 0);
     dct_test_inverse!(reference_dst7, reference_dst6, inverse_scale_plushalf, 1);
+    dct_test_inverse_plan!(reference_dst7, plan_inverse_dst7, Dst6, 1);
 }
 #[test]
 fn test_dst8_accuracy() {
-    dct_test_with_planner!(reference_dst8, Dst8Naive, process_dst8, plan_dst8, 6);
+    dct_test_with_known_data!(reference_dst8, Dst8Naive, process_dst8, known_values_dst8);
+    dct_test_with_planner!(reference_dst8, Dst8Naive, process_dst8, plan_dst8, 0);
     dct_test_inverse!(reference_dst8, reference_dst8, inverse_scale_minushalf, 1);
+    dct_test_inverse_plan!(reference_dst8, plan_inverse_dst8, Dst8, 1);
+}
+
+#[test]
+fn test_plan_with_inner() {
+    // Use the naive algorithm as a stand-in for a custom user-provided DCT3
+    let mut planner = DctPlanner::new();
+    for &(len, inner_len) in &[(2, 2), (8, 2), (4, 4), (16, 4), (64, 8), (128, 32)] {
+        let dct2 = planner.plan_dct2_with_inner(len, Arc::new(Type2And3Naive::new(inner_len)));
+        assert_eq!(dct2.len(), len);
+
+        let input = random_signal(len);
+        let mut actual_buffer = input.clone();
+        dct2.process_dct2(&mut actual_buffer);
+        assert!(
+            compare_float_vectors(&reference_dct2(&input), &actual_buffer),
+            "len = {}",
+            len
+        );
+
+        let dct4 = planner.plan_dct4_with_inner(Arc::new(Type2And3Naive::new(inner_len)));
+        assert_eq!(dct4.len(), inner_len * 2);
+
+        let input = random_signal(inner_len * 2);
+        let mut actual_buffer = input.clone();
+        dct4.process_dct4(&mut actual_buffer);
+        assert!(
+            compare_float_vectors(&reference_dct4(&input), &actual_buffer),
+            "len = {}",
+            inner_len * 2
+        );
+    }
+}
+
+#[test]
+#[should_panic(expected = "len >= inner.len() >= 2")]
+fn test_plan_with_inner_len_1() {
+    DctPlanner::new().plan_dct2_with_inner(8, Arc::new(Type2And3Naive::<f32>::new(1)));
+}
+
+#[test]
+#[should_panic(expected = "power-of-two sizes")]
+fn test_plan_with_inner_non_power_of_two() {
+    DctPlanner::new().plan_dct2_with_inner(24, Arc::new(Type2And3Naive::<f32>::new(3)));
+}
+
+#[test]
+#[should_panic(expected = "inner.len() >= 1")]
+fn test_plan_dct4_with_inner_len_0() {
+    DctPlanner::new().plan_dct4_with_inner(Arc::new(Type2And3Naive::<f32>::new(0)));
+}
+
+#[test]
+fn test_plan_dst_shares_dct_instance() {
+    let mut planner = DctPlanner::<f32>::new();
+    for &len in &[7, 16] {
+        let dct2 = planner.plan_dct2(len);
+        assert!(Arc::ptr_eq(&dct2, &planner.plan_dst2(len)));
+        assert!(Arc::ptr_eq(&dct2, &planner.plan_dst3(len)));
+    }
+}
+
+#[test]
+fn test_plan_dct3_real_fft() {
+    // even sizes past the cost model's crossover use a separate half-size FFT instance for the DCT3 and DST3, so the
+    // DCT2 and DST2 keep the full-size FFT
+    let mut planner = DctPlanner::<f64>::new();
+    let crossover = planner.cost_model().dct3_min_real_fft_len;
+    for &len in &[crossover - 2, crossover + 2, 1000] {
+        let dct2 = planner.plan_dct2(len);
+        let dct3 = planner.plan_dct3(len);
+        assert!(Arc::ptr_eq(&dct2, &planner.plan_dst2(len)));
+        assert!(Arc::ptr_eq(&dct3, &planner.plan_dst3(len)));
+        assert!(Arc::ptr_eq(&dct3, &planner.plan_dct3(len)));
+        assert_eq!(Arc::ptr_eq(&dct2, &dct3), len < crossover, "len = {}", len);
+
+        let input = random_signal(len);
+        let mut actual = input.clone();
+        dct3.process_dct3(&mut actual);
+        assert!(
+            compare_float_vectors(&reference_dct3(&input), &actual),
+            "len = {}",
+            len
+        );
+    }
+}
+
+#[test]
+fn test_plan_dst2_with_convention() {
+    let mut planner = DctPlanner::new();
+    for &len in &[7, 16] {
+        let dst2 = planner.plan_dst2(len);
+        let wang = planner.plan_dst2_with_convention(len, DstConvention::Wang);
+        assert!(Arc::ptr_eq(&dst2, &wang));
+
+        // FFTW's and SciPy's DST2 and DST3 are twice this crate's
+        let fftw = planner.plan_dst2_with_convention(len, DstConvention::Fftw);
+        assert_ne!(fftw.plan_key(), dst2.plan_key());
+        let input = random_signal(len);
+        let mut expected = reference_dst2(&input);
+        for value in expected.iter_mut() {
+            *value = *value * 2.0;
+        }
+        let mut actual = input.clone();
+        fftw.process_dst2(&mut actual);
+        assert!(compare_float_vectors(&expected, &actual), "len = {}", len);
+
+        // the DCTs aren't affected
+        let mut actual = input.clone();
+        fftw.process_dct2(&mut actual);
+        assert!(
+            compare_float_vectors(&reference_dct2(&input), &actual),
+            "len = {}",
+            len
+        );
+    }
+}
+
+#[test]
+fn test_plan_dct3_with_convention() {
+    let mut planner = DctPlanner::new();
+    for &len in &[7, 16] {
+        let dct3 = planner.plan_dct3(len);
+        let wang = planner.plan_dct3_with_convention(len, Dct3Convention::Wang);
+        assert!(Arc::ptr_eq(&dct3, &wang));
+
+        // FFTW's REDFT01 is twice this crate's DCT3
+        let fftw = planner.plan_dct3_with_convention(len, Dct3Convention::Fftw);
+        assert_ne!(fftw.plan_key(), dct3.plan_key());
+        let input = random_signal(len);
+        let expected: Vec<f64> = reference_dct3(&input).iter().map(|x| x * 2.0).collect();
+        let mut actual = input.clone();
+        fftw.process_dct3(&mut actual);
+        assert!(compare_float_vectors(&expected, &actual), "len = {}", len);
+
+        // MATLAB's idct inverts the orthonormal DCT2
+        let matlab = planner.plan_dct3_with_convention(len, Dct3Convention::Matlab);
+        let mut actual = input.clone();
+        planner
+            .plan_dct2_pair(len, Normalization::Orthonormal)
+            .forward(&mut actual);
+        matlab.process_dct3(&mut actual);
+        assert!(compare_float_vectors(&input, &actual), "len = {}", len);
+
+        // the DCT2 isn't affected
+        let mut actual = input.clone();
+        matlab.process_dct2(&mut actual);
+        assert!(
+            compare_float_vectors(&reference_dct2(&input), &actual),
+            "len = {}",
+            len
+        );
+    }
+}
+
+#[test]
+fn test_plan_dct2_mixed_precision() {
+    let mut planner = DctPlanner::new();
+    for &len in &[1, 7, 16, 100] {
+        let dct2 = planner.plan_dct2_mixed_precision(len);
+        assert_eq!(dct2.len(), len);
+        assert!(Arc::ptr_eq(&dct2, &planner.plan_dct2_mixed_precision(len)));
+
+        let input: Vec<f32> = random_signal(len);
+        let mut actual_buffer = input.clone();
+        dct2.process_dct2(&mut actual_buffer);
+
+        let f64_input: Vec<f64> = input.iter().map(|x| *x as f64).collect();
+        let expected: Vec<f32> = reference_dct2(&f64_input)
+            .iter()
+            .map(|x| *x as f32)
+            .collect();
+        assert!(
+            compare_float_vectors(&expected, &actual_buffer),
+            "len = {}",
+            len
+        );
+    }
 }
 
 #[test]
@@ -214,3 +450,1870 @@ fn test_mdct_tdac() {
         }
     }
 }
+
+#[test]
+fn test_mdct_window_scaling() {
+    // windows with very different scales, and a window that's reconstructed with a gain of 2
+    let windows: [fn(usize) -> Vec<f32>; 4] = [
+        window_fn::mp3,
+        window_fn::vorbis_invertible,
+        window_fn::one,
+        |len| window_fn::mp3::<f32>(len).iter().map(|w| w * 2.0).collect(),
+    ];
+    for &len in [2, 16, 60].iter() {
+        for window in windows.iter() {
+            let mut planner = DctPlanner::new();
+            planner.set_mdct_window_scaling(WindowScaling::Orthonormal);
+            assert_eq!(planner.mdct_window_scaling(), WindowScaling::Orthonormal);
+
+            let mdcts = vec![
+                planner.plan_mdct(len, window),
+                planner.plan_mdct_with_window_fill(
+                    len,
+                    |buffer: &mut [f32]| buffer.copy_from_slice(&window(len * 2)),
+                    WindowMode::Internal,
+                ),
+                planner.plan_mdct_bounded(len, window, 0),
+            ];
+            for mdct in mdcts {
+                let gain = verify_tdac(mdct.as_ref(), 1e-4).unwrap();
+                assert!((gain - 1.0).abs() < 1e-4, "len = {}, gain = {}", len, gain);
+            }
+        }
+
+        let mut planner = DctPlanner::new();
+        planner.set_mdct_window_scaling(WindowScaling::Orthonormal);
+        let mdct = planner.plan_mdct_mixed_precision(len, window_fn::vorbis);
+        let gain = verify_tdac(mdct.as_ref(), 1e-4).unwrap();
+        assert!((gain - 1.0).abs() < 1e-4, "len = {}, gain = {}", len, gain);
+    }
+}
+
+#[test]
+fn test_mdct_window_scaling_after_planning() {
+    // switching the scaling after planning must not return the instance that was cached with the previous scaling
+    let len = 16;
+    let mut planner = DctPlanner::<f32>::new();
+    let as_provided = planner.plan_mdct(len, window_fn::mp3);
+    let as_provided_mixed = planner.plan_mdct_mixed_precision(len, window_fn::mp3);
+
+    planner.set_mdct_window_scaling(WindowScaling::Orthonormal);
+    let orthonormal = planner.plan_mdct(len, window_fn::mp3);
+    let orthonormal_mixed = planner.plan_mdct_mixed_precision(len, window_fn::mp3);
+    assert!(!Arc::ptr_eq(&as_provided, &orthonormal));
+    assert!(!Arc::ptr_eq(&as_provided_mixed, &orthonormal_mixed));
+    for mdct in [orthonormal, orthonormal_mixed].iter() {
+        let gain = verify_tdac(mdct.as_ref(), 1e-4).unwrap();
+        assert!((gain - 1.0).abs() < 1e-4, "gain = {}", gain);
+    }
+
+    // and switching back returns the original instance
+    planner.set_mdct_window_scaling(WindowScaling::AsProvided);
+    assert!(Arc::ptr_eq(
+        &as_provided,
+        &planner.plan_mdct(len, window_fn::mp3)
+    ));
+}
+
+#[test]
+fn test_mdct_mixed_precision() {
+    let len = 1000;
+    let input: Vec<f32> = random_signal(len * 2);
+    let (input_a, input_b) = input.split_at(len);
+
+    let f64_input: Vec<f64> = input.iter().map(|&x| x as f64).collect();
+    let (f64_input_a, f64_input_b) = f64_input.split_at(len);
+    let f64_mdct = DctPlanner::<f64>::new().plan_mdct(len, window_fn::vorbis);
+    let mut expected = vec![0f64; len];
+    let mut scratch = vec![0f64; f64_mdct.get_scratch_len()];
+    f64_mdct.process_mdct_with_scratch(f64_input_a, f64_input_b, &mut expected, &mut scratch);
+
+    let mut planner = DctPlanner::<f32>::new();
+    let max_error = |mdct: Arc<dyn Mdct<f32>>| {
+        let mut actual = vec![0f32; len];
+        let mut scratch = vec![0f32; mdct.get_scratch_len()];
+        mdct.process_mdct_with_scratch(input_a, input_b, &mut actual, &mut scratch);
+        expected
+            .iter()
+            .zip(&actual)
+            .map(|(e, a)| (e - *a as f64).abs())
+            .fold(0.0, f64::max)
+    };
+
+    // the only f32 error of the mixed-precision MDCT is rounding the output
+    let mixed_precision = planner.plan_mdct_mixed_precision(len, window_fn::vorbis);
+    let f32_error = max_error(planner.plan_mdct(len, window_fn::vorbis));
+    let mixed_precision_error = max_error(Arc::clone(&mixed_precision));
+    assert!(
+        mixed_precision_error * 2.0 < f32_error,
+        "mixed precision error = {}, f32 error = {}",
+        mixed_precision_error,
+        f32_error
+    );
+    assert!(Arc::ptr_eq(
+        &mixed_precision,
+        &planner.plan_mdct_mixed_precision(len, window_fn::vorbis)
+    ));
+}
+
+#[test]
+fn test_dct1_large_accuracy() {
+    // the planned DCT1 computes the symmetric extension's FFT with a half-size FFT. its f32 rounding error shouldn't grow
+    // faster with the size than the error of converting to a full-size FFT
+    let mut f64_fft_planner = FftPlanner::new();
+    let mut fft_planner = FftPlanner::new();
+    let mut planner = DctPlanner::new();
+    for &len in &[4097, 65536, 65537] {
+        let input: Vec<f32> = random_signal::<f32>(len).iter().map(|x| x - 5.0).collect();
+        let mut expected: Vec<f64> = input.iter().map(|&x| x as f64).collect();
+        Dct1ConvertToFft::new(f64_fft_planner.plan_fft_forward(2 * (len - 1)))
+            .process_dct1(&mut expected);
+
+        let max_error = |dct: &dyn Dct1<f32>| {
+            let mut actual = input.clone();
+            dct.process_dct1(&mut actual);
+            expected
+                .iter()
+                .zip(&actual)
+                .map(|(e, a)| (e - *a as f64).abs())
+                .fold(0.0, f64::max)
+        };
+
+        let full_size_error = max_error(&Dct1ConvertToFft::new(
+            fft_planner.plan_fft_forward(2 * (len - 1)),
+        ));
+        let planned_error = max_error(&*planner.plan_dct1(len));
+        assert!(
+            planned_error < full_size_error * 2.0,
+            "len = {}: planned error = {}, full-size FFT error = {}",
+            len,
+            planned_error,
+            full_size_error
+        );
+    }
+}
+
+// Sizes whose internal lengths overflow usize should be rejected with a clear message at plan time, before anything is allocated
+#[test]
+#[should_panic(expected = "doesn't fit in a usize")]
+fn test_plan_dct1_overflow() {
+    // the scratch space holds a complex FFT buffer of size len - 1, which is 2 * (len - 1) reals
+    DctPlanner::<f32>::new().plan_dct1(usize::MAX - 1);
+}
+#[test]
+#[should_panic(expected = "DCT Type 1 is undefined for len == 1")]
+fn test_plan_dct1_tiny_sizes() {
+    // sizes without a FFT to convert to are planned like the default planner plans them, even if the cost model asks to
+    // convert every size
+    let cost_model = PlannerCostModel {
+        dct1_min_fast_len: 0,
+        ..PlannerCostModel::default()
+    };
+    DctPlanner::<f32>::with_cost_model(cost_model).plan_dct1(1);
+}
+#[test]
+#[should_panic(expected = "doesn't fit in a usize")]
+fn test_plan_dst1_overflow() {
+    DctPlanner::<f32>::new().plan_dst1(usize::MAX / 2);
+}
+#[test]
+#[should_panic(expected = "doesn't fit in a usize")]
+fn test_plan_dct2_overflow() {
+    // the largest power of two that fits in a usize
+    DctPlanner::<f32>::new().plan_dct2(usize::MAX / 2 + 1);
+}
+#[test]
+#[should_panic(expected = "doesn't fit in a usize")]
+fn test_plan_dct4_overflow() {
+    DctPlanner::<f32>::new().plan_dct4(usize::MAX / 8 + 1);
+}
+#[test]
+#[should_panic(expected = "doesn't fit in a usize")]
+fn test_plan_dst6_overflow() {
+    DctPlanner::<f32>::new().plan_dst6(usize::MAX / 4 + 1);
+}
+#[test]
+#[should_panic(expected = "doesn't fit in a usize")]
+fn test_plan_dct4_odd_overflow() {
+    DctPlanner::<f32>::new().plan_dct4(usize::MAX);
+}
+#[test]
+#[should_panic(expected = "doesn't fit in a usize")]
+fn test_naive_overflow() {
+    Type2And3Naive::<f32>::new(usize::MAX / 4 + 1);
+}
+
+type ReferenceFn = fn(&[f64]) -> Vec<f64>;
+
+#[test]
+fn test_scaling_factors() {
+    let reference_fns: [(DctType, ReferenceFn); 16] = [
+        (DctType::Dct1, reference_dct1),
+        (DctType::Dct2, reference_dct2),
+        (DctType::Dct3, reference_dct3),
+        (DctType::Dct4, reference_dct4),
+        (DctType::Dct5, reference_dct5),
+        (DctType::Dct6, reference_dct6),
+        (DctType::Dct7, reference_dct7),
+        (DctType::Dct8, reference_dct8),
+        (DctType::Dst1, reference_dst1),
+        (DctType::Dst2, reference_dst2),
+        (DctType::Dst3, reference_dst3),
+        (DctType::Dst4, reference_dst4),
+        (DctType::Dst5, reference_dst5),
+        (DctType::Dst6, reference_dst6),
+        (DctType::Dst7, reference_dst7),
+        (DctType::Dst8, reference_dst8),
+    ];
+    let reference_fn = |kind: DctType| {
+        reference_fns
+            .iter()
+            .find(|(entry_kind, _)| *entry_kind == kind)
+            .unwrap()
+            .1
+    };
+
+    for &(kind, forward_fn) in &reference_fns {
+        let inverse_fn = reference_fn(kind.inverse());
+        assert_eq!(kind.inverse().inverse(), kind);
+
+        for len in 2..20 {
+            let input: Vec<f64> = random_signal(len);
+            let forward = forward_fn(&input);
+
+            let scale: f64 = scaling_factor(kind, len);
+            let roundtrip: Vec<f64> = inverse_fn(&forward).iter().map(|x| x * scale).collect();
+            assert!(
+                compare_float_vectors(&input, &roundtrip),
+                "kind = {:?}, len = {}",
+                kind,
+                len
+            );
+
+            if let Some(expected_ratio) = expected_energy_ratio::<f64>(kind, len) {
+                let actual_ratio = energy_ratio(&input, &forward);
+                assert!(
+                    (actual_ratio - expected_ratio).abs() < 1e-6 * expected_ratio,
+                    "kind = {:?}, len = {}, expected energy ratio = {}, actual = {}",
+                    kind,
+                    len,
+                    expected_ratio,
+                    actual_ratio
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_plan_dct2_many() {
+    let mut planner = DctPlanner::new();
+    let lens = [16, 7, 64, 7, 100, 32];
+    let plans = planner.plan_dct2_many(&lens);
+    assert_eq!(plans.len(), 5);
+
+    for &len in &lens {
+        let dct2 = &plans[&len];
+        assert_eq!(dct2.len(), len);
+        assert!(Arc::ptr_eq(dct2, &planner.plan_dct2(len)));
+
+        let input = random_signal(len);
+        let mut actual_buffer = input.clone();
+        dct2.process_dct2(&mut actual_buffer);
+        assert!(
+            compare_float_vectors(&reference_dct2(&input), &actual_buffer),
+            "len = {}",
+            len
+        );
+    }
+}
+
+#[test]
+fn test_plan_dct2_with_output_order() {
+    let mut planner = DctPlanner::new();
+    for len in (1..20).chain(vec![32, 64, 100, 128]) {
+        let orders = [
+            OutputOrder::Natural,
+            OutputOrder::EvensThenOdds,
+            OutputOrder::Custom((0..len).rev().collect::<Vec<_>>().into()),
+        ];
+        for order in orders.iter() {
+            let dct2 = planner.plan_dct2_with_output_order(len, order.clone());
+            assert_eq!(dct2.len(), len);
+
+            let input = random_signal(len);
+            let natural = reference_dct2(&input);
+            let expected: Vec<f64> = order
+                .coefficient_order(len)
+                .into_iter()
+                .map(|coefficient| natural[coefficient])
+                .collect();
+
+            let mut actual_buffer = input.clone();
+            dct2.process_dct2(&mut actual_buffer);
+            assert!(
+                compare_float_vectors(&expected, &actual_buffer),
+                "len = {}, order = {:?}",
+                len,
+                order
+            );
+        }
+    }
+}
+
+#[test]
+fn test_process_from() {
+    let mut planner = DctPlanner::new();
+    for len in (1..20).chain(vec![32, 64, 100]) {
+        let dcts: Vec<Arc<dyn TransformType2And3<f64>>> =
+            vec![planner.plan_dct2(len), Arc::new(Type2And3Naive::new(len))];
+        for dct in dcts {
+            let input = random_signal(len);
+            let mut scratch = vec![0.0; dct.get_scratch_len()];
+
+            let mut output = vec![0.0; len];
+            dct.process_dct2_from(&input, &mut output, &mut scratch);
+            assert!(
+                compare_float_vectors(&reference_dct2(&input), &output),
+                "len = {}",
+                len
+            );
+
+            dct.process_dct3_from(&input, &mut output, &mut scratch);
+            assert!(
+                compare_float_vectors(&reference_dct3(&input), &output),
+                "len = {}",
+                len
+            );
+        }
+    }
+}
+
+// the `unchecked-process` feature skips this check in release builds
+#[test]
+#[should_panic]
+#[cfg(any(debug_assertions, not(feature = "unchecked-process")))]
+fn test_process_from_wrong_output_len() {
+    let mut planner = DctPlanner::new();
+    let dct = planner.plan_dct2(10);
+    let input = vec![0f32; 10];
+    let mut output = vec![0f32; 9];
+    let mut scratch = vec![0f32; dct.get_scratch_len()];
+    dct.process_dct2_from(&input, &mut output, &mut scratch);
+}
+
+#[test]
+fn test_process_planar() {
+    let mut planner = DctPlanner::new();
+    for len in (1..20).chain(vec![32, 64, 100]) {
+        let dct = planner.plan_dct2(len);
+        let inputs: Vec<Vec<f64>> = (0..3).map(|_| random_signal(len)).collect();
+        let mut scratch = vec![0.0; dct.get_scratch_len()];
+
+        let mut channels = inputs.clone();
+        let mut channel_refs: Vec<&mut [f64]> =
+            channels.iter_mut().map(|c| c.as_mut_slice()).collect();
+        dct.process_dct2_planar(&mut channel_refs, &mut scratch);
+        for (input, output) in inputs.iter().zip(&channels) {
+            assert!(
+                compare_float_vectors(&reference_dct2(input), output),
+                "len = {}",
+                len
+            );
+        }
+
+        let mut channels = inputs.clone();
+        let mut channel_refs: Vec<&mut [f64]> =
+            channels.iter_mut().map(|c| c.as_mut_slice()).collect();
+        dct.process_dct3_planar(&mut channel_refs, &mut scratch);
+        for (input, output) in inputs.iter().zip(&channels) {
+            assert!(
+                compare_float_vectors(&reference_dct3(input), output),
+                "len = {}",
+                len
+            );
+        }
+    }
+}
+
+// the `unchecked-process` feature skips this check in release builds
+#[test]
+#[should_panic(expected = "channel 1 was len = 9")]
+#[cfg(any(debug_assertions, not(feature = "unchecked-process")))]
+fn test_process_planar_wrong_channel_len() {
+    let mut planner = DctPlanner::new();
+    let dct = planner.plan_dct2(10);
+    let mut first = vec![0f32; 10];
+    let mut second = vec![0f32; 9];
+    let mut scratch = vec![0f32; dct.get_scratch_len()];
+    dct.process_dct2_planar(&mut [&mut first, &mut second], &mut scratch);
+}
+
+#[test]
+fn test_process_ring() {
+    let mut planner = DctPlanner::new();
+    for len in (1..20).chain(vec![32, 64, 100]) {
+        let dct = planner.plan_dct2(len);
+        let input = random_signal(len);
+        let expected = reference_dct2(&input);
+        let mut scratch = vec![0.0; dct.get_scratch_len()];
+
+        // every split point, including the ones where one of the slices is empty
+        for split in 0..=len {
+            let (first, second) = input.split_at(split);
+            let mut output = vec![0.0; len];
+            dct.process_dct2_ring(first, second, &mut output, &mut scratch);
+            assert!(
+                compare_float_vectors(&expected, &output),
+                "len = {}, split = {}",
+                len,
+                split
+            );
+        }
+    }
+}
+
+// the `unchecked-process` feature skips this check in release builds
+#[test]
+#[should_panic(expected = "got lens 6 + 3")]
+#[cfg(any(debug_assertions, not(feature = "unchecked-process")))]
+fn test_process_ring_wrong_len() {
+    let mut planner = DctPlanner::new();
+    let dct = planner.plan_dct2(10);
+    let first = vec![0f32; 6];
+    let second = vec![0f32; 3];
+    let mut output = vec![0f32; 10];
+    let mut scratch = vec![0f32; dct.get_scratch_len()];
+    dct.process_dct2_ring(&first, &second, &mut output, &mut scratch);
+}
+
+#[test]
+fn test_recommended_fast_size_above() {
+    let planner = DctPlanner::<f32>::new();
+    let kinds = [
+        DctType::Dct1,
+        DctType::Dct2,
+        DctType::Dct3,
+        DctType::Dct4,
+        DctType::Dct6,
+        DctType::Dct7,
+        DctType::Dct8,
+        DctType::Dst1,
+        DctType::Dst2,
+        DctType::Dst3,
+        DctType::Dst4,
+        DctType::Dst6,
+        DctType::Dst7,
+        DctType::Dst8,
+    ];
+    for &kind in &kinds {
+        // compare with a linear search
+        for len in 0..2000 {
+            let expected = (len.max(1)..).find(|&size| planner.is_fast_size(size, kind));
+            assert_eq!(
+                planner.recommended_fast_size_above(len, kind),
+                expected,
+                "{:?}, len = {}",
+                kind,
+                len
+            );
+        }
+
+        // sizes near the top of the range finish quickly, and never overflow
+        for &len in &[usize::MAX / 3, usize::MAX - 1000, usize::MAX] {
+            if let Some(size) = planner.recommended_fast_size_above(len, kind) {
+                assert!(size >= len && planner.is_fast_size(size, kind));
+            }
+        }
+    }
+
+    assert!(planner.is_fast_size(1000, DctType::Dct2));
+    assert!(!planner.is_fast_size(1009, DctType::Dct2));
+    assert!(!planner.is_fast_size(0, DctType::Dct2));
+    assert_eq!(planner.recommended_fast_size_above(10, DctType::Dct5), None);
+    assert_eq!(planner.recommended_fast_size_above(10, DctType::Dst5), None);
+}
+
+#[test]
+fn test_compensated_accumulation() {
+    let len = 5000;
+    let input: Vec<f32> = random_signal(len);
+    let mut expected: Vec<f64> = input.iter().map(|&x| x as f64).collect();
+    Dct5Naive::new(len).process_dct5(&mut expected);
+
+    let max_error = |accumulation| {
+        let mut planner = DctPlanner::new();
+        planner.set_accumulation(accumulation);
+        assert_eq!(planner.accumulation(), accumulation);
+
+        let mut actual = input.clone();
+        planner.plan_dct5(len).process_dct5(&mut actual);
+        expected
+            .iter()
+            .zip(&actual)
+            .map(|(e, a)| (e - *a as f64).abs())
+            .fold(0.0, f64::max)
+    };
+
+    // the compensated error is about the error of the products, which is much less than the error of the simple sum
+    let simple_error = max_error(Accumulation::Simple);
+    let compensated_error = max_error(Accumulation::Compensated);
+    assert!(
+        compensated_error * 4.0 < simple_error,
+        "compensated error = {}, simple error = {}",
+        compensated_error,
+        simple_error
+    );
+}
+
+#[test]
+fn test_accumulation_after_planning() {
+    // switching the accumulation after planning must not return the instances that were cached with the previous one
+    let mut planner = DctPlanner::<f32>::new();
+    let simple_dct5 = planner.plan_dct5(100);
+    let simple_dct4 = planner.plan_dct4(100);
+    let simple_mdct = planner.plan_mdct(100, window_fn::mp3);
+
+    planner.set_accumulation(Accumulation::Compensated);
+    assert!(!Arc::ptr_eq(&simple_dct5, &planner.plan_dct5(100)));
+    assert!(!Arc::ptr_eq(&simple_dct4, &planner.plan_dct4(100)));
+    assert!(!Arc::ptr_eq(
+        &simple_mdct,
+        &planner.plan_mdct(100, window_fn::mp3)
+    ));
+
+    // and switching back returns the original instances
+    planner.set_accumulation(Accumulation::Simple);
+    assert!(Arc::ptr_eq(&simple_dct5, &planner.plan_dct5(100)));
+    assert!(Arc::ptr_eq(&simple_dct4, &planner.plan_dct4(100)));
+}
+
+#[test]
+fn test_process_scaled() {
+    let mut planner = DctPlanner::new();
+
+    // butterflies, naive, split radix, real FFTs, and FFTs
+    for &len in [1, 2, 3, 4, 7, 16, 30, 64, 97, 100, 1031].iter() {
+        let scale = 2.0 / len as f32;
+        let dcts: Vec<(&str, Arc<dyn TransformType2And3<f32>>)> = vec![
+            ("planned", planner.plan_dct2(len)),
+            ("naive", Arc::new(Type2And3Naive::new(len))),
+            (
+                "dst via dct",
+                Arc::new(DstViaDct::new(planner.plan_dct2(len))),
+            ),
+            ("mixed precision", planner.plan_dct2_mixed_precision(len)),
+            (
+                "dst convention",
+                planner.plan_dst2_with_convention(len, DstConvention::Fftw),
+            ),
+        ];
+
+        for (name, dct) in dcts {
+            let input: Vec<f32> = random_signal(len);
+            let mut scratch = vec![0f32; dct.get_scratch_len()];
+
+            let mut expected = input.clone();
+            dct.process_dct3_with_scratch(&mut expected, &mut scratch);
+            expected.iter_mut().for_each(|element| *element *= scale);
+            let mut actual = input.clone();
+            dct.process_dct3_scaled(&mut actual, &mut scratch, scale);
+            assert!(
+                compare_float_vectors(&expected, &actual),
+                "dct3 {} len = {}",
+                name,
+                len
+            );
+
+            let mut expected = input.clone();
+            dct.process_dst3_with_scratch(&mut expected, &mut scratch);
+            expected.iter_mut().for_each(|element| *element *= scale);
+            let mut actual = input;
+            dct.process_dst3_scaled(&mut actual, &mut scratch, scale);
+            assert!(
+                compare_float_vectors(&expected, &actual),
+                "dst3 {} len = {}",
+                name,
+                len
+            );
+        }
+    }
+}
+
+#[test]
+fn test_process_dct2_two() {
+    let mut planner = DctPlanner::new();
+
+    // butterflies, naive, split radix, real FFTs, and FFTs
+    for &len in [1, 2, 3, 4, 7, 16, 30, 64, 97, 100, 1031, 2050].iter() {
+        let dcts: Vec<(&str, Arc<dyn TransformType2And3<f64>>)> = vec![
+            ("planned", planner.plan_dct2(len)),
+            ("planned two", planner.plan_dct2_two(len)),
+            ("naive", Arc::new(Type2And3Naive::new(len))),
+            (
+                "dst via dct",
+                Arc::new(DstViaDct::new(planner.plan_dct2_two(len))),
+            ),
+            (
+                "dst convention",
+                planner.plan_dst2_with_convention(len, DstConvention::Fftw),
+            ),
+        ];
+
+        // `random_signal` always starts with the same values, so take the two signals from one longer signal
+        let signals: Vec<f64> = random_signal(len * 2);
+        let expected_a = reference_dct2(&signals[..len]);
+        let expected_b = reference_dct2(&signals[len..]);
+
+        for (name, dct) in dcts {
+            let mut a = signals[..len].to_vec();
+            let mut b = signals[len..].to_vec();
+            let mut scratch = vec![0f64; dct.get_scratch_len()];
+            dct.process_dct2_two(&mut a, &mut b, &mut scratch);
+            assert!(
+                compare_float_vectors(&expected_a, &a),
+                "a {} len = {}",
+                name,
+                len
+            );
+            assert!(
+                compare_float_vectors(&expected_b, &b),
+                "b {} len = {}",
+                name,
+                len
+            );
+        }
+    }
+}
+
+#[test]
+#[cfg(any(debug_assertions, not(feature = "unchecked-process")))]
+#[should_panic(expected = "Provided buffer b must be equal to the transform size")]
+fn test_process_dct2_two_wrong_len() {
+    let dct = DctPlanner::new().plan_dct2_two(100);
+    let mut scratch = vec![0f32; dct.get_scratch_len()];
+    dct.process_dct2_two(&mut vec![0.0; 100], &mut vec![0.0; 99], &mut scratch);
+}
+
+#[test]
+fn test_mdct_convenience() {
+    let mut planner = DctPlanner::new();
+    for &len in [2, 4, 6, 8, 16, 32, 34, 64, 100].iter() {
+        let mdct = planner.plan_mdct(len, window_fn::vorbis);
+        if [4, 8, 16, 32].contains(&len) {
+            assert_eq!(mdct.get_scratch_len(), 0, "len = {}", len);
+        }
+
+        let input: Vec<f32> = random_signal(len * 2);
+        let (input_a, input_b) = input.split_at(len);
+        let mut scratch = vec![0f32; mdct.get_scratch_len()];
+
+        let mut expected = vec![0f32; len];
+        mdct.process_mdct_with_scratch(input_a, input_b, &mut expected, &mut scratch);
+        let mut actual = vec![0f32; len];
+        mdct.process_mdct(input_a, input_b, &mut actual);
+        assert_eq!(expected, actual, "len = {}", len);
+
+        // the IMDCT adds to its output, so start with nonzero outputs
+        let mut expected = input.clone();
+        let (expected_a, expected_b) = expected.split_at_mut(len);
+        mdct.process_imdct_with_scratch(&actual, expected_a, expected_b, &mut scratch);
+        let mut output = input.clone();
+        let (output_a, output_b) = output.split_at_mut(len);
+        mdct.process_imdct(&actual, output_a, output_b);
+        assert_eq!(expected, output, "len = {}", len);
+    }
+}
+
+fn assert_send_sync<T: Send + Sync + ?Sized>() {}
+
+#[test]
+fn test_plans_are_send_sync() {
+    assert_send_sync::<DctPlanner<f32>>();
+    assert_send_sync::<Type2And3Naive<f64>>();
+    assert_send_sync::<DstViaDct<dyn TransformType2And3<f32>>>();
+    assert_send_sync::<Type2And3Pair<f32>>();
+    assert_send_sync::<dyn Mdct<f32>>();
+}
+
+// A process method of a shared plan, which every thread calls with its own buffer and scratch
+type SharedProcess = Arc<dyn Fn(&mut [f32], &mut [f32]) + Send + Sync>;
+
+#[test]
+fn test_concurrent_process() {
+    let mut planner = DctPlanner::new();
+    let mut processes: Vec<(&str, usize, usize, SharedProcess)> = Vec::new();
+    for &len in [4, 17, 64, 100, 1031].iter() {
+        let dct = planner.plan_dct2(len);
+        let scratch_len = dct.get_scratch_len();
+        let shared = Arc::clone(&dct);
+        let process: SharedProcess = Arc::new(move |b, s| shared.process_dct2_with_scratch(b, s));
+        processes.push(("dct2", len, scratch_len, process));
+        let shared = Arc::clone(&dct);
+        let process: SharedProcess = Arc::new(move |b, s| shared.process_dct3_with_scratch(b, s));
+        processes.push(("dct3", len, scratch_len, process));
+        let process: SharedProcess = Arc::new(move |b, s| dct.process_dst3_with_scratch(b, s));
+        processes.push(("dst3", len, scratch_len, process));
+
+        let dct4 = planner.plan_dct4(len);
+        let scratch_len = dct4.get_scratch_len();
+        let process: SharedProcess = Arc::new(move |b, s| dct4.process_dct4_with_scratch(b, s));
+        processes.push(("dct4", len, scratch_len, process));
+    }
+
+    // every thread gets its own inputs, and the expected outputs are computed on a single thread
+    let thread_count = 8;
+    let cases: Vec<Vec<(Vec<f32>, Vec<f32>)>> = (0..thread_count)
+        .map(|_| {
+            processes
+                .iter()
+                .map(|(_, len, scratch_len, process)| {
+                    let input: Vec<f32> = random_signal(*len);
+                    let mut expected = input.clone();
+                    process(&mut expected, &mut vec![0f32; *scratch_len]);
+                    (input, expected)
+                })
+                .collect()
+        })
+        .collect();
+
+    let processes = Arc::new(processes);
+    let threads: Vec<_> = cases
+        .into_iter()
+        .map(|thread_cases| {
+            let processes = Arc::clone(&processes);
+            std::thread::spawn(move || {
+                for _ in 0..50 {
+                    for ((name, len, scratch_len, process), (input, expected)) in
+                        processes.iter().zip(thread_cases.iter())
+                    {
+                        let mut buffer = input.clone();
+                        process(&mut buffer, &mut vec![0f32; *scratch_len]);
+                        assert_eq!(&buffer, expected, "{} len = {}", name, len);
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for thread in threads {
+        thread.join().unwrap();
+    }
+}
+
+// Records every stage the planner reports, and whether it started or finished
+#[derive(Default)]
+struct RecordingObserver {
+    events: Mutex<Vec<(bool, PlanningStage)>>,
+}
+impl ProgressObserver for RecordingObserver {
+    fn stage_started(&self, stage: PlanningStage) {
+        self.events.lock().unwrap().push((true, stage));
+    }
+    fn stage_finished(&self, stage: PlanningStage) {
+        self.events.lock().unwrap().push((false, stage));
+    }
+}
+
+#[test]
+fn test_progress_observer() {
+    let observer = Arc::new(RecordingObserver::default());
+    let mut planner = DctPlanner::<f32>::new();
+    planner.set_progress_observer(observer.clone());
+
+    // 1031 is prime, so the DCT2 is converted to an FFT of the same size
+    planner.plan_dct2(1031);
+    planner.plan_dct2_mixed_precision(30);
+    let events = observer.events.lock().unwrap().clone();
+
+    // every finished stage matches the most recent unfinished one
+    let mut open = Vec::new();
+    for &(started, stage) in events.iter() {
+        if started {
+            open.push(stage);
+        } else {
+            assert_eq!(open.pop(), Some(stage));
+        }
+    }
+    assert!(open.is_empty());
+
+    assert!(events.contains(&(true, PlanningStage::FftPlanning { len: 1031 })));
+    let dct2 = PlanningStage::TwiddleGeneration {
+        transform: "DCT2",
+        len: 1031,
+    };
+    assert!(events.contains(&(true, dct2)));
+    // the mixed precision instance's inner f64 planner reports to the same observer
+    assert!(events.contains(&(true, PlanningStage::FftPlanning { len: 30 })));
+
+    // cached instances aren't planned again, and removed observers aren't called
+    planner.plan_dct2(1031);
+    planner.remove_progress_observer();
+    planner.plan_dct4(100);
+    assert_eq!(observer.events.lock().unwrap().len(), events.len());
+}
+
+#[test]
+fn test_sine_series() {
+    let mut planner = DctPlanner::new();
+    for len in 1..20 {
+        // sample a known sine series at the interior grid points, and make sure the coefficients come back out
+        let expected_coefficients: Vec<f64> = random_signal(len);
+        let values: Vec<f64> = (1..=len)
+            .map(|j| {
+                let x = j as f64 / (len + 1) as f64;
+                expected_coefficients
+                    .iter()
+                    .enumerate()
+                    .map(|(k, b)| b * (std::f64::consts::PI * (k + 1) as f64 * x).sin())
+                    .sum()
+            })
+            .collect();
+
+        let mut buffer = values.clone();
+        modal::sine_series_coefficients(&mut planner, &mut buffer);
+        assert!(
+            compare_float_vectors(&expected_coefficients, &buffer),
+            "len = {}",
+            len
+        );
+
+        modal::sine_series_values(&mut planner, &mut buffer);
+        assert!(compare_float_vectors(&values, &buffer), "len = {}", len);
+    }
+
+    assert_eq!(
+        modal::modal_frequencies(3, 2.0f32, 100.0),
+        vec![25.0, 50.0, 75.0]
+    );
+}
+
+#[test]
+fn test_max_scratch_of() {
+    let mut planner = DctPlanner::<f32>::new();
+    let dct2 = planner.plan_dct2(100);
+    let dct4 = planner.plan_dct4(63);
+    let dst1: Box<dyn Dst1<f32>> = Box::new(Dst1Naive::new(10));
+
+    let expected = dct2
+        .get_scratch_len()
+        .max(dct4.get_scratch_len())
+        .max(dst1.get_scratch_len());
+    assert_eq!(max_scratch_of(&[&dct2, &dct4, &dst1]), expected);
+    assert_eq!(max_scratch_of(&[&dst1]), dst1.get_scratch_len());
+    assert_eq!(max_scratch_of(&[]), 0);
+}
+
+#[test]
+fn test_complex_scratch() {
+    let mut planner = DctPlanner::<f32>::new();
+    for &len in &[7, 64, 1000, 1009] {
+        let dct2 = planner.plan_dct2(len);
+        assert!(2 * dct2.get_complex_scratch_len() <= dct2.get_scratch_len());
+
+        // a scratch buffer allocated as complex numbers gives the same output as a flat one
+        let input = random_signal::<f32>(len);
+        let mut expected = input.clone();
+        dct2.process_dct2(&mut expected);
+
+        let mut actual = input;
+        let mut complex_scratch = vec![Complex::new(0f32, 0f32); (dct2.get_scratch_len() + 1) / 2];
+        dct2.process_dct2_with_scratch(&mut actual, flatten_complex_scratch(&mut complex_scratch));
+        assert_eq!(actual, expected, "len = {}", len);
+    }
+
+    // large sizes compute an FFT, and view their scratch as complex numbers
+    assert!(planner.plan_dct2(1000).get_complex_scratch_len() > 0);
+    assert_eq!(Type2And3Naive::<f32>::new(10).get_complex_scratch_len(), 0);
+
+    // plans that pass their scratch to an inner plan that computes an FFT report the inner plan's complex scratch
+    // the even DCT4s have an odd inner DCT3, which converts to a full-size FFT that needs more scratch than `len`
+    let plans: Vec<Box<dyn RequiredScratch>> = vec![
+        Box::new(planner.plan_dct4(1002)),
+        Box::new(planner.plan_dct4(1001)),
+        Box::new(planner.plan_dct6(1000)),
+        Box::new(planner.plan_dct8(1000)),
+        Box::new(planner.plan_mdct(1006, window_fn::mp3)),
+        Box::new(planner.plan_mdct(1002, window_fn::mp3)),
+        Box::new(planner.plan_dct2_with_output_order(1000, OutputOrder::EvensThenOdds)),
+    ];
+    for plan in plans.iter() {
+        assert!(plan.get_complex_scratch_len() > 0);
+        assert!(2 * plan.get_complex_scratch_len() <= plan.get_scratch_len());
+    }
+}
+
+#[test]
+fn test_scratch_layout() {
+    fn check_layout(plan: &dyn RequiredScratch) {
+        let layout = plan.scratch_layout();
+        assert_eq!(layout.total_len(), plan.get_scratch_len());
+
+        let mut offset = 0;
+        for region in layout.regions() {
+            assert_eq!(region.offset, offset, "{:?}", layout);
+            assert!(region.len > 0, "{:?}", layout);
+            offset += region.len;
+        }
+    }
+
+    let mut planner = DctPlanner::<f32>::new();
+    for &len in &[7, 10, 64, 101, 1000, 4096] {
+        check_layout(&planner.plan_dct1(len));
+        check_layout(&planner.plan_dst1(len));
+        check_layout(&planner.plan_dct2(len));
+        check_layout(&planner.plan_dct4(len));
+        check_layout(&planner.plan_dct6(len));
+        check_layout(&planner.plan_dst6(len));
+        check_layout(&planner.plan_dct8(len));
+        check_layout(&planner.plan_dst8(len));
+        check_layout(&planner.plan_mdct(2 * len, window_fn::mp3));
+        check_layout(&planner.plan_dct2_with_output_order(len, OutputOrder::EvensThenOdds));
+    }
+
+    // composite plans describe their inner plan's regions, one level deeper than their own
+    for layout in &[
+        planner.plan_dct4(1002).scratch_layout(),
+        planner.plan_dct8(1000).scratch_layout(),
+        planner.plan_mdct(1000, window_fn::mp3).scratch_layout(),
+    ] {
+        assert!(layout.regions().len() > 1, "{:?}", layout);
+        assert!(
+            layout.regions().iter().any(|region| region.depth > 0),
+            "{:?}",
+            layout
+        );
+    }
+
+    // an odd DCT4 is converted to a FFT of the same size
+    let dct4 = planner.plan_dct4(1001);
+    let layout = dct4.scratch_layout();
+    let fft_buffer = layout.region("FFT buffer").unwrap();
+    assert_eq!((fft_buffer.offset, fft_buffer.len), (0, 2 * 1001));
+    assert!(fft_buffer.complex);
+    assert_eq!(fft_buffer.depth, 0);
+}
+
+#[test]
+fn test_plan_quality() {
+    let mut planner = DctPlanner::<f32>::new();
+    assert_eq!(planner.plan_quality(0, DctType::Dct2), PlanQuality::Good);
+    assert_eq!(planner.plan_quality(1000, DctType::Dct4), PlanQuality::Good);
+    assert_eq!(planner.plan_quality(4, DctType::Dct5), PlanQuality::Good);
+    assert_eq!(
+        planner.plan_quality(100, DctType::Dst5),
+        PlanQuality::Acceptable(QualityIssue::NaiveOnly)
+    );
+    assert_eq!(
+        planner.plan_quality(300, DctType::Dct5),
+        PlanQuality::Poor(QualityIssue::NaiveOnly)
+    );
+
+    // the DCT1 of size 1010 is computed with a FFT of size 1009
+    assert_eq!(
+        planner.plan_quality(1010, DctType::Dct1),
+        PlanQuality::Acceptable(QualityIssue::LargePrimeFactor {
+            fft_len: 1009,
+            prime_factor: 1009
+        })
+    );
+
+    // 2^20 + 7 is prime
+    let prime = 1_048_583;
+    let error = planner
+        .check_plan_quality(prime, DctType::Dst2)
+        .unwrap_err();
+    assert_eq!(error.kind, DctType::Dst2);
+    assert_eq!(error.len, prime);
+    assert_eq!(
+        error.issue,
+        QualityIssue::LargePrimeFactor {
+            fft_len: prime,
+            prime_factor: prime
+        }
+    );
+    assert!(planner.check_plan_quality(1009, DctType::Dct2).is_ok());
+
+    // with the default policy, poor sizes are planned anyway
+    assert_eq!(planner.poor_plan_policy(), PoorPlanPolicy::Allow);
+    planner.plan_dct5(300);
+
+    // the try_plan methods refuse poor sizes, even if they were already planned and cached
+    assert!(planner.try_plan_dct2(1009).is_ok());
+    let error = planner.try_plan_dct5(300).err().unwrap();
+    assert_eq!((error.kind, error.len), (DctType::Dct5, 300));
+    let error = planner.try_plan_dst5(301).err().unwrap();
+    assert_eq!(error.issue, QualityIssue::NaiveOnly);
+    let error = planner.try_plan_dst3(prime).err().unwrap();
+    assert_eq!((error.kind, error.len), (DctType::Dst3, prime));
+}
+
+// Collects the warnings that the planner emits, for test_poor_plan_warning
+#[cfg(feature = "log")]
+struct WarningLogger(Mutex<Vec<String>>);
+#[cfg(feature = "log")]
+impl log::Log for WarningLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::Level::Warn
+    }
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            self.0.lock().unwrap().push(record.args().to_string());
+        }
+    }
+    fn flush(&self) {}
+}
+
+#[cfg(feature = "log")]
+#[test]
+fn test_poor_plan_warning() {
+    static LOGGER: WarningLogger = WarningLogger(Mutex::new(Vec::new()));
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Warn);
+    // other tests plan in parallel, so only look at the warnings for the sizes this test plans
+    let warnings = || -> Vec<String> {
+        let warnings = LOGGER.0.lock().unwrap();
+        warnings
+            .iter()
+            .filter(|warning| warning.contains("size 2097166") || warning.contains("size 1048583"))
+            .cloned()
+            .collect()
+    };
+
+    let mut planner = DctPlanner::<f32>::new();
+    planner.set_poor_plan_policy(PoorPlanPolicy::Warn);
+    assert_eq!(planner.poor_plan_policy(), PoorPlanPolicy::Warn);
+
+    // the DCT4 is built from a poor DCT3 of size 1048583, but only the requested DCT4 is reported
+    assert!(planner.plan_quality(1_048_583, DctType::Dct3).is_poor());
+    planner.plan_dct4(2_097_166);
+    let expected = vec!["planned Dct4 of size 2097166, which is much slower than nearby sizes"];
+    let starts: Vec<String> = warnings()
+        .iter()
+        .map(|warning| warning.split(':').next().unwrap().to_owned())
+        .collect();
+    assert_eq!(starts, expected);
+
+    // cached instances aren't reported again
+    planner.plan_dct4(2_097_166);
+    assert_eq!(warnings().len(), 1);
+}
+
+#[test]
+fn test_to_owned_butterfly() {
+    let mut planner = DctPlanner::<f32>::new();
+
+    let dct2 = planner.plan_dct2(8);
+    let butterfly: Type2And3Butterfly8<f32> = dct2.to_owned_butterfly().unwrap();
+    let input = random_signal::<f32>(8);
+    let mut expected = input.clone();
+    let mut actual = input.clone();
+    dct2.process_dct2(&mut expected);
+    butterfly.process_dct2_with_scratch(&mut actual, &mut []);
+    assert!(compare_float_vectors(&expected, &actual));
+
+    let dct4 = planner.plan_dct4(16);
+    let butterfly: Type4Butterfly16<f32> = dct4.to_owned_butterfly().unwrap();
+    let mut expected = random_signal::<f32>(16);
+    let mut actual = expected.clone();
+    dct4.process_dst4(&mut expected);
+    butterfly.process_dst4_with_scratch(&mut actual, &mut []);
+    assert!(compare_float_vectors(&expected, &actual));
+
+    assert!(planner
+        .plan_dct1(5)
+        .to_owned_butterfly::<Dct1Butterfly5<f32>>()
+        .is_some());
+    assert!(planner
+        .plan_dct6(3)
+        .to_owned_butterfly::<Dct6And7Butterfly3<f32>>()
+        .is_some());
+
+    // a butterfly of a different size or transform, or of a plan with options that change its outputs, can't be created
+    assert!(dct2
+        .to_owned_butterfly::<Type2And3Butterfly4<f32>>()
+        .is_none());
+    assert!(dct4
+        .to_owned_butterfly::<Type2And3Butterfly16<f32>>()
+        .is_none());
+    assert!(planner
+        .plan_dst1(5)
+        .to_owned_butterfly::<Dct1Butterfly5<f32>>()
+        .is_none());
+    assert!(planner
+        .plan_dct2_with_output_order(8, OutputOrder::EvensThenOdds)
+        .to_owned_butterfly::<Type2And3Butterfly8<f32>>()
+        .is_none());
+}
+
+#[test]
+fn test_memory_usage() {
+    // a naive DCT2 only has its table of 4 * len complex twiddles
+    let naive = Type2And3Naive::<f32>::new(10);
+    let expected = PlanMemory {
+        twiddles_bytes: 40 * 8,
+        window_bytes: 0,
+        inner_plans_bytes: 0,
+    };
+    assert_eq!(naive.memory_usage(), expected);
+
+    let mut planner = DctPlanner::<f32>::new();
+    let mdct = planner.plan_mdct(64, window_fn::mp3);
+    let mdct_memory = mdct.memory_usage();
+    assert_eq!(mdct_memory.window_bytes, 128 * 4);
+    assert!(mdct_memory.inner_plans_bytes > 0);
+
+    // the DCT4 of size 1024 is computed with the same DCT3 of size 512 as the DCT2 of size 1024, so once the DCT2 has
+    // been counted, only the DCT4's own twiddles are left
+    let dct2 = planner.plan_dct2(1024);
+    let dct4 = planner.plan_dct4(1024);
+    let mut visitor = MemoryVisitor::new();
+    assert_eq!(visitor.inner_plan(&dct2), dct2.memory_usage().total_bytes());
+    assert_eq!(visitor.inner_plan(&dct2), 0);
+    assert_eq!(
+        visitor.inner_plan(&dct4),
+        dct4.memory_usage().twiddles_bytes
+    );
+}
+
+// A downstream algorithm that only implements the traits that the transform traits require
+struct CustomDct3(Type2And3Naive<f64>);
+impl Dct2<f64> for CustomDct3 {
+    fn process_dct2_with_scratch(&self, buffer: &mut [f64], scratch: &mut [f64]) {
+        self.0.process_dct2_with_scratch(buffer, scratch);
+    }
+}
+impl Dct3<f64> for CustomDct3 {
+    fn process_dct3_with_scratch(&self, buffer: &mut [f64], scratch: &mut [f64]) {
+        self.0.process_dct3_with_scratch(buffer, scratch);
+    }
+}
+impl Dst2<f64> for CustomDct3 {
+    fn process_dst2_with_scratch(&self, buffer: &mut [f64], scratch: &mut [f64]) {
+        self.0.process_dst2_with_scratch(buffer, scratch);
+    }
+}
+impl Dst3<f64> for CustomDct3 {
+    fn process_dst3_with_scratch(&self, buffer: &mut [f64], scratch: &mut [f64]) {
+        self.0.process_dst3_with_scratch(buffer, scratch);
+    }
+}
+impl TransformType2And3<f64> for CustomDct3 {}
+impl RequiredScratch for CustomDct3 {
+    fn get_scratch_len(&self) -> usize {
+        self.0.get_scratch_len()
+    }
+}
+impl rustdct::rustfft::Length for CustomDct3 {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+#[test]
+fn test_custom_inner_plan() {
+    let inner: Arc<dyn TransformType2And3<f64>> = Arc::new(CustomDct3(Type2And3Naive::new(8)));
+    let dct2 = DctPlanner::new().plan_dct2_with_inner(32, Arc::clone(&inner));
+
+    let input = random_signal(32);
+    let mut actual_buffer = input.clone();
+    dct2.process_dct2(&mut actual_buffer);
+    assert!(compare_float_vectors(
+        &reference_dct2(&input),
+        &actual_buffer
+    ));
+
+    // without the hooks, a plan reports no memory, and an opaque key that isn't equal to any key
+    assert_eq!(inner.memory_usage(), PlanMemory::default());
+    assert_ne!(inner.plan_key(), inner.plan_key());
+    assert_eq!(inner.plan_key().len, 8);
+    assert_ne!(
+        inner.plan_key(),
+        DctPlanner::<f64>::new().plan_dct2(8).plan_key()
+    );
+    assert!(dct2.memory_usage().twiddles_bytes > 0);
+}
+
+#[test]
+fn test_memory_usage_shared_twiddles() {
+    // a DCT2 with a permuted output order shares its twiddle table with the DCT2 of the same size, so once the DCT2
+    // has been counted, the reordered DCT2 only adds its table of output positions
+    let mut planner = DctPlanner::<f32>::new();
+    for &len in &[9, 64] {
+        let dct2 = planner.plan_dct2(len);
+        let reordered = planner.plan_dct2_with_output_order(len, OutputOrder::EvensThenOdds);
+        assert!(reordered.memory_usage().inner_plans_bytes > 0);
+
+        let mut visitor = MemoryVisitor::new();
+        assert_eq!(visitor.inner_plan(&dct2), dct2.memory_usage().total_bytes());
+        assert_eq!(
+            visitor.inner_plan(&reordered),
+            reordered.memory_usage().twiddles_bytes,
+            "len = {}",
+            len
+        );
+    }
+}
+
+#[test]
+fn test_dct_transform() {
+    let mut planner = DctPlanner::new();
+    let plans: Vec<(DctType, Box<dyn DctTransform<f64>>)> = vec![
+        (DctType::Dct1, Box::new(planner.plan_dct1(11))),
+        (DctType::Dct2, Box::new(planner.plan_dct2(12))),
+        (DctType::Dst3, Box::new(planner.plan_dst3(13))),
+        (DctType::Dst4, Box::new(planner.plan_dst4(14))),
+        (DctType::Dct7, Box::new(planner.plan_dct7(15))),
+        (DctType::Dst8, Box::new(planner.plan_dst8(16))),
+    ];
+    let reference_fns: [ReferenceFn; 6] = [
+        reference_dct1,
+        reference_dct2,
+        reference_dst3,
+        reference_dst4,
+        reference_dct7,
+        reference_dst8,
+    ];
+
+    for ((kind, plan), reference_fn) in plans.iter().zip(reference_fns.iter()) {
+        let input = random_signal(plan.transform_len());
+        let expected = reference_fn(&input);
+
+        let mut actual = input.clone();
+        let mut scratch = vec![0f64; plan.get_scratch_len()];
+        plan.execute(DctBuffers::InPlace {
+            kind: *kind,
+            buffer: &mut actual,
+            scratch: &mut scratch,
+        });
+        assert!(compare_float_vectors(&expected, &actual), "{:?}", kind);
+    }
+
+    let mdct = planner.plan_mdct(8, window_fn::mp3);
+    let transform: Box<dyn DctTransform<f64>> = Box::new(Arc::clone(&mdct));
+    assert_eq!(transform.transform_len(), 8);
+
+    let input = random_signal(16);
+    let mut expected = vec![0f64; 8];
+    let mut scratch = vec![0f64; mdct.get_scratch_len()];
+    mdct.process_mdct_with_scratch(&input[..8], &input[8..], &mut expected, &mut scratch);
+    let mut actual = vec![0f64; 8];
+    transform.execute(DctBuffers::Mdct {
+        input_a: &input[..8],
+        input_b: &input[8..],
+        output: &mut actual,
+        scratch: &mut scratch,
+    });
+    assert!(compare_float_vectors(&expected, &actual));
+}
+
+#[test]
+#[should_panic(expected = "can't compute a Dct2")]
+fn test_dct_transform_unsupported() {
+    let mut planner = DctPlanner::<f32>::new();
+    let transform: Box<dyn DctTransform<f32>> = Box::new(planner.plan_dct4(8));
+    let mut buffer = vec![0f32; 8];
+    let mut scratch = vec![0f32; transform.get_scratch_len()];
+    transform.execute(DctBuffers::InPlace {
+        kind: DctType::Dct2,
+        buffer: &mut buffer,
+        scratch: &mut scratch,
+    });
+}
+
+#[test]
+fn test_plan_key() {
+    // plans from different planners compute the same outputs, so they should have equal keys
+    let dct2 = DctPlanner::<f32>::new().plan_dct2(100);
+    let other_dct2 = DctPlanner::<f32>::new().plan_dct2(100);
+    assert_eq!(dct2.plan_key(), other_dct2.plan_key());
+    assert_eq!(dct2.plan_key().kind, PlanKind::Type2And3);
+    assert_eq!(dct2.plan_key().len, 100);
+
+    let mut planner = DctPlanner::<f32>::new();
+    assert_ne!(dct2.plan_key(), planner.plan_dct2(101).plan_key());
+    assert_ne!(
+        planner.plan_dct1(100).plan_key(),
+        planner.plan_dst1(100).plan_key()
+    );
+    assert_eq!(planner.plan_dct4(100).plan_key().kind, PlanKind::Type4);
+    assert_eq!(planner.plan_dst7(100).plan_key().kind, PlanKind::Dst6And7);
+
+    // the output order changes the outputs, so it's part of the key
+    let natural = planner.plan_dct2_with_output_order(16, OutputOrder::Natural);
+    let evens_then_odds = planner.plan_dct2_with_output_order(16, OutputOrder::EvensThenOdds);
+    assert_eq!(natural.plan_key().kind, PlanKind::Dct2);
+    assert_ne!(natural.plan_key(), evens_then_odds.plan_key());
+    assert_eq!(
+        evens_then_odds.plan_key(),
+        planner
+            .plan_dct2_with_output_order(16, OutputOrder::EvensThenOdds)
+            .plan_key()
+    );
+
+    // so does the window of a MDCT
+    let mp3 = planner.plan_mdct(32, window_fn::mp3);
+    let vorbis = DctPlanner::new().plan_mdct(32, window_fn::vorbis);
+    assert_eq!(
+        mp3.plan_key(),
+        planner.plan_mdct(32, window_fn::mp3).plan_key()
+    );
+    assert_ne!(mp3.plan_key(), vorbis.plan_key());
+
+    // keys can be used to look up data derived from plans
+    let cache = vec![(dct2.plan_key(), "DCT2"), (mp3.plan_key(), "MDCT")];
+    let lookup = |key: PlanKey<f32>| {
+        cache
+            .iter()
+            .find(|(cached, _)| *cached == key)
+            .map(|(_, data)| *data)
+    };
+    assert_eq!(lookup(other_dct2.plan_key()), Some("DCT2"));
+    assert_eq!(lookup(vorbis.plan_key()), None);
+
+    // equal keys have equal hashes
+    let hash = |key: PlanKey<f32>| {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        key.hash(&mut hasher);
+        hasher.finish()
+    };
+    assert_eq!(hash(dct2.plan_key()), hash(other_dct2.plan_key()));
+
+    // trait objects report the key of the algorithm behind them
+    let dct2_object: Arc<dyn Dct2<f32>> = dct2.clone();
+    assert_eq!(dct2_object.plan_key(), dct2.plan_key());
+}
+
+#[test]
+fn test_process_with_boundaries() {
+    use rustdct::BoundaryMode::*;
+    let modes = [
+        WholeSampleSymmetric,
+        HalfSampleSymmetric,
+        WholeSampleAntisymmetric,
+        HalfSampleAntisymmetric,
+    ];
+
+    let mut planner = DctPlanner::new();
+    let mut seen_types = Vec::new();
+    for &start in modes.iter() {
+        for &end in modes.iter() {
+            let expected_kind = DctType::from_boundaries(start, end);
+            assert_eq!(expected_kind.boundaries(), (start, end));
+            assert!(!seen_types.contains(&expected_kind));
+            seen_types.push(expected_kind);
+
+            let input: Vec<f64> = random_signal(10);
+            let expected = match expected_kind {
+                DctType::Dct1 => reference_dct1(&input),
+                DctType::Dct2 => reference_dct2(&input),
+                DctType::Dct3 => reference_dct3(&input),
+                DctType::Dct4 => reference_dct4(&input),
+                DctType::Dct5 => reference_dct5(&input),
+                DctType::Dct6 => reference_dct6(&input),
+                DctType::Dct7 => reference_dct7(&input),
+                DctType::Dct8 => reference_dct8(&input),
+                DctType::Dst1 => reference_dst1(&input),
+                DctType::Dst2 => reference_dst2(&input),
+                DctType::Dst3 => reference_dst3(&input),
+                DctType::Dst4 => reference_dst4(&input),
+                DctType::Dst5 => reference_dst5(&input),
+                DctType::Dst6 => reference_dst6(&input),
+                DctType::Dst7 => reference_dst7(&input),
+                DctType::Dst8 => reference_dst8(&input),
+            };
+
+            let mut actual = input.clone();
+            let kind = planner.process_with_boundaries(&mut actual, start, end);
+            assert_eq!(kind, expected_kind);
+            assert!(compare_float_vectors(&expected, &actual), "{:?}", kind);
+        }
+    }
+
+    // spot check the table against the textbook boundary conditions
+    assert_eq!(
+        DctType::from_boundaries(HalfSampleSymmetric, HalfSampleSymmetric),
+        DctType::Dct2
+    );
+    assert_eq!(
+        DctType::from_boundaries(WholeSampleAntisymmetric, WholeSampleAntisymmetric),
+        DctType::Dst1
+    );
+}
+
+#[test]
+fn test_planner_cost_model() {
+    assert_eq!(
+        DctPlanner::<f32>::new().cost_model(),
+        PlannerCostModel::default()
+    );
+
+    // with crossover sizes past the problem size, the planner uses the naive algorithms, which only need `len` scratch
+    let len = 50;
+    let naive_model = PlannerCostModel {
+        dst6_min_fast_len: 1000,
+        dct8_min_fast_len: 1000,
+        ..PlannerCostModel::default()
+    };
+    let mut naive_planner = DctPlanner::with_cost_model(naive_model);
+    assert_eq!(naive_planner.cost_model(), naive_model);
+    let mut default_planner = DctPlanner::new();
+
+    let input = random_signal(len);
+    let expected = reference_dst6(&input);
+    for dst6 in &[naive_planner.plan_dst6(len), default_planner.plan_dst6(len)] {
+        let mut actual = input.clone();
+        dst6.process_dst6(&mut actual);
+        assert!(compare_float_vectors(&expected, &actual));
+    }
+    assert_eq!(naive_planner.plan_dst6(len).get_scratch_len(), len);
+    assert!(default_planner.plan_dst6(len).get_scratch_len() > len);
+
+    let expected = reference_dct8(&input);
+    for dct8 in &[naive_planner.plan_dct8(len), default_planner.plan_dct8(len)] {
+        let mut actual = input.clone();
+        dct8.process_dct8(&mut actual);
+        assert!(compare_float_vectors(&expected, &actual));
+    }
+    assert_eq!(naive_planner.plan_dct8(len).get_scratch_len(), len);
+    assert!(default_planner.plan_dct8(len).get_scratch_len() > len);
+}
+
+#[test]
+fn test_planner_cost_model_tiny_sizes() {
+    // crossover sizes of 0 are raised to the smallest sizes that the fast algorithms support, so the smallest sizes are
+    // still planned like the default planner plans them
+    let zero_model = PlannerCostModel {
+        dct1_min_fast_len: 0,
+        dct3_min_real_fft_len: 0,
+        dct4_even_min_fast_len: 0,
+        ..PlannerCostModel::default()
+    };
+    let mut planner = DctPlanner::with_cost_model(zero_model);
+    assert_eq!(
+        planner.cost_model(),
+        PlannerCostModel {
+            dct1_min_fast_len: 3,
+            dct3_min_real_fft_len: 2,
+            dct4_even_min_fast_len: 2,
+            ..zero_model
+        }
+    );
+
+    for len in 0..5 {
+        let input = random_signal(len);
+
+        let mut actual = input.clone();
+        planner.plan_dct2(len).process_dct2(&mut actual);
+        assert!(
+            compare_float_vectors(&reference_dct2(&input), &actual),
+            "len = {}",
+            len
+        );
+
+        let mut actual = input.clone();
+        planner.plan_dct3(len).process_dct3(&mut actual);
+        assert!(
+            compare_float_vectors(&reference_dct3(&input), &actual),
+            "len = {}",
+            len
+        );
+
+        let mut actual = input.clone();
+        planner.plan_dct4(len).process_dct4(&mut actual);
+        assert!(
+            compare_float_vectors(&reference_dct4(&input), &actual),
+            "len = {}",
+            len
+        );
+    }
+}
+
+#[test]
+fn test_planner_tiny_sizes_type5to8() {
+    // with crossover sizes of 0, every size past the butterflies is converted, so the conversions get their smallest
+    // inner transforms
+    let convert_model = PlannerCostModel {
+        dct6_min_fast_len: 0,
+        dst6_min_fast_len: 0,
+        dct8_min_fast_len: 0,
+        dst8_min_fast_len: 0,
+        ..PlannerCostModel::default()
+    };
+    for &cost_model in &[PlannerCostModel::default(), convert_model] {
+        let mut planner = DctPlanner::with_cost_model(cost_model);
+        for len in 0..10 {
+            let input = random_signal(len);
+
+            macro_rules! check_plan {
+                ($reference_fn:ident, $planner_fn:ident, $process_fn:ident) => {
+                    let plan = planner.$planner_fn(len);
+                    assert_eq!(plan.len(), len);
+
+                    let mut actual = input.clone();
+                    plan.$process_fn(&mut actual);
+                    assert!(
+                        compare_float_vectors(&$reference_fn(&input), &actual),
+                        "{}, len = {}, cost model = {:?}",
+                        stringify!($planner_fn),
+                        len,
+                        cost_model
+                    );
+                };
+            }
+            check_plan!(reference_dct5, plan_dct5, process_dct5);
+            check_plan!(reference_dct6, plan_dct6, process_dct6);
+            check_plan!(reference_dct7, plan_dct7, process_dct7);
+            check_plan!(reference_dct8, plan_dct8, process_dct8);
+            check_plan!(reference_dst5, plan_dst5, process_dst5);
+            check_plan!(reference_dst6, plan_dst6, process_dst6);
+            check_plan!(reference_dst7, plan_dst7, process_dst7);
+            check_plan!(reference_dst8, plan_dst8, process_dst8);
+        }
+    }
+}
+
+const ALL_TYPES: [DctType; 16] = [
+    DctType::Dct1,
+    DctType::Dct2,
+    DctType::Dct3,
+    DctType::Dct4,
+    DctType::Dct5,
+    DctType::Dct6,
+    DctType::Dct7,
+    DctType::Dct8,
+    DctType::Dst1,
+    DctType::Dst2,
+    DctType::Dst3,
+    DctType::Dst4,
+    DctType::Dst5,
+    DctType::Dst6,
+    DctType::Dst7,
+    DctType::Dst8,
+];
+
+fn plan_transform<T: DctNum>(
+    planner: &mut DctPlanner<T>,
+    kind: DctType,
+    len: usize,
+) -> Box<dyn DctTransform<T>> {
+    match kind {
+        DctType::Dct1 => Box::new(planner.plan_dct1(len)),
+        DctType::Dct2 => Box::new(planner.plan_dct2(len)),
+        DctType::Dct3 => Box::new(planner.plan_dct3(len)),
+        DctType::Dct4 => Box::new(planner.plan_dct4(len)),
+        DctType::Dct5 => Box::new(planner.plan_dct5(len)),
+        DctType::Dct6 => Box::new(planner.plan_dct6(len)),
+        DctType::Dct7 => Box::new(planner.plan_dct7(len)),
+        DctType::Dct8 => Box::new(planner.plan_dct8(len)),
+        DctType::Dst1 => Box::new(planner.plan_dst1(len)),
+        DctType::Dst2 => Box::new(planner.plan_dst2(len)),
+        DctType::Dst3 => Box::new(planner.plan_dst3(len)),
+        DctType::Dst4 => Box::new(planner.plan_dst4(len)),
+        DctType::Dst5 => Box::new(planner.plan_dst5(len)),
+        DctType::Dst6 => Box::new(planner.plan_dst6(len)),
+        DctType::Dst7 => Box::new(planner.plan_dst7(len)),
+        DctType::Dst8 => Box::new(planner.plan_dst8(len)),
+    }
+}
+
+fn process_known_input<T: DctNum + ToPrimitive>(
+    plan: &dyn DctTransform<T>,
+    kind: DctType,
+    input: &[f64],
+) -> Vec<f64> {
+    let mut buffer: Vec<T> = input.iter().map(|&x| T::from_f64(x).unwrap()).collect();
+    let mut scratch = vec![T::zero(); plan.get_scratch_len()];
+    plan.execute(DctBuffers::InPlace {
+        kind,
+        buffer: &mut buffer,
+        scratch: &mut scratch,
+    });
+    buffer.iter().map(|x| x.to_f64().unwrap()).collect()
+}
+
+// Planners that between them select every algorithm for the known answer sizes: the default crossovers, crossovers
+// that convert every size without a butterfly, and crossovers that compute every size without a butterfly naively
+fn known_answer_planners<T: DctNum>() -> Vec<(&'static str, DctPlanner<T>)> {
+    let fast_model = PlannerCostModel {
+        dct1_min_fast_len: 0,
+        dst1_min_fast_len: 0,
+        dct3_min_real_fft_len: 0,
+        dct4_even_min_fast_len: 0,
+        dct4_max_split_radix_len: std::usize::MAX,
+        dct4_odd_min_fast_len: 0,
+        dct6_min_fast_len: 0,
+        dst6_min_fast_len: 0,
+        dct8_min_fast_len: 0,
+        dst8_min_fast_len: 0,
+    };
+    let naive_model = PlannerCostModel {
+        dct1_min_fast_len: std::usize::MAX,
+        dst1_min_fast_len: std::usize::MAX,
+        dct3_min_real_fft_len: std::usize::MAX,
+        dct4_even_min_fast_len: std::usize::MAX,
+        dct4_max_split_radix_len: 0,
+        dct4_odd_min_fast_len: std::usize::MAX,
+        dct6_min_fast_len: std::usize::MAX,
+        dst6_min_fast_len: std::usize::MAX,
+        dct8_min_fast_len: std::usize::MAX,
+        dst8_min_fast_len: std::usize::MAX,
+    };
+    vec![
+        ("default", DctPlanner::new()),
+        ("fast", DctPlanner::with_cost_model(fast_model)),
+        ("naive", DctPlanner::with_cost_model(naive_model)),
+    ]
+}
+
+// `precision` is the tolerance relative to the sum of the input's magnitudes
+fn check_known_answers<T: DctNum + ToPrimitive>(precision: f64) {
+    for (planner_name, mut planner) in known_answer_planners::<T>() {
+        for &kind in ALL_TYPES.iter() {
+            // the small known values are rounded to 4 decimal places
+            let small = known_values(kind).into_iter().map(|entry| (entry, 0.001));
+            let large = known_answers(kind).into_iter().map(|entry| (entry, 0.0));
+            for (entry, rounding) in small.chain(large) {
+                let len = entry.input.len();
+                let plan = plan_transform(&mut planner, kind, len);
+                let output = process_known_input(&*plan, kind, &entry.input);
+
+                let magnitude: f64 = entry.input.iter().map(|x| x.abs()).sum();
+                let tolerance = rounding + precision * (1.0 + magnitude);
+                for (k, (&expected, &actual)) in
+                    entry.expected_output.iter().zip(&output).enumerate()
+                {
+                    assert!(
+                        (expected - actual).abs() <= tolerance,
+                        "{} planner, {:?} of size {}, output {}: expected {}, got {}",
+                        planner_name,
+                        kind,
+                        len,
+                        k,
+                        expected,
+                        actual
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_known_answers() {
+    check_known_answers::<f32>(1e-5);
+    check_known_answers::<f64>(1e-12);
+}
+
+#[test]
+fn test_codec_matrices() {
+    let mut planner = DctPlanner::<f64>::new();
+    for entry in known_codec_matrices() {
+        let len = entry.matrix.len();
+        let plan = plan_transform(&mut planner, entry.kind, len);
+
+        // the unnormalized transform of each unit vector is a column of the transform matrix. scaling it by
+        // `sqrt(scaling_factor)` makes it orthonormal, except for the first row of a DCT2
+        let orthonormal_scale = scaling_factor::<f64>(entry.kind, len).sqrt();
+        let codec_scale = 64.0 * (len as f64).sqrt();
+        for n in 0..len {
+            let mut unit = vec![0.0; len];
+            unit[n] = 1.0;
+            let column = process_known_input(&*plan, entry.kind, &unit);
+            for (k, &value) in column.iter().enumerate() {
+                let mut scaled = value * orthonormal_scale * codec_scale;
+                if entry.kind == DctType::Dct2 && k == 0 {
+                    scaled /= 2f64.sqrt();
+                }
+                assert!(
+                    (scaled - entry.matrix[k][n] as f64).abs() <= 1.5,
+                    "{:?} of size {}, row {}, column {}: {} is too far from {}",
+                    entry.kind,
+                    len,
+                    k,
+                    n,
+                    scaled,
+                    entry.matrix[k][n]
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_planner_bounded_scratch() {
+    let mut planner = DctPlanner::new();
+    for &len in &[1, 7, 16, 30, 64, 100, 101, 128] {
+        let input = random_signal(len);
+
+        let expected = reference_dct2(&input);
+        let unbounded_len = planner.plan_dct2(len).get_scratch_len();
+        for &max_scratch_len in &[len, unbounded_len] {
+            let dct2 = planner.plan_dct2_bounded(len, max_scratch_len).unwrap();
+            assert!(dct2.get_scratch_len() <= max_scratch_len);
+
+            let mut actual = input.clone();
+            let mut scratch = vec![0.0; max_scratch_len];
+            dct2.process_dct2_with_scratch(&mut actual, &mut scratch);
+            assert!(
+                compare_float_vectors(&expected, &actual),
+                "dct2, len = {}",
+                len
+            );
+        }
+
+        let expected = reference_dct4(&input);
+        let unbounded_len = planner.plan_dct4(len).get_scratch_len();
+        for &max_scratch_len in &[len, unbounded_len] {
+            let dct4 = planner.plan_dct4_bounded(len, max_scratch_len).unwrap();
+            assert!(dct4.get_scratch_len() <= max_scratch_len);
+
+            let mut actual = input.clone();
+            let mut scratch = vec![0.0; max_scratch_len];
+            dct4.process_dct4_with_scratch(&mut actual, &mut scratch);
+            assert!(
+                compare_float_vectors(&expected, &actual),
+                "dct4, len = {}",
+                len
+            );
+        }
+    }
+
+    // the butterflies don't need any scratch, and every other algorithm needs at least `len`
+    assert_eq!(
+        planner.plan_dct2_bounded(16, 0).unwrap().get_scratch_len(),
+        0
+    );
+    assert_eq!(
+        planner.plan_dct4_bounded(32, 0).unwrap().get_scratch_len(),
+        0
+    );
+    assert_eq!(
+        planner.plan_dct2_bounded(100, 99).err(),
+        Some(ScratchLimitError {
+            kind: DctType::Dct2,
+            len: 100,
+            max_scratch_len: 99,
+            min_scratch_len: 100,
+        })
+    );
+    assert_eq!(
+        planner
+            .plan_dct4_bounded(101, 0)
+            .err()
+            .map(|error| error.kind),
+        Some(DctType::Dct4)
+    );
+
+    // the MDCT falls back to the naive algorithm, which doesn't need any scratch
+    let len = 40;
+    let input = random_signal(len * 2);
+    let mut expected = vec![0.0; len];
+    MdctNaive::new(len, window_fn::mp3).process_mdct_with_scratch(
+        &input[..len],
+        &input[len..],
+        &mut expected,
+        &mut [],
+    );
+    for &max_scratch_len in &[0, len, len * 2, len * 4] {
+        let mdct = planner.plan_mdct_bounded(len, window_fn::mp3, max_scratch_len);
+        assert!(mdct.get_scratch_len() <= max_scratch_len);
+
+        let mut actual = vec![0.0; len];
+        let mut scratch = vec![0.0; max_scratch_len];
+        mdct.process_mdct_with_scratch(&input[..len], &input[len..], &mut actual, &mut scratch);
+        assert!(
+            compare_float_vectors(&expected, &actual),
+            "mdct, max_scratch_len = {}",
+            max_scratch_len
+        );
+    }
+    assert!(
+        planner
+            .plan_mdct_bounded(len, window_fn::mp3, len * 4)
+            .get_scratch_len()
+            > 0
+    );
+}
+
+#[test]
+fn test_plan_mdct_window_panic() {
+    let len = 64;
+    let mut planner = DctPlanner::<f32>::new();
+
+    // a panicking window function, a window of the wrong length, and a window fill that panics after writing part of
+    // the window all leave the planner usable
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        planner.plan_mdct(len, |_| panic!("window function panicked"))
+    }));
+    assert!(result.is_err());
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        planner.plan_mdct(len, |window_len| vec![1.0; window_len - 1])
+    }));
+    assert!(result.is_err());
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        planner.plan_mdct_with_window_fill(
+            len,
+            |window| {
+                window[0] = 1.0;
+                panic!("window fill panicked")
+            },
+            WindowMode::Internal,
+        )
+    }));
+    assert!(result.is_err());
+
+    // nothing was cached for the panicking calls, so this plans the MDCT with the new window
+    let mdct = planner.plan_mdct(len, window_fn::mp3);
+    let naive = MdctNaive::new(len, window_fn::mp3);
+    let input = random_signal(len * 2);
+    let mut expected = vec![0f32; len];
+    let mut actual = vec![0f32; len];
+    naive.process_mdct_with_scratch(&input[..len], &input[len..], &mut expected, &mut []);
+    let mut scratch = vec![0f32; mdct.get_scratch_len()];
+    mdct.process_mdct_with_scratch(&input[..len], &input[len..], &mut actual, &mut scratch);
+    assert!(compare_float_vectors(&expected, &actual));
+}
+
+#[test]
+fn test_process_callback_panic() {
+    let len = 1024;
+    let mut planner = DctPlanner::<f32>::new();
+    let dct = planner.plan_dct2(len);
+    let input = random_signal(len);
+    let mut expected = input.clone();
+    Type2And3Naive::new(len).process_dct2(&mut expected);
+
+    // panic from the cancellation callback after the transform has started
+    let mut buffer = input.clone();
+    let mut scratch = vec![0f32; dct.get_scratch_len()];
+    let mut calls = 0;
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        dct.process_dct2_cancellable(&mut buffer, &mut scratch, &mut || {
+            calls += 1;
+            if calls > 2 {
+                panic!("callback panicked");
+            }
+            true
+        })
+    }));
+    assert!(result.is_err());
+
+    // panic from the filter of a transform pair, halfway through the coefficients
+    let pair = planner.plan_dct2_pair(len, Normalization::Inverse);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        pair.process_filtered(&mut buffer, &mut scratch, |index, coefficient| {
+            if index == len / 2 {
+                panic!("filter panicked");
+            }
+            coefficient
+        })
+    }));
+    assert!(result.is_err());
+
+    // the plans, and the scratch that was in use during the panics, still compute correct results
+    let mut buffer = input.clone();
+    dct.process_dct2_with_scratch(&mut buffer, &mut scratch);
+    assert!(compare_float_vectors(&expected, &buffer));
+    assert!(Arc::ptr_eq(&dct, &planner.plan_dct2(len)));
+}